@@ -0,0 +1,74 @@
+//! Compares the market data parse path before and after the zero-copy rework.
+//!
+//! Run with `cargo bench --bench market_data`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crypto_com_api::utils::{reprocess_data, reprocess_value};
+use crypto_com_api::websocket::data::{BookRes, RawBookRes, RawRes};
+use serde::Deserialize;
+
+const ITERATIONS: u32 = 20_000;
+
+fn book_payload() -> serde_json::Value {
+    let levels: Vec<serde_json::Value> = (0..50)
+        .map(|level| serde_json::json!([format!("{}.5", 30_000 + level), "0.0125", "3"]))
+        .collect();
+
+    serde_json::json!({
+        "instrument_name": "BTCUSD-PERP",
+        "subscription": "book.BTCUSD-PERP.50",
+        "channel": "book",
+        "depth": 50,
+        "data": [{
+            "bids": levels,
+            "asks": levels,
+            "tt": 1_686_000_000_000_u64,
+            "t": 1_686_000_000_001_u64,
+            "u": 42,
+            "cs": 0
+        }]
+    })
+}
+
+fn time(name: &str, mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+
+    for _ in 0..ITERATIONS {
+        f();
+    }
+
+    let elapsed = start.elapsed();
+    println!("{name:<24} {:>10.2?} / message", elapsed / ITERATIONS);
+
+    elapsed
+}
+
+fn main() {
+    let res = book_payload();
+
+    // What `process_market` used to do: serialize the result back to a string once to find the
+    // channel, then again to parse the data.
+    let string_round_trip = time("string round-trip", || {
+        let header = res.to_string();
+        let sub: RawRes<'_> = serde_json::from_str(&header).expect("valid header");
+        let body = res.to_string();
+        let book = reprocess_data::<RawBookRes<'_>, BookRes>(&body).expect("valid book");
+
+        black_box((sub.channel.len(), book));
+    });
+
+    // Borrow straight out of the parsed value.
+    let borrowed = time("borrowed from value", || {
+        let sub = RawRes::deserialize(&res).expect("valid header");
+        let book = reprocess_value::<RawBookRes<'_>, BookRes>(&res).expect("valid book");
+
+        black_box((sub.channel, book));
+    });
+
+    println!(
+        "speedup                  {:>10.2}x",
+        string_round_trip.as_secs_f64() / borrowed.as_secs_f64()
+    );
+}
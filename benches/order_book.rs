@@ -0,0 +1,111 @@
+//! Compares applying book updates with binary searches against rebuilding the sorted levels.
+//!
+//! Run with `cargo bench --bench order_book`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crypto_com_api::analytics::{Level, OrderBook};
+use crypto_com_api::types::{Book, EpochMs, Time};
+use crypto_com_api::websocket::data::BookUpdate;
+
+const ITERATIONS: u32 = 20_000;
+const DEPTH: u32 = 150;
+
+fn snapshot() -> Book {
+    let bids = (0..DEPTH)
+        .map(|level| (30_000.0 - f64::from(level) * 0.5, 1.0, 1))
+        .collect();
+    let asks = (0..DEPTH)
+        .map(|level| (30_000.5 + f64::from(level) * 0.5, 1.0, 1))
+        .collect();
+
+    Book {
+        bids,
+        asks,
+        t: None,
+        tt: None,
+        u: Some(0),
+        cs: None,
+    }
+}
+
+/// An update replacing one level near the top and one deep in the book on each side.
+fn update(sequence: u64) -> BookUpdate {
+    let size = if sequence.is_multiple_of(2) { 0.0 } else { 2.0 };
+
+    BookUpdate {
+        bids: vec![(29_999.0, size, 1), (29_950.0, 3.0, 2)],
+        asks: vec![(30_001.5, size, 1), (30_050.5, 3.0, 2)],
+        t: Time::from_epoch_ms(sequence),
+        tt: Time::from_epoch_ms(sequence),
+        u: sequence + 1,
+        pu: sequence,
+        cs: 0,
+    }
+}
+
+/// What a book without sorted levels does: merge the update in and sort everything again.
+fn rebuild(levels: &mut Vec<Level>, changes: &[Level], descending: bool) {
+    for change in changes {
+        levels.retain(|level| level.0 != change.0);
+
+        if change.1 != 0.0 {
+            levels.push(*change);
+        }
+    }
+
+    if descending {
+        levels.sort_by(|a, b| b.0.total_cmp(&a.0));
+    } else {
+        levels.sort_by(|a, b| a.0.total_cmp(&b.0));
+    }
+}
+
+fn time(name: &str, mut f: impl FnMut(u64)) -> Duration {
+    let start = Instant::now();
+
+    for iteration in 0..ITERATIONS {
+        f(u64::from(iteration));
+    }
+
+    let elapsed = start.elapsed();
+    println!("{name:<24} {:>10.2?} / update", elapsed / ITERATIONS);
+
+    elapsed
+}
+
+fn main() {
+    let snapshot = snapshot();
+    let updates: Vec<BookUpdate> = (0..u64::from(ITERATIONS)).map(update).collect();
+
+    let (mut bids, mut asks) = (snapshot.bids.clone(), snapshot.asks.clone());
+    let naive = time("vec rebuild + top 10", |iteration| {
+        let update = &updates[iteration as usize];
+        rebuild(&mut bids, &update.bids, true);
+        rebuild(&mut asks, &update.asks, false);
+
+        let top: (Vec<Level>, Vec<Level>) = (
+            bids.iter().take(10).copied().collect(),
+            asks.iter().take(10).copied().collect(),
+        );
+        black_box(top);
+    });
+
+    let mut book = OrderBook::new("BTCUSD-PERP").with_tick_size(0.5);
+    book.apply_snapshot(&snapshot);
+    let searched = time("binary search + top 10", |iteration| {
+        book.apply_update(&updates[iteration as usize])
+            .expect("updates follow each other");
+
+        black_box(book.top(10));
+    });
+
+    assert_eq!(book.bids, bids);
+    assert_eq!(book.asks, asks);
+
+    println!(
+        "speedup                  {:>10.2}x",
+        naive.as_secs_f64() / searched.as_secs_f64()
+    );
+}
@@ -0,0 +1,339 @@
+//! Price alerts on the ticker and book streams.
+//!
+//! Register alerts with [`AlertEngine::add`] and feed it the market data with
+//! [`crate::controller::Controller::watch_alerts`], which broadcasts a
+//! [`WebsocketData::AlertFired`] each time one fires. Alerts can be added and removed while it
+//! runs from any clone of the engine.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::types::Book;
+use crate::websocket::WebsocketData;
+
+/// When an alert fires.
+///
+/// Prices are the last trade price of a ticker, or the mid price of a book snapshot. Book updates
+/// are not tracked, subscribe to `ticker` or a `book` snapshot channel.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum AlertCondition {
+    /// The price is at or above this.
+    PriceAbove(f64),
+    /// The price is at or below this.
+    PriceBelow(f64),
+    /// The price moved by at least `percent` in either direction within `window`.
+    Move {
+        /// Smallest move in percent, e.g. `5.0`.
+        percent: f64,
+        /// How far back the move is measured from.
+        window: Duration,
+    },
+    /// The best ask is more than this above the best bid.
+    SpreadWider(f64),
+}
+
+/// An alert on one instrument.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Alert {
+    /// e.g. BTC_USDT.
+    pub instrument_name: String,
+    /// When the alert fires.
+    pub condition: AlertCondition,
+}
+
+impl Alert {
+    /// An alert on `instrument_name` that fires when `condition` holds.
+    #[must_use]
+    pub fn new(instrument_name: impl Into<String>, condition: AlertCondition) -> Self {
+        Self {
+            instrument_name: instrument_name.into(),
+            condition,
+        }
+    }
+}
+
+/// ID of an alert in an [`AlertEngine`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct AlertId(pub u64);
+
+impl fmt::Display for AlertId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// An alert whose condition started to hold.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AlertFired {
+    /// ID the alert was added with.
+    pub id: AlertId,
+    /// The alert that fired.
+    pub alert: Alert,
+    /// The price, move in percent, or spread that made it fire.
+    pub value: f64,
+    /// Local time in ms since the UNIX epoch at which the data was recieved.
+    pub received_at: u64,
+}
+
+/// An alert and whether its condition held the last time it was checked.
+#[derive(Debug)]
+struct Entry {
+    /// The alert.
+    alert: Alert,
+    /// Whether the condition held, the alert fires again once it stops holding and holds again.
+    holding: bool,
+}
+
+/// State shared by the clones of an [`AlertEngine`].
+#[derive(Debug, Default)]
+struct State {
+    /// ID of the next alert.
+    next_id: u64,
+    /// Alerts by ID.
+    alerts: BTreeMap<AlertId, Entry>,
+    /// Prices of each instrument as (recieved at, price), oldest first, kept for the longest
+    /// [`AlertCondition::Move`] window of the instrument.
+    prices: HashMap<String, VecDeque<(u64, f64)>>,
+}
+
+/// What one ticker or book says about an instrument.
+struct Quote<'a> {
+    /// e.g. BTC_USDT.
+    instrument_name: &'a str,
+    /// Last trade price or mid price.
+    price: Option<f64>,
+    /// Best ask minus best bid.
+    spread: Option<f64>,
+    /// Local time in ms since the UNIX epoch at which the data was recieved.
+    received_at: u64,
+}
+
+/// Checks alerts against the market data, firing each when its condition starts to hold.
+///
+/// An alert fires once when its condition starts to hold, e.g. the price crosses its threshold,
+/// and again only after the condition stopped holding in between. Clones share the same alerts.
+///
+/// ```
+/// use crypto_com_api::analytics::alerts::{Alert, AlertCondition, AlertEngine};
+///
+/// let engine = AlertEngine::new();
+/// let id = engine.add(Alert::new("BTC_USDT", AlertCondition::PriceAbove(30_000.0)));
+///
+/// assert_eq!(engine.alerts().len(), 1);
+/// assert!(engine.remove(id).is_some());
+/// assert!(engine.alerts().is_empty());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct AlertEngine {
+    /// Alerts and prices shared by every clone.
+    state: Arc<Mutex<State>>,
+}
+
+impl AlertEngine {
+    /// An engine without alerts.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `alert`, returning the ID to remove it with.
+    pub fn add(&self, alert: Alert) -> AlertId {
+        let mut state = self.lock();
+        let id = AlertId(state.next_id);
+
+        state.next_id += 1;
+        state.alerts.insert(
+            id,
+            Entry {
+                alert,
+                holding: false,
+            },
+        );
+
+        id
+    }
+
+    /// Remove the alert `id`, returning it if it existed.
+    pub fn remove(&self, id: AlertId) -> Option<Alert> {
+        self.lock().alerts.remove(&id).map(|entry| entry.alert)
+    }
+
+    /// Every alert with its ID, in the order they were added.
+    #[must_use]
+    pub fn alerts(&self) -> Vec<(AlertId, Alert)> {
+        self.lock()
+            .alerts
+            .iter()
+            .map(|(&id, entry)| (id, entry.alert.clone()))
+            .collect()
+    }
+
+    /// Check the alerts against `data`, returning the ones that fired.
+    ///
+    /// Only [`WebsocketData::Ticker`] and [`WebsocketData::Book`] are used.
+    #[must_use]
+    pub fn process(&self, data: &WebsocketData) -> Vec<AlertFired> {
+        let mut fired = vec![];
+
+        match *data {
+            WebsocketData::Ticker(ref tickers) => {
+                for ticker in &tickers.data {
+                    let mid = ticker.b.zip(ticker.k).map(|(bid, ask)| (bid + ask) / 2.0);
+
+                    fired.extend(self.check(&Quote {
+                        instrument_name: &ticker.i,
+                        price: ticker.a.or(mid),
+                        spread: ticker.b.zip(ticker.k).map(|(bid, ask)| ask - bid),
+                        received_at: tickers.received_at,
+                    }));
+                }
+            }
+            WebsocketData::Book(ref books) => {
+                for book in &books.data {
+                    let (bid, ask) = top_of_book(book);
+
+                    fired.extend(self.check(&Quote {
+                        instrument_name: &books.instrument_name,
+                        price: bid.zip(ask).map(|(bid, ask)| (bid + ask) / 2.0),
+                        spread: bid.zip(ask).map(|(bid, ask)| ask - bid),
+                        received_at: books.received_at,
+                    }));
+                }
+            }
+            _ => {}
+        }
+
+        fired
+    }
+
+    /// Check the alerts of the instrument of `quote`.
+    fn check(&self, quote: &Quote<'_>) -> Vec<AlertFired> {
+        let mut state = self.lock();
+        let State {
+            ref mut alerts,
+            ref mut prices,
+            ..
+        } = *state;
+
+        let longest_window = alerts
+            .values()
+            .filter(|entry| entry.alert.instrument_name == quote.instrument_name)
+            .filter_map(|entry| match entry.alert.condition {
+                AlertCondition::Move { window, .. } => Some(window),
+                _ => None,
+            })
+            .max();
+
+        let history = prices.entry(quote.instrument_name.to_owned()).or_default();
+
+        if let Some(price) = quote.price {
+            history.push_back((quote.received_at, price));
+        }
+
+        let window_ms = longest_window.map_or(0, |window| {
+            u64::try_from(window.as_millis()).unwrap_or(u64::MAX)
+        });
+
+        while history
+            .front()
+            .is_some_and(|&(at, _)| at.saturating_add(window_ms) < quote.received_at)
+        {
+            history.pop_front();
+        }
+
+        let mut fired = vec![];
+
+        for (&id, entry) in alerts.iter_mut() {
+            if entry.alert.instrument_name != quote.instrument_name {
+                continue;
+            }
+
+            let Some(value) = evaluate(entry.alert.condition, quote, history) else {
+                continue;
+            };
+
+            match value {
+                Some(value) if !entry.holding => {
+                    entry.holding = true;
+                    fired.push(AlertFired {
+                        id,
+                        alert: entry.alert.clone(),
+                        value,
+                        received_at: quote.received_at,
+                    });
+                }
+                Some(_) => {}
+                None => entry.holding = false,
+            }
+        }
+
+        fired
+    }
+
+    /// The state, even if a thread panicked while holding it.
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Best bid and best ask of `book`.
+pub(super) fn top_of_book(book: &Book) -> (Option<f64>, Option<f64>) {
+    let bid = book
+        .bids
+        .iter()
+        .map(|&(price, _, _)| price)
+        .reduce(f64::max);
+    let ask = book
+        .asks
+        .iter()
+        .map(|&(price, _, _)| price)
+        .reduce(f64::min);
+
+    (bid, ask)
+}
+
+/// Whether `condition` holds for `quote`, with the value that makes it hold.
+///
+/// `None` if the quote does not have what the condition needs, `Some(None)` if it does not hold.
+fn evaluate(
+    condition: AlertCondition,
+    quote: &Quote<'_>,
+    history: &VecDeque<(u64, f64)>,
+) -> Option<Option<f64>> {
+    match condition {
+        AlertCondition::PriceAbove(threshold) => {
+            let price = quote.price?;
+
+            Some((price >= threshold).then_some(price))
+        }
+        AlertCondition::PriceBelow(threshold) => {
+            let price = quote.price?;
+
+            Some((price <= threshold).then_some(price))
+        }
+        AlertCondition::Move { percent, window } => {
+            let price = quote.price?;
+            let window_ms = u64::try_from(window.as_millis()).unwrap_or(u64::MAX);
+            let &(_, start) = history
+                .iter()
+                .find(|&&(at, _)| at.saturating_add(window_ms) >= quote.received_at)?;
+
+            if start == 0.0 {
+                return Some(None);
+            }
+
+            let change = (price - start) / start * 100.0;
+
+            Some((change.abs() >= percent).then_some(change))
+        }
+        AlertCondition::SpreadWider(width) => {
+            let spread = quote.spread?;
+
+            Some((spread > width).then_some(spread))
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! Balance change notifications from consecutive balance snapshots.
+
+use std::collections::HashMap;
+
+use crate::websocket::data::{AccountSummary, EventKind, UserBalance, UserTradeRes};
+use crate::websocket::WebsocketData;
+
+/// Changes smaller than this are treated as rounding and not reported.
+pub const DEFAULT_TOLERANCE: f64 = 1e-9;
+
+/// What moved a balance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BalanceChangeKind {
+    /// Funds that arrived without a matching fill, e.g. a deposit or transfer in.
+    Deposit,
+    /// Funds that left without a matching fill, e.g. a withdrawal or transfer out.
+    Withdrawal,
+    /// Bought or sold in a `user.trade` fill.
+    TradeFill,
+    /// Fee deducted for a `user.trade` fill.
+    Fee,
+}
+
+/// A change to the total balance of one currency between two snapshots.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BalanceChange {
+    /// e.g. CRO.
+    pub currency: String,
+    /// What moved the balance.
+    pub kind: BalanceChangeKind,
+    /// Signed amount, negative when the balance went down.
+    pub amount: f64,
+    /// Total balance in the snapshot that showed the change.
+    pub balance: f64,
+}
+
+/// Fills and fees seen for one currency since its last snapshot.
+#[derive(Clone, Copy, Debug, Default)]
+struct Pending {
+    /// Net amount bought or sold.
+    fills: f64,
+    /// Fees deducted, positive.
+    fees: f64,
+}
+
+/// Diffs consecutive balance snapshots into [`BalanceChange`]s.
+///
+/// Feed it [`WebsocketData`] with [`BalanceTracker::process`]. `user.trade` fills between two
+/// snapshots are reported as [`BalanceChangeKind::TradeFill`] and [`BalanceChangeKind::Fee`],
+/// what is left of the change is a [`BalanceChangeKind::Deposit`] or
+/// [`BalanceChangeKind::Withdrawal`]. The first snapshot only sets the starting balances.
+#[derive(Clone, Debug)]
+pub struct BalanceTracker {
+    /// Total balance by currency from the last snapshot.
+    balances: HashMap<String, f64>,
+    /// Fills and fees not yet seen in a snapshot, by currency.
+    pending: HashMap<String, Pending>,
+    /// Whether a snapshot has been seen.
+    seeded: bool,
+    /// Changes smaller than this are not reported.
+    tolerance: f64,
+}
+
+impl Default for BalanceTracker {
+    fn default() -> Self {
+        Self {
+            balances: HashMap::new(),
+            pending: HashMap::new(),
+            seeded: false,
+            tolerance: DEFAULT_TOLERANCE,
+        }
+    }
+}
+
+impl BalanceTracker {
+    /// A tracker that has not seen a snapshot.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ignore changes smaller than `tolerance`, [`DEFAULT_TOLERANCE`] by default.
+    #[must_use]
+    pub const fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+
+        self
+    }
+
+    /// The total balance of `currency` in the last snapshot.
+    #[must_use]
+    pub fn balance(&self, currency: &str) -> Option<f64> {
+        self.balances.get(currency).copied()
+    }
+
+    /// Update the tracker with any data it uses and return the changes, other data is ignored.
+    pub fn process(&mut self, data: &WebsocketData) -> Vec<BalanceChange> {
+        match *data {
+            WebsocketData::UserBalance(ref balances) => self.apply_balances(balances),
+            WebsocketData::GetAccountSummary(ref summary) => self.apply_account_summary(summary),
+            WebsocketData::UserTrade(ref trades) => {
+                self.record_trades(trades);
+
+                vec![]
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Diff a `user.balance` snapshot against the last one.
+    pub fn apply_balances(&mut self, balances: &[UserBalance]) -> Vec<BalanceChange> {
+        self.apply_snapshot(
+            balances
+                .iter()
+                .map(|balance| (balance.currency.as_str(), balance.balance)),
+        )
+    }
+
+    /// Diff a `private/get-account-summary` snapshot against the last one.
+    pub fn apply_account_summary(&mut self, summary: &AccountSummary) -> Vec<BalanceChange> {
+        self.apply_snapshot(
+            summary
+                .accounts
+                .iter()
+                .map(|account| (account.currency.as_str(), account.balance)),
+        )
+    }
+
+    /// Remember the fills and fees in `trades` so the next snapshot can be explained by them.
+    ///
+    /// Snapshots sent when subscribing are skipped, they are older than the balances.
+    pub fn record_trades(&mut self, trades: &UserTradeRes) {
+        if trades.kind == EventKind::Snapshot {
+            return;
+        }
+
+        for trade in &trades.data {
+            let quantity = if trade.side.eq_ignore_ascii_case("SELL") {
+                -trade.traded_quantity
+            } else {
+                trade.traded_quantity
+            };
+
+            // Spot instruments are BASE_QUOTE and move balances, derivatives such as BTCUSD-PERP
+            // do not.
+            if let Some((base, quote)) = trades.instrument_name.split_once('_') {
+                self.pending.entry(base.to_owned()).or_default().fills += quantity;
+                self.pending.entry(quote.to_owned()).or_default().fills -=
+                    quantity * trade.traded_price;
+            }
+
+            self.pending
+                .entry(trade.fee_currency.clone())
+                .or_default()
+                .fees += trade.fee;
+        }
+    }
+
+    /// Diff the `(currency, balance)` pairs against the last snapshot, currencies that are not
+    /// in it keep their balance.
+    fn apply_snapshot<'a>(
+        &mut self,
+        balances: impl Iterator<Item = (&'a str, f64)>,
+    ) -> Vec<BalanceChange> {
+        let mut changes = vec![];
+
+        if !self.seeded {
+            self.seeded = true;
+            self.pending.clear();
+            self.balances
+                .extend(balances.map(|(currency, balance)| (currency.to_owned(), balance)));
+
+            return changes;
+        }
+
+        for (currency, balance) in balances {
+            let previous = self
+                .balances
+                .insert(currency.to_owned(), balance)
+                .unwrap_or_default();
+            let pending = self.pending.remove(currency).unwrap_or_default();
+            let mut change = |kind, amount: f64| {
+                if amount.abs() > self.tolerance {
+                    changes.push(BalanceChange {
+                        currency: currency.to_owned(),
+                        kind,
+                        amount,
+                        balance,
+                    });
+                }
+            };
+
+            change(BalanceChangeKind::TradeFill, pending.fills);
+            change(BalanceChangeKind::Fee, -pending.fees);
+
+            let unexplained = balance - previous - pending.fills + pending.fees;
+
+            if unexplained > 0.0 {
+                change(BalanceChangeKind::Deposit, unexplained);
+            } else {
+                change(BalanceChangeKind::Withdrawal, unexplained);
+            }
+        }
+
+        changes
+    }
+}
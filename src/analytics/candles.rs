@@ -0,0 +1,130 @@
+//! Gap filling and alignment of candlesticks.
+//!
+//! The exchange sends no candle for a period without trades. Indicators expect one candle per
+//! period, [`fill_gaps`] and [`CandleGapFiller`] add the missing ones at the previous close with
+//! zero volume.
+
+use crate::types::{Candlestick, EpochMs, Interval, Time};
+use crate::websocket::data::CandlestickRes;
+
+/// Length of `interval` in ms.
+fn interval_ms(interval: Interval) -> u64 {
+    u64::try_from(interval.as_duration().as_millis()).unwrap_or(u64::MAX)
+}
+
+/// `t` aligned down to the start of its `interval` period.
+///
+/// Periods are multiples of the interval length since the UNIX epoch, [`Interval::OneMonth`] is
+/// 28 days long, see [`Interval::as_duration`].
+#[must_use]
+pub fn align(t: u64, interval: Interval) -> u64 {
+    let step = interval_ms(interval);
+
+    t - t % step
+}
+
+/// Fills gaps in a stream of candlesticks, see [`CandleGapFiller::push`].
+#[derive(Clone, Debug)]
+pub struct CandleGapFiller {
+    /// Period of the candles.
+    interval: Interval,
+    /// The last candle passed on, aligned.
+    last: Option<Candlestick>,
+}
+
+impl CandleGapFiller {
+    /// A filler for candles of `interval`.
+    #[must_use]
+    pub const fn new(interval: Interval) -> Self {
+        Self {
+            interval,
+            last: None,
+        }
+    }
+
+    /// The last candle passed on.
+    #[must_use]
+    pub const fn last(&self) -> Option<&Candlestick> {
+        self.last.as_ref()
+    }
+
+    /// Align `candle` to its period and return it, preceded by a flat candle at the previous close
+    /// with zero volume for every period skipped since the last candle.
+    ///
+    /// An update to the last period is returned on its own, a candle older than the last period
+    /// is dropped.
+    pub fn push(&mut self, candle: &Candlestick) -> Vec<Candlestick> {
+        let step = interval_ms(self.interval);
+        let t = align(candle.t.epoch_ms(), self.interval);
+        let mut candles = vec![];
+
+        if let Some(ref last) = self.last {
+            let last_t = last.t.epoch_ms();
+
+            if t < last_t {
+                return candles;
+            }
+
+            let mut gap_t = last_t.saturating_add(step);
+
+            while gap_t < t {
+                candles.push(Candlestick {
+                    t: Time::from_epoch_ms(gap_t),
+                    ut: None,
+                    o: last.c,
+                    h: last.c,
+                    l: last.c,
+                    c: last.c,
+                    v: 0.0,
+                });
+                gap_t = gap_t.saturating_add(step);
+            }
+        }
+
+        let candle = Candlestick {
+            t: Time::from_epoch_ms(t),
+            ..candle.clone()
+        };
+
+        self.last = Some(candle.clone());
+        candles.push(candle);
+
+        candles
+    }
+
+    /// [`CandleGapFiller::push`] every candle in `res`, oldest first.
+    pub fn process(&mut self, res: &CandlestickRes) -> Vec<Candlestick> {
+        let mut data: Vec<&Candlestick> = res.data.iter().collect();
+        data.sort_by_key(|candle| candle.t.epoch_ms());
+
+        data.into_iter()
+            .flat_map(|candle| self.push(candle))
+            .collect()
+    }
+}
+
+/// `candles` aligned to `interval`, oldest first, with one candle per period from the first to the
+/// last.
+///
+/// Skipped periods get a flat candle at the previous close with zero volume, and of several
+/// candles in one period the last given is kept.
+#[must_use]
+pub fn fill_gaps(candles: &[Candlestick], interval: Interval) -> Vec<Candlestick> {
+    let mut sorted: Vec<&Candlestick> = candles.iter().collect();
+    // Stable, so the last given of several candles in a period stays last.
+    sorted.sort_by_key(|candle| align(candle.t.epoch_ms(), interval));
+
+    let mut filler = CandleGapFiller::new(interval);
+    let mut filled: Vec<Candlestick> = vec![];
+
+    for candle in sorted {
+        for candle in filler.push(candle) {
+            match filled.last_mut() {
+                Some(last) if last.t == candle.t => *last = candle,
+                _ => filled.push(candle),
+            }
+        }
+    }
+
+    filled
+}
@@ -0,0 +1,455 @@
+//! Streaming technical indicators over candlesticks.
+//!
+//! Each [`Indicator`] is updated with one completed candle at a time. An [`IndicatorPipeline`]
+//! takes the data of a `candlestick` subscription, waits for each candle to complete, and updates
+//! its indicators with it.
+
+use std::collections::VecDeque;
+use std::fmt::Debug;
+
+use crate::analytics::candles::CandleGapFiller;
+use crate::types::{Candlestick, Interval};
+use crate::websocket::data::CandlestickRes;
+
+/// The value of an indicator after a candle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IndicatorValue {
+    /// A single line, e.g. [`Sma`] or [`Rsi`].
+    Single(f64),
+    /// [`Macd`] lines.
+    Macd {
+        /// Fast EMA minus slow EMA.
+        macd: f64,
+        /// EMA of the MACD line.
+        signal: f64,
+        /// MACD line minus signal line.
+        histogram: f64,
+    },
+    /// [`Bollinger`] bands.
+    Bands {
+        /// Middle band plus the deviations.
+        upper: f64,
+        /// Simple moving average.
+        middle: f64,
+        /// Middle band minus the deviations.
+        lower: f64,
+    },
+}
+
+/// An indicator updated with one completed candle at a time.
+pub trait Indicator: Send + Debug {
+    /// Name of the indicator and its parameters, e.g. `SMA(20)`.
+    fn name(&self) -> String;
+
+    /// Update with the next completed candle, `None` until there are enough candles.
+    fn update(&mut self, candle: &Candlestick) -> Option<IndicatorValue>;
+}
+
+/// Simple moving average of the close.
+#[derive(Clone, Debug)]
+pub struct Sma {
+    /// Number of candles averaged.
+    period: usize,
+    /// The last `period` closes.
+    window: VecDeque<f64>,
+    /// Sum of `window`.
+    sum: f64,
+}
+
+impl Sma {
+    /// An average of the last `period` closes, at least one.
+    #[must_use]
+    pub fn new(period: usize) -> Self {
+        let period = period.max(1);
+
+        Self {
+            period,
+            window: VecDeque::with_capacity(period),
+            sum: 0.0,
+        }
+    }
+
+    /// Add `value` and return the average once the window is full.
+    fn next(&mut self, value: f64) -> Option<f64> {
+        if self.window.len() == self.period {
+            self.sum -= self.window.pop_front().unwrap_or_default();
+        }
+
+        self.window.push_back(value);
+        self.sum += value;
+
+        (self.window.len() == self.period).then(|| self.sum / self.period as f64)
+    }
+}
+
+impl Indicator for Sma {
+    fn name(&self) -> String {
+        format!("SMA({})", self.period)
+    }
+
+    fn update(&mut self, candle: &Candlestick) -> Option<IndicatorValue> {
+        self.next(candle.c).map(IndicatorValue::Single)
+    }
+}
+
+/// Exponential moving average of the close, seeded with the simple average of the first `period`
+/// closes.
+#[derive(Clone, Debug)]
+pub struct Ema {
+    /// Number of candles in the seed.
+    period: usize,
+    /// Weight of each new value.
+    alpha: f64,
+    /// Seed average, until it is full.
+    seed: Sma,
+    /// Current average.
+    value: Option<f64>,
+}
+
+impl Ema {
+    /// An average over `period` closes, at least one.
+    #[must_use]
+    pub fn new(period: usize) -> Self {
+        let period = period.max(1);
+
+        Self {
+            period,
+            alpha: 2.0 / (period as f64 + 1.0),
+            seed: Sma::new(period),
+            value: None,
+        }
+    }
+
+    /// Add `value` and return the average once seeded.
+    fn next(&mut self, value: f64) -> Option<f64> {
+        self.value = match self.value {
+            Some(ema) => Some(ema + self.alpha * (value - ema)),
+            None => self.seed.next(value),
+        };
+
+        self.value
+    }
+}
+
+impl Indicator for Ema {
+    fn name(&self) -> String {
+        format!("EMA({})", self.period)
+    }
+
+    fn update(&mut self, candle: &Candlestick) -> Option<IndicatorValue> {
+        self.next(candle.c).map(IndicatorValue::Single)
+    }
+}
+
+/// Wilder's smoothing, a moving average seeded with the simple average of the first `period`
+/// values.
+#[derive(Clone, Debug)]
+struct Wilder {
+    /// Number of values smoothed over.
+    period: usize,
+    /// Seed average, until it is full.
+    seed: Sma,
+    /// Current average.
+    value: Option<f64>,
+}
+
+impl Wilder {
+    /// Smoothing over `period` values.
+    fn new(period: usize) -> Self {
+        Self {
+            period,
+            seed: Sma::new(period),
+            value: None,
+        }
+    }
+
+    /// Add `value` and return the average once seeded.
+    fn next(&mut self, value: f64) -> Option<f64> {
+        let period = self.period as f64;
+
+        self.value = match self.value {
+            Some(avg) => Some((avg * (period - 1.0) + value) / period),
+            None => self.seed.next(value),
+        };
+
+        self.value
+    }
+}
+
+/// Relative strength index of the close, with Wilder's smoothing.
+#[derive(Clone, Debug)]
+pub struct Rsi {
+    /// Number of changes smoothed over.
+    period: usize,
+    /// Previous close.
+    prev_close: Option<f64>,
+    /// Average gain.
+    gain: Wilder,
+    /// Average loss.
+    loss: Wilder,
+}
+
+impl Rsi {
+    /// An RSI over `period` changes, at least one.
+    #[must_use]
+    pub fn new(period: usize) -> Self {
+        let period = period.max(1);
+
+        Self {
+            period,
+            prev_close: None,
+            gain: Wilder::new(period),
+            loss: Wilder::new(period),
+        }
+    }
+}
+
+impl Indicator for Rsi {
+    fn name(&self) -> String {
+        format!("RSI({})", self.period)
+    }
+
+    fn update(&mut self, candle: &Candlestick) -> Option<IndicatorValue> {
+        let prev_close = self.prev_close.replace(candle.c)?;
+        let change = candle.c - prev_close;
+
+        let gain = self.gain.next(change.max(0.0));
+        let loss = self.loss.next((-change).max(0.0));
+
+        let (gain, loss) = (gain?, loss?);
+
+        let rsi = if loss == 0.0 {
+            100.0
+        } else {
+            100.0 - 100.0 / (1.0 + gain / loss)
+        };
+
+        Some(IndicatorValue::Single(rsi))
+    }
+}
+
+/// Moving average convergence divergence of the close.
+#[derive(Clone, Debug)]
+pub struct Macd {
+    /// Fast average.
+    fast: Ema,
+    /// Slow average.
+    slow: Ema,
+    /// Average of the MACD line.
+    signal: Ema,
+}
+
+impl Macd {
+    /// A MACD of the `fast` and `slow` averages with a `signal` average, usually 12, 26, and 9.
+    #[must_use]
+    pub fn new(fast: usize, slow: usize, signal: usize) -> Self {
+        Self {
+            fast: Ema::new(fast),
+            slow: Ema::new(slow),
+            signal: Ema::new(signal),
+        }
+    }
+}
+
+impl Indicator for Macd {
+    fn name(&self) -> String {
+        format!(
+            "MACD({}, {}, {})",
+            self.fast.period, self.slow.period, self.signal.period
+        )
+    }
+
+    fn update(&mut self, candle: &Candlestick) -> Option<IndicatorValue> {
+        let fast = self.fast.next(candle.c);
+        let slow = self.slow.next(candle.c);
+        let macd = fast? - slow?;
+        let signal = self.signal.next(macd)?;
+
+        Some(IndicatorValue::Macd {
+            macd,
+            signal,
+            histogram: macd - signal,
+        })
+    }
+}
+
+/// Average true range, with Wilder's smoothing.
+#[derive(Clone, Debug)]
+pub struct Atr {
+    /// Number of ranges smoothed over.
+    period: usize,
+    /// Previous close.
+    prev_close: Option<f64>,
+    /// Average true range.
+    range: Wilder,
+}
+
+impl Atr {
+    /// An ATR over `period` candles, at least one.
+    #[must_use]
+    pub fn new(period: usize) -> Self {
+        let period = period.max(1);
+
+        Self {
+            period,
+            prev_close: None,
+            range: Wilder::new(period),
+        }
+    }
+}
+
+impl Indicator for Atr {
+    fn name(&self) -> String {
+        format!("ATR({})", self.period)
+    }
+
+    fn update(&mut self, candle: &Candlestick) -> Option<IndicatorValue> {
+        let range = match self.prev_close.replace(candle.c) {
+            Some(prev_close) => (candle.h - candle.l)
+                .max((candle.h - prev_close).abs())
+                .max((candle.l - prev_close).abs()),
+            None => candle.h - candle.l,
+        };
+
+        self.range.next(range).map(IndicatorValue::Single)
+    }
+}
+
+/// Bollinger bands of the close.
+#[derive(Clone, Debug)]
+pub struct Bollinger {
+    /// Middle band.
+    sma: Sma,
+    /// Number of standard deviations between the middle and outer bands.
+    deviations: f64,
+}
+
+impl Bollinger {
+    /// Bands `deviations` standard deviations around the average of `period` closes, usually 20
+    /// and 2.
+    #[must_use]
+    pub fn new(period: usize, deviations: f64) -> Self {
+        Self {
+            sma: Sma::new(period),
+            deviations,
+        }
+    }
+}
+
+impl Indicator for Bollinger {
+    fn name(&self) -> String {
+        format!("BOLL({}, {})", self.sma.period, self.deviations)
+    }
+
+    fn update(&mut self, candle: &Candlestick) -> Option<IndicatorValue> {
+        let middle = self.sma.next(candle.c)?;
+        let variance = self
+            .sma
+            .window
+            .iter()
+            .map(|close| (close - middle).powi(2))
+            .sum::<f64>()
+            / self.sma.period as f64;
+        let width = self.deviations * variance.sqrt();
+
+        Some(IndicatorValue::Bands {
+            upper: middle + width,
+            middle,
+            lower: middle - width,
+        })
+    }
+}
+
+/// A completed candle and the value of every indicator after it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndicatorEvent {
+    /// The completed candle, aligned to its period.
+    pub candle: Candlestick,
+    /// [`Indicator::name`] and value of each indicator, in the order they were added.
+    pub values: Vec<(String, Option<IndicatorValue>)>,
+}
+
+impl IndicatorEvent {
+    /// The value of the indicator named `name`.
+    #[must_use]
+    pub fn value(&self, name: &str) -> Option<IndicatorValue> {
+        self.values
+            .iter()
+            .find(|&(value_name, _)| value_name == name)
+            .and_then(|&(_, value)| value)
+    }
+}
+
+/// Updates a set of indicators with the candles of one `candlestick` subscription.
+///
+/// The candle of a period is sent again every time it changes, it is only complete once a candle
+/// of a later period arrives. Skipped periods are filled at the previous close with zero volume,
+/// see [`CandleGapFiller`].
+#[derive(Debug)]
+pub struct IndicatorPipeline {
+    /// The indicators updated.
+    indicators: Vec<Box<dyn Indicator>>,
+    /// Aligns and fills the candles.
+    filler: CandleGapFiller,
+    /// The candle of the current period, not yet complete.
+    pending: Option<Candlestick>,
+}
+
+impl IndicatorPipeline {
+    /// A pipeline with no indicators for candles of `interval`.
+    #[must_use]
+    pub const fn new(interval: Interval) -> Self {
+        Self {
+            indicators: vec![],
+            filler: CandleGapFiller::new(interval),
+            pending: None,
+        }
+    }
+
+    /// Update `indicator` with every completed candle.
+    #[must_use]
+    pub fn with_indicator(mut self, indicator: impl Indicator + 'static) -> Self {
+        self.indicators.push(Box::new(indicator));
+
+        self
+    }
+
+    /// The candle of the current period, not yet complete.
+    #[must_use]
+    pub const fn pending(&self) -> Option<&Candlestick> {
+        self.pending.as_ref()
+    }
+
+    /// Update the indicators with every candle in `res` that completes a period.
+    pub fn process(&mut self, res: &CandlestickRes) -> Vec<IndicatorEvent> {
+        self.filler
+            .process(res)
+            .into_iter()
+            .filter_map(|candle| self.push(candle))
+            .collect()
+    }
+
+    /// Update the indicators with the pending candle if `candle`, already aligned, starts a later
+    /// period.
+    fn push(&mut self, candle: Candlestick) -> Option<IndicatorEvent> {
+        let completed = match self.pending.take() {
+            Some(pending) if pending.t != candle.t => Some(pending),
+            _ => None,
+        };
+
+        self.pending = Some(candle);
+
+        completed.map(|candle| self.complete(candle))
+    }
+
+    /// Update the indicators with `candle`, which is complete.
+    pub fn complete(&mut self, candle: Candlestick) -> IndicatorEvent {
+        let values = self
+            .indicators
+            .iter_mut()
+            .map(|indicator| (indicator.name(), indicator.update(&candle)))
+            .collect();
+
+        IndicatorEvent { candle, values }
+    }
+}
@@ -0,0 +1,219 @@
+//! A combined view of the book and trades of one instrument.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use anyhow::Result;
+use tokio::sync::RwLock;
+
+use crate::analytics::order_book::{Level, OrderBook};
+use crate::prelude::ApiError;
+use crate::types::EpochMs;
+use crate::utils::action_queue::ActionQueue;
+use crate::websocket::actions::Subscribe;
+use crate::websocket::channel::Channel;
+use crate::websocket::data::{Trade, TradeRes};
+use crate::websocket::WebsocketData;
+
+/// The most recent trade.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LastTrade {
+    /// Side (buy or sell).
+    pub side: String,
+    /// Trade price.
+    pub price: f64,
+    /// Trade quantity.
+    pub quantity: f64,
+    /// Trade timestamp.
+    pub t: u64,
+}
+
+/// Volume and VWAP of the trades within a window ending at the last trade.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WindowStats {
+    /// Length of the window.
+    pub window: Duration,
+    /// Traded quantity.
+    pub volume: f64,
+    /// Volume weighted average price, `None` when nothing traded.
+    pub vwap: Option<f64>,
+}
+
+/// A point in time copy of a [`MarketView`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct MarketSnapshot {
+    /// e.g. BTCUSD-PERP.
+    pub instrument_name: String,
+    /// Highest bid.
+    pub best_bid: Option<Level>,
+    /// Lowest ask.
+    pub best_ask: Option<Level>,
+    /// The most recent trade.
+    pub last_trade: Option<LastTrade>,
+    /// Stats for each configured window, in the order they were given.
+    pub windows: Vec<WindowStats>,
+}
+
+/// State behind the [`MarketView`] lock.
+#[derive(Debug)]
+struct State {
+    /// The local order book.
+    book: OrderBook,
+    /// Trades within the longest window as (timestamp, price, quantity), oldest first.
+    trades: VecDeque<(u64, f64, f64)>,
+    /// The most recent trade.
+    last_trade: Option<LastTrade>,
+}
+
+/// Best bid/ask, last trade, and rolling volume and VWAP for one instrument.
+///
+/// Pass every event to [`MarketView::process`], each event is applied under a single lock so a
+/// [`MarketView::snapshot`] never sees half of an update.
+#[derive(Debug)]
+pub struct MarketView {
+    /// e.g. BTCUSD-PERP.
+    instrument_name: String,
+    /// Rolling windows to report.
+    windows: Vec<Duration>,
+    /// Current state.
+    state: RwLock<State>,
+}
+
+impl MarketView {
+    /// A view of `instrument_name` reporting volume and VWAP over each of `windows`.
+    #[must_use]
+    pub fn new(instrument_name: impl Into<String>, windows: Vec<Duration>) -> Self {
+        let instrument_name = instrument_name.into();
+
+        Self {
+            state: RwLock::new(State {
+                book: OrderBook::new(instrument_name.clone()),
+                trades: VecDeque::new(),
+                last_trade: None,
+            }),
+            instrument_name,
+            windows,
+        }
+    }
+
+    /// The subscription for the book and trade channels of the instrument.
+    #[must_use]
+    pub fn subscription(&self) -> Subscribe {
+        Subscribe::from_iter([
+            Channel::Book {
+                instrument_name: self.instrument_name.clone(),
+                depth: None,
+            },
+            Channel::Trade(self.instrument_name.clone()),
+        ])
+    }
+
+    /// Subscribe to the book and trade channels through the market `queue`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the subscription cannot be sent.
+    pub async fn subscribe(&self, queue: &ActionQueue) -> Result<u64> {
+        Ok(queue.push(self.subscription()).await?)
+    }
+
+    /// Apply book and trade data for the instrument, other data is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::BookOutOfSync`] if a book update does not follow the book.
+    pub async fn process(&self, data: &WebsocketData) -> Result<(), ApiError> {
+        match *data {
+            WebsocketData::Book(_) | WebsocketData::BookUpdate(_) => {
+                self.state.write().await.book.process(data)
+            }
+            WebsocketData::Trade(ref trades) if *trades.instrument_name == self.instrument_name => {
+                self.apply_trades(trades).await;
+
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Apply the trades in `trades`, oldest first.
+    pub async fn apply_trades(&self, trades: &TradeRes) {
+        let mut sorted: Vec<&Trade> = trades.data.iter().collect();
+        sorted.sort_by_key(|trade| trade.t);
+
+        let longest = self.windows.iter().max().copied().unwrap_or_default();
+        let mut state = self.state.write().await;
+
+        for trade in sorted {
+            state
+                .trades
+                .push_back((trade.t.epoch_ms(), trade.p, trade.q));
+
+            if state
+                .last_trade
+                .as_ref()
+                .is_none_or(|last_trade| trade.t.epoch_ms() >= last_trade.t)
+            {
+                state.last_trade = Some(LastTrade {
+                    side: trade.s.clone(),
+                    price: trade.p,
+                    quantity: trade.q,
+                    t: trade.t.epoch_ms(),
+                });
+            }
+        }
+
+        let latest = state
+            .last_trade
+            .as_ref()
+            .map_or(0, |last_trade| last_trade.t);
+        let cutoff = latest.saturating_sub(duration_ms(longest));
+
+        while state.trades.front().is_some_and(|trade| trade.0 <= cutoff) {
+            state.trades.pop_front();
+        }
+    }
+
+    /// A consistent copy of the view.
+    pub async fn snapshot(&self) -> MarketSnapshot {
+        let state = self.state.read().await;
+        let latest = state
+            .last_trade
+            .as_ref()
+            .map_or(0, |last_trade| last_trade.t);
+
+        let windows = self
+            .windows
+            .iter()
+            .map(|window| {
+                let cutoff = latest.saturating_sub(duration_ms(*window));
+                let (volume, notional) = state
+                    .trades
+                    .iter()
+                    .filter(|trade| trade.0 > cutoff)
+                    .fold((0.0, 0.0), |(volume, notional), trade| {
+                        (volume + trade.2, notional + trade.1 * trade.2)
+                    });
+
+                WindowStats {
+                    window: *window,
+                    volume,
+                    vwap: (volume > 0.0).then(|| notional / volume),
+                }
+            })
+            .collect();
+
+        MarketSnapshot {
+            instrument_name: self.instrument_name.clone(),
+            best_bid: state.book.best_bid(),
+            best_ask: state.book.best_ask(),
+            last_trade: state.last_trade.clone(),
+            windows,
+        }
+    }
+}
+
+/// `duration` in whole milliseconds.
+fn duration_ms(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
@@ -0,0 +1,21 @@
+//! Analytics built on top of the websocket data.
+
+pub mod alerts;
+pub mod balance;
+pub mod candles;
+#[cfg(feature = "indicators")]
+pub mod indicators;
+pub mod market_view;
+pub mod order_book;
+pub mod pnl;
+pub mod spread;
+
+pub use alerts::*;
+pub use balance::*;
+pub use candles::*;
+#[cfg(feature = "indicators")]
+pub use indicators::*;
+pub use market_view::*;
+pub use order_book::*;
+pub use pnl::*;
+pub use spread::*;
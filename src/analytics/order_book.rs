@@ -0,0 +1,202 @@
+//! A local order book built from `book` snapshots and `book.update` deltas.
+
+use std::cmp::Ordering;
+
+use crate::prelude::ApiError;
+use crate::types::Side;
+use crate::websocket::data::{Book, BookUpdate};
+use crate::websocket::WebsocketData;
+
+/// Level: (
+///     Price of the level,
+///     Total size of the level,
+///     Number of standing orders in the level,
+/// )
+pub type Level = (f64, f64, u64);
+
+/// Order book for a single instrument.
+///
+/// Subscribe to `book.{instrument_name}.{depth}` with
+/// [`crate::websocket::actions::BookSubscriptionType::SnapshotAndUpdate`] and pass every event to
+/// [`OrderBook::process`]. When an update is missed the book reports
+/// [`ApiError::BookOutOfSync`] and ignores updates until the next snapshot.
+///
+/// Levels are kept sorted, so the best bid and ask are the first of each side and updates and
+/// [`OrderBook::level`] are binary searches. With [`OrderBook::with_tick_size`] prices are
+/// compared as whole ticks, so a level sent as `30000.1` and updated as `30000.100000001` is the
+/// same level.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OrderBook {
+    /// e.g. BTCUSD-PERP.
+    pub instrument_name: String,
+    /// Bid levels, best (highest) first.
+    pub bids: Vec<Level>,
+    /// Ask levels, best (lowest) first.
+    pub asks: Vec<Level>,
+    /// Sequence of the last snapshot or update applied, `None` until a snapshot is applied.
+    pub sequence: Option<u64>,
+    /// Price tick of the instrument, e.g. [`crate::rest::data::Instrument::price_tick_size`],
+    /// `None` to compare prices exactly.
+    pub tick_size: Option<f64>,
+}
+
+/// The best levels of both sides of an [`OrderBook`], borrowed from the book.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BookTop<'a> {
+    /// Best bids, highest first.
+    pub bids: &'a [Level],
+    /// Best asks, lowest first.
+    pub asks: &'a [Level],
+}
+
+impl OrderBook {
+    /// An empty book for `instrument_name`.
+    #[must_use]
+    pub fn new(instrument_name: impl Into<String>) -> Self {
+        Self {
+            instrument_name: instrument_name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Compare prices as whole multiples of `tick_size`, ignored unless it is positive.
+    #[must_use]
+    pub fn with_tick_size(mut self, tick_size: f64) -> Self {
+        self.tick_size = Some(tick_size).filter(|tick_size| *tick_size > 0.0);
+        self
+    }
+
+    /// Apply the book data in `data` if it is for this instrument, other data is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::BookOutOfSync`] if an update does not follow the book.
+    pub fn process(&mut self, data: &WebsocketData) -> Result<(), ApiError> {
+        match *data {
+            WebsocketData::Book(ref book) if *book.instrument_name == self.instrument_name => {
+                for snapshot in &book.data {
+                    self.apply_snapshot(snapshot);
+                }
+            }
+            WebsocketData::BookUpdate(ref update)
+                if *update.instrument_name == self.instrument_name =>
+            {
+                for delta in &update.data {
+                    self.apply_update(delta)?;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Replace the book with `book`.
+    pub fn apply_snapshot(&mut self, book: &Book) {
+        self.bids.clone_from(&book.bids);
+        self.asks.clone_from(&book.asks);
+        let tick_size = self.tick_size;
+        self.bids
+            .sort_by(|a, b| compare_prices(tick_size, b.0, a.0));
+        self.asks
+            .sort_by(|a, b| compare_prices(tick_size, a.0, b.0));
+        self.sequence = book.u;
+    }
+
+    /// Apply the changed levels in `update`.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::BookOutOfSync`] if `update` does not follow the last snapshot or
+    /// update, the book must be rebuilt from a new snapshot.
+    pub fn apply_update(&mut self, update: &BookUpdate) -> Result<(), ApiError> {
+        if self.sequence != Some(update.pu) {
+            let expected = self.sequence.take();
+
+            return Err(ApiError::BookOutOfSync {
+                expected,
+                found: update.pu,
+            });
+        }
+
+        let tick_size = self.tick_size;
+
+        for bid in &update.bids {
+            apply_level(&mut self.bids, *bid, |a, b| compare_prices(tick_size, b, a));
+        }
+
+        for ask in &update.asks {
+            apply_level(&mut self.asks, *ask, |a, b| compare_prices(tick_size, a, b));
+        }
+
+        self.sequence = Some(update.u);
+
+        Ok(())
+    }
+
+    /// Highest bid.
+    #[must_use]
+    pub fn best_bid(&self) -> Option<Level> {
+        self.bids.first().copied()
+    }
+
+    /// Lowest ask.
+    #[must_use]
+    pub fn best_ask(&self) -> Option<Level> {
+        self.asks.first().copied()
+    }
+
+    /// The level at `price` of the bids for [`Side::Buy`] or the asks for [`Side::Sell`], `None`
+    /// if nothing is standing there.
+    #[must_use]
+    pub fn level(&self, side: Side, price: f64) -> Option<Level> {
+        let tick_size = self.tick_size;
+        let found = match side {
+            Side::Buy => self
+                .bids
+                .binary_search_by(|level| compare_prices(tick_size, price, level.0)),
+            Side::Sell => self
+                .asks
+                .binary_search_by(|level| compare_prices(tick_size, level.0, price)),
+        };
+
+        found.ok().map(|index| match side {
+            Side::Buy => self.bids[index],
+            Side::Sell => self.asks[index],
+        })
+    }
+
+    /// The best `n` levels of each side, fewer if the book is shallower.
+    #[must_use]
+    pub fn top(&self, n: usize) -> BookTop<'_> {
+        BookTop {
+            bids: &self.bids[..n.min(self.bids.len())],
+            asks: &self.asks[..n.min(self.asks.len())],
+        }
+    }
+}
+
+/// Order of two prices, in whole ticks when `tick_size` is set.
+#[allow(clippy::cast_possible_truncation)]
+fn compare_prices(tick_size: Option<f64>, a: f64, b: f64) -> Ordering {
+    match tick_size {
+        Some(tick_size) => {
+            let ticks = |price: f64| (price / tick_size).round() as i64;
+
+            ticks(a).cmp(&ticks(b))
+        }
+        None => a.total_cmp(&b),
+    }
+}
+
+/// Insert, replace, or remove (size 0) `level` in `levels`, kept sorted by `cmp` on price.
+fn apply_level(levels: &mut Vec<Level>, level: Level, cmp: impl Fn(f64, f64) -> Ordering) {
+    match levels.binary_search_by(|existing| cmp(existing.0, level.0)) {
+        Ok(index) if level.1 == 0.0 => {
+            levels.remove(index);
+        }
+        Ok(index) => levels[index] = level,
+        Err(_) if level.1 == 0.0 => {}
+        Err(index) => levels.insert(index, level),
+    }
+}
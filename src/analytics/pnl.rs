@@ -0,0 +1,229 @@
+//! Position and PnL tracking from the `user.trade` stream.
+
+use std::collections::HashMap;
+
+use tokio::sync::RwLock;
+
+use crate::websocket::data::{TickerRes, UserBalance, UserTrade, UserTradeRes};
+use crate::websocket::WebsocketData;
+
+/// Position in a single instrument.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Position {
+    /// e.g. BTC_USDT.
+    pub instrument_name: String,
+    /// Position size, positive when long and negative when short.
+    pub size: f64,
+    /// Average entry price of the open position, 0 when flat.
+    pub avg_entry_price: f64,
+    /// PnL from closed size, in the quote currency.
+    pub realized_pnl: f64,
+    /// PnL of the open size at `mark_price`, in the quote currency.
+    pub unrealized_pnl: f64,
+    /// Latest price from the ticker, `None` until a ticker has been seen.
+    pub mark_price: Option<f64>,
+}
+
+impl Position {
+    /// A flat position in `instrument_name`.
+    fn new(instrument_name: &str) -> Self {
+        Self {
+            instrument_name: instrument_name.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    /// Apply a fill of `quantity` at `price`, `quantity` is negative for sells.
+    fn fill(&mut self, quantity: f64, price: f64) {
+        if self.size == 0.0 || self.size.signum() == quantity.signum() {
+            let size = self.size.abs() + quantity.abs();
+            self.avg_entry_price =
+                (self.avg_entry_price * self.size.abs() + price * quantity.abs()) / size;
+            self.size += quantity;
+        } else {
+            let closed = quantity.abs().min(self.size.abs());
+            self.realized_pnl += closed * (price - self.avg_entry_price) * self.size.signum();
+
+            let flipped = quantity.abs() > self.size.abs();
+            self.size += quantity;
+
+            if flipped {
+                self.avg_entry_price = price;
+            } else if self.size == 0.0 {
+                self.avg_entry_price = 0.0;
+            }
+        }
+
+        self.mark();
+    }
+
+    /// Update the mark price and unrealized PnL.
+    fn set_mark_price(&mut self, price: f64) {
+        self.mark_price = Some(price);
+        self.mark();
+    }
+
+    /// Recalculate unrealized PnL from the current mark price.
+    fn mark(&mut self) {
+        self.unrealized_pnl = match self.mark_price {
+            Some(price) if self.size != 0.0 => self.size * (price - self.avg_entry_price),
+            _ => 0.0,
+        };
+    }
+}
+
+/// A point in time copy of everything the [`PnlTracker`] knows.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PnlSnapshot {
+    /// Positions by instrument name.
+    pub positions: HashMap<String, Position>,
+    /// Balances by currency, seeded with [`PnlTracker::set_balances`] and moved by spot trades.
+    pub balances: HashMap<String, f64>,
+    /// Fees paid by currency.
+    pub fees: HashMap<String, f64>,
+}
+
+impl PnlSnapshot {
+    /// Realized PnL summed over every position.
+    #[must_use]
+    pub fn realized_pnl(&self) -> f64 {
+        self.positions.values().map(|pos| pos.realized_pnl).sum()
+    }
+
+    /// Unrealized PnL summed over every position.
+    #[must_use]
+    pub fn unrealized_pnl(&self) -> f64 {
+        self.positions.values().map(|pos| pos.unrealized_pnl).sum()
+    }
+}
+
+/// Tracks position size, average entry price, and realized and unrealized PnL per instrument.
+///
+/// Feed it [`WebsocketData`] with [`PnlTracker::process`], it uses `user.trade` fills for
+/// positions, tickers for mark prices, and `user.balance` for balances. Snapshots can be taken
+/// from any task while it is being fed.
+#[derive(Debug, Default)]
+pub struct PnlTracker {
+    /// Tracked state.
+    state: RwLock<PnlSnapshot>,
+}
+
+impl PnlTracker {
+    /// An empty tracker.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A tracker starting from `balances`.
+    #[must_use]
+    pub fn with_balances(balances: &[UserBalance]) -> Self {
+        let mut state = PnlSnapshot::default();
+        set_balances(&mut state, balances);
+
+        Self {
+            state: RwLock::new(state),
+        }
+    }
+
+    /// Update the tracker with any data it uses, other data is ignored.
+    pub async fn process(&self, data: &WebsocketData) {
+        match *data {
+            WebsocketData::UserTrade(ref trades) => self.apply_trades(trades).await,
+            WebsocketData::Ticker(ref ticker) => self.apply_ticker(ticker).await,
+            WebsocketData::UserBalance(ref balances) => self.set_balances(balances).await,
+            _ => {}
+        }
+    }
+
+    /// Replace the balances of the currencies in `balances`.
+    pub async fn set_balances(&self, balances: &[UserBalance]) {
+        set_balances(&mut *self.state.write().await, balances);
+    }
+
+    /// Apply the fills in `trades`, oldest first.
+    pub async fn apply_trades(&self, trades: &UserTradeRes) {
+        let mut fills: Vec<&UserTrade> = trades.data.iter().collect();
+        fills.sort_by_key(|trade| trade.create_time);
+
+        let mut state = self.state.write().await;
+
+        for trade in fills {
+            apply_trade(&mut state, &trades.instrument_name, trade);
+        }
+    }
+
+    /// Mark the positions in the ticker's instruments to the latest price.
+    ///
+    /// The latest trade price is used, or the middle of the best bid and ask if there were no
+    /// trades.
+    pub async fn apply_ticker(&self, ticker: &TickerRes) {
+        let mut state = self.state.write().await;
+
+        for tick in &ticker.data {
+            let price = match (tick.a, tick.b, tick.k) {
+                (Some(last), _, _) => last,
+                (None, Some(bid), Some(ask)) => (bid + ask) / 2.0,
+                _ => continue,
+            };
+
+            state
+                .positions
+                .entry(tick.i.to_string())
+                .or_insert_with(|| Position::new(&tick.i))
+                .set_mark_price(price);
+        }
+    }
+
+    /// The position in `instrument_name`, if any trades or tickers have been seen for it.
+    pub async fn position(&self, instrument_name: &str) -> Option<Position> {
+        self.state
+            .read()
+            .await
+            .positions
+            .get(instrument_name)
+            .cloned()
+    }
+
+    /// A copy of the current positions, balances, and fees.
+    pub async fn snapshot(&self) -> PnlSnapshot {
+        self.state.read().await.clone()
+    }
+}
+
+/// Replace the balances of the currencies in `balances`.
+fn set_balances(state: &mut PnlSnapshot, balances: &[UserBalance]) {
+    for balance in balances {
+        state
+            .balances
+            .insert(balance.currency.clone(), balance.balance);
+    }
+}
+
+/// Apply one fill to the position, fees, and spot balances.
+fn apply_trade(state: &mut PnlSnapshot, instrument_name: &str, trade: &UserTrade) {
+    let quantity = if trade.side.eq_ignore_ascii_case("SELL") {
+        -trade.traded_quantity
+    } else {
+        trade.traded_quantity
+    };
+
+    state
+        .positions
+        .entry(instrument_name.to_owned())
+        .or_insert_with(|| Position::new(instrument_name))
+        .fill(quantity, trade.traded_price);
+
+    *state.fees.entry(trade.fee_currency.clone()).or_default() += trade.fee;
+
+    // Spot instruments are BASE_QUOTE and move balances, derivatives such as BTCUSD-PERP do not.
+    if let Some((base, quote)) = instrument_name.split_once('_') {
+        *state.balances.entry(base.to_owned()).or_default() += quantity;
+        *state.balances.entry(quote.to_owned()).or_default() -= quantity * trade.traded_price;
+    }
+
+    *state
+        .balances
+        .entry(trade.fee_currency.clone())
+        .or_default() -= trade.fee;
+}
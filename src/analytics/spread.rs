@@ -0,0 +1,358 @@
+//! Spreads between instruments, e.g. the basis of a perpetual over its spot market.
+//!
+//! A [`SpreadTracker`] keeps the best bid and ask of a base instrument and of every instrument
+//! compared to it. Each time either side of a pair is quoted, a [`SpreadEvent`] is computed from
+//! their latest quotes. Create a [`SpreadStream`] with
+//! [`crate::controller::Controller::watch_spread`], or feed a tracker yourself.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::analytics::alerts::top_of_book;
+use crate::prelude::DataReciever;
+use crate::utils::trace;
+use crate::websocket::actions::Subscribe;
+use crate::websocket::channel::Channel;
+use crate::websocket::WebsocketData;
+
+/// How the spreads of a [`SpreadEvent`] are smoothed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Smoothing {
+    /// Not smoothed, the smoothed values are the latest values.
+    #[default]
+    None,
+    /// Exponential moving average with this weight for the latest value, between 0 and 1.
+    Ema(f64),
+}
+
+impl Smoothing {
+    /// `latest` folded into the smoothed value `previous`.
+    fn apply(self, previous: Option<f64>, latest: f64) -> f64 {
+        match (self, previous) {
+            (Self::Ema(alpha), Some(previous)) => {
+                let alpha = alpha.clamp(0.0, 1.0);
+
+                alpha.mul_add(latest - previous, previous)
+            }
+            _ => latest,
+        }
+    }
+}
+
+/// The best bid and ask of an instrument.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Leg {
+    /// e.g. BTC_USDT.
+    pub instrument_name: String,
+    /// Best bid.
+    pub bid: f64,
+    /// Best ask.
+    pub ask: f64,
+    /// Local time in ms since the UNIX epoch at which the quote was recieved.
+    pub received_at: u64,
+}
+
+impl Leg {
+    /// Halfway between the best bid and ask.
+    #[must_use]
+    pub fn mid(&self) -> f64 {
+        (self.bid + self.ask) / 2.0
+    }
+}
+
+/// The spread of an instrument over the base instrument, from their latest quotes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpreadEvent {
+    /// The base instrument, e.g. BTC_USDT.
+    pub base: Leg,
+    /// The instrument compared to it, e.g. BTCUSD-PERP.
+    pub other: Leg,
+    /// Mid price of `other` minus the mid price of `base`.
+    pub spread: f64,
+    /// `spread` in percent of the mid price of `base`.
+    pub basis: f64,
+    /// `spread` smoothed over the previous events of the pair.
+    pub smoothed_spread: f64,
+    /// `basis` smoothed over the previous events of the pair.
+    pub smoothed_basis: f64,
+    /// Selling `other` at its bid and buying `base` at its ask, positive when it locks in a
+    /// profit before fees.
+    pub sell_other: f64,
+    /// Buying `other` at its ask and selling `base` at its bid, positive when it locks in a
+    /// profit before fees.
+    pub buy_other: f64,
+    /// Local time in ms since the UNIX epoch at which the data was recieved.
+    pub received_at: u64,
+}
+
+/// Aligns the quotes of a base instrument and the instruments compared to it.
+///
+/// Quotes come from tickers and book snapshots, book updates are not tracked. Subscribe to the
+/// channels of [`SpreadTracker::subscription`].
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use crypto_com_api::analytics::spread::{Smoothing, SpreadTracker};
+///
+/// let tracker = SpreadTracker::new("BTC_USDT", "BTCUSD-PERP")
+///     .with_instrument("BTC_USD")
+///     .with_smoothing(Smoothing::Ema(0.2))
+///     .with_max_skew(Duration::from_secs(1));
+///
+/// assert_eq!(
+///     tracker.subscription().channels,
+///     ["ticker.BTC_USDT", "ticker.BTCUSD-PERP", "ticker.BTC_USD"]
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct SpreadTracker {
+    /// The instrument the others are compared to.
+    base: String,
+    /// The instruments compared to the base, in the order they were added.
+    others: Vec<String>,
+    /// How the spreads are smoothed.
+    smoothing: Smoothing,
+    /// Largest time between the quotes of a pair for them to be compared.
+    max_skew: Option<Duration>,
+    /// Latest quote of each instrument.
+    quotes: HashMap<String, Leg>,
+    /// Smoothed (spread, basis) of each instrument compared to the base.
+    smoothed: HashMap<String, (f64, f64)>,
+}
+
+impl SpreadTracker {
+    /// Compare `other` to `base`.
+    #[must_use]
+    pub fn new(base: impl Into<String>, other: impl Into<String>) -> Self {
+        Self {
+            base: base.into(),
+            others: vec![other.into()],
+            smoothing: Smoothing::None,
+            max_skew: None,
+            quotes: HashMap::new(),
+            smoothed: HashMap::new(),
+        }
+    }
+
+    /// Compare `other` to the base instrument as well.
+    #[must_use]
+    pub fn with_instrument(mut self, other: impl Into<String>) -> Self {
+        let other = other.into();
+
+        if other != self.base && !self.others.contains(&other) {
+            self.others.push(other);
+        }
+
+        self
+    }
+
+    /// Smooth the spreads with `smoothing`, they are not smoothed by default.
+    #[must_use]
+    pub const fn with_smoothing(mut self, smoothing: Smoothing) -> Self {
+        self.smoothing = smoothing;
+
+        self
+    }
+
+    /// Only compare quotes recieved at most `max_skew` apart, a pair is not compared while one of
+    /// its quotes is older.
+    #[must_use]
+    pub const fn with_max_skew(mut self, max_skew: Duration) -> Self {
+        self.max_skew = Some(max_skew);
+
+        self
+    }
+
+    /// The base instrument followed by the instruments compared to it.
+    pub fn instruments(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.base.as_str()).chain(self.others.iter().map(String::as_str))
+    }
+
+    /// The subscription for the ticker channels of every instrument.
+    #[must_use]
+    pub fn subscription(&self) -> Subscribe {
+        Subscribe::from_iter(
+            self.instruments()
+                .map(|instrument_name| Channel::Ticker(instrument_name.to_owned())),
+        )
+    }
+
+    /// The latest quote of `instrument_name`.
+    #[must_use]
+    pub fn quote(&self, instrument_name: &str) -> Option<&Leg> {
+        self.quotes.get(instrument_name)
+    }
+
+    /// Apply the quotes in `data`, returning the spreads of the pairs they changed.
+    ///
+    /// Only [`WebsocketData::Ticker`] and [`WebsocketData::Book`] are used.
+    pub fn process(&mut self, data: &WebsocketData) -> Vec<SpreadEvent> {
+        let mut events = vec![];
+
+        match *data {
+            WebsocketData::Ticker(ref tickers) => {
+                for ticker in &tickers.data {
+                    if let (Some(bid), Some(ask)) = (ticker.b, ticker.k) {
+                        events.extend(self.quoted(&ticker.i, bid, ask, tickers.received_at));
+                    }
+                }
+            }
+            WebsocketData::Book(ref books) => {
+                for book in &books.data {
+                    if let (Some(bid), Some(ask)) = top_of_book(book) {
+                        events.extend(self.quoted(
+                            &books.instrument_name,
+                            bid,
+                            ask,
+                            books.received_at,
+                        ));
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        events
+    }
+
+    /// Record a quote of `instrument_name`, returning the spreads of the pairs it is part of.
+    fn quoted(
+        &mut self,
+        instrument_name: &str,
+        bid: f64,
+        ask: f64,
+        received_at: u64,
+    ) -> Vec<SpreadEvent> {
+        let is_base = instrument_name == self.base;
+
+        if !is_base && !self.others.iter().any(|other| other == instrument_name) {
+            return vec![];
+        }
+
+        self.quotes.insert(
+            instrument_name.to_owned(),
+            Leg {
+                instrument_name: instrument_name.to_owned(),
+                bid,
+                ask,
+                received_at,
+            },
+        );
+
+        let pairs: Vec<String> = if is_base {
+            self.others.clone()
+        } else {
+            vec![instrument_name.to_owned()]
+        };
+
+        pairs
+            .iter()
+            .filter_map(|other| self.compare(other, received_at))
+            .collect()
+    }
+
+    /// The spread of `other` over the base from their latest quotes, `None` if either is missing
+    /// or they are too far apart.
+    fn compare(&mut self, other: &str, received_at: u64) -> Option<SpreadEvent> {
+        let base = self.quotes.get(&self.base)?.clone();
+        let other = self.quotes.get(other)?.clone();
+
+        if let Some(max_skew) = self.max_skew {
+            let max_skew = u64::try_from(max_skew.as_millis()).unwrap_or(u64::MAX);
+
+            if base.received_at.abs_diff(other.received_at) > max_skew {
+                return None;
+            }
+        }
+
+        let base_mid = base.mid();
+
+        if base_mid == 0.0 {
+            return None;
+        }
+
+        let spread = other.mid() - base_mid;
+        let basis = spread / base_mid * 100.0;
+
+        let previous = self.smoothed.get(&other.instrument_name).copied();
+        let smoothed_spread = self
+            .smoothing
+            .apply(previous.map(|(spread, _)| spread), spread);
+        let smoothed_basis = self
+            .smoothing
+            .apply(previous.map(|(_, basis)| basis), basis);
+
+        self.smoothed.insert(
+            other.instrument_name.clone(),
+            (smoothed_spread, smoothed_basis),
+        );
+
+        Some(SpreadEvent {
+            sell_other: other.bid - base.ask,
+            buy_other: base.bid - other.ask,
+            base,
+            other,
+            spread,
+            basis,
+            smoothed_spread,
+            smoothed_basis,
+            received_at,
+        })
+    }
+}
+
+/// The spreads of a [`SpreadTracker`] as they change, read from the data broadcast.
+#[derive(Debug)]
+pub struct SpreadStream {
+    /// Data broadcast reciever.
+    data_rx: DataReciever,
+    /// Aligns the quotes.
+    tracker: SpreadTracker,
+    /// Events not yet returned, oldest last.
+    queued: Vec<SpreadEvent>,
+}
+
+impl SpreadStream {
+    /// Follow the instruments of `tracker` with the data from `data_rx`.
+    #[must_use]
+    pub const fn new(data_rx: DataReciever, tracker: SpreadTracker) -> Self {
+        Self {
+            data_rx,
+            tracker,
+            queued: vec![],
+        }
+    }
+
+    /// The tracker aligning the quotes.
+    #[must_use]
+    pub const fn tracker(&self) -> &SpreadTracker {
+        &self.tracker
+    }
+
+    /// The next spread, `None` once the data broadcast closes.
+    pub async fn next(&mut self) -> Option<SpreadEvent> {
+        loop {
+            if let Some(event) = self.queued.pop() {
+                return Some(event);
+            }
+
+            match self.data_rx.recv().await {
+                Ok(data) => {
+                    if let Some(ref data) = data.result {
+                        self.queued = self.tracker.process(data);
+                        self.queued.reverse();
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    trace::warning!(
+                        "Spread stream lagged behind, {skipped} messages were skipped."
+                    );
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
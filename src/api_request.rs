@@ -1,7 +1,7 @@
 //! Request handler for the API Requests.
 
 use crate::prelude::*;
-use crate::utils::get_epoch_ms;
+use crate::utils::clock::nonce_ms;
 
 use serde::Serialize;
 
@@ -24,6 +24,41 @@ pub struct ApiRequest {
     pub nonce: Option<u64>,
 }
 
+/// How a method is authorized by the exchange.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Authorization {
+    /// Anyone can call it.
+    Public,
+    /// The request must carry `api_key`, `sig`, and `nonce`, unless it is sent on a websocket
+    /// session that was authorized with `public/auth`.
+    Signed,
+}
+
+/// Authorization of each method, matched in order by exact name or by a prefix ending in `/`.
+pub const METHOD_AUTHORIZATION: &[(&str, Authorization)] = &[
+    ("public/auth", Authorization::Signed),
+    ("public/", Authorization::Public),
+    ("private/", Authorization::Signed),
+    ("subscribe", Authorization::Public),
+    ("unsubscribe", Authorization::Public),
+];
+
+/// The [`Authorization`] of `method`, [`Authorization::Public`] if it is not in
+/// [`METHOD_AUTHORIZATION`].
+#[must_use]
+pub fn authorization(method: &str) -> Authorization {
+    METHOD_AUTHORIZATION
+        .iter()
+        .find(|(name, _)| {
+            if name.ends_with('/') {
+                method.starts_with(name)
+            } else {
+                method == *name
+            }
+        })
+        .map_or(Authorization::Public, |(_, authorization)| *authorization)
+}
+
 /// Builder for [`ApiRequest`]
 #[derive(Default, Debug)]
 pub struct ApiRequestBuilder {
@@ -39,6 +74,8 @@ pub struct ApiRequestBuilder {
     sig: Option<String>,
     /// The send nonce of the request.
     pub nonce: Option<u64>,
+    /// Sent on a websocket session authorized with `public/auth`, signed methods need no signature.
+    pub authorized_session: bool,
 }
 
 impl ApiRequestBuilder {
@@ -70,53 +107,17 @@ impl ApiRequestBuilder {
         self
     }
 
-    /// Creates the digital signature needed for private requests.
-    ///
-    /// [Digital Signature](https://exchange-docs.crypto.com/spot/index.html#digital-signature)
+    /// With nonce, the local time corrected by [`crate::utils::clock::clock_offset`].
     #[must_use]
-    pub fn with_digital_signature(mut self, secret: impl Into<String>) -> Self {
-        let secret = secret.into();
-
-        // Get the params as key + value (no spaces, no delimiters).
-        let param_str = self
-            .params
-            .as_ref()
-            .map_or_else(String::new, crate::utils::params_to_str);
-
-        // The payload format is method + id + api_key + parameter string + nonce.
-        let mut payload_str = String::new();
-        payload_str += &self.method;
-
-        if let Some(id) = self.id {
-            payload_str += &id.to_string();
-        }
-
-        if let Some(ref key) = self.api_key {
-            payload_str += key;
-        }
-
-        payload_str += &param_str;
-
-        if let Some(ref nonce) = self.nonce {
-            payload_str += &nonce.to_string();
-        }
-
-        // As per the documentation we use HMAC-SHA256 and encode it to bytes, then hex encode it
-        // as a string.
-        let mut mac =
-            HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
-        mac.update(payload_str.as_bytes());
-        let result = mac.finalize();
-        let sig = result.into_bytes();
-
-        self.sig = Some(hex::encode(sig));
+    pub fn with_nonce(mut self) -> Self {
+        self.nonce = Some(nonce_ms());
         self
     }
 
-    /// With nonce.
+    /// On a websocket session authorized with `public/auth`.
     #[must_use]
-    pub fn with_nonce(mut self) -> Self {
-        self.nonce = Some(get_epoch_ms());
+    pub const fn on_authorized_session(mut self) -> Self {
+        self.authorized_session = true;
         self
     }
 
@@ -124,12 +125,32 @@ impl ApiRequestBuilder {
     ///
     /// # Errors
     ///
-    /// Will return [`ApiError`] if `method` is empty.
+    /// Will return [`ApiError::InvalidApiRequest`] if `method` is empty, or listing whichever of
+    /// `api_key`, `sig`, and `nonce` are missing from a signed method.
     pub fn build(self) -> Result<ApiRequest, ApiError> {
         if self.method.is_empty() {
             return Err(ApiError::InvalidApiRequest("method".to_owned()));
         }
 
+        if !self.authorized_session && authorization(&self.method) == Authorization::Signed {
+            let missing: Vec<&str> = [
+                ("api_key", self.api_key.is_none()),
+                ("sig", self.sig.is_none()),
+                ("nonce", self.nonce.is_none()),
+            ]
+            .into_iter()
+            .filter_map(|(name, missing)| missing.then_some(name))
+            .collect();
+
+            if !missing.is_empty() {
+                return Err(ApiError::InvalidApiRequest(format!(
+                    "{}` for signed method `{}",
+                    missing.join("`, `"),
+                    self.method
+                )));
+            }
+        }
+
         Ok(ApiRequest {
             id: self.id,
             method: self.method,
@@ -140,3 +161,23 @@ impl ApiRequestBuilder {
         })
     }
 }
+
+#[cfg(feature = "signing")]
+impl ApiRequestBuilder {
+    /// Creates the digital signature needed for private requests.
+    ///
+    /// [Digital Signature](https://exchange-docs.crypto.com/spot/index.html#digital-signature)
+    #[must_use]
+    pub fn with_digital_signature(mut self, secret: impl Into<String>) -> Self {
+        let payload = crate::signing::payload(
+            &self.method,
+            self.id,
+            self.api_key.as_deref(),
+            self.params.as_ref(),
+            self.nonce,
+        );
+
+        self.sig = Some(crate::signing::sign(&secret.into(), &payload));
+        self
+    }
+}
@@ -2,6 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "websocket-market")]
 use crate::websocket::WebsocketData;
 
 /// The format of an API response from the crypto.com server.
@@ -45,6 +46,7 @@ impl<T> Default for ApiResponse<T> {
 
 impl<T> ApiResponse<T> {
     /// Make the [`ApiResponse`] convert to send [`WebsocketData`]
+    #[cfg(feature = "websocket-market")]
     pub fn websocket_data(&self, data: WebsocketData) -> ApiResponse<WebsocketData> {
         ApiResponse {
             id: self.id,
@@ -0,0 +1,158 @@
+//! Callbacks registered per channel, an alternative to matching on every [`WebsocketData`] in one
+//! [`crate::controller::Controller::listen`] closure.
+//!
+//! Register callbacks with [`crate::controller::Controller::on_ticker`] and the other `on_*`
+//! methods, each callback is called with the data of its channel by one listener started with
+//! the first registration. Any number of callbacks can be registered for a channel, they are
+//! called in the order they were registered.
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! use crypto_com_api::controller::ControllerBuilder;
+//!
+//! let mut controller = ControllerBuilder::new().build();
+//!
+//! controller
+//!     .on_ticker(|tickers| println!("{} tickers", tickers.data.len()))
+//!     .on_book(|books| println!("book of {}", books.instrument_name));
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+
+use crate::websocket::data::{
+    BookRes, BookUpdateRes, CandlestickRes, TickerRes, TradeRes, UserBalance, UserOrderRes,
+    UserTradeRes,
+};
+use crate::websocket::WebsocketData;
+
+/// A callback for the data of one channel.
+type Callback<T> = Box<dyn FnMut(&T) + Send>;
+
+/// The callbacks registered for each channel.
+#[derive(Default)]
+pub struct Callbacks {
+    /// `ticker` callbacks.
+    ticker: Vec<Callback<TickerRes>>,
+    /// `book` callbacks.
+    book: Vec<Callback<BookRes>>,
+    /// `book.update` callbacks.
+    book_update: Vec<Callback<BookUpdateRes>>,
+    /// `trade` callbacks.
+    trade: Vec<Callback<TradeRes>>,
+    /// `candlestick` callbacks.
+    candlestick: Vec<Callback<CandlestickRes>>,
+    /// `user.order` callbacks.
+    user_order: Vec<Callback<UserOrderRes>>,
+    /// `user.trade` callbacks.
+    user_trade: Vec<Callback<UserTradeRes>>,
+    /// `user.balance` callbacks.
+    user_balance: Vec<Callback<[UserBalance]>>,
+}
+
+impl fmt::Debug for Callbacks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Callbacks")
+            .field("ticker", &self.ticker.len())
+            .field("book", &self.book.len())
+            .field("book_update", &self.book_update.len())
+            .field("trade", &self.trade.len())
+            .field("candlestick", &self.candlestick.len())
+            .field("user_order", &self.user_order.len())
+            .field("user_trade", &self.user_trade.len())
+            .field("user_balance", &self.user_balance.len())
+            .finish()
+    }
+}
+
+impl Callbacks {
+    /// No callbacks.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call `callback` with every `ticker` update.
+    pub fn on_ticker(&mut self, callback: impl FnMut(&TickerRes) + Send + 'static) {
+        self.ticker.push(Box::new(callback));
+    }
+
+    /// Call `callback` with every `book` snapshot.
+    pub fn on_book(&mut self, callback: impl FnMut(&BookRes) + Send + 'static) {
+        self.book.push(Box::new(callback));
+    }
+
+    /// Call `callback` with every `book.update` delta.
+    pub fn on_book_update(&mut self, callback: impl FnMut(&BookUpdateRes) + Send + 'static) {
+        self.book_update.push(Box::new(callback));
+    }
+
+    /// Call `callback` with every `trade` update.
+    pub fn on_trade(&mut self, callback: impl FnMut(&TradeRes) + Send + 'static) {
+        self.trade.push(Box::new(callback));
+    }
+
+    /// Call `callback` with every `candlestick` update.
+    pub fn on_candlestick(&mut self, callback: impl FnMut(&CandlestickRes) + Send + 'static) {
+        self.candlestick.push(Box::new(callback));
+    }
+
+    /// Call `callback` with every `user.order` update.
+    pub fn on_user_order(&mut self, callback: impl FnMut(&UserOrderRes) + Send + 'static) {
+        self.user_order.push(Box::new(callback));
+    }
+
+    /// Call `callback` with every `user.trade` update.
+    pub fn on_user_trade(&mut self, callback: impl FnMut(&UserTradeRes) + Send + 'static) {
+        self.user_trade.push(Box::new(callback));
+    }
+
+    /// Call `callback` with every `user.balance` update.
+    pub fn on_user_balance(&mut self, callback: impl FnMut(&[UserBalance]) + Send + 'static) {
+        self.user_balance.push(Box::new(callback));
+    }
+
+    /// Number of callbacks registered.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.ticker.len()
+            + self.book.len()
+            + self.book_update.len()
+            + self.trade.len()
+            + self.candlestick.len()
+            + self.user_order.len()
+            + self.user_trade.len()
+            + self.user_balance.len()
+    }
+
+    /// Whether no callbacks are registered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Call the callbacks registered for the channel of `data`, if any.
+    pub fn dispatch(&mut self, data: &WebsocketData) {
+        match *data {
+            WebsocketData::Ticker(ref tickers) => call(&mut self.ticker, tickers),
+            WebsocketData::Book(ref books) => call(&mut self.book, books),
+            WebsocketData::BookUpdate(ref updates) => call(&mut self.book_update, updates),
+            WebsocketData::Trade(ref trades) => call(&mut self.trade, trades),
+            WebsocketData::Candlestick(ref candles) => call(&mut self.candlestick, candles),
+            WebsocketData::UserOrder(ref orders) => call(&mut self.user_order, orders),
+            WebsocketData::UserTrade(ref trades) => call(&mut self.user_trade, trades),
+            WebsocketData::UserBalance(ref balances) => {
+                call(&mut self.user_balance, balances.as_slice());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Call each of `callbacks` with `data`.
+fn call<T: ?Sized>(callbacks: &mut [Callback<T>], data: &T) {
+    for callback in callbacks {
+        callback(data);
+    }
+}
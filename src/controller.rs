@@ -1,18 +1,92 @@
 //! Controller crate.
 
+use std::collections::HashMap;
 use std::marker::PhantomData;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, PoisonError};
+use std::time::Duration;
 
-use anyhow::Result;
-use futures_util::StreamExt;
+use tokio::sync::broadcast::{self, error::RecvError};
 use tokio::sync::Mutex;
 use tokio::task::JoinHandle;
+use tungstenite::Message;
 
+use crate::analytics::alerts::AlertEngine;
+use crate::analytics::spread::{SpreadStream, SpreadTracker};
 use crate::api_response::ApiResponse;
-use crate::prelude::{ActionStoreSender, DataReciever, DataSender};
-use crate::utils::action::{Action, ActionStore};
-use crate::utils::config::Config;
-use crate::websocket::{market_api, user_api, WebsocketData};
+use crate::callbacks::Callbacks;
+#[cfg(feature = "websocket")]
+use crate::dead_mans_switch::DeadMansSwitch;
+use crate::error::Result;
+use crate::fills::{FillStream, FillTracker, OrderRef};
+#[cfg(feature = "rest")]
+use crate::history::CandleStream;
+use crate::listener::ListenerHandle;
+use crate::prelude::{
+    ActionStoreSender, ApiError, DataEvent, DataReciever, DataSender, MessageSender,
+};
+#[cfg(feature = "websocket")]
+use crate::prelude::{SharedExchange, SharedRiskManager};
+use crate::rest::data::InstrumentRules;
+#[cfg(feature = "rest")]
+use crate::rest::instrument_cache::InstrumentCache;
+#[cfg(feature = "rest")]
+use crate::rest::{
+    data::{AnnouncementCategory, GetAnnouncementsParams, SystemStatus},
+    public,
+};
+#[cfg(feature = "websocket")]
+use crate::risk::RiskManager;
+#[cfg(feature = "websocket")]
+use crate::simulator::SimulatedExchange;
+use crate::supervisor::{Supervisor, Task};
+#[cfg(feature = "websocket")]
+use crate::throttle::{OrderScheduler, ThrottleLimits};
+#[cfg(feature = "rest")]
+use crate::types::Interval;
+#[cfg(feature = "websocket")]
+use crate::utils::action::Action;
+#[cfg(feature = "websocket")]
+use crate::utils::action_queue::wait_for_reply;
+use crate::utils::action_queue::ActionQueue;
+use crate::utils::clock::SkewEstimator;
+#[cfg(feature = "websocket")]
+use crate::utils::config::Credentials;
+use crate::utils::config::{Config, ErrorPolicy};
+use crate::utils::environment::Environment;
+#[cfg(feature = "rest")]
+use crate::utils::get_epoch_ms;
+use crate::utils::in_flight::{Api, InFlight};
+use crate::utils::message_stats::{MessageStats, MessageStatsSnapshot};
+use crate::utils::raw_hook::RawMessageHook;
+use crate::utils::request_log::RequestLog;
+use crate::utils::trace;
+#[cfg(feature = "websocket")]
+use crate::websocket::actions::spot_trading_api::{
+    CancelAllOrders, CancelOrder, CreateOrder, GetAccountSummary, GetOpenOrders, GetOrderDetail,
+    GetOrderHistory, GetTrades, Paginated,
+};
+use crate::websocket::actions::{self, ActionKind};
+use crate::websocket::auth_state::AuthGate;
+#[cfg(feature = "websocket")]
+use crate::websocket::auth_state::{self, AuthStatus, CredentialRefresh};
+use crate::websocket::connection_state::{send_state, ConnectionState, DisconnectKind};
+#[cfg(feature = "websocket")]
+use crate::websocket::dedup::{UserDedup, UserDedupHandler, DEDUP_METHODS};
+use crate::websocket::handler::MessageHandler;
+use crate::websocket::resync::{collect_resync, ResyncTracker};
+use crate::websocket::sequence::{SequenceChecker, SequenceHandler};
+use crate::websocket::subscriptions::Subscriptions;
+#[cfg(feature = "websocket")]
+use crate::websocket::user_api;
+use crate::websocket::{data, expire_requests, market_api, replayable, send_data, WebsocketData};
+
+/// How long [`Controller::rotate_credentials`] waits for the old user websocket to answer its
+/// requests before closing it.
+pub const ROTATION_DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Number of data messages buffered for each subscriber before the slowest starts to lag.
+pub const DATA_CAPACITY: usize = 4096;
 
 /// No auth keys state.
 #[derive(Debug)]
@@ -35,6 +109,20 @@ pub struct NoMarketWs;
 #[derive(Debug)]
 pub struct MarketWs;
 
+/// A user websocket signed with one of the named credentials in [`Config::keyring`], see
+/// [`Controller::connect_account`].
+#[derive(Debug)]
+pub struct AccountConnection {
+    /// Actions sender of the account's websocket.
+    pub actions_tx: ActionStoreSender,
+    /// Websocket message sender.
+    pub user_tx: MessageSender,
+    /// Requests sent on this account that have not yet been answered.
+    pub in_flight: Arc<InFlight>,
+    /// Authorization of this account's user websocket.
+    pub auth: Arc<AuthGate>,
+}
+
 /// The main controller of the application, this contains all of the information, threads, and the
 /// current ID used.
 #[derive(Debug)]
@@ -42,23 +130,65 @@ pub struct Controller<U, M> {
     /// Application config.
     pub config: Config,
     /// Current ID, we don't want to use duplicates so a current count is stored.
-    pub current_id: u64,
+    ///
+    /// Shared with every [`ActionQueue`] handed out by the controller.
+    pub current_id: Arc<AtomicU64>,
+    /// Set by [`Controller::shutdown`], no more actions are accepted once this is true.
+    pub shutting_down: Arc<AtomicBool>,
+    /// Mutating actions are refused while this is true, see [`Controller::set_read_only`].
+    ///
+    /// Shared with every [`ActionQueue`] handed out by the controller.
+    pub read_only: Arc<AtomicBool>,
     /// Market actions sender, this is used to send actions to the market API.
     pub market_actions_tx: Option<ActionStoreSender>,
-    /// Market actions `JoinHandle`.
-    pub market_actions_handle: Option<JoinHandle<Result<()>>>,
-    /// Market stream `JoinHandle`.
-    pub market_stream_handle: Option<JoinHandle<Result<()>>>,
     /// User actions sender, this is used to send actions to the user API.
     pub user_actions_tx: Option<ActionStoreSender>,
-    /// User actions `JoinHandle`.
-    pub user_actions_handle: Option<JoinHandle<Result<()>>>,
-    /// User stream `JoinHandle`.
-    pub user_stream_handle: Option<JoinHandle<Result<()>>>,
-    /// Data action sender.
+    /// Supervises the action and stream tasks.
+    pub supervisor: Supervisor,
+    /// Market websocket message sender.
+    pub market_tx: Option<MessageSender>,
+    /// User websocket message sender.
+    pub user_tx: Option<MessageSender>,
+    /// Requests that have been sent and not yet answered.
+    pub in_flight: Arc<InFlight>,
+    /// Called with every frame before it is parsed, kept for reconnecting.
+    pub raw_hook: Option<RawMessageHook>,
+    /// Counts every frame while set, see [`ControllerBuilder::with_message_stats`].
+    pub message_stats: Option<MessageStats>,
+    /// User websockets for the named credentials in [`Config::keyring`], connected on first use.
+    pub accounts: HashMap<String, AccountConnection>,
+    /// Checked by [`Controller::create_order`] before an order is sent, see
+    /// [`Controller::set_instrument_rules`].
+    pub instrument_rules: Option<InstrumentRules>,
+    /// Checked by [`Controller::create_order`] before an order is sent, in place of
+    /// `instrument_rules`, see [`Controller::set_instrument_cache`].
+    #[cfg(feature = "rest")]
+    pub instrument_cache: Option<Arc<InstrumentCache>>,
+    /// Orders are matched here instead of being sent while set, shared with every
+    /// [`ActionQueue`], see [`Controller::dry_run`].
+    #[cfg(feature = "websocket")]
+    pub simulator: Arc<std::sync::RwLock<Option<SharedExchange>>>,
+    /// Checks every order before it is sent while set, shared with every [`ActionQueue`], see
+    /// [`Controller::set_risk_manager`].
+    #[cfg(feature = "websocket")]
+    pub risk_manager: Arc<std::sync::RwLock<Option<SharedRiskManager>>>,
+    /// Market channels subscribed with [`Controller::subscribe`] or
+    /// [`Controller::push_market_action`], shared so they can be changed from any task.
+    pub subscriptions: Arc<Mutex<Subscriptions>>,
+    /// User channels subscribed with [`Controller::subscribe_user`] or
+    /// [`Controller::push_user_action`], subscribed to again by [`Controller::reconnect_user`].
+    pub user_subscriptions: Arc<Mutex<Subscriptions>>,
+    /// Authorization of the user websocket, private actions wait for it, see [`AuthGate`].
+    pub user_auth: Arc<AuthGate>,
+    /// Data broadcast sender.
     pub data_tx: DataSender,
-    /// Data reciever.
-    pub data_rx: DataReciever,
+    /// Data reciever created with the broadcast, handed to the first subscriber so it sees the
+    /// handshakes that happened while building.
+    pub data_rx: Option<DataReciever>,
+    /// Callbacks registered with [`Controller::on_ticker`] and the other `on_*` methods.
+    pub callbacks: Arc<std::sync::Mutex<Callbacks>>,
+    /// The listener calling `callbacks`, started by the first registration.
+    pub callback_listener: Option<ListenerHandle>,
     /// Marker for user websocket.
     _mark_user_ws: PhantomData<U>,
     /// Marker for market websocket.
@@ -72,19 +202,27 @@ pub struct ControllerBuilder<A, U, M> {
     pub config: Config,
     /// Market actions sender, this is used to send actions to the market API.
     pub market_actions_tx: Option<ActionStoreSender>,
-    /// Market actions `JoinHandle`.
-    pub market_actions_handle: Option<JoinHandle<Result<()>>>,
-    /// Market stream `JoinHandle`.
-    pub market_stream_handle: Option<JoinHandle<Result<()>>>,
     /// User actions sender, this is used to send actions to the user API.
     pub user_actions_tx: Option<ActionStoreSender>,
-    /// User actions `JoinHandle`.
-    pub user_actions_handle: Option<JoinHandle<Result<()>>>,
-    /// User stream `JoinHandle`.
-    pub user_stream_handle: Option<JoinHandle<Result<()>>>,
-    /// Data action sender.
+    /// Supervises the action and stream tasks.
+    pub supervisor: Supervisor,
+    /// Market websocket message sender.
+    pub market_tx: Option<MessageSender>,
+    /// User websocket message sender.
+    pub user_tx: Option<MessageSender>,
+    /// Requests that have been sent and not yet answered.
+    pub in_flight: Arc<InFlight>,
+    /// Called with every frame before it is parsed, see [`ControllerBuilder::on_raw_message`].
+    pub raw_hook: Option<RawMessageHook>,
+    /// Counts every frame while set, see [`ControllerBuilder::with_message_stats`].
+    pub message_stats: Option<MessageStats>,
+    /// How long a request may go unanswered, see [`ControllerBuilder::with_request_timeout`].
+    pub request_timeout: Option<Duration>,
+    /// Whether the controller starts read-only, see [`ControllerBuilder::read_only`].
+    pub read_only: bool,
+    /// Data broadcast sender.
     pub data_tx: DataSender,
-    /// Data reciever.
+    /// Data reciever created with the broadcast.
     pub data_rx: DataReciever,
     /// Marker for authorization.
     _mark_auth: PhantomData<A>,
@@ -104,27 +242,219 @@ impl ControllerBuilder<NoAuth, NoUserWs, NoMarketWs> {
     /// The base controller builder with no actions.
     #[must_use]
     pub fn new() -> Self {
-        let (data_tx, data_rx) = futures_channel::mpsc::unbounded::<ApiResponse<WebsocketData>>();
+        let (data_tx, data_rx) = broadcast::channel(DATA_CAPACITY);
 
         Self {
             config: Config::default(),
             market_actions_tx: None,
-            market_actions_handle: None,
-            market_stream_handle: None,
             user_actions_tx: None,
-            user_actions_handle: None,
-            user_stream_handle: None,
-            data_rx: Arc::new(Mutex::new(data_rx)),
-            data_tx: Arc::new(Mutex::new(data_tx)),
+            supervisor: Supervisor::new(),
+            market_tx: None,
+            user_tx: None,
+            in_flight: Arc::new(InFlight::default()),
+            raw_hook: None,
+            message_stats: None,
+            request_timeout: None,
+            read_only: false,
+            data_rx,
+            data_tx,
             _mark_auth: PhantomData,
             _mark_user_ws: PhantomData,
             _mark_market_ws: PhantomData,
         }
     }
+
+    /// Build a controller with auth and both websockets in one call.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if either websocket fails to connect.
+    #[cfg(feature = "websocket")]
+    pub async fn full(
+        api_key: impl Into<String>,
+        secret_key: impl Into<String>,
+        user_url: url::Url,
+        market_url: url::Url,
+    ) -> Result<Controller<UserWs, MarketWs>> {
+        Ok(Self::new()
+            .with_auth(api_key, secret_key)
+            .with_user_websocket(user_url)
+            .await?
+            .with_market_websocket(market_url)
+            .await?
+            .build())
+    }
+
+    /// Build a controller with auth and both websockets of `environment` in one call.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if either websocket fails to connect.
+    #[cfg(feature = "websocket")]
+    pub async fn full_in(
+        environment: Environment,
+        api_key: impl Into<String>,
+        secret_key: impl Into<String>,
+    ) -> Result<Controller<UserWs, MarketWs>> {
+        Ok(Self::new()
+            .with_environment(environment)
+            .with_auth(api_key, secret_key)
+            .with_user_websocket(environment.websocket_user_url())
+            .await?
+            .with_market_websocket(environment.websocket_market_url())
+            .await?
+            .build())
+    }
 }
 
 impl<A, U, M> ControllerBuilder<A, U, M> {
+    /// Call `hook` with every text frame exactly as it was recieved, before it is parsed.
+    ///
+    /// Only websockets added after this is called will use the hook.
+    #[must_use]
+    pub fn on_raw_message<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        self.raw_hook = Some(RawMessageHook::new(hook));
+
+        self
+    }
+
+    /// Count the messages and bytes recieved on every websocket by subscription in `stats`, read
+    /// them with [`MessageStats::snapshot`] or [`Controller::message_stats`].
+    ///
+    /// Only websockets added after this is called are counted.
+    #[must_use]
+    pub fn with_message_stats(mut self, stats: MessageStats) -> Self {
+        self.message_stats = Some(stats);
+
+        self
+    }
+
+    /// The hook given to new websockets, counting frames in the message stats if set.
+    fn stream_hook(&self) -> Option<RawMessageHook> {
+        stream_hook(self.message_stats.as_ref(), self.raw_hook.as_ref())
+    }
+
+    /// Keep at most `capacity` unanswered requests for replay, the oldest is dropped to make room
+    /// and reported with [`WebsocketData::RequestDropped`].
+    ///
+    /// Must be called before any websocket is added, they share the queue that exists when they
+    /// are added.
+    #[must_use]
+    pub fn with_send_queue_capacity(mut self, capacity: usize) -> Self {
+        self.in_flight = Arc::new(InFlight::with_capacity(capacity));
+
+        self
+    }
+
+    /// Give up on requests that are not answered within `timeout`, each is removed from the send
+    /// queue and reported with [`WebsocketData::RequestTimeout`].
+    #[must_use]
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+
+        self
+    }
+
+    /// Build a read-only controller, for dashboards running with keys that can trade, see
+    /// [`Controller::set_read_only`].
+    #[must_use]
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+
+        self
+    }
+
+    /// Use the URLs of `environment`, withdrawals are refused when it does not allow them, see
+    /// [`Config::with_environment`].
+    #[must_use]
+    pub fn with_environment(mut self, environment: Environment) -> Self {
+        self.config = self.config.with_environment(environment);
+
+        self
+    }
+
+    /// What the websockets added after this do with a message that fails to process, the stream
+    /// stops with the error by default.
+    #[must_use]
+    pub fn with_error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.config.error_policy = policy;
+
+        self
+    }
+
+    /// Send the header `name: value` with the handshake of websockets added after this, see
+    /// [`Config::with_header`].
+    #[must_use]
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config = self.config.with_header(name, value);
+
+        self
+    }
+
+    /// Send `user_agent` with the handshake of websockets added after this, see
+    /// [`Config::with_user_agent`].
+    #[must_use]
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.config = self.config.with_user_agent(user_agent);
+
+        self
+    }
+
+    /// Log the frames of websockets added after this with `log`, see [`Config::with_request_log`].
+    #[must_use]
+    pub fn with_request_log(mut self, log: RequestLog) -> Self {
+        self.config = self.config.with_request_log(log);
+
+        self
+    }
+
+    /// Handle messages with `method` from the `api` websocket with `handler`, before the built in
+    /// handlers, e.g. for methods this crate does not support yet.
+    ///
+    /// Only websockets added after this is called will use the handler.
+    #[must_use]
+    pub fn with_handler<H>(mut self, api: Api, method: impl Into<String>, handler: H) -> Self
+    where
+        H: MessageHandler + 'static,
+    {
+        match api {
+            Api::Market => self.config.market_handlers.register(method, handler),
+            Api::User => self.config.user_handlers.register(method, handler),
+        };
+
+        self
+    }
+
+    /// Check market data with `checker` before it is sent, dropping duplicates or sending
+    /// [`crate::websocket::WebsocketData::SequenceGap`] by the policy of each subscription.
+    ///
+    /// This replaces the `subscribe` handler of the market websocket, use
+    /// [`SequenceHandler`] with [`Self::with_handler`] to keep a handle on the checker.
+    #[must_use]
+    pub fn with_sequence_checking(self, checker: SequenceChecker) -> Self {
+        self.with_handler(Api::Market, "subscribe", SequenceHandler::new(checker))
+    }
+
+    /// Drop user trades and order updates that were already delivered, e.g. pushed again after a
+    /// reconnect or already returned by a recovery fetch, see [`UserDedup`].
+    ///
+    /// This replaces the user websocket handlers of [`DEDUP_METHODS`], use
+    /// [`UserDedupHandler`] with [`Self::with_handler`] to keep a handle on the memory.
+    #[cfg(feature = "websocket")]
+    #[must_use]
+    pub fn with_user_dedup(self, dedup: UserDedup) -> Self {
+        let handler = UserDedupHandler::new(dedup);
+
+        DEDUP_METHODS.iter().fold(self, |builder, &method| {
+            builder.with_handler(Api::User, method, handler.clone())
+        })
+    }
+
     /// With authorization (`api_key`, `secret_key`), required for user websocket.
+    #[cfg(feature = "websocket")]
     pub fn with_auth(
         mut self,
         api_key: impl Into<String>,
@@ -136,11 +466,15 @@ impl<A, U, M> ControllerBuilder<A, U, M> {
         ControllerBuilder {
             config: self.config,
             market_actions_tx: self.market_actions_tx,
-            market_actions_handle: self.market_actions_handle,
-            market_stream_handle: self.market_stream_handle,
             user_actions_tx: self.user_actions_tx,
-            user_actions_handle: self.user_actions_handle,
-            user_stream_handle: self.user_stream_handle,
+            supervisor: self.supervisor,
+            market_tx: self.market_tx,
+            user_tx: self.user_tx,
+            in_flight: self.in_flight,
+            raw_hook: self.raw_hook,
+            message_stats: self.message_stats,
+            request_timeout: self.request_timeout,
+            read_only: self.read_only,
             data_tx: self.data_tx,
             data_rx: self.data_rx,
             _mark_auth: PhantomData,
@@ -149,6 +483,31 @@ impl<A, U, M> ControllerBuilder<A, U, M> {
         }
     }
 
+    /// Fetch new credentials with `refresh` when [`Controller::authenticate`] is refused, e.g. after
+    /// the key was revoked while reconnecting.
+    #[cfg(feature = "websocket")]
+    #[must_use]
+    pub fn with_credential_refresh(mut self, refresh: CredentialRefresh) -> Self {
+        self.config.credential_refresh = Some(refresh);
+
+        self
+    }
+
+    /// Add named credentials to the keyring, actions can then be signed with them through
+    /// [`Controller::push_account_action`].
+    #[cfg(feature = "websocket")]
+    #[must_use]
+    pub fn with_credentials(
+        mut self,
+        name: impl Into<String>,
+        api_key: impl Into<String>,
+        secret_key: impl Into<String>,
+    ) -> Self {
+        self.config = self.config.with_credentials(name, api_key, secret_key);
+
+        self
+    }
+
     /// With the Market Websocket.
     ///
     /// # Errors
@@ -159,19 +518,32 @@ impl<A, U, M> ControllerBuilder<A, U, M> {
         url: url::Url,
     ) -> Result<ControllerBuilder<A, U, MarketWs>> {
         self.config.websocket_market_api = Some(url);
-        let (market_stream_handle, market_tx_arc) =
-            market_api::initialize_market_stream(&self.config, Arc::clone(&self.data_tx)).await?;
+        let (market_stream_handle, market_tx_arc) = market_api::initialize_market_stream(
+            &self.config,
+            self.data_tx.clone(),
+            Arc::clone(&self.in_flight),
+            self.stream_hook(),
+        )
+        .await?;
         let (market_join_handle, market_actions_tx) =
             market_api::initialize_market_actions(Arc::clone(&market_tx_arc)).await;
+        self.supervisor
+            .supervise(Task::MarketStream, market_stream_handle);
+        self.supervisor
+            .supervise(Task::MarketActions, market_join_handle);
 
         Ok(ControllerBuilder {
             config: self.config,
             market_actions_tx: Some(Arc::new(Mutex::new(market_actions_tx))),
-            market_actions_handle: Some(market_join_handle),
-            market_stream_handle: Some(market_stream_handle),
             user_actions_tx: self.user_actions_tx,
-            user_actions_handle: self.user_actions_handle,
-            user_stream_handle: self.user_stream_handle,
+            supervisor: self.supervisor,
+            market_tx: Some(market_tx_arc),
+            user_tx: self.user_tx,
+            in_flight: self.in_flight,
+            raw_hook: self.raw_hook,
+            message_stats: self.message_stats,
+            request_timeout: self.request_timeout,
+            read_only: self.read_only,
             data_tx: self.data_tx,
             data_rx: self.data_rx,
             _mark_auth: PhantomData,
@@ -181,26 +553,47 @@ impl<A, U, M> ControllerBuilder<A, U, M> {
     }
 }
 
-impl<Auth, U, M> ControllerBuilder<Auth, U, M> {
-    /// With the User Websocket, requires `api_key` and `secret_key` [`ControllerBuilder::with_auth`].
+#[cfg(feature = "websocket")]
+impl<A, U, M> ControllerBuilder<A, U, M> {
+    /// With the User Websocket.
+    ///
+    /// Private methods need `api_key` and `secret_key` from [`ControllerBuilder::with_auth`],
+    /// which can be called before or after this.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `initialize_user_stream` fails.
     pub async fn with_user_websocket(
         mut self,
         url: url::Url,
-    ) -> Result<ControllerBuilder<Auth, UserWs, M>> {
+    ) -> Result<ControllerBuilder<A, UserWs, M>> {
         self.config.websocket_user_api = Some(url);
-        let (user_stream_handle, user_tx_arc) =
-            user_api::initialize_user_stream(&self.config, Arc::clone(&self.data_tx)).await?;
+        let (user_stream_handle, user_tx_arc) = user_api::initialize_user_stream(
+            &self.config,
+            self.data_tx.clone(),
+            Arc::clone(&self.in_flight),
+            self.stream_hook(),
+        )
+        .await?;
         let (user_actions_handle, user_actions_tx) =
             user_api::initialize_user_actions(Arc::clone(&user_tx_arc)).await;
+        self.supervisor
+            .supervise(Task::UserStream, user_stream_handle);
+        self.supervisor
+            .supervise(Task::UserActions, user_actions_handle);
 
         Ok(ControllerBuilder {
             config: self.config,
             market_actions_tx: self.market_actions_tx,
-            market_actions_handle: self.market_actions_handle,
-            market_stream_handle: self.market_stream_handle,
             user_actions_tx: Some(Arc::new(Mutex::new(user_actions_tx))),
-            user_actions_handle: Some(user_actions_handle),
-            user_stream_handle: Some(user_stream_handle),
+            supervisor: self.supervisor,
+            market_tx: self.market_tx,
+            user_tx: Some(user_tx_arc),
+            in_flight: self.in_flight,
+            raw_hook: self.raw_hook,
+            message_stats: self.message_stats,
+            request_timeout: self.request_timeout,
+            read_only: self.read_only,
             data_tx: self.data_tx,
             data_rx: self.data_rx,
             _mark_auth: PhantomData,
@@ -211,134 +604,1606 @@ impl<Auth, U, M> ControllerBuilder<Auth, U, M> {
 }
 
 impl<A, U, M> ControllerBuilder<A, U, M> {
-    /// Build a controller with auth and a user websocket but no market websocket.
+    /// Build the controller with the websockets that were added, in whichever order.
+    ///
+    /// Must be called inside a tokio runtime when [`ControllerBuilder::with_request_timeout`] is
+    /// set, the timeouts are checked by a spawned task.
     #[must_use]
     pub fn build(self) -> Controller<U, M> {
+        if let Some(timeout) = self.request_timeout {
+            tokio::spawn(expire_requests(
+                Arc::downgrade(&self.in_flight),
+                self.data_tx.clone(),
+                timeout,
+            ));
+        }
+
         Controller {
             config: self.config,
-            current_id: 0,
+            current_id: Arc::new(AtomicU64::new(0)),
+            shutting_down: Arc::new(AtomicBool::new(false)),
+            read_only: Arc::new(AtomicBool::new(self.read_only)),
             market_actions_tx: self.market_actions_tx,
-            market_actions_handle: self.market_actions_handle,
-            market_stream_handle: self.market_stream_handle,
             user_actions_tx: self.user_actions_tx,
-            user_actions_handle: self.user_actions_handle,
-            user_stream_handle: self.user_stream_handle,
+            supervisor: self.supervisor,
+            market_tx: self.market_tx,
+            user_tx: self.user_tx,
+            in_flight: self.in_flight,
+            raw_hook: self.raw_hook,
+            message_stats: self.message_stats,
+            accounts: HashMap::new(),
+            instrument_rules: None,
+            #[cfg(feature = "rest")]
+            instrument_cache: None,
+            #[cfg(feature = "websocket")]
+            simulator: Arc::new(std::sync::RwLock::new(None)),
+            #[cfg(feature = "websocket")]
+            risk_manager: Arc::new(std::sync::RwLock::new(None)),
+            subscriptions: Arc::new(Mutex::new(Subscriptions::default())),
+            user_subscriptions: Arc::new(Mutex::new(Subscriptions::default())),
+            user_auth: Arc::new(AuthGate::new()),
             data_tx: self.data_tx,
-            data_rx: self.data_rx,
+            data_rx: Some(self.data_rx),
+            callbacks: Arc::new(std::sync::Mutex::new(Callbacks::new())),
+            callback_listener: None,
             _mark_user_ws: PhantomData,
             _mark_market_ws: PhantomData,
         }
     }
 }
 
-impl<UserWs, W> Controller<UserWs, W> {
+#[cfg(feature = "websocket")]
+impl<M> Controller<UserWs, M> {
+    /// Arm a [`DeadMansSwitch`] that cancels all orders in `instrument_names` unless it is fed
+    /// within `timeout`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if sending the cancel on disconnect request fails.
+    pub async fn dead_mans_switch(
+        &self,
+        timeout: Duration,
+        instrument_names: Vec<String>,
+    ) -> Result<DeadMansSwitch> {
+        Ok(DeadMansSwitch::arm(self.user_queue(), timeout, instrument_names).await?)
+    }
+
+    /// Authorize the user websocket with [`Config::api_key`] and [`Config::secret_key`] and wait
+    /// for the reply, sending each step as [`WebsocketData::AuthState`].
+    ///
+    /// When crypto.com refuses the keys and [`ControllerBuilder::with_credential_refresh`] was
+    /// set, new keys are fetched and tried until one is accepted, the accepted keys replace the
+    /// ones in the config. See [`auth_state::authenticate`].
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::ConfigMissing`] if the keys are not set, otherwise the same errors
+    /// as [`auth_state::authenticate`].
+    pub async fn authenticate(&mut self) -> Result<()> {
+        let (Some(api_key), Some(secret_key)) =
+            (self.config.api_key.clone(), self.config.secret_key.clone())
+        else {
+            return Err(ApiError::ConfigMissing("api_key".to_owned()));
+        };
+
+        let queue = self.user_queue();
+        let credentials = auth_state::authenticate(
+            Credentials {
+                api_key,
+                secret_key,
+            },
+            self.config.credential_refresh.as_ref(),
+            &self.data_tx,
+            |auth| {
+                let queue = queue.clone();
+
+                Box::pin(async move { queue.request(auth).await.map(drop).map_err(Into::into) })
+            },
+        )
+        .await?;
+
+        self.config.api_key = Some(credentials.api_key);
+        self.config.secret_key = Some(credentials.secret_key);
+
+        Ok(())
+    }
+
+    /// Start an [`OrderScheduler`] that sends orders and cancels to the user websocket no faster
+    /// than `limits` allow for each instrument.
+    #[must_use]
+    pub fn order_scheduler(&self, limits: ThrottleLimits) -> OrderScheduler {
+        OrderScheduler::spawn(self.user_queue(), limits)
+    }
+
+    /// A handle for sending actions to the user websocket from other tasks.
+    #[must_use]
+    pub fn user_queue(&self) -> ActionQueue {
+        self.queue(Api::User)
+            .expect("a controller with a user websocket has a user actions sender")
+    }
+
     /// Push an action to the user websocket and increment the current ID to prevent duplicates.
     ///
+    /// The channels of a [`actions::Subscribe`] or a user subscription action, e.g.
+    /// [`actions::SubscribeUserBalance`], are recorded in [`Controller::user_subscriptions`].
+    ///
+    /// A private action pushed right after [`actions::Auth`] waits for crypto.com to accept the
+    /// keys before it is sent, see [`Controller::user_auth`].
+    ///
     /// # Errors
     ///
-    /// Will return `Err` if `unbounded_send` fails.
-    pub async fn push_user_action(&mut self, action: Box<dyn Action>) -> Result<()> {
-        if let Some(ref user_actions_tx) = self.user_actions_tx {
-            user_actions_tx.lock().await.unbounded_send(ActionStore {
-                id: self.current_id,
-                action,
-            })?;
+    /// Will return `Err` if `unbounded_send` fails or [`ApiError::ShuttingDown`] if
+    /// [`Controller::shutdown`] has been called, [`ApiError::ReadOnly`] for a mutating action
+    /// while the controller is read-only, [`ApiError::WithdrawalRefused`] for a withdrawal in an
+    /// environment that does not allow them, [`ApiError::RiskViolation`] for orders rejected by
+    /// the risk manager, [`ApiError::DryRun`] for an action that is not simulated in dry run mode,
+    /// [`ApiError::NotAuthenticated`] for a private action when the user websocket was not
+    /// authorized or the keys were refused.
+    pub async fn push_user_action(&mut self, action: impl Into<ActionKind>) -> Result<()> {
+        let action = Arc::new(action.into());
+
+        self.refuse_read_only(action.as_ref())?;
+        let subscribe = actions::as_subscribe(action.as_ref());
 
-            self.current_id += 1;
+        self.queue_action(Api::User, action).await?;
+
+        if let Some(subscribe) = subscribe {
+            self.user_subscriptions.lock().await.insert(&subscribe);
         }
 
         Ok(())
     }
-}
 
-impl<U, MarketWs> Controller<U, MarketWs> {
-    /// Push an action to the market websocket and increment the current ID to prevent duplicates.
+    /// Subscribe to the user channels of `subscribe` that are not in
+    /// [`Controller::user_subscriptions`] and wait for the exchange to accept them, e.g.
+    /// `controller.subscribe_user(SubscribeUserOrders(None))`.
+    ///
+    /// The channels are recorded before the request is sent and forgotten again if it fails.
+    ///
+    /// Returns the channels that were subscribed, empty if every channel already was.
     ///
     /// # Errors
     ///
-    /// Will return `Err` if `unbounded_send` fails.
-    pub async fn push_market_action(&mut self, action: Box<dyn Action>) -> Result<()> {
-        if let Some(ref market_actions_tx) = self.market_actions_tx {
-            market_actions_tx.lock().await.unbounded_send(ActionStore {
-                id: self.current_id,
-                action,
-            })?;
+    /// Will return the same errors as [`ActionQueue::request`].
+    pub async fn subscribe_user(
+        &self,
+        subscribe: impl Into<actions::Subscribe>,
+    ) -> Result<Vec<String>> {
+        let mut subscribe = subscribe.into();
+
+        {
+            let mut subscriptions = self.user_subscriptions.lock().await;
+
+            subscribe.channels.sort_unstable();
+            subscribe.channels.dedup();
+            subscribe
+                .channels
+                .retain(|channel| !subscriptions.contains(channel));
+            subscriptions.insert(&subscribe);
+        }
+
+        if subscribe.channels.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let channels = subscribe.channels.clone();
 
-            self.current_id += 1;
+        if let Err(err) = self.user_queue().request(subscribe).await {
+            let mut subscriptions = self.user_subscriptions.lock().await;
+
+            for channel in &channels {
+                subscriptions.remove(channel);
+            }
+
+            return Err(err);
         }
 
+        Ok(channels)
+    }
+
+    /// Send `order` without waiting for the reply, checking it against the instrument rules first
+    /// if they have been set with [`Controller::set_instrument_cache`] or
+    /// [`Controller::set_instrument_rules`].
+    ///
+    /// Returns the ID of the request, the reply is broadcast like any other.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::InvalidOrder`] if the order fails the instrument rules, `Err` if
+    /// the instrument cache could not be refreshed, otherwise the same errors as
+    /// [`Controller::push_user_action`].
+    pub async fn submit_order(&mut self, order: CreateOrder) -> Result<u64> {
+        self.validate_order(&order).await?;
+
+        let action = ActionKind::CreateOrder(order);
+
+        self.refuse_read_only(&action)?;
+
+        self.user_queue().push(action).await
+    }
+
+    /// Send `order` like [`Controller::submit_order`] and wait for the reply.
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`Controller::submit_order`] and [`ActionQueue::request`].
+    pub async fn create_order(&mut self, order: CreateOrder) -> Result<data::CreateOrder> {
+        // Subscribed before sending so the reply is not missed.
+        let mut data_rx = self.data_tx.subscribe();
+        let id = self.submit_order(order).await?;
+        let reply = wait_for_reply(&mut data_rx, id).await?;
+
+        reply_data(&reply, "private/create-order", |data| match *data {
+            WebsocketData::CreateOrder(ref res) => Some(res.clone()),
+            _ => None,
+        })
+    }
+
+    /// Cancel `order` without waiting for the reply, in the simulator when in dry run mode.
+    ///
+    /// Returns the ID of the request, the reply is broadcast like any other.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::InvalidOrder`] if the order is not open in the simulator, otherwise
+    /// the same errors as [`Controller::push_user_action`].
+    pub async fn submit_cancel(&mut self, order: CancelOrder) -> Result<u64> {
+        self.refuse_read_only(&order)?;
+
+        self.user_queue().push(order).await
+    }
+
+    /// Cancel `order` like [`Controller::submit_cancel`] and wait for the reply.
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`Controller::submit_cancel`] and [`ActionQueue::request`].
+    pub async fn cancel_order(&mut self, order: CancelOrder) -> Result<data::CancelOrder> {
+        // Subscribed before sending so the reply is not missed.
+        let mut data_rx = self.data_tx.subscribe();
+        let id = self.submit_cancel(order).await?;
+        let reply = wait_for_reply(&mut data_rx, id).await?;
+
+        reply_data(&reply, "private/cancel-order", |data| match *data {
+            WebsocketData::CancelOrder(ref res) => Some(res.clone()),
+            _ => None,
+        })
+    }
+
+    /// Cancel every order of `instrument_name` and wait for the reply.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::ReadOnly`] while the controller is read-only, otherwise the same
+    /// errors as [`ActionQueue::request`].
+    pub async fn cancel_all_orders(&self, instrument_name: impl Into<String>) -> Result<()> {
+        let action = CancelAllOrders {
+            instrument_name: instrument_name.into(),
+        };
+
+        let action = ActionKind::CancelAllOrders(action);
+
+        self.refuse_read_only(&action)?;
+        self.user_queue().request(action).await?;
+
         Ok(())
     }
-}
 
-impl<U, W> Controller<U, W> {
-    /// Get a clone of the data reader.
-    #[must_use]
-    pub fn get_data_reader(&self) -> DataReciever {
-        Arc::clone(&self.data_rx)
+    /// The balances of `currency`, or of every currency if `None`.
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`ActionQueue::request`], or [`ApiError::UnexpectedReply`]
+    /// if the reply has no account summary.
+    pub async fn get_account_summary(
+        &self,
+        currency: Option<String>,
+    ) -> Result<data::AccountSummary> {
+        self.request_user(GetAccountSummary { currency }, |data| match *data {
+            WebsocketData::GetAccountSummary(ref res) => Some(res.clone()),
+            _ => None,
+        })
+        .await
     }
 
-    /// Create a data listener.
+    /// The first page of open orders of `instrument_name`, or of every instrument if `None`.
     ///
-    /// In order to use this function you must pass a lambda that returns `Ok(false)` to continue
-    /// processing data, or `Ok(true)` to break the loop and stop processing data.
-    pub fn listen<F>(&mut self, mut async_fn: F) -> JoinHandle<Result<()>>
+    /// # Errors
+    ///
+    /// Will return the same errors as [`ActionQueue::request`], or [`ApiError::UnexpectedReply`]
+    /// if the reply has no open orders.
+    pub async fn get_open_orders(
+        &self,
+        instrument_name: Option<String>,
+    ) -> Result<data::OpenOrders> {
+        let action = GetOpenOrders {
+            instrument_name,
+            ..GetOpenOrders::default()
+        };
+
+        self.request_user(action, |data| match *data {
+            WebsocketData::GetOpenOrders(ref res) => Some(res.clone()),
+            _ => None,
+        })
+        .await
+    }
+
+    /// One page of the order history.
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`ActionQueue::request`], or [`ApiError::UnexpectedReply`]
+    /// if the reply has no order history.
+    pub async fn get_order_history(&self, params: Paginated) -> Result<data::OrderHistory> {
+        self.request_user(GetOrderHistory(params), |data| match *data {
+            WebsocketData::GetOrderHistory(ref res) => Some(res.clone()),
+            _ => None,
+        })
+        .await
+    }
+
+    /// The details of `order_id`.
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`ActionQueue::request`], or [`ApiError::UnexpectedReply`]
+    /// if the reply has no order detail.
+    pub async fn get_order_detail(&self, order_id: impl Into<String>) -> Result<data::OrderDetail> {
+        let action = GetOrderDetail {
+            order_id: order_id.into(),
+        };
+
+        self.request_user(action, |data| match *data {
+            WebsocketData::GetOrderDetail(ref res) => Some(res.as_ref().clone()),
+            _ => None,
+        })
+        .await
+    }
+
+    /// One page of the executed trades.
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`ActionQueue::request`], or [`ApiError::UnexpectedReply`]
+    /// if the reply has no trades.
+    pub async fn get_trades(&self, params: Paginated) -> Result<data::Trades> {
+        self.request_user(GetTrades(params), |data| match *data {
+            WebsocketData::GetTrades(ref res) => Some(res.clone()),
+            _ => None,
+        })
+        .await
+    }
+
+    /// Send `action` on the user websocket, wait for the reply, and take its data with `take`.
+    async fn request_user<A, T>(
+        &self,
+        action: A,
+        take: impl FnOnce(&WebsocketData) -> Option<T>,
+    ) -> Result<T>
     where
-        F: FnMut(ApiResponse<WebsocketData>) -> Result<bool> + Send + 'static,
+        A: Into<ActionKind>,
     {
-        let data_rx_arc = self.get_data_reader();
-        let market_actions_handle = self.market_actions_handle.take();
-        let market_stream_handle = self.market_stream_handle.take();
-        let user_actions_handle = self.user_actions_handle.take();
-        let user_stream_handle = self.user_stream_handle.take();
+        let action = action.into();
+        let method = action.method();
+        let reply = self.user_queue().request(action).await?;
 
-        tokio::spawn(async move {
-            let listener_join_handle = tokio::spawn(async move {
-                let mut data_rx = data_rx_arc.lock().await;
+        reply_data(&reply, method, take)
+    }
 
-                while let Some(data) = data_rx.next().await {
-                    if async_fn(data)? {
-                        break;
-                    }
-                }
+    /// Reconnect the user websocket, authorize it again with [`Controller::authenticate`] if keys
+    /// are set, subscribe to [`Controller::user_subscriptions`] again, and replay the requests that
+    /// were not answered, each with a fresh ID and nonce. Unanswered requests that change orders
+    /// or funds are reported with [`WebsocketData::RequestOutcomeUnknown`] instead.
+    ///
+    /// Returns the new IDs of the replayed requests in the order they were originally sent.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `initialize_user_stream` or `unbounded_send` fails, or the same errors
+    /// as [`Controller::authenticate`] if the websocket could not be authorized again.
+    pub async fn reconnect_user(&mut self) -> Result<Vec<u64>> {
+        send_state(&self.data_tx, ConnectionState::Reconnecting(Api::User));
 
-                Ok(())
-            });
-
-            match (
-                market_actions_handle,
-                market_stream_handle,
-                user_actions_handle,
-                user_stream_handle,
-            ) {
-                (
-                    Some(market_actions_handle),
-                    Some(market_stream_handle),
-                    Some(user_actions_handle),
-                    Some(user_stream_handle),
-                ) => tokio::select! {
-                    listener = listener_join_handle => listener,
-                    market_actions = market_actions_handle => market_actions,
-                    market_stream = market_stream_handle => market_stream,
-                    user_actions = user_actions_handle => user_actions,
-                    user_stream = user_stream_handle => user_stream,
-                }?,
-                (Some(market_actions_handle), Some(market_stream_handle), None, None) => {
-                    tokio::select! {
-                        listener = listener_join_handle => listener,
-                        market_actions = market_actions_handle => market_actions,
-                        market_stream = market_stream_handle => market_stream,
-                    }?
-                }
-                (None, None, Some(user_actions_handle), Some(user_stream_handle)) => {
-                    tokio::select! {
-                        listener = listener_join_handle => listener,
-                        user_actions = user_actions_handle => user_actions,
-                        user_stream = user_stream_handle => user_stream,
-                    }?
-                }
-                _ => listener_join_handle.await?,
+        let (user_stream_handle, user_tx_arc) = match user_api::initialize_user_stream(
+            &self.config,
+            self.data_tx.clone(),
+            Arc::clone(&self.in_flight),
+            self.stream_hook(),
+        )
+        .await
+        {
+            Ok(stream) => stream,
+            Err(err) => {
+                send_state(
+                    &self.data_tx,
+                    ConnectionState::ReconnectFailed {
+                        api: Api::User,
+                        error: format!("{err:#}"),
+                        kind: DisconnectKind::of(&err),
+                    },
+                );
+
+                return Err(err.into());
             }
-        })
+        };
+        self.supervisor
+            .supervise(Task::UserStream, user_stream_handle);
+
+        // The actions task holds the old sender arc, so the new connection is swapped into it.
+        if let Some(ref user_tx) = self.user_tx {
+            *user_tx.lock().await = user_tx_arc.lock().await.clone();
+        }
+
+        send_state(&self.data_tx, ConnectionState::Reconnected(Api::User));
+
+        let pending = self.in_flight.take(Api::User).await;
+
+        // Authorization does not carry over to the new connection.
+        self.user_auth.set(AuthStatus::Unauthenticated);
+
+        if self.config.api_key.is_some() && self.config.secret_key.is_some() {
+            self.authenticate().await?;
+        }
+
+        let channels: Vec<String> = self
+            .user_subscriptions
+            .lock()
+            .await
+            .channels()
+            .map(str::to_owned)
+            .collect();
+
+        // Neither do subscriptions.
+        if !channels.is_empty() {
+            let subscribe = actions::Subscribe {
+                channels,
+                ..actions::Subscribe::default()
+            };
+            self.queue_action(Api::User, Arc::new(subscribe.into()))
+                .await?;
+        }
+
+        self.replay(Api::User, pending).await
     }
+
+    /// Switch the user websocket to new keys without a gap, e.g. when the old keys are about to
+    /// be revoked.
+    ///
+    /// A second user websocket is connected, authorized with the new keys, and subscribed to
+    /// every channel in [`Controller::user_subscriptions`]. Only then are new actions sent on it
+    /// instead of the old one. The old websocket is closed once every request sent on it has been
+    /// answered, requests still unanswered after [`ROTATION_DRAIN_TIMEOUT`] are replayed on the
+    /// new one, except those that change orders or funds, which are reported with
+    /// [`WebsocketData::RequestOutcomeUnknown`]. A [`ConnectionState::Rotated`] is sent after the swap.
+    ///
+    /// Both websockets are subscribed while the old one drains, so user events in that window may
+    /// be broadcast twice.
+    ///
+    /// Returns the IDs of the replayed requests.
+    ///
+    /// # Errors
+    ///
+    /// Will return the errors of `initialize_user_stream`, [`auth_state::authenticate`], or the
+    /// subscription, in which case the new websocket is closed and the old one is left as it was.
+    pub async fn rotate_credentials(
+        &mut self,
+        api_key: impl Into<String>,
+        secret_key: impl Into<String>,
+    ) -> Result<Vec<u64>> {
+        let Some(user_tx) = self.user_tx.clone() else {
+            return Err(ApiError::ConfigMissing("websocket_user_api".to_owned()));
+        };
+
+        let credentials = Credentials {
+            api_key: api_key.into(),
+            secret_key: secret_key.into(),
+        };
+
+        let (stream_handle, new_tx) = user_api::initialize_user_stream(
+            &self.config,
+            self.data_tx.clone(),
+            Arc::clone(&self.in_flight),
+            self.stream_hook(),
+        )
+        .await?;
+
+        // Sends the auth and subscribe requests on the new websocket only.
+        let (actions_handle, actions_tx) =
+            user_api::initialize_user_actions(Arc::clone(&new_tx)).await;
+        let actions_tx = Arc::new(Mutex::new(actions_tx));
+        let queue = self.action_queue(
+            Api::User,
+            &actions_tx,
+            &self.in_flight,
+            Some(Arc::new(AuthGate::new())),
+        );
+
+        let channels: Vec<String> = self
+            .user_subscriptions
+            .lock()
+            .await
+            .channels()
+            .map(str::to_owned)
+            .collect();
+
+        let ready = async {
+            let credentials = auth_state::authenticate(credentials, None, &self.data_tx, |auth| {
+                let queue = queue.clone();
+
+                Box::pin(async move { queue.request(auth).await.map(drop).map_err(Into::into) })
+            })
+            .await?;
+
+            if !channels.is_empty() {
+                let subscribe = actions::Subscribe {
+                    channels,
+                    ..actions::Subscribe::default()
+                };
+                queue.request(subscribe).await?;
+            }
+
+            anyhow::Ok(credentials)
+        }
+        .await;
+
+        let credentials = match ready {
+            Ok(credentials) => credentials,
+            Err(err) => {
+                close_connection(&new_tx, &actions_tx).await?;
+
+                return Err(err.into());
+            }
+        };
+
+        // The temporary actions task ends, new actions go through the controller's own.
+        actions_tx.lock().await.close_channel();
+        drop(actions_handle);
+        let new_sender = new_tx.lock().await.clone();
+
+        // Requests below this ID were sent on the old websocket.
+        let cutoff = self.current_id.load(Ordering::SeqCst);
+        let old_sender = std::mem::replace(&mut *user_tx.lock().await, new_sender);
+        self.supervisor.supervise(Task::UserStream, stream_handle);
+
+        self.config.api_key = Some(credentials.api_key);
+        self.config.secret_key = Some(credentials.secret_key);
+        self.user_auth.set(AuthStatus::Authenticated);
+        send_state(&self.data_tx, ConnectionState::Rotated(Api::User));
+
+        let settled = self.in_flight.settled_before(Api::User, cutoff);
+        if tokio::time::timeout(ROTATION_DRAIN_TIMEOUT, settled)
+            .await
+            .is_err()
+        {
+            trace::warning!("The old user websocket did not drain, replaying its requests.");
+        }
+
+        if !old_sender.is_closed() {
+            old_sender.unbounded_send(Message::Close(None))?;
+        }
+
+        let pending = self.in_flight.take_before(Api::User, cutoff).await;
+        self.replay(Api::User, pending).await
+    }
+}
+
+impl<U> Controller<U, MarketWs> {
+    /// A handle for sending actions to the market websocket from other tasks.
+    #[must_use]
+    pub fn market_queue(&self) -> ActionQueue {
+        self.queue(Api::Market)
+            .expect("a controller with a market websocket has a market actions sender")
+    }
+
+    /// Push an action to the market websocket and increment the current ID to prevent duplicates.
+    ///
+    /// The channels of a [`actions::Subscribe`] are recorded in [`Controller::subscriptions`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `unbounded_send` fails or [`ApiError::ShuttingDown`] if
+    /// [`Controller::shutdown`] has been called, [`ApiError::InvalidInterval`] for a subscription
+    /// to a candlestick channel with an unknown interval, [`ApiError::PrivateOnMarket`] for a
+    /// private action, e.g. `private/create-order` or a `user.` subscription.
+    pub async fn push_market_action(&mut self, action: impl Into<ActionKind>) -> Result<()> {
+        let action = Arc::new(action.into());
+        let subscribe = match *action {
+            ActionKind::Subscribe(ref subscribe) => Some(subscribe),
+            _ => None,
+        };
+
+        if let Some(subscribe) = subscribe {
+            subscribe.validate()?;
+        }
+
+        self.queue_action(Api::Market, Arc::clone(&action)).await?;
+
+        if let Some(subscribe) = subscribe {
+            self.subscriptions.lock().await.insert(subscribe);
+        }
+
+        Ok(())
+    }
+
+    /// Subscribe to the market `channels` that are not already subscribed and wait for the
+    /// exchange to accept them, see [`Controller::subscribe_with`].
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`Controller::subscribe_with`].
+    pub async fn subscribe<I, S>(&self, channels: I) -> Result<Vec<String>>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.subscribe_with(actions::Subscribe {
+            channels: channels.into_iter().map(Into::into).collect(),
+            ..actions::Subscribe::default()
+        })
+        .await
+    }
+
+    /// Subscribe to the channels of `subscribe` that are not in [`Controller::subscriptions`] and
+    /// wait for the exchange to accept them.
+    ///
+    /// The new channels are recorded before the request is sent, so a call from another task for
+    /// the same channels in the meantime sends nothing. They are forgotten again if the request
+    /// fails.
+    ///
+    /// Returns the channels that were subscribed, empty if every channel already was.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::InvalidInterval`] if a candlestick channel has an unknown interval,
+    /// otherwise the same errors as [`ActionQueue::request`].
+    pub async fn subscribe_with(&self, mut subscribe: actions::Subscribe) -> Result<Vec<String>> {
+        subscribe.validate()?;
+
+        {
+            let mut subscriptions = self.subscriptions.lock().await;
+
+            subscribe.channels.sort_unstable();
+            subscribe.channels.dedup();
+            subscribe
+                .channels
+                .retain(|channel| !subscriptions.contains(channel));
+            subscriptions.insert(&subscribe);
+        }
+
+        if subscribe.channels.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let channels = subscribe.channels.clone();
+
+        if let Err(err) = self.market_queue().request(subscribe).await {
+            let mut subscriptions = self.subscriptions.lock().await;
+
+            for channel in &channels {
+                subscriptions.remove(channel);
+            }
+
+            return Err(err);
+        }
+
+        Ok(channels)
+    }
+
+    /// Unsubscribe from the market `channels` in [`Controller::subscriptions`] and wait for the
+    /// exchange to accept it.
+    ///
+    /// The channels are forgotten before the request is sent, and recorded again if it fails.
+    ///
+    /// Returns the channels that were unsubscribed, empty if none of them were subscribed.
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`ActionQueue::request`].
+    pub async fn unsubscribe<I, S>(&self, channels: I) -> Result<Vec<String>>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let removed: Vec<_> = {
+            let mut subscriptions = self.subscriptions.lock().await;
+
+            channels
+                .into_iter()
+                .map(Into::into)
+                .filter_map(|channel| {
+                    let options = subscriptions.remove(&channel)?;
+
+                    Some((channel, options))
+                })
+                .collect()
+        };
+
+        if removed.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let unsubscribe = actions::Unsubscribe {
+            channels: removed.iter().map(|(channel, _)| channel.clone()).collect(),
+        };
+        let channels = unsubscribe.channels.clone();
+
+        if let Err(err) = self.market_queue().request(unsubscribe).await {
+            let mut subscriptions = self.subscriptions.lock().await;
+
+            for (channel, options) in removed {
+                subscriptions.insert_channel(channel, options);
+            }
+
+            return Err(err);
+        }
+
+        Ok(channels)
+    }
+
+    /// Reconnect the market websocket and replay the requests that were not answered, each with a
+    /// fresh ID and nonce. Unanswered requests that change orders or funds are reported with
+    /// [`WebsocketData::RequestOutcomeUnknown`] instead.
+    ///
+    /// Returns the new IDs of the replayed requests in the order they were originally sent.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `initialize_market_stream` or `unbounded_send` fails.
+    pub async fn reconnect_market(&mut self) -> Result<Vec<u64>> {
+        send_state(&self.data_tx, ConnectionState::Reconnecting(Api::Market));
+
+        let (market_stream_handle, market_tx_arc) = match market_api::initialize_market_stream(
+            &self.config,
+            self.data_tx.clone(),
+            Arc::clone(&self.in_flight),
+            self.stream_hook(),
+        )
+        .await
+        {
+            Ok(stream) => stream,
+            Err(err) => {
+                send_state(
+                    &self.data_tx,
+                    ConnectionState::ReconnectFailed {
+                        api: Api::Market,
+                        error: format!("{err:#}"),
+                        kind: DisconnectKind::of(&err),
+                    },
+                );
+
+                return Err(err.into());
+            }
+        };
+        self.supervisor
+            .supervise(Task::MarketStream, market_stream_handle);
+
+        // The actions task holds the old sender arc, so the new connection is swapped into it.
+        if let Some(ref market_tx) = self.market_tx {
+            *market_tx.lock().await = market_tx_arc.lock().await.clone();
+        }
+
+        send_state(&self.data_tx, ConnectionState::Reconnected(Api::Market));
+
+        let pending = self.in_flight.take(Api::Market).await;
+
+        self.replay(Api::Market, pending).await
+    }
+}
+
+impl<U, W> Controller<U, W> {
+    /// Refuse actions that change orders, funds, or account settings while `read_only` is true,
+    /// with [`ApiError::ReadOnly`], see [`Action::mutates`].
+    ///
+    /// This covers every [`ActionQueue`] handed out by the controller, including ones handed out
+    /// before this is called, e.g. to an [`OrderScheduler`] or a [`DeadMansSwitch`].
+    pub fn set_read_only(&self, read_only: bool) {
+        self.read_only.store(read_only, Ordering::SeqCst);
+    }
+
+    /// Whether mutating actions are refused, see [`Controller::set_read_only`].
+    #[must_use]
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::SeqCst)
+    }
+
+    /// Check orders sent with [`Controller::create_order`] against `rules`, built from
+    /// `public/get-instruments` with [`InstrumentRules::from`].
+    pub fn set_instrument_rules(&mut self, rules: InstrumentRules) {
+        self.instrument_rules = Some(rules);
+    }
+
+    /// Check orders sent with [`Controller::create_order`] against the rules in `cache`, fetched
+    /// again whenever they are older than its TTL. Takes the place of
+    /// [`Controller::set_instrument_rules`].
+    #[cfg(feature = "rest")]
+    pub fn set_instrument_cache(&mut self, cache: Arc<InstrumentCache>) {
+        self.instrument_cache = Some(cache);
+    }
+
+    /// Open a user websocket for the credentials named `name` and authorize it, doing nothing if
+    /// it is already open.
+    ///
+    /// The websocket uses the user websocket URL from the config and sends its data to the same
+    /// broadcast as the main websockets. Request IDs are shared across every connection, so
+    /// responses can be matched to the account by ID.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::UnknownCredentials`] if there are no credentials named `name`, or
+    /// `Err` if `initialize_user_stream` or `unbounded_send` fails.
+    #[cfg(feature = "websocket")]
+    pub async fn connect_account(&mut self, name: &str) -> Result<()> {
+        if self.accounts.contains_key(name) {
+            return Ok(());
+        }
+
+        let config = self.config.for_credentials(name)?;
+        let in_flight = Arc::new(InFlight::default());
+
+        let (user_stream_handle, user_tx) = user_api::initialize_user_stream(
+            &config,
+            self.data_tx.clone(),
+            Arc::clone(&in_flight),
+            self.stream_hook(),
+        )
+        .await?;
+        let (user_actions_handle, actions_tx) =
+            user_api::initialize_user_actions(Arc::clone(&user_tx)).await;
+        self.supervisor
+            .supervise(Task::UserStream, user_stream_handle);
+        self.supervisor
+            .supervise(Task::UserActions, user_actions_handle);
+
+        self.accounts.insert(
+            name.to_owned(),
+            AccountConnection {
+                actions_tx: Arc::new(Mutex::new(actions_tx)),
+                user_tx,
+                in_flight,
+                auth: Arc::new(AuthGate::new()),
+            },
+        );
+        trace::info!("Connected account {name}.");
+
+        if let (Some(api_key), Some(secret_key)) = (config.api_key, config.secret_key) {
+            let auth = actions::Auth {
+                api_key,
+                secret_key,
+            };
+            self.push_account_action(name, auth).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Push an action to the user websocket of the credentials named `name`, connecting it first
+    /// if needed, and increment the current ID to prevent duplicates.
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`Controller::connect_account`], `Err` if `unbounded_send`
+    /// fails or [`ApiError::ShuttingDown`] if [`Controller::shutdown`] has been called,
+    /// [`ApiError::WithdrawalRefused`] for a withdrawal in an environment that does not allow them,
+    /// [`ApiError::RiskViolation`] for orders rejected by the risk manager.
+    #[cfg(feature = "websocket")]
+    pub async fn push_account_action(
+        &mut self,
+        name: &str,
+        action: impl Into<ActionKind>,
+    ) -> Result<()> {
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return Err(ApiError::ShuttingDown);
+        }
+
+        let action = Arc::new(action.into());
+
+        self.refuse_read_only(action.as_ref())?;
+        // Boxed because connecting pushes the auth action back through here.
+        Box::pin(self.connect_account(name)).await?;
+
+        let Some(queue) = self.account_queue(name) else {
+            return Err(ApiError::UnknownCredentials(name.to_owned()));
+        };
+        queue.push_shared(action).await?;
+
+        Ok(())
+    }
+
+    /// A handle for sending actions to the user websocket of the credentials named `name`, `None`
+    /// if it is not connected.
+    #[cfg(feature = "websocket")]
+    #[must_use]
+    pub fn account_queue(&self, name: &str) -> Option<ActionQueue> {
+        let account = self.accounts.get(name)?;
+
+        Some(self.action_queue(
+            Api::User,
+            &account.actions_tx,
+            &account.in_flight,
+            Some(Arc::clone(&account.auth)),
+        ))
+    }
+
+    /// Close the user websocket of the credentials named `name`.
+    ///
+    /// Returns the IDs of the requests that were still unanswered, `None` if it was not connected.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `unbounded_send` fails.
+    #[cfg(feature = "websocket")]
+    pub async fn disconnect_account(&mut self, name: &str) -> Result<Option<Vec<u64>>> {
+        let Some(account) = self.accounts.remove(name) else {
+            return Ok(None);
+        };
+
+        close_connection(&account.user_tx, &account.actions_tx).await?;
+        trace::info!("Disconnected account {name}.");
+
+        Ok(Some(account.in_flight.ids().await))
+    }
+
+    /// Paper trade: every `private/create-order` and `private/cancel-order`, including those
+    /// pushed through an [`ActionQueue`] handed out earlier, is matched against the local books in
+    /// `exchange` instead of being sent, and the resulting [`WebsocketData::UserOrder`] and
+    /// [`WebsocketData::UserTrade`] data is broadcast like the exchange's own.
+    ///
+    /// The returned task feeds the market data to `exchange`, subscribe to the `book` channels of
+    /// the traded instruments so it has books to match against. `private/cancel-all-orders`, e.g.
+    /// from a [`DeadMansSwitch`], cancels the simulated orders. Order lists and withdrawals fail
+    /// with [`ApiError::DryRun`] while the simulator is set.
+    #[cfg(feature = "websocket")]
+    pub fn dry_run(&mut self, exchange: SimulatedExchange) -> JoinHandle<Result<()>> {
+        let simulator = Arc::new(Mutex::new(exchange));
+        let mut data_rx = self.data_tx.subscribe();
+        let data_tx = self.data_tx.clone();
+
+        *self
+            .simulator
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(Arc::clone(&simulator));
+
+        tokio::spawn(async move {
+            loop {
+                let data = match data_rx.recv().await {
+                    Ok(data) => data,
+                    Err(RecvError::Lagged(skipped)) => {
+                        trace::warning!(
+                            "Simulator lagged behind, {skipped} messages were skipped."
+                        );
+                        continue;
+                    }
+                    Err(RecvError::Closed) => return Ok(()),
+                };
+
+                let Some(ref data) = data.result else {
+                    continue;
+                };
+
+                for event in simulator.lock().await.process(data) {
+                    send_data(
+                        &data_tx,
+                        ApiResponse::<WebsocketData>::default().websocket_data(event),
+                    );
+                }
+            }
+        })
+    }
+
+    /// Check the alerts of `engine` against the market data, broadcasting a
+    /// [`WebsocketData::AlertFired`] each time one fires.
+    ///
+    /// Keep a clone of `engine` to add and remove alerts while the returned task runs. Subscribe
+    /// to the `ticker` or `book` channels of the instruments with alerts.
+    pub fn watch_alerts(&self, engine: AlertEngine) -> JoinHandle<Result<()>> {
+        let mut data_rx = self.data_tx.subscribe();
+        let data_tx = self.data_tx.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let data = match data_rx.recv().await {
+                    Ok(data) => data,
+                    Err(RecvError::Lagged(skipped)) => {
+                        trace::warning!("Alerts lagged behind, {skipped} messages were skipped.");
+                        continue;
+                    }
+                    Err(RecvError::Closed) => return Ok(()),
+                };
+
+                let Some(ref data) = data.result else {
+                    continue;
+                };
+
+                for fired in engine.process(data) {
+                    send_data(
+                        &data_tx,
+                        ApiResponse::<WebsocketData>::default()
+                            .websocket_data(WebsocketData::AlertFired(fired)),
+                    );
+                }
+            }
+        })
+    }
+
+    /// Check every order with `manager` before it is sent, including those pushed through an
+    /// [`ActionQueue`] handed out earlier, rejecting the ones that break its limits with
+    /// [`ApiError::RiskViolation`].
+    ///
+    /// The returned task feeds the data to `manager`, subscribe to `user.order` and `user.trade`
+    /// so it can track open orders and positions, and to `trade` or `ticker` of the instruments
+    /// traded by market order so it can price them.
+    #[cfg(feature = "websocket")]
+    pub fn set_risk_manager(&mut self, manager: RiskManager) -> JoinHandle<Result<()>> {
+        let risk_manager = Arc::new(Mutex::new(manager));
+        let mut data_rx = self.data_tx.subscribe();
+
+        *self
+            .risk_manager
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(Arc::clone(&risk_manager));
+
+        tokio::spawn(async move {
+            loop {
+                let data = match data_rx.recv().await {
+                    Ok(data) => data,
+                    Err(RecvError::Lagged(skipped)) => {
+                        trace::warning!(
+                            "Risk manager lagged behind, {skipped} messages were skipped."
+                        );
+                        continue;
+                    }
+                    Err(RecvError::Closed) => return Ok(()),
+                };
+
+                if let Some(ref data) = data.result {
+                    risk_manager.lock().await.process(data);
+                }
+            }
+        })
+    }
+
+    /// Correct request nonces for the difference between the local clock and the exchange's,
+    /// estimated with a [`SkewEstimator`] from the market data and applied every `interval`.
+    ///
+    /// The correction is shared by every request in the process, see
+    /// [`crate::utils::clock::set_clock_offset`]. Subscribe to a market channel, e.g. `ticker`, so
+    /// there are timestamps to estimate from.
+    pub fn correct_clock_skew(&self, interval: Duration) -> JoinHandle<Result<()>> {
+        let mut data_rx = self.data_tx.subscribe();
+
+        tokio::spawn(async move {
+            let mut estimator = SkewEstimator::new();
+            let mut ticks = tokio::time::interval(interval);
+
+            loop {
+                tokio::select! {
+                    _ = ticks.tick() => {
+                        if let Some(offset) = estimator.apply() {
+                            trace::debug!("Clock offset for nonces is now {offset}ms.");
+                        }
+                    }
+                    data = data_rx.recv() => match data {
+                        Ok(data) => {
+                            if let Some(ref data) = data.result {
+                                estimator.process(data);
+                            }
+                        }
+                        Err(RecvError::Lagged(_)) => {}
+                        Err(RecvError::Closed) => return Ok(()),
+                    },
+                }
+            }
+        })
+    }
+}
+
+#[cfg(feature = "websocket")]
+impl<U, W> Controller<U, W> {
+    /// Check `order` against the instrument cache if it is set, otherwise the instrument rules if
+    /// they are set.
+    async fn validate_order(&self, order: &CreateOrder) -> Result<()> {
+        #[cfg(feature = "rest")]
+        if let Some(ref cache) = self.instrument_cache {
+            cache.validate_order(order).await?;
+
+            return Ok(());
+        }
+
+        if let Some(ref rules) = self.instrument_rules {
+            order.validate(rules)?;
+        }
+
+        Ok(())
+    }
+
+    /// Refuse `action` if it mutates and the controller is read-only, see
+    /// [`Controller::set_read_only`].
+    fn refuse_read_only(&self, action: &dyn Action) -> Result<()> {
+        if action.mutates() && self.is_read_only() {
+            return Err(ApiError::ReadOnly(action.method().to_owned()));
+        }
+
+        Ok(())
+    }
+}
+
+impl<U, W> Controller<U, W> {
+    /// Message counts and sizes by subscription, `None` unless
+    /// [`ControllerBuilder::with_message_stats`] was set.
+    #[must_use]
+    pub fn message_stats(&self) -> Option<MessageStatsSnapshot> {
+        self.message_stats.as_ref().map(MessageStats::snapshot)
+    }
+
+    /// The hook given to new websockets, counting frames in the message stats if set.
+    fn stream_hook(&self) -> Option<RawMessageHook> {
+        stream_hook(self.message_stats.as_ref(), self.raw_hook.as_ref())
+    }
+
+    /// A handle for sending actions on `api`, `None` if `api` is not connected.
+    pub(crate) fn queue(&self, api: Api) -> Option<ActionQueue> {
+        let actions_tx = match api {
+            Api::User => self.user_actions_tx.as_ref()?,
+            Api::Market => self.market_actions_tx.as_ref()?,
+        };
+
+        let auth = (api == Api::User).then(|| Arc::clone(&self.user_auth));
+
+        Some(self.action_queue(api, actions_tx, &self.in_flight, auth))
+    }
+
+    /// A handle for sending actions through `actions_tx`, sharing the request IDs and guards of
+    /// the controller.
+    fn action_queue(
+        &self,
+        api: Api,
+        actions_tx: &ActionStoreSender,
+        in_flight: &Arc<InFlight>,
+        auth: Option<Arc<AuthGate>>,
+    ) -> ActionQueue {
+        ActionQueue {
+            api,
+            actions_tx: Arc::clone(actions_tx),
+            current_id: Arc::clone(&self.current_id),
+            shutting_down: Arc::clone(&self.shutting_down),
+            read_only: Arc::clone(&self.read_only),
+            in_flight: Arc::clone(in_flight),
+            data_tx: self.data_tx.clone(),
+            auth,
+            #[cfg(feature = "websocket")]
+            environment: self.config.environment,
+            #[cfg(feature = "websocket")]
+            simulator: Arc::clone(&self.simulator),
+            #[cfg(feature = "websocket")]
+            risk_manager: Arc::clone(&self.risk_manager),
+        }
+    }
+
+    /// Queue `action` as unanswered and send it on `api` with the next ID.
+    ///
+    /// Returns the ID the action was sent with, or `None` if `api` is not connected.
+    async fn queue_action(&mut self, api: Api, action: Arc<ActionKind>) -> Result<Option<u64>> {
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return Err(ApiError::ShuttingDown);
+        }
+
+        match self.queue(api) {
+            Some(queue) => Ok(Some(queue.push_shared(action).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Send the `pending` requests taken from `api` again with a fresh ID.
+    ///
+    /// Requests that change orders or funds are not sent again, they are reported with
+    /// [`WebsocketData::RequestOutcomeUnknown`], see [`replayable`].
+    async fn replay(&mut self, api: Api, pending: Vec<(u64, Arc<ActionKind>)>) -> Result<Vec<u64>> {
+        let mut ids = vec![];
+
+        for (old_id, action) in replayable(&self.data_tx, pending) {
+            if let Some(id) = self.queue_action(api, action).await? {
+                trace::info!("Replaying request {old_id} as {id}.");
+                ids.push(id);
+            }
+        }
+
+        Ok(ids)
+    }
+
+    /// Bring local state back in line with the exchange, e.g. after a reconnect.
+    ///
+    /// Requests the open orders and the account summary on the user websocket, and subscribes to
+    /// every `book` channel in [`Controller::subscriptions`] again on the market websocket for a
+    /// fresh snapshot. Websockets that are not connected are skipped. The replies are broadcast
+    /// like any other, followed by a single [`WebsocketData::ResyncComplete`] once every request
+    /// has an outcome and every book has a snapshot.
+    ///
+    /// Returns the IDs of the requests in the order they were sent.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `unbounded_send` fails or [`ApiError::ShuttingDown`] if
+    /// [`Controller::shutdown`] has been called, requests sent before the error are not reported.
+    pub async fn resync(&mut self) -> Result<Vec<u64>> {
+        // Subscribed before sending so no reply is missed.
+        let data_rx = self.data_tx.subscribe();
+        let mut ids = vec![];
+        let mut books = vec![];
+
+        #[cfg(feature = "websocket")]
+        {
+            let user: [ActionKind; 2] = [
+                GetOpenOrders::default().into(),
+                GetAccountSummary { currency: None }.into(),
+            ];
+
+            for action in user {
+                ids.extend(self.queue_action(Api::User, Arc::new(action)).await?);
+            }
+        }
+
+        let book_subscriptions = self.subscriptions.lock().await.books();
+
+        for subscribe in book_subscriptions {
+            let channel = subscribe.channels[0].clone();
+
+            if let Some(id) = self
+                .queue_action(Api::Market, Arc::new(subscribe.into()))
+                .await?
+            {
+                ids.push(id);
+                books.push((id, channel));
+            }
+        }
+
+        trace::info!("Resyncing with {} requests.", ids.len());
+
+        tokio::spawn(collect_resync(
+            data_rx,
+            self.data_tx.clone(),
+            ResyncTracker::new(ids.clone(), books),
+        ));
+
+        Ok(ids)
+    }
+
+    /// Follow the fills of `order`, see [`FillTracker`].
+    ///
+    /// Subscribe to `user.trade` and `user.order` so the fills and the order status are recieved.
+    /// Only data sent after this is called is seen, call it before creating the order.
+    pub fn watch_fills(&mut self, order: OrderRef) -> FillStream {
+        FillStream::new(self.subscribe_data(), FillTracker::new(order))
+    }
+
+    /// Follow the spreads of the instruments of `tracker`, see [`SpreadTracker`].
+    ///
+    /// Subscribe to [`SpreadTracker::subscription`] so the quotes are recieved.
+    pub fn watch_spread(&mut self, tracker: SpreadTracker) -> SpreadStream {
+        SpreadStream::new(self.subscribe_data(), tracker)
+    }
+
+    /// Follow the `instrument_name` candles of `interval`, with the candles missed while the
+    /// market websocket was disconnected fetched from `public/get-candlestick`, see
+    /// [`CandleStream`].
+    ///
+    /// Subscribe to [`CandleStream::subscription`] so the candles are recieved.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::ConfigMissing`] if `rest_url` is not set in the config.
+    #[cfg(feature = "rest")]
+    pub fn watch_candles(
+        &mut self,
+        instrument_name: &str,
+        interval: Interval,
+    ) -> Result<CandleStream<Config>> {
+        let Some(ref rest_url) = self.config.rest_url else {
+            return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+        };
+
+        let config = Config {
+            rest_url: Some(rest_url.clone()),
+            ..Config::default()
+        };
+
+        Ok(CandleStream::new(
+            self.subscribe_data(),
+            config,
+            instrument_name,
+            interval,
+        ))
+    }
+
+    /// Call `callback` with every `ticker` update, see [`Callbacks`].
+    pub fn on_ticker(
+        &mut self,
+        callback: impl FnMut(&data::TickerRes) + Send + 'static,
+    ) -> &mut Self {
+        self.register(|callbacks| callbacks.on_ticker(callback))
+    }
+
+    /// Call `callback` with every `book` snapshot, see [`Callbacks`].
+    pub fn on_book(&mut self, callback: impl FnMut(&data::BookRes) + Send + 'static) -> &mut Self {
+        self.register(|callbacks| callbacks.on_book(callback))
+    }
+
+    /// Call `callback` with every `book.update` delta, see [`Callbacks`].
+    pub fn on_book_update(
+        &mut self,
+        callback: impl FnMut(&data::BookUpdateRes) + Send + 'static,
+    ) -> &mut Self {
+        self.register(|callbacks| callbacks.on_book_update(callback))
+    }
+
+    /// Call `callback` with every `trade` update, see [`Callbacks`].
+    pub fn on_trade(
+        &mut self,
+        callback: impl FnMut(&data::TradeRes) + Send + 'static,
+    ) -> &mut Self {
+        self.register(|callbacks| callbacks.on_trade(callback))
+    }
+
+    /// Call `callback` with every `candlestick` update, see [`Callbacks`].
+    pub fn on_candlestick(
+        &mut self,
+        callback: impl FnMut(&data::CandlestickRes) + Send + 'static,
+    ) -> &mut Self {
+        self.register(|callbacks| callbacks.on_candlestick(callback))
+    }
+
+    /// Call `callback` with every `user.order` update, see [`Callbacks`].
+    pub fn on_user_order(
+        &mut self,
+        callback: impl FnMut(&data::UserOrderRes) + Send + 'static,
+    ) -> &mut Self {
+        self.register(|callbacks| callbacks.on_user_order(callback))
+    }
+
+    /// Call `callback` with every `user.trade` update, see [`Callbacks`].
+    pub fn on_user_trade(
+        &mut self,
+        callback: impl FnMut(&data::UserTradeRes) + Send + 'static,
+    ) -> &mut Self {
+        self.register(|callbacks| callbacks.on_user_trade(callback))
+    }
+
+    /// Call `callback` with every `user.balance` update, see [`Callbacks`].
+    pub fn on_user_balance(
+        &mut self,
+        callback: impl FnMut(&[data::UserBalance]) + Send + 'static,
+    ) -> &mut Self {
+        self.register(|callbacks| callbacks.on_user_balance(callback))
+    }
+
+    /// Add a callback with `add`, starting the listener that calls them if it is not running.
+    ///
+    /// Must be called inside a tokio runtime.
+    fn register(&mut self, add: impl FnOnce(&mut Callbacks)) -> &mut Self {
+        add(&mut self
+            .callbacks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner));
+
+        if !self
+            .callback_listener
+            .as_ref()
+            .is_some_and(ListenerHandle::is_alive)
+        {
+            let callbacks = Arc::clone(&self.callbacks);
+
+            self.callback_listener = Some(self.listen(move |data| {
+                if let Some(ref data) = data.result {
+                    callbacks
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .dispatch(data);
+                }
+
+                Ok(false)
+            }));
+        }
+
+        self
+    }
+
+    /// Subscribe to the data broadcast, every subscriber recieves every message.
+    ///
+    /// The first subscriber also recieves the data sent while the controller was being built,
+    /// later subscribers only recieve data sent after they subscribed.
+    pub fn subscribe_data(&mut self) -> DataReciever {
+        self.data_rx
+            .take()
+            .unwrap_or_else(|| self.data_tx.subscribe())
+    }
+
+    /// Shut the websockets down, giving requests that are still in flight a chance to finish.
+    ///
+    /// New actions are refused as soon as this is called. Responses to requests that were already
+    /// sent keep flowing to the listener for up to `drain_timeout`, after which a close frame is
+    /// sent on each websocket and the action channels are closed.
+    ///
+    /// Returns the IDs of the requests that were still unanswered, their outcome is unknown.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `unbounded_send` fails.
+    pub async fn shutdown(&mut self, drain_timeout: Duration) -> Result<Vec<u64>> {
+        self.shutting_down.store(true, Ordering::SeqCst);
+
+        let drained = async {
+            self.in_flight.drained().await;
+
+            for account in self.accounts.values() {
+                account.in_flight.drained().await;
+            }
+        };
+
+        if tokio::time::timeout(drain_timeout, drained).await.is_err() {
+            trace::warning!("Shutdown drain timed out with requests still in flight.");
+        }
+
+        let mut unanswered = self.in_flight.ids().await;
+
+        for (tx, actions_tx) in [
+            (&self.market_tx, &self.market_actions_tx),
+            (&self.user_tx, &self.user_actions_tx),
+        ] {
+            if let (Some(tx), Some(actions_tx)) = (tx, actions_tx) {
+                close_connection(tx, actions_tx).await?;
+            }
+        }
+
+        for (_, account) in self.accounts.drain() {
+            close_connection(&account.user_tx, &account.actions_tx).await?;
+            unanswered.extend(account.in_flight.ids().await);
+        }
+
+        unanswered.sort_unstable();
+
+        Ok(unanswered)
+    }
+
+    /// Poll `public/get-announcements` every `interval` and send a [`WebsocketData::SystemStatus`]
+    /// whenever a maintenance window is scheduled or ongoing.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::ConfigMissing`] if `rest_url` is not set in the config.
+    #[cfg(feature = "rest")]
+    pub fn watch_system_status(&self, interval: Duration) -> Result<JoinHandle<Result<()>>> {
+        let Some(ref rest_url) = self.config.rest_url else {
+            return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+        };
+
+        let config = Config {
+            rest_url: Some(rest_url.clone()),
+            ..Config::default()
+        };
+        let params = GetAnnouncementsParams {
+            category: Some(AnnouncementCategory::System),
+            ..GetAnnouncementsParams::default()
+        };
+        let data_tx = self.data_tx.clone();
+
+        Ok(tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+
+            loop {
+                ticks.tick().await;
+
+                let announcements = match public::get_announcements(&config, &params).await {
+                    Ok(ApiResponse {
+                        result: Some(announcements),
+                        ..
+                    }) => announcements.data,
+                    Ok(res) => {
+                        trace::warning!("System status check had no result. {res:#?}");
+                        continue;
+                    }
+                    Err(err) => {
+                        trace::warning!("System status check failed: {err:#}");
+                        continue;
+                    }
+                };
+
+                let status = SystemStatus::new(&announcements, get_epoch_ms());
+
+                if !status.maintenance.is_empty() {
+                    send_data(
+                        &data_tx,
+                        ApiResponse::<WebsocketData>::default()
+                            .websocket_data(WebsocketData::SystemStatus(status)),
+                    );
+                }
+            }
+        }))
+    }
+
+    /// Create a data listener, any number of listeners can be created.
+    ///
+    /// In order to use this function you must pass a lambda that returns `Ok(false)` to continue
+    /// processing data, or `Ok(true)` to break the loop and stop processing data.
+    ///
+    /// The listener also stops when a supervised task finishes, returning its error if it failed,
+    /// or when [`ListenerHandle::stop`] is called.
+    pub fn listen<F>(&mut self, mut async_fn: F) -> ListenerHandle
+    where
+        F: FnMut(DataEvent) -> anyhow::Result<bool> + Send + 'static,
+    {
+        let mut data_rx = self.subscribe_data();
+        let mut exits = self.supervisor.exits();
+
+        ListenerHandle::spawn(|stop| async move {
+            loop {
+                tokio::select! {
+                    biased;
+
+                    () = stop.notified() => return Ok(()),
+                    data = data_rx.recv() => match data {
+                        Ok(data) => {
+                            if async_fn(data)? {
+                                return Ok(());
+                            }
+                        }
+                        Err(RecvError::Lagged(skipped)) => {
+                            trace::warning!("Listener lagged behind, {skipped} messages were skipped.");
+                        }
+                        Err(RecvError::Closed) => return Ok(()),
+                    },
+                    exit = exits.wait_for(Option::is_some) => {
+                        let exit = exit.map(|exit| exit.clone());
+
+                        return match exit {
+                            Ok(Some(exit)) => exit.into_result(),
+                            _ => Ok(()),
+                        };
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// Take the data of `reply` to a `method` request with `take`.
+#[cfg(feature = "websocket")]
+fn reply_data<T>(
+    reply: &DataEvent,
+    method: &str,
+    take: impl FnOnce(&WebsocketData) -> Option<T>,
+) -> Result<T> {
+    reply
+        .result
+        .as_ref()
+        .and_then(take)
+        .ok_or_else(|| ApiError::UnexpectedReply {
+            id: u64::try_from(reply.id).unwrap_or_default(),
+            method: method.to_owned(),
+        })
+}
+
+/// `raw_hook` preceded by counting each frame in `message_stats`, if set.
+fn stream_hook(
+    message_stats: Option<&MessageStats>,
+    raw_hook: Option<&RawMessageHook>,
+) -> Option<RawMessageHook> {
+    match message_stats {
+        Some(stats) => Some(stats.hook(raw_hook.cloned())),
+        None => raw_hook.cloned(),
+    }
+}
+
+/// Send a close frame on `tx` and close `actions_tx`.
+async fn close_connection(tx: &MessageSender, actions_tx: &ActionStoreSender) -> Result<()> {
+    {
+        let tx = tx.lock().await;
+
+        // The connection may already be gone, in which case there is nothing to close.
+        if !tx.is_closed() {
+            tx.unbounded_send(Message::Close(None))?;
+        }
+    }
+
+    actions_tx.lock().await.close_channel();
+
+    Ok(())
 }
@@ -0,0 +1,113 @@
+//! Cancel all orders when the application stops checking in.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use tokio::task::JoinHandle;
+
+use crate::utils::action_queue::ActionQueue;
+use crate::utils::trace;
+use crate::websocket::actions::spot_trading_api::CancelAllOrders;
+use crate::websocket::actions::SetCancelOnDisconnect;
+
+/// Cancels all orders in a set of instruments if [`DeadMansSwitch::feed`] is not called within
+/// the timeout.
+///
+/// Arming the switch also sets cancel on disconnect for the connection, so orders are cancelled by
+/// the exchange if the websocket drops and by the switch if the application hangs.
+///
+/// Dropping the switch does not disarm it, a crashed application still trips it, call
+/// [`DeadMansSwitch::disarm`] to stop it.
+#[derive(Debug)]
+pub struct DeadMansSwitch {
+    /// When the application last checked in.
+    last_fed: Arc<Mutex<Instant>>,
+    /// Set once the orders have been cancelled.
+    tripped: Arc<AtomicBool>,
+    /// The background task watching `last_fed`.
+    handle: JoinHandle<Result<()>>,
+}
+
+impl DeadMansSwitch {
+    /// Arm a switch that cancels all orders in `instrument_names` through `queue` if it is not fed
+    /// within `timeout`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if sending the cancel on disconnect request fails.
+    pub async fn arm(
+        queue: ActionQueue,
+        timeout: Duration,
+        instrument_names: Vec<String>,
+    ) -> Result<Self> {
+        queue
+            .push(SetCancelOnDisconnect {
+                scope: "CONNECTION".to_owned(),
+            })
+            .await?;
+
+        let last_fed = Arc::new(Mutex::new(Instant::now()));
+        let tripped = Arc::new(AtomicBool::new(false));
+
+        let handle = {
+            let last_fed = Arc::clone(&last_fed);
+            let tripped = Arc::clone(&tripped);
+
+            tokio::spawn(async move {
+                // Checking a few times per timeout keeps the delay to a fraction of it.
+                let mut ticks = tokio::time::interval((timeout / 4).max(Duration::from_millis(1)));
+
+                loop {
+                    ticks.tick().await;
+
+                    let elapsed = last_fed
+                        .lock()
+                        .map_or(timeout, |last_fed| last_fed.elapsed());
+
+                    if elapsed < timeout {
+                        continue;
+                    }
+
+                    trace::warning!(
+                        "Dead man's switch was not fed for {elapsed:?}, cancelling all orders."
+                    );
+                    tripped.store(true, Ordering::SeqCst);
+
+                    for instrument_name in instrument_names {
+                        queue.push(CancelAllOrders { instrument_name }).await?;
+                    }
+
+                    return Ok(());
+                }
+            })
+        };
+
+        Ok(Self {
+            last_fed,
+            tripped,
+            handle,
+        })
+    }
+
+    /// Check in, restarting the timeout.
+    pub fn feed(&self) {
+        if let Ok(mut last_fed) = self.last_fed.lock() {
+            *last_fed = Instant::now();
+        }
+    }
+
+    /// Whether the timeout passed and the orders were cancelled.
+    #[must_use]
+    pub fn is_tripped(&self) -> bool {
+        self.tripped.load(Ordering::SeqCst)
+    }
+
+    /// Stop watching without cancelling anything.
+    ///
+    /// Cancel on disconnect stays set for the connection.
+    pub fn disarm(self) {
+        self.handle.abort();
+    }
+}
@@ -1,10 +1,22 @@
 //! Main crate error.
+//!
+//! The public API of the controller, the websocket actions, and the REST functions returns
+//! [`Result`], so failures can be matched on without downcasting. Internally errors are still
+//! carried as [`anyhow::Error`], they convert back with [`ApiError::from`], unwrapping an
+//! [`ApiError`] that was carried inside.
 
 use std::num::{ParseFloatError, ParseIntError};
 
 use anyhow::Error as AnyError;
 
-use crate::{api_response::ApiResponse, websocket::WebsocketData};
+use crate::api_response::ApiResponse;
+use crate::types::{ApiVersion, ExecInst};
+use crate::utils::environment::Environment;
+#[cfg(feature = "websocket-market")]
+use crate::websocket::WebsocketData;
+
+/// Result of the public API, see [`ApiError`].
+pub type Result<T, E = ApiError> = std::result::Result<T, E>;
 
 /// Handles conversion of an anyhow error into a `ApiError` when `process_user` or `process_market`
 /// encounters an error.
@@ -18,6 +30,7 @@ pub fn processing_error(err: AnyError) -> ApiError {
         return ApiError::Utf8Error;
     }
 
+    #[cfg(feature = "websocket-market")]
     if let Some(_err) = err.downcast_ref::<futures_channel::mpsc::TrySendError<WebsocketData>>() {
         return ApiError::WebsocketSend;
     }
@@ -29,9 +42,27 @@ pub fn processing_error(err: AnyError) -> ApiError {
     ApiError::Downcast
 }
 
-/// Handles converting from [`tokio_tungstenite::tungstenite::Error`] to [`ApiError`].
-pub fn convert_tungstenite_error(_error: tokio_tungstenite::tungstenite::Error) -> ApiError {
-    ApiError::Unhandled
+/// Handles converting from [`tungstenite::Error`] to [`ApiError`].
+#[must_use]
+pub fn convert_tungstenite_error(error: tungstenite::Error) -> ApiError {
+    use tungstenite::Error as WsError;
+
+    match error {
+        WsError::ConnectionClosed | WsError::AlreadyClosed => ApiError::ConnectionClosed {
+            code: None,
+            reason: error.to_string(),
+        },
+        WsError::Io(err) => ApiError::Io(err.to_string()),
+        WsError::Tls(err) => ApiError::Tls(err.to_string()),
+        WsError::Capacity(err) => ApiError::Capacity(err.to_string()),
+        WsError::WriteBufferFull(_) => ApiError::Capacity(error.to_string()),
+        WsError::Utf8 => ApiError::Utf8Error,
+        WsError::Protocol(_)
+        | WsError::AttackAttempt
+        | WsError::Url(_)
+        | WsError::Http(_)
+        | WsError::HttpFormat(_) => ApiError::Protocol(error.to_string()),
+    }
 }
 
 /// Main error enum.
@@ -63,13 +94,271 @@ pub enum ApiError {
     Unhandled,
     /// A subscription that we are not handling.
     #[error("unsupported subscription `{0:#?}`")]
-    UnsupportedSubscription(ApiResponse<serde_json::Value>),
+    UnsupportedSubscription(Box<ApiResponse<serde_json::Value>>),
     /// A method that we are not handling.
     #[error("unsupported method `{0:#?}`")]
-    UnsupportedMethod(ApiResponse<serde_json::Value>),
+    UnsupportedMethod(Box<ApiResponse<serde_json::Value>>),
+    /// The websocket was closed, by a close frame from crypto.com or after the close handshake.
+    #[error("connection closed with code `{code:?}`: {reason}")]
+    ConnectionClosed {
+        /// [Close code](https://www.rfc-editor.org/rfc/rfc6455#section-7.4), `None` if there was
+        /// no close frame or it had no body.
+        code: Option<u16>,
+        /// Reason given with the close code.
+        reason: String,
+    },
+    /// Reading from or writing to the websocket failed.
+    #[error("websocket io error: {0}")]
+    Io(String),
+    /// The websocket or its handshake broke the protocol, e.g. a reset without a close frame.
+    #[error("websocket protocol error: {0}")]
+    Protocol(String),
+    /// A message or the send buffer was too large.
+    #[error("websocket capacity exceeded: {0}")]
+    Capacity(String),
+    /// The TLS connection failed.
+    #[error("websocket tls error: {0}")]
+    Tls(String),
     /// Missing a method in the config file.
     #[error("config missing `{0}`")]
     ConfigMissing(String),
+    /// A header in [`crate::utils::config::Config::headers`] or the user agent is not valid.
+    #[error("invalid header `{0}`")]
+    InvalidHeader(String),
+    /// No credentials with this name in [`crate::utils::config::Config::keyring`].
+    #[error("no credentials named `{0}`")]
+    UnknownCredentials(String),
+    /// An action that changes orders or funds and is not simulated was pushed in dry run mode.
+    #[error("`{0}` is not simulated in dry run mode")]
+    DryRun(String),
+    /// An action that changes orders, funds, or account settings was sent while the controller
+    /// is read-only, see [`crate::utils::action::Action::mutates`].
+    #[error("`{0}` is refused in read-only mode")]
+    ReadOnly(String),
+    /// A withdrawal was sent in an environment that does not allow them, see
+    /// [`crate::utils::environment::Environment::allows_withdrawals`].
+    #[error("withdrawals are refused in `{0:?}`")]
+    WithdrawalRefused(Environment),
+    /// The controller is shutting down and no longer accepts actions.
+    #[error("controller is shutting down")]
+    ShuttingDown,
+    /// A private action was sent on the market websocket, which crypto.com would reject.
+    #[error("`{0}` is private and cannot be sent on the market websocket")]
+    PrivateOnMarket(String),
+    /// A private action was sent before the user websocket was authorized, or after the
+    /// authorization was refused.
+    #[error("`{0}` needs the user websocket to be authorized")]
+    NotAuthenticated(String),
+    /// A candlestick interval that is not supported.
+    #[error("invalid interval `{0}`")]
+    InvalidInterval(String),
+    /// A book depth outside of 1 to 50.
+    #[error("invalid depth `{0}`, must be 1 to 50")]
+    InvalidDepth(u8),
+    /// A page size outside of 1 to 200.
+    #[error("invalid page size `{0}`, must be 1 to 200")]
+    InvalidPageSize(u64),
+    /// A time range that ends before it starts.
+    #[error("invalid time range, `{start_ts}` is after `{end_ts}`")]
+    InvalidTimeRange {
+        /// Start timestamp in ms since the UNIX epoch.
+        start_ts: u64,
+        /// End timestamp in ms since the UNIX epoch.
+        end_ts: u64,
+    },
+    /// A withdrawal failed validation before being sent.
+    #[error(transparent)]
+    InvalidWithdrawal(#[from] WithdrawalError),
+    /// An order failed validation before being sent.
+    #[error(transparent)]
+    InvalidOrder(#[from] OrderError),
+    /// An order was rejected by the [`crate::risk::RiskManager`].
+    #[error(transparent)]
+    RiskViolation(#[from] RiskViolation),
+    /// A `book.update` does not follow the last update applied to the local book.
+    #[error("book update follows `{found}` but the local book is at `{expected:?}`")]
+    BookOutOfSync {
+        /// Sequence of the local book, `None` before the first snapshot.
+        expected: Option<u64>,
+        /// Previous sequence carried by the update.
+        found: u64,
+    },
+    /// A request timed out, was dropped from the send queue, or the controller closed before it
+    /// was answered.
+    #[error("request `{0}` was not answered")]
+    Unanswered(u64),
+    /// A request was answered with data of another method.
+    #[error("request `{id}` was answered with data other than `{method}`")]
+    UnexpectedReply {
+        /// ID of the request.
+        id: u64,
+        /// Method of the request.
+        method: String,
+    },
+    /// crypto.com answered a request with a non-zero response code.
+    #[error("request failed with code `{code}`: {message:?}")]
+    RequestFailed {
+        /// [Response code](https://exchange-docs.crypto.com/spot/index.html#response-and-reason-codes).
+        code: u64,
+        /// Message sent with the code, if any.
+        message: Option<String>,
+    },
+    /// A REST request could not be sent or its response could not be read.
+    #[cfg(feature = "rest")]
+    #[error("http error: {0}")]
+    Http(#[from] reqwest::Error),
+    /// Any other error, e.g. from a [`crate::websocket::handler::MessageHandler`] or a callback.
+    #[error(transparent)]
+    Other(anyhow::Error),
+}
+
+/// Reasons a withdrawal is rejected by [`crate::rest::data::CurrencyNetworks::validate_withdrawal`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum WithdrawalError {
+    /// The currency is not in the currency networks.
+    #[error("unknown currency `{0}`")]
+    UnknownCurrency(String),
+    /// The network does not exist for the currency, or no network was given and there is no
+    /// default.
+    #[error("unknown network `{network_id}` for `{currency}`")]
+    UnknownNetwork {
+        /// e.g. BTC, CRO.
+        currency: String,
+        /// The network that was requested.
+        network_id: String,
+    },
+    /// Withdrawals are disabled on the network.
+    #[error("withdrawals of `{currency}` are disabled on `{network_id}`")]
+    WithdrawalDisabled {
+        /// e.g. BTC, CRO.
+        currency: String,
+        /// The network that was selected.
+        network_id: String,
+    },
+    /// The amount is below the network's minimum withdrawal amount.
+    #[error("amount {amount} is below the minimum withdrawal amount {min_withdrawal_amount}")]
+    BelowMinimum {
+        /// Requested amount.
+        amount: f64,
+        /// Network minimum.
+        min_withdrawal_amount: f64,
+    },
+}
+
+/// Reasons an order is rejected by [`crate::rest::data::InstrumentRules`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum OrderError {
+    /// The instrument is not in the rules.
+    #[error("unknown instrument `{0}`")]
+    UnknownInstrument(String),
+    /// The order is not open.
+    #[error("unknown order `{0}`")]
+    UnknownOrder(String),
+    /// The price is not a multiple of the price tick size.
+    #[error("price {price} is not a multiple of the tick size {price_tick_size}")]
+    PriceNotOnTick {
+        /// Requested price.
+        price: f64,
+        /// Instrument price tick size.
+        price_tick_size: f64,
+    },
+    /// The quantity is not a multiple of the quantity tick size.
+    #[error("quantity {quantity} is not a multiple of the tick size {quantity_tick_size}")]
+    QuantityNotOnTick {
+        /// Requested quantity.
+        quantity: f64,
+        /// Instrument quantity tick size.
+        quantity_tick_size: f64,
+    },
+    /// The price is outside of the instrument's price range.
+    #[error("price {price} is outside of {min_price}..={max_price}")]
+    PriceOutOfRange {
+        /// Requested price.
+        price: f64,
+        /// Instrument minimum price.
+        min_price: f64,
+        /// Instrument maximum price.
+        max_price: f64,
+    },
+    /// The quantity is outside of the instrument's quantity range.
+    #[error("quantity {quantity} is outside of {min_quantity}..={max_quantity}")]
+    QuantityOutOfRange {
+        /// Requested quantity.
+        quantity: f64,
+        /// Instrument minimum quantity.
+        min_quantity: f64,
+        /// Instrument maximum quantity.
+        max_quantity: f64,
+    },
+    /// Execution instructions were set on an order that does not rest at a price.
+    #[error("execution instructions are only allowed on limit orders, not `{0}`")]
+    ExecInstNotAllowed(String),
+    /// The API version the order is sent to does not accept the execution instruction.
+    #[error("execution instruction `{exec_inst}` is not supported by {version:?}")]
+    UnsupportedExecInst {
+        /// Requested instruction.
+        exec_inst: ExecInst,
+        /// Version the order is sent to.
+        version: ApiVersion,
+    },
+}
+
+/// Limits an order would break, see [`crate::risk::RiskLimits`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum RiskViolation {
+    /// The kill switch is set, no orders are sent.
+    #[error("kill switch is set")]
+    KillSwitch,
+    /// The order is worth more than the maximum order notional.
+    #[error("order notional {notional} is above the maximum {max}")]
+    OrderNotional {
+        /// Notional of the order.
+        notional: f64,
+        /// Maximum order notional.
+        max: f64,
+    },
+    /// Sending the orders would leave more orders open than allowed.
+    #[error("{open} open orders is above the maximum {max}")]
+    OpenOrders {
+        /// Open orders including the ones being sent.
+        open: usize,
+        /// Maximum open orders.
+        max: usize,
+    },
+    /// The order is for an instrument crypto.com does not list, see
+    /// [`crate::risk::RiskManager::with_instrument_cache`].
+    #[error("unknown instrument `{0}`")]
+    UnknownInstrument(String),
+    /// Filling the orders would make the position larger than allowed.
+    #[error("position {position} in `{instrument_name}` is above the maximum {max}")]
+    Position {
+        /// e.g. BTC_USDT.
+        instrument_name: String,
+        /// Position if the orders were filled.
+        position: f64,
+        /// Maximum position size.
+        max: f64,
+    },
+    /// A market order had to be priced for a limit but no trade or ticker of its instrument has
+    /// been seen, see [`crate::risk::RiskManager::last_price`].
+    #[error("no last price for `{0}` to price the market order")]
+    Unpriced(String),
+}
+
+impl From<AnyError> for ApiError {
+    /// The [`ApiError`] carried by `value`, or [`ApiError::Other`].
+    fn from(value: AnyError) -> Self {
+        match value.downcast::<Self>() {
+            Ok(err) => err,
+            Err(err) => Self::Other(err),
+        }
+    }
+}
+
+impl<T> From<futures_channel::mpsc::TrySendError<T>> for ApiError {
+    fn from(_value: futures_channel::mpsc::TrySendError<T>) -> Self {
+        Self::WebsocketSend
+    }
 }
 
 impl From<ParseFloatError> for ApiError {
@@ -0,0 +1,340 @@
+//! Fills of a single order, assembled from `user.trade` and `user.order`.
+//!
+//! Create a [`FillStream`] with [`crate::controller::Controller::watch_fills`], or feed a
+//! [`FillTracker`] yourself.
+
+use std::collections::HashSet;
+
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::prelude::DataReciever;
+use crate::utils::trace;
+use crate::websocket::data::{OrderItem, UserTrade};
+use crate::websocket::WebsocketData;
+
+/// Most trades kept while the order ID of a [`OrderRef::ClientOid`] is not known yet.
+const MAX_PENDING_TRADES: usize = 256;
+
+/// Leeway when comparing quantities, which are summed as floats.
+const QUANTITY_EPSILON: f64 = 1e-9;
+
+/// The order to follow.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum OrderRef {
+    /// The order ID given by the exchange.
+    OrderId(u64),
+    /// The client order ID given when creating the order, resolved to the order ID from the
+    /// `private/create-order` answer or `user.order`.
+    ClientOid(String),
+}
+
+/// A trade of the order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrderFill {
+    /// The trade.
+    pub trade: UserTrade,
+    /// Quantity filled so far, including this trade.
+    pub filled_quantity: f64,
+}
+
+/// How the order ended.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FillStatus {
+    /// Its whole quantity was filled.
+    Filled,
+    /// It was closed before it was filled, with the status from `user.order`, e.g. `CANCELED`,
+    /// `REJECTED`, or `EXPIRED`.
+    Closed(String),
+}
+
+/// Every fill of a completed order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FillSummary {
+    /// Order ID.
+    pub order_id: u64,
+    /// How the order ended.
+    pub status: FillStatus,
+    /// Total quantity filled.
+    pub filled_quantity: f64,
+    /// Average price of the fills, `None` without fills.
+    pub average_price: Option<f64>,
+    /// Total fees, in the fee currency of the trades.
+    pub fees: f64,
+}
+
+/// Sent by a [`FillTracker`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum FillEvent {
+    /// The order was filled, partially or fully.
+    Fill(OrderFill),
+    /// The order is complete, no more events follow.
+    Complete(FillSummary),
+}
+
+/// Follows the fills of one order until it is complete.
+///
+/// The order is complete once the quantity filled reaches the order quantity, or once `user.order`
+/// reports it closed and every fill it reports has arrived.
+#[derive(Clone, Debug)]
+pub struct FillTracker {
+    /// The order followed.
+    order: OrderRef,
+    /// Order ID, once known.
+    order_id: Option<u64>,
+    /// Quantity of the order, once known.
+    quantity: Option<f64>,
+    /// Quantity filled once the order is closed, and the status it closed with.
+    closed: Option<(f64, FillStatus)>,
+    /// IDs of the trades already counted.
+    trade_ids: HashSet<u64>,
+    /// Trades recieved before the order ID was known.
+    pending: Vec<UserTrade>,
+    /// Total quantity filled.
+    filled_quantity: f64,
+    /// Total of price times quantity of the fills.
+    filled_value: f64,
+    /// Total fees.
+    fees: f64,
+    /// Set once [`FillEvent::Complete`] is sent.
+    complete: bool,
+}
+
+impl FillTracker {
+    /// Follow `order`.
+    #[must_use]
+    pub fn new(order: OrderRef) -> Self {
+        let order_id = match order {
+            OrderRef::OrderId(order_id) => Some(order_id),
+            OrderRef::ClientOid(_) => None,
+        };
+
+        Self {
+            order,
+            order_id,
+            quantity: None,
+            closed: None,
+            trade_ids: HashSet::new(),
+            pending: vec![],
+            filled_quantity: 0.0,
+            filled_value: 0.0,
+            fees: 0.0,
+            complete: false,
+        }
+    }
+
+    /// Follow `order` of `quantity`, for orders created before `user.order` was subscribed.
+    #[must_use]
+    pub fn with_quantity(mut self, quantity: f64) -> Self {
+        self.quantity = Some(quantity);
+
+        self
+    }
+
+    /// The order ID, `None` until a [`OrderRef::ClientOid`] is resolved.
+    #[must_use]
+    pub const fn order_id(&self) -> Option<u64> {
+        self.order_id
+    }
+
+    /// Total quantity filled.
+    #[must_use]
+    pub const fn filled_quantity(&self) -> f64 {
+        self.filled_quantity
+    }
+
+    /// Whether [`FillEvent::Complete`] was sent.
+    #[must_use]
+    pub const fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// The events for the order in `data`, other data is ignored.
+    pub fn process(&mut self, data: &WebsocketData) -> Vec<FillEvent> {
+        let mut events = vec![];
+
+        if self.complete {
+            return events;
+        }
+
+        match *data {
+            WebsocketData::CreateOrder(ref order) => {
+                if let OrderRef::ClientOid(ref client_oid) = self.order {
+                    if order.client_oid.as_ref() == Some(client_oid) {
+                        self.resolve(order.order_id, &mut events);
+                    }
+                }
+            }
+            WebsocketData::UserOrder(ref orders) => {
+                for order in &orders.data {
+                    self.apply_order(order, &mut events);
+                }
+            }
+            WebsocketData::UserTrade(ref trades) => {
+                for trade in &trades.data {
+                    match self.order_id {
+                        Some(order_id) if trade.order_id == order_id => {
+                            self.apply_trade(trade.clone(), &mut events);
+                        }
+                        Some(_) => {}
+                        None => {
+                            if self.pending.len() == MAX_PENDING_TRADES {
+                                self.pending.remove(0);
+                            }
+
+                            self.pending.push(trade.clone());
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        self.check_complete(&mut events);
+
+        events
+    }
+
+    /// Set the order ID and count the pending trades of the order.
+    fn resolve(&mut self, order_id: u64, events: &mut Vec<FillEvent>) {
+        if self.order_id.is_some() {
+            return;
+        }
+
+        self.order_id = Some(order_id);
+
+        for trade in std::mem::take(&mut self.pending) {
+            if trade.order_id == order_id {
+                self.apply_trade(trade, events);
+            }
+        }
+    }
+
+    /// Update the order quantity and status from `order` if it is the order followed.
+    fn apply_order(&mut self, order: &OrderItem, events: &mut Vec<FillEvent>) {
+        let Ok(order_id) = order.order_id.parse::<u64>() else {
+            return;
+        };
+
+        let is_order = match self.order {
+            OrderRef::OrderId(id) => id == order_id,
+            OrderRef::ClientOid(ref client_oid) => *client_oid == order.client_oid,
+        };
+
+        if !is_order {
+            return;
+        }
+
+        self.resolve(order_id, events);
+        self.quantity = Some(order.quantity);
+
+        match order.status.as_str() {
+            "FILLED" => {
+                self.closed = Some((order.cumulative_quantity, FillStatus::Filled));
+            }
+            "CANCELED" | "REJECTED" | "EXPIRED" => {
+                self.closed = Some((
+                    order.cumulative_quantity,
+                    FillStatus::Closed(order.status.clone()),
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    /// Count `trade` unless it was already counted.
+    fn apply_trade(&mut self, trade: UserTrade, events: &mut Vec<FillEvent>) {
+        if !self.trade_ids.insert(trade.trade_id) {
+            return;
+        }
+
+        self.filled_quantity += trade.traded_quantity;
+        self.filled_value += trade.traded_quantity * trade.traded_price;
+        self.fees += trade.fee;
+
+        events.push(FillEvent::Fill(OrderFill {
+            trade,
+            filled_quantity: self.filled_quantity,
+        }));
+    }
+
+    /// Send [`FillEvent::Complete`] if the order is complete.
+    fn check_complete(&mut self, events: &mut Vec<FillEvent>) {
+        let Some(order_id) = self.order_id else {
+            return;
+        };
+
+        let filled = |quantity: f64| self.filled_quantity + QUANTITY_EPSILON >= quantity;
+
+        let status = match (&self.closed, self.quantity) {
+            (&Some((cumulative, ref status)), _) if filled(cumulative) => status.clone(),
+            (_, Some(quantity)) if filled(quantity) => FillStatus::Filled,
+            _ => return,
+        };
+
+        self.complete = true;
+
+        events.push(FillEvent::Complete(FillSummary {
+            order_id,
+            status,
+            filled_quantity: self.filled_quantity,
+            average_price: (self.filled_quantity > 0.0)
+                .then(|| self.filled_value / self.filled_quantity),
+            fees: self.fees,
+        }));
+    }
+}
+
+/// The [`FillEvent`]s of one order, see [`crate::controller::Controller::watch_fills`].
+#[derive(Debug)]
+pub struct FillStream {
+    /// Data broadcast reciever.
+    data_rx: DataReciever,
+    /// Follows the order.
+    tracker: FillTracker,
+    /// Events not yet returned, oldest last.
+    queued: Vec<FillEvent>,
+}
+
+impl FillStream {
+    /// Follow the order of `tracker` with the data from `data_rx`.
+    #[must_use]
+    pub const fn new(data_rx: DataReciever, tracker: FillTracker) -> Self {
+        Self {
+            data_rx,
+            tracker,
+            queued: vec![],
+        }
+    }
+
+    /// The tracker following the order.
+    #[must_use]
+    pub const fn tracker(&self) -> &FillTracker {
+        &self.tracker
+    }
+
+    /// The next event, `None` after [`FillEvent::Complete`] or once the data broadcast closes.
+    pub async fn next(&mut self) -> Option<FillEvent> {
+        loop {
+            if let Some(event) = self.queued.pop() {
+                return Some(event);
+            }
+
+            if self.tracker.is_complete() {
+                return None;
+            }
+
+            match self.data_rx.recv().await {
+                Ok(data) => {
+                    if let Some(ref data) = data.result {
+                        self.queued = self.tracker.process(data);
+                        self.queued.reverse();
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    trace::warning!("Fill stream lagged behind, {skipped} messages were skipped.");
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
@@ -0,0 +1,320 @@
+//! Downloading candlestick history over long time ranges, and trade history with
+//! [`TradeDownloader`].
+//!
+//! `public/get-candlestick` returns at most 300 candles per request, [`CandleDownloader`] splits a
+//! range into windows that fit in one request, stitches the pages together without gaps or
+//! repeats, and writes them to a [`CandleSink`] as it goes.
+//!
+//! ```no_run
+//! # async fn example(config: crypto_com_api::utils::config::Config) -> anyhow::Result<()> {
+//! use crypto_com_api::history::{CandleDownloader, CsvSink};
+//! use crypto_com_api::types::Interval;
+//!
+//! let file = std::fs::File::create("BTC_USDT.csv")?;
+//! let mut sink = CsvSink::new(std::io::BufWriter::new(file));
+//!
+//! CandleDownloader::new("BTC_USDT", Interval::OneHour, 1_672_531_200_000, 1_704_067_200_000)
+//!     .with_progress(|progress| println!("{:.0}%", progress.fraction() * 100.0))
+//!     .download(&config, &mut sink)
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+
+use anyhow::Result;
+
+use crate::prelude::ApiError;
+use crate::rest::data::{Candlestick, CandlestickParams};
+use crate::rest::public::get_candlestick_range;
+use crate::types::{EpochMs, Interval};
+use crate::utils::config::Config;
+use crate::utils::trace;
+
+#[cfg(feature = "websocket-market")]
+pub mod resume;
+pub mod sink;
+pub mod trades;
+
+#[cfg(feature = "websocket-market")]
+pub use resume::{CandleEvent, CandleStream};
+pub use sink::{CandleSink, CsvSink};
+pub use trades::{TradeDownloader, TradeProgress, TradeSink, TradeSource};
+
+/// Response code crypto.com sends when the rate limit has been hit.
+pub const TOO_MANY_REQUESTS: u64 = 42901;
+
+/// Most candles `public/get-candlestick` returns in one request.
+pub const MAX_PAGE_SIZE: u64 = 300;
+
+/// Somewhere to fetch pages of candles from.
+///
+/// Implemented for [`Config`] to fetch from the REST API, implement it for anything else to
+/// download from a cache or a test fixture instead.
+pub trait CandleSource {
+    /// Fetch the candles described by `params`, in any order.
+    ///
+    /// Return [`ApiError::RequestFailed`] with [`TOO_MANY_REQUESTS`] to have the request retried
+    /// after a back off.
+    fn fetch(
+        &self,
+        params: &CandlestickParams,
+    ) -> impl Future<Output = Result<Vec<Candlestick>>> + Send;
+}
+
+impl CandleSource for Config {
+    async fn fetch(&self, params: &CandlestickParams) -> Result<Vec<Candlestick>> {
+        let res = get_candlestick_range(self, params).await?;
+
+        if let Some(code) = res.code.filter(|code| *code != 0) {
+            anyhow::bail!(ApiError::RequestFailed {
+                code,
+                message: res.message,
+            });
+        }
+
+        Ok(res.result.map(|res| res.data).unwrap_or_default())
+    }
+}
+
+/// How far a download has got.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// Requests made so far, including retries.
+    pub requests: u64,
+    /// Candles recieved so far, including any that were dropped as repeats.
+    pub fetched: u64,
+    /// Candles written to the sink so far.
+    pub written: u64,
+    /// Start of the range in ms since the UNIX epoch.
+    pub start_ts: u64,
+    /// Everything before this has been downloaded, in ms since the UNIX epoch.
+    pub cursor: u64,
+    /// End of the range in ms since the UNIX epoch.
+    pub end_ts: u64,
+}
+
+impl Progress {
+    /// How much of the range has been downloaded, from `0.0` to `1.0`.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn fraction(&self) -> f64 {
+        let total = self.end_ts.saturating_sub(self.start_ts);
+
+        if total == 0 {
+            return 1.0;
+        }
+
+        self.cursor.saturating_sub(self.start_ts) as f64 / total as f64
+    }
+}
+
+/// Called with the [`Progress`] of a download after each page is written.
+pub type ProgressCallback = Box<dyn FnMut(&Progress) + Send>;
+
+/// Pages through the candles of one instrument and interval between two times.
+///
+/// The range is `start_ts` inclusive to `end_ts` exclusive, both in ms since the UNIX epoch.
+pub struct CandleDownloader {
+    /// e.g. ETH_CRO, BTC_USDT.
+    instrument_name: String,
+    /// [`Interval`]
+    interval: Interval,
+    /// Start of the range.
+    start_ts: u64,
+    /// End of the range.
+    end_ts: u64,
+    /// Candles asked for per request.
+    page_size: u64,
+    /// Least time between the start of two requests.
+    min_request_interval: Duration,
+    /// Times a rate limited request is retried before giving up.
+    max_retries: u32,
+    /// Called after each page is written.
+    on_progress: Option<ProgressCallback>,
+}
+
+impl fmt::Debug for CandleDownloader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CandleDownloader")
+            .field("instrument_name", &self.instrument_name)
+            .field("interval", &self.interval)
+            .field("start_ts", &self.start_ts)
+            .field("end_ts", &self.end_ts)
+            .field("page_size", &self.page_size)
+            .field("min_request_interval", &self.min_request_interval)
+            .field("max_retries", &self.max_retries)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CandleDownloader {
+    /// Download `instrument_name` candles of `interval` from `start_ts` up to `end_ts`.
+    ///
+    /// Defaults to full pages of [`MAX_PAGE_SIZE`], 100ms between requests, and 5 retries.
+    pub fn new(instrument_name: &str, interval: Interval, start_ts: u64, end_ts: u64) -> Self {
+        Self {
+            instrument_name: instrument_name.to_owned(),
+            interval,
+            start_ts,
+            end_ts,
+            page_size: MAX_PAGE_SIZE,
+            min_request_interval: Duration::from_millis(100),
+            max_retries: 5,
+            on_progress: None,
+        }
+    }
+
+    /// Ask for `page_size` candles per request, clamped to `1..=MAX_PAGE_SIZE`.
+    #[must_use]
+    pub fn with_page_size(mut self, page_size: u64) -> Self {
+        self.page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+        self
+    }
+
+    /// Wait at least `interval` between the start of two requests.
+    #[must_use]
+    pub const fn with_min_request_interval(mut self, interval: Duration) -> Self {
+        self.min_request_interval = interval;
+        self
+    }
+
+    /// Retry a rate limited request up to `max_retries` times, doubling the wait each time.
+    #[must_use]
+    pub const fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Call `on_progress` after each page is written.
+    #[must_use]
+    pub fn with_progress<F>(mut self, on_progress: F) -> Self
+    where
+        F: FnMut(&Progress) + Send + 'static,
+    {
+        self.on_progress = Some(Box::new(on_progress));
+        self
+    }
+
+    /// Download the range from `source` into `sink`, returning the final [`Progress`].
+    ///
+    /// Candles are written in ascending time order, each time at most once, and
+    /// [`CandleSink::finish`] is called once the whole range has been fetched.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a request fails, a request is still rate limited after the last
+    /// retry, or the sink fails to write.
+    pub async fn download<S, K>(&mut self, source: &S, sink: &mut K) -> Result<Progress>
+    where
+        S: CandleSource + ?Sized,
+        K: CandleSink + ?Sized,
+    {
+        let step = u64::try_from(self.interval.as_duration().as_millis()).unwrap_or(u64::MAX);
+        let window = step.saturating_mul(self.page_size);
+
+        let mut progress = Progress {
+            start_ts: self.start_ts,
+            cursor: self.start_ts,
+            end_ts: self.end_ts,
+            ..Progress::default()
+        };
+        let mut last_written: Option<u64> = None;
+        let mut last_request: Option<tokio::time::Instant> = None;
+
+        while progress.cursor < self.end_ts {
+            let page_end = progress.cursor.saturating_add(window).min(self.end_ts);
+            let params = CandlestickParams {
+                instrument_name: self.instrument_name.clone(),
+                timeframe: self.interval,
+                count: Some(self.page_size),
+                start_ts: Some(progress.cursor),
+                end_ts: Some(page_end),
+            };
+
+            let mut candles = self
+                .fetch_page(source, &params, &mut progress, &mut last_request)
+                .await?;
+            progress.fetched += candles.len() as u64;
+
+            let cursor = progress.cursor;
+            candles.retain(|candle| {
+                let t = candle.t.epoch_ms();
+
+                t >= cursor && t < page_end && last_written.is_none_or(|last| t > last)
+            });
+            candles.sort_by_key(|candle| candle.t);
+            candles.dedup_by_key(|candle| candle.t);
+
+            if let Some(last) = candles.last() {
+                last_written = Some(last.t.epoch_ms());
+                sink.write(&candles)?;
+                progress.written += candles.len() as u64;
+            }
+
+            progress.cursor = page_end;
+
+            if let Some(ref mut on_progress) = self.on_progress {
+                on_progress(&progress);
+            }
+        }
+
+        sink.finish()?;
+
+        Ok(progress)
+    }
+
+    /// Fetch one page, waiting out the request interval and retrying while rate limited.
+    async fn fetch_page<S>(
+        &self,
+        source: &S,
+        params: &CandlestickParams,
+        progress: &mut Progress,
+        last_request: &mut Option<tokio::time::Instant>,
+    ) -> Result<Vec<Candlestick>>
+    where
+        S: CandleSource + ?Sized,
+    {
+        let mut backoff = self.min_request_interval;
+        let mut retries = 0;
+
+        loop {
+            if let Some(at) = *last_request {
+                tokio::time::sleep_until(at + self.min_request_interval).await;
+            }
+
+            *last_request = Some(tokio::time::Instant::now());
+            progress.requests += 1;
+
+            match source.fetch(params).await {
+                Ok(candles) => return Ok(candles),
+                Err(err) if retries < self.max_retries && is_rate_limited(&err) => {
+                    retries += 1;
+                    backoff = backoff.saturating_mul(2);
+
+                    trace::warning!(
+                        "Rate limited fetching {} candles, retry {retries} in {backoff:?}",
+                        params.instrument_name
+                    );
+
+                    tokio::time::sleep(backoff).await;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+/// Whether `err` is crypto.com refusing a request for exceeding the rate limit.
+fn is_rate_limited(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<ApiError>(),
+        Some(&ApiError::RequestFailed {
+            code: TOO_MANY_REQUESTS,
+            ..
+        })
+    )
+}
@@ -0,0 +1,249 @@
+//! Candlesticks of one subscription, carried on across reconnects.
+//!
+//! After the market websocket reconnects the `candlestick` channel only sends new candles. A
+//! [`CandleStream`] remembers the last candle it returned and, with the first live candle after a
+//! disconnect, fetches the candles in between from `public/get-candlestick` so they are returned
+//! in order before it.
+//!
+//! ```no_run
+//! # async fn example(
+//! #     mut controller: crypto_com_api::controller::Controller<
+//! #         crypto_com_api::controller::NoUserWs,
+//! #         crypto_com_api::controller::MarketWs,
+//! #     >,
+//! # ) -> anyhow::Result<()> {
+//! use crypto_com_api::history::CandleEvent;
+//! use crypto_com_api::types::Interval;
+//!
+//! let mut candles = controller.watch_candles("BTC_USDT", Interval::OneMinute)?;
+//! controller.subscribe([candles.subscription()]).await?;
+//!
+//! while let Some(event) = candles.next().await {
+//!     match event {
+//!         CandleEvent::Live(candle) | CandleEvent::Backfilled(candle) => {
+//!             println!("{:?} closed at {}", candle.t, candle.c);
+//!         }
+//!         CandleEvent::BackfillFailed { error, .. } => eprintln!("gap left: {error}"),
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::history::{CandleDownloader, CandleSource};
+use crate::prelude::DataReciever;
+use crate::types::{Candlestick, EpochMs, Interval};
+use crate::utils::in_flight::Api;
+use crate::utils::trace;
+use crate::websocket::data::CandlestickRes;
+use crate::websocket::WebsocketData;
+
+/// Returned by [`CandleStream::next`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum CandleEvent {
+    /// A candle, or an update to the last one, from the subscription.
+    Live(Candlestick),
+    /// A candle missed while disconnected, fetched from `public/get-candlestick`.
+    Backfilled(Candlestick),
+    /// The missed candles could not be fetched, the stream carries on from the live candles.
+    BackfillFailed {
+        /// Start of the gap in ms since the UNIX epoch.
+        start_ts: u64,
+        /// End of the gap in ms since the UNIX epoch, the first live candle.
+        end_ts: u64,
+        /// Why the fetch failed.
+        error: String,
+    },
+}
+
+impl CandleEvent {
+    /// The candle of a [`CandleEvent::Live`] or [`CandleEvent::Backfilled`].
+    #[must_use]
+    pub const fn candle(&self) -> Option<&Candlestick> {
+        match *self {
+            Self::Live(ref candle) | Self::Backfilled(ref candle) => Some(candle),
+            Self::BackfillFailed { .. } => None,
+        }
+    }
+}
+
+/// The candles of `candlestick.{interval}.{instrument_name}`, with the ones missed while the
+/// market websocket was disconnected fetched from `source`.
+///
+/// The gap is fetched from the start of the last candle returned, whose final values may not
+/// have been recieved, up to the first live candle. A receiver that lagged behind the data
+/// broadcast is treated as a disconnect.
+pub struct CandleStream<S> {
+    /// Data broadcast reciever.
+    data_rx: DataReciever,
+    /// Where missed candles are fetched from.
+    source: S,
+    /// e.g. BTC_USDT.
+    instrument_name: String,
+    /// Period of the candles.
+    interval: Interval,
+    /// `candlestick.{interval}.{instrument_name}`, to subscribe to.
+    subscription: String,
+    /// Start of the last candle returned, in ms since the UNIX epoch.
+    last: Option<u64>,
+    /// Set from a disconnect until the next live candle.
+    interrupted: bool,
+    /// Events not yet returned, oldest first.
+    queued: VecDeque<CandleEvent>,
+}
+
+impl<S> fmt::Debug for CandleStream<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CandleStream")
+            .field("subscription", &self.subscription)
+            .field("last", &self.last)
+            .field("interrupted", &self.interrupted)
+            .field("queued", &self.queued.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: CandleSource> CandleStream<S> {
+    /// Follow the `instrument_name` candles of `interval` in `data_rx`, fetching missed ones from
+    /// `source`.
+    #[must_use]
+    pub fn new(
+        data_rx: DataReciever,
+        source: S,
+        instrument_name: &str,
+        interval: Interval,
+    ) -> Self {
+        Self {
+            data_rx,
+            source,
+            instrument_name: instrument_name.to_owned(),
+            interval,
+            subscription: format!(
+                "candlestick.{}.{instrument_name}",
+                interval.as_websocket_str()
+            ),
+            last: None,
+            interrupted: false,
+            queued: VecDeque::new(),
+        }
+    }
+
+    /// The channel to subscribe to, e.g. `candlestick.M1.BTC_USDT`.
+    #[must_use]
+    pub fn subscription(&self) -> &str {
+        &self.subscription
+    }
+
+    /// Start of the last candle returned or queued, in ms since the UNIX epoch.
+    #[must_use]
+    pub const fn last(&self) -> Option<u64> {
+        self.last
+    }
+
+    /// The next event, `None` once the data broadcast closes.
+    pub async fn next(&mut self) -> Option<CandleEvent> {
+        loop {
+            if let Some(event) = self.queued.pop_front() {
+                return Some(event);
+            }
+
+            let data = match self.data_rx.recv().await {
+                Ok(data) => data,
+                Err(RecvError::Lagged(skipped)) => {
+                    trace::warning!(
+                        "Candle stream lagged behind, {skipped} messages were skipped."
+                    );
+                    self.interrupted = true;
+                    continue;
+                }
+                Err(RecvError::Closed) => return None,
+            };
+
+            match data.result {
+                Some(WebsocketData::ConnectionState(ref state))
+                    if state.api() == Api::Market && !state.is_connected() =>
+                {
+                    self.interrupted = true;
+                }
+                Some(WebsocketData::Candlestick(ref res)) if self.is_followed(res) => {
+                    self.process(res).await;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Whether `res` is of the instrument and interval followed, the interval is sent with either
+    /// its websocket or its REST name.
+    fn is_followed(&self, res: &CandlestickRes) -> bool {
+        *res.instrument_name == *self.instrument_name
+            && res.interval.parse::<Interval>().ok() == Some(self.interval)
+    }
+
+    /// Queue the candles of `res`, after the missed ones if there was a disconnect.
+    async fn process(&mut self, res: &CandlestickRes) {
+        let mut candles: Vec<&Candlestick> = res.data.iter().collect();
+        candles.sort_by_key(|candle| candle.t.epoch_ms());
+
+        let Some(first) = candles.first() else {
+            return;
+        };
+
+        if self.interrupted {
+            self.interrupted = false;
+
+            if let Some(last) = self.last {
+                self.backfill(last, first.t.epoch_ms()).await;
+            }
+        }
+
+        for candle in candles {
+            let t = candle.t.epoch_ms();
+
+            if self.last.is_some_and(|last| t < last) {
+                continue;
+            }
+
+            self.last = Some(t);
+            self.queued.push_back(CandleEvent::Live(candle.clone()));
+        }
+    }
+
+    /// Queue the candles from `start_ts` up to `end_ts`.
+    async fn backfill(&mut self, start_ts: u64, end_ts: u64) {
+        if start_ts >= end_ts {
+            return;
+        }
+
+        let mut candles: Vec<Candlestick> = vec![];
+        let downloaded =
+            CandleDownloader::new(&self.instrument_name, self.interval, start_ts, end_ts)
+                .download(&self.source, &mut candles)
+                .await;
+
+        match downloaded {
+            Ok(_) => {
+                if let Some(candle) = candles.last() {
+                    self.last = Some(candle.t.epoch_ms());
+                }
+
+                self.queued
+                    .extend(candles.into_iter().map(CandleEvent::Backfilled));
+            }
+            Err(err) => {
+                trace::warning!("Could not backfill {} candles: {err:#}", self.subscription);
+
+                self.queued.push_back(CandleEvent::BackfillFailed {
+                    start_ts,
+                    end_ts,
+                    error: format!("{err:#}"),
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Destinations for downloaded candles.
+
+use std::io::Write;
+
+use anyhow::Result;
+
+use crate::rest::data::Candlestick;
+use crate::types::EpochMs;
+
+/// Somewhere to write candles as they are downloaded.
+///
+/// Each call to [`CandleSink::write`] is given the next page of candles in ascending time order,
+/// with no candle repeated across calls.
+pub trait CandleSink {
+    /// Write a page of candles.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the candles could not be written, the download is stopped.
+    fn write(&mut self, candles: &[Candlestick]) -> Result<()>;
+
+    /// Called once after the last page has been written.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the sink could not be flushed.
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl CandleSink for Vec<Candlestick> {
+    fn write(&mut self, candles: &[Candlestick]) -> Result<()> {
+        self.extend_from_slice(candles);
+
+        Ok(())
+    }
+}
+
+/// Writes candles as CSV rows of `t,o,h,l,c,v` with a header line, `t` in ms since the UNIX epoch.
+#[derive(Debug)]
+pub struct CsvSink<W: Write> {
+    /// Where the rows are written.
+    writer: W,
+    /// Whether the header line has been written.
+    header_written: bool,
+}
+
+impl<W: Write> CsvSink<W> {
+    /// Write CSV rows to `writer`, wrap files in a [`std::io::BufWriter`].
+    pub const fn new(writer: W) -> Self {
+        Self {
+            writer,
+            header_written: false,
+        }
+    }
+
+    /// The writer the rows were written to.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> CandleSink for CsvSink<W> {
+    fn write(&mut self, candles: &[Candlestick]) -> Result<()> {
+        if !self.header_written {
+            writeln!(self.writer, "t,o,h,l,c,v")?;
+            self.header_written = true;
+        }
+
+        for candle in candles {
+            writeln!(
+                self.writer,
+                "{},{},{},{},{},{}",
+                candle.t.epoch_ms(),
+                candle.o,
+                candle.h,
+                candle.l,
+                candle.c,
+                candle.v
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if !self.header_written {
+            writeln!(self.writer, "t,o,h,l,c,v")?;
+            self.header_written = true;
+        }
+
+        self.writer.flush()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,359 @@
+//! Downloading trade history over long time ranges.
+//!
+//! `public/get-trades` returns at most [`MAX_TRADES_COUNT`] trades per request, newest first.
+//! [`TradeDownloader`] walks backward from the end of a range, asking for the trades before the
+//! oldest one it has seen until it reaches the start.
+
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+
+use anyhow::Result;
+
+use crate::prelude::ApiError;
+use crate::rest::data::{Trade, TradesParams, MAX_TRADES_COUNT};
+use crate::rest::public::get_trades_range;
+use crate::types::EpochMs;
+use crate::utils::config::Config;
+use crate::utils::trace;
+
+use super::is_rate_limited;
+
+/// Somewhere to fetch pages of trades from.
+///
+/// Implemented for [`Config`] to fetch from the REST API, implement it for anything else to
+/// download from a cache or a test fixture instead.
+pub trait TradeSource {
+    /// Fetch the trades described by `params`, in any order.
+    ///
+    /// Return [`ApiError::RequestFailed`] with [`super::TOO_MANY_REQUESTS`] to have the request
+    /// retried after a back off.
+    fn fetch(&self, params: &TradesParams) -> impl Future<Output = Result<Vec<Trade>>> + Send;
+}
+
+impl TradeSource for Config {
+    async fn fetch(&self, params: &TradesParams) -> Result<Vec<Trade>> {
+        let res = get_trades_range(self, params).await?;
+
+        if let Some(code) = res.code.filter(|code| *code != 0) {
+            anyhow::bail!(ApiError::RequestFailed {
+                code,
+                message: res.message,
+            });
+        }
+
+        Ok(res.result.map(|res| res.data).unwrap_or_default())
+    }
+}
+
+/// Somewhere to write trades as they are downloaded.
+///
+/// Each call to [`TradeSink::write`] is given the next page of trades in descending time order,
+/// older than every trade written before, with no trade repeated across calls.
+pub trait TradeSink {
+    /// Write a page of trades.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the trades could not be written, the download is stopped.
+    fn write(&mut self, trades: &[Trade]) -> Result<()>;
+}
+
+impl TradeSink for Vec<Trade> {
+    fn write(&mut self, trades: &[Trade]) -> Result<()> {
+        self.extend_from_slice(trades);
+
+        Ok(())
+    }
+}
+
+impl TradeSink for tokio::sync::mpsc::UnboundedSender<Trade> {
+    /// Send each trade to the reciever, to consume the download as a stream.
+    fn write(&mut self, trades: &[Trade]) -> Result<()> {
+        for trade in trades {
+            self.send(trade.clone())
+                .map_err(|_| anyhow::anyhow!("the trade reciever was dropped"))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// How far a trade download has got.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TradeProgress {
+    /// Requests made so far, including retries.
+    pub requests: u64,
+    /// Trades recieved so far, including any that were dropped as repeats.
+    pub fetched: u64,
+    /// Trades written to the sink so far.
+    pub written: u64,
+    /// Start of the range in ms since the UNIX epoch.
+    pub start_ts: u64,
+    /// Everything from this up to the end has been downloaded, in ms since the UNIX epoch.
+    pub cursor: u64,
+    /// End of the range in ms since the UNIX epoch.
+    pub end_ts: u64,
+}
+
+impl TradeProgress {
+    /// How much of the range has been downloaded, from `0.0` to `1.0`.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn fraction(&self) -> f64 {
+        let total = self.end_ts.saturating_sub(self.start_ts);
+
+        if total == 0 {
+            return 1.0;
+        }
+
+        self.end_ts.saturating_sub(self.cursor) as f64 / total as f64
+    }
+}
+
+/// Called with the [`TradeProgress`] of a download after each page is written.
+pub type TradeProgressCallback = Box<dyn FnMut(&TradeProgress) + Send>;
+
+/// Pages backward through the trades of one instrument between two times.
+///
+/// The range is `start_ts` inclusive to `end_ts` exclusive, both in ms since the UNIX epoch.
+///
+/// ```no_run
+/// # async fn example(config: crypto_com_api::utils::config::Config) -> anyhow::Result<()> {
+/// use crypto_com_api::history::TradeDownloader;
+///
+/// let trades = TradeDownloader::new("BTC_USDT", 1_704_063_600_000, 1_704_067_200_000)
+///     .download_vec(&config)
+///     .await?;
+///
+/// println!("{} trades in the last hour of 2023", trades.len());
+/// # Ok(())
+/// # }
+/// ```
+pub struct TradeDownloader {
+    /// e.g. ETH_CRO, BTC_USDT.
+    instrument_name: String,
+    /// Start of the range.
+    start_ts: u64,
+    /// End of the range.
+    end_ts: u64,
+    /// Trades asked for per request.
+    page_size: u64,
+    /// Least time between the start of two requests.
+    min_request_interval: Duration,
+    /// Times a rate limited request is retried before giving up.
+    max_retries: u32,
+    /// Called after each page is written.
+    on_progress: Option<TradeProgressCallback>,
+}
+
+impl fmt::Debug for TradeDownloader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TradeDownloader")
+            .field("instrument_name", &self.instrument_name)
+            .field("start_ts", &self.start_ts)
+            .field("end_ts", &self.end_ts)
+            .field("page_size", &self.page_size)
+            .field("min_request_interval", &self.min_request_interval)
+            .field("max_retries", &self.max_retries)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TradeDownloader {
+    /// Download `instrument_name` trades from `start_ts` up to `end_ts`.
+    ///
+    /// Defaults to full pages of [`MAX_TRADES_COUNT`], 100ms between requests, and 5 retries.
+    pub fn new(instrument_name: &str, start_ts: u64, end_ts: u64) -> Self {
+        Self {
+            instrument_name: instrument_name.to_owned(),
+            start_ts,
+            end_ts,
+            page_size: MAX_TRADES_COUNT,
+            min_request_interval: Duration::from_millis(100),
+            max_retries: 5,
+            on_progress: None,
+        }
+    }
+
+    /// Ask for `page_size` trades per request, clamped to `1..=MAX_TRADES_COUNT`.
+    #[must_use]
+    pub fn with_page_size(mut self, page_size: u64) -> Self {
+        self.page_size = page_size.clamp(1, MAX_TRADES_COUNT);
+        self
+    }
+
+    /// Wait at least `interval` between the start of two requests.
+    #[must_use]
+    pub const fn with_min_request_interval(mut self, interval: Duration) -> Self {
+        self.min_request_interval = interval;
+        self
+    }
+
+    /// Retry a rate limited request up to `max_retries` times, doubling the wait each time.
+    #[must_use]
+    pub const fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Call `on_progress` after each page is written.
+    #[must_use]
+    pub fn with_progress<F>(mut self, on_progress: F) -> Self
+    where
+        F: FnMut(&TradeProgress) + Send + 'static,
+    {
+        self.on_progress = Some(Box::new(on_progress));
+        self
+    }
+
+    /// Download the range from `source` into a `Vec`, oldest trade first.
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`TradeDownloader::download`].
+    pub async fn download_vec<S>(&mut self, source: &S) -> Result<Vec<Trade>>
+    where
+        S: TradeSource + ?Sized,
+    {
+        let mut trades = vec![];
+        self.download(source, &mut trades).await?;
+        trades.reverse();
+
+        Ok(trades)
+    }
+
+    /// Download the range from `source` into `sink`, returning the final [`TradeProgress`].
+    ///
+    /// Trades are written newest first, each at most once. Each request ends at the oldest trade
+    /// seen so far, trades sharing its timestamp are told apart by their ID.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a request fails, a request is still rate limited after the last
+    /// retry, or the sink fails to write.
+    pub async fn download<S, K>(&mut self, source: &S, sink: &mut K) -> Result<TradeProgress>
+    where
+        S: TradeSource + ?Sized,
+        K: TradeSink + ?Sized,
+    {
+        let mut progress = TradeProgress {
+            start_ts: self.start_ts,
+            cursor: self.end_ts,
+            end_ts: self.end_ts,
+            ..TradeProgress::default()
+        };
+        // IDs of the written trades at `progress.cursor`, which the next page repeats.
+        let mut at_cursor: HashSet<String> = HashSet::new();
+        let mut last_request: Option<tokio::time::Instant> = None;
+
+        // The last page is the first that is not full, trades at `start_ts` are in the range.
+        loop {
+            let params = TradesParams {
+                instrument_name: self.instrument_name.clone(),
+                count: Some(self.page_size),
+                start_ts: Some(self.start_ts),
+                end_ts: Some(progress.cursor),
+            };
+
+            let mut trades = self
+                .fetch_page(source, &params, &mut progress, &mut last_request)
+                .await?;
+            let full_page = trades.len() as u64 >= self.page_size;
+            progress.fetched += trades.len() as u64;
+
+            let (start_ts, end_ts, cursor) = (self.start_ts, self.end_ts, progress.cursor);
+            trades.retain(|trade| {
+                let t = trade.t.epoch_ms();
+
+                t >= start_ts && t < end_ts && t <= cursor && !at_cursor.contains(&trade.d)
+            });
+            trades.sort_by_key(|trade| Reverse(trade.t.epoch_ms()));
+
+            let Some(oldest) = trades.last().map(|trade| trade.t.epoch_ms()) else {
+                if !full_page || progress.cursor <= self.start_ts {
+                    break;
+                }
+
+                // A full page of repeats, every trade at the cursor did not fit in one page.
+                trace::warning!(
+                    "More than {} {} trades at {}, some may be missing.",
+                    self.page_size,
+                    self.instrument_name,
+                    progress.cursor
+                );
+                progress.cursor -= 1;
+                at_cursor.clear();
+                continue;
+            };
+
+            if oldest < progress.cursor {
+                at_cursor.clear();
+            }
+            at_cursor.extend(
+                trades
+                    .iter()
+                    .filter(|trade| trade.t.epoch_ms() == oldest)
+                    .map(|trade| trade.d.clone()),
+            );
+
+            sink.write(&trades)?;
+            progress.written += trades.len() as u64;
+            progress.cursor = if full_page { oldest } else { self.start_ts };
+
+            if let Some(ref mut on_progress) = self.on_progress {
+                on_progress(&progress);
+            }
+
+            if !full_page {
+                break;
+            }
+        }
+
+        progress.cursor = self.start_ts;
+
+        Ok(progress)
+    }
+
+    /// Fetch one page, waiting out the request interval and retrying while rate limited.
+    async fn fetch_page<S>(
+        &self,
+        source: &S,
+        params: &TradesParams,
+        progress: &mut TradeProgress,
+        last_request: &mut Option<tokio::time::Instant>,
+    ) -> Result<Vec<Trade>>
+    where
+        S: TradeSource + ?Sized,
+    {
+        let mut backoff = self.min_request_interval;
+        let mut retries = 0;
+
+        loop {
+            if let Some(at) = *last_request {
+                tokio::time::sleep_until(at + self.min_request_interval).await;
+            }
+
+            *last_request = Some(tokio::time::Instant::now());
+            progress.requests += 1;
+
+            match source.fetch(params).await {
+                Ok(trades) => return Ok(trades),
+                Err(err) if retries < self.max_retries && is_rate_limited(&err) => {
+                    retries += 1;
+                    backoff = backoff.saturating_mul(2);
+
+                    trace::warning!(
+                        "Rate limited fetching {} trades, retry {retries} in {backoff:?}",
+                        params.instrument_name
+                    );
+
+                    tokio::time::sleep(backoff).await;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
@@ -24,15 +24,70 @@
 //! To start using the Websocket API, refer to [`crate::controller::Controller::new`]
 //!
 //! To start using the REST API, refer to [`crate::rest`]
+//!
+//! Only the market websocket is built with the `websocket-market` feature alone, for public market
+//! data without the signing and REST dependencies, and without the order and wallet actions or the
+//! modules trading with them.
+
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    any(feature = "rest", feature = "websocket-market"),
+    not(any(feature = "rustls", feature = "native-tls"))
+))]
+compile_error!(
+    "the `rest` and websocket features need a TLS backend, enable `rustls` or `native-tls`"
+);
 
+#[cfg(feature = "websocket-market")]
+pub mod analytics;
 pub mod api_request;
 pub mod api_response;
-#[cfg(feature = "websocket")]
+#[cfg(feature = "websocket-market")]
+pub mod callbacks;
+#[cfg(all(feature = "websocket-market", feature = "native"))]
 pub mod controller;
+#[cfg(all(feature = "websocket", feature = "native"))]
+pub mod dead_mans_switch;
 pub mod error;
+#[cfg(feature = "websocket-market")]
+pub mod fills;
+#[cfg(feature = "rest")]
+pub mod history;
+#[cfg(all(feature = "websocket-market", feature = "native"))]
+pub mod listener;
+#[cfg(feature = "websocket")]
+pub mod oco;
+#[cfg(feature = "persistence")]
+pub mod persistence;
 pub mod prelude;
+#[cfg(all(feature = "websocket", feature = "native"))]
+pub mod read_only;
 #[cfg(feature = "rest")]
+pub mod reconciliation;
+#[cfg(feature = "websocket-market")]
+pub mod recorder;
+#[cfg(any(feature = "rest", feature = "websocket-market"))]
 pub mod rest;
-pub mod utils;
 #[cfg(feature = "websocket")]
+pub mod risk;
+pub mod schema;
+#[cfg(all(feature = "websocket-market", feature = "native"))]
+pub mod shutdown;
+#[cfg(feature = "signing")]
+pub mod signing;
+#[cfg(feature = "websocket")]
+pub mod simulator;
+#[cfg(all(feature = "websocket", feature = "native"))]
+pub mod strategy;
+#[cfg(all(feature = "websocket-market", feature = "native"))]
+pub mod supervisor;
+#[cfg(feature = "websocket-market")]
+pub mod testing;
+#[cfg(all(feature = "websocket", feature = "native"))]
+pub mod throttle;
+pub mod types;
+pub mod utils;
+#[cfg(feature = "websocket-market")]
 pub mod websocket;
+
+pub use schema::schema;
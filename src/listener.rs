@@ -0,0 +1,64 @@
+//! Handle to a data listener created with [`crate::controller::Controller::listen`].
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use anyhow::Result;
+use tokio::sync::Notify;
+use tokio::task::{JoinError, JoinHandle};
+
+/// A running data listener.
+///
+/// Awaiting the handle waits for the listener to end, like its [`JoinHandle`]. Dropping the handle
+/// does not stop the listener, call [`ListenerHandle::stop`] first.
+#[derive(Debug)]
+pub struct ListenerHandle {
+    /// Notified to stop the loop.
+    stop: Arc<Notify>,
+    /// The listener loop.
+    handle: JoinHandle<Result<()>>,
+}
+
+impl ListenerHandle {
+    /// Spawn `listener`, which must end once `stop` is notified.
+    pub(crate) fn spawn<F, Fut>(listener: F) -> Self
+    where
+        F: FnOnce(Arc<Notify>) -> Fut,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let stop = Arc::new(Notify::new());
+        let handle = tokio::spawn(listener(Arc::clone(&stop)));
+
+        Self { stop, handle }
+    }
+
+    /// Stop the listener after the data it is processing, it then ends with `Ok(())`.
+    pub fn stop(&self) {
+        self.stop.notify_one();
+    }
+
+    /// Whether the listener is still running.
+    #[must_use]
+    pub fn is_alive(&self) -> bool {
+        !self.handle.is_finished()
+    }
+
+    /// Wait for the listener to end and return its result.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the listener failed or panicked.
+    pub async fn join(self) -> Result<()> {
+        self.handle.await?
+    }
+}
+
+impl Future for ListenerHandle {
+    type Output = Result<Result<()>, JoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.handle).poll(cx)
+    }
+}
@@ -0,0 +1,257 @@
+//! Following a one cancels the other pair, assembled from the `private/create-order-list` reply
+//! and `user.order`.
+//!
+//! Send a [`crate::websocket::actions::spot_trading_api::Oco`] as a
+//! [`crate::websocket::actions::spot_trading_api::CreateOrderList`], and feed an [`OcoTracker`]
+//! the data broadcast from before it was sent.
+
+use crate::websocket::data::{CreateOrderList, OrderItem};
+use crate::websocket::WebsocketData;
+
+/// Most `user.order` updates kept while the order IDs of the pair are not known yet.
+const MAX_PENDING_ORDERS: usize = 256;
+
+/// One order of the pair.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OcoLeg {
+    /// The `LIMIT` order, first in the list.
+    Limit,
+    /// The `STOP_LIMIT` order, second in the list.
+    Stop,
+}
+
+impl OcoLeg {
+    /// The other order of the pair.
+    #[must_use]
+    pub const fn other(self) -> Self {
+        match self {
+            Self::Limit => Self::Stop,
+            Self::Stop => Self::Limit,
+        }
+    }
+
+    /// Index of the order in the list.
+    const fn index(self) -> usize {
+        match self {
+            Self::Limit => 0,
+            Self::Stop => 1,
+        }
+    }
+}
+
+/// Sent by an [`OcoTracker`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OcoEvent {
+    /// Both orders were created.
+    Placed {
+        /// Order ID of the [`OcoLeg::Limit`] order.
+        limit_order_id: u64,
+        /// Order ID of the [`OcoLeg::Stop`] order.
+        stop_order_id: u64,
+        /// Contingency ID of the pair, if the reply had one.
+        list_id: Option<String>,
+    },
+    /// The exchange refused to create the pair, no more events follow.
+    Rejected {
+        /// The order that was refused.
+        leg: OcoLeg,
+        /// Code of the refused order.
+        code: u64,
+        /// Message of the refused order.
+        message: Option<String>,
+    },
+    /// An order was filled, the other is canceled by the exchange.
+    Executed {
+        /// The order that was filled.
+        leg: OcoLeg,
+        /// Its order ID.
+        order_id: u64,
+    },
+    /// An order was closed without being filled, with the status from `user.order`, e.g.
+    /// `CANCELED` or `EXPIRED`.
+    Canceled {
+        /// The order that was closed.
+        leg: OcoLeg,
+        /// Its order ID.
+        order_id: u64,
+        /// Status it closed with.
+        status: String,
+    },
+    /// Both orders are closed, no more events follow.
+    Complete {
+        /// The order that was filled, `None` if both were closed unfilled.
+        executed: Option<OcoLeg>,
+    },
+}
+
+/// Follows one OCO pair from the `private/create-order-list` reply until both orders are closed.
+///
+/// Each leg is reported once, as [`OcoEvent::Executed`] or [`OcoEvent::Canceled`], followed by a
+/// single [`OcoEvent::Complete`]. `user.order` updates that arrive before the reply are kept and
+/// applied once the order IDs are known.
+#[derive(Clone, Debug, Default)]
+pub struct OcoTracker {
+    /// Order IDs of the limit and stop orders, once known.
+    order_ids: Option<[u64; 2]>,
+    /// Whether each leg has been reported closed.
+    closed: [bool; 2],
+    /// The leg that was filled.
+    executed: Option<OcoLeg>,
+    /// Updates recieved before the order IDs were known.
+    pending: Vec<OrderItem>,
+    /// Set once [`OcoEvent::Complete`] or [`OcoEvent::Rejected`] is sent.
+    complete: bool,
+}
+
+impl OcoTracker {
+    /// Follow the first OCO reply recieved, create one tracker per pair and send the next pair
+    /// only after the reply.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Follow the pair with the order IDs `limit_order_id` and `stop_order_id`, for pairs created
+    /// before the tracker.
+    #[must_use]
+    pub fn with_order_ids(limit_order_id: u64, stop_order_id: u64) -> Self {
+        Self {
+            order_ids: Some([limit_order_id, stop_order_id]),
+            ..Self::default()
+        }
+    }
+
+    /// Order ID of `leg`, `None` until the reply is recieved.
+    #[must_use]
+    pub fn order_id(&self, leg: OcoLeg) -> Option<u64> {
+        self.order_ids.map(|order_ids| order_ids[leg.index()])
+    }
+
+    /// The leg that was filled, if any.
+    #[must_use]
+    pub const fn executed(&self) -> Option<OcoLeg> {
+        self.executed
+    }
+
+    /// Whether [`OcoEvent::Complete`] or [`OcoEvent::Rejected`] was sent.
+    #[must_use]
+    pub const fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// The events for the pair in `data`, other data is ignored.
+    pub fn process(&mut self, data: &WebsocketData) -> Vec<OcoEvent> {
+        let mut events = vec![];
+
+        if self.complete {
+            return events;
+        }
+
+        match *data {
+            WebsocketData::CreateOrderList(ref list) if self.order_ids.is_none() => {
+                self.apply_reply(list, &mut events);
+            }
+            WebsocketData::UserOrder(ref orders) => {
+                for order in &orders.data {
+                    if self.order_ids.is_some() {
+                        self.apply_order(order, &mut events);
+                    } else {
+                        if self.pending.len() == MAX_PENDING_ORDERS {
+                            self.pending.remove(0);
+                        }
+
+                        self.pending.push(order.clone());
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        events
+    }
+
+    /// Set the order IDs from the reply and apply the pending updates.
+    fn apply_reply(&mut self, list: &CreateOrderList, events: &mut Vec<OcoEvent>) {
+        // A reply to a LIST of another length is not the pair.
+        if list.result_list.len() != 2 {
+            return;
+        }
+
+        let mut order_ids = [0; 2];
+
+        for item in &list.result_list {
+            let leg = if item.index == 0 {
+                OcoLeg::Limit
+            } else {
+                OcoLeg::Stop
+            };
+
+            if item.code != 0 {
+                self.complete = true;
+                events.push(OcoEvent::Rejected {
+                    leg,
+                    code: item.code,
+                    message: item.message.clone(),
+                });
+
+                return;
+            }
+
+            order_ids[leg.index()] = item.order_id;
+        }
+
+        self.order_ids = Some(order_ids);
+        events.push(OcoEvent::Placed {
+            limit_order_id: order_ids[0],
+            stop_order_id: order_ids[1],
+            list_id: list.list_id.clone(),
+        });
+
+        for order in std::mem::take(&mut self.pending) {
+            self.apply_order(&order, events);
+        }
+    }
+
+    /// Report the leg of `order` if it was closed.
+    fn apply_order(&mut self, order: &OrderItem, events: &mut Vec<OcoEvent>) {
+        let (Some(order_ids), Ok(order_id)) = (self.order_ids, order.order_id.parse::<u64>())
+        else {
+            return;
+        };
+
+        let Some(leg) = [OcoLeg::Limit, OcoLeg::Stop]
+            .into_iter()
+            .find(|leg| order_ids[leg.index()] == order_id)
+        else {
+            return;
+        };
+
+        if self.complete || self.closed[leg.index()] {
+            return;
+        }
+
+        match order.status.as_str() {
+            "FILLED" => {
+                self.executed = Some(leg);
+                events.push(OcoEvent::Executed { leg, order_id });
+            }
+            "CANCELED" | "REJECTED" | "EXPIRED" => {
+                events.push(OcoEvent::Canceled {
+                    leg,
+                    order_id,
+                    status: order.status.clone(),
+                });
+            }
+            _ => return,
+        }
+
+        self.closed[leg.index()] = true;
+
+        if self.closed.iter().all(|closed| *closed) {
+            self.complete = true;
+            events.push(OcoEvent::Complete {
+                executed: self.executed,
+            });
+        }
+    }
+}
@@ -0,0 +1,355 @@
+//! Storing orders, fills, balances, and connection events for audit trails and crash recovery.
+//!
+//! [`Store`] is what a backend implements, [`SqliteStore`] keeps everything in a SQLite file.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::Result;
+use rusqlite::{params, Connection};
+
+use crate::types::EpochMs;
+use crate::utils::get_epoch_ms;
+use crate::utils::in_flight::Api;
+use crate::websocket::actions::spot_trading_api::CreateOrder;
+use crate::websocket::connection_state::ConnectionState;
+use crate::websocket::data::{OrderItem, UserBalance, UserTrade};
+use crate::websocket::WebsocketData;
+
+/// Order statuses that are final, orders in any other status are open.
+const CLOSED_STATUSES: [&str; 4] = ["CANCELED", "FILLED", "REJECTED", "EXPIRED"];
+
+/// Somewhere orders, fills, balances, and connection events are recorded as they happen.
+///
+/// Every record is kept with the time it was recorded in ms since the UNIX epoch.
+/// [`Store::process`] records what it can from [`WebsocketData`], the orders being sent are not
+/// broadcast and are recorded with [`Store::record_order_request`] before they are pushed.
+///
+/// ```no_run
+/// # fn example<U, M>(mut controller: crypto_com_api::controller::Controller<U, M>) -> anyhow::Result<()> {
+/// use crypto_com_api::persistence::{SqliteStore, Store};
+///
+/// let mut store = SqliteStore::open("trading.sqlite")?;
+///
+/// for order in store.open_orders()? {
+///     println!("{} was open before the restart", order.order_id);
+/// }
+///
+/// controller.listen(move |data| {
+///     if let Some(ref data) = data.result {
+///         store.process(data)?;
+///     }
+///
+///     Ok(false)
+/// });
+/// # Ok(())
+/// # }
+/// ```
+pub trait Store {
+    /// Record an order about to be sent.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the order could not be stored.
+    fn record_order_request(&mut self, order: &CreateOrder) -> Result<()>;
+
+    /// Record the state of an order from `user.order` or an order query.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the order could not be stored.
+    fn record_order(&mut self, order: &OrderItem) -> Result<()>;
+
+    /// Record a fill of `instrument_name`, a fill already recorded is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the fill could not be stored.
+    fn record_trade(&mut self, instrument_name: &str, trade: &UserTrade) -> Result<()>;
+
+    /// Record a snapshot of the balances.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the balances could not be stored.
+    fn record_balances(&mut self, balances: &[UserBalance]) -> Result<()>;
+
+    /// Record a change in the state of a websocket connection.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the event could not be stored.
+    fn record_connection(&mut self, state: &ConnectionState) -> Result<()>;
+
+    /// The latest recorded state of every order that is not canceled, filled, rejected, or
+    /// expired, to restore local state after a crash.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the orders could not be read.
+    fn open_orders(&self) -> Result<Vec<OrderItem>>;
+
+    /// Record the orders, fills, balances, and connection events in `data`, other data is
+    /// ignored.
+    ///
+    /// Returns the number of records written.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a record could not be stored.
+    fn process(&mut self, data: &WebsocketData) -> Result<usize> {
+        let orders = match *data {
+            WebsocketData::UserOrder(ref res) => &res.data,
+            WebsocketData::GetOpenOrders(ref res) => &res.order_list,
+            WebsocketData::GetOrderHistory(ref res) => &res.order_list,
+            WebsocketData::UserTrade(ref res) => {
+                for trade in &res.data {
+                    self.record_trade(&res.instrument_name, trade)?;
+                }
+
+                return Ok(res.data.len());
+            }
+            WebsocketData::UserBalance(ref balances) => {
+                self.record_balances(balances)?;
+
+                return Ok(balances.len());
+            }
+            WebsocketData::ConnectionState(ref state) => {
+                self.record_connection(state)?;
+
+                return Ok(1);
+            }
+            _ => return Ok(0),
+        };
+
+        for order in orders {
+            self.record_order(order)?;
+        }
+
+        Ok(orders.len())
+    }
+}
+
+/// A [`Store`] in a SQLite database.
+///
+/// Orders, fills, and balances are stored as their JSON with the columns needed to look them up.
+#[derive(Debug)]
+pub struct SqliteStore {
+    /// The open database.
+    conn: Connection,
+}
+
+impl SqliteStore {
+    /// Open the database at `path`, creating it and its tables if they do not exist.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the database could not be opened or the tables created.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// A database that only lives as long as the store, for tests and dry runs.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the tables could not be created.
+    pub fn open_in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    /// Use `conn`, creating the tables if they do not exist.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the tables could not be created.
+    pub fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS order_requests (
+                id INTEGER PRIMARY KEY,
+                recorded_at INTEGER NOT NULL,
+                instrument_name TEXT NOT NULL,
+                client_oid TEXT,
+                payload TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS orders (
+                id INTEGER PRIMARY KEY,
+                recorded_at INTEGER NOT NULL,
+                order_id TEXT NOT NULL,
+                instrument_name TEXT NOT NULL,
+                status TEXT NOT NULL,
+                update_time INTEGER NOT NULL,
+                payload TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS orders_order_id ON orders (order_id);
+            CREATE TABLE IF NOT EXISTS trades (
+                trade_id INTEGER PRIMARY KEY,
+                recorded_at INTEGER NOT NULL,
+                order_id INTEGER NOT NULL,
+                instrument_name TEXT NOT NULL,
+                payload TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS balances (
+                id INTEGER PRIMARY KEY,
+                recorded_at INTEGER NOT NULL,
+                currency TEXT NOT NULL,
+                payload TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS connection_events (
+                id INTEGER PRIMARY KEY,
+                recorded_at INTEGER NOT NULL,
+                api TEXT NOT NULL,
+                connected INTEGER NOT NULL,
+                state TEXT NOT NULL
+            );",
+        )?;
+
+        Ok(Self { conn })
+    }
+
+    /// The database, to query the records directly.
+    #[must_use]
+    pub const fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// The recorded fills of `order_id`, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the fills could not be read.
+    pub fn trades_of(&self, order_id: u64) -> Result<Vec<UserTrade>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT payload FROM trades WHERE order_id = ?1 ORDER BY trade_id")?;
+        let payloads = stmt.query_map(params![order_id], |row| row.get::<_, String>(0))?;
+
+        let mut trades = vec![];
+
+        for payload in payloads {
+            trades.push(serde_json::from_str(&payload?)?);
+        }
+
+        Ok(trades)
+    }
+}
+
+impl Store for SqliteStore {
+    fn record_order_request(&mut self, order: &CreateOrder) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO order_requests (recorded_at, instrument_name, client_oid, payload)
+            VALUES (?1, ?2, ?3, ?4)",
+            params![
+                get_epoch_ms(),
+                order.instrument_name,
+                order.client_oid,
+                serde_json::to_string(order)?
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn record_order(&mut self, order: &OrderItem) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO orders (recorded_at, order_id, instrument_name, status, update_time, payload)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                get_epoch_ms(),
+                order.order_id,
+                order.instrument_name,
+                order.status,
+                order.update_time.epoch_ms(),
+                serde_json::to_string(order)?
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn record_trade(&mut self, instrument_name: &str, trade: &UserTrade) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO trades (trade_id, recorded_at, order_id, instrument_name, payload)
+            VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                trade.trade_id,
+                get_epoch_ms(),
+                trade.order_id,
+                instrument_name,
+                serde_json::to_string(trade)?
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn record_balances(&mut self, balances: &[UserBalance]) -> Result<()> {
+        let recorded_at = get_epoch_ms();
+        let tx = self.conn.transaction()?;
+
+        for balance in balances {
+            tx.execute(
+                "INSERT INTO balances (recorded_at, currency, payload) VALUES (?1, ?2, ?3)",
+                params![
+                    recorded_at,
+                    balance.currency,
+                    serde_json::to_string(balance)?
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn record_connection(&mut self, state: &ConnectionState) -> Result<()> {
+        let api = match state.api() {
+            Api::User => "user",
+            Api::Market => "market",
+        };
+
+        self.conn.execute(
+            "INSERT INTO connection_events (recorded_at, api, connected, state)
+            VALUES (?1, ?2, ?3, ?4)",
+            params![
+                get_epoch_ms(),
+                api,
+                state.is_connected(),
+                format!("{state:?}")
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn open_orders(&self) -> Result<Vec<OrderItem>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT order_id, payload FROM orders ORDER BY update_time, id")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut latest: HashMap<String, String> = HashMap::new();
+
+        for row in rows {
+            let (order_id, payload) = row?;
+            latest.insert(order_id, payload);
+        }
+
+        let mut orders = vec![];
+
+        for payload in latest.values() {
+            let order: OrderItem = serde_json::from_str(payload)?;
+
+            if !CLOSED_STATUSES.contains(&order.status.as_str()) {
+                orders.push(order);
+            }
+        }
+
+        orders.sort_by_key(|order| order.create_time.epoch_ms());
+
+        Ok(orders)
+    }
+}
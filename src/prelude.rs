@@ -1,30 +1,55 @@
 //! Crate prelude.
 
+#[cfg(feature = "websocket-market")]
 use std::sync::Arc;
 
-use futures_channel::mpsc::{UnboundedReceiver, UnboundedSender};
-use hmac::Hmac;
+#[cfg(feature = "websocket-market")]
+use futures_channel::mpsc::UnboundedSender;
+#[cfg(feature = "signing")]
 pub use hmac::Mac;
-use sha2::Sha256;
-use tokio::sync::Mutex;
-use tokio_tungstenite::tungstenite::Message;
+#[cfg(feature = "websocket-market")]
+use tokio::sync::{broadcast, Mutex};
+#[cfg(feature = "websocket-market")]
+use tungstenite::Message;
 
+#[cfg(feature = "websocket-market")]
 use crate::api_response::ApiResponse;
 pub use crate::error::ApiError;
+#[cfg(feature = "websocket")]
+use crate::risk::RiskManager;
+#[cfg(feature = "signing")]
+pub use crate::signing::HmacSha256;
+#[cfg(feature = "websocket")]
+use crate::simulator::SimulatedExchange;
+#[cfg(feature = "websocket-market")]
 use crate::utils::action::ActionStore;
+#[cfg(feature = "websocket-market")]
 use crate::websocket::WebsocketData;
 
-/// Hashing type for digital signatures.
-pub type HmacSha256 = Hmac<Sha256>;
-
 /// Message sender type.
+#[cfg(feature = "websocket-market")]
 pub type MessageSender = Arc<Mutex<UnboundedSender<Message>>>;
 
 /// Action store type.
+#[cfg(feature = "websocket-market")]
 pub type ActionStoreSender = Arc<Mutex<UnboundedSender<ActionStore>>>;
 
+/// Data event type, shared between every subscriber of the data broadcast.
+#[cfg(feature = "websocket-market")]
+pub type DataEvent = Arc<ApiResponse<WebsocketData>>;
+
 /// Data sender type.
-pub type DataSender = Arc<Mutex<UnboundedSender<ApiResponse<WebsocketData>>>>;
+#[cfg(feature = "websocket-market")]
+pub type DataSender = broadcast::Sender<DataEvent>;
 
 /// Data reciever type.
-pub type DataReciever = Arc<Mutex<UnboundedReceiver<ApiResponse<WebsocketData>>>>;
+#[cfg(feature = "websocket-market")]
+pub type DataReciever = broadcast::Receiver<DataEvent>;
+
+/// Simulated exchange type, shared with the task feeding it market data.
+#[cfg(feature = "websocket")]
+pub type SharedExchange = Arc<Mutex<SimulatedExchange>>;
+
+/// Risk manager type, shared with the task feeding it user data.
+#[cfg(feature = "websocket")]
+pub type SharedRiskManager = Arc<Mutex<RiskManager>>;
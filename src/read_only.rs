@@ -0,0 +1,176 @@
+//! A controller that can only read, for dashboards running with keys that can trade.
+//!
+//! [`ReadOnly`] wraps a [`Controller`] and only has its methods that read data, so code holding
+//! one cannot place orders or withdraw without a compile error. The wrapped controller is also
+//! set read-only with [`Controller::set_read_only`], so the queues it hands out refuse mutating
+//! actions with [`crate::error::ApiError::ReadOnly`] as well.
+
+use std::time::Duration;
+
+use crate::controller::{Controller, MarketWs, UserWs};
+use crate::error::Result;
+use crate::listener::ListenerHandle;
+use crate::prelude::{DataEvent, DataReciever};
+use crate::utils::action_queue::ActionQueue;
+use crate::utils::message_stats::MessageStatsSnapshot;
+use crate::websocket::actions::spot_trading_api::Paginated;
+use crate::websocket::actions::Subscribe;
+use crate::websocket::data;
+
+/// A [`Controller`] without the methods that change orders, funds, or account settings.
+///
+/// Made with [`Controller::into_read_only`], there is no way back to the controller.
+///
+/// ```compile_fail
+/// use crypto_com_api::controller::{MarketWs, UserWs};
+/// use crypto_com_api::read_only::ReadOnly;
+///
+/// async fn cancel(controller: &ReadOnly<UserWs, MarketWs>) {
+///     controller.cancel_all_orders("BTC_USDT").await.unwrap();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ReadOnly<U, M>(Controller<U, M>);
+
+impl<U, M> Controller<U, M> {
+    /// Set the controller read-only and keep only its methods that read data.
+    #[must_use]
+    pub fn into_read_only(self) -> ReadOnly<U, M> {
+        self.set_read_only(true);
+
+        ReadOnly(self)
+    }
+}
+
+impl<M> ReadOnly<UserWs, M> {
+    /// A handle for sending actions to the user websocket from other tasks, mutating actions are
+    /// refused.
+    #[must_use]
+    pub fn user_queue(&self) -> ActionQueue {
+        self.0.user_queue()
+    }
+
+    /// See [`Controller::subscribe_user`].
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`Controller::subscribe_user`].
+    pub async fn subscribe_user(&self, subscribe: impl Into<Subscribe>) -> Result<Vec<String>> {
+        self.0.subscribe_user(subscribe).await
+    }
+
+    /// See [`Controller::get_account_summary`].
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`Controller::get_account_summary`].
+    pub async fn get_account_summary(
+        &self,
+        currency: Option<String>,
+    ) -> Result<data::AccountSummary> {
+        self.0.get_account_summary(currency).await
+    }
+
+    /// See [`Controller::get_open_orders`].
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`Controller::get_open_orders`].
+    pub async fn get_open_orders(
+        &self,
+        instrument_name: Option<String>,
+    ) -> Result<data::OpenOrders> {
+        self.0.get_open_orders(instrument_name).await
+    }
+
+    /// See [`Controller::get_order_history`].
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`Controller::get_order_history`].
+    pub async fn get_order_history(&self, params: Paginated) -> Result<data::OrderHistory> {
+        self.0.get_order_history(params).await
+    }
+
+    /// See [`Controller::get_order_detail`].
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`Controller::get_order_detail`].
+    pub async fn get_order_detail(&self, order_id: impl Into<String>) -> Result<data::OrderDetail> {
+        self.0.get_order_detail(order_id).await
+    }
+
+    /// See [`Controller::get_trades`].
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`Controller::get_trades`].
+    pub async fn get_trades(&self, params: Paginated) -> Result<data::Trades> {
+        self.0.get_trades(params).await
+    }
+}
+
+impl<U> ReadOnly<U, MarketWs> {
+    /// A handle for sending actions to the market websocket from other tasks.
+    #[must_use]
+    pub fn market_queue(&self) -> ActionQueue {
+        self.0.market_queue()
+    }
+
+    /// See [`Controller::subscribe`].
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`Controller::subscribe`].
+    pub async fn subscribe<I, S>(&self, channels: I) -> Result<Vec<String>>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.0.subscribe(channels).await
+    }
+
+    /// See [`Controller::unsubscribe`].
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`Controller::unsubscribe`].
+    pub async fn unsubscribe<I, S>(&self, channels: I) -> Result<Vec<String>>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.0.unsubscribe(channels).await
+    }
+}
+
+impl<U, M> ReadOnly<U, M> {
+    /// See [`Controller::subscribe_data`].
+    pub fn subscribe_data(&mut self) -> DataReciever {
+        self.0.subscribe_data()
+    }
+
+    /// See [`Controller::listen`].
+    pub fn listen<F>(&mut self, async_fn: F) -> ListenerHandle
+    where
+        F: FnMut(DataEvent) -> anyhow::Result<bool> + Send + 'static,
+    {
+        self.0.listen(async_fn)
+    }
+
+    /// See [`Controller::message_stats`].
+    #[must_use]
+    pub fn message_stats(&self) -> Option<MessageStatsSnapshot> {
+        self.0.message_stats()
+    }
+
+    /// See [`Controller::shutdown`].
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`Controller::shutdown`].
+    pub async fn shutdown(&mut self, drain_timeout: Duration) -> Result<Vec<u64>> {
+        self.0.shutdown(drain_timeout).await
+    }
+}
@@ -0,0 +1,503 @@
+//! Reconciliation of deposits and withdrawals over a time range, for accounting.
+//!
+//! A [`Reconciler`] pages through `private/get-deposit-history` and
+//! `private/get-withdrawal-history`, fetches `private/get-account-summary`, and builds a
+//! [`ReconciliationReport`] with the totals of each currency, the transfers still pending, and the
+//! transfers whose status and transaction hash disagree.
+//!
+//! ```no_run
+//! # async fn example(config: crypto_com_api::utils::config::Config) -> anyhow::Result<()> {
+//! use crypto_com_api::reconciliation::Reconciler;
+//! use crypto_com_api::types::TimeRange;
+//!
+//! let report = Reconciler::new(TimeRange::new(1_672_531_200_000, 1_680_307_200_000)?)
+//!     .with_currency("BTC")
+//!     .run(&config)
+//!     .await?;
+//!
+//! for (currency, totals) in &report.currencies {
+//!     println!("{currency}: {} in, {} out", totals.deposited, totals.withdrawn);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::future::Future;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::prelude::ApiError;
+use crate::rest::data::{
+    Account, AccountSummaryParams, DepositHistoryItem, DepositHistoryParams, WithdrawalHistoryItem,
+};
+use crate::rest::private::{
+    get_account_summary, get_deposit_history, get_withdrawal_history, GetWithdrawalHistoryParams,
+};
+use crate::types::{DepositStatus, EpochMs, PageQuery, TimeRange, WithdrawalStatus};
+use crate::utils::config::Config;
+
+/// Somewhere to fetch deposits, withdrawals, and balances from.
+///
+/// Implemented for [`Config`] to fetch from the REST API, implement it for anything else to
+/// reconcile from a cache or a test fixture instead.
+pub trait TransferSource {
+    /// Fetch the page of deposits described by `params`.
+    fn deposits(
+        &self,
+        params: &DepositHistoryParams,
+    ) -> impl Future<Output = Result<Vec<DepositHistoryItem>>> + Send;
+
+    /// Fetch the page of withdrawals described by `params`.
+    fn withdrawals(
+        &self,
+        params: &GetWithdrawalHistoryParams,
+    ) -> impl Future<Output = Result<Vec<WithdrawalHistoryItem>>> + Send;
+
+    /// Fetch the balance of `currency`, or of every currency if `None`.
+    fn accounts(&self, currency: Option<&str>)
+        -> impl Future<Output = Result<Vec<Account>>> + Send;
+}
+
+/// Bail with [`ApiError::RequestFailed`] if `code` is an error.
+fn check_code(code: Option<u64>, message: Option<String>) -> Result<()> {
+    if let Some(code) = code.filter(|code| *code != 0) {
+        anyhow::bail!(ApiError::RequestFailed { code, message });
+    }
+
+    Ok(())
+}
+
+impl TransferSource for Config {
+    async fn deposits(&self, params: &DepositHistoryParams) -> Result<Vec<DepositHistoryItem>> {
+        let res = get_deposit_history(self, params.clone()).await?;
+        check_code(res.code, res.message)?;
+
+        Ok(res.result.map(|res| res.deposit_list).unwrap_or_default())
+    }
+
+    async fn withdrawals(
+        &self,
+        params: &GetWithdrawalHistoryParams,
+    ) -> Result<Vec<WithdrawalHistoryItem>> {
+        let res = get_withdrawal_history(self, params.clone()).await?;
+        check_code(res.code, res.message)?;
+
+        Ok(res
+            .result
+            .map(|res| res.withdrawal_list)
+            .unwrap_or_default())
+    }
+
+    async fn accounts(&self, currency: Option<&str>) -> Result<Vec<Account>> {
+        let params = AccountSummaryParams {
+            currency: currency.map(str::to_owned),
+        };
+        let res = get_account_summary(self, params).await?;
+        check_code(res.code, res.message)?;
+
+        Ok(res.result.map(|res| res.accounts).unwrap_or_default())
+    }
+}
+
+/// Where a transfer is in its life.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Settlement {
+    /// Not yet credited or paid out.
+    Pending,
+    /// Credited or paid out.
+    Completed,
+    /// Failed, rejected, or cancelled, nothing moved.
+    Failed,
+}
+
+impl From<DepositStatus> for Settlement {
+    fn from(status: DepositStatus) -> Self {
+        match status {
+            DepositStatus::NotArrived | DepositStatus::Pending => Self::Pending,
+            DepositStatus::Arrived => Self::Completed,
+            DepositStatus::Failed => Self::Failed,
+        }
+    }
+}
+
+impl From<WithdrawalStatus> for Settlement {
+    fn from(status: WithdrawalStatus) -> Self {
+        match status {
+            WithdrawalStatus::Pending
+            | WithdrawalStatus::Processing
+            | WithdrawalStatus::PaymentInProgress => Self::Pending,
+            WithdrawalStatus::Completed => Self::Completed,
+            WithdrawalStatus::Rejected
+            | WithdrawalStatus::PaymentFailed
+            | WithdrawalStatus::Cancelled => Self::Failed,
+        }
+    }
+}
+
+/// Totals of one currency over the range of a [`ReconciliationReport`].
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct CurrencyTotals {
+    /// Amount of the deposits that arrived.
+    pub deposited: f64,
+    /// Fees of the deposits that arrived.
+    pub deposit_fees: f64,
+    /// Number of deposits that arrived.
+    pub deposits: u64,
+    /// Amount of the completed withdrawals.
+    pub withdrawn: f64,
+    /// Fees of the completed withdrawals.
+    pub withdrawal_fees: f64,
+    /// Number of completed withdrawals.
+    pub withdrawals: u64,
+    /// Amount of the deposits not yet arrived.
+    pub pending_deposits: f64,
+    /// Amount of the withdrawals not yet completed, fees excluded.
+    pub pending_withdrawals: f64,
+    /// Number of deposits and withdrawals that failed or were cancelled.
+    pub failed: u64,
+    /// Total balance from the account summary, `None` if the currency had no account.
+    pub balance: Option<f64>,
+    /// Available balance from the account summary, `None` if the currency had no account.
+    pub available: Option<f64>,
+}
+
+impl CurrencyTotals {
+    /// Deposits less withdrawals and every fee, the change in balance from transfers.
+    #[must_use]
+    pub fn net_flow(&self) -> f64 {
+        self.deposited - self.deposit_fees - self.withdrawn - self.withdrawal_fees
+    }
+
+    /// Sum of the deposit and withdrawal fees.
+    #[must_use]
+    pub fn fees(&self) -> f64 {
+        self.deposit_fees + self.withdrawal_fees
+    }
+}
+
+/// A deposit or withdrawal not yet settled.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub enum PendingItem {
+    /// A deposit that has not arrived.
+    Deposit(DepositHistoryItem),
+    /// A withdrawal that has not completed.
+    Withdrawal(WithdrawalHistoryItem),
+}
+
+impl PendingItem {
+    /// e.g. BTC, CRO.
+    #[must_use]
+    pub fn currency(&self) -> &str {
+        match *self {
+            Self::Deposit(ref deposit) => &deposit.currency,
+            Self::Withdrawal(ref withdrawal) => &withdrawal.currency,
+        }
+    }
+
+    /// Amount, fees excluded.
+    #[must_use]
+    pub const fn amount(&self) -> f64 {
+        match *self {
+            Self::Deposit(ref deposit) => deposit.amount,
+            Self::Withdrawal(ref withdrawal) => withdrawal.amount,
+        }
+    }
+}
+
+/// A transfer whose status and transaction hash disagree, to be checked by hand.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub enum Discrepancy {
+    /// A completed withdrawal with no transaction hash.
+    MissingTxid {
+        /// Withdrawal ID.
+        withdrawal_id: u64,
+    },
+    /// A withdrawal that failed or was cancelled but has a transaction hash, the funds may have
+    /// left anyway.
+    FailedWithTxid {
+        /// Withdrawal ID.
+        withdrawal_id: u64,
+        /// Transaction hash.
+        txid: String,
+    },
+    /// Several withdrawals with the same transaction hash.
+    DuplicateTxid {
+        /// Transaction hash.
+        txid: String,
+        /// IDs of the withdrawals, in ascending order.
+        withdrawal_ids: Vec<u64>,
+    },
+}
+
+/// Deposits and withdrawals over a time range, summed by currency.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct ReconciliationReport {
+    /// The time range the transfers were taken from.
+    pub range: TimeRange,
+    /// Totals by currency, every currency with a transfer or an account.
+    pub currencies: BTreeMap<String, CurrencyTotals>,
+    /// Transfers not yet settled, oldest first.
+    pub pending: Vec<PendingItem>,
+    /// Transfers to check by hand.
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+impl ReconciliationReport {
+    /// Build the report from already fetched data.
+    ///
+    /// Transfers created outside `range` are ignored and a transfer repeated across pages is
+    /// counted once.
+    ///
+    /// ```
+    /// use crypto_com_api::reconciliation::ReconciliationReport;
+    /// use crypto_com_api::types::TimeRange;
+    ///
+    /// let report = ReconciliationReport::build(TimeRange::default(), &[], &[], &[]);
+    ///
+    /// assert!(report.currencies.is_empty());
+    /// assert!(report.is_clean());
+    /// ```
+    #[must_use]
+    pub fn build(
+        range: TimeRange,
+        deposits: &[DepositHistoryItem],
+        withdrawals: &[WithdrawalHistoryItem],
+        accounts: &[Account],
+    ) -> Self {
+        let in_range = |time: u64| {
+            range.start_ts.is_none_or(|start_ts| time >= start_ts)
+                && range.end_ts.is_none_or(|end_ts| time <= end_ts)
+        };
+
+        let mut report = Self {
+            range,
+            ..Self::default()
+        };
+
+        for account in accounts {
+            let totals = report
+                .currencies
+                .entry(account.currency.clone())
+                .or_default();
+            totals.balance = Some(account.balance);
+            totals.available = Some(account.available);
+        }
+
+        let mut seen = HashSet::new();
+
+        for deposit in deposits {
+            if !in_range(deposit.create_time.epoch_ms()) || !seen.insert(deposit.id) {
+                continue;
+            }
+
+            let totals = report
+                .currencies
+                .entry(deposit.currency.clone())
+                .or_default();
+
+            match Settlement::from(deposit.status) {
+                Settlement::Pending => {
+                    totals.pending_deposits += deposit.amount;
+                    report.pending.push(PendingItem::Deposit(deposit.clone()));
+                }
+                Settlement::Completed => {
+                    totals.deposited += deposit.amount;
+                    totals.deposit_fees += deposit.fee;
+                    totals.deposits += 1;
+                }
+                Settlement::Failed => totals.failed += 1,
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let mut by_txid: HashMap<&str, Vec<u64>> = HashMap::new();
+
+        for withdrawal in withdrawals {
+            if !in_range(withdrawal.create_time.epoch_ms()) || !seen.insert(withdrawal.id) {
+                continue;
+            }
+
+            let totals = report
+                .currencies
+                .entry(withdrawal.currency.clone())
+                .or_default();
+            let settlement = Settlement::from(withdrawal.status);
+
+            match settlement {
+                Settlement::Pending => {
+                    totals.pending_withdrawals += withdrawal.amount;
+                    report
+                        .pending
+                        .push(PendingItem::Withdrawal(withdrawal.clone()));
+                }
+                Settlement::Completed => {
+                    totals.withdrawn += withdrawal.amount;
+                    totals.withdrawal_fees += withdrawal.fee;
+                    totals.withdrawals += 1;
+                }
+                Settlement::Failed => totals.failed += 1,
+            }
+
+            if withdrawal.txid.is_empty() {
+                if settlement == Settlement::Completed {
+                    report.discrepancies.push(Discrepancy::MissingTxid {
+                        withdrawal_id: withdrawal.id,
+                    });
+                }
+
+                continue;
+            }
+
+            if settlement == Settlement::Failed {
+                report.discrepancies.push(Discrepancy::FailedWithTxid {
+                    withdrawal_id: withdrawal.id,
+                    txid: withdrawal.txid.clone(),
+                });
+            }
+
+            by_txid
+                .entry(&withdrawal.txid)
+                .or_default()
+                .push(withdrawal.id);
+        }
+
+        let mut duplicates: Vec<(&str, Vec<u64>)> = by_txid
+            .into_iter()
+            .filter(|(_, ids)| ids.len() > 1)
+            .collect();
+        duplicates.sort_unstable_by_key(|&(txid, _)| txid);
+
+        for (txid, mut withdrawal_ids) in duplicates {
+            withdrawal_ids.sort_unstable();
+
+            report.discrepancies.push(Discrepancy::DuplicateTxid {
+                txid: txid.to_owned(),
+                withdrawal_ids,
+            });
+        }
+
+        report.pending.sort_by_key(|item| match *item {
+            PendingItem::Deposit(ref deposit) => deposit.create_time.epoch_ms(),
+            PendingItem::Withdrawal(ref withdrawal) => withdrawal.create_time.epoch_ms(),
+        });
+
+        report
+    }
+
+    /// The totals of `currency`.
+    #[must_use]
+    pub fn totals(&self, currency: &str) -> Option<&CurrencyTotals> {
+        self.currencies.get(currency)
+    }
+
+    /// Whether nothing is pending and there are no discrepancies.
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.pending.is_empty() && self.discrepancies.is_empty()
+    }
+}
+
+/// Fetches the transfers of a time range and builds a [`ReconciliationReport`].
+#[derive(Clone, Debug)]
+pub struct Reconciler {
+    /// Range the transfers are fetched for.
+    range: TimeRange,
+    /// Only reconcile this currency when set.
+    currency: Option<String>,
+    /// Transfers requested per page.
+    page_size: u64,
+}
+
+impl Reconciler {
+    /// Reconcile every currency over `range`.
+    ///
+    /// The exchange only returns the last 90 days when the start of the range is omitted.
+    #[must_use]
+    pub const fn new(range: TimeRange) -> Self {
+        Self {
+            range,
+            currency: None,
+            page_size: PageQuery::MAX_PAGE_SIZE,
+        }
+    }
+
+    /// Only reconcile `currency`, e.g. BTC.
+    #[must_use]
+    pub fn with_currency(mut self, currency: impl Into<String>) -> Self {
+        self.currency = Some(currency.into());
+
+        self
+    }
+
+    /// Request `page_size` transfers per page, [`PageQuery::MAX_PAGE_SIZE`] by default.
+    #[must_use]
+    pub const fn with_page_size(mut self, page_size: u64) -> Self {
+        self.page_size = page_size;
+
+        self
+    }
+
+    /// Fetch every page of deposits and withdrawals and the balances from `source`.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::InvalidTimeRange`] or [`ApiError::InvalidPageSize`] before
+    /// fetching anything, or the error of the first request that fails.
+    pub async fn run(&self, source: &impl TransferSource) -> Result<ReconciliationReport> {
+        self.range.validate()?;
+        let first = PageQuery::new(0, self.page_size)?;
+
+        let mut deposits = vec![];
+        let mut page = first;
+
+        loop {
+            let params = DepositHistoryParams {
+                currency: self.currency.clone(),
+                range: self.range,
+                page,
+                status: None,
+            };
+            let fetched = source.deposits(&params).await?;
+            let done = (fetched.len() as u64) < self.page_size;
+            deposits.extend(fetched);
+
+            if done {
+                break;
+            }
+
+            page = page.next();
+        }
+
+        let mut withdrawals = vec![];
+        let mut page = first;
+
+        loop {
+            let params = GetWithdrawalHistoryParams {
+                currency: self.currency.clone(),
+                range: self.range,
+                page,
+                status: None,
+            };
+            let fetched = source.withdrawals(&params).await?;
+            let done = (fetched.len() as u64) < self.page_size;
+            withdrawals.extend(fetched);
+
+            if done {
+                break;
+            }
+
+            page = page.next();
+        }
+
+        let accounts = source.accounts(self.currency.as_deref()).await?;
+
+        Ok(ReconciliationReport::build(
+            self.range,
+            &deposits,
+            &withdrawals,
+            &accounts,
+        ))
+    }
+}
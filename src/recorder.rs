@@ -0,0 +1,380 @@
+//! Recording trades to rotating CSV or JSON lines files.
+
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::types::{EpochMs, Time};
+use crate::utils::get_epoch_ms;
+use crate::utils::trace;
+use crate::websocket::data::{TradeRes, UserTradeRes};
+use crate::websocket::WebsocketData;
+
+/// The format trades are written in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordFormat {
+    /// Comma separated values with a header line at the top of each file.
+    Csv,
+    /// One JSON object per line.
+    JsonLines,
+}
+
+impl RecordFormat {
+    /// The file extension, without the dot.
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::JsonLines => "jsonl",
+        }
+    }
+}
+
+/// Whether a recorded trade was public or one of the user's own fills.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TradeSource {
+    /// From `trade.{instrument_name}`.
+    Market,
+    /// From `user.trade.{instrument_name}`.
+    User,
+}
+
+/// One row written by the [`TradeRecorder`].
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct TradeRecord {
+    /// [`TradeSource`]
+    pub source: TradeSource,
+    /// e.g. BTCUSD-PERP.
+    pub instrument_name: String,
+    /// Trade ID.
+    pub trade_id: String,
+    /// Side (buy or sell).
+    pub side: String,
+    /// Trade price.
+    pub price: f64,
+    /// Trade quantity.
+    pub quantity: f64,
+    /// Trade timestamp.
+    pub t: Time,
+    /// Fee paid, user trades only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee: Option<f64>,
+    /// Currency the fee was paid in, user trades only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_currency: Option<String>,
+    /// Order ID, user trades only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_id: Option<u64>,
+}
+
+impl TradeRecord {
+    /// Header line written at the top of CSV files.
+    pub const CSV_HEADER: &'static str =
+        "source,instrument_name,trade_id,side,price,quantity,t,fee,fee_currency,order_id";
+
+    /// The records for every trade in `trades`.
+    #[must_use]
+    pub fn from_trades(trades: &TradeRes) -> Vec<Self> {
+        trades
+            .data
+            .iter()
+            .map(|trade| Self {
+                source: TradeSource::Market,
+                instrument_name: trade.i.to_string(),
+                trade_id: trade.d.clone(),
+                side: trade.s.clone(),
+                price: trade.p,
+                quantity: trade.q,
+                t: trade.t,
+                fee: None,
+                fee_currency: None,
+                order_id: None,
+            })
+            .collect()
+    }
+
+    /// The records for every fill in `trades`.
+    #[must_use]
+    pub fn from_user_trades(trades: &UserTradeRes) -> Vec<Self> {
+        trades
+            .data
+            .iter()
+            .map(|trade| Self {
+                source: TradeSource::User,
+                instrument_name: trades.instrument_name.clone(),
+                trade_id: trade.trade_id.to_string(),
+                side: trade.side.clone(),
+                price: trade.traded_price,
+                quantity: trade.traded_quantity,
+                t: trade.create_time,
+                fee: Some(trade.fee),
+                fee_currency: Some(trade.fee_currency.clone()),
+                order_id: Some(trade.order_id),
+            })
+            .collect()
+    }
+
+    /// The record as a CSV line with `t` in ms since the UNIX epoch, without the line ending.
+    #[must_use]
+    pub fn to_csv(&self) -> String {
+        let source = match self.source {
+            TradeSource::Market => "market",
+            TradeSource::User => "user",
+        };
+
+        format!(
+            "{source},{},{},{},{},{},{},{},{},{}",
+            self.instrument_name,
+            self.trade_id,
+            self.side,
+            self.price,
+            self.quantity,
+            self.t.epoch_ms(),
+            self.fee.map(|fee| fee.to_string()).unwrap_or_default(),
+            self.fee_currency.as_deref().unwrap_or_default(),
+            self.order_id
+                .map(|order_id| order_id.to_string())
+                .unwrap_or_default(),
+        )
+    }
+}
+
+/// The file currently being written.
+#[derive(Debug)]
+struct OpenFile {
+    /// Where the file is.
+    path: PathBuf,
+    /// Buffered writer for the file.
+    writer: BufWriter<File>,
+    /// Bytes written to the file so far.
+    bytes: u64,
+    /// When the file was opened.
+    opened_at: Instant,
+}
+
+/// Appends [`WebsocketData::Trade`], and optionally [`WebsocketData::UserTrade`], to files in a
+/// directory, starting a new file when the current one gets too large or too old.
+///
+/// Files are named `{prefix}-{epoch_ms}-{sequence}.{extension}` so they sort in the order they
+/// were written.
+///
+/// ```no_run
+/// # fn example<U, M>(mut controller: crypto_com_api::controller::Controller<U, M>) -> anyhow::Result<()> {
+/// use std::time::Duration;
+///
+/// use crypto_com_api::recorder::{RecordFormat, TradeRecorder};
+///
+/// let mut recorder = TradeRecorder::new("trades", "BTCUSD-PERP", RecordFormat::Csv)
+///     .with_max_bytes(64 * 1024 * 1024)
+///     .with_max_age(Duration::from_secs(60 * 60));
+///
+/// controller.listen(move |data| {
+///     if let Some(ref data) = data.result {
+///         recorder.process(data)?;
+///     }
+///
+///     Ok(false)
+/// });
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TradeRecorder {
+    /// Directory the files are written to.
+    dir: PathBuf,
+    /// Start of every file name.
+    prefix: String,
+    /// [`RecordFormat`]
+    format: RecordFormat,
+    /// Start a new file once the current one reaches this many bytes.
+    max_bytes: Option<u64>,
+    /// Start a new file once the current one has been open this long.
+    max_age: Option<Duration>,
+    /// Also record the user's own fills.
+    user_trades: bool,
+    /// Files opened so far.
+    sequence: u64,
+    /// [`OpenFile`]
+    current: Option<OpenFile>,
+}
+
+impl TradeRecorder {
+    /// Record trades into `dir`, which is created if it does not exist, as `format` files whose
+    /// names start with `prefix`.
+    ///
+    /// Files are not rotated until [`TradeRecorder::with_max_bytes`] or
+    /// [`TradeRecorder::with_max_age`] is set.
+    pub fn new(dir: impl AsRef<Path>, prefix: &str, format: RecordFormat) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            prefix: prefix.to_owned(),
+            format,
+            max_bytes: None,
+            max_age: None,
+            user_trades: false,
+            sequence: 0,
+            current: None,
+        }
+    }
+
+    /// Start a new file once the current one reaches `max_bytes`.
+    #[must_use]
+    pub const fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Start a new file once the current one has been open for `max_age`.
+    #[must_use]
+    pub const fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Also record [`WebsocketData::UserTrade`].
+    #[must_use]
+    pub const fn with_user_trades(mut self) -> Self {
+        self.user_trades = true;
+        self
+    }
+
+    /// The file currently being written, `None` before the first trade.
+    #[must_use]
+    pub fn current_path(&self) -> Option<&Path> {
+        self.current.as_ref().map(|file| file.path.as_path())
+    }
+
+    /// Record the trades in `data`, other data is ignored.
+    ///
+    /// Returns the number of trades written.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a file could not be created or written to.
+    pub fn process(&mut self, data: &WebsocketData) -> Result<usize> {
+        let records = match *data {
+            WebsocketData::Trade(ref trades) => TradeRecord::from_trades(trades),
+            WebsocketData::UserTrade(ref trades) if self.user_trades => {
+                TradeRecord::from_user_trades(trades)
+            }
+            _ => return Ok(0),
+        };
+
+        self.write(&records)?;
+
+        Ok(records.len())
+    }
+
+    /// Append `records`, rotating first if the current file is too large or too old.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a file could not be created or written to.
+    pub fn write(&mut self, records: &[TradeRecord]) -> Result<()> {
+        for record in records {
+            let line = match self.format {
+                RecordFormat::Csv => record.to_csv(),
+                RecordFormat::JsonLines => serde_json::to_string(record)?,
+            };
+
+            let file = self.file()?;
+            writeln!(file.writer, "{line}")?;
+            file.bytes += line.len() as u64 + 1;
+        }
+
+        Ok(())
+    }
+
+    /// Flush the current file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file could not be written to.
+    pub fn flush(&mut self) -> Result<()> {
+        if let Some(ref mut file) = self.current {
+            file.writer.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Flush and close the current file, the next trade starts a new one.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file could not be written to.
+    pub fn rotate(&mut self) -> Result<()> {
+        if let Some(mut file) = self.current.take() {
+            file.writer.flush()?;
+            trace::debug!("Closed trade recording {}", file.path.display());
+        }
+
+        Ok(())
+    }
+
+    /// The file to write the next record to, rotating or opening one as needed.
+    fn file(&mut self) -> Result<&mut OpenFile> {
+        let expired = self.current.as_ref().is_some_and(|file| {
+            self.max_bytes.is_some_and(|max| file.bytes >= max)
+                || self
+                    .max_age
+                    .is_some_and(|max| file.opened_at.elapsed() >= max)
+        });
+
+        if expired {
+            self.rotate()?;
+        }
+
+        if self.current.is_none() {
+            self.current = Some(self.open()?);
+        }
+
+        self.current
+            .as_mut()
+            .ok_or_else(|| anyhow::anyhow!("trade recording file was not opened"))
+    }
+
+    /// Open the next file in the sequence.
+    fn open(&mut self) -> Result<OpenFile> {
+        fs::create_dir_all(&self.dir)?;
+
+        self.sequence += 1;
+
+        let path = self.dir.join(format!(
+            "{}-{}-{}.{}",
+            self.prefix,
+            get_epoch_ms(),
+            self.sequence,
+            self.format.extension()
+        ));
+        let mut writer = BufWriter::new(File::create(&path)?);
+        let mut bytes = 0;
+
+        if self.format == RecordFormat::Csv {
+            writeln!(writer, "{}", TradeRecord::CSV_HEADER)?;
+            bytes += TradeRecord::CSV_HEADER.len() as u64 + 1;
+        }
+
+        trace::debug!("Opened trade recording {}", path.display());
+
+        Ok(OpenFile {
+            path,
+            writer,
+            bytes,
+            opened_at: Instant::now(),
+        })
+    }
+}
+
+impl Drop for TradeRecorder {
+    fn drop(&mut self) {
+        if let Err(err) = self.flush() {
+            trace::error!("Failed to flush trade recording: {err:#}");
+        }
+    }
+}
@@ -0,0 +1,22 @@
+//! Synchronous wrappers of the REST API for use outside of an async runtime.
+//!
+//! These mirror [`crate::rest::public`] and [`crate::rest::private`] and return the same types.
+//! They must not be called from within an async runtime, use the async versions there.
+
+pub mod private;
+pub mod public;
+
+use crate::error::Result;
+use crate::utils::config::Config;
+
+/// A blocking client that sends the headers and user agent in `config`.
+///
+/// # Errors
+///
+/// Will return [`crate::prelude::ApiError::InvalidHeader`] if a header is not valid, or
+/// [`crate::prelude::ApiError::Http`] if the client cannot be built.
+pub(crate) fn client(config: &Config) -> Result<reqwest::blocking::Client> {
+    Ok(reqwest::blocking::Client::builder()
+        .default_headers(config.header_map()?)
+        .build()?)
+}
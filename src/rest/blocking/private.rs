@@ -0,0 +1,465 @@
+//! Blocking private REST API functions, see [`crate::rest::private`].
+
+use crate::error::Result;
+use crate::prelude::ApiError;
+use crate::rest::data::{
+    account_summary::{AccountSummary, AccountSummaryParams},
+    currency_networks::{CurrencyNetworks, WithdrawalQuote},
+    deposit_address::{
+        CreateDepositAddressParams, DepositAddress, DepositAddressItem, DepositAddressParams,
+    },
+    deposit_history::{DepositHistory, DepositHistoryParams},
+    dust_conversion::{
+        ConvertDustParams, DustConversion, DustConversionQuote, DustConversionQuoteParams,
+    },
+    withdrawal_history::WithdrawalHistory,
+    CreateWithdrawalRes,
+};
+use crate::rest::private::{CreateWithdrawal, GetWithdrawalHistoryParams};
+use crate::{api_request::ApiRequestBuilder, api_response::ApiResponse, utils::config::Config};
+
+/// Creates a withdrawal request. Withdrawal setting must be enabled for your API Key. If you do
+/// not see the option when viewing your API Key, this feature is not yet available to you.
+///
+/// # Errors
+///
+/// Will return [`ApiError::WithdrawalRefused`] if the environment does not allow withdrawals.
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, `api_key`, or
+/// `secret_key`, or [`ApiError::Http`] if send fails or the response cannot be deserialized.
+pub fn create_withdrawal(
+    config: &Config,
+    params: CreateWithdrawal,
+) -> Result<ApiResponse<CreateWithdrawalRes>> {
+    config.check_withdrawal()?;
+
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let Some(ref api_key) = config.api_key else {
+        return Err(ApiError::ConfigMissing("api_key".to_owned()));
+    };
+
+    let Some(ref secret) = config.secret_key else {
+        return Err(ApiError::ConfigMissing("secret_key".to_owned()));
+    };
+
+    let req = ApiRequestBuilder::default()
+        .with_id(0)
+        .with_method("private/create-withdrawal")
+        .with_params(params)
+        .with_api_key(api_key)
+        .with_nonce()
+        .with_digital_signature(secret)
+        .build()?;
+
+    let res = client
+        .post(format!("{rest_url}{}", req.method))
+        .json(&req)
+        .send()?
+        .json::<ApiResponse<CreateWithdrawalRes>>()?;
+
+    Ok(res)
+}
+
+/// Estimate the fee of `params` from [`get_currency_networks`], checking that the withdrawal would
+/// be accepted.
+///
+/// crypto.com has no fee endpoint, the fee is the one listed for the network the withdrawal will
+/// be sent on.
+///
+/// # Errors
+///
+/// Will return [`ApiError::RequestFailed`] if the currency networks could not be fetched, or
+/// [`ApiError::InvalidWithdrawal`] if the withdrawal would be rejected.
+pub fn estimate_withdrawal_fee(
+    config: &Config,
+    params: &CreateWithdrawal,
+) -> Result<WithdrawalQuote> {
+    let res = get_currency_networks(config)?;
+
+    let Some(networks) = res.result.filter(|_| res.code.unwrap_or(0) == 0) else {
+        return Err(ApiError::RequestFailed {
+            code: res.code.unwrap_or_default(),
+            message: res.message,
+        });
+    };
+
+    Ok(params.validate(&networks)?)
+}
+
+/// [`create_withdrawal`] after quoting it with [`estimate_withdrawal_fee`], the quoted fee is kept
+/// in [`CreateWithdrawalRes::expected_fee`] to reconcile against the fee charged.
+///
+/// # Errors
+///
+/// Will return the errors of [`estimate_withdrawal_fee`] without sending the withdrawal, otherwise
+/// the errors of [`create_withdrawal`].
+pub fn create_quoted_withdrawal(
+    config: &Config,
+    params: CreateWithdrawal,
+) -> Result<ApiResponse<CreateWithdrawalRes>> {
+    let quote = estimate_withdrawal_fee(config, &params)?;
+    let mut res = create_withdrawal(config, params)?;
+
+    if let Some(ref mut withdrawal) = res.result {
+        withdrawal.expected_fee = Some(quote.fee);
+    }
+
+    Ok(res)
+}
+
+/// Try to get the currency network data.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, `api_key`, or
+/// `secret_key`, or [`ApiError::Http`] if send fails or the response cannot be deserialized.
+pub fn get_currency_networks(config: &Config) -> Result<ApiResponse<CurrencyNetworks>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let Some(ref api_key) = config.api_key else {
+        return Err(ApiError::ConfigMissing("api_key".to_owned()));
+    };
+
+    let Some(ref secret) = config.secret_key else {
+        return Err(ApiError::ConfigMissing("secret_key".to_owned()));
+    };
+
+    let req = ApiRequestBuilder::default()
+        .with_id(0)
+        .with_method("private/get-currency-networks")
+        .with_api_key(api_key)
+        .with_nonce()
+        .with_digital_signature(secret)
+        .build()?;
+
+    let res = client
+        .post(format!("{rest_url}{}", req.method))
+        .json(&req)
+        .send()?
+        .json::<ApiResponse<CurrencyNetworks>>()?;
+
+    Ok(res)
+}
+
+/// Fetches withdrawal history. Withdrawal setting must be enabled for your
+/// API Key. If you do not see the option when viewing your API Keys, this
+/// feature is not yet available for you.
+///
+/// # Errors
+///
+/// Will return [`ApiError::InvalidTimeRange`] or [`ApiError::InvalidPageSize`] before sending
+/// if the params are invalid.
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, `api_key`, or
+/// `secret_key`, or [`ApiError::Http`] if send fails or the response cannot be deserialized.
+pub fn get_withdrawal_history(
+    config: &Config,
+    params: GetWithdrawalHistoryParams,
+) -> Result<ApiResponse<WithdrawalHistory>> {
+    params.validate()?;
+
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let Some(ref api_key) = config.api_key else {
+        return Err(ApiError::ConfigMissing("api_key".to_owned()));
+    };
+
+    let Some(ref secret) = config.secret_key else {
+        return Err(ApiError::ConfigMissing("secret_key".to_owned()));
+    };
+
+    let req = ApiRequestBuilder::default()
+        .with_id(0)
+        .with_method("private/get-withdrawal-history")
+        .with_params(params)
+        .with_api_key(api_key)
+        .with_nonce()
+        .with_digital_signature(secret)
+        .build()?;
+
+    let res = client
+        .post(format!("{rest_url}{}", req.method))
+        .json(&req)
+        .send()?
+        .json::<ApiResponse<WithdrawalHistory>>()?;
+
+    Ok(res)
+}
+
+/// Fetches deposit history. Withdrawal setting must be enabled for your
+/// API Key. If you do not see the option when viewing your API Keys, this
+/// feature is not yet available for you.
+///
+/// # Errors
+///
+/// Will return [`ApiError::InvalidTimeRange`] or [`ApiError::InvalidPageSize`] before sending
+/// if the params are invalid.
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, `api_key`, or
+/// `secret_key`, or [`ApiError::Http`] if send fails or the response cannot be deserialized.
+pub fn get_deposit_history(
+    config: &Config,
+    params: DepositHistoryParams,
+) -> Result<ApiResponse<DepositHistory>> {
+    params.validate()?;
+
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let Some(ref api_key) = config.api_key else {
+        return Err(ApiError::ConfigMissing("api_key".to_owned()));
+    };
+
+    let Some(ref secret) = config.secret_key else {
+        return Err(ApiError::ConfigMissing("secret_key".to_owned()));
+    };
+
+    let req = ApiRequestBuilder::default()
+        .with_id(0)
+        .with_method("private/get-deposit-history")
+        .with_params(params)
+        .with_api_key(api_key)
+        .with_nonce()
+        .with_digital_signature(secret)
+        .build()?;
+
+    let res = client
+        .post(format!("{rest_url}{}", req.method))
+        .json(&req)
+        .send()?
+        .json::<ApiResponse<DepositHistory>>()?;
+
+    Ok(res)
+}
+
+/// Fetches deposit address. Withdrawal setting must be enabled for your
+/// API Key. If you do not see the option when viewing your API Keys, this
+/// feature is not yet available for you.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, `api_key`, or
+/// `secret_key`, or [`ApiError::Http`] if send fails or the response cannot be deserialized.
+pub fn get_deposit_address(
+    config: &Config,
+    params: DepositAddressParams,
+) -> Result<ApiResponse<DepositAddress>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let Some(ref api_key) = config.api_key else {
+        return Err(ApiError::ConfigMissing("api_key".to_owned()));
+    };
+
+    let Some(ref secret) = config.secret_key else {
+        return Err(ApiError::ConfigMissing("secret_key".to_owned()));
+    };
+
+    let req = ApiRequestBuilder::default()
+        .with_id(0)
+        .with_method("private/get-deposit-address")
+        .with_params(params)
+        .with_api_key(api_key)
+        .with_nonce()
+        .with_digital_signature(secret)
+        .build()?;
+
+    let res = client
+        .post(format!("{rest_url}{}", req.method))
+        .json(&req)
+        .send()?
+        .json::<ApiResponse<DepositAddress>>()?;
+
+    Ok(res)
+}
+
+/// Creates a deposit address for a currency on a network, where crypto.com supports it, see
+/// [`CreateDepositAddressParams`]. Select from the existing ones with
+/// [`DepositAddress::select`] first.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, `api_key`, or
+/// `secret_key`, or [`ApiError::Http`] if send fails or the response cannot be deserialized.
+pub fn create_deposit_address(
+    config: &Config,
+    params: CreateDepositAddressParams,
+) -> Result<ApiResponse<DepositAddressItem>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let Some(ref api_key) = config.api_key else {
+        return Err(ApiError::ConfigMissing("api_key".to_owned()));
+    };
+
+    let Some(ref secret) = config.secret_key else {
+        return Err(ApiError::ConfigMissing("secret_key".to_owned()));
+    };
+
+    let req = ApiRequestBuilder::default()
+        .with_id(0)
+        .with_method("private/create-deposit-address")
+        .with_params(params)
+        .with_api_key(api_key)
+        .with_nonce()
+        .with_digital_signature(secret)
+        .build()?;
+
+    let res = client
+        .post(format!("{rest_url}{}", req.method))
+        .json(&req)
+        .send()?
+        .json::<ApiResponse<DepositAddressItem>>()?;
+
+    Ok(res)
+}
+
+/// Returns the account balance of a user for a particular token.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, `api_key`, or
+/// `secret_key`, or [`ApiError::Http`] if send fails or the response cannot be deserialized.
+pub fn get_account_summary(
+    config: &Config,
+    params: AccountSummaryParams,
+) -> Result<ApiResponse<AccountSummary>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let Some(ref api_key) = config.api_key else {
+        return Err(ApiError::ConfigMissing("api_key".to_owned()));
+    };
+
+    let Some(ref secret) = config.secret_key else {
+        return Err(ApiError::ConfigMissing("secret_key".to_owned()));
+    };
+
+    let req = ApiRequestBuilder::default()
+        .with_id(0)
+        .with_method("private/get-account-summary")
+        .with_params(params)
+        .with_api_key(api_key)
+        .with_nonce()
+        .with_digital_signature(secret)
+        .build()?;
+
+    let res = client
+        .post(format!("{rest_url}{}", req.method))
+        .json(&req)
+        .send()?
+        .json::<ApiResponse<AccountSummary>>()?;
+
+    Ok(res)
+}
+
+/// Quotes converting small balances to CRO, the quote is converted with [`convert_dust`] before
+/// it expires.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, `api_key`, or
+/// `secret_key`, or [`ApiError::Http`] if send fails or the response cannot be deserialized.
+pub fn get_dust_conversion_quote(
+    config: &Config,
+    params: DustConversionQuoteParams,
+) -> Result<ApiResponse<DustConversionQuote>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let Some(ref api_key) = config.api_key else {
+        return Err(ApiError::ConfigMissing("api_key".to_owned()));
+    };
+
+    let Some(ref secret) = config.secret_key else {
+        return Err(ApiError::ConfigMissing("secret_key".to_owned()));
+    };
+
+    let req = ApiRequestBuilder::default()
+        .with_id(0)
+        .with_method("private/get-dust-conversion-quote")
+        .with_params(params)
+        .with_api_key(api_key)
+        .with_nonce()
+        .with_digital_signature(secret)
+        .build()?;
+
+    let res = client
+        .post(format!("{rest_url}{}", req.method))
+        .json(&req)
+        .send()?
+        .json::<ApiResponse<DustConversionQuote>>()?;
+
+    Ok(res)
+}
+
+/// Converts the small balances of an unexpired quote from [`get_dust_conversion_quote`] to CRO,
+/// e.g. `convert_dust(&config, (&quote).into())`.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, `api_key`, or
+/// `secret_key`, or [`ApiError::Http`] if send fails or the response cannot be deserialized.
+pub fn convert_dust(
+    config: &Config,
+    params: ConvertDustParams,
+) -> Result<ApiResponse<DustConversion>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let Some(ref api_key) = config.api_key else {
+        return Err(ApiError::ConfigMissing("api_key".to_owned()));
+    };
+
+    let Some(ref secret) = config.secret_key else {
+        return Err(ApiError::ConfigMissing("secret_key".to_owned()));
+    };
+
+    let req = ApiRequestBuilder::default()
+        .with_id(0)
+        .with_method("private/convert-dust")
+        .with_params(params)
+        .with_api_key(api_key)
+        .with_nonce()
+        .with_digital_signature(secret)
+        .build()?;
+
+    let res = client
+        .post(format!("{rest_url}{}", req.method))
+        .json(&req)
+        .send()?
+        .json::<ApiResponse<DustConversion>>()?;
+
+    Ok(res)
+}
@@ -0,0 +1,553 @@
+//! Blocking public REST API functions, see [`crate::rest::public`].
+
+use crate::api_response::ApiResponse;
+use crate::error::Result;
+use crate::prelude::ApiError;
+use crate::rest::data::{
+    announcements::{Announcements, GetAnnouncementsParams},
+    book::{BookRes, RawBookRes},
+    candlestick::{CandlestickParams, CandlestickRes, RawCandlestickRes},
+    conversion_rate::{ConversionRate, ConversionRateParams, RawConversionRate},
+    expired_settlement_price::{
+        ExpiredSettlementPriceParams, ExpiredSettlementPriceRes, RawExpiredSettlementPriceRes,
+    },
+    instruments::{InstrumentsRes, RawInstrumentsRes},
+    insurance::{InsuranceParams, InsuranceRes, RawInsuranceRes},
+    ticker::{RawTickerRes, TickerRes, TickersRes},
+    trades::{RawTradesRes, TradesParams, TradesRes},
+    valuations::{GetValuationsParams, RawValuationsRes, ValuationType, ValuationsRes},
+};
+use crate::types::{Depth, Interval};
+use crate::utils::config::Config;
+
+/// Try to get the instrument data.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
+pub fn get_instruments(config: &Config) -> Result<ApiResponse<InstrumentsRes>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let res = client
+        .get(format!("{rest_url}public/get-instruments"))
+        .send()?
+        .json::<ApiResponse<RawInstrumentsRes>>()?;
+
+    Ok(ApiResponse {
+        id: res.id,
+        method: res.method,
+        result: if let Some(raw_instrument_res) = res.result {
+            Some(InstrumentsRes::try_from(raw_instrument_res)?)
+        } else {
+            None
+        },
+        code: res.code,
+        message: res.message,
+        original: res.original,
+        detail_code: res.detail_code,
+        detail_message: res.detail_message,
+    })
+}
+
+/// Try to get the book data, `depth` levels on each side.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
+pub fn get_book(
+    config: &Config,
+    instrument_name: String,
+    depth: Depth,
+) -> Result<ApiResponse<BookRes>> {
+    let client = super::client(config)?;
+
+    let params = [
+        ("instrument_name", &instrument_name),
+        ("depth", &depth.to_string()),
+    ];
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let res = client
+        .get(format!("{rest_url}public/get-book"))
+        .form(&params)
+        .send()?
+        .json::<ApiResponse<RawBookRes>>()?;
+
+    Ok(ApiResponse {
+        id: res.id,
+        method: res.method,
+        result: if let Some(ref res) = res.result {
+            Some(BookRes::try_from(res)?)
+        } else {
+            None
+        },
+        code: res.code,
+        message: res.message,
+        original: res.original,
+        detail_code: res.detail_code,
+        detail_message: res.detail_message,
+    })
+}
+
+/// Try to get the candlestick data.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
+pub fn get_candlestick(
+    config: &Config,
+    instrument_name: String,
+    timeframe: Interval,
+) -> Result<ApiResponse<CandlestickRes>> {
+    let client = super::client(config)?;
+
+    let params = [
+        ("instrument_name", instrument_name.as_str()),
+        ("timeframe", timeframe.as_rest_str()),
+    ];
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let res = client
+        .get(format!("{rest_url}public/get-candlestick"))
+        .form(&params)
+        .send()?
+        .json::<ApiResponse<RawCandlestickRes>>()?;
+
+    Ok(ApiResponse {
+        id: res.id,
+        method: res.method,
+        result: if let Some(ref res) = res.result {
+            Some(CandlestickRes::try_from(res)?)
+        } else {
+            None
+        },
+        code: res.code,
+        message: res.message,
+        original: res.original,
+        detail_code: res.detail_code,
+        detail_message: res.detail_message,
+    })
+}
+
+/// Try to get the candlestick data between `start_ts` and `end_ts`.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
+pub fn get_candlestick_range(
+    config: &Config,
+    params: &CandlestickParams,
+) -> Result<ApiResponse<CandlestickRes>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let res = client
+        .get(format!("{rest_url}public/get-candlestick"))
+        .query(params)
+        .send()?
+        .json::<ApiResponse<RawCandlestickRes>>()?;
+
+    Ok(ApiResponse {
+        id: res.id,
+        method: res.method,
+        result: if let Some(ref res) = res.result {
+            Some(CandlestickRes::try_from(res)?)
+        } else {
+            None
+        },
+        code: res.code,
+        message: res.message,
+        original: res.original,
+        detail_code: res.detail_code,
+        detail_message: res.detail_message,
+    })
+}
+
+/// Try to get the ticker data.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
+pub fn get_ticker(
+    config: &Config,
+    instrument_name: Option<String>,
+) -> Result<ApiResponse<TickerRes>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let mut res = client.get(format!("{rest_url}public/get-ticker"));
+
+    if let Some(instrument_name) = instrument_name {
+        let params = [("instrument_name", &instrument_name)];
+
+        res = res.form(&params);
+    }
+
+    let res = res
+        .send()?
+        .json::<ApiResponse<RawTickerRes>>()?;
+
+    Ok(ApiResponse {
+        id: res.id,
+        method: res.method,
+        result: if let Some(ref res) = res.result {
+            Some(TickerRes::try_from(res)?)
+        } else {
+            None
+        },
+        code: res.code,
+        message: res.message,
+        original: res.original,
+        detail_code: res.detail_code,
+        detail_message: res.detail_message,
+    })
+}
+
+/// Try to get the tickers of every instrument, by instrument name.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
+pub fn get_tickers(config: &Config) -> Result<ApiResponse<TickersRes>> {
+    let res = get_ticker(config, None)?;
+
+    Ok(ApiResponse {
+        id: res.id,
+        method: res.method,
+        result: res.result.map(TickersRes::from),
+        code: res.code,
+        message: res.message,
+        original: res.original,
+        detail_code: res.detail_code,
+        detail_message: res.detail_message,
+    })
+}
+
+/// Try to get the trades data.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
+pub fn get_trades(
+    config: &Config,
+    instrument_name: Option<String>,
+) -> Result<ApiResponse<TradesRes>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let mut res = client.get(format!("{rest_url}public/get-trades"));
+
+    if let Some(instrument_name) = instrument_name {
+        let params = [("instrument_name", &instrument_name)];
+
+        res = res.form(&params);
+    }
+
+    let res = res
+        .send()?
+        .json::<ApiResponse<RawTradesRes>>()?;
+
+    Ok(ApiResponse {
+        id: res.id,
+        method: res.method,
+        result: if let Some(ref res) = res.result {
+            Some(TradesRes::try_from(res)?)
+        } else {
+            None
+        },
+        code: res.code,
+        message: res.message,
+        original: res.original,
+        detail_code: res.detail_code,
+        detail_message: res.detail_message,
+    })
+}
+
+/// Try to get the trades between `start_ts` and `end_ts`, newest first, see
+/// [`crate::history::TradeDownloader`] for ranges with more trades than one request returns.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
+pub fn get_trades_range(config: &Config, params: &TradesParams) -> Result<ApiResponse<TradesRes>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let res = client
+        .get(format!("{rest_url}public/get-trades"))
+        .query(params)
+        .send()?
+        .json::<ApiResponse<RawTradesRes>>()?;
+
+    Ok(ApiResponse {
+        id: res.id,
+        method: res.method,
+        result: if let Some(ref res) = res.result {
+            Some(TradesRes::try_from(res)?)
+        } else {
+            None
+        },
+        code: res.code,
+        message: res.message,
+        original: res.original,
+        detail_code: res.detail_code,
+        detail_message: res.detail_message,
+    })
+}
+
+/// Try to get the index price, mark price, or funding rates of an instrument.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
+pub fn get_valuations(
+    config: &Config,
+    params: &GetValuationsParams,
+) -> Result<ApiResponse<ValuationsRes>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let res = client
+        .get(format!("{rest_url}public/get-valuations"))
+        .query(params)
+        .send()?
+        .json::<ApiResponse<RawValuationsRes>>()?;
+
+    Ok(ApiResponse {
+        id: res.id,
+        method: res.method,
+        result: if let Some(ref res) = res.result {
+            Some(ValuationsRes::try_from(res)?)
+        } else {
+            None
+        },
+        code: res.code,
+        message: res.message,
+        original: res.original,
+        detail_code: res.detail_code,
+        detail_message: res.detail_message,
+    })
+}
+
+/// Try to get the hourly funding rate history of a perpetual, e.g. BTCUSD-PERP, between
+/// `start_ts` and `end_ts` in ms since the UNIX epoch.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
+pub fn get_funding_rate_history(
+    config: &Config,
+    instrument_name: &str,
+    start_ts: Option<u64>,
+    end_ts: Option<u64>,
+) -> Result<ApiResponse<ValuationsRes>> {
+    get_valuations(
+        config,
+        &GetValuationsParams {
+            instrument_name: instrument_name.to_owned(),
+            valuation_type: ValuationType::FundingHist,
+            count: None,
+            start_ts,
+            end_ts,
+        },
+    )
+}
+
+/// Try to get the estimated funding rate for the next interval of a perpetual, e.g.
+/// BTCUSD-PERP.
+///
+/// See [`crate::rest::data::valuations::funding_payment`] for what it costs a position.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
+pub fn get_estimated_funding_rate(
+    config: &Config,
+    instrument_name: &str,
+) -> Result<ApiResponse<ValuationsRes>> {
+    get_valuations(
+        config,
+        &GetValuationsParams {
+            instrument_name: instrument_name.to_owned(),
+            valuation_type: ValuationType::EstimatedFundingRate,
+            count: Some(1),
+            start_ts: None,
+            end_ts: None,
+        },
+    )
+}
+
+/// Try to get the exchange announcements, including scheduled system maintenance.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
+pub fn get_announcements(
+    config: &Config,
+    params: &GetAnnouncementsParams,
+) -> Result<ApiResponse<Announcements>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let res = client
+        .get(format!("{rest_url}public/get-announcements"))
+        .query(params)
+        .send()?
+        .json::<ApiResponse<Announcements>>()?;
+
+    Ok(res)
+}
+
+/// Try to get the settlement prices of expired instruments.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
+pub fn get_expired_settlement_price(
+    config: &Config,
+    params: &ExpiredSettlementPriceParams,
+) -> Result<ApiResponse<ExpiredSettlementPriceRes>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let res = client
+        .get(format!("{rest_url}public/get-expired-settlement-price"))
+        .query(params)
+        .send()?
+        .json::<ApiResponse<RawExpiredSettlementPriceRes>>()?;
+
+    Ok(ApiResponse {
+        id: res.id,
+        method: res.method,
+        result: if let Some(ref res) = res.result {
+            Some(ExpiredSettlementPriceRes::try_from(res)?)
+        } else {
+            None
+        },
+        code: res.code,
+        message: res.message,
+        original: res.original,
+        detail_code: res.detail_code,
+        detail_message: res.detail_message,
+    })
+}
+
+/// Try to get the balance history of the insurance fund.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
+pub fn get_insurance(
+    config: &Config,
+    params: &InsuranceParams,
+) -> Result<ApiResponse<InsuranceRes>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let res = client
+        .get(format!("{rest_url}public/get-insurance"))
+        .query(params)
+        .send()?
+        .json::<ApiResponse<RawInsuranceRes>>()?;
+
+    Ok(ApiResponse {
+        id: res.id,
+        method: res.method,
+        result: if let Some(ref res) = res.result {
+            Some(InsuranceRes::try_from(res)?)
+        } else {
+            None
+        },
+        code: res.code,
+        message: res.message,
+        original: res.original,
+        detail_code: res.detail_code,
+        detail_message: res.detail_message,
+    })
+}
+
+/// Try to get the rate one unit of a liquid staking token, e.g. CDCETH, converts at.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
+pub fn get_conversion_rate(
+    config: &Config,
+    params: &ConversionRateParams,
+) -> Result<ApiResponse<ConversionRate>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let res = client
+        .get(format!("{rest_url}public/get-conversion-rate"))
+        .query(params)
+        .send()?
+        .json::<ApiResponse<RawConversionRate>>()?;
+
+    Ok(ApiResponse {
+        id: res.id,
+        method: res.method,
+        result: if let Some(ref res) = res.result {
+            Some(ConversionRate::try_from(res)?)
+        } else {
+            None
+        },
+        code: res.code,
+        message: res.message,
+        original: res.original,
+        detail_code: res.detail_code,
+        detail_message: res.detail_message,
+    })
+}
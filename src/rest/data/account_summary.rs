@@ -4,13 +4,16 @@ use serde::{Deserialize, Serialize};
 
 /// Account summary params.
 #[derive(Serialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct AccountSummaryParams {
     /// Specific currency, e.g. CRO. Omit for 'all'.
     pub currency: Option<String>,
 }
 
 /// Account data.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct Account {
     /// Total balance.
     pub balance: f64,
@@ -25,8 +28,11 @@ pub struct Account {
 }
 
 /// Account summary.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct AccountSummary {
     /// An array of accounts.
+    #[serde(default)]
     pub accounts: Vec<Account>,
 }
@@ -0,0 +1,171 @@
+//! Data from [public/get-announcements](https://exchange-docs.crypto.com/exchange/v1/rest-ws/index.html#public-get-announcements)
+
+use serde::{Deserialize, Serialize};
+
+/// Announcement category.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum AnnouncementCategory {
+    /// System maintenance and incidents.
+    System,
+    /// New listings.
+    List,
+    /// Delistings.
+    Delist,
+    /// Events.
+    Event,
+    /// Product updates.
+    Product,
+}
+
+/// Get announcements params.
+#[derive(Serialize, Debug, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct GetAnnouncementsParams {
+    /// Only announcements in this category.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<AnnouncementCategory>,
+    /// e.g. Spot, Derivative.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product_type: Option<String>,
+}
+
+/// How much of a service is affected by an announcement.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Impact {
+    /// Business as usual.
+    Bau,
+    /// Partially unavailable.
+    Partial,
+    /// Fully unavailable.
+    All,
+}
+
+/// The services affected by an announcement, `None` when not reported.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct ImpactedParams {
+    /// Spot trading.
+    pub spot_trading_impacted: Option<Impact>,
+    /// Derivative trading.
+    pub derivative_trading_impacted: Option<Impact>,
+    /// Margin trading.
+    pub margin_trading_impacted: Option<Impact>,
+    /// OTC trading.
+    pub otc_trading_impacted: Option<Impact>,
+    /// Convert.
+    pub convert_impacted: Option<Impact>,
+    /// Staking.
+    pub staking_impacted: Option<Impact>,
+    /// Trading bots.
+    pub trading_bot_impacted: Option<Impact>,
+    /// Crypto deposits and withdrawals.
+    pub crypto_wallet_impacted: Option<Impact>,
+    /// Fiat deposits and withdrawals.
+    pub fiat_wallet_impacted: Option<Impact>,
+    /// Logging in.
+    pub login_impacted: Option<Impact>,
+}
+
+/// A single announcement.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct Announcement {
+    /// Announcement ID.
+    pub id: String,
+    /// [`AnnouncementCategory`]
+    pub category: AnnouncementCategory,
+    /// Comma separated product types, e.g. Spot,Derivative.
+    pub product_type: String,
+    /// Time the announcement was published.
+    pub announced_at: u64,
+    /// Title.
+    pub title: String,
+    /// Content.
+    pub content: String,
+    /// e.g. BTCUSD-PERP, `None` when not about an instrument.
+    pub instrument_name: Option<String>,
+    /// [`ImpactedParams`]
+    #[serde(default)]
+    pub impacted_params: ImpactedParams,
+    /// Start of the announced window.
+    pub start_time: Option<u64>,
+    /// End of the announced window.
+    pub end_time: Option<u64>,
+}
+
+impl Announcement {
+    /// Whether this is a system announcement with a window that has not ended at `now`.
+    #[must_use]
+    pub fn is_pending_maintenance(&self, now: u64) -> bool {
+        self.category == AnnouncementCategory::System
+            && self.start_time.is_some()
+            && self.end_time.is_none_or(|end_time| end_time > now)
+    }
+
+    /// Whether the announced window contains `now`.
+    #[must_use]
+    pub fn is_active(&self, now: u64) -> bool {
+        self.start_time.is_some_and(|start_time| start_time <= now)
+            && self.end_time.is_none_or(|end_time| end_time > now)
+    }
+}
+
+/// Announcements response.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct Announcements {
+    /// [`Announcement`]
+    #[serde(default)]
+    pub data: Vec<Announcement>,
+}
+
+/// Scheduled and ongoing maintenance windows at a point in time.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct SystemStatus {
+    /// Time in ms since the UNIX epoch the status was checked at.
+    pub checked_at: u64,
+    /// System announcements whose window has not ended, ordered by start time.
+    pub maintenance: Vec<Announcement>,
+}
+
+impl SystemStatus {
+    /// The maintenance windows in `announcements` that have not ended at `now`.
+    #[must_use]
+    pub fn new(announcements: &[Announcement], now: u64) -> Self {
+        let mut maintenance: Vec<Announcement> = announcements
+            .iter()
+            .filter(|announcement| announcement.is_pending_maintenance(now))
+            .cloned()
+            .collect();
+        maintenance.sort_by_key(|announcement| announcement.start_time);
+
+        Self {
+            checked_at: now,
+            maintenance,
+        }
+    }
+
+    /// Whether a maintenance window is ongoing at `checked_at`.
+    #[must_use]
+    pub fn is_under_maintenance(&self) -> bool {
+        self.maintenance
+            .iter()
+            .any(|announcement| announcement.is_active(self.checked_at))
+    }
+
+    /// The start of the next maintenance window that has not begun yet.
+    #[must_use]
+    pub fn next_maintenance(&self) -> Option<u64> {
+        self.maintenance
+            .iter()
+            .filter_map(|announcement| announcement.start_time)
+            .find(|start_time| *start_time > self.checked_at)
+    }
+}
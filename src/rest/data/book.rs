@@ -1,8 +1,9 @@
 //! Data from [public/get-book](https://exchange-docs.crypto.com/spot/index.html#public-get-book)
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::prelude::ApiError;
+use crate::types::{parse_levels, EpochMs, Time};
 
 /// The raw book data response.
 ///
@@ -12,77 +13,53 @@ use crate::prelude::ApiError;
 ///     Number of Orders,
 /// )
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct RawBook {
     /// Array of level.
+    #[serde(default)]
     pub bids: Vec<(String, String, String)>,
     /// Array of level.
+    #[serde(default)]
     pub asks: Vec<(String, String, String)>,
-    /// Timestamp of the data.
+    /// Epoch millis of message publish.
     pub t: Option<u64>,
+    /// Epoch millis of last book update.
+    pub tt: Option<u64>,
 }
 
 /// The raw book response.
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct RawBookRes {
     /// e.g. BTC_USDT, ETH_CRO, etc.
     pub instrument_name: String,
     /// Number of bids and asks to return (up to 50).
     pub depth: u64,
     /// [`RawBook`]
+    #[serde(default)]
     pub data: Vec<RawBook>,
 }
 
-/// The processed data response.
-///
-/// Level: (
-///     Price,
-///     Quantity,
-///     Number of Orders,
-/// )
-#[derive(Debug, Default)]
-pub struct Book {
-    /// Array of level.
-    pub bids: Vec<(f64, f64, u64)>,
-    /// Array of level.
-    pub asks: Vec<(f64, f64, u64)>,
-    /// Timestamp of the data.
-    pub t: Option<u64>,
-}
+/// The processed data response, shared with the websocket API.
+pub use crate::types::Book;
 
 impl TryFrom<&RawBook> for Book {
     type Error = ApiError;
 
     fn try_from(value: &RawBook) -> Result<Self, Self::Error> {
-        let mut bids = vec![];
-
-        for bid in &value.bids {
-            bids.push((
-                bid.0.parse::<f64>()?,
-                bid.1.parse::<f64>()?,
-                bid.2.parse::<u64>()?,
-            ));
-        }
-
-        let mut asks = vec![];
-
-        for ask in &value.asks {
-            asks.push((
-                ask.0.parse::<f64>()?,
-                ask.1.parse::<f64>()?,
-                ask.2.parse::<u64>()?,
-            ));
-        }
-
         Ok(Self {
-            bids,
-            asks,
-            t: value.t,
+            bids: parse_levels(&value.bids)?,
+            asks: parse_levels(&value.asks)?,
+            t: value.t.map(Time::from_epoch_ms),
+            tt: value.tt.map(Time::from_epoch_ms),
+            ..Self::default()
         })
     }
 }
 
 /// The processed book response.
-#[derive(Debug, Default)]
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct BookRes {
     /// e.g. BTC_USDT, ETH_CRO, etc.
     pub instrument_name: String,
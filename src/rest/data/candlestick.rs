@@ -1,11 +1,42 @@
 //! Data from [public/get-candlestick](https://exchange-docs.crypto.com/spot/index.html#public-get-candlestick)
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize, Serializer};
 
 use crate::prelude::ApiError;
+use crate::types::{EpochMs, Interval, Time};
+
+/// Get candlestick params for a time range.
+#[derive(Serialize, Clone, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CandlestickParams {
+    /// e.g. ETH_CRO, BTC_USDT.
+    pub instrument_name: String,
+    /// [`Interval`]
+    #[serde(serialize_with = "serialize_timeframe")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    pub timeframe: Interval,
+    /// Number of candles (Default: 25, Max: 300).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<u64>,
+    /// Start time in ms since the UNIX epoch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_ts: Option<u64>,
+    /// End time in ms since the UNIX epoch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_ts: Option<u64>,
+}
+
+/// Serialize `timeframe` with its REST name.
+fn serialize_timeframe<S: Serializer>(
+    timeframe: &Interval,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(timeframe.as_rest_str())
+}
 
 /// The raw candlestick data response.
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct RawCandlestick {
     /// End time of candlestick (Unix timestamp).
     pub t: u64,
@@ -23,38 +54,27 @@ pub struct RawCandlestick {
 
 /// The raw candlestick response.
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct RawCandlestickRes {
     /// e.g. ETH_CRO, BTC_USDT.
     pub instrument_name: String,
     /// The period (e.g. 5m).
     pub interval: String,
     /// [`RawCandlestick`]
+    #[serde(default)]
     pub data: Vec<RawCandlestick>,
 }
 
-/// The processed candlestick data response.
-#[derive(Debug, Default)]
-pub struct Candlestick {
-    /// End time of candlestick (Unix timestamp).
-    pub t: u64,
-    /// Open.
-    pub o: f64,
-    /// High.
-    pub h: f64,
-    /// Low.
-    pub l: f64,
-    /// Close.
-    pub c: f64,
-    /// Volume.
-    pub v: f64,
-}
+/// The processed candlestick data response, shared with the websocket API.
+pub use crate::types::Candlestick;
 
 impl TryFrom<&RawCandlestick> for Candlestick {
     type Error = ApiError;
 
     fn try_from(value: &RawCandlestick) -> Result<Self, Self::Error> {
         Ok(Self {
-            t: value.t,
+            t: Time::from_epoch_ms(value.t),
+            ut: None,
             o: value.o.parse::<f64>()?,
             h: value.h.parse::<f64>()?,
             l: value.l.parse::<f64>()?,
@@ -65,7 +85,8 @@ impl TryFrom<&RawCandlestick> for Candlestick {
 }
 
 /// The processed candlestick response.
-#[derive(Debug, Default)]
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct CandlestickRes {
     /// e.g. ETH_CRO, BTC_USDT.
     pub instrument_name: String,
@@ -0,0 +1,44 @@
+//! Data from [public/get-conversion-rate](https://exchange-docs.crypto.com/exchange/v1/rest-ws/index.html#public-get-conversion-rate)
+
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::ApiError;
+
+/// Get conversion rate params.
+#[derive(Serialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ConversionRateParams {
+    /// e.g. CDCETH.
+    pub instrument_name: String,
+}
+
+/// The raw conversion rate response.
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct RawConversionRate {
+    /// e.g. CDCETH.
+    pub instrument_name: String,
+    /// Units of the underlying currency one unit converts to.
+    pub conversion_rate: String,
+}
+
+/// The processed conversion rate response.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ConversionRate {
+    /// e.g. CDCETH.
+    pub instrument_name: String,
+    /// Units of the underlying currency one unit converts to.
+    pub conversion_rate: f64,
+}
+
+impl TryFrom<&RawConversionRate> for ConversionRate {
+    type Error = ApiError;
+
+    fn try_from(value: &RawConversionRate) -> Result<Self, Self::Error> {
+        Ok(Self {
+            instrument_name: value.instrument_name.clone(),
+            conversion_rate: value.conversion_rate.parse::<f64>()?,
+        })
+    }
+}
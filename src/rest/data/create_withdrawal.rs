@@ -1,9 +1,13 @@
 //! Data from [private/create-withdrawal](https://exchange-docs.crypto.com/spot/index.html#private-create-withdrawal)
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{deserialize_time, Time};
 
 /// Create withdrawal return values.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct CreateWithdrawalRes {
     /// Newly created withdrawal ID.
     pub id: u64,
@@ -18,5 +22,19 @@ pub struct CreateWithdrawalRes {
     /// Address with Address Tag (if any).
     pub address: Option<String>,
     /// Create time.
-    pub create_time: u64,
+    #[serde(deserialize_with = "deserialize_time")]
+    pub create_time: Time,
+    /// Fee quoted from `private/get-currency-networks` when sent with
+    /// [`crate::rest::private::create_quoted_withdrawal`], `None` otherwise.
+    #[serde(skip)]
+    pub expected_fee: Option<f64>,
+}
+
+impl CreateWithdrawalRes {
+    /// How much more the withdrawal was charged than quoted, `None` if there was no quote.
+    #[must_use]
+    pub fn fee_difference(&self) -> Option<f64> {
+        self.expected_fee
+            .map(|expected_fee| self.fee - expected_fee)
+    }
 }
@@ -0,0 +1,100 @@
+//! Currencies listed by [public/get-instruments](https://exchange-docs.crypto.com/spot/index.html#public-get-instruments)
+//!
+//! The exchange has no public currency listing, so currencies are gathered from the instruments
+//! that trade them. Display names are only known from `private/get-currency-networks`, see
+//! [`Currencies::with_networks`].
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::rest::data::{CurrencyNetworks, InstrumentsRes};
+
+/// One currency traded on the exchange.
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Currency {
+    /// e.g. BTC.
+    pub currency: String,
+    /// e.g. Bitcoin, `None` unless filled from currency networks.
+    pub display_name: Option<String>,
+    /// Most quantity decimal places of the instruments it is the base of, `None` if it is only
+    /// ever quoted.
+    pub decimals: Option<u8>,
+    /// Instruments it is the base of, e.g. BTC_USDT.
+    pub base_of: Vec<String>,
+    /// Instruments it is the quote of, e.g. ETH_BTC.
+    pub quote_of: Vec<String>,
+}
+
+/// Every currency of `public/get-instruments`.
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Currencies {
+    /// [`Currency`] by currency.
+    pub currencies: BTreeMap<String, Currency>,
+}
+
+impl From<&InstrumentsRes> for Currencies {
+    fn from(value: &InstrumentsRes) -> Self {
+        let mut currencies = BTreeMap::<String, Currency>::new();
+
+        for instrument in &value.instruments {
+            let base = currencies
+                .entry(instrument.base_currency.clone())
+                .or_insert_with(|| Currency {
+                    currency: instrument.base_currency.clone(),
+                    ..Currency::default()
+                });
+
+            base.base_of.push(instrument.instrument_name.clone());
+            base.decimals = base.decimals.max(Some(instrument.quantity_decimals));
+
+            currencies
+                .entry(instrument.quote_currency.clone())
+                .or_insert_with(|| Currency {
+                    currency: instrument.quote_currency.clone(),
+                    ..Currency::default()
+                })
+                .quote_of
+                .push(instrument.instrument_name.clone());
+        }
+
+        Self { currencies }
+    }
+}
+
+impl Currencies {
+    /// Fill the display names from `networks`, currencies it does not list keep theirs.
+    #[must_use]
+    pub fn with_networks(mut self, networks: &CurrencyNetworks) -> Self {
+        for (name, currency) in &mut self.currencies {
+            if let Some(map) = networks.currency_map.get(name) {
+                currency.display_name = Some(map.full_name.clone());
+            }
+        }
+
+        self
+    }
+
+    /// The currency named `currency`, `None` if no instrument trades it.
+    #[must_use]
+    pub fn get(&self, currency: &str) -> Option<&Currency> {
+        self.currencies.get(currency)
+    }
+
+    /// Every currency, in alphabetical order.
+    pub fn iter(&self) -> impl Iterator<Item = &Currency> {
+        self.currencies.values()
+    }
+
+    /// Number of currencies.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.currencies.len()
+    }
+
+    /// Whether there are no currencies.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.currencies.is_empty()
+    }
+}
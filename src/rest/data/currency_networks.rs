@@ -2,10 +2,15 @@
 
 use std::collections::HashMap;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use crate::error::WithdrawalError;
+use crate::types::{deserialize_time, Time};
 
 /// Currency network.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct CurrencyNetwork {
     /// The network id, can be used in `create-withdrawal`.
     pub network_id: String,
@@ -22,7 +27,9 @@ pub struct CurrencyNetwork {
 }
 
 /// Currency map.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct CurrencyMap {
     /// e.g. SHIBA INU
     pub full_name: String,
@@ -30,14 +37,105 @@ pub struct CurrencyMap {
     /// there is more than 1 network available.
     pub default_network: String,
     /// A list of networks.
+    #[serde(default)]
     pub network_list: Vec<CurrencyNetwork>,
 }
 
 /// Currency Networks.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct CurrencyNetworks {
     /// Update time.
-    pub update_time: u64,
+    #[serde(deserialize_with = "deserialize_time")]
+    pub update_time: Time,
     /// Currency map.
     pub currency_map: HashMap<String, CurrencyMap>,
 }
+
+/// The outcome of a withdrawal that passed [`CurrencyNetworks::validate_withdrawal`].
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct WithdrawalQuote {
+    /// The network the withdrawal will be sent on.
+    pub network_id: String,
+    /// Expected withdrawal fee, 0 if the network has none.
+    pub fee: f64,
+    /// Amount that will arrive after the fee is taken.
+    pub amount_received: f64,
+}
+
+impl CurrencyMap {
+    /// Get the network for `network_id`, or the network the exchange will pick when it is `None`.
+    #[must_use]
+    pub fn network(&self, network_id: Option<&str>) -> Option<&CurrencyNetwork> {
+        match network_id {
+            Some(network_id) => self
+                .network_list
+                .iter()
+                .find(|network| network.network_id == network_id),
+            None => {
+                let only = match self.network_list.as_slice() {
+                    [network] => Some(network),
+                    _ => None,
+                };
+
+                self.network_list
+                    .iter()
+                    .find(|network| network.network_id == self.default_network)
+                    .or(only)
+            }
+        }
+    }
+}
+
+impl CurrencyNetworks {
+    /// Check a withdrawal of `amount` `currency` on `network_id` before it is signed and sent.
+    ///
+    /// When `network_id` is `None` the currency's default network is used, as the exchange does.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`WithdrawalError`] if the currency or network is unknown, withdrawals are
+    /// disabled on the network, or `amount` is below the network's minimum withdrawal amount.
+    pub fn validate_withdrawal(
+        &self,
+        currency: &str,
+        amount: f64,
+        network_id: Option<&str>,
+    ) -> Result<WithdrawalQuote, WithdrawalError> {
+        let Some(currency_map) = self.currency_map.get(currency) else {
+            return Err(WithdrawalError::UnknownCurrency(currency.to_owned()));
+        };
+
+        let Some(network) = currency_map.network(network_id) else {
+            return Err(WithdrawalError::UnknownNetwork {
+                currency: currency.to_owned(),
+                network_id: network_id
+                    .unwrap_or(&currency_map.default_network)
+                    .to_owned(),
+            });
+        };
+
+        if !network.withdrawal_enabled {
+            return Err(WithdrawalError::WithdrawalDisabled {
+                currency: currency.to_owned(),
+                network_id: network.network_id.clone(),
+            });
+        }
+
+        if amount < network.min_withdrawal_amount {
+            return Err(WithdrawalError::BelowMinimum {
+                amount,
+                min_withdrawal_amount: network.min_withdrawal_amount,
+            });
+        }
+
+        let fee = network.withdrawal_fee.unwrap_or(0.0);
+
+        Ok(WithdrawalQuote {
+            network_id: network.network_id.clone(),
+            fee,
+            amount_received: amount - fee,
+        })
+    }
+}
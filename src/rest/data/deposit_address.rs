@@ -1,16 +1,35 @@
 //! Data from [private/get-deposit-address](https://exchange-docs.crypto.com/spot/index.html#private-get-deposit-address)
+//! and `private/create-deposit-address`.
 
 use serde::{Deserialize, Serialize};
 
+use crate::types::{deserialize_time, Time};
+
 /// Deposit address params.
 #[derive(Serialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct DepositAddressParams {
     /// e.g. BTC, CRO.
     pub currency: String,
 }
 
+/// Create deposit address params.
+///
+/// Not every currency and network lets an address be created, crypto.com replies with an error
+/// code for those.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CreateDepositAddressParams {
+    /// e.g. BTC, CRO.
+    pub currency: String,
+    /// e.g. ETH, CRO, the default network of the currency when not set.
+    pub network: Option<String>,
+}
+
 /// Deposit address item.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct DepositAddressItem {
     /// Newly created deposit ID.
     pub id: usize,
@@ -24,7 +43,8 @@ pub struct DepositAddressItem {
     /// Address with Address Tag (if any).
     pub address: Option<String>,
     /// Create time.
-    pub create_time: u64,
+    #[serde(deserialize_with = "deserialize_time")]
+    pub create_time: Time,
     /// "0"
     ///
     /// 0 - Inactive.
@@ -33,8 +53,100 @@ pub struct DepositAddressItem {
 }
 
 /// Deposit address.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct DepositAddress {
     /// Array of deposit address items.
+    #[serde(default)]
     pub deposit_address_list: Vec<DepositAddressItem>,
 }
+
+/// URI schemes of the networks that have one, others use the network in lower case.
+const URI_SCHEMES: &[(&str, &str)] = &[
+    ("BTC", "bitcoin"),
+    ("BCH", "bitcoincash"),
+    ("LTC", "litecoin"),
+    ("DOGE", "dogecoin"),
+    ("ETH", "ethereum"),
+];
+
+impl DepositAddressItem {
+    /// Whether the address can be deposited to.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.status == "1"
+    }
+
+    /// The address without its tag, crypto.com appends the tag after a `?`.
+    #[must_use]
+    pub fn base_address(&self) -> Option<&str> {
+        let address = self.address.as_deref()?;
+
+        Some(
+            address
+                .split_once('?')
+                .map_or(address, |(address, _)| address),
+        )
+    }
+
+    /// The tag of the address, also known as memo, for coins like XRP and XLM.
+    #[must_use]
+    pub fn address_tag(&self) -> Option<&str> {
+        let (_, tag) = self.address.as_deref()?.split_once('?')?;
+
+        Some(tag.split_once('=').map_or(tag, |(_, tag)| tag))
+    }
+
+    /// A payment URI for the address, e.g. `bitcoin:bc1q...?amount=0.5`, to give to a wallet or
+    /// encode as a QR code.
+    ///
+    /// The tag is kept as crypto.com sent it, `amount` is added when set. `None` if there is no
+    /// address.
+    #[must_use]
+    pub fn payment_uri(&self, amount: Option<f64>) -> Option<String> {
+        let address = self.address.as_deref()?;
+        let scheme = URI_SCHEMES
+            .iter()
+            .find(|&&(network, _)| network.eq_ignore_ascii_case(&self.network))
+            .map_or_else(
+                || self.network.to_lowercase(),
+                |&(_, scheme)| scheme.to_owned(),
+            );
+
+        let mut uri = format!("{scheme}:{address}");
+
+        if let Some(amount) = amount {
+            uri.push(if address.contains('?') { '&' } else { '?' });
+            uri.push_str(&format!("amount={amount}"));
+        }
+
+        Some(uri)
+    }
+}
+
+impl DepositAddress {
+    /// The addresses on `network`, e.g. `ETH` for ERC20 addresses.
+    pub fn on_network<'a>(
+        &'a self,
+        network: &'a str,
+    ) -> impl Iterator<Item = &'a DepositAddressItem> + 'a {
+        self.deposit_address_list
+            .iter()
+            .filter(move |item| item.network.eq_ignore_ascii_case(network))
+    }
+
+    /// The address of `currency` to deposit to on `network`, an active one if there is one, the
+    /// newest first.
+    #[must_use]
+    pub fn select(&self, currency: &str, network: &str) -> Option<&DepositAddressItem> {
+        self.deposit_address_list
+            .iter()
+            .filter(|item| {
+                item.network.eq_ignore_ascii_case(network)
+                    && item.currency.eq_ignore_ascii_case(currency)
+                    && item.address.is_some()
+            })
+            .max_by_key(|item| (item.is_active(), item.create_time))
+    }
+}
@@ -2,26 +2,41 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::prelude::ApiError;
+use crate::types::{deserialize_time, DepositStatus, PageQuery, Time, TimeRange};
+
 /// Deposit history params.
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Clone, Debug, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct DepositHistoryParams {
     /// e.g. BTC, CRO.
     pub currency: Option<String>,
-    /// Default is 90 days from current timestamp.
-    pub start_ts: Option<u64>,
-    /// Default is current timestamp.
-    pub end_ts: Option<u64>,
-    /// Page size (Default: 20, Max: 200).
-    pub page_size: Option<u64>,
-    /// 0 - Not Arrived.
-    /// 1 - Arrived.
-    /// 2 - Failed.
-    /// 3 - Pending.
-    pub status: Option<String>,
+    /// Defaults to the last 90 days.
+    #[serde(flatten)]
+    pub range: TimeRange,
+    /// Defaults to the first page of 20.
+    #[serde(flatten)]
+    pub page: PageQuery,
+    /// Deposit status, filters the history when set.
+    pub status: Option<DepositStatus>,
+}
+
+impl DepositHistoryParams {
+    /// Check the time range and the page size.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::InvalidTimeRange`] or [`ApiError::InvalidPageSize`].
+    pub fn validate(&self) -> Result<(), ApiError> {
+        self.range.validate()?;
+        self.page.validate()
+    }
 }
 
 /// Deposit history item.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct DepositHistoryItem {
     /// Newly created deposit ID.
     pub id: u64,
@@ -34,17 +49,18 @@ pub struct DepositHistoryItem {
     /// Address with Address Tag (if any).
     pub address: String,
     /// Create time.
-    pub create_time: u64,
-    /// 0 - Not Arrived.
-    /// 1 - Arrived.
-    /// 2 - Failed.
-    /// 3 - Pending.
-    pub status: String,
+    #[serde(deserialize_with = "deserialize_time")]
+    pub create_time: Time,
+    /// Deposit status, see [`DepositStatus`].
+    pub status: DepositStatus,
 }
 
 /// Deposit history.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct DepositHistory {
     /// Deposit list.
+    #[serde(default)]
     pub deposit_list: Vec<DepositHistoryItem>,
 }
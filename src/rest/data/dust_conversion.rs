@@ -0,0 +1,94 @@
+//! Data from [private/get-dust-conversion-quote](https://exchange-docs.crypto.com/spot/index.html#private-get-dust-conversion-quote)
+//! and [private/convert-dust](https://exchange-docs.crypto.com/spot/index.html#private-convert-dust)
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{deserialize_time, Time};
+
+/// Dust conversion quote params.
+#[derive(Serialize, Clone, Debug, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DustConversionQuoteParams {
+    /// Currencies to convert, e.g. BTC, ETH. Omit for every balance small enough to convert.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub currencies: Vec<String>,
+}
+
+/// One small balance in a [`DustConversionQuote`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct DustConversionItem {
+    /// e.g. BTC, ETH.
+    pub currency: String,
+    /// Balance converted.
+    pub quantity: f64,
+    /// Amount of the target currency recieved, after the fee.
+    pub target_quantity: f64,
+    /// Fee in the target currency.
+    pub fee: f64,
+}
+
+/// Dust conversion quote, valid until `expire_time`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct DustConversionQuote {
+    /// ID to pass to [`crate::rest::private::convert_dust`].
+    pub quote_id: String,
+    /// Currency the balances are converted to, e.g. CRO.
+    pub target_currency: String,
+    /// Total of the target currency recieved, after fees.
+    pub target_quantity: f64,
+    /// Balances that would be converted.
+    #[serde(default)]
+    pub items: Vec<DustConversionItem>,
+    /// The quote can no longer be converted after this.
+    #[serde(deserialize_with = "deserialize_time")]
+    pub expire_time: Time,
+}
+
+/// Convert dust params.
+#[derive(Serialize, Clone, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ConvertDustParams {
+    /// [`DustConversionQuote::quote_id`] of an unexpired quote.
+    pub quote_id: String,
+}
+
+impl From<&DustConversionQuote> for ConvertDustParams {
+    fn from(value: &DustConversionQuote) -> Self {
+        Self {
+            quote_id: value.quote_id.clone(),
+        }
+    }
+}
+
+/// Result of a dust conversion.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct DustConversion {
+    /// Newly created conversion ID.
+    pub conversion_id: String,
+    /// Quote that was converted.
+    pub quote_id: String,
+    /// e.g. CRO.
+    pub target_currency: String,
+    /// Total of the target currency credited, after fees.
+    pub target_quantity: f64,
+    /// Balances that were converted.
+    #[serde(default)]
+    pub items: Vec<DustConversionItem>,
+    /// Create time.
+    #[serde(deserialize_with = "deserialize_time")]
+    pub create_time: Time,
+}
+
+impl DustConversion {
+    /// Total fee charged in the target currency.
+    #[must_use]
+    pub fn total_fee(&self) -> f64 {
+        self.items.iter().map(|item| item.fee).sum()
+    }
+}
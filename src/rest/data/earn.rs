@@ -0,0 +1,227 @@
+//! Data from the earn endpoints, see [`crate::rest::earn`].
+
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::ApiError;
+use crate::types::{deserialize_optional_time, deserialize_time, PageQuery, Time, TimeRange};
+
+/// How long an earn product holds the allocated amount.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EarnTerm {
+    /// Redeemable at any time.
+    Flexible,
+    /// Held until the end of the term, see [`EarnProduct::duration_days`].
+    Fixed,
+}
+
+/// Status of an earn position.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EarnPositionStatus {
+    /// Earning yield.
+    Active,
+    /// A redemption was requested and is being processed.
+    Redeeming,
+    /// Fully redeemed, no longer earning.
+    Redeemed,
+}
+
+/// Get earn products params.
+#[derive(Serialize, Clone, Debug, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct EarnProductsParams {
+    /// e.g. BTC, CRO. Omit for every currency.
+    pub currency: Option<String>,
+}
+
+/// An earn product amounts can be allocated to.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct EarnProduct {
+    /// ID to allocate to with [`crate::rest::earn::allocate`].
+    pub product_id: String,
+    /// e.g. BTC, CRO.
+    pub currency: String,
+    /// Flexible or fixed term.
+    pub term: EarnTerm,
+    /// Length of a fixed term in days, `None` for a flexible product.
+    pub duration_days: Option<u32>,
+    /// Annual percentage rate, e.g. `0.05` for 5%.
+    pub apr: f64,
+    /// Least amount that can be allocated.
+    pub min_amount: f64,
+    /// Most amount that can be allocated, `None` if there is no limit.
+    pub max_amount: Option<f64>,
+    /// Whether the product currently accepts allocations.
+    pub available: bool,
+}
+
+impl EarnProduct {
+    /// Whether `amount` can be allocated to the product.
+    #[must_use]
+    pub fn accepts(&self, amount: f64) -> bool {
+        self.available
+            && amount >= self.min_amount
+            && self
+                .max_amount
+                .is_none_or(|max_amount| amount <= max_amount)
+    }
+}
+
+/// Earn products.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct EarnProducts {
+    /// Array of products.
+    #[serde(default)]
+    pub products: Vec<EarnProduct>,
+}
+
+impl EarnProducts {
+    /// The available product of `currency` and `term` with the highest APR.
+    #[must_use]
+    pub fn best(&self, currency: &str, term: EarnTerm) -> Option<&EarnProduct> {
+        self.products
+            .iter()
+            .filter(|product| {
+                product.available && product.currency == currency && product.term == term
+            })
+            .max_by(|a, b| a.apr.total_cmp(&b.apr))
+    }
+}
+
+/// Get earn positions params.
+#[derive(Serialize, Clone, Debug, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct EarnPositionsParams {
+    /// e.g. BTC, CRO. Omit for every currency.
+    pub currency: Option<String>,
+}
+
+/// An amount allocated to an earn product.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct EarnPosition {
+    /// ID to redeem with [`crate::rest::earn::redeem`].
+    pub position_id: String,
+    /// Product the amount is allocated to.
+    pub product_id: String,
+    /// e.g. BTC, CRO.
+    pub currency: String,
+    /// Amount allocated.
+    pub amount: f64,
+    /// Yield earned and not yet paid out.
+    pub accrued_interest: f64,
+    /// Annual percentage rate the position earns.
+    pub apr: f64,
+    /// Status of the position.
+    pub status: EarnPositionStatus,
+    /// When the amount was allocated.
+    #[serde(deserialize_with = "deserialize_time")]
+    pub start_time: Time,
+    /// End of a fixed term, `None` for a flexible product.
+    #[serde(default, deserialize_with = "deserialize_optional_time")]
+    pub end_time: Option<Time>,
+}
+
+/// Earn positions.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct EarnPositions {
+    /// Array of positions.
+    #[serde(default)]
+    pub positions: Vec<EarnPosition>,
+}
+
+/// Allocate params.
+#[derive(Serialize, Clone, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct AllocateParams {
+    /// [`EarnProduct::product_id`].
+    pub product_id: String,
+    /// Amount of the product's currency to allocate.
+    pub amount: f64,
+}
+
+/// Redeem params.
+#[derive(Serialize, Clone, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RedeemParams {
+    /// [`EarnPosition::position_id`].
+    pub position_id: String,
+    /// Amount to redeem, omit to redeem the whole position.
+    pub amount: Option<f64>,
+}
+
+/// Get earn yield history params.
+#[derive(Serialize, Clone, Debug, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct EarnYieldHistoryParams {
+    /// e.g. BTC, CRO. Omit for every currency.
+    pub currency: Option<String>,
+    /// Defaults to the last 90 days.
+    #[serde(flatten)]
+    pub range: TimeRange,
+    /// Defaults to the first page of 20.
+    #[serde(flatten)]
+    pub page: PageQuery,
+}
+
+impl EarnYieldHistoryParams {
+    /// Check the time range and the page size.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::InvalidTimeRange`] or [`ApiError::InvalidPageSize`].
+    pub fn validate(&self) -> Result<(), ApiError> {
+        self.range.validate()?;
+        self.page.validate()
+    }
+}
+
+/// One yield payment of a position.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct EarnYield {
+    /// Position the yield was paid for.
+    pub position_id: String,
+    /// Product of the position.
+    pub product_id: String,
+    /// e.g. BTC, CRO.
+    pub currency: String,
+    /// Amount paid.
+    pub amount: f64,
+    /// When the yield was paid.
+    #[serde(deserialize_with = "deserialize_time")]
+    pub time: Time,
+}
+
+/// Earn yield history.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct EarnYieldHistory {
+    /// Array of yield payments.
+    #[serde(default)]
+    pub yield_list: Vec<EarnYield>,
+}
+
+impl EarnYieldHistory {
+    /// Total yield paid in `currency`.
+    #[must_use]
+    pub fn total(&self, currency: &str) -> f64 {
+        self.yield_list
+            .iter()
+            .filter(|payment| payment.currency == currency)
+            .map(|payment| payment.amount)
+            .sum()
+    }
+}
@@ -0,0 +1,103 @@
+//! Data from [public/get-expired-settlement-price](https://exchange-docs.crypto.com/exchange/v1/rest-ws/index.html#public-get-expired-settlement-price)
+
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::ApiError;
+use crate::types::{EpochMs, InstrumentType, Time};
+
+/// Get expired settlement price params.
+#[derive(Serialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ExpiredSettlementPriceParams {
+    /// Usually [`InstrumentType::Future`].
+    pub instrument_type: InstrumentType,
+    /// Page number (Default: 1).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u64>,
+}
+
+/// The raw settlement price data response.
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct RawSettlementPrice {
+    /// Instrument name.
+    pub i: String,
+    /// Expiry timestamp.
+    pub x: u64,
+    /// Settlement price.
+    pub v: String,
+    /// Timestamp.
+    pub t: u64,
+}
+
+/// The raw expired settlement price response.
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct RawExpiredSettlementPriceRes {
+    /// [`RawSettlementPrice`]
+    #[serde(default)]
+    pub data: Vec<RawSettlementPrice>,
+}
+
+/// The processed settlement price data response.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SettlementPrice {
+    /// Instrument name.
+    pub i: String,
+    /// Expiry timestamp.
+    pub x: u64,
+    /// Settlement price.
+    pub v: f64,
+    /// Timestamp.
+    pub t: Time,
+}
+
+impl TryFrom<&RawSettlementPrice> for SettlementPrice {
+    type Error = ApiError;
+
+    fn try_from(value: &RawSettlementPrice) -> Result<Self, Self::Error> {
+        Ok(Self {
+            i: value.i.clone(),
+            x: value.x,
+            v: value.v.parse::<f64>()?,
+            t: Time::from_epoch_ms(value.t),
+        })
+    }
+}
+
+/// The processed expired settlement price response.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ExpiredSettlementPriceRes {
+    /// [`SettlementPrice`]
+    pub data: Vec<SettlementPrice>,
+}
+
+impl TryFrom<&RawExpiredSettlementPriceRes> for ExpiredSettlementPriceRes {
+    type Error = ApiError;
+
+    fn try_from(value: &RawExpiredSettlementPriceRes) -> Result<Self, Self::Error> {
+        let mut data = vec![];
+
+        for price in &value.data {
+            data.push(SettlementPrice::try_from(price)?);
+        }
+
+        Ok(Self { data })
+    }
+}
+
+impl TryFrom<RawExpiredSettlementPriceRes> for ExpiredSettlementPriceRes {
+    type Error = ApiError;
+
+    fn try_from(value: RawExpiredSettlementPriceRes) -> Result<Self, Self::Error> {
+        let mut data = vec![];
+
+        for price in &value.data {
+            data.push(SettlementPrice::try_from(price)?);
+        }
+
+        Ok(Self { data })
+    }
+}
@@ -1,11 +1,15 @@
 //! Data from [public/get-instruments](https://exchange-docs.crypto.com/spot/index.html#public-get-instruments)
 
-use serde::Deserialize;
+use std::collections::HashMap;
 
+use serde::{Deserialize, Serialize};
+
+use crate::error::OrderError;
 use crate::prelude::ApiError;
 
 /// The raw instrument data response.
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct RawInstrument {
     /// e.g. BTC_USDT.
     pub instrument_name: String,
@@ -41,13 +45,16 @@ pub struct RawInstrument {
 
 /// The raw instrument response.
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct RawInstrumentsRes {
     /// [`RawInstrument`]
+    #[serde(default)]
     pub instruments: Vec<RawInstrument>,
 }
 
 /// The processed instrument data response.
-#[derive(Debug, Default)]
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Instrument {
     /// e.g. BTC_USDT.
     pub instrument_name: String,
@@ -106,7 +113,8 @@ impl TryFrom<&RawInstrument> for Instrument {
 }
 
 /// The instrument response.
-#[derive(Debug, Default)]
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct InstrumentsRes {
     /// [`Instrument`]
     pub instruments: Vec<Instrument>,
@@ -139,3 +147,215 @@ impl TryFrom<RawInstrumentsRes> for InstrumentsRes {
         Ok(Self { instruments })
     }
 }
+
+/// The price and quantity rules of one instrument.
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct InstrumentRule {
+    /// Maximum decimal places for specifying price.
+    pub price_decimals: u8,
+    /// Maximum decimal places for specifying quantity.
+    pub quantity_decimals: u8,
+    /// Price tick size.
+    pub price_tick_size: f64,
+    /// Quantity tick size.
+    pub quantity_tick_size: f64,
+    /// Minimum price.
+    pub min_price: f64,
+    /// Maximum price, not checked when 0.
+    pub max_price: f64,
+    /// Minimum quantity.
+    pub min_quantity: f64,
+    /// Maximum quantity, not checked when 0.
+    pub max_quantity: f64,
+}
+
+impl From<&Instrument> for InstrumentRule {
+    fn from(value: &Instrument) -> Self {
+        Self {
+            price_decimals: value.price_decimals,
+            quantity_decimals: value.quantity_decimals,
+            price_tick_size: value.price_tick_size,
+            quantity_tick_size: value.quantity_tick_size,
+            min_price: value.min_price,
+            max_price: value.max_price,
+            min_quantity: value.min_quantity,
+            max_quantity: value.max_quantity,
+        }
+    }
+}
+
+/// How far off a tick a value may be and still count as on it, absorbs float error.
+const TICK_TOLERANCE: f64 = 1e-6;
+
+/// Whether `value` is a whole number of `tick`s, always true when `tick` is not positive.
+fn on_tick(value: f64, tick: f64) -> bool {
+    if tick <= 0.0 {
+        return true;
+    }
+
+    let ticks = value / tick;
+
+    (ticks - ticks.round()).abs() <= TICK_TOLERANCE
+}
+
+/// Round `value` to `decimals` places, cleaning up the float error left by tick rounding.
+fn round_decimals(value: f64, decimals: u8) -> f64 {
+    let factor = 10_f64.powi(i32::from(decimals));
+
+    (value * factor).round() / factor
+}
+
+impl InstrumentRule {
+    /// Round `price` to the nearest tick.
+    #[must_use]
+    pub fn round_price(&self, price: f64) -> f64 {
+        if self.price_tick_size <= 0.0 {
+            return round_decimals(price, self.price_decimals);
+        }
+
+        let ticks = (price / self.price_tick_size).round();
+
+        round_decimals(ticks * self.price_tick_size, self.price_decimals)
+    }
+
+    /// Round `quantity` down to a whole tick, so an order never exceeds what was asked for.
+    #[must_use]
+    pub fn round_quantity(&self, quantity: f64) -> f64 {
+        if self.quantity_tick_size <= 0.0 {
+            return round_decimals(quantity, self.quantity_decimals);
+        }
+
+        let ticks = (quantity / self.quantity_tick_size + TICK_TOLERANCE).floor();
+
+        round_decimals(ticks * self.quantity_tick_size, self.quantity_decimals)
+    }
+
+    /// Check `price` is on a tick and within the price range.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`OrderError::PriceNotOnTick`] or [`OrderError::PriceOutOfRange`].
+    pub fn validate_price(&self, price: f64) -> Result<(), OrderError> {
+        if !on_tick(price, self.price_tick_size) {
+            return Err(OrderError::PriceNotOnTick {
+                price,
+                price_tick_size: self.price_tick_size,
+            });
+        }
+
+        if price < self.min_price || (self.max_price > 0.0 && price > self.max_price) {
+            return Err(OrderError::PriceOutOfRange {
+                price,
+                min_price: self.min_price,
+                max_price: self.max_price,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Check `quantity` is on a tick and within the quantity range.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`OrderError::QuantityNotOnTick`] or [`OrderError::QuantityOutOfRange`].
+    pub fn validate_quantity(&self, quantity: f64) -> Result<(), OrderError> {
+        if !on_tick(quantity, self.quantity_tick_size) {
+            return Err(OrderError::QuantityNotOnTick {
+                quantity,
+                quantity_tick_size: self.quantity_tick_size,
+            });
+        }
+
+        if quantity < self.min_quantity || (self.max_quantity > 0.0 && quantity > self.max_quantity)
+        {
+            return Err(OrderError::QuantityOutOfRange {
+                quantity,
+                min_quantity: self.min_quantity,
+                max_quantity: self.max_quantity,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// The price and quantity rules of every instrument, built from `public/get-instruments`.
+///
+/// Orders whose price or quantity is off a tick are rejected by the exchange, these rules let them
+/// be rounded or rejected before they are sent.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct InstrumentRules {
+    /// [`InstrumentRule`] by instrument name.
+    pub rules: HashMap<String, InstrumentRule>,
+}
+
+impl From<&InstrumentsRes> for InstrumentRules {
+    fn from(value: &InstrumentsRes) -> Self {
+        Self {
+            rules: value
+                .instruments
+                .iter()
+                .map(|instrument| {
+                    (
+                        instrument.instrument_name.clone(),
+                        InstrumentRule::from(instrument),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+impl InstrumentRules {
+    /// The rule for `instrument_name`.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`OrderError::UnknownInstrument`] if the instrument is not in the rules.
+    pub fn rule(&self, instrument_name: &str) -> Result<&InstrumentRule, OrderError> {
+        self.rules
+            .get(instrument_name)
+            .ok_or_else(|| OrderError::UnknownInstrument(instrument_name.to_owned()))
+    }
+
+    /// Round `price` to the nearest tick of `instrument_name`.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`OrderError::UnknownInstrument`] if the instrument is not in the rules.
+    pub fn round_price(&self, instrument_name: &str, price: f64) -> Result<f64, OrderError> {
+        Ok(self.rule(instrument_name)?.round_price(price))
+    }
+
+    /// Round `quantity` down to a whole tick of `instrument_name`.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`OrderError::UnknownInstrument`] if the instrument is not in the rules.
+    pub fn round_quantity(&self, instrument_name: &str, quantity: f64) -> Result<f64, OrderError> {
+        Ok(self.rule(instrument_name)?.round_quantity(quantity))
+    }
+
+    /// Check `price` against the rules of `instrument_name`.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`OrderError`] if the instrument is unknown or the price would be rejected.
+    pub fn validate_price(&self, instrument_name: &str, price: f64) -> Result<(), OrderError> {
+        self.rule(instrument_name)?.validate_price(price)
+    }
+
+    /// Check `quantity` against the rules of `instrument_name`.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`OrderError`] if the instrument is unknown or the quantity would be rejected.
+    pub fn validate_quantity(
+        &self,
+        instrument_name: &str,
+        quantity: f64,
+    ) -> Result<(), OrderError> {
+        self.rule(instrument_name)?.validate_quantity(quantity)
+    }
+}
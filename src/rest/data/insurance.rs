@@ -0,0 +1,109 @@
+//! Data from [public/get-insurance](https://exchange-docs.crypto.com/exchange/v1/rest-ws/index.html#public-get-insurance)
+
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::ApiError;
+use crate::types::{EpochMs, Time};
+
+/// Get insurance params.
+#[derive(Serialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct InsuranceParams {
+    /// e.g. USD.
+    pub currency: String,
+    /// Number of results (Default: 25).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<u64>,
+    /// Start time in ms since the UNIX epoch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_ts: Option<u64>,
+    /// End time in ms since the UNIX epoch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_ts: Option<u64>,
+}
+
+/// The raw insurance fund data response.
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct RawInsurance {
+    /// Insurance fund balance.
+    pub v: String,
+    /// Timestamp.
+    pub t: u64,
+}
+
+/// The raw insurance fund response.
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct RawInsuranceRes {
+    /// The requested currency.
+    pub instrument_name: String,
+    /// [`RawInsurance`]
+    #[serde(default)]
+    pub data: Vec<RawInsurance>,
+}
+
+/// The processed insurance fund data response.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Insurance {
+    /// Insurance fund balance.
+    pub v: f64,
+    /// Timestamp.
+    pub t: Time,
+}
+
+impl TryFrom<&RawInsurance> for Insurance {
+    type Error = ApiError;
+
+    fn try_from(value: &RawInsurance) -> Result<Self, Self::Error> {
+        Ok(Self {
+            v: value.v.parse::<f64>()?,
+            t: Time::from_epoch_ms(value.t),
+        })
+    }
+}
+
+/// The processed insurance fund response.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct InsuranceRes {
+    /// The requested currency.
+    pub instrument_name: String,
+    /// [`Insurance`]
+    pub data: Vec<Insurance>,
+}
+
+impl TryFrom<&RawInsuranceRes> for InsuranceRes {
+    type Error = ApiError;
+
+    fn try_from(value: &RawInsuranceRes) -> Result<Self, Self::Error> {
+        let mut data = vec![];
+
+        for insurance in &value.data {
+            data.push(Insurance::try_from(insurance)?);
+        }
+
+        Ok(Self {
+            instrument_name: value.instrument_name.clone(),
+            data,
+        })
+    }
+}
+
+impl TryFrom<RawInsuranceRes> for InsuranceRes {
+    type Error = ApiError;
+
+    fn try_from(value: RawInsuranceRes) -> Result<Self, Self::Error> {
+        let mut data = vec![];
+
+        for insurance in &value.data {
+            data.push(Insurance::try_from(insurance)?);
+        }
+
+        Ok(Self {
+            instrument_name: value.instrument_name,
+            data,
+        })
+    }
+}
@@ -1,25 +1,41 @@
 //! Data from the REST API.
 
 pub mod account_summary;
+pub mod announcements;
 pub mod book;
 pub mod candlestick;
+pub mod conversion_rate;
 pub mod create_withdrawal;
+pub mod currencies;
 pub mod currency_networks;
 pub mod deposit_address;
 pub mod deposit_history;
+pub mod dust_conversion;
+pub mod earn;
+pub mod expired_settlement_price;
 pub mod instruments;
+pub mod insurance;
 pub mod ticker;
 pub mod trades;
+pub mod valuations;
 pub mod withdrawal_history;
 
 pub use account_summary::*;
+pub use announcements::*;
 pub use book::*;
 pub use candlestick::*;
+pub use conversion_rate::*;
 pub use create_withdrawal::*;
+pub use currencies::*;
 pub use currency_networks::*;
 pub use deposit_address::*;
 pub use deposit_history::*;
+pub use dust_conversion::*;
+pub use earn::*;
+pub use expired_settlement_price::*;
 pub use instruments::*;
+pub use insurance::*;
 pub use ticker::*;
 pub use trades::*;
+pub use valuations::*;
 pub use withdrawal_history::*;
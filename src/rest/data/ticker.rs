@@ -1,11 +1,16 @@
 //! Data from [public/get-ticker](https://exchange-docs.crypto.com/spot/index.html#public-get-ticker)
 
-use serde::Deserialize;
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
 
 use crate::prelude::ApiError;
+use crate::types::{EpochMs, Time};
+use crate::utils::intern::intern;
 
 /// The raw ticker data response.
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct RawTicker {
     /// Price of the 24h highest trade.
     pub h: Option<String>,
@@ -33,37 +38,15 @@ pub struct RawTicker {
 
 /// The raw ticker response.
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct RawTickerRes {
     /// [`RawTicker`]
+    #[serde(default)]
     pub data: Vec<RawTicker>,
 }
 
-/// The processed ticker response.
-#[derive(Debug, Default)]
-pub struct Ticker {
-    /// Price of the 24h highest trade.
-    pub h: Option<f64>,
-    /// Price of the 24h lowest trade, null if there weren't any trades.
-    pub l: Option<f64>,
-    /// The price of the latest trade, null if there weren't any trades.
-    pub a: Option<f64>,
-    /// Instrument name.
-    pub i: String,
-    /// The total 24h traded volume.
-    pub v: f64,
-    /// The total 24h traded volume value (in USD).
-    pub vv: f64,
-    /// The open interest.
-    pub oi: Option<f64>,
-    /// 24-hour price change, null if there weren't any trades.
-    pub c: Option<f64>,
-    /// The current best bid price, null if there weren't any bids.
-    pub b: Option<f64>,
-    /// The current best ask price, null if there aren't any asks.
-    pub k: Option<f64>,
-    /// Trade timestamp.
-    pub t: u64,
-}
+/// The processed ticker data, shared with the websocket API.
+pub use crate::types::Ticker;
 
 impl TryFrom<&RawTicker> for Ticker {
     type Error = ApiError;
@@ -85,7 +68,7 @@ impl TryFrom<&RawTicker> for Ticker {
             } else {
                 None
             },
-            i: value.i.clone(),
+            i: intern(&value.i),
             v: value.v.parse::<f64>()?,
             vv: value.vv.parse::<f64>()?,
             oi: if let Some(ref oi) = value.oi {
@@ -103,18 +86,21 @@ impl TryFrom<&RawTicker> for Ticker {
             } else {
                 None
             },
+            bs: None,
             k: if let Some(ref k) = value.k {
                 Some(k.parse::<f64>()?)
             } else {
                 None
             },
-            t: value.t,
+            ks: None,
+            t: Time::from_epoch_ms(value.t),
         })
     }
 }
 
 /// The processed ticker response.
-#[derive(Debug, Default)]
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct TickerRes {
     /// [`Ticker`]
     pub data: Vec<Ticker>,
@@ -147,3 +133,59 @@ impl TryFrom<RawTickerRes> for TickerRes {
         Ok(Self { data })
     }
 }
+
+/// The tickers of every instrument, from `public/get-ticker` without an instrument name.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct TickersRes {
+    /// [`Ticker`] by instrument name.
+    pub tickers: HashMap<String, Ticker>,
+}
+
+impl From<&TickerRes> for TickersRes {
+    fn from(value: &TickerRes) -> Self {
+        Self {
+            tickers: value
+                .data
+                .iter()
+                .map(|ticker| (ticker.i.to_string(), ticker.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl From<TickerRes> for TickersRes {
+    fn from(value: TickerRes) -> Self {
+        Self {
+            tickers: value
+                .data
+                .into_iter()
+                .map(|ticker| (ticker.i.to_string(), ticker))
+                .collect(),
+        }
+    }
+}
+
+impl TickersRes {
+    /// The ticker of `instrument_name`, `None` if the exchange did not send it.
+    #[must_use]
+    pub fn ticker(&self, instrument_name: &str) -> Option<&Ticker> {
+        self.tickers.get(instrument_name)
+    }
+
+    /// The best bid and best ask price of `instrument_name`, `None` if the instrument is unknown
+    /// or either side is empty.
+    #[must_use]
+    pub fn best_bid_ask(&self, instrument_name: &str) -> Option<(f64, f64)> {
+        let ticker = self.ticker(instrument_name)?;
+
+        Some((ticker.b?, ticker.k?))
+    }
+
+    /// The price halfway between the best bid and best ask of `instrument_name`.
+    #[must_use]
+    pub fn mid_price(&self, instrument_name: &str) -> Option<f64> {
+        let (bid, ask) = self.best_bid_ask(instrument_name)?;
+
+        Some((bid + ask) / 2.0)
+    }
+}
@@ -1,11 +1,33 @@
 //! Data from [public/get-trades](https://exchange-docs.crypto.com/spot/index.html#public-get-trades)
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::prelude::ApiError;
+use crate::types::{EpochMs, Time};
+use crate::utils::intern::intern;
+
+/// Most trades `public/get-trades` returns in one request.
+pub const MAX_TRADES_COUNT: u64 = 150;
+
+/// Get trades params for a time range.
+#[derive(Serialize, Clone, Debug)]
+pub struct TradesParams {
+    /// e.g. ETH_CRO, BTC_USDT.
+    pub instrument_name: String,
+    /// Number of trades, newest first (Default: 25, Max: [`MAX_TRADES_COUNT`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<u64>,
+    /// Start time in ms since the UNIX epoch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_ts: Option<u64>,
+    /// End time in ms since the UNIX epoch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_ts: Option<u64>,
+}
 
 /// The raw trade data response.
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct RawTrade {
     /// Side ("BUY" or "SELL").
     pub s: String,
@@ -26,29 +48,15 @@ pub struct RawTrade {
 
 /// The raw trade response.
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct RawTradesRes {
     /// [`RawTrade`]
+    #[serde(default)]
     pub data: Vec<RawTrade>,
 }
 
-/// The processed trade data response.
-#[derive(Debug, Default)]
-pub struct Trade {
-    /// Side ("BUY" or "SELL").
-    pub s: String,
-    /// Trade price.
-    pub p: f64,
-    /// Trade quantity.
-    pub q: f64,
-    /// Trade timestamp.
-    pub t: u64,
-    /// Trade ID.
-    pub d: u64,
-    /// Instrument name e.g. BTC_CRO.
-    pub i: String,
-    /// Reserved. Can be ignored.
-    pub data_time: u64,
-}
+/// The processed trade data response, shared with the websocket API.
+pub use crate::types::Trade;
 
 impl TryFrom<&RawTrade> for Trade {
     type Error = ApiError;
@@ -58,16 +66,17 @@ impl TryFrom<&RawTrade> for Trade {
             s: value.s.clone(),
             p: value.p.parse::<f64>()?,
             q: value.q.parse::<f64>()?,
-            t: value.t,
-            d: value.d.parse::<u64>()?,
-            i: value.i.clone(),
-            data_time: value.data_time,
+            t: Time::from_epoch_ms(value.t),
+            d: value.d.clone(),
+            i: intern(&value.i),
+            data_time: Some(value.data_time),
         })
     }
 }
 
 /// The processed trade response.
-#[derive(Debug, Default)]
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct TradesRes {
     /// [`Trade`]
     pub data: Vec<Trade>,
@@ -0,0 +1,147 @@
+//! Data from [public/get-valuations](https://exchange-docs.crypto.com/exchange/v1/rest-ws/index.html#public-get-valuations)
+
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::ApiError;
+use crate::types::{EpochMs, Time};
+
+/// The kind of valuation to fetch.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ValuationType {
+    /// Index price, use an index instrument e.g. BTCUSD-INDEX.
+    IndexPrice,
+    /// Mark price.
+    MarkPrice,
+    /// Hourly funding rate history.
+    FundingHist,
+    /// Current funding rate.
+    FundingRate,
+    /// Estimated funding rate for the next interval.
+    EstimatedFundingRate,
+}
+
+/// Get valuations params.
+#[derive(Serialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct GetValuationsParams {
+    /// e.g. BTCUSD-PERP or BTCUSD-INDEX.
+    pub instrument_name: String,
+    /// [`ValuationType`]
+    pub valuation_type: ValuationType,
+    /// Number of results (Default: 25).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<u64>,
+    /// Start time in ms since the UNIX epoch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_ts: Option<u64>,
+    /// End time in ms since the UNIX epoch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_ts: Option<u64>,
+}
+
+/// The raw valuation data response.
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct RawValuation {
+    /// Value.
+    pub v: String,
+    /// Timestamp.
+    pub t: u64,
+}
+
+/// The raw valuations response.
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct RawValuationsRes {
+    /// e.g. BTCUSD-PERP.
+    pub instrument_name: String,
+    /// [`RawValuation`]
+    #[serde(default)]
+    pub data: Vec<RawValuation>,
+}
+
+/// The processed valuation data response.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Valuation {
+    /// Value, a price or a funding rate depending on the [`ValuationType`].
+    pub v: f64,
+    /// Timestamp.
+    pub t: Time,
+}
+
+impl TryFrom<&RawValuation> for Valuation {
+    type Error = ApiError;
+
+    fn try_from(value: &RawValuation) -> Result<Self, Self::Error> {
+        Ok(Self {
+            v: value.v.parse::<f64>()?,
+            t: Time::from_epoch_ms(value.t),
+        })
+    }
+}
+
+/// The processed valuations response.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ValuationsRes {
+    /// e.g. BTCUSD-PERP.
+    pub instrument_name: String,
+    /// [`Valuation`]
+    pub data: Vec<Valuation>,
+}
+
+impl ValuationsRes {
+    /// The most recent valuation.
+    #[must_use]
+    pub fn latest(&self) -> Option<&Valuation> {
+        self.data
+            .iter()
+            .max_by_key(|valuation| valuation.t.epoch_ms())
+    }
+}
+
+/// Funding paid on a perpetual position of `quantity`, negative when short, at `mark_price` for
+/// one interval at `rate`.
+///
+/// Positive when the position pays funding, negative when it recieves it.
+#[must_use]
+pub fn funding_payment(quantity: f64, mark_price: f64, rate: f64) -> f64 {
+    quantity * mark_price * rate
+}
+
+impl TryFrom<&RawValuationsRes> for ValuationsRes {
+    type Error = ApiError;
+
+    fn try_from(value: &RawValuationsRes) -> Result<Self, Self::Error> {
+        let mut data = vec![];
+
+        for valuation in &value.data {
+            data.push(Valuation::try_from(valuation)?);
+        }
+
+        Ok(Self {
+            instrument_name: value.instrument_name.clone(),
+            data,
+        })
+    }
+}
+
+impl TryFrom<RawValuationsRes> for ValuationsRes {
+    type Error = ApiError;
+
+    fn try_from(value: RawValuationsRes) -> Result<Self, Self::Error> {
+        let mut data = vec![];
+
+        for valuation in &value.data {
+            data.push(Valuation::try_from(valuation)?);
+        }
+
+        Ok(Self {
+            instrument_name: value.instrument_name,
+            data,
+        })
+    }
+}
@@ -1,9 +1,13 @@
 //! Data from [private/get-withdrawal-history](https://exchange-docs.crypto.com/spot/index.html#private-get-withdrawal-history)
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{deserialize_time, Time, WithdrawalStatus};
 
 /// Withdrawal history item.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct WithdrawalHistoryItem {
     /// Newly created withdrawal ID.
     pub id: u64,
@@ -18,15 +22,10 @@ pub struct WithdrawalHistoryItem {
     /// Address with Address Tag (if any).
     pub address: Option<String>,
     /// Create time.
-    pub create_time: u64,
-    /// 0 - Pending
-    /// 1 - Processing
-    /// 2 - Rejected
-    /// 3 - Payment In-progress
-    /// 4 - Payment failed
-    /// 5 - Completed
-    /// 6 - Cancelled
-    pub status: String,
+    #[serde(deserialize_with = "deserialize_time")]
+    pub create_time: Time,
+    /// Withdrawal status, see [`WithdrawalStatus`].
+    pub status: WithdrawalStatus,
     /// Transaction hash.
     pub txid: String,
     /// Network for the transaction - please see `get-currency-networks`. Only available when
@@ -35,8 +34,14 @@ pub struct WithdrawalHistoryItem {
 }
 
 /// Withdrawal result.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct WithdrawalHistory {
     /// Array of withdrawal items.
+    #[serde(default)]
     pub withdrawal_list: Vec<WithdrawalHistoryItem>,
+    /// Total number of withdrawals, when sent.
+    #[serde(default)]
+    pub count: Option<u64>,
 }
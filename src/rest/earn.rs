@@ -0,0 +1,145 @@
+//! Private REST routes of the earn program, allocating balances to products that pay a yield.
+//!
+//! Every method is a signed `POST` like [`crate::rest::private`], only available to API keys with
+//! earn enabled.
+//!
+//! ```no_run
+//! # async fn example(config: crypto_com_api::utils::config::Config) -> anyhow::Result<()> {
+//! use crypto_com_api::rest::data::{AllocateParams, EarnProductsParams, EarnTerm};
+//! use crypto_com_api::rest::earn;
+//!
+//! let products = earn::get_products(&config, EarnProductsParams::default()).await?;
+//!
+//! if let Some(product) = products
+//!     .result
+//!     .as_ref()
+//!     .and_then(|products| products.best("CRO", EarnTerm::Flexible))
+//!     .filter(|product| product.accepts(100.0))
+//! {
+//!     let params = AllocateParams {
+//!         product_id: product.product_id.clone(),
+//!         amount: 100.0,
+//!     };
+//!     earn::allocate(&config, params).await?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::prelude::ApiError;
+use crate::rest::data::earn::{
+    AllocateParams, EarnPosition, EarnPositions, EarnPositionsParams, EarnProducts,
+    EarnProductsParams, EarnYieldHistory, EarnYieldHistoryParams, RedeemParams,
+};
+use crate::{api_request::ApiRequestBuilder, api_response::ApiResponse, utils::config::Config};
+
+/// Returns the earn products and their rates.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, `api_key`, or
+/// `secret_key`, or [`ApiError::Http`] if send fails or the response cannot be deserialized.
+pub async fn get_products(
+    config: &Config,
+    params: EarnProductsParams,
+) -> Result<ApiResponse<EarnProducts>> {
+    post(config, "private/get-earn-products", params).await
+}
+
+/// Returns the amounts allocated to earn products.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, `api_key`, or
+/// `secret_key`, or [`ApiError::Http`] if send fails or the response cannot be deserialized.
+pub async fn get_positions(
+    config: &Config,
+    params: EarnPositionsParams,
+) -> Result<ApiResponse<EarnPositions>> {
+    post(config, "private/get-earn-positions", params).await
+}
+
+/// Allocates an amount to an earn product, returning the new position.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, `api_key`, or
+/// `secret_key`, or [`ApiError::Http`] if send fails or the response cannot be deserialized.
+pub async fn allocate(
+    config: &Config,
+    params: AllocateParams,
+) -> Result<ApiResponse<EarnPosition>> {
+    post(config, "private/earn-allocate", params).await
+}
+
+/// Redeems all or part of an earn position, returning the position as it is after the request.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, `api_key`, or
+/// `secret_key`, or [`ApiError::Http`] if send fails or the response cannot be deserialized.
+pub async fn redeem(config: &Config, params: RedeemParams) -> Result<ApiResponse<EarnPosition>> {
+    post(config, "private/earn-redeem", params).await
+}
+
+/// Returns the yield paid to earn positions.
+///
+/// # Errors
+///
+/// Will return [`ApiError::InvalidTimeRange`] or [`ApiError::InvalidPageSize`] before sending
+/// if the params are invalid.
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, `api_key`, or
+/// `secret_key`, or [`ApiError::Http`] if send fails or the response cannot be deserialized.
+pub async fn get_yield_history(
+    config: &Config,
+    params: EarnYieldHistoryParams,
+) -> Result<ApiResponse<EarnYieldHistory>> {
+    params.validate()?;
+
+    post(config, "private/get-earn-yield-history", params).await
+}
+
+/// Sign `params` for `method` and post them to `{rest_url}{method}`.
+async fn post<P, R>(config: &Config, method: &str, params: P) -> Result<ApiResponse<R>>
+where
+    P: Serialize,
+    R: DeserializeOwned,
+{
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let Some(ref api_key) = config.api_key else {
+        return Err(ApiError::ConfigMissing("api_key".to_owned()));
+    };
+
+    let Some(ref secret) = config.secret_key else {
+        return Err(ApiError::ConfigMissing("secret_key".to_owned()));
+    };
+
+    let req = ApiRequestBuilder::default()
+        .with_id(0)
+        .with_method(method)
+        .with_params(params)
+        .with_api_key(api_key)
+        .with_nonce()
+        .with_digital_signature(secret)
+        .build()?;
+
+    let res = client
+        .post(format!("{rest_url}{}", req.method))
+        .json(&req)
+        .send()
+        .await?
+        .json::<ApiResponse<R>>()
+        .await?;
+
+    Ok(res)
+}
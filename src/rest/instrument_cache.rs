@@ -0,0 +1,269 @@
+//! A cache of `public/get-instruments`, fetched on demand and shared by everything that needs the
+//! instruments.
+
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, Instant};
+
+use tokio::sync::Mutex;
+
+use crate::error::{ApiError, Result};
+use crate::rest::data::{Currencies, Currency, Instrument, InstrumentRules, InstrumentsRes};
+use crate::rest::public;
+use crate::utils::config::Config;
+#[cfg(feature = "websocket")]
+use crate::websocket::actions::spot_trading_api::CreateOrder;
+
+/// How long instruments are kept when no TTL is given, they rarely change within a day.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// The instruments as of one fetch.
+#[derive(Debug)]
+struct Cached {
+    /// When the instruments were fetched or inserted.
+    fetched_at: Instant,
+    /// The instruments, in the order crypto.com listed them.
+    instruments: Arc<InstrumentsRes>,
+    /// Index in `instruments` by instrument name.
+    by_name: HashMap<String, usize>,
+    /// The price and quantity rules of `instruments`.
+    rules: Arc<InstrumentRules>,
+    /// The currencies traded by `instruments`.
+    currencies: Arc<Currencies>,
+}
+
+impl Cached {
+    /// Index and build the rules and currencies of `instruments`.
+    fn new(instruments: InstrumentsRes) -> Self {
+        Self {
+            fetched_at: Instant::now(),
+            by_name: instruments
+                .instruments
+                .iter()
+                .enumerate()
+                .map(|(index, instrument)| (instrument.instrument_name.clone(), index))
+                .collect(),
+            rules: Arc::new(InstrumentRules::from(&instruments)),
+            currencies: Arc::new(Currencies::from(&instruments)),
+            instruments: Arc::new(instruments),
+        }
+    }
+}
+
+/// `public/get-instruments`, fetched the first time it is needed and again once it is older than
+/// the TTL.
+///
+/// Share one cache with [`std::sync::Arc`] between the controller, see
+/// [`crate::controller::Controller::set_instrument_cache`], the
+/// [`crate::risk::RiskManager`], and anything rounding orders, so the full list is fetched once
+/// per TTL instead of by each of them.
+///
+/// ```no_run
+/// # async fn example(config: crypto_com_api::utils::config::Config) -> anyhow::Result<()> {
+/// use crypto_com_api::rest::instrument_cache::{InstrumentCache, DEFAULT_TTL};
+///
+/// let cache = InstrumentCache::new(config, DEFAULT_TTL);
+///
+/// if let Some(instrument) = cache.instrument("BTC_USDT").await? {
+///     println!("{} price decimals", instrument.price_decimals);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct InstrumentCache {
+    /// Config the instruments are fetched with.
+    config: Config,
+    /// How long fetched instruments are used before fetching them again.
+    ttl: Duration,
+    /// The last fetched instruments, `None` until the first fetch.
+    cached: RwLock<Option<Cached>>,
+    /// Held while fetching, so tasks that find the cache stale at once fetch it once.
+    fetching: Mutex<()>,
+}
+
+impl InstrumentCache {
+    /// An empty cache fetching with `config`, keeping instruments for `ttl`.
+    #[must_use]
+    pub fn new(config: Config, ttl: Duration) -> Self {
+        Self {
+            config,
+            ttl,
+            cached: RwLock::new(None),
+            fetching: Mutex::new(()),
+        }
+    }
+
+    /// How long fetched instruments are kept.
+    #[must_use]
+    pub const fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Whether instruments are cached and younger than the TTL.
+    #[must_use]
+    pub fn is_fresh(&self) -> bool {
+        self.read(|cached| cached.fetched_at.elapsed() < self.ttl)
+            .unwrap_or(false)
+    }
+
+    /// Every instrument, fetched if the cache is empty or stale.
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`InstrumentCache::refresh`] when a fetch is needed.
+    pub async fn instruments(&self) -> Result<Arc<InstrumentsRes>> {
+        self.fresh(|cached| Arc::clone(&cached.instruments)).await
+    }
+
+    /// The price and quantity rules of every instrument, fetched if the cache is empty or stale.
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`InstrumentCache::refresh`] when a fetch is needed.
+    pub async fn rules(&self) -> Result<Arc<InstrumentRules>> {
+        self.fresh(|cached| Arc::clone(&cached.rules)).await
+    }
+
+    /// The instrument named `instrument_name`, `None` if crypto.com does not list it.
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`InstrumentCache::refresh`] when a fetch is needed.
+    pub async fn instrument(&self, instrument_name: &str) -> Result<Option<Instrument>> {
+        self.fresh(|cached| {
+            cached
+                .by_name
+                .get(instrument_name)
+                .map(|&index| cached.instruments.instruments[index].clone())
+        })
+        .await
+    }
+
+    /// Every currency traded by the instruments, fetched if the cache is empty or stale.
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`InstrumentCache::refresh`] when a fetch is needed.
+    pub async fn currencies(&self) -> Result<Arc<Currencies>> {
+        self.fresh(|cached| Arc::clone(&cached.currencies)).await
+    }
+
+    /// The currency named `currency`, `None` if no instrument trades it.
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`InstrumentCache::refresh`] when a fetch is needed.
+    pub async fn currency(&self, currency: &str) -> Result<Option<Currency>> {
+        self.fresh(|cached| cached.currencies.get(currency).cloned())
+            .await
+    }
+
+    /// Round the price, trigger price, and quantity of `order` to the ticks of its instrument.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::InvalidOrder`] if the instrument is not listed, otherwise the same
+    /// errors as [`InstrumentCache::refresh`] when a fetch is needed.
+    #[cfg(feature = "websocket")]
+    pub async fn round_order(&self, order: &mut CreateOrder) -> Result<()> {
+        let rules = self.rules().await?;
+
+        order.round(&rules)?;
+
+        Ok(())
+    }
+
+    /// Check `order` against the rules of its instrument.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::InvalidOrder`] if the order would be rejected, otherwise the same
+    /// errors as [`InstrumentCache::refresh`] when a fetch is needed.
+    #[cfg(feature = "websocket")]
+    pub async fn validate_order(&self, order: &CreateOrder) -> Result<()> {
+        let rules = self.rules().await?;
+
+        order.validate(&rules)?;
+
+        Ok(())
+    }
+
+    /// The cached rules whatever their age, without fetching.
+    #[must_use]
+    pub fn cached_rules(&self) -> Option<Arc<InstrumentRules>> {
+        self.read(|cached| Arc::clone(&cached.rules))
+    }
+
+    /// Fetch the instruments now, whatever the age of the cached ones.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::Http`] if the request fails, or [`ApiError::RequestFailed`] if
+    /// crypto.com answers with a non-zero code or no instruments.
+    pub async fn refresh(&self) -> Result<Arc<InstrumentsRes>> {
+        let _fetching = self.fetching.lock().await;
+
+        self.fetch().await
+    }
+
+    /// Cache `instruments` as if they were just fetched, e.g. from a websocket
+    /// `public/get-instruments` reply.
+    pub fn insert(&self, instruments: InstrumentsRes) {
+        *self.cached.write().unwrap_or_else(PoisonError::into_inner) =
+            Some(Cached::new(instruments));
+    }
+
+    /// Forget the cached instruments, the next lookup fetches them.
+    pub fn invalidate(&self) {
+        *self.cached.write().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+
+    /// `f` of the cached instruments, `None` if there are none.
+    fn read<T>(&self, f: impl FnOnce(&Cached) -> T) -> Option<T> {
+        self.cached
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+            .map(f)
+    }
+
+    /// `f` of the cached instruments, fetching them first if the cache is empty or stale.
+    async fn fresh<T>(&self, f: impl Fn(&Cached) -> T) -> Result<T> {
+        if self.is_fresh() {
+            if let Some(value) = self.read(&f) {
+                return Ok(value);
+            }
+        }
+
+        let _fetching = self.fetching.lock().await;
+
+        // Another task may have fetched while this one waited.
+        if !self.is_fresh() {
+            self.fetch().await?;
+        }
+
+        self.read(&f).ok_or_else(|| {
+            ApiError::Other(anyhow::anyhow!("instrument cache is empty after a fetch"))
+        })
+    }
+
+    /// Fetch and cache the instruments, the caller holds `fetching`.
+    async fn fetch(&self) -> Result<Arc<InstrumentsRes>> {
+        let res = public::get_instruments(&self.config).await?;
+
+        let (Some(0) | None, Some(instruments)) = (res.code, res.result) else {
+            return Err(ApiError::RequestFailed {
+                code: res.code.unwrap_or_default(),
+                message: res.message,
+            });
+        };
+
+        let cached = Cached::new(instruments);
+        let instruments = Arc::clone(&cached.instruments);
+
+        *self.cached.write().unwrap_or_else(PoisonError::into_inner) = Some(cached);
+
+        Ok(instruments)
+    }
+}
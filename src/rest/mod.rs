@@ -1,5 +1,34 @@
 //! Data and helper functions for interacting with the REST system.
+//!
+//! With `websocket-market` but not `rest` only [`data`] is built, for the websocket replies that
+//! share its models.
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
 pub mod data;
+#[cfg(feature = "rest")]
+pub mod earn;
+#[cfg(feature = "rest")]
+pub mod instrument_cache;
+#[cfg(feature = "rest")]
 pub mod private;
+#[cfg(feature = "rest")]
 pub mod public;
+
+#[cfg(feature = "rest")]
+use crate::error::Result;
+#[cfg(feature = "rest")]
+use crate::utils::config::Config;
+
+/// A client that sends the headers and user agent in `config`.
+///
+/// # Errors
+///
+/// Will return [`crate::prelude::ApiError::InvalidHeader`] if a header is not valid, or
+/// [`crate::prelude::ApiError::Http`] if the client cannot be built.
+#[cfg(feature = "rest")]
+pub(crate) fn client(config: &Config) -> Result<reqwest::Client> {
+    Ok(reqwest::Client::builder()
+        .default_headers(config.header_map()?)
+        .build()?)
+}
@@ -1,21 +1,30 @@
 //! Data for private REST routes.
+//!
+//! Every private method is a `POST` of the signed request as JSON to `{rest_url}{method}`.
 
-use anyhow::Result;
 use serde::Serialize;
 
+use crate::error::{Result, WithdrawalError};
 use crate::prelude::ApiError;
 use crate::rest::data::{
     account_summary::{AccountSummary, AccountSummaryParams},
-    currency_networks::CurrencyNetworks,
-    deposit_address::{DepositAddress, DepositAddressParams},
+    currency_networks::{CurrencyNetworks, WithdrawalQuote},
+    deposit_address::{
+        CreateDepositAddressParams, DepositAddress, DepositAddressItem, DepositAddressParams,
+    },
     deposit_history::{DepositHistory, DepositHistoryParams},
+    dust_conversion::{
+        ConvertDustParams, DustConversion, DustConversionQuote, DustConversionQuoteParams,
+    },
     withdrawal_history::WithdrawalHistory,
     CreateWithdrawalRes,
 };
+use crate::types::{PageQuery, TimeRange, WithdrawalStatus};
 use crate::{api_request::ApiRequestBuilder, api_response::ApiResponse, utils::config::Config};
 
 /// Create withdrawal params.
 #[derive(Serialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct CreateWithdrawal {
     /// Client withdrawal ID.
     pub client_wid: Option<String>,
@@ -27,29 +36,51 @@ pub struct CreateWithdrawal {
     pub address: String,
     /// Secondary address identifier for coins like XRP, XLM, etc. Also known as memo or tags.
     pub address_tag: Option<String>,
+    /// Select the desired network, requires the address to be whitelisted first.
+    pub network_id: Option<String>,
+}
+
+impl CreateWithdrawal {
+    /// Validate against [`get_currency_networks`] data before sending.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`WithdrawalError`] if the withdrawal would be rejected, see
+    /// [`CurrencyNetworks::validate_withdrawal`].
+    pub fn validate(
+        &self,
+        networks: &CurrencyNetworks,
+    ) -> Result<WithdrawalQuote, WithdrawalError> {
+        networks.validate_withdrawal(&self.currency, self.amount, self.network_id.as_deref())
+    }
 }
 
 /// Get withdrawal history params.
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Clone, Debug, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetWithdrawalHistoryParams {
     /// e.g. BTC, CRO
     pub currency: Option<String>,
-    /// Default is 90 days from current timestamp.
-    pub start_ts: Option<u64>,
-    /// Default is current timestamp.
-    pub end_ts: Option<u64>,
-    /// Page size (Default: 20, Max: 200).
-    pub page_size: Option<u64>,
-    /// Page number (0-based).
-    pub page: Option<u64>,
-    /// 0 - Pending
-    /// 1 - Processing
-    /// 2 - Rejected
-    /// 3 - Payment In-progress
-    /// 4 - Payment Failed
-    /// 5 - Completed
-    /// 6 - Cancelled
-    pub status: String,
+    /// Defaults to the last 90 days.
+    #[serde(flatten)]
+    pub range: TimeRange,
+    /// Defaults to the first page of 20.
+    #[serde(flatten)]
+    pub page: PageQuery,
+    /// Withdrawal status, filters the history when set.
+    pub status: Option<WithdrawalStatus>,
+}
+
+impl GetWithdrawalHistoryParams {
+    /// Check the time range and the page size.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::InvalidTimeRange`] or [`ApiError::InvalidPageSize`].
+    pub fn validate(&self) -> Result<(), ApiError> {
+        self.range.validate()?;
+        self.page.validate()
+    }
 }
 
 /// Creates a withdrawal request. Withdrawal setting must be enabled for your API Key. If you do
@@ -57,23 +88,28 @@ pub struct GetWithdrawalHistoryParams {
 ///
 /// # Errors
 ///
-/// Will return [`reqwest::Error`] if send fails or if serialization fails.
+/// Will return [`ApiError::WithdrawalRefused`] if the environment does not allow withdrawals.
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, `api_key`, or
+/// `secret_key`, or [`ApiError::Http`] if send fails or the response cannot be deserialized.
 pub async fn create_withdrawal(
     config: &Config,
     params: CreateWithdrawal,
 ) -> Result<ApiResponse<CreateWithdrawalRes>> {
-    let client = reqwest::Client::new();
+    config.check_withdrawal()?;
+
+    let client = super::client(config)?;
 
     let Some(ref rest_url) = config.rest_url else {
-        anyhow::bail!(ApiError::ConfigMissing("rest_url".to_owned()));
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
     };
 
     let Some(ref api_key) = config.api_key else {
-        anyhow::bail!(ApiError::ConfigMissing("api_key".to_owned()));
+        return Err(ApiError::ConfigMissing("api_key".to_owned()));
     };
 
     let Some(ref secret) = config.secret_key else {
-        anyhow::bail!(ApiError::ConfigMissing("secret_key".to_owned()));
+        return Err(ApiError::ConfigMissing("secret_key".to_owned()));
     };
 
     let req = ApiRequestBuilder::default()
@@ -81,12 +117,13 @@ pub async fn create_withdrawal(
         .with_method("private/create-withdrawal")
         .with_params(params)
         .with_api_key(api_key)
+        .with_nonce()
         .with_digital_signature(secret)
         .build()?;
 
     let res = client
-        .post(rest_url.to_string())
-        .body(serde_json::to_string(&req)?)
+        .post(format!("{rest_url}{}", req.method))
+        .json(&req)
         .send()
         .await?
         .json::<ApiResponse<CreateWithdrawalRes>>()
@@ -95,36 +132,85 @@ pub async fn create_withdrawal(
     Ok(res)
 }
 
+/// Estimate the fee of `params` from [`get_currency_networks`], checking that the withdrawal would
+/// be accepted.
+///
+/// crypto.com has no fee endpoint, the fee is the one listed for the network the withdrawal will
+/// be sent on.
+///
+/// # Errors
+///
+/// Will return [`ApiError::RequestFailed`] if the currency networks could not be fetched, or
+/// [`ApiError::InvalidWithdrawal`] if the withdrawal would be rejected.
+pub async fn estimate_withdrawal_fee(
+    config: &Config,
+    params: &CreateWithdrawal,
+) -> Result<WithdrawalQuote> {
+    let res = get_currency_networks(config).await?;
+
+    let Some(networks) = res.result.filter(|_| res.code.unwrap_or(0) == 0) else {
+        return Err(ApiError::RequestFailed {
+            code: res.code.unwrap_or_default(),
+            message: res.message,
+        });
+    };
+
+    Ok(params.validate(&networks)?)
+}
+
+/// [`create_withdrawal`] after quoting it with [`estimate_withdrawal_fee`], the quoted fee is kept
+/// in [`CreateWithdrawalRes::expected_fee`] to reconcile against the fee charged.
+///
+/// # Errors
+///
+/// Will return the errors of [`estimate_withdrawal_fee`] without sending the withdrawal, otherwise
+/// the errors of [`create_withdrawal`].
+pub async fn create_quoted_withdrawal(
+    config: &Config,
+    params: CreateWithdrawal,
+) -> Result<ApiResponse<CreateWithdrawalRes>> {
+    let quote = estimate_withdrawal_fee(config, &params).await?;
+    let mut res = create_withdrawal(config, params).await?;
+
+    if let Some(ref mut withdrawal) = res.result {
+        withdrawal.expected_fee = Some(quote.fee);
+    }
+
+    Ok(res)
+}
+
 /// Try to get the currency network data.
 ///
 /// # Errors
 ///
-/// Will return [`reqwest::Error`] if send fails or if serialization fails.
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, `api_key`, or
+/// `secret_key`, or [`ApiError::Http`] if send fails or the response cannot be deserialized.
 pub async fn get_currency_networks(config: &Config) -> Result<ApiResponse<CurrencyNetworks>> {
-    let client = reqwest::Client::new();
+    let client = super::client(config)?;
 
     let Some(ref rest_url) = config.rest_url else {
-        anyhow::bail!(ApiError::ConfigMissing("rest_url".to_owned()));
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
     };
 
     let Some(ref api_key) = config.api_key else {
-        anyhow::bail!(ApiError::ConfigMissing("api_key".to_owned()));
+        return Err(ApiError::ConfigMissing("api_key".to_owned()));
     };
 
     let Some(ref secret) = config.secret_key else {
-        anyhow::bail!(ApiError::ConfigMissing("secret_key".to_owned()));
+        return Err(ApiError::ConfigMissing("secret_key".to_owned()));
     };
 
     let req = ApiRequestBuilder::default()
         .with_id(0)
         .with_method("private/get-currency-networks")
         .with_api_key(api_key)
+        .with_nonce()
         .with_digital_signature(secret)
         .build()?;
 
     let res = client
-        .post(rest_url.to_string())
-        .body(serde_json::to_string(&req)?)
+        .post(format!("{rest_url}{}", req.method))
+        .json(&req)
         .send()
         .await?
         .json::<ApiResponse<CurrencyNetworks>>()
@@ -139,23 +225,29 @@ pub async fn get_currency_networks(config: &Config) -> Result<ApiResponse<Curren
 ///
 /// # Errors
 ///
-/// Will return [`reqwest::Error`] if send fails or if serialization fails.
+/// Will return [`ApiError::InvalidTimeRange`] or [`ApiError::InvalidPageSize`] before sending
+/// if the params are invalid.
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, `api_key`, or
+/// `secret_key`, or [`ApiError::Http`] if send fails or the response cannot be deserialized.
 pub async fn get_withdrawal_history(
     config: &Config,
     params: GetWithdrawalHistoryParams,
 ) -> Result<ApiResponse<WithdrawalHistory>> {
-    let client = reqwest::Client::new();
+    params.validate()?;
+
+    let client = super::client(config)?;
 
     let Some(ref rest_url) = config.rest_url else {
-        anyhow::bail!(ApiError::ConfigMissing("rest_url".to_owned()));
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
     };
 
     let Some(ref api_key) = config.api_key else {
-        anyhow::bail!(ApiError::ConfigMissing("api_key".to_owned()));
+        return Err(ApiError::ConfigMissing("api_key".to_owned()));
     };
 
     let Some(ref secret) = config.secret_key else {
-        anyhow::bail!(ApiError::ConfigMissing("secret_key".to_owned()));
+        return Err(ApiError::ConfigMissing("secret_key".to_owned()));
     };
 
     let req = ApiRequestBuilder::default()
@@ -163,12 +255,13 @@ pub async fn get_withdrawal_history(
         .with_method("private/get-withdrawal-history")
         .with_params(params)
         .with_api_key(api_key)
+        .with_nonce()
         .with_digital_signature(secret)
         .build()?;
 
     let res = client
-        .post(rest_url.to_string())
-        .body(serde_json::to_string(&req)?)
+        .post(format!("{rest_url}{}", req.method))
+        .json(&req)
         .send()
         .await?
         .json::<ApiResponse<WithdrawalHistory>>()
@@ -183,23 +276,29 @@ pub async fn get_withdrawal_history(
 ///
 /// # Errors
 ///
-/// Will return [`reqwest::Error`] if send fails or if serialization fails.
+/// Will return [`ApiError::InvalidTimeRange`] or [`ApiError::InvalidPageSize`] before sending
+/// if the params are invalid.
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, `api_key`, or
+/// `secret_key`, or [`ApiError::Http`] if send fails or the response cannot be deserialized.
 pub async fn get_deposit_history(
     config: &Config,
     params: DepositHistoryParams,
 ) -> Result<ApiResponse<DepositHistory>> {
-    let client = reqwest::Client::new();
+    params.validate()?;
+
+    let client = super::client(config)?;
 
     let Some(ref rest_url) = config.rest_url else {
-        anyhow::bail!(ApiError::ConfigMissing("rest_url".to_owned()));
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
     };
 
     let Some(ref api_key) = config.api_key else {
-        anyhow::bail!(ApiError::ConfigMissing("api_key".to_owned()));
+        return Err(ApiError::ConfigMissing("api_key".to_owned()));
     };
 
     let Some(ref secret) = config.secret_key else {
-        anyhow::bail!(ApiError::ConfigMissing("secret_key".to_owned()));
+        return Err(ApiError::ConfigMissing("secret_key".to_owned()));
     };
 
     let req = ApiRequestBuilder::default()
@@ -207,12 +306,13 @@ pub async fn get_deposit_history(
         .with_method("private/get-deposit-history")
         .with_params(params)
         .with_api_key(api_key)
+        .with_nonce()
         .with_digital_signature(secret)
         .build()?;
 
     let res = client
-        .post(rest_url.to_string())
-        .body(serde_json::to_string(&req)?)
+        .post(format!("{rest_url}{}", req.method))
+        .json(&req)
         .send()
         .await?
         .json::<ApiResponse<DepositHistory>>()
@@ -227,23 +327,24 @@ pub async fn get_deposit_history(
 ///
 /// # Errors
 ///
-/// Will return [`reqwest::Error`] if send fails or if serialization fails.
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, `api_key`, or
+/// `secret_key`, or [`ApiError::Http`] if send fails or the response cannot be deserialized.
 pub async fn get_deposit_address(
     config: &Config,
     params: DepositAddressParams,
 ) -> Result<ApiResponse<DepositAddress>> {
-    let client = reqwest::Client::new();
+    let client = super::client(config)?;
 
     let Some(ref rest_url) = config.rest_url else {
-        anyhow::bail!(ApiError::ConfigMissing("rest_url".to_owned()));
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
     };
 
     let Some(ref api_key) = config.api_key else {
-        anyhow::bail!(ApiError::ConfigMissing("api_key".to_owned()));
+        return Err(ApiError::ConfigMissing("api_key".to_owned()));
     };
 
     let Some(ref secret) = config.secret_key else {
-        anyhow::bail!(ApiError::ConfigMissing("secret_key".to_owned()));
+        return Err(ApiError::ConfigMissing("secret_key".to_owned()));
     };
 
     let req = ApiRequestBuilder::default()
@@ -251,12 +352,13 @@ pub async fn get_deposit_address(
         .with_method("private/get-deposit-address")
         .with_params(params)
         .with_api_key(api_key)
+        .with_nonce()
         .with_digital_signature(secret)
         .build()?;
 
     let res = client
-        .post(rest_url.to_string())
-        .body(serde_json::to_string(&req)?)
+        .post(format!("{rest_url}{}", req.method))
+        .json(&req)
         .send()
         .await?
         .json::<ApiResponse<DepositAddress>>()
@@ -265,27 +367,74 @@ pub async fn get_deposit_address(
     Ok(res)
 }
 
+/// Creates a deposit address for a currency on a network, where crypto.com supports it, see
+/// [`CreateDepositAddressParams`]. Select from the existing ones with
+/// [`DepositAddress::select`] first.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, `api_key`, or
+/// `secret_key`, or [`ApiError::Http`] if send fails or the response cannot be deserialized.
+pub async fn create_deposit_address(
+    config: &Config,
+    params: CreateDepositAddressParams,
+) -> Result<ApiResponse<DepositAddressItem>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let Some(ref api_key) = config.api_key else {
+        return Err(ApiError::ConfigMissing("api_key".to_owned()));
+    };
+
+    let Some(ref secret) = config.secret_key else {
+        return Err(ApiError::ConfigMissing("secret_key".to_owned()));
+    };
+
+    let req = ApiRequestBuilder::default()
+        .with_id(0)
+        .with_method("private/create-deposit-address")
+        .with_params(params)
+        .with_api_key(api_key)
+        .with_nonce()
+        .with_digital_signature(secret)
+        .build()?;
+
+    let res = client
+        .post(format!("{rest_url}{}", req.method))
+        .json(&req)
+        .send()
+        .await?
+        .json::<ApiResponse<DepositAddressItem>>()
+        .await?;
+
+    Ok(res)
+}
+
 /// Returns the account balance of a user for a particular token.
 ///
 /// # Errors
 ///
-/// Will return [`reqwest::Error`] if send fails or if serialization fails.
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, `api_key`, or
+/// `secret_key`, or [`ApiError::Http`] if send fails or the response cannot be deserialized.
 pub async fn get_account_summary(
     config: &Config,
     params: AccountSummaryParams,
 ) -> Result<ApiResponse<AccountSummary>> {
-    let client = reqwest::Client::new();
+    let client = super::client(config)?;
 
     let Some(ref rest_url) = config.rest_url else {
-        anyhow::bail!(ApiError::ConfigMissing("rest_url".to_owned()));
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
     };
 
     let Some(ref api_key) = config.api_key else {
-        anyhow::bail!(ApiError::ConfigMissing("api_key".to_owned()));
+        return Err(ApiError::ConfigMissing("api_key".to_owned()));
     };
 
     let Some(ref secret) = config.secret_key else {
-        anyhow::bail!(ApiError::ConfigMissing("secret_key".to_owned()));
+        return Err(ApiError::ConfigMissing("secret_key".to_owned()));
     };
 
     let req = ApiRequestBuilder::default()
@@ -293,12 +442,13 @@ pub async fn get_account_summary(
         .with_method("private/get-account-summary")
         .with_params(params)
         .with_api_key(api_key)
+        .with_nonce()
         .with_digital_signature(secret)
         .build()?;
 
     let res = client
-        .post(rest_url.to_string())
-        .body(serde_json::to_string(&req)?)
+        .post(format!("{rest_url}{}", req.method))
+        .json(&req)
         .send()
         .await?
         .json::<ApiResponse<AccountSummary>>()
@@ -306,3 +456,93 @@ pub async fn get_account_summary(
 
     Ok(res)
 }
+
+/// Quotes converting small balances to CRO, the quote is converted with [`convert_dust`] before
+/// it expires.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, `api_key`, or
+/// `secret_key`, or [`ApiError::Http`] if send fails or the response cannot be deserialized.
+pub async fn get_dust_conversion_quote(
+    config: &Config,
+    params: DustConversionQuoteParams,
+) -> Result<ApiResponse<DustConversionQuote>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let Some(ref api_key) = config.api_key else {
+        return Err(ApiError::ConfigMissing("api_key".to_owned()));
+    };
+
+    let Some(ref secret) = config.secret_key else {
+        return Err(ApiError::ConfigMissing("secret_key".to_owned()));
+    };
+
+    let req = ApiRequestBuilder::default()
+        .with_id(0)
+        .with_method("private/get-dust-conversion-quote")
+        .with_params(params)
+        .with_api_key(api_key)
+        .with_nonce()
+        .with_digital_signature(secret)
+        .build()?;
+
+    let res = client
+        .post(format!("{rest_url}{}", req.method))
+        .json(&req)
+        .send()
+        .await?
+        .json::<ApiResponse<DustConversionQuote>>()
+        .await?;
+
+    Ok(res)
+}
+
+/// Converts the small balances of an unexpired quote from [`get_dust_conversion_quote`] to CRO,
+/// e.g. `convert_dust(&config, (&quote).into())`.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, `api_key`, or
+/// `secret_key`, or [`ApiError::Http`] if send fails or the response cannot be deserialized.
+pub async fn convert_dust(
+    config: &Config,
+    params: ConvertDustParams,
+) -> Result<ApiResponse<DustConversion>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let Some(ref api_key) = config.api_key else {
+        return Err(ApiError::ConfigMissing("api_key".to_owned()));
+    };
+
+    let Some(ref secret) = config.secret_key else {
+        return Err(ApiError::ConfigMissing("secret_key".to_owned()));
+    };
+
+    let req = ApiRequestBuilder::default()
+        .with_id(0)
+        .with_method("private/convert-dust")
+        .with_params(params)
+        .with_api_key(api_key)
+        .with_nonce()
+        .with_digital_signature(secret)
+        .build()?;
+
+    let res = client
+        .post(format!("{rest_url}{}", req.method))
+        .json(&req)
+        .send()
+        .await?
+        .json::<ApiResponse<DustConversion>>()
+        .await?;
+
+    Ok(res)
+}
@@ -1,28 +1,37 @@
 //! Public REST API functions.
 
-use anyhow::Result;
+use crate::error::Result;
 
 use crate::api_response::ApiResponse;
 use crate::prelude::ApiError;
 use crate::rest::data::{
+    announcements::{Announcements, GetAnnouncementsParams},
     book::{BookRes, RawBookRes},
-    candlestick::{CandlestickRes, RawCandlestickRes},
+    candlestick::{CandlestickParams, CandlestickRes, RawCandlestickRes},
+    conversion_rate::{ConversionRate, ConversionRateParams, RawConversionRate},
+    expired_settlement_price::{
+        ExpiredSettlementPriceParams, ExpiredSettlementPriceRes, RawExpiredSettlementPriceRes,
+    },
     instruments::{InstrumentsRes, RawInstrumentsRes},
-    ticker::{RawTickerRes, TickerRes},
-    trades::{RawTradesRes, TradesRes},
+    insurance::{InsuranceParams, InsuranceRes, RawInsuranceRes},
+    ticker::{RawTickerRes, TickerRes, TickersRes},
+    trades::{RawTradesRes, TradesParams, TradesRes},
+    valuations::{GetValuationsParams, RawValuationsRes, ValuationType, ValuationsRes},
 };
+use crate::types::{Depth, Interval};
 use crate::utils::config::Config;
 
 /// Try to get the instrument data.
 ///
 /// # Errors
 ///
-/// Will return [`reqwest::Error`] if send fails or if serialization fails.
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
 pub async fn get_instruments(config: &Config) -> Result<ApiResponse<InstrumentsRes>> {
-    let client = reqwest::Client::new();
+    let client = super::client(config)?;
 
     let Some(ref rest_url) = config.rest_url else {
-        anyhow::bail!(ApiError::ConfigMissing("rest_url".to_owned()));
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
     };
 
     let res = client
@@ -48,17 +57,18 @@ pub async fn get_instruments(config: &Config) -> Result<ApiResponse<InstrumentsR
     })
 }
 
-/// Try to get the book data.
+/// Try to get the book data, `depth` levels on each side.
 ///
 /// # Errors
 ///
-/// Will return [`reqwest::Error`] if send fails or if serialization fails.
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
 pub async fn get_book(
     config: &Config,
     instrument_name: String,
-    depth: u8,
+    depth: Depth,
 ) -> Result<ApiResponse<BookRes>> {
-    let client = reqwest::Client::new();
+    let client = super::client(config)?;
 
     let params = [
         ("instrument_name", &instrument_name),
@@ -66,7 +76,7 @@ pub async fn get_book(
     ];
 
     let Some(ref rest_url) = config.rest_url else {
-        anyhow::bail!(ApiError::ConfigMissing("rest_url".to_owned()));
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
     };
 
     let res = client
@@ -97,21 +107,22 @@ pub async fn get_book(
 ///
 /// # Errors
 ///
-/// Will return [`reqwest::Error`] if send fails or if serialization fails.
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
 pub async fn get_candlestick(
     config: &Config,
     instrument_name: String,
-    timeframe: String,
+    timeframe: Interval,
 ) -> Result<ApiResponse<CandlestickRes>> {
-    let client = reqwest::Client::new();
+    let client = super::client(config)?;
 
     let params = [
-        ("instrument_name", &instrument_name),
-        ("timeframe", &timeframe),
+        ("instrument_name", instrument_name.as_str()),
+        ("timeframe", timeframe.as_rest_str()),
     ];
 
     let Some(ref rest_url) = config.rest_url else {
-        anyhow::bail!(ApiError::ConfigMissing("rest_url".to_owned()));
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
     };
 
     let res = client
@@ -138,19 +149,60 @@ pub async fn get_candlestick(
     })
 }
 
+/// Try to get the candlestick data between `start_ts` and `end_ts`.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
+pub async fn get_candlestick_range(
+    config: &Config,
+    params: &CandlestickParams,
+) -> Result<ApiResponse<CandlestickRes>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let res = client
+        .get(format!("{rest_url}public/get-candlestick"))
+        .query(params)
+        .send()
+        .await?
+        .json::<ApiResponse<RawCandlestickRes>>()
+        .await?;
+
+    Ok(ApiResponse {
+        id: res.id,
+        method: res.method,
+        result: if let Some(ref res) = res.result {
+            Some(CandlestickRes::try_from(res)?)
+        } else {
+            None
+        },
+        code: res.code,
+        message: res.message,
+        original: res.original,
+        detail_code: res.detail_code,
+        detail_message: res.detail_message,
+    })
+}
+
 /// Try to get the ticker data.
 ///
 /// # Errors
 ///
-/// Will return [`reqwest::Error`] if send fails or if serialization fails.
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
 pub async fn get_ticker(
     config: &Config,
     instrument_name: Option<String>,
 ) -> Result<ApiResponse<TickerRes>> {
-    let client = reqwest::Client::new();
+    let client = super::client(config)?;
 
     let Some(ref rest_url) = config.rest_url else {
-        anyhow::bail!(ApiError::ConfigMissing("rest_url".to_owned()));
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
     };
 
     let mut res = client.get(format!("{rest_url}public/get-ticker"));
@@ -183,19 +235,41 @@ pub async fn get_ticker(
     })
 }
 
+/// Try to get the tickers of every instrument, by instrument name.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
+pub async fn get_tickers(config: &Config) -> Result<ApiResponse<TickersRes>> {
+    let res = get_ticker(config, None).await?;
+
+    Ok(ApiResponse {
+        id: res.id,
+        method: res.method,
+        result: res.result.map(TickersRes::from),
+        code: res.code,
+        message: res.message,
+        original: res.original,
+        detail_code: res.detail_code,
+        detail_message: res.detail_message,
+    })
+}
+
 /// Try to get the trades data.
 ///
 /// # Errors
 ///
-/// Will return [`reqwest::Error`] if send fails or if serialization fails.
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
 pub async fn get_trades(
     config: &Config,
     instrument_name: Option<String>,
 ) -> Result<ApiResponse<TradesRes>> {
-    let client = reqwest::Client::new();
+    let client = super::client(config)?;
 
     let Some(ref rest_url) = config.rest_url else {
-        anyhow::bail!(ApiError::ConfigMissing("rest_url".to_owned()));
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
     };
 
     let mut res = client.get(format!("{rest_url}public/get-trades"));
@@ -227,3 +301,283 @@ pub async fn get_trades(
         detail_message: res.detail_message,
     })
 }
+
+/// Try to get the trades between `start_ts` and `end_ts`, newest first, see
+/// [`crate::history::TradeDownloader`] for ranges with more trades than one request returns.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
+pub async fn get_trades_range(
+    config: &Config,
+    params: &TradesParams,
+) -> Result<ApiResponse<TradesRes>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let res = client
+        .get(format!("{rest_url}public/get-trades"))
+        .query(params)
+        .send()
+        .await?
+        .json::<ApiResponse<RawTradesRes>>()
+        .await?;
+
+    Ok(ApiResponse {
+        id: res.id,
+        method: res.method,
+        result: if let Some(ref res) = res.result {
+            Some(TradesRes::try_from(res)?)
+        } else {
+            None
+        },
+        code: res.code,
+        message: res.message,
+        original: res.original,
+        detail_code: res.detail_code,
+        detail_message: res.detail_message,
+    })
+}
+
+/// Try to get the index price, mark price, or funding rates of an instrument.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
+pub async fn get_valuations(
+    config: &Config,
+    params: &GetValuationsParams,
+) -> Result<ApiResponse<ValuationsRes>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let res = client
+        .get(format!("{rest_url}public/get-valuations"))
+        .query(params)
+        .send()
+        .await?
+        .json::<ApiResponse<RawValuationsRes>>()
+        .await?;
+
+    Ok(ApiResponse {
+        id: res.id,
+        method: res.method,
+        result: if let Some(ref res) = res.result {
+            Some(ValuationsRes::try_from(res)?)
+        } else {
+            None
+        },
+        code: res.code,
+        message: res.message,
+        original: res.original,
+        detail_code: res.detail_code,
+        detail_message: res.detail_message,
+    })
+}
+
+/// Try to get the hourly funding rate history of a perpetual, e.g. BTCUSD-PERP, between
+/// `start_ts` and `end_ts` in ms since the UNIX epoch.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
+pub async fn get_funding_rate_history(
+    config: &Config,
+    instrument_name: &str,
+    start_ts: Option<u64>,
+    end_ts: Option<u64>,
+) -> Result<ApiResponse<ValuationsRes>> {
+    get_valuations(
+        config,
+        &GetValuationsParams {
+            instrument_name: instrument_name.to_owned(),
+            valuation_type: ValuationType::FundingHist,
+            count: None,
+            start_ts,
+            end_ts,
+        },
+    )
+    .await
+}
+
+/// Try to get the estimated funding rate for the next interval of a perpetual, e.g.
+/// BTCUSD-PERP.
+///
+/// See [`crate::rest::data::valuations::funding_payment`] for what it costs a position.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
+pub async fn get_estimated_funding_rate(
+    config: &Config,
+    instrument_name: &str,
+) -> Result<ApiResponse<ValuationsRes>> {
+    get_valuations(
+        config,
+        &GetValuationsParams {
+            instrument_name: instrument_name.to_owned(),
+            valuation_type: ValuationType::EstimatedFundingRate,
+            count: Some(1),
+            start_ts: None,
+            end_ts: None,
+        },
+    )
+    .await
+}
+
+/// Try to get the exchange announcements, including scheduled system maintenance.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
+pub async fn get_announcements(
+    config: &Config,
+    params: &GetAnnouncementsParams,
+) -> Result<ApiResponse<Announcements>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let res = client
+        .get(format!("{rest_url}public/get-announcements"))
+        .query(params)
+        .send()
+        .await?
+        .json::<ApiResponse<Announcements>>()
+        .await?;
+
+    Ok(res)
+}
+
+/// Try to get the settlement prices of expired instruments.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
+pub async fn get_expired_settlement_price(
+    config: &Config,
+    params: &ExpiredSettlementPriceParams,
+) -> Result<ApiResponse<ExpiredSettlementPriceRes>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let res = client
+        .get(format!("{rest_url}public/get-expired-settlement-price"))
+        .query(params)
+        .send()
+        .await?
+        .json::<ApiResponse<RawExpiredSettlementPriceRes>>()
+        .await?;
+
+    Ok(ApiResponse {
+        id: res.id,
+        method: res.method,
+        result: if let Some(ref res) = res.result {
+            Some(ExpiredSettlementPriceRes::try_from(res)?)
+        } else {
+            None
+        },
+        code: res.code,
+        message: res.message,
+        original: res.original,
+        detail_code: res.detail_code,
+        detail_message: res.detail_message,
+    })
+}
+
+/// Try to get the balance history of the insurance fund.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
+pub async fn get_insurance(
+    config: &Config,
+    params: &InsuranceParams,
+) -> Result<ApiResponse<InsuranceRes>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let res = client
+        .get(format!("{rest_url}public/get-insurance"))
+        .query(params)
+        .send()
+        .await?
+        .json::<ApiResponse<RawInsuranceRes>>()
+        .await?;
+
+    Ok(ApiResponse {
+        id: res.id,
+        method: res.method,
+        result: if let Some(ref res) = res.result {
+            Some(InsuranceRes::try_from(res)?)
+        } else {
+            None
+        },
+        code: res.code,
+        message: res.message,
+        original: res.original,
+        detail_code: res.detail_code,
+        detail_message: res.detail_message,
+    })
+}
+
+/// Try to get the rate one unit of a liquid staking token, e.g. CDCETH, converts at.
+///
+/// # Errors
+///
+/// Will return [`ApiError::ConfigMissing`] if `config` has no `rest_url`, or [`ApiError::Http`] if
+/// send fails or the response cannot be deserialized.
+pub async fn get_conversion_rate(
+    config: &Config,
+    params: &ConversionRateParams,
+) -> Result<ApiResponse<ConversionRate>> {
+    let client = super::client(config)?;
+
+    let Some(ref rest_url) = config.rest_url else {
+        return Err(ApiError::ConfigMissing("rest_url".to_owned()));
+    };
+
+    let res = client
+        .get(format!("{rest_url}public/get-conversion-rate"))
+        .query(params)
+        .send()
+        .await?
+        .json::<ApiResponse<RawConversionRate>>()
+        .await?;
+
+    Ok(ApiResponse {
+        id: res.id,
+        method: res.method,
+        result: if let Some(ref res) = res.result {
+            Some(ConversionRate::try_from(res)?)
+        } else {
+            None
+        },
+        code: res.code,
+        message: res.message,
+        original: res.original,
+        detail_code: res.detail_code,
+        detail_message: res.detail_message,
+    })
+}
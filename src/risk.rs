@@ -0,0 +1,306 @@
+//! Local risk limits checked before orders are sent.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::error::RiskViolation;
+use crate::rest::data::InstrumentRules;
+#[cfg(feature = "rest")]
+use crate::rest::instrument_cache::InstrumentCache;
+use crate::websocket::actions::spot_trading_api::{CreateOrder, CreateOrderList};
+use crate::websocket::data::{EventKind, UserOrderRes, UserTradeRes};
+use crate::websocket::WebsocketData;
+
+/// Limits enforced by a [`RiskManager`], `None` is unlimited.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RiskLimits {
+    /// Most an order may be worth in the quote currency, see [`order_notional`].
+    pub max_order_notional: Option<f64>,
+    /// Most orders that may be open at once, counting the ones being sent.
+    pub max_open_orders: Option<usize>,
+    /// Largest position size in any one instrument, long or short, counting the ones being sent
+    /// as if they were filled.
+    pub max_position: Option<f64>,
+}
+
+/// Rejects orders that would break its [`RiskLimits`] before they are sent.
+///
+/// Feed it [`WebsocketData`] with [`RiskManager::process`], it uses `user.order` for open orders,
+/// `user.trade` fills for positions, and `trade` and `ticker` for the last prices market orders
+/// are checked at. Subscribe to one of them for every instrument traded by market order, a market
+/// order that has to be priced without a last price is rejected. Set it on a controller with
+/// [`crate::controller::Controller::set_risk_manager`] to check every order pushed through it.
+#[derive(Clone, Debug, Default)]
+pub struct RiskManager {
+    /// The limits checked.
+    limits: RiskLimits,
+    /// Every order is rejected while set.
+    killed: bool,
+    /// IDs of the open orders by instrument.
+    open_orders: HashMap<String, HashSet<String>>,
+    /// Position size by instrument, negative when short.
+    positions: HashMap<String, f64>,
+    /// Price of the last trade by instrument.
+    last_prices: HashMap<String, f64>,
+    /// Orders for instruments it does not list are rejected while set.
+    #[cfg(feature = "rest")]
+    instrument_cache: Option<Arc<InstrumentCache>>,
+}
+
+impl RiskManager {
+    /// A manager enforcing `limits`, with no open orders or positions.
+    #[must_use]
+    pub fn new(limits: RiskLimits) -> Self {
+        Self {
+            limits,
+            ..Self::default()
+        }
+    }
+
+    /// Reject orders for instruments that `cache` does not list.
+    ///
+    /// Only the cached instruments are checked, the controller refreshes them before checking an
+    /// order once they are older than the TTL. Orders are not checked against an empty cache.
+    #[cfg(feature = "rest")]
+    #[must_use]
+    pub fn with_instrument_cache(mut self, cache: Arc<InstrumentCache>) -> Self {
+        self.instrument_cache = Some(cache);
+
+        self
+    }
+
+    /// The instruments orders are checked against, if set.
+    #[cfg(feature = "rest")]
+    #[must_use]
+    pub const fn instrument_cache(&self) -> Option<&Arc<InstrumentCache>> {
+        self.instrument_cache.as_ref()
+    }
+
+    /// The rules of the cached instruments whatever their age, `None` without a cache.
+    fn cached_rules(&self) -> Option<Arc<InstrumentRules>> {
+        #[cfg(feature = "rest")]
+        {
+            self.instrument_cache
+                .as_ref()
+                .and_then(|cache| cache.cached_rules())
+        }
+
+        #[cfg(not(feature = "rest"))]
+        {
+            None
+        }
+    }
+
+    /// The limits checked.
+    #[must_use]
+    pub const fn limits(&self) -> &RiskLimits {
+        &self.limits
+    }
+
+    /// Replace the limits checked.
+    pub fn set_limits(&mut self, limits: RiskLimits) {
+        self.limits = limits;
+    }
+
+    /// Reject every order while `killed` is set.
+    pub fn set_kill_switch(&mut self, killed: bool) {
+        self.killed = killed;
+    }
+
+    /// Whether the kill switch is set.
+    #[must_use]
+    pub const fn is_killed(&self) -> bool {
+        self.killed
+    }
+
+    /// Number of open orders across every instrument.
+    #[must_use]
+    pub fn open_orders(&self) -> usize {
+        self.open_orders.values().map(HashSet::len).sum()
+    }
+
+    /// Position size in `instrument_name`, negative when short.
+    #[must_use]
+    pub fn position(&self, instrument_name: &str) -> f64 {
+        self.positions
+            .get(instrument_name)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Set the position size in `instrument_name`, e.g. from an account summary on start.
+    pub fn set_position(&mut self, instrument_name: impl Into<String>, size: f64) {
+        self.positions.insert(instrument_name.into(), size);
+    }
+
+    /// Price of the last trade in `instrument_name`, `None` until a trade or ticker is seen.
+    #[must_use]
+    pub fn last_price(&self, instrument_name: &str) -> Option<f64> {
+        self.last_prices.get(instrument_name).copied()
+    }
+
+    /// Set the price of the last trade in `instrument_name`, e.g. from a REST ticker on start.
+    pub fn set_last_price(&mut self, instrument_name: impl Into<String>, price: f64) {
+        self.last_prices.insert(instrument_name.into(), price);
+    }
+
+    /// Update the manager with any data it uses, other data is ignored.
+    pub fn process(&mut self, data: &WebsocketData) {
+        match *data {
+            WebsocketData::UserOrder(ref orders) => self.apply_orders(orders),
+            WebsocketData::UserTrade(ref trades) if trades.kind != EventKind::Snapshot => {
+                self.apply_trades(trades);
+            }
+            // Trades are sent newest first.
+            WebsocketData::Trade(ref trades) => {
+                if let Some(trade) = trades.data.first() {
+                    self.set_last_price(trades.instrument_name.as_ref(), trade.p);
+                }
+            }
+            WebsocketData::Ticker(ref tickers) => {
+                for ticker in &tickers.data {
+                    if let Some(price) = ticker.a {
+                        self.set_last_price(ticker.i.as_ref(), price);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Track the open orders in `orders`, a snapshot replaces the ones of its instrument.
+    pub fn apply_orders(&mut self, orders: &UserOrderRes) {
+        if orders.kind == EventKind::Snapshot {
+            self.open_orders.remove(&orders.instrument_name);
+        }
+
+        for order in &orders.data {
+            let open = self
+                .open_orders
+                .entry(order.instrument_name.clone())
+                .or_default();
+
+            if order.status == "ACTIVE" {
+                open.insert(order.order_id.clone());
+            } else {
+                open.remove(&order.order_id);
+            }
+        }
+    }
+
+    /// Apply the fills in `trades` to the position.
+    pub fn apply_trades(&mut self, trades: &UserTradeRes) {
+        let position = self
+            .positions
+            .entry(trades.instrument_name.clone())
+            .or_default();
+
+        for trade in &trades.data {
+            *position += signed_quantity(&trade.side, trade.traded_quantity);
+        }
+    }
+
+    /// Check `order` against the limits.
+    ///
+    /// # Errors
+    ///
+    /// Will return the first [`RiskViolation`] the order would cause.
+    pub fn check_order(&self, order: &CreateOrder) -> Result<(), RiskViolation> {
+        self.check(std::slice::from_ref(order))
+    }
+
+    /// Check every order in `list` against the limits, as if they were all sent at once.
+    ///
+    /// # Errors
+    ///
+    /// Will return the first [`RiskViolation`] the orders would cause.
+    pub fn check_order_list(&self, list: &CreateOrderList) -> Result<(), RiskViolation> {
+        self.check(&list.order_list)
+    }
+
+    /// Check `orders`, sent together, against the limits.
+    fn check(&self, orders: &[CreateOrder]) -> Result<(), RiskViolation> {
+        if self.killed {
+            return Err(RiskViolation::KillSwitch);
+        }
+
+        if let Some(max) = self.limits.max_open_orders {
+            let open = self.open_orders() + orders.len();
+
+            if open > max {
+                return Err(RiskViolation::OpenOrders { open, max });
+            }
+        }
+
+        let rules = self.cached_rules();
+        let mut positions: HashMap<&str, f64> = HashMap::new();
+
+        for order in orders {
+            if let Some(ref rules) = rules {
+                if !rules.rules.contains_key(&order.instrument_name) {
+                    return Err(RiskViolation::UnknownInstrument(
+                        order.instrument_name.clone(),
+                    ));
+                }
+            }
+
+            if let Some(max) = self.limits.max_order_notional {
+                let notional = order_notional(order)
+                    .or_else(|| Some(order.quantity()? * self.last_price(&order.instrument_name)?))
+                    .ok_or_else(|| RiskViolation::Unpriced(order.instrument_name.clone()))?;
+
+                if notional > max {
+                    return Err(RiskViolation::OrderNotional { notional, max });
+                }
+            }
+
+            let Some(max) = self.limits.max_position else {
+                continue;
+            };
+
+            // A market buy sized by notional fills about its notional at the last price.
+            let quantity = order
+                .quantity()
+                .or_else(|| Some(order.notional()? / self.last_price(&order.instrument_name)?))
+                .ok_or_else(|| RiskViolation::Unpriced(order.instrument_name.clone()))?;
+
+            let position = positions
+                .entry(&order.instrument_name)
+                .or_insert_with(|| self.position(&order.instrument_name));
+            *position += signed_quantity(&order.side, quantity);
+
+            if position.abs() > max {
+                return Err(RiskViolation::Position {
+                    instrument_name: order.instrument_name.clone(),
+                    position: *position,
+                    max,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// What `order` is worth in the quote currency, its `notional` or its quantity at its price, or
+/// at its trigger price for stop and take profit orders.
+///
+/// `None` for market orders by quantity, their price is not known until they fill, the
+/// [`RiskManager`] checks them at [`RiskManager::last_price`].
+#[must_use]
+pub fn order_notional(order: &CreateOrder) -> Option<f64> {
+    if let Some(notional) = order.notional() {
+        return Some(notional);
+    }
+
+    Some(order.quantity()? * order.price.or(order.trigger_price)?)
+}
+
+/// `quantity` signed by `side`, negative for sells.
+fn signed_quantity(side: &str, quantity: f64) -> f64 {
+    if side.eq_ignore_ascii_case("SELL") {
+        -quantity
+    } else {
+        quantity
+    }
+}
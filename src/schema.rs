@@ -0,0 +1,384 @@
+//! Introspection of the methods and channels supported by this crate.
+//!
+//! [`schema`] lists every request method and subscription channel along with the Rust types used
+//! for its params and response, which is useful for generating documentation or bindings for
+//! systems built on top of this crate.
+
+#[cfg(any(feature = "rest", feature = "websocket-market"))]
+use std::any::type_name;
+use std::sync::OnceLock;
+
+/// Whether an entry is a request/response method or a subscription channel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventKind {
+    /// A method that is requested and answered once, e.g. `private/create-order`.
+    Method,
+    /// A subscription channel that pushes data, e.g. `book.{instrument_name}`.
+    Channel,
+}
+
+/// Where an entry is served from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transport {
+    /// The REST API.
+    Rest,
+    /// The websocket user API.
+    WebsocketUser,
+    /// The websocket market API.
+    WebsocketMarket,
+}
+
+/// Description of a supported method or channel.
+#[derive(Clone, Debug)]
+pub struct EventSchema {
+    /// Method name or channel pattern, e.g. `private/get-trades` or `ticker.{instrument_name}`.
+    pub name: &'static str,
+    /// Method or channel.
+    pub kind: EventKind,
+    /// Where it is served from.
+    pub transport: Transport,
+    /// Requires an API key and digital signature.
+    pub private: bool,
+    /// Type name of the params or action, if any.
+    pub params: Option<&'static str>,
+    /// Type name of the processed response, if any.
+    pub response: Option<&'static str>,
+    /// JSON schema of the params, if any.
+    #[cfg(feature = "schemars")]
+    pub params_schema: Option<schemars::Schema>,
+    /// JSON schema of the processed response, if any.
+    #[cfg(feature = "schemars")]
+    pub response_schema: Option<schemars::Schema>,
+}
+
+/// What a params or response type needs to be listed, a JSON schema with the `schemars` feature.
+#[cfg(any(feature = "rest", feature = "websocket-market"))]
+mod described {
+    #[cfg(feature = "schemars")]
+    pub(super) trait Described: schemars::JsonSchema {}
+
+    #[cfg(feature = "schemars")]
+    impl<T: ?Sized + schemars::JsonSchema> Described for T {}
+
+    #[cfg(not(feature = "schemars"))]
+    pub(super) trait Described {}
+
+    #[cfg(not(feature = "schemars"))]
+    impl<T: ?Sized> Described for T {}
+}
+
+#[cfg(any(feature = "rest", feature = "websocket-market"))]
+use described::Described;
+
+#[cfg(any(feature = "rest", feature = "websocket-market"))]
+impl EventSchema {
+    /// A new entry with no params and no response.
+    fn new(name: &'static str, kind: EventKind, transport: Transport) -> Self {
+        Self {
+            name,
+            kind,
+            transport,
+            private: name.starts_with("private/"),
+            params: None,
+            response: None,
+            #[cfg(feature = "schemars")]
+            params_schema: None,
+            #[cfg(feature = "schemars")]
+            response_schema: None,
+        }
+    }
+
+    /// With the params type.
+    fn with_params<T: ?Sized + Described>(mut self) -> Self {
+        self.params = Some(type_name::<T>());
+
+        #[cfg(feature = "schemars")]
+        {
+            self.params_schema = Some(schemars::schema_for!(T));
+        }
+
+        self
+    }
+
+    /// With the response type.
+    fn with_response<T: ?Sized + Described>(mut self) -> Self {
+        self.response = Some(type_name::<T>());
+
+        #[cfg(feature = "schemars")]
+        {
+            self.response_schema = Some(schemars::schema_for!(T));
+        }
+
+        self
+    }
+
+    /// Mark a channel as private.
+    #[cfg(feature = "websocket-market")]
+    fn private(mut self) -> Self {
+        self.private = true;
+        self
+    }
+}
+
+/// Every method and channel supported by the enabled features.
+#[must_use]
+pub fn schema() -> &'static [EventSchema] {
+    static SCHEMA: OnceLock<Vec<EventSchema>> = OnceLock::new();
+
+    SCHEMA.get_or_init(|| {
+        let entries = std::iter::empty();
+
+        #[cfg(feature = "websocket")]
+        let entries = entries.chain([auth_entry()]);
+
+        #[cfg(feature = "websocket-market")]
+        let entries = entries.chain(websocket_entries());
+
+        #[cfg(feature = "websocket")]
+        let entries = entries.chain(websocket_user_entries());
+
+        #[cfg(feature = "rest")]
+        let entries = entries.chain(rest_entries());
+
+        entries.collect()
+    })
+}
+
+/// Entry for `public/auth`, which needs signing.
+#[cfg(feature = "websocket")]
+fn auth_entry() -> EventSchema {
+    use crate::websocket::actions::Auth;
+
+    EventSchema::new("public/auth", EventKind::Method, Transport::WebsocketUser)
+        .with_params::<Auth>()
+}
+
+/// Entries for the websocket user and market APIs.
+#[cfg(feature = "websocket-market")]
+fn websocket_entries() -> Vec<EventSchema> {
+    use crate::rest::data::InstrumentsRes;
+    use crate::websocket::actions::{
+        GetCancelOnDisconnect, GetInstruments, SetCancelOnDisconnect, Subscribe,
+    };
+    use crate::websocket::data::{
+        BookRes, BookUpdateRes, CandlestickRes, OtcBookRes, Scope, TickerRes, TradeRes,
+        UserBalance, UserOrderRes, UserTradeRes, ValuationRes,
+    };
+
+    use EventKind::{Channel, Method};
+    use Transport::{WebsocketMarket, WebsocketUser};
+
+    vec![
+        // Sent by crypto.com on both websockets and answered with `public/respond-heartbeat`.
+        EventSchema::new("public/heartbeat", Method, WebsocketUser),
+        EventSchema::new("public/respond-heartbeat", Method, WebsocketUser),
+        EventSchema::new("public/heartbeat", Method, WebsocketMarket),
+        EventSchema::new("public/respond-heartbeat", Method, WebsocketMarket),
+        EventSchema::new("public/get-instruments", Method, WebsocketUser)
+            .with_params::<GetInstruments>()
+            .with_response::<InstrumentsRes>(),
+        EventSchema::new("private/set-cancel-on-disconnect", Method, WebsocketUser)
+            .with_params::<SetCancelOnDisconnect>()
+            .with_response::<Scope>(),
+        EventSchema::new("private/get-cancel-on-disconnect", Method, WebsocketUser)
+            .with_params::<GetCancelOnDisconnect>()
+            .with_response::<Scope>(),
+        EventSchema::new("user.order.{instrument_name}", Channel, WebsocketUser)
+            .private()
+            .with_params::<Subscribe>()
+            .with_response::<UserOrderRes>(),
+        EventSchema::new("user.trade.{instrument_name}", Channel, WebsocketUser)
+            .private()
+            .with_params::<Subscribe>()
+            .with_response::<UserTradeRes>(),
+        EventSchema::new("user.balance", Channel, WebsocketUser)
+            .private()
+            .with_params::<Subscribe>()
+            .with_response::<Vec<UserBalance>>(),
+        EventSchema::new("book.{instrument_name}", Channel, WebsocketMarket)
+            .with_params::<Subscribe>()
+            .with_response::<BookRes>(),
+        EventSchema::new("book.update", Channel, WebsocketMarket)
+            .with_params::<Subscribe>()
+            .with_response::<BookUpdateRes>(),
+        EventSchema::new("ticker.{instrument_name}", Channel, WebsocketMarket)
+            .with_params::<Subscribe>()
+            .with_response::<TickerRes>(),
+        EventSchema::new("ticker", Channel, WebsocketMarket)
+            .with_params::<Subscribe>()
+            .with_response::<TickerRes>(),
+        EventSchema::new("trade.{instrument_name}", Channel, WebsocketMarket)
+            .with_params::<Subscribe>()
+            .with_response::<TradeRes>(),
+        EventSchema::new(
+            "candlestick.{time_frame}.{instrument_name}",
+            Channel,
+            WebsocketMarket,
+        )
+        .with_params::<Subscribe>()
+        .with_response::<CandlestickRes>(),
+        EventSchema::new("otc_book.{instrument_name}", Channel, WebsocketMarket)
+            .with_params::<Subscribe>()
+            .with_response::<OtcBookRes>(),
+        EventSchema::new("index.{instrument_name}", Channel, WebsocketMarket)
+            .with_params::<Subscribe>()
+            .with_response::<ValuationRes>(),
+        EventSchema::new("mark.{instrument_name}", Channel, WebsocketMarket)
+            .with_params::<Subscribe>()
+            .with_response::<ValuationRes>(),
+        EventSchema::new("funding.{instrument_name}", Channel, WebsocketMarket)
+            .with_params::<Subscribe>()
+            .with_response::<ValuationRes>(),
+        EventSchema::new(
+            "estimatedfunding.{instrument_name}",
+            Channel,
+            WebsocketMarket,
+        )
+        .with_params::<Subscribe>()
+        .with_response::<ValuationRes>(),
+    ]
+}
+
+/// Entries for the trading and wallet methods of the websocket user API.
+#[cfg(feature = "websocket")]
+fn websocket_user_entries() -> Vec<EventSchema> {
+    use crate::rest::data::{DepositAddress, DepositAddressItem};
+    use crate::websocket::actions::{spot_trading_api, wallet_management_api};
+    use crate::websocket::data::{
+        AccountSummary, CancelOrder, CancelOrderList, CreateOrder, CreateOrderList,
+        CreateWithdrawal, OpenOrders, OrderDetail, OrderHistory, Trades, WithdrawalHistory,
+    };
+
+    use EventKind::Method;
+    use Transport::WebsocketUser;
+
+    vec![
+        EventSchema::new("private/create-withdrawal", Method, WebsocketUser)
+            .with_params::<wallet_management_api::CreateWithdrawal>()
+            .with_response::<CreateWithdrawal>(),
+        EventSchema::new("private/get-withdrawal-history", Method, WebsocketUser)
+            .with_params::<wallet_management_api::GetWithdrawalHistory>()
+            .with_response::<WithdrawalHistory>(),
+        EventSchema::new("private/get-deposit-address", Method, WebsocketUser)
+            .with_params::<wallet_management_api::GetDepositAddress>()
+            .with_response::<DepositAddress>(),
+        EventSchema::new("private/create-deposit-address", Method, WebsocketUser)
+            .with_params::<wallet_management_api::CreateDepositAddress>()
+            .with_response::<DepositAddressItem>(),
+        EventSchema::new("private/get-account-summary", Method, WebsocketUser)
+            .with_params::<spot_trading_api::GetAccountSummary>()
+            .with_response::<AccountSummary>(),
+        EventSchema::new("private/create-order", Method, WebsocketUser)
+            .with_params::<spot_trading_api::CreateOrder>()
+            .with_response::<CreateOrder>(),
+        EventSchema::new("private/cancel-order", Method, WebsocketUser)
+            .with_params::<spot_trading_api::CancelOrder>()
+            .with_response::<CancelOrder>(),
+        EventSchema::new("private/create-order-list", Method, WebsocketUser)
+            .with_params::<spot_trading_api::CreateOrderList>()
+            .with_response::<CreateOrderList>(),
+        EventSchema::new("private/cancel-order-list", Method, WebsocketUser)
+            .with_params::<spot_trading_api::CancelOrderList>()
+            .with_response::<CancelOrderList>(),
+        EventSchema::new("private/cancel-all-orders", Method, WebsocketUser)
+            .with_params::<spot_trading_api::CancelAllOrders>(),
+        EventSchema::new("private/get-order-history", Method, WebsocketUser)
+            .with_params::<spot_trading_api::GetOrderHistory>()
+            .with_response::<OrderHistory>(),
+        EventSchema::new("private/get-open-orders", Method, WebsocketUser)
+            .with_params::<spot_trading_api::GetOpenOrders>()
+            .with_response::<OpenOrders>(),
+        EventSchema::new("private/get-order-detail", Method, WebsocketUser)
+            .with_params::<spot_trading_api::GetOrderDetail>()
+            .with_response::<OrderDetail>(),
+        EventSchema::new("private/get-trades", Method, WebsocketUser)
+            .with_params::<spot_trading_api::GetTrades>()
+            .with_response::<Trades>(),
+    ]
+}
+
+/// Entries for the REST API.
+#[cfg(feature = "rest")]
+fn rest_entries() -> Vec<EventSchema> {
+    use crate::rest::data::{
+        AccountSummary, AccountSummaryParams, AllocateParams, Announcements, BookRes,
+        CandlestickParams, CandlestickRes, ConversionRate, ConversionRateParams, ConvertDustParams,
+        CreateDepositAddressParams, CreateWithdrawalRes, CurrencyNetworks, DepositAddress,
+        DepositAddressItem, DepositAddressParams, DepositHistory, DepositHistoryParams,
+        DustConversion, DustConversionQuote, DustConversionQuoteParams, EarnPosition,
+        EarnPositions, EarnPositionsParams, EarnProducts, EarnProductsParams, EarnYieldHistory,
+        EarnYieldHistoryParams, ExpiredSettlementPriceParams, ExpiredSettlementPriceRes,
+        GetAnnouncementsParams, GetValuationsParams, InstrumentsRes, InsuranceParams, InsuranceRes,
+        RedeemParams, TickerRes, TradesRes, ValuationsRes, WithdrawalHistory,
+    };
+    use crate::rest::private::{CreateWithdrawal, GetWithdrawalHistoryParams};
+
+    use EventKind::Method;
+    use Transport::Rest;
+
+    vec![
+        EventSchema::new("public/get-instruments", Method, Rest).with_response::<InstrumentsRes>(),
+        EventSchema::new("public/get-book", Method, Rest).with_response::<BookRes>(),
+        EventSchema::new("public/get-candlestick", Method, Rest)
+            .with_params::<CandlestickParams>()
+            .with_response::<CandlestickRes>(),
+        EventSchema::new("public/get-ticker", Method, Rest).with_response::<TickerRes>(),
+        EventSchema::new("public/get-trades", Method, Rest).with_response::<TradesRes>(),
+        EventSchema::new("public/get-announcements", Method, Rest)
+            .with_params::<GetAnnouncementsParams>()
+            .with_response::<Announcements>(),
+        EventSchema::new("public/get-expired-settlement-price", Method, Rest)
+            .with_params::<ExpiredSettlementPriceParams>()
+            .with_response::<ExpiredSettlementPriceRes>(),
+        EventSchema::new("public/get-insurance", Method, Rest)
+            .with_params::<InsuranceParams>()
+            .with_response::<InsuranceRes>(),
+        EventSchema::new("public/get-valuations", Method, Rest)
+            .with_params::<GetValuationsParams>()
+            .with_response::<ValuationsRes>(),
+        EventSchema::new("public/get-conversion-rate", Method, Rest)
+            .with_params::<ConversionRateParams>()
+            .with_response::<ConversionRate>(),
+        EventSchema::new("private/create-withdrawal", Method, Rest)
+            .with_params::<CreateWithdrawal>()
+            .with_response::<CreateWithdrawalRes>(),
+        EventSchema::new("private/get-currency-networks", Method, Rest)
+            .with_response::<CurrencyNetworks>(),
+        EventSchema::new("private/get-withdrawal-history", Method, Rest)
+            .with_params::<GetWithdrawalHistoryParams>()
+            .with_response::<WithdrawalHistory>(),
+        EventSchema::new("private/get-deposit-history", Method, Rest)
+            .with_params::<DepositHistoryParams>()
+            .with_response::<DepositHistory>(),
+        EventSchema::new("private/get-deposit-address", Method, Rest)
+            .with_params::<DepositAddressParams>()
+            .with_response::<DepositAddress>(),
+        EventSchema::new("private/create-deposit-address", Method, Rest)
+            .with_params::<CreateDepositAddressParams>()
+            .with_response::<DepositAddressItem>(),
+        EventSchema::new("private/get-account-summary", Method, Rest)
+            .with_params::<AccountSummaryParams>()
+            .with_response::<AccountSummary>(),
+        EventSchema::new("private/get-dust-conversion-quote", Method, Rest)
+            .with_params::<DustConversionQuoteParams>()
+            .with_response::<DustConversionQuote>(),
+        EventSchema::new("private/convert-dust", Method, Rest)
+            .with_params::<ConvertDustParams>()
+            .with_response::<DustConversion>(),
+        EventSchema::new("private/get-earn-products", Method, Rest)
+            .with_params::<EarnProductsParams>()
+            .with_response::<EarnProducts>(),
+        EventSchema::new("private/get-earn-positions", Method, Rest)
+            .with_params::<EarnPositionsParams>()
+            .with_response::<EarnPositions>(),
+        EventSchema::new("private/earn-allocate", Method, Rest)
+            .with_params::<AllocateParams>()
+            .with_response::<EarnPosition>(),
+        EventSchema::new("private/earn-redeem", Method, Rest)
+            .with_params::<RedeemParams>()
+            .with_response::<EarnPosition>(),
+        EventSchema::new("private/get-earn-yield-history", Method, Rest)
+            .with_params::<EarnYieldHistoryParams>()
+            .with_response::<EarnYieldHistory>(),
+    ]
+}
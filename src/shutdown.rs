@@ -0,0 +1,259 @@
+//! Shut down on Ctrl-C or SIGTERM, after a policy such as cancelling every open order has run.
+//!
+//! A [`ShutdownHook`] waits for the signal, runs its policy with the action queues of the
+//! controller for up to the policy timeout, then calls [`Controller::shutdown`]. A policy that
+//! fails or runs out of time does not stop the connections from being closed.
+//!
+//! ```no_run
+//! # async fn example(
+//! #     mut controller: crypto_com_api::controller::Controller<
+//! #         crypto_com_api::controller::UserWs,
+//! #         crypto_com_api::controller::MarketWs,
+//! #     >,
+//! # ) -> anyhow::Result<()> {
+//! use crypto_com_api::shutdown::ShutdownHook;
+//!
+//! let hook = ShutdownHook::cancel_all_orders(vec!["BTC_USDT".to_owned()]);
+//!
+//! // Trade until Ctrl-C, then cancel the orders and close the websockets.
+//! let report = hook.on_signal(&mut controller).await?;
+//! println!("shut down after {:?}", report.signal);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+use anyhow::Result;
+
+use crate::controller::Controller;
+use crate::utils::action_queue::ActionQueue;
+use crate::utils::in_flight::Api;
+use crate::utils::trace;
+#[cfg(feature = "websocket")]
+use crate::websocket::actions::spot_trading_api::CancelAllOrders;
+#[cfg(feature = "websocket")]
+use crate::websocket::actions::SetCancelOnDisconnect;
+
+/// How long a policy may run when no timeout is given.
+pub const DEFAULT_POLICY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long requests in flight are waited for when no timeout is given.
+pub const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The signal that started a shutdown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Signal {
+    /// SIGINT, i.e. Ctrl-C.
+    Interrupt,
+    /// SIGTERM, only on unix.
+    Terminate,
+}
+
+/// Wait for Ctrl-C, or SIGTERM on unix.
+///
+/// # Errors
+///
+/// Will return `Err` if the signal handlers cannot be installed.
+pub async fn signal() -> Result<Signal> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut terminate = signal(SignalKind::terminate())?;
+
+        tokio::select! {
+            interrupt = tokio::signal::ctrl_c() => interrupt.map(|()| Signal::Interrupt),
+            _ = terminate.recv() => Ok(Signal::Terminate),
+        }
+        .map_err(Into::into)
+    }
+
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c().await?;
+
+        Ok(Signal::Interrupt)
+    }
+}
+
+/// A boxed shutdown policy future, for policies whose future cannot be named.
+pub type PolicyFuture = Pin<Box<dyn Future<Output = Result<()>> + Send>>;
+
+/// What a shutdown policy is run with.
+#[derive(Clone, Debug)]
+pub struct ShutdownContext {
+    /// The signal that started the shutdown, `None` when [`ShutdownHook::run`] was called
+    /// directly.
+    pub signal: Option<Signal>,
+    /// Actions sender of the user websocket, `None` if it is not connected.
+    pub user_queue: Option<ActionQueue>,
+    /// Actions sender of the market websocket, `None` if it is not connected.
+    pub market_queue: Option<ActionQueue>,
+}
+
+/// How the shutdown policy ended.
+#[derive(Debug)]
+pub enum PolicyOutcome {
+    /// The policy finished in time.
+    Completed,
+    /// The policy returned an error.
+    Failed(anyhow::Error),
+    /// The policy was still running at the policy timeout.
+    TimedOut,
+}
+
+/// What happened during a shutdown.
+#[derive(Debug)]
+pub struct ShutdownReport {
+    /// The signal that started the shutdown.
+    pub signal: Option<Signal>,
+    /// How the policy ended.
+    pub policy: PolicyOutcome,
+    /// The IDs of the requests that were still unanswered when the connections closed.
+    pub unanswered: Vec<u64>,
+}
+
+/// A policy run before the connections close, then [`Controller::shutdown`].
+pub struct ShutdownHook<P> {
+    /// Run with the action queues of the controller.
+    policy: P,
+    /// How long `policy` may run.
+    policy_timeout: Duration,
+    /// Passed to [`Controller::shutdown`].
+    drain_timeout: Duration,
+}
+
+impl<P> fmt::Debug for ShutdownHook<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShutdownHook")
+            .field("policy_timeout", &self.policy_timeout)
+            .field("drain_timeout", &self.drain_timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "websocket")]
+impl ShutdownHook<()> {
+    /// A hook that sets cancel on disconnect for the user websocket and cancels every open order
+    /// in `instrument_names`.
+    ///
+    /// Cancel on disconnect is set first, so the exchange still cancels the orders when the
+    /// connection closes if the cancels run out of time.
+    #[must_use]
+    pub fn cancel_all_orders(
+        instrument_names: Vec<String>,
+    ) -> ShutdownHook<impl FnOnce(ShutdownContext) -> PolicyFuture> {
+        ShutdownHook::new(move |context: ShutdownContext| -> PolicyFuture {
+            Box::pin(cancel_all(context.user_queue, instrument_names))
+        })
+    }
+}
+
+impl<P, F> ShutdownHook<P>
+where
+    P: FnOnce(ShutdownContext) -> F,
+    F: Future<Output = Result<()>>,
+{
+    /// A hook running `policy`, with the default timeouts.
+    #[must_use]
+    pub const fn new(policy: P) -> Self {
+        Self {
+            policy,
+            policy_timeout: DEFAULT_POLICY_TIMEOUT,
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+        }
+    }
+
+    /// Give the policy up to `timeout` to finish.
+    #[must_use]
+    pub const fn with_policy_timeout(mut self, timeout: Duration) -> Self {
+        self.policy_timeout = timeout;
+        self
+    }
+
+    /// Wait up to `timeout` for requests in flight once the policy ends.
+    #[must_use]
+    pub const fn with_drain_timeout(mut self, timeout: Duration) -> Self {
+        self.drain_timeout = timeout;
+        self
+    }
+
+    /// Wait for Ctrl-C or SIGTERM, then [`ShutdownHook::run`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the signal handlers cannot be installed, otherwise the same errors as
+    /// [`ShutdownHook::run`].
+    pub async fn on_signal<U, M>(
+        self,
+        controller: &mut Controller<U, M>,
+    ) -> Result<ShutdownReport> {
+        let signal = signal().await?;
+
+        trace::warning!("Recieved {signal:?}, shutting down.");
+
+        self.run(controller, Some(signal)).await
+    }
+
+    /// Run the policy, then shut `controller` down.
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`Controller::shutdown`], errors of the policy are in the
+    /// report.
+    pub async fn run<U, M>(
+        self,
+        controller: &mut Controller<U, M>,
+        signal: Option<Signal>,
+    ) -> Result<ShutdownReport> {
+        let context = ShutdownContext {
+            signal,
+            user_queue: controller.queue(Api::User),
+            market_queue: controller.queue(Api::Market),
+        };
+
+        let policy = match tokio::time::timeout(self.policy_timeout, (self.policy)(context)).await {
+            Ok(Ok(())) => PolicyOutcome::Completed,
+            Ok(Err(err)) => {
+                trace::error!("Shutdown policy failed: {err}");
+                PolicyOutcome::Failed(err)
+            }
+            Err(_) => {
+                trace::warning!("Shutdown policy timed out.");
+                PolicyOutcome::TimedOut
+            }
+        };
+
+        let unanswered = controller.shutdown(self.drain_timeout).await?;
+
+        Ok(ShutdownReport {
+            signal,
+            policy,
+            unanswered,
+        })
+    }
+}
+
+/// Set cancel on disconnect on `queue` and cancel the orders in each of `instrument_names`.
+#[cfg(feature = "websocket")]
+async fn cancel_all(queue: Option<ActionQueue>, instrument_names: Vec<String>) -> Result<()> {
+    let Some(queue) = queue else {
+        anyhow::bail!("no user websocket to cancel orders on");
+    };
+
+    queue
+        .request(SetCancelOnDisconnect {
+            scope: "CONNECTION".to_owned(),
+        })
+        .await?;
+
+    for instrument_name in instrument_names {
+        queue.request(CancelAllOrders { instrument_name }).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+//! [Digital signatures](https://exchange-docs.crypto.com/spot/index.html#digital-signature) of
+//! private requests.
+//!
+//! Only built with the `signing` feature, which `rest` and `websocket` enable. A build with only
+//! `websocket-market` has no signing and cannot authorize.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// Hashing type for digital signatures.
+pub type HmacSha256 = Hmac<Sha256>;
+
+/// The payload signed for a request, method + id + api_key + parameter string + nonce.
+///
+/// The parameter string is the parameters as key + value (no spaces, no delimiters), see
+/// [`crate::utils::params_to_str`].
+#[must_use]
+pub fn payload(
+    method: &str,
+    id: Option<u64>,
+    api_key: Option<&str>,
+    params: Option<&serde_json::Value>,
+    nonce: Option<u64>,
+) -> String {
+    let mut payload = String::from(method);
+
+    if let Some(id) = id {
+        payload += &id.to_string();
+    }
+
+    if let Some(api_key) = api_key {
+        payload += api_key;
+    }
+
+    if let Some(params) = params {
+        payload += &crate::utils::params_to_str(params);
+    }
+
+    if let Some(nonce) = nonce {
+        payload += &nonce.to_string();
+    }
+
+    payload
+}
+
+/// The HMAC-SHA256 of `payload` keyed with `secret`, hex encoded.
+///
+/// # Panics
+///
+/// Will not panic, HMAC takes a key of any size.
+#[must_use]
+pub fn sign(secret: &str, payload: &str) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(payload.as_bytes());
+
+    hex::encode(mac.finalize().into_bytes())
+}
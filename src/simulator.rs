@@ -0,0 +1,491 @@
+//! Paper trading against the local order books, see [`SimulatedExchange`].
+
+use std::collections::HashMap;
+
+use crate::analytics::{Level, OrderBook};
+use crate::error::OrderError;
+use crate::types::{EpochMs, ExecInst, Time};
+use crate::utils::get_epoch_ms;
+use crate::utils::trace;
+use crate::websocket::actions::spot_trading_api::{CancelOrder, CreateOrder};
+use crate::websocket::data::{
+    EventKind, ExtraFields, OrderItem, UserOrderRes, UserTrade, UserTradeRes,
+};
+use crate::websocket::WebsocketData;
+
+/// An order resting in the [`SimulatedExchange`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulatedOrder {
+    /// Order ID assigned by the simulator.
+    pub order_id: u64,
+    /// Client order ID from the request, the order ID if none was given.
+    pub client_oid: String,
+    /// e.g. BTCUSD-PERP.
+    pub instrument_name: String,
+    /// BUY, SELL.
+    pub side: String,
+    /// LIMIT or MARKET.
+    pub order_type: String,
+    /// Limit price, 0 for market orders.
+    pub price: f64,
+    /// Order quantity, 0 for market buys by notional.
+    pub quantity: f64,
+    /// Amount to spend, market buys only.
+    pub notional: Option<f64>,
+    /// Quantity filled so far.
+    pub cumulative_quantity: f64,
+    /// Value filled so far.
+    pub cumulative_value: f64,
+    /// GOOD_TILL_CANCEL, FILL_OR_KILL, IMMEDIATE_OR_CANCEL.
+    pub time_in_force: String,
+    /// Execution instructions.
+    pub exec_inst: Vec<ExecInst>,
+    /// When the order was created.
+    pub create_time: Time,
+}
+
+impl SimulatedOrder {
+    /// Whether the order buys.
+    fn is_buy(&self) -> bool {
+        self.side.eq_ignore_ascii_case("BUY")
+    }
+
+    /// Quantity still to be filled.
+    fn remaining(&self) -> f64 {
+        (self.quantity - self.cumulative_quantity).max(0.0)
+    }
+
+    /// The order as it appears in `user.order` with `status`.
+    fn item(&self, status: &str, reason: Option<&str>, fee_currency: &str) -> OrderItem {
+        let avg_price = if self.cumulative_quantity > 0.0 {
+            self.cumulative_value / self.cumulative_quantity
+        } else {
+            0.0
+        };
+
+        OrderItem {
+            status: status.to_owned(),
+            reason: reason.map(str::to_owned),
+            side: self.side.clone(),
+            price: self.price,
+            quantity: self.quantity,
+            order_id: self.order_id.to_string(),
+            client_oid: self.client_oid.clone(),
+            create_time: self.create_time,
+            update_time: now(),
+            order_type: self.order_type.clone(),
+            instrument_name: self.instrument_name.clone(),
+            cumulative_quantity: self.cumulative_quantity,
+            cumulative_value: self.cumulative_value,
+            avg_price,
+            fee_currency: fee_currency.to_owned(),
+            time_in_force: self.time_in_force.clone(),
+            exec_inst: (!self.exec_inst.is_empty()).then(|| {
+                self.exec_inst
+                    .iter()
+                    .map(|exec_inst| exec_inst.as_str())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            }),
+            trigger_price: None,
+            extra: ExtraFields::new(),
+        }
+    }
+}
+
+/// Matches orders against the order books seen on the market websocket instead of sending them to
+/// crypto.com, producing the [`WebsocketData::UserOrder`] and [`WebsocketData::UserTrade`] data
+/// the exchange would have sent.
+///
+/// Feed it every market event with [`SimulatedExchange::process`], usually through
+/// [`crate::controller::Controller::dry_run`], and subscribe to the `book` channels of the
+/// instruments that will be traded.
+///
+/// Only `LIMIT` and `MARKET` orders are simulated, trigger orders are rejected. Fills do not
+/// remove liquidity from the local books, so a resting order can fill against the same level more
+/// than once if the book does not change.
+#[derive(Clone, Debug)]
+pub struct SimulatedExchange {
+    /// Local books by instrument name.
+    pub books: HashMap<String, OrderBook>,
+    /// Orders resting in the simulated books.
+    pub open_orders: Vec<SimulatedOrder>,
+    /// Fee rate paid by resting orders.
+    pub maker_fee_rate: f64,
+    /// Fee rate paid by orders that fill on arrival.
+    pub taker_fee_rate: f64,
+    /// Currency the fees are reported in.
+    pub fee_currency: String,
+    /// Next order ID.
+    next_order_id: u64,
+    /// Next trade ID.
+    next_trade_id: u64,
+}
+
+impl Default for SimulatedExchange {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimulatedExchange {
+    /// A simulator with no books, no orders, and no fees.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            books: HashMap::new(),
+            open_orders: vec![],
+            maker_fee_rate: 0.0,
+            taker_fee_rate: 0.0,
+            fee_currency: "USD".to_owned(),
+            next_order_id: 1,
+            next_trade_id: 1,
+        }
+    }
+
+    /// Charge `maker_fee_rate` and `taker_fee_rate` of the traded value, reported in
+    /// `fee_currency`.
+    #[must_use]
+    pub fn with_fees(
+        mut self,
+        maker_fee_rate: f64,
+        taker_fee_rate: f64,
+        fee_currency: impl Into<String>,
+    ) -> Self {
+        self.maker_fee_rate = maker_fee_rate;
+        self.taker_fee_rate = taker_fee_rate;
+        self.fee_currency = fee_currency.into();
+        self
+    }
+
+    /// Apply the book data in `data` and fill the resting orders it crosses, other data is
+    /// ignored.
+    ///
+    /// Returns the `user.trade` and `user.order` data for the fills.
+    pub fn process(&mut self, data: &WebsocketData) -> Vec<WebsocketData> {
+        let instrument_name = match *data {
+            WebsocketData::Book(ref book) => {
+                let instrument_name = book.instrument_name.to_string();
+                self.books
+                    .entry(instrument_name.clone())
+                    .or_insert_with(|| OrderBook::new(instrument_name.clone()));
+                instrument_name
+            }
+            WebsocketData::BookUpdate(ref update) => update.instrument_name.to_string(),
+            _ => return vec![],
+        };
+
+        let Some(book) = self.books.get_mut(&instrument_name) else {
+            return vec![];
+        };
+
+        if let Err(err) = book.process(data) {
+            trace::warning!("Simulated book for {instrument_name} is out of sync: {err}");
+
+            return vec![];
+        }
+
+        self.match_resting(&instrument_name)
+    }
+
+    /// Accept `order`, filling what it crosses in the local book.
+    ///
+    /// Returns the order ID along with the `user.trade` and `user.order` data for the order.
+    pub fn create_order(&mut self, order: &CreateOrder) -> (u64, Vec<WebsocketData>) {
+        let order_id = self.next_order_id;
+        self.next_order_id += 1;
+
+        let mut simulated = SimulatedOrder {
+            order_id,
+            client_oid: order
+                .client_oid
+                .clone()
+                .unwrap_or_else(|| order_id.to_string()),
+            instrument_name: order.instrument_name.clone(),
+            side: order.side.clone(),
+            order_type: order.order_type.clone(),
+            price: order.price.unwrap_or_default(),
+            quantity: order.quantity().unwrap_or_default(),
+            notional: order.notional(),
+            cumulative_quantity: 0.0,
+            cumulative_value: 0.0,
+            time_in_force: order
+                .time_in_force
+                .clone()
+                .unwrap_or_else(|| "GOOD_TILL_CANCEL".to_owned()),
+            exec_inst: order.exec_inst.clone(),
+            create_time: now(),
+        };
+
+        let events = match order.order_type.as_str() {
+            "MARKET" => self.market(&mut simulated),
+            "LIMIT" => self.limit(simulated.clone()),
+            _ => vec![self.order_event(&simulated, "REJECTED", Some("UNSUPPORTED_ORDER_TYPE"))],
+        };
+
+        (order_id, events)
+    }
+
+    /// Cancel a resting order.
+    ///
+    /// Returns the `user.order` data for the cancellation.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`OrderError::UnknownOrder`] if the order is not resting in the simulator.
+    pub fn cancel_order(&mut self, order: &CancelOrder) -> Result<Vec<WebsocketData>, OrderError> {
+        let Some(index) = self.open_orders.iter().position(|open| {
+            open.instrument_name == order.instrument_name
+                && open.order_id.to_string() == order.order_id
+        }) else {
+            return Err(OrderError::UnknownOrder(order.order_id.clone()));
+        };
+
+        let cancelled = self.open_orders.remove(index);
+
+        Ok(vec![self.order_event(&cancelled, "CANCELED", None)])
+    }
+
+    /// Cancel every resting order of `instrument_name`.
+    ///
+    /// Returns the `user.order` data for the cancellations.
+    pub fn cancel_all_orders(&mut self, instrument_name: &str) -> Vec<WebsocketData> {
+        let (cancelled, open_orders): (Vec<_>, Vec<_>) = std::mem::take(&mut self.open_orders)
+            .into_iter()
+            .partition(|open| open.instrument_name == instrument_name);
+        self.open_orders = open_orders;
+
+        cancelled
+            .iter()
+            .map(|order| self.order_event(order, "CANCELED", None))
+            .collect()
+    }
+
+    /// Fill a market order against the book, cancelling whatever is left.
+    fn market(&mut self, order: &mut SimulatedOrder) -> Vec<WebsocketData> {
+        if order.quantity <= 0.0 && !(order.is_buy() && order.notional.is_some()) {
+            return vec![self.order_event(order, "REJECTED", Some("INVALID_QUANTITY"))];
+        }
+
+        let Some(levels) = self.opposite_levels(order) else {
+            return vec![self.order_event(order, "REJECTED", Some("NO_BOOK"))];
+        };
+
+        let fills = match order.notional.filter(|_| order.quantity <= 0.0) {
+            Some(notional) => take_notional(&levels, notional),
+            None => take(&levels, order.is_buy(), None, order.quantity),
+        };
+
+        let mut events = self.fill(order, &fills, self.taker_fee_rate);
+
+        let done = match order.notional.filter(|_| order.quantity <= 0.0) {
+            Some(notional) => order.cumulative_value >= notional - 1e-9,
+            None => order.remaining() <= 0.0,
+        };
+        let status = if done { "FILLED" } else { "CANCELED" };
+        events.push(self.order_event(order, status, None));
+
+        events
+    }
+
+    /// Fill a limit order against the book and rest the remainder unless its time in force says
+    /// otherwise.
+    fn limit(&mut self, mut order: SimulatedOrder) -> Vec<WebsocketData> {
+        if order.price <= 0.0 || order.quantity <= 0.0 {
+            return vec![self.order_event(&order, "REJECTED", Some("INVALID_ORDER"))];
+        }
+
+        let fills = self
+            .opposite_levels(&order)
+            .map(|levels| take(&levels, order.is_buy(), Some(order.price), order.quantity))
+            .unwrap_or_default();
+        let fillable: f64 = fills.iter().map(|&(_, quantity)| quantity).sum();
+
+        let post_only = order.exec_inst.contains(&ExecInst::PostOnly);
+
+        if post_only && fillable > 0.0 {
+            return vec![self.order_event(&order, "REJECTED", Some("POST_ONLY_REJ"))];
+        }
+
+        if order.time_in_force == "FILL_OR_KILL" && fillable < order.quantity - 1e-9 {
+            return vec![self.order_event(&order, "CANCELED", None)];
+        }
+
+        let mut events = self.fill(&mut order, &fills, self.taker_fee_rate);
+
+        if order.remaining() <= 0.0 {
+            events.push(self.order_event(&order, "FILLED", None));
+        } else if order.time_in_force == "IMMEDIATE_OR_CANCEL" {
+            events.push(self.order_event(&order, "CANCELED", None));
+        } else {
+            events.push(self.order_event(&order, "ACTIVE", None));
+            self.open_orders.push(order);
+        }
+
+        events
+    }
+
+    /// Fill the resting orders on `instrument_name` that the book now crosses, at their own price.
+    fn match_resting(&mut self, instrument_name: &str) -> Vec<WebsocketData> {
+        let mut events = vec![];
+        let mut index = 0;
+
+        while index < self.open_orders.len() {
+            let order = &self.open_orders[index];
+
+            if order.instrument_name != instrument_name {
+                index += 1;
+                continue;
+            }
+
+            let fillable: f64 = self
+                .opposite_levels(order)
+                .map(|levels| {
+                    take(
+                        &levels,
+                        order.is_buy(),
+                        Some(order.price),
+                        order.remaining(),
+                    )
+                })
+                .unwrap_or_default()
+                .iter()
+                .map(|&(_, quantity)| quantity)
+                .sum();
+
+            if fillable <= 0.0 {
+                index += 1;
+                continue;
+            }
+
+            let mut order = self.open_orders.remove(index);
+            let fills = [(order.price, fillable)];
+            events.extend(self.fill(&mut order, &fills, self.maker_fee_rate));
+
+            if order.remaining() <= 0.0 {
+                events.push(self.order_event(&order, "FILLED", None));
+            } else {
+                events.push(self.order_event(&order, "ACTIVE", None));
+                self.open_orders.insert(index, order);
+                index += 1;
+            }
+        }
+
+        events
+    }
+
+    /// The levels `order` would trade against, `None` if there is no book for the instrument.
+    fn opposite_levels(&self, order: &SimulatedOrder) -> Option<Vec<Level>> {
+        let book = self.books.get(&order.instrument_name)?;
+
+        Some(if order.is_buy() {
+            book.asks.clone()
+        } else {
+            book.bids.clone()
+        })
+    }
+
+    /// Record `fills` on `order`, returning the `user.trade` data for them.
+    fn fill(
+        &mut self,
+        order: &mut SimulatedOrder,
+        fills: &[(f64, f64)],
+        fee_rate: f64,
+    ) -> Vec<WebsocketData> {
+        if fills.is_empty() {
+            return vec![];
+        }
+
+        let mut trades = vec![];
+
+        for &(price, quantity) in fills {
+            order.cumulative_quantity += quantity;
+            order.cumulative_value += price * quantity;
+
+            trades.push(UserTrade {
+                side: order.side.clone(),
+                fee: price * quantity * fee_rate,
+                trade_id: self.next_trade_id,
+                create_time: now(),
+                traded_price: price,
+                traded_quantity: quantity,
+                fee_currency: self.fee_currency.clone(),
+                order_id: order.order_id,
+            });
+            self.next_trade_id += 1;
+        }
+
+        vec![WebsocketData::UserTrade(UserTradeRes {
+            instrument_name: order.instrument_name.clone(),
+            subscription: format!("user.trade.{}", order.instrument_name),
+            channel: "user.trade".to_owned(),
+            data: trades,
+            kind: EventKind::Update,
+        })]
+    }
+
+    /// The `user.order` data for `order` with `status`.
+    fn order_event(
+        &self,
+        order: &SimulatedOrder,
+        status: &str,
+        reason: Option<&str>,
+    ) -> WebsocketData {
+        WebsocketData::UserOrder(UserOrderRes {
+            instrument_name: order.instrument_name.clone(),
+            subscription: format!("user.order.{}", order.instrument_name),
+            channel: "user.order".to_owned(),
+            data: vec![order.item(status, reason, &self.fee_currency)],
+            kind: EventKind::Update,
+        })
+    }
+}
+
+/// Take up to `quantity` from `levels`, best first, stopping at levels past `limit`.
+fn take(levels: &[Level], is_buy: bool, limit: Option<f64>, quantity: f64) -> Vec<(f64, f64)> {
+    let mut fills = vec![];
+    let mut remaining = quantity;
+
+    for &(price, size, _) in levels {
+        let crosses = limit.is_none_or(|limit| {
+            if is_buy {
+                price <= limit
+            } else {
+                price >= limit
+            }
+        });
+
+        if remaining <= 0.0 || !crosses {
+            break;
+        }
+
+        let quantity = size.min(remaining);
+        fills.push((price, quantity));
+        remaining -= quantity;
+    }
+
+    fills
+}
+
+/// Spend up to `notional` on `levels`, best first.
+fn take_notional(levels: &[Level], notional: f64) -> Vec<(f64, f64)> {
+    let mut fills = vec![];
+    let mut remaining = notional;
+
+    for &(price, size, _) in levels {
+        if remaining <= 0.0 || price <= 0.0 {
+            break;
+        }
+
+        let quantity = size.min(remaining / price);
+        fills.push((price, quantity));
+        remaining -= price * quantity;
+    }
+
+    fills
+}
+
+/// The current time.
+fn now() -> Time {
+    Time::from_epoch_ms(get_epoch_ms())
+}
@@ -0,0 +1,354 @@
+//! A harness for trading bots: implement [`Strategy`] and let a [`StrategyRunner`] drive it.
+//!
+//! The runner subscribes to the channels the strategy asks for, keeps an [`OrderBook`] for each
+//! `book` channel, and calls the strategy with typed events. Orders placed through the
+//! [`OrderGateway`] are sent after the callback returns with [`Controller::create_order`] and
+//! [`Controller::cancel_order`], so instrument rules and [`Controller::dry_run`] apply to them.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::Result;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{self, Instant, Interval};
+
+use crate::analytics::OrderBook;
+use crate::controller::{Controller, MarketWs, UserWs};
+use crate::utils::trace;
+use crate::websocket::actions::spot_trading_api::{CancelOrder, CreateOrder};
+use crate::websocket::actions::{BookSubscriptionType, Subscribe, SubscribeUserTrades};
+use crate::websocket::channel::Channel;
+use crate::websocket::data::{EventKind, Ticker, UserTrade};
+use crate::websocket::WebsocketData;
+
+/// A trading strategy driven by a [`StrategyRunner`].
+///
+/// Every callback is given an [`OrderGateway`] to place or cancel orders with, returning `Err`
+/// stops the runner with that error.
+pub trait Strategy: Send {
+    /// Market channels to subscribe to when the runner starts.
+    fn channels(&self) -> Vec<Channel>;
+
+    /// User channels to subscribe to when the runner starts, `user.trade` for
+    /// [`Strategy::on_fill`] by default.
+    fn user_channels(&self) -> Vec<String> {
+        vec![SubscribeUserTrades(None).channel()]
+    }
+
+    /// How often [`Strategy::on_timer`] is called, never by default.
+    fn timer_interval(&self) -> Option<Duration> {
+        None
+    }
+
+    /// A ticker from a `ticker` channel.
+    ///
+    /// # Errors
+    ///
+    /// Return `Err` to stop the runner.
+    fn on_tick(&mut self, _ticker: &Ticker, _gateway: &mut OrderGateway) -> Result<()> {
+        Ok(())
+    }
+
+    /// A book changed by a `book` snapshot or `book.update`.
+    ///
+    /// # Errors
+    ///
+    /// Return `Err` to stop the runner.
+    fn on_book(&mut self, _book: &OrderBook, _gateway: &mut OrderGateway) -> Result<()> {
+        Ok(())
+    }
+
+    /// One of the user's orders in `instrument_name` was filled, the fills that happened before
+    /// subscribing are not passed on.
+    ///
+    /// # Errors
+    ///
+    /// Return `Err` to stop the runner.
+    fn on_fill(
+        &mut self,
+        _instrument_name: &str,
+        _fill: &UserTrade,
+        _gateway: &mut OrderGateway,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called every [`Strategy::timer_interval`].
+    ///
+    /// # Errors
+    ///
+    /// Return `Err` to stop the runner.
+    fn on_timer(&mut self, _gateway: &mut OrderGateway) -> Result<()> {
+        Ok(())
+    }
+
+    /// Any data, called before the typed callback, e.g. for order acknowledgements.
+    ///
+    /// # Errors
+    ///
+    /// Return `Err` to stop the runner.
+    fn on_data(&mut self, _data: &WebsocketData, _gateway: &mut OrderGateway) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// An order request made by a [`Strategy`].
+#[derive(Clone, Debug)]
+pub enum OrderRequest {
+    /// Create the order.
+    Create(CreateOrder),
+    /// Cancel the order.
+    Cancel(CancelOrder),
+}
+
+/// Collects the orders a [`Strategy`] places during a callback, the runner sends them in order
+/// once it returns.
+#[derive(Debug, Default)]
+pub struct OrderGateway {
+    /// Requests not yet sent.
+    requests: Vec<OrderRequest>,
+    /// Set by [`OrderGateway::stop`].
+    stopped: bool,
+}
+
+impl OrderGateway {
+    /// A gateway with no requests.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Place `order`.
+    pub fn create_order(&mut self, order: CreateOrder) {
+        self.requests.push(OrderRequest::Create(order));
+    }
+
+    /// Cancel `order`.
+    pub fn cancel_order(&mut self, order: CancelOrder) {
+        self.requests.push(OrderRequest::Cancel(order));
+    }
+
+    /// Stop the runner once the requests made so far are sent.
+    pub fn stop(&mut self) {
+        self.stopped = true;
+    }
+
+    /// Whether [`OrderGateway::stop`] has been called.
+    #[must_use]
+    pub const fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// The requests not yet sent.
+    #[must_use]
+    pub fn requests(&self) -> &[OrderRequest] {
+        &self.requests
+    }
+
+    /// Take the requests not yet sent.
+    pub fn take_requests(&mut self) -> Vec<OrderRequest> {
+        std::mem::take(&mut self.requests)
+    }
+}
+
+/// Runs a [`Strategy`] against a [`Controller`].
+#[derive(Debug)]
+pub struct StrategyRunner<S> {
+    /// The strategy being run.
+    strategy: S,
+    /// Local books by instrument, for the `book` channels of the strategy.
+    books: HashMap<String, OrderBook>,
+}
+
+impl<S: Strategy> StrategyRunner<S> {
+    /// A runner for `strategy`.
+    #[must_use]
+    pub fn new(strategy: S) -> Self {
+        let books = strategy
+            .channels()
+            .into_iter()
+            .filter_map(|channel| match channel {
+                Channel::Book {
+                    instrument_name, ..
+                } => Some((instrument_name.clone(), OrderBook::new(instrument_name))),
+                _ => None,
+            })
+            .collect();
+
+        Self { strategy, books }
+    }
+
+    /// The strategy being run.
+    #[must_use]
+    pub const fn strategy(&self) -> &S {
+        &self.strategy
+    }
+
+    /// The local book of `instrument_name`, if the strategy subscribes to its `book` channel.
+    #[must_use]
+    pub fn book(&self, instrument_name: &str) -> Option<&OrderBook> {
+        self.books.get(instrument_name)
+    }
+
+    /// Stop running and return the strategy.
+    #[must_use]
+    pub fn into_inner(self) -> S {
+        self.strategy
+    }
+
+    /// Pass `data` to the strategy callbacks it is for.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a callback does.
+    pub fn dispatch(&mut self, data: &WebsocketData, gateway: &mut OrderGateway) -> Result<()> {
+        self.strategy.on_data(data, gateway)?;
+
+        match *data {
+            WebsocketData::Ticker(ref tickers) => {
+                for ticker in &tickers.data {
+                    self.strategy.on_tick(ticker, gateway)?;
+                }
+            }
+            WebsocketData::Book(ref books) => {
+                self.apply_book(&books.instrument_name, data, gateway)?;
+            }
+            WebsocketData::BookUpdate(ref updates) => {
+                self.apply_book(&updates.instrument_name, data, gateway)?;
+            }
+            WebsocketData::UserTrade(ref trades) if trades.kind != EventKind::Snapshot => {
+                for fill in &trades.data {
+                    self.strategy
+                        .on_fill(&trades.instrument_name, fill, gateway)?;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Apply `data` to the local book of `instrument_name` and pass the book to the strategy.
+    ///
+    /// A missed update leaves the book waiting for the next snapshot, the strategy is not called
+    /// until it arrives.
+    fn apply_book(
+        &mut self,
+        instrument_name: &str,
+        data: &WebsocketData,
+        gateway: &mut OrderGateway,
+    ) -> Result<()> {
+        let Some(book) = self.books.get_mut(instrument_name) else {
+            return Ok(());
+        };
+
+        if let Err(err) = book.process(data) {
+            trace::warning!("Book for {instrument_name} was not updated: {err}");
+
+            return Ok(());
+        }
+
+        self.strategy.on_book(book, gateway)
+    }
+
+    /// Subscribe to the strategy's channels and run it until it stops through
+    /// [`OrderGateway::stop`], a callback fails, or a websocket task finishes.
+    ///
+    /// `book` channels are subscribed with [`BookSubscriptionType::SnapshotAndUpdate`]. The user
+    /// channels are private, push [`crate::websocket::actions::Auth`] or call
+    /// [`Controller::authenticate`] first.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if subscribing or sending an order fails, if a callback fails, or if a
+    /// websocket task failed.
+    pub async fn run(&mut self, controller: &mut Controller<UserWs, MarketWs>) -> Result<()> {
+        let mut data_rx = controller.subscribe_data();
+        let mut exits = controller.supervisor.exits();
+
+        let channels: Vec<String> = self
+            .strategy
+            .channels()
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        if !channels.is_empty() {
+            controller
+                .push_market_action(
+                    Subscribe {
+                        channels,
+                        ..Subscribe::default()
+                    }
+                    .with_book_subscription_type(BookSubscriptionType::SnapshotAndUpdate),
+                )
+                .await?;
+        }
+
+        let user_channels = self.strategy.user_channels();
+
+        if !user_channels.is_empty() {
+            controller
+                .push_user_action(Subscribe {
+                    channels: user_channels,
+                    ..Subscribe::default()
+                })
+                .await?;
+        }
+
+        let mut timer = self
+            .strategy
+            .timer_interval()
+            .map(|period| time::interval_at(Instant::now() + period, period));
+
+        loop {
+            let mut gateway = OrderGateway::new();
+
+            tokio::select! {
+                biased;
+
+                exit = exits.wait_for(Option::is_some) => {
+                    let exit = exit.map(|exit| exit.clone());
+
+                    return match exit {
+                        Ok(Some(exit)) => exit.into_result(),
+                        _ => Ok(()),
+                    };
+                }
+                () = tick(timer.as_mut()) => self.strategy.on_timer(&mut gateway)?,
+                data = data_rx.recv() => match data {
+                    Ok(data) => {
+                        if let Some(ref data) = data.result {
+                            self.dispatch(data, &mut gateway)?;
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        trace::warning!("Strategy lagged behind, {skipped} messages were skipped.");
+                    }
+                    Err(RecvError::Closed) => return Ok(()),
+                },
+            }
+
+            for request in gateway.take_requests() {
+                match request {
+                    OrderRequest::Create(order) => controller.submit_order(order).await?,
+                    OrderRequest::Cancel(order) => controller.submit_cancel(order).await?,
+                };
+            }
+
+            if gateway.is_stopped() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Wait for the next tick of `timer`, forever if there is no timer.
+async fn tick(timer: Option<&mut Interval>) {
+    match timer {
+        Some(timer) => {
+            timer.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
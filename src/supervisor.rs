@@ -0,0 +1,154 @@
+//! Supervision of the websocket tasks spawned by the [`crate::controller::ControllerBuilder`].
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use anyhow::Result;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::utils::trace;
+
+/// A task started by the controller.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Task {
+    /// Sends market actions to the market websocket.
+    MarketActions,
+    /// Reads and processes the market websocket.
+    MarketStream,
+    /// Sends user actions to the user websocket.
+    UserActions,
+    /// Reads and processes the user websocket.
+    UserStream,
+}
+
+/// How the first supervised task to finish ended.
+#[derive(Clone, Debug)]
+pub struct TaskExit {
+    /// The task that finished.
+    pub task: Task,
+    /// The error it returned or panicked with, `None` if it completed normally.
+    pub error: Option<Arc<anyhow::Error>>,
+}
+
+impl TaskExit {
+    /// Convert into a `Result` for returning from a listener.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the task ended with an error.
+    pub fn into_result(self) -> Result<()> {
+        match self.error {
+            Some(err) => anyhow::bail!("{:?} task failed: {err:#}", self.task),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Watches the controller's tasks and records the first one to finish.
+///
+/// Any number of listeners can wait on [`Supervisor::exits`], so a failing websocket is seen by
+/// every consumer of the data rather than only the one that owned the task handles.
+///
+/// Supervising a new handle for a task replaces the old one, e.g. after a reconnect, and the old
+/// handle finishing is no longer recorded.
+#[derive(Debug)]
+pub struct Supervisor {
+    /// First task exit, `None` while everything is running.
+    exit_tx: Arc<watch::Sender<Option<TaskExit>>>,
+    /// Number of handles supervised for each task, only the latest one's exit is recorded.
+    generations: Arc<Mutex<HashMap<Task, u64>>>,
+}
+
+impl Default for Supervisor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Supervisor {
+    /// A supervisor with no tasks.
+    #[must_use]
+    pub fn new() -> Self {
+        let (exit_tx, _) = watch::channel(None);
+
+        Self {
+            exit_tx: Arc::new(exit_tx),
+            generations: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Supervise `handle`, recording its exit if it is the first task to finish and no newer
+    /// handle of `task` is supervised by then.
+    pub fn supervise(&self, task: Task, handle: JoinHandle<Result<()>>) {
+        let exit_tx = Arc::clone(&self.exit_tx);
+        let generations = Arc::clone(&self.generations);
+        let generation = {
+            let mut generations = generations.lock().unwrap_or_else(PoisonError::into_inner);
+            let generation = generations.entry(task).or_default();
+            *generation += 1;
+
+            *generation
+        };
+
+        tokio::spawn(async move {
+            let error = match handle.await {
+                Ok(Ok(())) => None,
+                Ok(Err(err)) => Some(Arc::new(err)),
+                Err(err) => Some(Arc::new(anyhow::Error::new(err))),
+            };
+
+            if let Some(ref err) = error {
+                trace::error!("{task:?} task failed: {err:#}");
+            } else {
+                trace::info!("{task:?} task completed");
+            }
+
+            let replaced = generations
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .get(&task)
+                .is_some_and(|latest| *latest != generation);
+
+            if replaced {
+                return;
+            }
+
+            exit_tx.send_if_modified(|exit| {
+                if exit.is_some() {
+                    return false;
+                }
+
+                *exit = Some(TaskExit { task, error });
+
+                true
+            });
+        });
+    }
+
+    /// A reciever that is updated when the first supervised task finishes.
+    #[must_use]
+    pub fn exits(&self) -> watch::Receiver<Option<TaskExit>> {
+        self.exit_tx.subscribe()
+    }
+
+    /// The first task to finish, if any has.
+    #[must_use]
+    pub fn exit(&self) -> Option<TaskExit> {
+        self.exit_tx.borrow().clone()
+    }
+
+    /// Wait until the first supervised task finishes.
+    pub async fn wait(&self) -> TaskExit {
+        let mut exits = self.exits();
+
+        if let Ok(exit) = exits.wait_for(Option::is_some).await {
+            if let Some(exit) = exit.clone() {
+                return exit;
+            }
+        }
+
+        // The sender lives as long as `self`, so this is not reachable.
+        std::future::pending().await
+    }
+}
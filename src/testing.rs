@@ -0,0 +1,87 @@
+//! Decoding captured websocket frames without a connection, for checking fixtures.
+//!
+//! Frames go through the same [`crate::websocket::handler`] registries as on a live websocket, so
+//! the data returned is what a [`crate::controller::Controller`] would broadcast for them.
+//!
+//! ```
+//! use crypto_com_api::testing::decode_frame;
+//! use crypto_com_api::utils::in_flight::Api;
+//! use crypto_com_api::websocket::WebsocketData;
+//!
+//! let sent = decode_frame(
+//!     Api::Market,
+//!     r#"{"id":1,"method":"public/heartbeat","code":0}"#,
+//!     0,
+//! )
+//! .unwrap();
+//!
+//! assert!(matches!(sent[0].result, Some(WebsocketData::MarketHeartbeat)));
+//! ```
+
+use tokio::sync::broadcast;
+
+use crate::api_response::ApiResponse;
+use crate::error::Result;
+use crate::prelude::DataEvent;
+use crate::utils::in_flight::Api;
+use crate::websocket::handler::{dispatch, HandlerContext, HandlerRegistry};
+use crate::websocket::market_api;
+#[cfg(feature = "websocket")]
+use crate::websocket::user_api;
+
+/// Most data one frame can be decoded into, more than any built in handler sends.
+const MAX_DATA_PER_FRAME: usize = 256;
+
+/// Decode the text frame `text` as recieved from `api` at `received_at` epoch milliseconds,
+/// returning the data broadcast for it in order.
+///
+/// Replies are decoded without the request they answer, and messages that need a reply, e.g.
+/// heartbeats, are answered into a sender that is dropped.
+///
+/// # Errors
+///
+/// Will return [`crate::error::ApiError::SerdeJSON`] if `text` is not an API response.
+///
+/// Will return [`crate::error::ApiError::UnsupportedMethod`] if no built in handler handles
+/// the method.
+///
+/// Will return the [`crate::error::ApiError`] of the handler if it fails, e.g. the result does
+/// not match the data of its channel.
+pub fn decode_frame(api: Api, text: &str, received_at: u64) -> Result<Vec<DataEvent>> {
+    let msg: ApiResponse<serde_json::Value> = serde_json::from_str(text)?;
+    let (data_tx, mut data_rx) = broadcast::channel(MAX_DATA_PER_FRAME);
+    let (tx, _rx) = futures_channel::mpsc::unbounded();
+
+    let builtin = match api {
+        Api::Market => market_api::builtin_handlers(),
+        #[cfg(feature = "websocket")]
+        Api::User => user_api::builtin_handlers(),
+        #[cfg(not(feature = "websocket"))]
+        Api::User => {
+            return Err(crate::error::ApiError::Other(anyhow::anyhow!(
+                "the user websocket needs the `websocket` feature"
+            )))
+        }
+    };
+
+    dispatch(
+        &HandlerRegistry::new(),
+        builtin,
+        &HandlerContext {
+            api,
+            msg: &msg,
+            data_tx: &data_tx,
+            tx: &tx,
+            request: None,
+            received_at,
+        },
+    )?;
+
+    let mut sent = vec![];
+
+    while let Ok(data) = data_rx.try_recv() {
+        sent.push(data);
+    }
+
+    Ok(sent)
+}
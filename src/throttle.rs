@@ -0,0 +1,439 @@
+//! Pacing order actions per instrument to stay within the exchange's rate limits.
+//!
+//! [`OrderQueue`] decides what may be sent and when, [`OrderScheduler`] sends from one in the
+//! background.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use tokio::sync::{oneshot, Notify};
+use tokio::task::JoinHandle;
+
+use crate::error::Result;
+use crate::utils::action_queue::ActionQueue;
+use crate::utils::trace;
+use crate::websocket::actions::spot_trading_api::{CancelOrder, CreateOrder};
+use crate::websocket::actions::ActionKind;
+
+/// How many order actions may be sent for one instrument within a window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ThrottleLimits {
+    /// Most actions sent per instrument within `window`.
+    pub max_actions: usize,
+    /// Length of the sliding window.
+    pub window: Duration,
+}
+
+impl Default for ThrottleLimits {
+    /// The documented limit of `private/create-order` and `private/cancel-order`, 15 requests per
+    /// 100ms.
+    fn default() -> Self {
+        Self {
+            max_actions: 15,
+            window: Duration::from_millis(100),
+        }
+    }
+}
+
+/// An order action waiting to be sent.
+#[derive(Clone, Debug)]
+pub enum QueuedAction {
+    /// `private/create-order`.
+    Create(CreateOrder),
+    /// `private/cancel-order`, sent before any queued order of its instrument.
+    Cancel(CancelOrder),
+}
+
+impl QueuedAction {
+    /// The instrument the action is for.
+    #[must_use]
+    pub fn instrument_name(&self) -> &str {
+        match *self {
+            Self::Create(ref order) => &order.instrument_name,
+            Self::Cancel(ref cancel) => &cancel.instrument_name,
+        }
+    }
+
+    /// Whether the action is a cancel.
+    #[must_use]
+    pub const fn is_cancel(&self) -> bool {
+        matches!(*self, Self::Cancel(_))
+    }
+
+    /// The action to send.
+    #[must_use]
+    pub fn into_action(self) -> ActionKind {
+        match self {
+            Self::Create(order) => order.into(),
+            Self::Cancel(cancel) => cancel.into(),
+        }
+    }
+}
+
+/// Queue depth of an [`OrderQueue`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct QueueMetrics {
+    /// Actions waiting, by instrument. Instruments with nothing waiting are left out.
+    pub depth: BTreeMap<String, usize>,
+    /// Actions waiting across every instrument.
+    pub queued: usize,
+    /// Cancels waiting across every instrument.
+    pub cancels_queued: usize,
+    /// Actions sent so far.
+    pub sent: u64,
+}
+
+/// The actions of one instrument.
+#[derive(Debug, Default)]
+struct InstrumentQueue {
+    /// Actions waiting, cancels first.
+    pending: VecDeque<QueuedAction>,
+    /// When the actions sent within the window were sent, oldest first.
+    sent: VecDeque<Instant>,
+}
+
+impl InstrumentQueue {
+    /// Forget the sends that are out of the window at `now`.
+    fn prune(&mut self, now: Instant, window: Duration) {
+        while self
+            .sent
+            .front()
+            .is_some_and(|sent| now.saturating_duration_since(*sent) >= window)
+        {
+            self.sent.pop_front();
+        }
+    }
+
+    /// Number of cancels at the front of the queue.
+    fn cancels(&self) -> usize {
+        self.pending
+            .iter()
+            .take_while(|action| action.is_cancel())
+            .count()
+    }
+}
+
+/// Order actions queued per instrument, released no faster than its [`ThrottleLimits`] allow.
+///
+/// Cancels jump ahead of the orders queued for their instrument, and are released before the
+/// orders of any instrument.
+#[derive(Debug, Default)]
+pub struct OrderQueue {
+    /// The limits applied to each instrument.
+    limits: ThrottleLimits,
+    /// [`InstrumentQueue`] by instrument.
+    queues: BTreeMap<String, InstrumentQueue>,
+    /// Actions released so far.
+    sent: u64,
+}
+
+impl OrderQueue {
+    /// An empty queue applying `limits` to each instrument.
+    #[must_use]
+    pub fn new(limits: ThrottleLimits) -> Self {
+        Self {
+            limits,
+            ..Self::default()
+        }
+    }
+
+    /// The limits applied to each instrument.
+    #[must_use]
+    pub const fn limits(&self) -> &ThrottleLimits {
+        &self.limits
+    }
+
+    /// Queue `action`, cancels go after the cancels already queued for the instrument but before
+    /// its orders.
+    pub fn push(&mut self, action: QueuedAction) {
+        let queue = self
+            .queues
+            .entry(action.instrument_name().to_owned())
+            .or_default();
+
+        if action.is_cancel() {
+            let at = queue.cancels();
+            queue.pending.insert(at, action);
+        } else {
+            queue.pending.push_back(action);
+        }
+    }
+
+    /// The next action that may be sent at `now`, recording it as sent.
+    ///
+    /// Cancels of any instrument are released before orders.
+    pub fn pop_ready(&mut self, now: Instant) -> Option<QueuedAction> {
+        let limits = self.limits;
+
+        for cancels_only in [true, false] {
+            for queue in self.queues.values_mut() {
+                queue.prune(now, limits.window);
+
+                let ready = queue.sent.len() < limits.max_actions
+                    && queue
+                        .pending
+                        .front()
+                        .is_some_and(|action| !cancels_only || action.is_cancel());
+
+                if !ready {
+                    continue;
+                }
+
+                queue.sent.push_back(now);
+                self.sent += 1;
+
+                return queue.pending.pop_front();
+            }
+        }
+
+        None
+    }
+
+    /// When the next queued action may be sent, `None` when nothing is queued.
+    #[must_use]
+    pub fn next_ready_at(&self, now: Instant) -> Option<Instant> {
+        self.queues
+            .values()
+            .filter(|queue| !queue.pending.is_empty())
+            .map(|queue| {
+                let in_window = queue
+                    .sent
+                    .iter()
+                    .filter(|sent| now.saturating_duration_since(**sent) < self.limits.window)
+                    .count();
+
+                if in_window < self.limits.max_actions {
+                    return now;
+                }
+
+                // The send that leaves the window next makes room.
+                queue.sent[queue.sent.len() - in_window] + self.limits.window
+            })
+            .min()
+    }
+
+    /// Actions waiting for `instrument_name`.
+    #[must_use]
+    pub fn depth(&self, instrument_name: &str) -> usize {
+        self.queues
+            .get(instrument_name)
+            .map_or(0, |queue| queue.pending.len())
+    }
+
+    /// Queue depth by instrument and in total.
+    #[must_use]
+    pub fn metrics(&self) -> QueueMetrics {
+        let mut metrics = QueueMetrics {
+            sent: self.sent,
+            ..QueueMetrics::default()
+        };
+
+        for (instrument_name, queue) in &self.queues {
+            if queue.pending.is_empty() {
+                continue;
+            }
+
+            metrics
+                .depth
+                .insert(instrument_name.clone(), queue.pending.len());
+            metrics.queued += queue.pending.len();
+            metrics.cancels_queued += queue.cancels();
+        }
+
+        metrics
+    }
+}
+
+/// Receives the request ID of an action queued with an [`OrderScheduler`] once it is sent, or the
+/// error sending it.
+pub type ScheduledReply = oneshot::Receiver<Result<u64>>;
+
+/// The actions of an [`OrderScheduler`] and where to send the result of each.
+#[derive(Debug, Default)]
+struct Scheduled {
+    /// The queued actions.
+    queue: OrderQueue,
+    /// Result senders by instrument and whether the action is a cancel, in the order the actions
+    /// were queued. [`OrderQueue`] releases the cancels of an instrument in order, and its orders
+    /// in order, so the front sender belongs to the next one released.
+    replies: HashMap<(String, bool), VecDeque<oneshot::Sender<Result<u64>>>>,
+}
+
+impl Scheduled {
+    /// The next action that may be sent at `now` along with its result sender.
+    fn pop_ready(&mut self, now: Instant) -> Option<(QueuedAction, oneshot::Sender<Result<u64>>)> {
+        let action = self.queue.pop_ready(now)?;
+        let key = (action.instrument_name().to_owned(), action.is_cancel());
+        let reply = self
+            .replies
+            .get_mut(&key)
+            .and_then(VecDeque::pop_front)
+            .expect("every queued action has a reply sender");
+
+        Some((action, reply))
+    }
+}
+
+/// Sends order actions from an [`OrderQueue`] through an [`ActionQueue`] as fast as its limits
+/// allow.
+///
+/// Actions are checked by the risk manager and simulated in dry run mode like any other pushed
+/// through the [`ActionQueue`], but not checked against the instrument rules of the controller.
+/// The sending task stops when the scheduler is dropped.
+///
+/// ```no_run
+/// # async fn example<M>(controller: crypto_com_api::controller::Controller<crypto_com_api::controller::UserWs, M>, order: crypto_com_api::websocket::actions::spot_trading_api::CreateOrder) -> anyhow::Result<()> {
+/// use crypto_com_api::throttle::ThrottleLimits;
+///
+/// let scheduler = controller.order_scheduler(ThrottleLimits::default());
+///
+/// let reply = scheduler.create_order(order);
+/// println!("{} actions waiting", scheduler.metrics().queued);
+/// println!("Sent as request {}", reply.await??);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct OrderScheduler {
+    /// The queued actions.
+    scheduled: Arc<Mutex<Scheduled>>,
+    /// Wakes the sending task when an action is queued.
+    queued: Arc<Notify>,
+    /// The background task sending the actions.
+    handle: JoinHandle<()>,
+}
+
+impl OrderScheduler {
+    /// Start sending the actions queued with `limits` through `actions`.
+    ///
+    /// An action that fails to send, e.g. one rejected by the risk manager, has its error sent to
+    /// its [`ScheduledReply`] and the actions after it are still sent.
+    #[must_use]
+    pub fn spawn(actions: ActionQueue, limits: ThrottleLimits) -> Self {
+        let scheduled = Arc::new(Mutex::new(Scheduled {
+            queue: OrderQueue::new(limits),
+            replies: HashMap::new(),
+        }));
+        let queued = Arc::new(Notify::new());
+
+        let handle = {
+            let scheduled = Arc::clone(&scheduled);
+            let queued = Arc::clone(&queued);
+
+            tokio::spawn(async move {
+                loop {
+                    let (ready, wake_at) = {
+                        let mut scheduled =
+                            scheduled.lock().unwrap_or_else(PoisonError::into_inner);
+                        let now = Instant::now();
+                        let mut ready = vec![];
+
+                        while let Some(action) = scheduled.pop_ready(now) {
+                            ready.push(action);
+                        }
+
+                        (ready, scheduled.queue.next_ready_at(now))
+                    };
+
+                    for (action, reply) in ready {
+                        trace::debug!(
+                            "Sending throttled {} for {}.",
+                            if action.is_cancel() {
+                                "cancel"
+                            } else {
+                                "order"
+                            },
+                            action.instrument_name()
+                        );
+
+                        let result = actions.push(action.into_action()).await;
+
+                        if let Err(ref err) = result {
+                            trace::warning!("Throttled action was not sent: {err}");
+                        }
+
+                        // The caller may not be waiting for the result.
+                        let _ = reply.send(result);
+                    }
+
+                    match wake_at {
+                        Some(wake_at) => {
+                            tokio::select! {
+                                () = tokio::time::sleep_until(wake_at.into()) => {}
+                                () = queued.notified() => {}
+                            }
+                        }
+                        None => queued.notified().await,
+                    }
+                }
+            })
+        };
+
+        Self {
+            scheduled,
+            queued,
+            handle,
+        }
+    }
+
+    /// Queue `order` to be sent.
+    ///
+    /// Returns the receiver of its request ID, it can be dropped if the ID is not needed.
+    pub fn create_order(&self, order: CreateOrder) -> ScheduledReply {
+        self.push(QueuedAction::Create(order))
+    }
+
+    /// Queue `cancel` to be sent ahead of the orders queued for its instrument.
+    ///
+    /// Returns the receiver of its request ID, it can be dropped if the ID is not needed.
+    pub fn cancel_order(&self, cancel: CancelOrder) -> ScheduledReply {
+        self.push(QueuedAction::Cancel(cancel))
+    }
+
+    /// Queue `action` to be sent.
+    ///
+    /// Returns the receiver of its request ID, it can be dropped if the ID is not needed.
+    pub fn push(&self, action: QueuedAction) -> ScheduledReply {
+        let (reply_tx, reply_rx) = oneshot::channel();
+
+        {
+            let mut scheduled = self
+                .scheduled
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+
+            scheduled
+                .replies
+                .entry((action.instrument_name().to_owned(), action.is_cancel()))
+                .or_default()
+                .push_back(reply_tx);
+            scheduled.queue.push(action);
+        }
+
+        self.queued.notify_one();
+
+        reply_rx
+    }
+
+    /// Queue depth by instrument and in total.
+    #[must_use]
+    pub fn metrics(&self) -> QueueMetrics {
+        self.scheduled
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .queue
+            .metrics()
+    }
+
+    /// Stop sending, the actions still queued are dropped and their [`ScheduledReply`] is closed.
+    pub fn stop(self) {
+        drop(self);
+    }
+}
+
+impl Drop for OrderScheduler {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
@@ -0,0 +1,49 @@
+//! An order book, as sent by `public/get-book` and the `book` channel.
+
+use serde::Serialize;
+
+#[cfg(any(feature = "rest", feature = "websocket-market"))]
+use crate::prelude::ApiError;
+use crate::types::Time;
+
+/// An order book of the REST or websocket API.
+///
+/// Level: (
+///     Price of the level,
+///     Total size of the level,
+///     Number of standing orders in the level,
+/// )
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Book {
+    /// Array of level.
+    pub bids: Vec<(f64, f64, u64)>,
+    /// Array of level.
+    pub asks: Vec<(f64, f64, u64)>,
+    /// Epoch millis of message publish.
+    pub t: Option<Time>,
+    /// Epoch millis of last book update.
+    pub tt: Option<Time>,
+    /// Update sequence, only sent by the websocket.
+    pub u: Option<u64>,
+    /// Internal use only, only sent by the websocket.
+    pub cs: Option<i64>,
+}
+
+/// Parse raw `(price, size, count)` levels.
+#[cfg(any(feature = "rest", feature = "websocket-market"))]
+pub(crate) fn parse_levels<S: AsRef<str>>(
+    levels: &[(S, S, S)],
+) -> Result<Vec<(f64, f64, u64)>, ApiError> {
+    let mut parsed = vec![];
+
+    for level in levels {
+        parsed.push((
+            level.0.as_ref().parse::<f64>()?,
+            level.1.as_ref().parse::<f64>()?,
+            level.2.as_ref().parse::<u64>()?,
+        ));
+    }
+
+    Ok(parsed)
+}
@@ -0,0 +1,25 @@
+//! A candlestick, as sent by `public/get-candlestick` and the `candlestick` channel.
+
+use serde::Serialize;
+
+use crate::types::Time;
+
+/// A candlestick of the REST or websocket API.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Candlestick {
+    /// Time of the candlestick, the end of the period from REST and the start from the websocket.
+    pub t: Time,
+    /// Update time of candlestick in ms since the UNIX epoch, only sent by the websocket.
+    pub ut: Option<u64>,
+    /// Open.
+    pub o: f64,
+    /// High.
+    pub h: f64,
+    /// Low.
+    pub l: f64,
+    /// Close.
+    pub c: f64,
+    /// Volume.
+    pub v: f64,
+}
@@ -0,0 +1,67 @@
+//! Order book depth.
+
+use std::fmt;
+
+use crate::prelude::ApiError;
+
+/// Number of levels on each side of a book, crypto.com accepts 1 to 50 and silently clamps
+/// anything else.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Depth(u8);
+
+impl Depth {
+    /// Fewest levels accepted.
+    pub const MIN: Self = Self(1);
+    /// Most levels accepted.
+    pub const MAX: Self = Self(50);
+    /// Ten levels, the shallow `book` channel depth.
+    pub const TEN: Self = Self(10);
+    /// Fifty levels, the deep `book` channel depth.
+    pub const FIFTY: Self = Self(50);
+
+    /// `levels` levels.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::InvalidDepth`] if `levels` is not within
+    /// [`Depth::MIN`]`..=`[`Depth::MAX`].
+    pub const fn new(levels: u8) -> Result<Self, ApiError> {
+        if levels < Self::MIN.0 || levels > Self::MAX.0 {
+            return Err(ApiError::InvalidDepth(levels));
+        }
+
+        Ok(Self(levels))
+    }
+
+    /// Number of levels.
+    #[must_use]
+    pub const fn get(self) -> u8 {
+        self.0
+    }
+}
+
+impl Default for Depth {
+    fn default() -> Self {
+        Self::MAX
+    }
+}
+
+impl TryFrom<u8> for Depth {
+    type Error = ApiError;
+
+    fn try_from(levels: u8) -> Result<Self, Self::Error> {
+        Self::new(levels)
+    }
+}
+
+impl From<Depth> for u8 {
+    fn from(depth: Depth) -> Self {
+        depth.0
+    }
+}
+
+impl fmt::Display for Depth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
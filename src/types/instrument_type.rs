@@ -0,0 +1,16 @@
+//! Instrument types.
+
+use serde::{Deserialize, Serialize};
+
+/// The kind of instrument.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum InstrumentType {
+    /// Spot currency pair, e.g. BTC_USD.
+    CcyPair,
+    /// Perpetual swap, e.g. BTCUSD-PERP.
+    PerpetualSwap,
+    /// Dated future, e.g. BTCUSD-210528m2.
+    Future,
+}
@@ -0,0 +1,177 @@
+//! Candlestick intervals.
+//!
+//! The websocket channel and the REST `timeframe` param name the same periods differently
+//! (`M5` vs `5m`), [`Interval`] handles both.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::prelude::ApiError;
+
+/// A candlestick period.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum Interval {
+    /// One minute.
+    OneMinute,
+    /// Five minutes.
+    FiveMinutes,
+    /// Fifteen minutes.
+    FifteenMinutes,
+    /// Thirty minutes.
+    ThirtyMinutes,
+    /// One hour.
+    OneHour,
+    /// Four hours.
+    FourHours,
+    /// Six hours.
+    SixHours,
+    /// Twelve hours.
+    TwelveHours,
+    /// One day.
+    OneDay,
+    /// Seven days.
+    SevenDays,
+    /// Fourteen days.
+    FourteenDays,
+    /// One month.
+    OneMonth,
+}
+
+impl Interval {
+    /// Every supported interval, shortest first.
+    pub const ALL: [Self; 12] = [
+        Self::OneMinute,
+        Self::FiveMinutes,
+        Self::FifteenMinutes,
+        Self::ThirtyMinutes,
+        Self::OneHour,
+        Self::FourHours,
+        Self::SixHours,
+        Self::TwelveHours,
+        Self::OneDay,
+        Self::SevenDays,
+        Self::FourteenDays,
+        Self::OneMonth,
+    ];
+
+    /// Every supported interval, shortest first, e.g. to offer them in a dashboard.
+    pub fn all() -> impl Iterator<Item = Self> {
+        Self::ALL.into_iter()
+    }
+
+    /// Whether the websocket has a `candlestick` channel of this interval, six hours is only
+    /// offered by REST.
+    #[must_use]
+    pub const fn has_channel(self) -> bool {
+        !matches!(self, Self::SixHours)
+    }
+
+    /// Parse the name used in the `candlestick` channel, e.g. `M5`, unlike [`Interval::from_str`]
+    /// the REST names are refused.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::InvalidInterval`] for any other name, or for an interval without a
+    /// channel, see [`Interval::has_channel`].
+    pub fn from_websocket_str(s: &str) -> Result<Self, ApiError> {
+        Self::all()
+            .filter(|interval| interval.has_channel())
+            .find(|interval| interval.as_websocket_str() == s)
+            .ok_or_else(|| ApiError::InvalidInterval(s.to_owned()))
+    }
+
+    /// The name used in the `candlestick.{time_frame}.{instrument_name}` channel, e.g. `M5`.
+    #[must_use]
+    pub const fn as_websocket_str(self) -> &'static str {
+        match self {
+            Self::OneMinute => "M1",
+            Self::FiveMinutes => "M5",
+            Self::FifteenMinutes => "M15",
+            Self::ThirtyMinutes => "M30",
+            Self::OneHour => "H1",
+            Self::FourHours => "H4",
+            Self::SixHours => "H6",
+            Self::TwelveHours => "H12",
+            Self::OneDay => "D1",
+            Self::SevenDays => "7D",
+            Self::FourteenDays => "14D",
+            Self::OneMonth => "1M",
+        }
+    }
+
+    /// The name used in the `public/get-candlestick` `timeframe` param, e.g. `5m`.
+    #[must_use]
+    pub const fn as_rest_str(self) -> &'static str {
+        match self {
+            Self::OneMinute => "1m",
+            Self::FiveMinutes => "5m",
+            Self::FifteenMinutes => "15m",
+            Self::ThirtyMinutes => "30m",
+            Self::OneHour => "1h",
+            Self::FourHours => "4h",
+            Self::SixHours => "6h",
+            Self::TwelveHours => "12h",
+            Self::OneDay => "1D",
+            Self::SevenDays => "7D",
+            Self::FourteenDays => "14D",
+            Self::OneMonth => "1M",
+        }
+    }
+
+    /// The length of one candle.
+    ///
+    /// [`Interval::OneMonth`] is the shortest month, 28 days, so a range never holds more candles
+    /// than its length divided by this.
+    #[must_use]
+    pub const fn as_duration(self) -> Duration {
+        const MINUTE: u64 = 60;
+        const HOUR: u64 = 60 * MINUTE;
+        const DAY: u64 = 24 * HOUR;
+
+        Duration::from_secs(match self {
+            Self::OneMinute => MINUTE,
+            Self::FiveMinutes => 5 * MINUTE,
+            Self::FifteenMinutes => 15 * MINUTE,
+            Self::ThirtyMinutes => 30 * MINUTE,
+            Self::OneHour => HOUR,
+            Self::FourHours => 4 * HOUR,
+            Self::SixHours => 6 * HOUR,
+            Self::TwelveHours => 12 * HOUR,
+            Self::OneDay => DAY,
+            Self::SevenDays => 7 * DAY,
+            Self::FourteenDays => 14 * DAY,
+            Self::OneMonth => 28 * DAY,
+        })
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_websocket_str())
+    }
+}
+
+impl FromStr for Interval {
+    type Err = ApiError;
+
+    /// Parses either the websocket or the REST name.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "M1" | "1m" => Self::OneMinute,
+            "M5" | "5m" => Self::FiveMinutes,
+            "M15" | "15m" => Self::FifteenMinutes,
+            "M30" | "30m" => Self::ThirtyMinutes,
+            "H1" | "1h" => Self::OneHour,
+            "H4" | "4h" => Self::FourHours,
+            "H6" | "6h" => Self::SixHours,
+            "H12" | "12h" => Self::TwelveHours,
+            "D1" | "1D" => Self::OneDay,
+            "7D" => Self::SevenDays,
+            "14D" => Self::FourteenDays,
+            "1M" => Self::OneMonth,
+            _ => return Err(ApiError::InvalidInterval(s.to_owned())),
+        })
+    }
+}
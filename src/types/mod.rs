@@ -0,0 +1,27 @@
+//! Types shared between the REST and websocket APIs.
+
+pub mod book;
+pub mod candlestick;
+pub mod depth;
+pub mod instrument_type;
+pub mod interval;
+pub mod order;
+pub mod page;
+pub mod reject_reason;
+pub mod status;
+pub mod ticker;
+pub mod timestamp;
+pub mod trade;
+
+pub use book::*;
+pub use candlestick::*;
+pub use depth::*;
+pub use instrument_type::*;
+pub use interval::*;
+pub use order::*;
+pub use page::*;
+pub use reject_reason::*;
+pub use status::*;
+pub use ticker::*;
+pub use timestamp::*;
+pub use trade::*;
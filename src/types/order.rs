@@ -0,0 +1,135 @@
+//! Order sides, amounts, and execution instructions.
+//!
+//! Quantities are in the base currency and notionals in the quote currency, a MARKET BUY takes
+//! either but a SELL only takes a quantity. [`Quantity`] and [`Notional`] keep the two apart in
+//! the typed [`crate::websocket::actions::spot_trading_api::CreateOrder`] constructors.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Side of an order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Side {
+    /// `BUY`.
+    Buy,
+    /// `SELL`.
+    Sell,
+}
+
+impl Side {
+    /// The name crypto.com uses for the side.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Buy => "BUY",
+            Self::Sell => "SELL",
+        }
+    }
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An amount of the base currency, e.g. BTC of BTC_USDT.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Quantity(pub f64);
+
+/// An amount of the quote currency to spend, e.g. USDT of BTC_USDT.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Notional(pub f64);
+
+/// How much an order buys or sells, a quantity or a notional but never both. Only buys spend a
+/// notional, see [`crate::websocket::actions::spot_trading_api::CreateOrder`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BuyAmount {
+    /// Buy this much of the base currency.
+    Quantity(Quantity),
+    /// Spend this much of the quote currency.
+    Notional(Notional),
+}
+
+impl BuyAmount {
+    /// The quantity, `None` for a notional.
+    #[must_use]
+    pub const fn quantity(self) -> Option<f64> {
+        match self {
+            Self::Quantity(Quantity(quantity)) => Some(quantity),
+            Self::Notional(_) => None,
+        }
+    }
+
+    /// The notional, `None` for a quantity.
+    #[must_use]
+    pub const fn notional(self) -> Option<f64> {
+        match self {
+            Self::Notional(Notional(notional)) => Some(notional),
+            Self::Quantity(_) => None,
+        }
+    }
+}
+
+impl From<Quantity> for BuyAmount {
+    fn from(value: Quantity) -> Self {
+        Self::Quantity(value)
+    }
+}
+
+impl From<Notional> for BuyAmount {
+    fn from(value: Notional) -> Self {
+        Self::Notional(value)
+    }
+}
+
+/// Version of the exchange API a request is shaped for.
+///
+/// The URLs of [`crate::utils::environment::Environment`] are v2, v1 is the unified exchange API,
+/// whose `exec_inst` is a list.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ApiVersion {
+    /// The v1 exchange API.
+    V1,
+    /// The v2 spot API.
+    #[default]
+    V2,
+}
+
+/// Execution instruction of a limit order.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ExecInst {
+    /// `POST_ONLY`, rejected if it would take liquidity.
+    PostOnly,
+    /// `SMART_POST_ONLY`, repriced to rest on the book if it would take liquidity, v1 only.
+    SmartPostOnly,
+    /// `ISOLATED_MARGIN`, placed on an isolated margin position, v1 only.
+    IsolatedMargin,
+}
+
+impl ExecInst {
+    /// The name crypto.com uses for the instruction.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::PostOnly => "POST_ONLY",
+            Self::SmartPostOnly => "SMART_POST_ONLY",
+            Self::IsolatedMargin => "ISOLATED_MARGIN",
+        }
+    }
+
+    /// Whether `version` accepts the instruction.
+    #[must_use]
+    pub const fn supported_by(self, version: ApiVersion) -> bool {
+        matches!((self, version), (Self::PostOnly, _) | (_, ApiVersion::V1))
+    }
+}
+
+impl fmt::Display for ExecInst {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,147 @@
+//! Pagination and time range params shared by the paginated requests.
+
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::ApiError;
+
+/// The page of a paginated request, flattened into its params.
+///
+/// The exchange defaults, the first page of [`PageQuery::DEFAULT_PAGE_SIZE`] items, are used for
+/// whichever is omitted.
+///
+/// ```
+/// use crypto_com_api::types::PageQuery;
+///
+/// let page = PageQuery::new(0, 100).unwrap();
+///
+/// assert_eq!(page.next().page, Some(1));
+/// assert!(PageQuery::new(0, 500).is_err());
+/// ```
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PageQuery {
+    /// Page size (Default: 20, Max: 200).
+    pub page_size: Option<u64>,
+    /// Page number (0-based).
+    pub page: Option<u64>,
+}
+
+impl PageQuery {
+    /// Page size used by the exchange when the request has none.
+    pub const DEFAULT_PAGE_SIZE: u64 = 20;
+    /// Largest page size accepted.
+    pub const MAX_PAGE_SIZE: u64 = 200;
+
+    /// Page `page` of `page_size` items.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::InvalidPageSize`] if `page_size` is not within
+    /// `1..=`[`PageQuery::MAX_PAGE_SIZE`].
+    pub fn new(page: u64, page_size: u64) -> Result<Self, ApiError> {
+        let query = Self {
+            page_size: Some(page_size),
+            page: Some(page),
+        };
+        query.validate()?;
+
+        Ok(query)
+    }
+
+    /// The page after this one, with the same page size.
+    #[must_use]
+    pub const fn next(self) -> Self {
+        let page = match self.page {
+            Some(page) => page.saturating_add(1),
+            None => 1,
+        };
+
+        Self {
+            page_size: self.page_size,
+            page: Some(page),
+        }
+    }
+
+    /// Check the page size, the fields are public so it is checked again before sending.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::InvalidPageSize`] if the page size is not within
+    /// `1..=`[`PageQuery::MAX_PAGE_SIZE`].
+    pub const fn validate(&self) -> Result<(), ApiError> {
+        match self.page_size {
+            Some(page_size) if page_size == 0 || page_size > Self::MAX_PAGE_SIZE => {
+                Err(ApiError::InvalidPageSize(page_size))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The time range of a request in ms since the UNIX epoch, flattened into its params.
+///
+/// The exchange defaults, which depend on the request, are used for whichever end is omitted.
+///
+/// ```
+/// use crypto_com_api::types::TimeRange;
+///
+/// assert_eq!(TimeRange::since(1_000).end_ts, None);
+/// assert!(TimeRange::new(2_000, 1_000).is_err());
+/// ```
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct TimeRange {
+    /// Start timestamp (milliseconds since the Unix epoch).
+    pub start_ts: Option<u64>,
+    /// End timestamp (milliseconds since the Unix epoch).
+    pub end_ts: Option<u64>,
+}
+
+impl TimeRange {
+    /// From `start_ts` up to `end_ts`.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::InvalidTimeRange`] if `start_ts` is after `end_ts`.
+    pub fn new(start_ts: u64, end_ts: u64) -> Result<Self, ApiError> {
+        let range = Self {
+            start_ts: Some(start_ts),
+            end_ts: Some(end_ts),
+        };
+        range.validate()?;
+
+        Ok(range)
+    }
+
+    /// From `start_ts` up to the exchange default, usually now.
+    #[must_use]
+    pub const fn since(start_ts: u64) -> Self {
+        Self {
+            start_ts: Some(start_ts),
+            end_ts: None,
+        }
+    }
+
+    /// From the exchange default up to `end_ts`.
+    #[must_use]
+    pub const fn until(end_ts: u64) -> Self {
+        Self {
+            start_ts: None,
+            end_ts: Some(end_ts),
+        }
+    }
+
+    /// Check that the range does not end before it starts.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::InvalidTimeRange`] if the start is after the end.
+    pub const fn validate(&self) -> Result<(), ApiError> {
+        match (self.start_ts, self.end_ts) {
+            (Some(start_ts), Some(end_ts)) if start_ts > end_ts => {
+                Err(ApiError::InvalidTimeRange { start_ts, end_ts })
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! Reasons an order is rejected, from the
+//! [reason codes](https://exchange-docs.crypto.com/spot/index.html#response-and-reason-codes).
+//!
+//! crypto.com sends the code as a string in the `reason` of a `user.order` update and as a number
+//! in the `code` of each `private/create-order-list` result, see
+//! [`crate::websocket::data::OrderItem::reject_reason`] and
+//! [`crate::websocket::data::CreateOrderListItem::reject_reason`].
+
+use std::fmt;
+
+/// Define [`RejectReason`] from `Variant = code, "NAME", "message";` rows.
+macro_rules! reject_reasons {
+    ($($variant:ident = $code:literal, $name:literal, $message:literal;)+) => {
+        /// Why the exchange rejected an order.
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum RejectReason {
+            $(
+                #[doc = concat!(stringify!($code), " - ", $message, ".")]
+                $variant,
+            )+
+            /// A code this crate does not know, as sent.
+            Unknown(String),
+        }
+
+        impl RejectReason {
+            /// The reason for `code`, [`RejectReason::Unknown`] if it is not one.
+            #[must_use]
+            pub fn from_code(code: u64) -> Self {
+                match code {
+                    $($code => Self::$variant,)+
+                    _ => Self::Unknown(code.to_string()),
+                }
+            }
+
+            /// The reason named `name`, e.g. `POST_ONLY_REJ`, `None` if it is not one.
+            fn from_name(name: &str) -> Option<Self> {
+                Some(match name {
+                    $($name => Self::$variant,)+
+                    _ => return None,
+                })
+            }
+
+            /// The numeric code, `None` for an unknown reason that is not a number.
+            #[must_use]
+            pub fn code(&self) -> Option<u64> {
+                match *self {
+                    $(Self::$variant => Some($code),)+
+                    Self::Unknown(ref raw) => raw.parse().ok(),
+                }
+            }
+
+            /// The name crypto.com gives the code, e.g. `POST_ONLY_REJ`, or the unknown reason as
+            /// sent.
+            #[must_use]
+            pub fn name(&self) -> &str {
+                match *self {
+                    $(Self::$variant => $name,)+
+                    Self::Unknown(ref raw) => raw,
+                }
+            }
+
+            /// What the code means.
+            #[must_use]
+            pub const fn message(&self) -> &'static str {
+                match *self {
+                    $(Self::$variant => $message,)+
+                    Self::Unknown(_) => "Unknown reason",
+                }
+            }
+        }
+    };
+}
+
+reject_reasons! {
+    SysError = 10001, "SYS_ERROR", "Malformed request";
+    Unauthorized = 10002, "UNAUTHORIZED", "Not authenticated, or key or signature incorrect";
+    IpIllegal = 10003, "IP_ILLEGAL", "IP address not whitelisted";
+    BadRequest = 10004, "BAD_REQUEST", "Missing required fields";
+    UserTierInvalid = 10005, "USER_TIER_INVALID", "Disallowed based on user tier";
+    TooManyRequests = 10006, "TOO_MANY_REQUESTS", "Requests have exceeded rate limits";
+    InvalidNonce = 10007, "INVALID_NONCE", "Nonce differs by more than 30 seconds from the server";
+    MethodNotFound = 10008, "METHOD_NOT_FOUND", "Invalid method specified";
+    InvalidDateRange = 10009, "INVALID_DATE_RANGE", "Invalid date range";
+    DuplicateRecord = 20001, "DUPLICATE_RECORD", "Duplicated record";
+    NegativeBalance = 20002, "NEGATIVE_BALANCE", "Insufficient balance";
+    SymbolNotFound = 30003, "SYMBOL_NOT_FOUND", "Invalid instrument_name specified";
+    SideNotSupported = 30004, "SIDE_NOT_SUPPORTED", "Invalid side specified";
+    OrderTypeNotSupported = 30005, "ORDERTYPE_NOT_SUPPORTED", "Invalid type specified";
+    MinPriceViolated = 30006, "MIN_PRICE_VIOLATED", "Price is lower than the minimum";
+    MaxPriceViolated = 30007, "MAX_PRICE_VIOLATED", "Price is higher than the maximum";
+    MinQuantityViolated = 30008, "MIN_QUANTITY_VIOLATED", "Quantity is lower than the minimum";
+    MaxQuantityViolated = 30009, "MAX_QUANTITY_VIOLATED", "Quantity is higher than the maximum";
+    MissingArgument = 30010, "MISSING_ARGUMENT", "Required argument is blank or missing";
+    InvalidPricePrecision = 30013, "INVALID_PRICE_PRECISION", "Too many decimal places for price";
+    InvalidQuantityPrecision = 30014, "INVALID_QUANTITY_PRECISION",
+        "Too many decimal places for quantity";
+    MinNotionalViolated = 30016, "MIN_NOTIONAL_VIOLATED", "Notional is lower than the minimum";
+    MaxNotionalViolated = 30017, "MAX_NOTIONAL_VIOLATED", "Notional is higher than the maximum";
+    MinAmountViolated = 30023, "MIN_AMOUNT_VIOLATED", "Amount is lower than the minimum";
+    MaxAmountViolated = 30024, "MAX_AMOUNT_VIOLATED", "Amount is higher than the maximum";
+    AmountPrecisionOverflow = 30025, "AMOUNT_PRECISION_OVERFLOW",
+        "Amount precision exceeds the maximum";
+    DuplicateClientOid = 204, "DUPLICATE_CLORDID", "Duplicate client order ID";
+    InsufficientAvailableBalance = 306, "INSUFFICIENT_AVAILABLE_BALANCE",
+        "Insufficient available balance";
+    InvalidPrice = 308, "INVALID_PRICE", "Invalid price";
+    FarAwayLimitPrice = 315, "FAR_AWAY_LIMIT_PRICE", "Limit price too far from the mark price";
+    ExceedsMaxAllowedOrders = 318, "EXCEEDS_MAX_ALLOWED_ORDERS", "Too many open orders";
+    BelowMinOrderSize = 415, "BELOW_MIN_ORDER_SIZE", "Order size is below the minimum";
+    FillOrKill = 43003, "FILL_OR_KILL", "FILL_OR_KILL order could not be filled in full";
+    ImmediateOrCancel = 43004, "IMMEDIATE_OR_CANCEL",
+        "IMMEDIATE_OR_CANCEL order could not be filled at all";
+    PostOnlyRejected = 43005, "POST_ONLY_REJ", "POST_ONLY order would have taken liquidity";
+    SelfTradePrevention = 43012, "SELF_TRADE_PREVENTION",
+        "Order would have traded against an order of the same account";
+}
+
+impl From<&str> for RejectReason {
+    /// Decode a code sent as a number, e.g. `"43005"`, or by name, e.g. `"POST_ONLY_REJ"`.
+    fn from(value: &str) -> Self {
+        if let Ok(code) = value.parse::<u64>() {
+            return Self::from_code(code);
+        }
+
+        Self::from_name(value).unwrap_or_else(|| Self::Unknown(value.to_owned()))
+    }
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self, self.code()) {
+            (&Self::Unknown(_), _) | (_, None) => write!(f, "{} ({})", self.message(), self.name()),
+            (_, Some(code)) => write!(f, "{} ({code} {})", self.message(), self.name()),
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Withdrawal and deposit statuses.
+//!
+//! crypto.com sends these as numeric codes (`"0"` to `"6"`), they are sent back in the same form
+//! when used as history filters.
+
+use std::fmt;
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+
+/// Status of a withdrawal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WithdrawalStatus {
+    /// 0 - Pending.
+    Pending,
+    /// 1 - Processing.
+    Processing,
+    /// 2 - Rejected.
+    Rejected,
+    /// 3 - Payment In-progress.
+    PaymentInProgress,
+    /// 4 - Payment Failed.
+    PaymentFailed,
+    /// 5 - Completed.
+    Completed,
+    /// 6 - Cancelled.
+    Cancelled,
+}
+
+impl WithdrawalStatus {
+    /// The status for `code`, if it is one.
+    #[must_use]
+    pub const fn from_code(code: u64) -> Option<Self> {
+        Some(match code {
+            0 => Self::Pending,
+            1 => Self::Processing,
+            2 => Self::Rejected,
+            3 => Self::PaymentInProgress,
+            4 => Self::PaymentFailed,
+            5 => Self::Completed,
+            6 => Self::Cancelled,
+            _ => return None,
+        })
+    }
+
+    /// The code crypto.com uses for the status.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match *self {
+            Self::Pending => "0",
+            Self::Processing => "1",
+            Self::Rejected => "2",
+            Self::PaymentInProgress => "3",
+            Self::PaymentFailed => "4",
+            Self::Completed => "5",
+            Self::Cancelled => "6",
+        }
+    }
+}
+
+/// Status of a deposit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DepositStatus {
+    /// 0 - Not Arrived.
+    NotArrived,
+    /// 1 - Arrived.
+    Arrived,
+    /// 2 - Failed.
+    Failed,
+    /// 3 - Pending.
+    Pending,
+}
+
+impl DepositStatus {
+    /// The status for `code`, if it is one.
+    #[must_use]
+    pub const fn from_code(code: u64) -> Option<Self> {
+        Some(match code {
+            0 => Self::NotArrived,
+            1 => Self::Arrived,
+            2 => Self::Failed,
+            3 => Self::Pending,
+            _ => return None,
+        })
+    }
+
+    /// The code crypto.com uses for the status.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match *self {
+            Self::NotArrived => "0",
+            Self::Arrived => "1",
+            Self::Failed => "2",
+            Self::Pending => "3",
+        }
+    }
+}
+
+impl fmt::Display for WithdrawalStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for DepositStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for WithdrawalStatus {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl Serialize for DepositStatus {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// The codes as sent, `"0"` to `"6"`.
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for WithdrawalStatus {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "WithdrawalStatus".into()
+    }
+
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({ "type": "string", "enum": ["0", "1", "2", "3", "4", "5", "6"] })
+    }
+}
+
+/// The codes as sent, `"0"` to `"3"`.
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for DepositStatus {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "DepositStatus".into()
+    }
+
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({ "type": "string", "enum": ["0", "1", "2", "3"] })
+    }
+}
+
+impl<'de> Deserialize<'de> for WithdrawalStatus {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = deserializer.deserialize_any(CodeVisitor)?;
+
+        Self::from_code(code).ok_or_else(|| {
+            de::Error::invalid_value(de::Unexpected::Unsigned(code), &"a withdrawal status 0-6")
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for DepositStatus {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = deserializer.deserialize_any(CodeVisitor)?;
+
+        Self::from_code(code).ok_or_else(|| {
+            de::Error::invalid_value(de::Unexpected::Unsigned(code), &"a deposit status 0-3")
+        })
+    }
+}
+
+/// Reads a status code sent as either a numeric string or a number.
+struct CodeVisitor;
+
+impl Visitor<'_> for CodeVisitor {
+    type Value = u64;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a numeric status code")
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<u64, E> {
+        Ok(value)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<u64, E> {
+        u64::try_from(value).map_err(|_err| E::invalid_value(de::Unexpected::Signed(value), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<u64, E> {
+        value
+            .parse()
+            .map_err(|_err| E::invalid_value(de::Unexpected::Str(value), &self))
+    }
+}
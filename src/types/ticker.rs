@@ -0,0 +1,39 @@
+//! A ticker, as sent by `public/get-ticker` and the `ticker` channel.
+
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::types::Time;
+
+/// A ticker of the REST or websocket API.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Ticker {
+    /// Price of the 24h highest trade.
+    pub h: Option<f64>,
+    /// Price of the 24h lowest trade, null if there weren't any trades.
+    pub l: Option<f64>,
+    /// The price of the latest trade, null if there weren't any trades.
+    pub a: Option<f64>,
+    /// Instrument name.
+    pub i: Arc<str>,
+    /// The total 24h traded volume.
+    pub v: f64,
+    /// The total 24h traded volume value (in USD).
+    pub vv: f64,
+    /// The open interest.
+    pub oi: Option<f64>,
+    /// 24-hour price change, null if there weren't any trades.
+    pub c: Option<f64>,
+    /// The current best bid price, null if there aren't any bids.
+    pub b: Option<f64>,
+    /// The current best bid size, only sent by the websocket.
+    pub bs: Option<f64>,
+    /// The current best ask price, null if there aren't any asks.
+    pub k: Option<f64>,
+    /// The current best ask size, only sent by the websocket.
+    pub ks: Option<f64>,
+    /// Trade timestamp.
+    pub t: Time,
+}
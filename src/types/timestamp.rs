@@ -0,0 +1,93 @@
+//! Exchange timestamps.
+//!
+//! crypto.com sends every time as milliseconds since the UNIX epoch. The processed structs keep
+//! them as [`Time`], which is a plain `u64` by default and a [`chrono::DateTime`] in UTC with the
+//! `datetime` feature, the `Raw*` structs always keep the number as it was sent.
+
+use serde::{Deserialize, Deserializer};
+
+/// The type of the timestamps in processed structs.
+#[cfg(not(feature = "datetime"))]
+pub type Time = u64;
+
+/// The type of the timestamps in processed structs.
+#[cfg(feature = "datetime")]
+pub type Time = chrono::DateTime<chrono::Utc>;
+
+/// Conversion between [`Time`] and milliseconds since the UNIX epoch, whichever type it is.
+pub trait EpochMs: Copy {
+    /// From milliseconds since the UNIX epoch.
+    fn from_epoch_ms(ms: u64) -> Self;
+
+    /// Milliseconds since the UNIX epoch.
+    fn epoch_ms(self) -> u64;
+}
+
+impl EpochMs for u64 {
+    fn from_epoch_ms(ms: u64) -> Self {
+        ms
+    }
+
+    fn epoch_ms(self) -> u64 {
+        self
+    }
+}
+
+#[cfg(feature = "datetime")]
+impl EpochMs for chrono::DateTime<chrono::Utc> {
+    /// Times past the range of [`chrono::DateTime`] become the UNIX epoch.
+    fn from_epoch_ms(ms: u64) -> Self {
+        i64::try_from(ms)
+            .ok()
+            .and_then(Self::from_timestamp_millis)
+            .unwrap_or_default()
+    }
+
+    /// Times before the UNIX epoch become 0.
+    fn epoch_ms(self) -> u64 {
+        u64::try_from(self.timestamp_millis()).unwrap_or_default()
+    }
+}
+
+/// A time as crypto.com sends it, or as [`Time`] serializes with the `datetime` feature.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SentTime {
+    /// Milliseconds since the UNIX epoch.
+    Millis(u64),
+    /// An RFC 3339 string.
+    #[cfg(feature = "datetime")]
+    Rfc3339(chrono::DateTime<chrono::Utc>),
+}
+
+impl From<SentTime> for Time {
+    fn from(value: SentTime) -> Self {
+        match value {
+            SentTime::Millis(ms) => Self::from_epoch_ms(ms),
+            #[cfg(feature = "datetime")]
+            SentTime::Rfc3339(time) => time,
+        }
+    }
+}
+
+/// Deserialize a [`Time`] from milliseconds since the UNIX epoch, or with the `datetime` feature
+/// also from an RFC 3339 string, so processed structs read both crypto.com responses and their own
+/// serialized form.
+///
+/// # Errors
+///
+/// Will return `Err` if the value is neither.
+pub fn deserialize_time<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Time, D::Error> {
+    SentTime::deserialize(deserializer).map(Time::from)
+}
+
+/// [`deserialize_time`] for an optional field.
+///
+/// # Errors
+///
+/// Will return `Err` if the value is neither null nor a time.
+pub fn deserialize_optional_time<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Time>, D::Error> {
+    Option::<SentTime>::deserialize(deserializer).map(|time| time.map(Time::from))
+}
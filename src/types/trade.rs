@@ -0,0 +1,27 @@
+//! A public trade, as sent by `public/get-trades` and the `trade` channel.
+
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::types::Time;
+
+/// A public trade of the REST or websocket API.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Trade {
+    /// Side ("BUY" or "SELL").
+    pub s: String,
+    /// Trade price.
+    pub p: f64,
+    /// Trade quantity.
+    pub q: f64,
+    /// Trade timestamp.
+    pub t: Time,
+    /// Trade ID.
+    pub d: String,
+    /// Instrument name e.g. BTC_CRO.
+    pub i: Arc<str>,
+    /// Reserved, only sent by REST. Can be ignored.
+    pub data_time: Option<u64>,
+}
@@ -1,10 +1,48 @@
 //! Action crate.
 
+use core::any::Any;
 use core::fmt::Debug;
+use std::sync::Arc;
 
-use anyhow::Result;
 use futures_channel::mpsc::UnboundedSender;
-use tokio_tungstenite::tungstenite::Message;
+use tungstenite::Message;
+
+use crate::error::Result;
+use crate::websocket::actions::ActionKind;
+
+/// Whether an action can be sent before authorizing, see [`Action::access`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Access {
+    /// Can be sent on either websocket without authorizing.
+    Public,
+    /// Only accepted on an authorized user websocket.
+    Private,
+}
+
+impl Access {
+    /// The access of `method`, private for the `private/` methods.
+    #[must_use]
+    pub fn of_method(method: &str) -> Self {
+        if method.starts_with("private/") {
+            Self::Private
+        } else {
+            Self::Public
+        }
+    }
+}
+
+/// Methods that change orders, funds, or account settings, refused in read-only mode, see
+/// [`Action::mutates`].
+pub const MUTATING_METHODS: [&str; 8] = [
+    "private/create-order",
+    "private/cancel-order",
+    "private/create-order-list",
+    "private/cancel-order-list",
+    "private/cancel-all-orders",
+    "private/create-withdrawal",
+    "private/create-deposit-address",
+    "private/set-cancel-on-disconnect",
+];
 
 /// A trait to generically handle process "Actions".
 pub trait Action: Send + Debug + Sync {
@@ -15,6 +53,48 @@ pub trait Action: Send + Debug + Sync {
     /// Will return `Err` if `ApiRequestBuilder` does not contain method or if
     /// `get_order_history_message` fails to serialize into a string.
     fn process(&self, tx: &UnboundedSender<Message>, id: u64) -> Result<()>;
+
+    /// The method the action sends, used to report requests that time out.
+    fn method(&self) -> &'static str {
+        "unknown"
+    }
+
+    /// Whether the action needs an authorized user websocket.
+    ///
+    /// Private actions are refused on the market websocket with
+    /// [`crate::error::ApiError::PrivateOnMarket`] and held back on the user websocket until it is
+    /// authorized.
+    fn access(&self) -> Access {
+        Access::of_method(self.method())
+    }
+
+    /// Whether the action changes orders, funds, or account settings, one of
+    /// [`MUTATING_METHODS`] by default.
+    ///
+    /// Mutating actions are refused with [`crate::error::ApiError::ReadOnly`] while the controller
+    /// is read-only, see [`crate::controller::Controller::set_read_only`]. Custom actions that
+    /// change anything should return `true`.
+    fn mutates(&self) -> bool {
+        MUTATING_METHODS.contains(&self.method())
+    }
+
+    /// Check the parameters before the action is queued, e.g. the page size of a paginated
+    /// request.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the action would be rejected by crypto.com, nothing is sent.
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// The action as [`Any`], so its parameters can be read back when the response arrives or
+    /// checked before it is sent.
+    ///
+    /// `None` for actions whose parameters are not needed.
+    fn as_any(&self) -> Option<&dyn Any> {
+        None
+    }
 }
 
 /// Used to store an action with an id.
@@ -22,6 +102,7 @@ pub trait Action: Send + Debug + Sync {
 pub struct ActionStore {
     /// The ID to send to the server.
     pub id: u64,
-    /// The action to be sent.
-    pub action: Box<dyn Action>,
+    /// The action to be sent, shared with [`crate::utils::in_flight::InFlight`] so it can be
+    /// replayed after a reconnect.
+    pub action: Arc<ActionKind>,
 }
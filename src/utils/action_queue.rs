@@ -0,0 +1,386 @@
+//! A cloneable handle for sending actions to one websocket.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+#[cfg(feature = "websocket")]
+use std::sync::{PoisonError, RwLock};
+
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::api_response::ApiResponse;
+use crate::error::Result;
+use crate::prelude::{ActionStoreSender, ApiError, DataEvent, DataReciever, DataSender};
+#[cfg(feature = "websocket")]
+use crate::prelude::{SharedExchange, SharedRiskManager};
+use crate::utils::action::{Access, Action, ActionStore};
+#[cfg(feature = "websocket")]
+use crate::utils::environment::{self, Environment};
+use crate::utils::in_flight::{Api, InFlight};
+use crate::utils::trace;
+use crate::websocket::actions::{ActionKind, Subscribe};
+use crate::websocket::auth_state::{track_auth, AuthGate, AuthStatus};
+#[cfg(feature = "websocket")]
+use crate::websocket::data;
+use crate::websocket::subscribe_batch::collect_batch;
+use crate::websocket::{send_data, WebsocketData};
+
+/// Methods refused in dry run mode, orders and cancels are answered by the simulator instead, see
+/// [`crate::controller::Controller::dry_run`].
+#[cfg(feature = "websocket")]
+const DRY_RUN_REFUSED: [&str; 3] = [
+    "private/create-order-list",
+    "private/cancel-order-list",
+    "private/create-withdrawal",
+];
+
+/// Sends actions to one websocket with IDs shared with the [`crate::controller::Controller`].
+///
+/// Handed out by [`crate::controller::Controller::user_queue`] and
+/// [`crate::controller::Controller::market_queue`] so background tasks can send actions without
+/// borrowing the controller.
+#[derive(Clone, Debug)]
+pub struct ActionQueue {
+    /// The websocket the actions are sent on.
+    pub(crate) api: Api,
+    /// Actions sender of the websocket.
+    pub(crate) actions_tx: ActionStoreSender,
+    /// Next request ID.
+    pub(crate) current_id: Arc<AtomicU64>,
+    /// Set once the controller starts shutting down.
+    pub(crate) shutting_down: Arc<AtomicBool>,
+    /// Set while the controller is read-only, mutating actions are refused.
+    pub(crate) read_only: Arc<AtomicBool>,
+    /// Requests that have been sent and not yet answered.
+    pub(crate) in_flight: Arc<InFlight>,
+    /// Data broadcast sender, used to report dropped requests.
+    pub(crate) data_tx: DataSender,
+    /// Authorization of the user websocket, `None` for the market websocket.
+    pub(crate) auth: Option<Arc<AuthGate>>,
+    /// The environment of the controller, withdrawals are refused where it does not allow them.
+    #[cfg(feature = "websocket")]
+    pub(crate) environment: Option<Environment>,
+    /// Orders and cancels are answered here instead of being sent while set, see
+    /// [`crate::controller::Controller::dry_run`].
+    #[cfg(feature = "websocket")]
+    pub(crate) simulator: Arc<RwLock<Option<SharedExchange>>>,
+    /// Checks every order before it is sent while set, see
+    /// [`crate::controller::Controller::set_risk_manager`].
+    #[cfg(feature = "websocket")]
+    pub(crate) risk_manager: Arc<RwLock<Option<SharedRiskManager>>>,
+}
+
+impl ActionQueue {
+    /// Queue `action` as unanswered and send it with the next ID.
+    ///
+    /// On the user websocket a private action waits for a pending `public/auth` to be answered
+    /// first, see [`AuthGate`].
+    ///
+    /// Returns the ID the action was sent with.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `unbounded_send` fails or [`ApiError::ShuttingDown`] if
+    /// [`crate::controller::Controller::shutdown`] has been called,
+    /// [`ApiError::PrivateOnMarket`] for a private action on the market websocket, or
+    /// [`ApiError::NotAuthenticated`] for a private action on a user websocket that is not
+    /// authorized, [`ApiError::ReadOnly`] for a mutating action while the controller is
+    /// read-only, [`ApiError::WithdrawalRefused`] for a withdrawal in an environment that does
+    /// not allow them, [`ApiError::RiskViolation`] for orders rejected by the risk manager,
+    /// [`ApiError::DryRun`] for an order list or a withdrawal in dry run mode, or [`ApiError::InvalidOrder`] for a simulated cancel of an
+    /// order that is not open.
+    pub async fn push(&self, action: impl Into<ActionKind>) -> Result<u64> {
+        self.push_shared(Arc::new(action.into())).await
+    }
+
+    /// [`ActionQueue::push`] for an action that is already shared, e.g. one replayed from
+    /// [`InFlight`].
+    pub(crate) async fn push_shared(&self, action: Arc<ActionKind>) -> Result<u64> {
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return Err(ApiError::ShuttingDown);
+        }
+
+        if action.mutates() && self.read_only.load(Ordering::SeqCst) {
+            return Err(ApiError::ReadOnly(action.method().to_owned()));
+        }
+
+        #[cfg(feature = "websocket")]
+        if let ActionKind::CreateWithdrawal(_) = *action {
+            environment::check_withdrawal(self.environment)?;
+        }
+
+        action.validate()?;
+
+        let private = action.access() == Access::Private;
+
+        if private && self.api == Api::Market {
+            return Err(ApiError::PrivateOnMarket(action.method().to_owned()));
+        }
+
+        #[cfg(feature = "websocket")]
+        {
+            self.check_risk(&action).await?;
+
+            if let Some(id) = self.simulate(&action).await? {
+                return Ok(id);
+            }
+        }
+
+        let Some(ref auth) = self.auth else {
+            return self.send(action).await;
+        };
+
+        if action.method() == "public/auth" {
+            // Subscribed before sending so the reply is not missed.
+            let data_rx = self.data_tx.subscribe();
+            auth.set(AuthStatus::Pending);
+
+            let id = match self.send(action).await {
+                Ok(id) => id,
+                Err(err) => {
+                    auth.set(AuthStatus::Unauthenticated);
+
+                    return Err(err);
+                }
+            };
+            tokio::spawn(track_auth(data_rx, id, Arc::clone(auth)));
+
+            return Ok(id);
+        }
+
+        if private {
+            auth.wait(action.method()).await?;
+        }
+
+        self.send(action).await
+    }
+
+    /// Queue `action` as unanswered and send it with the next ID, without checking its access.
+    async fn send(&self, action: Arc<ActionKind>) -> Result<u64> {
+        let id = self.current_id.fetch_add(1, Ordering::SeqCst);
+
+        if let Some(dropped) = self
+            .in_flight
+            .insert(id, self.api, Arc::clone(&action))
+            .await
+        {
+            trace::warning!("Send queue is full, request {dropped} was dropped.");
+
+            send_data(
+                &self.data_tx,
+                ApiResponse::<WebsocketData>::default()
+                    .websocket_data(WebsocketData::RequestDropped(dropped)),
+            );
+        }
+
+        self.actions_tx
+            .lock()
+            .await
+            .unbounded_send(ActionStore { id, action })?;
+
+        Ok(id)
+    }
+
+    /// Fail with [`ApiError::RiskViolation`] if `action` creates orders that break the limits of
+    /// the risk manager.
+    #[cfg(feature = "websocket")]
+    async fn check_risk(&self, action: &ActionKind) -> Result<()> {
+        let risk_manager = self
+            .risk_manager
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        let Some(risk_manager) = risk_manager else {
+            return Ok(());
+        };
+
+        if !matches!(
+            *action,
+            ActionKind::CreateOrder(_) | ActionKind::CreateOrderList(_)
+        ) {
+            return Ok(());
+        }
+
+        #[cfg(feature = "rest")]
+        {
+            let cache = risk_manager.lock().await.instrument_cache().cloned();
+
+            if let Some(cache) = cache {
+                // Refreshes the instruments the risk manager checks against once they are stale.
+                cache.rules().await?;
+            }
+        }
+
+        let risk_manager = risk_manager.lock().await;
+
+        match *action {
+            ActionKind::CreateOrder(ref order) => risk_manager.check_order(order)?,
+            ActionKind::CreateOrderList(ref list) => risk_manager.check_order_list(list)?,
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Answer `action` from the simulator in dry run mode instead of sending it.
+    ///
+    /// Returns the ID of the simulated request, `None` if the simulator is not set or `action` is
+    /// not an order or a cancel.
+    #[cfg(feature = "websocket")]
+    async fn simulate(&self, action: &ActionKind) -> Result<Option<u64>> {
+        let simulator = self
+            .simulator
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        let Some(simulator) = simulator else {
+            return Ok(None);
+        };
+
+        if DRY_RUN_REFUSED.contains(&action.method()) {
+            return Err(ApiError::DryRun(action.method().to_owned()));
+        }
+
+        let (id, result, events) = match *action {
+            ActionKind::CreateOrder(ref order) => {
+                let id = self.current_id.fetch_add(1, Ordering::SeqCst);
+                let (order_id, events) = simulator.lock().await.create_order(order);
+                let result = WebsocketData::CreateOrder(data::CreateOrder {
+                    order_id,
+                    client_oid: order.client_oid.clone(),
+                });
+
+                (id, result, events)
+            }
+            ActionKind::CancelOrder(ref order) => {
+                let events = simulator.lock().await.cancel_order(order)?;
+                let id = self.current_id.fetch_add(1, Ordering::SeqCst);
+                let result =
+                    WebsocketData::CancelOrder(data::CancelOrder::new(id, 0, None, Some(order)));
+
+                (id, result, events)
+            }
+            ActionKind::CancelAllOrders(ref order) => {
+                let events = simulator
+                    .lock()
+                    .await
+                    .cancel_all_orders(&order.instrument_name);
+                let id = self.current_id.fetch_add(1, Ordering::SeqCst);
+
+                (id, WebsocketData::CancelAllOrders, events)
+            }
+            _ => return Ok(None),
+        };
+
+        send_data(
+            &self.data_tx,
+            ApiResponse::<WebsocketData> {
+                id: i64::try_from(id).unwrap_or(i64::MAX),
+                method: Some(action.method().to_owned()),
+                result: Some(result),
+                code: Some(0),
+                ..ApiResponse::default()
+            },
+        );
+
+        for event in events {
+            send_data(
+                &self.data_tx,
+                ApiResponse::<WebsocketData>::default().websocket_data(event),
+            );
+        }
+
+        Ok(Some(id))
+    }
+
+    /// Send `action` like [`ActionQueue::push`] and wait for the reply to it.
+    ///
+    /// Waits until the reply arrives unless
+    /// [`crate::controller::ControllerBuilder::with_request_timeout`] is set. A request replayed
+    /// after a reconnect is sent with a new ID, its reply is not seen.
+    ///
+    /// # Errors
+    ///
+    /// Will return the same errors as [`ActionQueue::push`], [`ApiError::RequestFailed`] if the
+    /// reply has a non-zero code, or [`ApiError::Unanswered`] if the request timed out, was
+    /// dropped, or the data broadcast closed.
+    pub async fn request(&self, action: impl Into<ActionKind>) -> Result<DataEvent> {
+        // Subscribed before sending so the reply is not missed.
+        let mut data_rx = self.data_tx.subscribe();
+        let id = self.push(action).await?;
+
+        wait_for_reply(&mut data_rx, id).await
+    }
+
+    /// Send `subscribe` as requests of at most `chunk_size` channels, see
+    /// [`crate::websocket::actions::SUBSCRIBE_CHUNK_SIZE`], sent one after another.
+    ///
+    /// Once every request is answered, timed out, or dropped a single
+    /// [`WebsocketData::SubscribeBatch`] is sent with the first ID, listing the requests that
+    /// failed.
+    ///
+    /// Returns the IDs of the requests in the order they were sent.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::InvalidInterval`] before anything is sent if a candlestick channel
+    /// has an unknown interval, otherwise the same errors as [`ActionQueue::push`], requests sent
+    /// before the error are not reported in a batch.
+    pub async fn subscribe_many(
+        &self,
+        subscribe: &Subscribe,
+        chunk_size: usize,
+    ) -> Result<Vec<u64>> {
+        subscribe.validate()?;
+
+        // Subscribed before sending so no reply is missed.
+        let data_rx = self.data_tx.subscribe();
+        let mut ids = vec![];
+
+        for chunk in subscribe.chunks(chunk_size) {
+            ids.push(self.push(chunk).await?);
+        }
+
+        tokio::spawn(collect_batch(data_rx, self.data_tx.clone(), ids.clone()));
+
+        Ok(ids)
+    }
+}
+
+/// Wait on `data_rx` for the reply to request `id`, see [`ActionQueue::request`].
+///
+/// # Errors
+///
+/// Will return [`ApiError::RequestFailed`] if the reply has a non-zero code, or
+/// [`ApiError::Unanswered`] if the request timed out, was dropped, was not replayed after a
+/// reconnect, or the data broadcast closed.
+pub(crate) async fn wait_for_reply(data_rx: &mut DataReciever, id: u64) -> Result<DataEvent> {
+    loop {
+        let data = match data_rx.recv().await {
+            Ok(data) => data,
+            Err(RecvError::Lagged(skipped)) => {
+                trace::warning!("Request {id} lagged behind, {skipped} messages were skipped.");
+                continue;
+            }
+            Err(RecvError::Closed) => return Err(ApiError::Unanswered(id)),
+        };
+
+        match data.result {
+            Some(
+                WebsocketData::RequestTimeout { id: unanswered, .. }
+                | WebsocketData::RequestDropped(unanswered)
+                | WebsocketData::RequestOutcomeUnknown { id: unanswered, .. },
+            ) if unanswered == id => return Err(ApiError::Unanswered(id)),
+            _ if u64::try_from(data.id) == Ok(id) => {
+                if let Some(code) = data.code.filter(|code| *code != 0) {
+                    return Err(ApiError::RequestFailed {
+                        code,
+                        message: data.message.clone(),
+                    });
+                }
+
+                return Ok(data);
+            }
+            _ => {}
+        }
+    }
+}
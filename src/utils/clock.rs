@@ -0,0 +1,206 @@
+//! Correction of the local clock against the exchange's for request nonces.
+//!
+//! crypto.com rejects signed requests whose nonce is too far from its own clock. The offset set
+//! with [`set_clock_offset`] is added to every nonce, [`SkewEstimator`] estimates it from the
+//! timestamps the exchange sends.
+//!
+//! [`EventTime`] puts the exchange and local times of a market event on the exchange's clock, see
+//! [`crate::websocket::WebsocketData::event_times`].
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::Duration;
+
+#[cfg(feature = "websocket-market")]
+use crate::types::EpochMs;
+use crate::utils::get_epoch_ms;
+#[cfg(feature = "websocket-market")]
+use crate::websocket::WebsocketData;
+
+/// Milliseconds added to the local clock for nonces, shared by every request.
+static CLOCK_OFFSET_MS: AtomicI64 = AtomicI64::new(0);
+
+/// Number of samples kept by [`SkewEstimator::new`].
+pub const DEFAULT_SAMPLES: usize = 64;
+
+/// Milliseconds the exchange's clock is ahead of the local one, as used for nonces.
+#[must_use]
+pub fn clock_offset() -> i64 {
+    CLOCK_OFFSET_MS.load(Ordering::Relaxed)
+}
+
+/// Add `offset_ms` to the local clock for every nonce from now on.
+pub fn set_clock_offset(offset_ms: i64) {
+    CLOCK_OFFSET_MS.store(offset_ms, Ordering::Relaxed);
+}
+
+/// The local time in ms since the UNIX epoch corrected by [`clock_offset`], used as the nonce.
+#[must_use]
+pub fn nonce_ms() -> u64 {
+    get_epoch_ms().saturating_add_signed(clock_offset())
+}
+
+/// When a market event happened on the exchange and when it was recieved.
+///
+/// The exchange times are on the exchange's clock and `received_at` on the local one,
+/// [`EventTime::latency`] and [`EventTime::age`] correct the local times by `skew_ms` before
+/// comparing them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EventTime {
+    /// Epoch millis of message publish, `t`. Candlesticks carry no publish time, this is their
+    /// update time `ut`, or their start time when there is none.
+    pub published: u64,
+    /// Epoch millis of the last book update, `tt`, books and book updates only.
+    pub updated: Option<u64>,
+    /// Local epoch millis when the message was recieved.
+    pub received_at: u64,
+    /// Milliseconds the exchange's clock was ahead of the local one when the event was read.
+    pub skew_ms: i64,
+}
+
+impl EventTime {
+    /// An event published at `published` and recieved at `received_at`, with the current
+    /// [`clock_offset`] as the skew.
+    #[must_use]
+    pub fn new(published: u64, updated: Option<u64>, received_at: u64) -> Self {
+        Self {
+            published,
+            updated,
+            received_at,
+            skew_ms: clock_offset(),
+        }
+    }
+
+    /// The same event with `skew_ms` as the skew, e.g. a [`SkewEstimator::estimate`] that is not
+    /// applied to nonces.
+    #[must_use]
+    pub const fn with_skew(mut self, skew_ms: i64) -> Self {
+        self.skew_ms = skew_ms;
+        self
+    }
+
+    /// When the message was recieved, on the exchange's clock.
+    #[must_use]
+    pub const fn received_exchange_ms(&self) -> u64 {
+        self.received_at.saturating_add_signed(self.skew_ms)
+    }
+
+    /// Time from publish to receipt, zero if the skew puts the receipt first.
+    #[must_use]
+    pub const fn latency(&self) -> Duration {
+        Duration::from_millis(self.received_exchange_ms().saturating_sub(self.published))
+    }
+
+    /// Time since the event was published.
+    #[must_use]
+    pub fn age(&self) -> Duration {
+        self.age_at(get_epoch_ms())
+    }
+
+    /// Time from publish to the local epoch millis `now_ms`, zero if the skew puts `now_ms`
+    /// first.
+    #[must_use]
+    pub const fn age_at(&self, now_ms: u64) -> Duration {
+        Duration::from_millis(
+            now_ms
+                .saturating_add_signed(self.skew_ms)
+                .saturating_sub(self.published),
+        )
+    }
+}
+
+/// Estimates how far the exchange's clock is ahead of the local one from the timestamps of the
+/// messages it sends.
+///
+/// Each message was published before it was recieved, so every sample underestimates the offset
+/// by the time the message spent in flight. The largest sample in the window, the message that
+/// arrived fastest, is the estimate.
+#[derive(Clone, Debug)]
+pub struct SkewEstimator {
+    /// Most recent offsets, exchange time minus local time.
+    samples: VecDeque<i64>,
+    /// Number of samples kept.
+    capacity: usize,
+}
+
+impl Default for SkewEstimator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SkewEstimator {
+    /// An estimator keeping the last [`DEFAULT_SAMPLES`] samples.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_SAMPLES)
+    }
+
+    /// An estimator keeping the last `capacity` samples, at least one.
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Record a message published by the exchange at `exchange_ms` and recieved at `local_ms`.
+    pub fn observe(&mut self, exchange_ms: u64, local_ms: u64) {
+        let offset =
+            i64::try_from(i128::from(exchange_ms) - i128::from(local_ms)).unwrap_or_default();
+
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+
+        self.samples.push_back(offset);
+    }
+
+    /// Record the publish times of the market data in `data`, other data is ignored.
+    #[cfg(feature = "websocket-market")]
+    pub fn process(&mut self, data: &WebsocketData) {
+        match *data {
+            WebsocketData::Book(ref books) => {
+                for book in &books.data {
+                    if let Some(t) = book.t {
+                        self.observe(t.epoch_ms(), books.received_at);
+                    }
+                }
+            }
+            WebsocketData::BookUpdate(ref updates) => {
+                for update in &updates.data {
+                    self.observe(update.t.epoch_ms(), updates.received_at);
+                }
+            }
+            WebsocketData::Ticker(ref tickers) => {
+                for ticker in &tickers.data {
+                    self.observe(ticker.t.epoch_ms(), tickers.received_at);
+                }
+            }
+            WebsocketData::Trade(ref trades) => {
+                for trade in &trades.data {
+                    self.observe(trade.t.epoch_ms(), trades.received_at);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The estimated offset in ms, `None` before any samples.
+    #[must_use]
+    pub fn estimate(&self) -> Option<i64> {
+        self.samples.iter().copied().max()
+    }
+
+    /// Set the estimate as the [`clock_offset`] and return it, the offset is unchanged before any
+    /// samples.
+    pub fn apply(&self) -> Option<i64> {
+        let estimate = self.estimate()?;
+        set_clock_offset(estimate);
+
+        Some(estimate)
+    }
+}
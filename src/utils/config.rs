@@ -1,7 +1,20 @@
 //! Crate config module.
 
+use std::collections::HashMap;
+
+use anyhow::Result;
+use tungstenite::http::header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT};
+
+use crate::prelude::ApiError;
+use crate::utils::environment::{self, Environment};
+use crate::utils::request_log::RequestLog;
+#[cfg(feature = "websocket-market")]
+use crate::websocket::auth_state::CredentialRefresh;
+#[cfg(feature = "websocket-market")]
+use crate::websocket::handler::HandlerRegistry;
+
 /// The config of the API, this is passed often through the system.
-#[derive(Default, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct Config {
     /// User API key.
     pub api_key: Option<String>,
@@ -11,6 +24,179 @@ pub struct Config {
     pub websocket_user_api: Option<url::Url>,
     /// Websocket market api URL.
     pub websocket_market_api: Option<url::Url>,
-    /// REST URL.
+    /// REST URL ending in `/`, e.g. `https://api.crypto.com/v2/`, the method is appended to it.
     pub rest_url: Option<url::Url>,
+    /// The environment the URLs were set from, see [`Config::with_environment`].
+    pub environment: Option<Environment>,
+    /// Extra named credentials, e.g. one per sub-account, see [`Config::with_credentials`].
+    pub keyring: HashMap<String, Credentials>,
+    /// What the websocket streams do with a message that fails to process.
+    pub error_policy: ErrorPolicy,
+    /// Extra headers sent with every REST request and websocket handshake, see
+    /// [`Config::with_header`].
+    pub headers: Vec<(String, String)>,
+    /// User agent sent with every REST request and websocket handshake instead of the default.
+    pub user_agent: Option<String>,
+    /// Logs the frames of the websockets while set, see [`Config::with_request_log`].
+    pub request_log: Option<RequestLog>,
+    /// Handlers for market websocket methods, used before the built in ones.
+    #[cfg(feature = "websocket-market")]
+    pub market_handlers: HandlerRegistry,
+    /// Handlers for user websocket methods, used before the built in ones.
+    #[cfg(feature = "websocket-market")]
+    pub user_handlers: HandlerRegistry,
+    /// Fetches new credentials when the user websocket's `public/auth` is refused.
+    #[cfg(feature = "websocket-market")]
+    pub credential_refresh: Option<CredentialRefresh>,
+}
+
+/// What a websocket stream does when a message fails to parse or is not supported.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Stop the stream with the error.
+    #[default]
+    FailFast,
+    /// Log the error and carry on with the next message.
+    SkipAndLog,
+    /// Send the error as `WebsocketData::ProcessingError` and carry on with the next message.
+    Forward,
+}
+
+/// An API key and its secret.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Credentials {
+    /// API key.
+    pub api_key: String,
+    /// Secret key.
+    pub secret_key: String,
+}
+
+impl Config {
+    /// A config with the REST and websocket URLs of `environment`.
+    #[must_use]
+    pub fn for_environment(environment: Environment) -> Self {
+        Self::default().with_environment(environment)
+    }
+
+    /// Use the REST and websocket URLs of `environment`, replacing any already set.
+    #[must_use]
+    pub fn with_environment(mut self, environment: Environment) -> Self {
+        self.rest_url = Some(environment.rest_url());
+        self.websocket_user_api = Some(environment.websocket_user_url());
+        self.websocket_market_api = Some(environment.websocket_market_url());
+        self.environment = Some(environment);
+
+        self
+    }
+
+    /// Refuse withdrawals when [`Config::environment`] does not allow them, see
+    /// [`Environment::allows_withdrawals`].
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::WithdrawalRefused`] if the environment does not allow withdrawals.
+    pub fn check_withdrawal(&self) -> Result<(), ApiError> {
+        environment::check_withdrawal(self.environment)
+    }
+
+    /// Add `api_key` and `secret_key` to the keyring as `name`, replacing any credentials already
+    /// stored under that name.
+    #[must_use]
+    pub fn with_credentials(
+        mut self,
+        name: impl Into<String>,
+        api_key: impl Into<String>,
+        secret_key: impl Into<String>,
+    ) -> Self {
+        self.keyring.insert(
+            name.into(),
+            Credentials {
+                api_key: api_key.into(),
+                secret_key: secret_key.into(),
+            },
+        );
+
+        self
+    }
+
+    /// Send the header `name: value` with every REST request and websocket handshake, e.g.
+    /// `X-Forwarded-For` for a gateway.
+    ///
+    /// Headers with the same name are all sent.
+    #[must_use]
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+
+        self
+    }
+
+    /// Send `user_agent` as the `User-Agent` of every REST request and websocket handshake.
+    #[must_use]
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+
+        self
+    }
+
+    /// Log every frame sent to and recieved from the websockets with `log`, with the API key,
+    /// signature, and addresses redacted.
+    ///
+    /// Only websockets connected after this is set log their frames.
+    #[must_use]
+    pub fn with_request_log(mut self, log: RequestLog) -> Self {
+        self.request_log = Some(log);
+
+        self
+    }
+
+    /// [`Config::headers`] and [`Config::user_agent`] as a header map.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::InvalidHeader`] if a header name or value is not valid.
+    pub fn header_map(&self) -> Result<HeaderMap, ApiError> {
+        let mut headers = HeaderMap::new();
+
+        for (name, value) in &self.headers {
+            let name = HeaderName::try_from(name.as_str())
+                .map_err(|_| ApiError::InvalidHeader(name.clone()))?;
+            let value = HeaderValue::try_from(value.as_str())
+                .map_err(|_| ApiError::InvalidHeader(name.to_string()))?;
+
+            headers.append(name, value);
+        }
+
+        if let Some(ref user_agent) = self.user_agent {
+            let value = HeaderValue::try_from(user_agent.as_str())
+                .map_err(|_| ApiError::InvalidHeader(USER_AGENT.to_string()))?;
+
+            headers.insert(USER_AGENT, value);
+        }
+
+        Ok(headers)
+    }
+
+    /// The credentials stored as `name`.
+    #[must_use]
+    pub fn credentials(&self, name: &str) -> Option<&Credentials> {
+        self.keyring.get(name)
+    }
+
+    /// A copy of the config that signs with the credentials stored as `name`, for passing to the
+    /// private REST functions.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::UnknownCredentials`] if there are no credentials named `name`.
+    pub fn for_credentials(&self, name: &str) -> Result<Self> {
+        let Some(credentials) = self.credentials(name) else {
+            anyhow::bail!(ApiError::UnknownCredentials(name.to_owned()));
+        };
+
+        Ok(Self {
+            api_key: Some(credentials.api_key.clone()),
+            secret_key: Some(credentials.secret_key.clone()),
+            ..self.clone()
+        })
+    }
 }
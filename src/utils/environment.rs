@@ -0,0 +1,99 @@
+//! The crypto.com environments and their URLs.
+
+use crate::error::ApiError;
+
+/// A crypto.com environment, supplies the REST and websocket URLs to connect to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Environment {
+    /// The live exchange.
+    #[default]
+    Production,
+    /// The UAT sandbox, for testing against without real funds.
+    UatSandbox,
+}
+
+impl Environment {
+    /// REST URL ending in `/`, see [`crate::utils::config::Config::rest_url`].
+    #[must_use]
+    pub const fn rest_url_str(self) -> &'static str {
+        match self {
+            Self::Production => "https://api.crypto.com/v2/",
+            Self::UatSandbox => "https://uat-api.3ona.co/v2/",
+        }
+    }
+
+    /// User websocket URL.
+    #[must_use]
+    pub const fn websocket_user_url_str(self) -> &'static str {
+        match self {
+            Self::Production => "wss://stream.crypto.com/v2/user",
+            Self::UatSandbox => "wss://uat-stream.3ona.co/v2/user",
+        }
+    }
+
+    /// Market websocket URL.
+    #[must_use]
+    pub const fn websocket_market_url_str(self) -> &'static str {
+        match self {
+            Self::Production => "wss://stream.crypto.com/v2/market",
+            Self::UatSandbox => "wss://uat-stream.3ona.co/v2/market",
+        }
+    }
+
+    /// REST URL ending in `/`, see [`crate::utils::config::Config::rest_url`].
+    ///
+    /// # Panics
+    ///
+    /// Never, every environment URL is valid.
+    #[must_use]
+    pub fn rest_url(self) -> url::Url {
+        parse(self.rest_url_str())
+    }
+
+    /// User websocket URL.
+    ///
+    /// # Panics
+    ///
+    /// Never, every environment URL is valid.
+    #[must_use]
+    pub fn websocket_user_url(self) -> url::Url {
+        parse(self.websocket_user_url_str())
+    }
+
+    /// Market websocket URL.
+    ///
+    /// # Panics
+    ///
+    /// Never, every environment URL is valid.
+    #[must_use]
+    pub fn websocket_market_url(self) -> url::Url {
+        parse(self.websocket_market_url_str())
+    }
+
+    /// Whether withdrawals may be sent, the sandbox refuses them so a test never moves real
+    /// funds by pointing at the wrong exchange.
+    #[must_use]
+    pub const fn allows_withdrawals(self) -> bool {
+        matches!(self, Self::Production)
+    }
+}
+
+/// Fail with [`ApiError::WithdrawalRefused`] if `environment` does not allow withdrawals, an
+/// unknown environment allows them.
+///
+/// # Errors
+///
+/// Will return [`ApiError::WithdrawalRefused`] if the environment does not allow withdrawals.
+pub fn check_withdrawal(environment: Option<Environment>) -> Result<(), ApiError> {
+    match environment {
+        Some(environment) if !environment.allows_withdrawals() => {
+            Err(ApiError::WithdrawalRefused(environment))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Parse one of the environment URLs.
+fn parse(url: &'static str) -> url::Url {
+    url::Url::parse(url).expect("environment URLs are valid")
+}
@@ -0,0 +1,221 @@
+//! Tracking of requests that have been sent and not yet answered.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::sync::{Mutex, Notify};
+
+use crate::websocket::actions::ActionKind;
+
+/// The websocket a request was sent on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum Api {
+    /// The user websocket.
+    User,
+    /// The market websocket.
+    Market,
+}
+
+/// A request waiting on a response, kept so it can be replayed after a reconnect.
+#[derive(Debug)]
+struct Pending {
+    /// The websocket the request was sent on.
+    api: Api,
+    /// The action that produced the request.
+    action: Arc<ActionKind>,
+    /// When the request was queued.
+    sent_at: Instant,
+}
+
+/// The requests that are waiting on a response from crypto.com.
+///
+/// Requests are added by the controller before an action is sent and removed by the stream
+/// processors when a response carrying the same ID arrives. Anything still here when a websocket
+/// drops can be replayed with [`crate::controller::Controller::reconnect_market`] or
+/// [`crate::controller::Controller::reconnect_user`].
+#[derive(Debug, Default)]
+pub struct InFlight {
+    /// Unanswered requests by ID.
+    requests: Mutex<BTreeMap<u64, Pending>>,
+    /// Most requests kept at once, `None` for no limit.
+    capacity: Option<usize>,
+    /// Notified when the last unanswered request is resolved.
+    drained: Notify,
+    /// Notified whenever requests are resolved, taken, or expired.
+    removed: Notify,
+}
+
+impl InFlight {
+    /// Keep at most `capacity` unanswered requests, the oldest is dropped to make room.
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity: Some(capacity),
+            ..Self::default()
+        }
+    }
+
+    /// Mark `id` as sent on `api` and awaiting a response.
+    ///
+    /// Returns the ID of the oldest request if it was dropped to stay within the capacity, it will
+    /// not be replayed.
+    pub async fn insert(&self, id: u64, api: Api, action: Arc<ActionKind>) -> Option<u64> {
+        let mut requests = self.requests.lock().await;
+        requests.insert(
+            id,
+            Pending {
+                api,
+                action,
+                sent_at: Instant::now(),
+            },
+        );
+
+        match self.capacity {
+            Some(capacity) if requests.len() > capacity => {
+                requests.pop_first().map(|(dropped, _)| dropped)
+            }
+            _ => None,
+        }
+    }
+
+    /// Mark `id` as answered, returns the action that was sent or `None` if `id` was not in
+    /// flight.
+    pub async fn resolve(&self, id: u64) -> Option<Arc<ActionKind>> {
+        let mut requests = self.requests.lock().await;
+        let removed = requests.remove(&id).map(|pending| pending.action);
+
+        if removed.is_some() {
+            self.removed.notify_waiters();
+
+            if requests.is_empty() {
+                self.drained.notify_waiters();
+            }
+        }
+
+        removed
+    }
+
+    /// Remove every unanswered request sent on `api`, in the order they were sent.
+    pub async fn take(&self, api: Api) -> Vec<(u64, Arc<ActionKind>)> {
+        let mut requests = self.requests.lock().await;
+        let ids: Vec<u64> = requests
+            .iter()
+            .filter(|(_, pending)| pending.api == api)
+            .map(|(id, _)| *id)
+            .collect();
+
+        let taken = ids
+            .into_iter()
+            .filter_map(|id| requests.remove(&id).map(|pending| (id, pending.action)))
+            .collect();
+
+        self.removed.notify_waiters();
+
+        if requests.is_empty() {
+            self.drained.notify_waiters();
+        }
+
+        taken
+    }
+
+    /// Remove every unanswered request sent on `api` with an ID below `id`, in the order they
+    /// were sent.
+    pub async fn take_before(&self, api: Api, id: u64) -> Vec<(u64, Arc<ActionKind>)> {
+        let mut requests = self.requests.lock().await;
+        let ids: Vec<u64> = requests
+            .range(..id)
+            .filter(|(_, pending)| pending.api == api)
+            .map(|(id, _)| *id)
+            .collect();
+
+        let taken = ids
+            .into_iter()
+            .filter_map(|id| requests.remove(&id).map(|pending| (id, pending.action)))
+            .collect();
+
+        self.removed.notify_waiters();
+
+        if requests.is_empty() {
+            self.drained.notify_waiters();
+        }
+
+        taken
+    }
+
+    /// Remove every request that has waited longer than `timeout` for a response.
+    pub async fn expire(&self, timeout: Duration) -> Vec<(u64, Arc<ActionKind>)> {
+        let mut requests = self.requests.lock().await;
+        let ids: Vec<u64> = requests
+            .iter()
+            .filter(|(_, pending)| pending.sent_at.elapsed() >= timeout)
+            .map(|(id, _)| *id)
+            .collect();
+
+        let expired = ids
+            .into_iter()
+            .filter_map(|id| requests.remove(&id).map(|pending| (id, pending.action)))
+            .collect();
+
+        self.removed.notify_waiters();
+
+        if requests.is_empty() {
+            self.drained.notify_waiters();
+        }
+
+        expired
+    }
+
+    /// The unanswered requests sent on `api`, in the order they were sent, e.g. to save the
+    /// serializable ones, see [`ActionKind::is_serializable`].
+    pub async fn pending(&self, api: Api) -> Vec<(u64, Arc<ActionKind>)> {
+        self.requests
+            .lock()
+            .await
+            .iter()
+            .filter(|(_, pending)| pending.api == api)
+            .map(|(id, pending)| (*id, Arc::clone(&pending.action)))
+            .collect()
+    }
+
+    /// The IDs that are still waiting on a response, in ascending order.
+    pub async fn ids(&self) -> Vec<u64> {
+        self.requests.lock().await.keys().copied().collect()
+    }
+
+    /// Wait until every request in flight has been answered.
+    pub async fn drained(&self) {
+        loop {
+            // Created before checking so a resolve between the check and the await is not missed.
+            let notified = self.drained.notified();
+
+            if self.requests.lock().await.is_empty() {
+                return;
+            }
+
+            notified.await;
+        }
+    }
+
+    /// Wait until no request sent on `api` with an ID below `id` is waiting on a response.
+    pub async fn settled_before(&self, api: Api, id: u64) {
+        loop {
+            // Created before checking so a removal between the check and the await is not missed.
+            let notified = self.removed.notified();
+
+            let waiting = self
+                .requests
+                .lock()
+                .await
+                .range(..id)
+                .any(|(_, pending)| pending.api == api);
+
+            if !waiting {
+                return;
+            }
+
+            notified.await;
+        }
+    }
+}
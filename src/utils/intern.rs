@@ -0,0 +1,32 @@
+//! String interning for values that repeat in every market data message.
+//!
+//! Channel names, subscriptions, and instrument names are the same handful of strings for the
+//! lifetime of a connection, so rather than allocating a fresh `String` for each message they are
+//! stored once and handed out as [`Arc<str>`].
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+/// Global intern table.
+static INTERNED: OnceLock<Mutex<HashSet<Arc<str>>>> = OnceLock::new();
+
+/// Get a shared [`Arc<str>`] for `value`, allocating only the first time it is seen.
+///
+/// The table is never pruned, it is intended for the bounded set of channel and instrument names,
+/// not for unique values such as trade IDs.
+#[must_use]
+pub fn intern(value: &str) -> Arc<str> {
+    let mut interned = INTERNED
+        .get_or_init(Mutex::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+
+    if let Some(existing) = interned.get(value) {
+        return Arc::clone(existing);
+    }
+
+    let new: Arc<str> = Arc::from(value);
+    interned.insert(Arc::clone(&new));
+
+    new
+}
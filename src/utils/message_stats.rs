@@ -0,0 +1,329 @@
+//! Message counts and sizes per subscription, for planning how many instruments one websocket
+//! can carry.
+//!
+//! Enable it with [`crate::controller::ControllerBuilder::with_message_stats`] and read it with
+//! [`MessageStats::snapshot`]. Sizes are of the text frames as recieved, crypto.com does not
+//! compress them.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use crate::utils::raw_hook::RawMessageHook;
+
+/// Key for frames that are not subscription results, e.g. heartbeats and request replies.
+pub const OTHER: &str = "other";
+
+/// Counts of one subscription.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChannelStats {
+    /// Channel of the subscription, e.g. `book`.
+    pub channel: String,
+    /// Messages since the stats were created.
+    pub messages: u64,
+    /// Bytes since the stats were created.
+    pub bytes: u64,
+    /// Largest message since the stats were created, in bytes.
+    pub largest_message: u64,
+    /// Messages in the rolling window.
+    pub window_messages: u64,
+    /// Bytes in the rolling window.
+    pub window_bytes: u64,
+    /// Messages per second over the rolling window.
+    pub messages_per_sec: f64,
+    /// Bytes per second over the rolling window.
+    pub bytes_per_sec: f64,
+}
+
+impl ChannelStats {
+    /// Add the counts of `other`, keeping the channel.
+    fn add(&mut self, other: &Self) {
+        self.messages += other.messages;
+        self.bytes += other.bytes;
+        self.largest_message = self.largest_message.max(other.largest_message);
+        self.window_messages += other.window_messages;
+        self.window_bytes += other.window_bytes;
+        self.messages_per_sec += other.messages_per_sec;
+        self.bytes_per_sec += other.bytes_per_sec;
+    }
+}
+
+/// Counts by subscription, from [`MessageStats::snapshot`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MessageStatsSnapshot {
+    /// Length of the rolling window the rates are taken over, shorter until the stats are that
+    /// old.
+    pub window: Duration,
+    /// Counts by subscription, e.g. `book.BTC_USDT.10`, or [`OTHER`].
+    pub subscriptions: BTreeMap<String, ChannelStats>,
+}
+
+impl MessageStatsSnapshot {
+    /// Counts of every subscription of `channel` added together, e.g. every `book` subscription.
+    #[must_use]
+    pub fn channel(&self, channel: &str) -> ChannelStats {
+        let mut total = ChannelStats {
+            channel: channel.to_owned(),
+            ..ChannelStats::default()
+        };
+
+        for stats in self.subscriptions.values() {
+            if stats.channel == channel {
+                total.add(stats);
+            }
+        }
+
+        total
+    }
+
+    /// Counts of every message added together.
+    #[must_use]
+    pub fn total(&self) -> ChannelStats {
+        let mut total = ChannelStats::default();
+
+        for stats in self.subscriptions.values() {
+            total.add(stats);
+        }
+
+        total
+    }
+}
+
+/// Messages and bytes recieved in one second.
+#[derive(Clone, Copy, Debug)]
+struct Bucket {
+    /// Whole seconds since the stats were created.
+    second: u64,
+    /// Messages in the second.
+    messages: u64,
+    /// Bytes in the second.
+    bytes: u64,
+}
+
+/// Counts of one subscription, with a bucket for each second of the window.
+#[derive(Debug, Default)]
+struct Counter {
+    /// Channel of the subscription.
+    channel: String,
+    /// Messages since the stats were created.
+    messages: u64,
+    /// Bytes since the stats were created.
+    bytes: u64,
+    /// Largest message in bytes.
+    largest_message: u64,
+    /// Seconds with messages in the window, oldest first.
+    buckets: VecDeque<Bucket>,
+}
+
+impl Counter {
+    /// Drop the buckets that are older than `window_secs` at `second`.
+    fn trim(&mut self, second: u64, window_secs: u64) {
+        while self
+            .buckets
+            .front()
+            .is_some_and(|bucket| bucket.second + window_secs <= second)
+        {
+            self.buckets.pop_front();
+        }
+    }
+}
+
+/// The routing fields of a subscription result.
+#[derive(Deserialize)]
+struct Probe {
+    /// Result of the frame.
+    result: Option<ProbeResult>,
+}
+
+/// The routing fields of a result.
+#[derive(Deserialize)]
+struct ProbeResult {
+    /// Channel of a subscription result.
+    channel: Option<String>,
+    /// Subscription of a subscription result.
+    subscription: Option<String>,
+}
+
+/// State shared by the clones of a [`MessageStats`].
+#[derive(Debug)]
+struct State {
+    /// When the stats were created, buckets are whole seconds since.
+    started: Instant,
+    /// Counts by subscription.
+    counters: HashMap<String, Counter>,
+}
+
+/// Counts messages and bytes by subscription, in total and over a rolling window.
+///
+/// Clones share the same counts, so a clone handed to the builder can be read from anywhere.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use crypto_com_api::utils::message_stats::MessageStats;
+///
+/// let stats = MessageStats::new(Duration::from_secs(60));
+/// stats.record(r#"{"method":"subscribe","result":{"channel":"trade","subscription":"trade.BTC_USDT","data":[]}}"#);
+///
+/// let snapshot = stats.snapshot();
+/// assert_eq!(snapshot.subscriptions["trade.BTC_USDT"].messages, 1);
+/// assert_eq!(snapshot.channel("trade").messages, 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct MessageStats {
+    /// Length of the rolling window.
+    window: Duration,
+    /// Counts shared by every clone.
+    state: Arc<Mutex<State>>,
+}
+
+impl Default for MessageStats {
+    /// Stats with a one minute window.
+    fn default() -> Self {
+        Self::new(Duration::from_secs(60))
+    }
+}
+
+impl MessageStats {
+    /// Stats with rates over the last `window`, rounded up to a whole second.
+    #[must_use]
+    pub fn new(window: Duration) -> Self {
+        let secs = window.as_secs() + u64::from(window.subsec_nanos() > 0);
+
+        Self {
+            window: Duration::from_secs(secs.max(1)),
+            state: Arc::new(Mutex::new(State {
+                started: Instant::now(),
+                counters: HashMap::new(),
+            })),
+        }
+    }
+
+    /// Length of the rolling window.
+    #[must_use]
+    pub const fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// A [`RawMessageHook`] that records every frame and then calls `next`, if any.
+    #[must_use]
+    pub fn hook(&self, next: Option<RawMessageHook>) -> RawMessageHook {
+        let stats = self.clone();
+
+        RawMessageHook::new(move |text| {
+            stats.record(text);
+
+            if let Some(ref next) = next {
+                next.call_text(text);
+            }
+        })
+    }
+
+    /// Record the text frame `text` as recieved now.
+    pub fn record(&self, text: &str) {
+        self.record_at(text, Instant::now());
+    }
+
+    /// Record the text frame `text` as recieved at `now`.
+    pub fn record_at(&self, text: &str, now: Instant) {
+        let (subscription, channel) = match serde_json::from_str::<Probe>(text) {
+            Ok(Probe {
+                result:
+                    Some(ProbeResult {
+                        channel,
+                        subscription: Some(subscription),
+                    }),
+            }) => {
+                let channel = channel.unwrap_or_else(|| {
+                    subscription
+                        .split('.')
+                        .next()
+                        .unwrap_or_default()
+                        .to_owned()
+                });
+
+                (subscription, channel)
+            }
+            _ => (OTHER.to_owned(), OTHER.to_owned()),
+        };
+
+        let bytes = text.len() as u64;
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let second = now.saturating_duration_since(state.started).as_secs();
+        let window_secs = self.window.as_secs();
+
+        let counter = state
+            .counters
+            .entry(subscription)
+            .or_insert_with(|| Counter {
+                channel,
+                ..Counter::default()
+            });
+
+        counter.messages += 1;
+        counter.bytes += bytes;
+        counter.largest_message = counter.largest_message.max(bytes);
+        counter.trim(second, window_secs);
+
+        match counter.buckets.back_mut() {
+            Some(bucket) if bucket.second == second => {
+                bucket.messages += 1;
+                bucket.bytes += bytes;
+            }
+            _ => counter.buckets.push_back(Bucket {
+                second,
+                messages: 1,
+                bytes,
+            }),
+        }
+    }
+
+    /// The counts as they are now.
+    #[must_use]
+    pub fn snapshot(&self) -> MessageStatsSnapshot {
+        self.snapshot_at(Instant::now())
+    }
+
+    /// The counts as they are at `now`.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn snapshot_at(&self, now: Instant) -> MessageStatsSnapshot {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let elapsed = now.saturating_duration_since(state.started);
+        let second = elapsed.as_secs();
+        let window_secs = self.window.as_secs();
+        // Until the stats are a window old, the rates are over the time so far.
+        let window = self.window.min(elapsed).max(Duration::from_secs(1));
+
+        let subscriptions = state
+            .counters
+            .iter_mut()
+            .map(|(subscription, counter)| {
+                counter.trim(second, window_secs);
+
+                let window_messages = counter.buckets.iter().map(|bucket| bucket.messages).sum();
+                let window_bytes = counter.buckets.iter().map(|bucket| bucket.bytes).sum();
+
+                let stats = ChannelStats {
+                    channel: counter.channel.clone(),
+                    messages: counter.messages,
+                    bytes: counter.bytes,
+                    largest_message: counter.largest_message,
+                    window_messages,
+                    window_bytes,
+                    messages_per_sec: window_messages as f64 / window.as_secs_f64(),
+                    bytes_per_sec: window_bytes as f64 / window.as_secs_f64(),
+                };
+
+                (subscription.clone(), stats)
+            })
+            .collect();
+
+        MessageStatsSnapshot {
+            window,
+            subscriptions,
+        }
+    }
+}
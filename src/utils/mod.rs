@@ -1,20 +1,30 @@
 //! Utility functions that are typically used for one situation in multiple places.
 
-use std::{
-    str,
-    sync::Arc,
-    time::{SystemTime, UNIX_EPOCH},
-};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{str, sync::Arc};
 
 use anyhow::Result as AnyResult;
 use futures_channel::mpsc::UnboundedSender;
 use tokio::sync::Mutex;
-use tokio_tungstenite::tungstenite::Message;
+use tungstenite::Message;
 
 use crate::{api_response::ApiResponse, prelude::ApiError};
 
+#[cfg(feature = "websocket-market")]
 pub mod action;
+#[cfg(feature = "websocket-market")]
+pub mod action_queue;
+pub mod clock;
 pub mod config;
+pub mod environment;
+#[cfg(feature = "websocket-market")]
+pub mod in_flight;
+pub mod intern;
+pub mod message_stats;
+pub mod raw_hook;
+pub mod request_log;
+pub(crate) mod trace;
 
 /// Process parameters to a format of key + value with no spaces and no delimiters.
 ///
@@ -63,12 +73,13 @@ pub fn params_to_str(obj: &serde_json::Value) -> String {
         }
     }
 
-    log::info!("{return_str}");
+    trace::info!("{return_str}");
 
     return_str
 }
 
 /// The the epoch since the UNIX epoch in ms AKA the nonce value.
+#[cfg(not(target_arch = "wasm32"))]
 #[must_use]
 pub fn get_epoch_ms() -> u64 {
     let start = SystemTime::now();
@@ -79,6 +90,14 @@ pub fn get_epoch_ms() -> u64 {
     since_the_epoch.as_secs() * 1000 + u64::from(since_the_epoch.subsec_nanos()) / 1_000_000
 }
 
+/// The the epoch since the UNIX epoch in ms AKA the nonce value, from the browser's clock as
+/// [`SystemTime`](std::time::SystemTime) panics on wasm32.
+#[cfg(target_arch = "wasm32")]
+#[must_use]
+pub fn get_epoch_ms() -> u64 {
+    js_sys::Date::now() as u64
+}
+
 /// An odd function I needed for handling turning a message string into a typed value when
 /// processing data from crypto.com.
 ///
@@ -98,6 +117,26 @@ where
     Ok(A::try_from(raw_msg)?)
 }
 
+/// Turn an already parsed [`serde_json::Value`] into a typed value without serializing it back
+/// into a string first.
+///
+/// Raw types that borrow `&'a str` fields borrow them straight from `value`, so the only
+/// allocations are the ones made by the processed type.
+///
+/// # Errors
+///
+/// Will return [`serde_json::Error`] if `value` does not match the shape of `T`.
+pub fn reprocess_value<'a, T, A>(value: &'a serde_json::Value) -> Result<A, ApiError>
+where
+    T: serde::Deserialize<'a>,
+    A: TryFrom<T> + std::fmt::Debug,
+    ApiError: From<<A as TryFrom<T>>::Error>,
+{
+    let raw_msg = T::deserialize(value)?;
+
+    Ok(A::try_from(raw_msg)?)
+}
+
 /// A function to convert a `tungstenite::Message` into a `ApiResponse<serde_json::Value>`.
 ///
 /// NOTE: This function does also handle sending back ping messages as they arrive.
@@ -130,7 +169,7 @@ pub async fn message_to_api_response(
             }
         }
         ref msg => {
-            log::warn!("Unsupported message recieved. {:#?}", &msg);
+            trace::warning!("Unsupported message recieved. {:#?}", &msg);
 
             ApiResponse::default()
         }
@@ -0,0 +1,48 @@
+//! Hook for observing websocket frames before they are parsed.
+
+use std::fmt;
+use std::sync::Arc;
+
+use tungstenite::Message;
+
+/// A callback given every text frame exactly as it was recieved from crypto.com.
+///
+/// This is useful for debugging and for reading fields that this crate does not model yet.
+#[derive(Clone)]
+pub struct RawMessageHook(Arc<dyn Fn(&str) + Send + Sync>);
+
+impl RawMessageHook {
+    /// Wrap `hook` so it can be shared between the websocket streams.
+    pub fn new<F>(hook: F) -> Self
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        Self(Arc::new(hook))
+    }
+
+    /// Call the hook with the text of `message`, binary frames are passed on if they are UTF-8.
+    ///
+    /// Control frames are ignored.
+    pub fn call(&self, message: &Message) {
+        match *message {
+            Message::Text(ref text) => self.call_text(text),
+            Message::Binary(ref data) => {
+                if let Ok(text) = std::str::from_utf8(data) {
+                    self.call_text(text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Call the hook with `text`.
+    pub fn call_text(&self, text: &str) {
+        (self.0)(text);
+    }
+}
+
+impl fmt::Debug for RawMessageHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RawMessageHook")
+    }
+}
@@ -0,0 +1,251 @@
+//! Logging of the requests sent to and the responses recieved from the websockets, with secrets
+//! redacted.
+//!
+//! Enable it with [`crate::utils::config::Config::with_request_log`]. Each frame is logged at
+//! debug level to the [`TARGET`] target of `tracing` with the `tracing` feature, as the fields
+//! `direction`, `id`, `method`, and `payload`, or of `log` without it, unless a sink is given with
+//! [`RequestLog::with_sink`].
+
+use std::fmt;
+use std::sync::Arc;
+
+use serde_json::Value;
+use tungstenite::Message;
+
+use crate::utils::trace;
+
+/// Target the frames are logged to.
+pub const TARGET: &str = "crypto_com_api::wire";
+
+/// What a redacted value is replaced with.
+pub const REDACTED: &str = "[redacted]";
+
+/// Keys whose values are always redacted, wherever they are in a frame.
+pub const DEFAULT_REDACTED_KEYS: &[&str] =
+    &["api_key", "sig", "secret_key", "address", "address_tag"];
+
+/// How much of each frame is logged.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// The method and id of requests and responses.
+    Methods,
+    /// The redacted payload of requests as well.
+    #[default]
+    Requests,
+    /// The redacted payload of responses as well, including every subscription message.
+    All,
+}
+
+/// Whether a frame was sent or recieved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// Sent to crypto.com.
+    Request,
+    /// Recieved from crypto.com.
+    Response,
+}
+
+impl Direction {
+    /// `request` or `response`.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Request => "request",
+            Self::Response => "response",
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One frame as it is logged.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LoggedFrame {
+    /// Whether the frame was sent or recieved.
+    pub direction: Direction,
+    /// Id of the request, or of the request the response answers.
+    pub id: Option<i64>,
+    /// Method of the frame, e.g. `private/create-order`.
+    pub method: Option<String>,
+    /// The frame with secrets redacted, if the verbosity includes it.
+    pub payload: Option<String>,
+}
+
+/// Where the frames go instead of the log.
+type Sink = Arc<dyn Fn(&LoggedFrame) + Send + Sync>;
+
+/// Logs the frames of the websockets at a [`Verbosity`], redacting the values of
+/// [`DEFAULT_REDACTED_KEYS`] and any added with [`RequestLog::redact`].
+///
+/// ```
+/// use crypto_com_api::utils::request_log::{Direction, RequestLog, Verbosity};
+///
+/// let log = RequestLog::new(Verbosity::Requests);
+/// let frame = log.frame(
+///     Direction::Request,
+///     r#"{"id":1,"method":"public/auth","api_key":"key","sig":"abc","nonce":1}"#,
+/// );
+///
+/// assert_eq!(frame.method.as_deref(), Some("public/auth"));
+/// assert_eq!(
+///     frame.payload.as_deref(),
+///     Some(r#"{"api_key":"[redacted]","id":1,"method":"public/auth","nonce":1,"sig":"[redacted]"}"#)
+/// );
+/// ```
+#[derive(Clone)]
+pub struct RequestLog {
+    /// How much of each frame is logged.
+    verbosity: Verbosity,
+    /// Keys whose values are redacted.
+    redacted: Vec<String>,
+    /// Given every frame in place of the log while set.
+    sink: Option<Sink>,
+}
+
+impl Default for RequestLog {
+    fn default() -> Self {
+        Self::new(Verbosity::default())
+    }
+}
+
+impl fmt::Debug for RequestLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestLog")
+            .field("verbosity", &self.verbosity)
+            .field("redacted", &self.redacted)
+            .field("sink", &self.sink.is_some())
+            .finish()
+    }
+}
+
+impl RequestLog {
+    /// Log frames at `verbosity`, redacting [`DEFAULT_REDACTED_KEYS`].
+    #[must_use]
+    pub fn new(verbosity: Verbosity) -> Self {
+        Self {
+            verbosity,
+            redacted: DEFAULT_REDACTED_KEYS
+                .iter()
+                .map(|&key| key.to_owned())
+                .collect(),
+            sink: None,
+        }
+    }
+
+    /// Redact the value of `key` as well, wherever it is in a frame.
+    #[must_use]
+    pub fn redact(mut self, key: impl Into<String>) -> Self {
+        self.redacted.push(key.into());
+
+        self
+    }
+
+    /// Give every frame to `sink` instead of logging it.
+    #[must_use]
+    pub fn with_sink<F>(mut self, sink: F) -> Self
+    where
+        F: Fn(&LoggedFrame) + Send + Sync + 'static,
+    {
+        self.sink = Some(Arc::new(sink));
+
+        self
+    }
+
+    /// How much of each frame is logged.
+    #[must_use]
+    pub const fn verbosity(&self) -> Verbosity {
+        self.verbosity
+    }
+
+    /// `text` with the value of every redacted key replaced with [`REDACTED`], `None` if it is
+    /// not JSON.
+    #[must_use]
+    pub fn redact_text(&self, text: &str) -> Option<String> {
+        let mut value: Value = serde_json::from_str(text).ok()?;
+        self.redact_value(&mut value);
+
+        Some(value.to_string())
+    }
+
+    /// Replace the value of every redacted key in `value` with [`REDACTED`].
+    fn redact_value(&self, value: &mut Value) {
+        match *value {
+            Value::Object(ref mut map) => {
+                for (key, value) in map.iter_mut() {
+                    if self.redacted.iter().any(|redacted| redacted == key) {
+                        *value = Value::String(REDACTED.to_owned());
+                    } else {
+                        self.redact_value(value);
+                    }
+                }
+            }
+            Value::Array(ref mut values) => {
+                for value in values {
+                    self.redact_value(value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The text frame `text` as it is logged when going in `direction`.
+    ///
+    /// A frame that is not JSON is logged without a payload, it cannot be redacted.
+    #[must_use]
+    pub fn frame(&self, direction: Direction, text: &str) -> LoggedFrame {
+        let with_payload = match direction {
+            Direction::Request => self.verbosity >= Verbosity::Requests,
+            Direction::Response => self.verbosity >= Verbosity::All,
+        };
+
+        let Ok(mut value) = serde_json::from_str::<Value>(text) else {
+            return LoggedFrame {
+                direction,
+                id: None,
+                method: None,
+                payload: None,
+            };
+        };
+
+        let id = value.get("id").and_then(Value::as_i64);
+        let method = value
+            .get("method")
+            .and_then(Value::as_str)
+            .map(str::to_owned);
+
+        let payload = with_payload.then(|| {
+            self.redact_value(&mut value);
+            value.to_string()
+        });
+
+        LoggedFrame {
+            direction,
+            id,
+            method,
+            payload,
+        }
+    }
+
+    /// Log `message` going in `direction`, control frames are not logged.
+    pub fn log(&self, direction: Direction, message: &Message) {
+        let text = match *message {
+            Message::Text(ref text) => text.as_str(),
+            Message::Binary(ref data) => match std::str::from_utf8(data) {
+                Ok(text) => text,
+                Err(_) => return,
+            },
+            _ => return,
+        };
+
+        let frame = self.frame(direction, text);
+
+        match self.sink {
+            Some(ref sink) => sink(&frame),
+            None => trace::log_frame(&frame),
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Diagnostics that go to `tracing` with the `tracing` feature and to `log` without it.
+//!
+//! With `tracing`, each recieved websocket message is processed inside a `message` span carrying
+//! its `id`, `method`, `channel`, and `instrument`, and each sent request inside a `request` span
+//! carrying its `id` and `method`, so requests can be correlated with their responses.
+
+// The spans are for websocket messages, unused by REST alone.
+#![cfg_attr(not(feature = "websocket-market"), allow(dead_code))]
+
+use std::future::Future;
+
+use crate::utils::request_log::{LoggedFrame, TARGET};
+
+/// Forward to `tracing::$level!` or `log::$level!`, defined per level below.
+///
+/// `warn` is named `warning` here as `warn` is taken by the built-in lint attribute.
+#[rustfmt::skip]
+macro_rules! forward {
+    ($name:ident, $level:ident, $dollar:tt) => {
+        // Not every level is used with every set of features, e.g. REST alone.
+        #[allow(unused_macros)]
+        macro_rules! $name {
+            ($dollar($dollar arg:tt)+) => {{
+                #[cfg(feature = "tracing")]
+                ::tracing::$level!($dollar($dollar arg)+);
+                #[cfg(not(feature = "tracing"))]
+                ::log::$level!($dollar($dollar arg)+);
+            }};
+        }
+
+        #[allow(unused_imports)]
+        pub(crate) use $name;
+    };
+}
+
+forward!(error, error, $);
+forward!(warning, warn, $);
+forward!(info, info, $);
+forward!(debug, debug, $);
+
+/// Run `fut` inside a span for one message recieved from the `api` websocket.
+///
+/// The message fields are filled in with [`record_message`] and [`record_subscription`] once
+/// they are parsed.
+#[cfg(feature = "tracing")]
+pub(crate) fn in_message_span<F: Future>(
+    api: &'static str,
+    fut: F,
+) -> tracing::instrument::Instrumented<F> {
+    use tracing::field::Empty;
+    use tracing::Instrument;
+
+    fut.instrument(tracing::info_span!(
+        "message",
+        api,
+        id = Empty,
+        method = Empty,
+        channel = Empty,
+        instrument = Empty,
+    ))
+}
+
+/// Run `fut` inside a span for one message recieved from the `api` websocket.
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn in_message_span<F: Future>(_api: &'static str, fut: F) -> F {
+    fut
+}
+
+/// Record the `id` and `method` of the message being processed.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn record_message(id: i64, method: &str) {
+    #[cfg(feature = "tracing")]
+    tracing::Span::current()
+        .record("id", id)
+        .record("method", method);
+}
+
+/// Record the `channel` and `instrument` of the subscription data being processed.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn record_subscription(channel: &str, instrument: Option<&str>) {
+    #[cfg(feature = "tracing")]
+    {
+        let span = tracing::Span::current();
+        span.record("channel", channel);
+
+        if let Some(instrument) = instrument {
+            span.record("instrument", instrument);
+        }
+    }
+}
+
+/// Enter a span for sending request `id` with `method`, it is exited when the guard is dropped.
+#[cfg(feature = "tracing")]
+pub(crate) fn enter_request(id: u64, method: &str) -> tracing::span::EnteredSpan {
+    tracing::info_span!("request", id, method).entered()
+}
+
+/// Stand-in for an entered span when the `tracing` feature is disabled.
+#[cfg(not(feature = "tracing"))]
+#[derive(Debug)]
+#[must_use]
+pub(crate) struct EnteredSpan;
+
+/// Enter a span for sending request `id` with `method`, it is exited when the guard is dropped.
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn enter_request(_id: u64, _method: &str) -> EnteredSpan {
+    EnteredSpan
+}
+
+/// Log a frame sent to or recieved from a websocket, see [`crate::utils::request_log`].
+pub(crate) fn log_frame(frame: &LoggedFrame) {
+    #[cfg(feature = "tracing")]
+    ::tracing::debug!(
+        target: TARGET,
+        direction = frame.direction.as_str(),
+        id = frame.id,
+        method = frame.method.as_deref(),
+        payload = frame.payload.as_deref(),
+    );
+
+    #[cfg(not(feature = "tracing"))]
+    ::log::debug!(
+        target: TARGET,
+        "{} {} id={} {}",
+        frame.direction,
+        frame.method.as_deref().unwrap_or("-"),
+        frame.id.map_or_else(|| "-".to_owned(), |id| id.to_string()),
+        frame.payload.as_deref().unwrap_or_default(),
+    );
+}
@@ -1,32 +1,264 @@
 //! A crate for holding websocket actions.
 
-use anyhow::Result;
+use std::any::Any;
+use std::sync::Arc;
+
 use futures_channel::mpsc::UnboundedSender;
-use serde::Serialize;
-use tokio_tungstenite::tungstenite::Message;
+use serde::{Deserialize, Serialize};
+use tungstenite::Message;
 
-use crate::utils::action::Action;
+use crate::error::Result;
+use crate::prelude::ApiError;
+use crate::types::Interval;
+use crate::utils::action::{Access, Action};
+#[cfg(feature = "websocket")]
 use crate::websocket::auth;
+use crate::websocket::channel::Channel;
 use crate::websocket::{send_msg, send_params_msg};
 
+#[cfg(feature = "websocket")]
 pub mod spot_trading_api;
+#[cfg(feature = "websocket")]
 pub mod wallet_management_api;
 
+/// Channels sent in each request by [`crate::utils::action_queue::ActionQueue::subscribe_many`]
+/// when no smaller chunk size is given.
+pub const SUBSCRIBE_CHUNK_SIZE: usize = 100;
+
+/// How `book` channels are delivered.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BookSubscriptionType {
+    /// A full snapshot on every push.
+    Snapshot,
+    /// A single snapshot followed by `book.update` deltas.
+    SnapshotAndUpdate,
+}
+
 /// Subscription action.
-#[derive(Serialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Subscribe {
     /// A list of channels to subscribe to.
     pub channels: Vec<String>,
+    /// How `book` channels are delivered, the exchange default is used when omitted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub book_subscription_type: Option<BookSubscriptionType>,
+    /// Milliseconds between `book` pushes, the exchange default is used when omitted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub book_update_frequency: Option<u64>,
+}
+
+impl Subscribe {
+    /// Deliver `book` channels as `subscription_type`.
+    #[must_use]
+    pub fn with_book_subscription_type(mut self, subscription_type: BookSubscriptionType) -> Self {
+        self.book_subscription_type = Some(subscription_type);
+
+        self
+    }
+
+    /// Push `book` channels every `frequency` milliseconds.
+    #[must_use]
+    pub fn with_book_update_frequency(mut self, frequency: u64) -> Self {
+        self.book_update_frequency = Some(frequency);
+
+        self
+    }
+
+    /// Check the interval of every `candlestick.{time_frame}.{instrument_name}` channel against
+    /// the websocket names of [`Interval`], see [`Interval::from_websocket_str`], crypto.com only
+    /// answers an unknown interval with a generic error.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::InvalidInterval`] for the first channel with an unknown interval.
+    pub fn validate(&self) -> Result<(), ApiError> {
+        for channel in &self.channels {
+            let mut parts = channel.split('.');
+
+            if parts.next() != Some("candlestick") {
+                continue;
+            }
+
+            let time_frame = parts.next().unwrap_or_default();
+            Interval::from_websocket_str(time_frame)?;
+        }
+
+        Ok(())
+    }
+
+    /// Split into subscriptions of at most `chunk_size` channels each, with the same book
+    /// options, a `chunk_size` of 0 is treated as 1.
+    #[must_use]
+    pub fn chunks(&self, chunk_size: usize) -> Vec<Self> {
+        self.channels
+            .chunks(chunk_size.max(1))
+            .map(|channels| Self {
+                channels: channels.to_vec(),
+                ..self.clone()
+            })
+            .collect()
+    }
+}
+
+impl FromIterator<Channel> for Subscribe {
+    /// Build a subscription from typed [`Channel`]s.
+    fn from_iter<T: IntoIterator<Item = Channel>>(iter: T) -> Self {
+        Self {
+            channels: iter.into_iter().map(String::from).collect(),
+            ..Self::default()
+        }
+    }
 }
 
 impl Action for Subscribe {
     fn process(&self, tx: &UnboundedSender<Message>, id: u64) -> Result<()> {
         send_params_msg(tx, id, "subscribe", self)
     }
+
+    fn method(&self) -> &'static str {
+        "subscribe"
+    }
+
+    /// Private if any channel is a `user.` channel.
+    fn access(&self) -> Access {
+        if self
+            .channels
+            .iter()
+            .any(|channel| channel.starts_with("user."))
+        {
+            Access::Private
+        } else {
+            Access::Public
+        }
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
+}
+
+/// Subscription to `user.balance`, the balance of every currency whenever it changes.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SubscribeUserBalance;
+
+impl SubscribeUserBalance {
+    /// The channel subscribed to.
+    #[must_use]
+    pub fn channel(&self) -> String {
+        "user.balance".to_owned()
+    }
+}
+
+/// Subscription to `user.order.{instrument_name}`, or `user.order` for the orders of every
+/// instrument when no instrument is given.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SubscribeUserOrders(pub Option<String>);
+
+impl SubscribeUserOrders {
+    /// The channel subscribed to.
+    #[must_use]
+    pub fn channel(&self) -> String {
+        user_channel("user.order", self.0.as_deref())
+    }
+}
+
+/// Subscription to `user.trade.{instrument_name}`, or `user.trade` for the trades of every
+/// instrument when no instrument is given.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SubscribeUserTrades(pub Option<String>);
+
+impl SubscribeUserTrades {
+    /// The channel subscribed to.
+    #[must_use]
+    pub fn channel(&self) -> String {
+        user_channel("user.trade", self.0.as_deref())
+    }
+}
+
+/// `prefix.{instrument_name}`, or `prefix` alone for every instrument.
+fn user_channel(prefix: &str, instrument_name: Option<&str>) -> String {
+    instrument_name.map_or_else(
+        || prefix.to_owned(),
+        |instrument_name| format!("{prefix}.{instrument_name}"),
+    )
+}
+
+macro_rules! user_subscription {
+    ($action:ty) => {
+        impl From<$action> for Subscribe {
+            fn from(value: $action) -> Self {
+                Self {
+                    channels: vec![value.channel()],
+                    ..Self::default()
+                }
+            }
+        }
+
+        impl Action for $action {
+            fn process(&self, tx: &UnboundedSender<Message>, id: u64) -> Result<()> {
+                send_params_msg(tx, id, "subscribe", &Subscribe::from(self.clone()))
+            }
+
+            fn method(&self) -> &'static str {
+                "subscribe"
+            }
+
+            fn access(&self) -> Access {
+                Access::Private
+            }
+
+            fn as_any(&self) -> Option<&dyn Any> {
+                Some(self)
+            }
+        }
+    };
+}
+
+user_subscription!(SubscribeUserBalance);
+user_subscription!(SubscribeUserOrders);
+user_subscription!(SubscribeUserTrades);
+
+/// The subscription `action` sends, if it is a [`Subscribe`] or one of the user subscription
+/// actions.
+#[must_use]
+pub fn as_subscribe(action: &ActionKind) -> Option<Subscribe> {
+    match *action {
+        ActionKind::Subscribe(ref subscribe) => Some(subscribe.clone()),
+        ActionKind::SubscribeUserBalance(balance) => Some(Subscribe::from(balance)),
+        ActionKind::SubscribeUserOrders(ref orders) => Some(Subscribe::from(orders.clone())),
+        ActionKind::SubscribeUserTrades(ref trades) => Some(Subscribe::from(trades.clone())),
+        _ => None,
+    }
+}
+
+/// Unsubscribe action.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Unsubscribe {
+    /// A list of channels to unsubscribe from.
+    pub channels: Vec<String>,
+}
+
+impl Action for Unsubscribe {
+    fn process(&self, tx: &UnboundedSender<Message>, id: u64) -> Result<()> {
+        send_params_msg(tx, id, "unsubscribe", self)
+    }
+
+    fn method(&self) -> &'static str {
+        "unsubscribe"
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
 }
 
-/// Auth action.
-#[derive(Debug)]
+/// Auth action, only with the `websocket` feature.
+#[cfg(feature = "websocket")]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Auth {
     /// Your API key.
     pub api_key: String,
@@ -34,25 +266,36 @@ pub struct Auth {
     pub secret_key: String,
 }
 
+#[cfg(feature = "websocket")]
 impl Action for Auth {
     fn process(&self, tx: &UnboundedSender<Message>, id: u64) -> Result<()> {
         auth(tx, id, &self.api_key, &self.secret_key)
     }
+
+    fn method(&self) -> &'static str {
+        "public/auth"
+    }
 }
 
 /// Get instruments action.
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetInstruments;
 
 impl Action for GetInstruments {
     fn process(&self, tx: &UnboundedSender<Message>, id: u64) -> Result<()> {
         send_msg(tx, id, "public/get-instruments")
     }
+
+    fn method(&self) -> &'static str {
+        "public/get-instruments"
+    }
 }
 
 /// Cancel on Disconnect is an optional feature that will cancel all open orders created by the
 /// connection upon loss of connectivity between client or server.
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct SetCancelOnDisconnect {
     /// `ACCOUNT` or `CONNECTION`.
     pub scope: String,
@@ -62,14 +305,264 @@ impl Action for SetCancelOnDisconnect {
     fn process(&self, tx: &UnboundedSender<Message>, id: u64) -> Result<()> {
         send_params_msg(tx, id, "private/set-cancel-on-disconnect", self)
     }
+
+    fn method(&self) -> &'static str {
+        "private/set-cancel-on-disconnect"
+    }
 }
 
 /// Returns the scope of cancellation for the connection.
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetCancelOnDisconnect;
 
 impl Action for GetCancelOnDisconnect {
     fn process(&self, tx: &UnboundedSender<Message>, id: u64) -> Result<()> {
         send_msg(tx, id, "private/get-cancel-on-disconnect")
     }
+
+    fn method(&self) -> &'static str {
+        "private/get-cancel-on-disconnect"
+    }
+}
+
+/// Every built in action, so queued actions can be cloned, serialized, and matched on.
+///
+/// Actions of other types are sent as [`ActionKind::Custom`], which is neither serialized nor
+/// deserialized. [`ActionKind::Auth`] is not either, so the secret key is never written out.
+///
+/// ```
+/// use crypto_com_api::websocket::actions::spot_trading_api::CancelAllOrders;
+/// use crypto_com_api::websocket::actions::ActionKind;
+///
+/// let action = ActionKind::from(CancelAllOrders {
+///     instrument_name: "BTC_USDT".to_owned(),
+/// });
+/// let json = serde_json::to_string(&action).unwrap();
+///
+/// assert_eq!(
+///     json,
+///     r#"{"action":"CancelAllOrders","params":{"instrument_name":"BTC_USDT"}}"#
+/// );
+/// assert!(matches!(
+///     serde_json::from_str(&json).unwrap(),
+///     ActionKind::CancelAllOrders(_)
+/// ));
+/// ```
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "action", content = "params")]
+pub enum ActionKind {
+    /// [`Subscribe`].
+    Subscribe(Subscribe),
+    /// [`SubscribeUserBalance`].
+    SubscribeUserBalance(SubscribeUserBalance),
+    /// [`SubscribeUserOrders`].
+    SubscribeUserOrders(SubscribeUserOrders),
+    /// [`SubscribeUserTrades`].
+    SubscribeUserTrades(SubscribeUserTrades),
+    /// [`Unsubscribe`].
+    Unsubscribe(Unsubscribe),
+    /// [`Auth`], skipped when serializing.
+    #[cfg(feature = "websocket")]
+    #[serde(skip)]
+    Auth(Auth),
+    /// [`GetInstruments`].
+    GetInstruments(GetInstruments),
+    /// [`SetCancelOnDisconnect`].
+    SetCancelOnDisconnect(SetCancelOnDisconnect),
+    /// [`GetCancelOnDisconnect`].
+    GetCancelOnDisconnect(GetCancelOnDisconnect),
+    /// [`spot_trading_api::GetAccountSummary`].
+    #[cfg(feature = "websocket")]
+    GetAccountSummary(spot_trading_api::GetAccountSummary),
+    /// [`spot_trading_api::CreateOrder`].
+    #[cfg(feature = "websocket")]
+    CreateOrder(spot_trading_api::CreateOrder),
+    /// [`spot_trading_api::CancelOrder`].
+    #[cfg(feature = "websocket")]
+    CancelOrder(spot_trading_api::CancelOrder),
+    /// [`spot_trading_api::CreateOrderList`].
+    #[cfg(feature = "websocket")]
+    CreateOrderList(spot_trading_api::CreateOrderList),
+    /// [`spot_trading_api::CancelOrderList`].
+    #[cfg(feature = "websocket")]
+    CancelOrderList(spot_trading_api::CancelOrderList),
+    /// [`spot_trading_api::CancelAllOrders`].
+    #[cfg(feature = "websocket")]
+    CancelAllOrders(spot_trading_api::CancelAllOrders),
+    /// [`spot_trading_api::GetOrderHistory`].
+    #[cfg(feature = "websocket")]
+    GetOrderHistory(spot_trading_api::GetOrderHistory),
+    /// [`spot_trading_api::GetOpenOrders`].
+    #[cfg(feature = "websocket")]
+    GetOpenOrders(spot_trading_api::GetOpenOrders),
+    /// [`spot_trading_api::GetOrderDetail`].
+    #[cfg(feature = "websocket")]
+    GetOrderDetail(spot_trading_api::GetOrderDetail),
+    /// [`spot_trading_api::GetTrades`].
+    #[cfg(feature = "websocket")]
+    GetTrades(spot_trading_api::GetTrades),
+    /// [`wallet_management_api::CreateWithdrawal`].
+    #[cfg(feature = "websocket")]
+    CreateWithdrawal(wallet_management_api::CreateWithdrawal),
+    /// [`wallet_management_api::GetWithdrawalHistory`].
+    #[cfg(feature = "websocket")]
+    GetWithdrawalHistory(wallet_management_api::GetWithdrawalHistory),
+    /// [`wallet_management_api::GetDepositAddress`].
+    #[cfg(feature = "websocket")]
+    GetDepositAddress(wallet_management_api::GetDepositAddress),
+    /// [`wallet_management_api::CreateDepositAddress`].
+    #[cfg(feature = "websocket")]
+    CreateDepositAddress(wallet_management_api::CreateDepositAddress),
+    /// Any other action, skipped when serializing.
+    #[serde(skip)]
+    Custom(Arc<dyn Action>),
+}
+
+impl ActionKind {
+    /// The action as a trait object.
+    #[must_use]
+    pub fn as_action(&self) -> &dyn Action {
+        match *self {
+            Self::Subscribe(ref action) => action,
+            Self::SubscribeUserBalance(ref action) => action,
+            Self::SubscribeUserOrders(ref action) => action,
+            Self::SubscribeUserTrades(ref action) => action,
+            Self::Unsubscribe(ref action) => action,
+            #[cfg(feature = "websocket")]
+            Self::Auth(ref action) => action,
+            Self::GetInstruments(ref action) => action,
+            Self::SetCancelOnDisconnect(ref action) => action,
+            Self::GetCancelOnDisconnect(ref action) => action,
+            #[cfg(feature = "websocket")]
+            Self::GetAccountSummary(ref action) => action,
+            #[cfg(feature = "websocket")]
+            Self::CreateOrder(ref action) => action,
+            #[cfg(feature = "websocket")]
+            Self::CancelOrder(ref action) => action,
+            #[cfg(feature = "websocket")]
+            Self::CreateOrderList(ref action) => action,
+            #[cfg(feature = "websocket")]
+            Self::CancelOrderList(ref action) => action,
+            #[cfg(feature = "websocket")]
+            Self::CancelAllOrders(ref action) => action,
+            #[cfg(feature = "websocket")]
+            Self::GetOrderHistory(ref action) => action,
+            #[cfg(feature = "websocket")]
+            Self::GetOpenOrders(ref action) => action,
+            #[cfg(feature = "websocket")]
+            Self::GetOrderDetail(ref action) => action,
+            #[cfg(feature = "websocket")]
+            Self::GetTrades(ref action) => action,
+            #[cfg(feature = "websocket")]
+            Self::CreateWithdrawal(ref action) => action,
+            #[cfg(feature = "websocket")]
+            Self::GetWithdrawalHistory(ref action) => action,
+            #[cfg(feature = "websocket")]
+            Self::GetDepositAddress(ref action) => action,
+            #[cfg(feature = "websocket")]
+            Self::CreateDepositAddress(ref action) => action,
+            Self::Custom(ref action) => &**action,
+        }
+    }
+
+    /// The client ID of the order or withdrawal the action sends, `client_oid` of
+    /// [`spot_trading_api::CreateOrder`] and [`spot_trading_api::CancelOrder`] and `client_wid` of
+    /// [`wallet_management_api::CreateWithdrawal`], `None` for any other action.
+    #[must_use]
+    pub fn client_id(&self) -> Option<&str> {
+        match *self {
+            #[cfg(feature = "websocket")]
+            Self::CreateOrder(ref action) => action.client_oid.as_deref(),
+            #[cfg(feature = "websocket")]
+            Self::CancelOrder(ref action) => action.client_oid.as_deref(),
+            #[cfg(feature = "websocket")]
+            Self::CreateWithdrawal(ref action) => action.client_wid.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Whether the action can be serialized, [`ActionKind::Auth`] and [`ActionKind::Custom`]
+    /// cannot.
+    #[must_use]
+    pub const fn is_serializable(&self) -> bool {
+        match *self {
+            #[cfg(feature = "websocket")]
+            Self::Auth(_) => false,
+            Self::Custom(_) => false,
+            _ => true,
+        }
+    }
+}
+
+/// Sends the wrapped action, match on the variant to read its parameters.
+impl Action for ActionKind {
+    fn process(&self, tx: &UnboundedSender<Message>, id: u64) -> Result<()> {
+        self.as_action().process(tx, id)
+    }
+
+    fn method(&self) -> &'static str {
+        self.as_action().method()
+    }
+
+    fn access(&self) -> Access {
+        self.as_action().access()
+    }
+
+    fn mutates(&self) -> bool {
+        self.as_action().mutates()
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.as_action().validate()
+    }
+}
+
+macro_rules! action_kind {
+    ($($variant:ident($action:ty)),* $(,)?) => {
+        $(
+            impl From<$action> for ActionKind {
+                fn from(value: $action) -> Self {
+                    Self::$variant(value)
+                }
+            }
+        )*
+    };
+}
+
+action_kind!(
+    Subscribe(Subscribe),
+    SubscribeUserBalance(SubscribeUserBalance),
+    SubscribeUserOrders(SubscribeUserOrders),
+    SubscribeUserTrades(SubscribeUserTrades),
+    Unsubscribe(Unsubscribe),
+    GetInstruments(GetInstruments),
+    SetCancelOnDisconnect(SetCancelOnDisconnect),
+    GetCancelOnDisconnect(GetCancelOnDisconnect),
+    Custom(Arc<dyn Action>),
+);
+
+#[cfg(feature = "websocket")]
+action_kind!(
+    Auth(Auth),
+    GetAccountSummary(spot_trading_api::GetAccountSummary),
+    CreateOrder(spot_trading_api::CreateOrder),
+    CancelOrder(spot_trading_api::CancelOrder),
+    CreateOrderList(spot_trading_api::CreateOrderList),
+    CancelOrderList(spot_trading_api::CancelOrderList),
+    CancelAllOrders(spot_trading_api::CancelAllOrders),
+    GetOrderHistory(spot_trading_api::GetOrderHistory),
+    GetOpenOrders(spot_trading_api::GetOpenOrders),
+    GetOrderDetail(spot_trading_api::GetOrderDetail),
+    GetTrades(spot_trading_api::GetTrades),
+    CreateWithdrawal(wallet_management_api::CreateWithdrawal),
+    GetWithdrawalHistory(wallet_management_api::GetWithdrawalHistory),
+    GetDepositAddress(wallet_management_api::GetDepositAddress),
+    CreateDepositAddress(wallet_management_api::CreateDepositAddress),
+);
+
+impl From<Box<dyn Action>> for ActionKind {
+    fn from(value: Box<dyn Action>) -> Self {
+        Self::Custom(Arc::from(value))
+    }
 }
@@ -1,30 +1,50 @@
 //! The interaction systems for the websocket Spot Trading API.
 
-use anyhow::Result;
+use std::any::Any;
+
 use futures_channel::mpsc::UnboundedSender;
-use serde::Serialize;
-use tokio_tungstenite::tungstenite::Message;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use tungstenite::Message;
 
+use crate::error::{OrderError, Result};
+use crate::rest::data::InstrumentRules;
+use crate::types::{
+    ApiVersion, BuyAmount, ExecInst, Notional, PageQuery, Quantity, Side, TimeRange,
+};
 use crate::utils::action::Action;
 use crate::websocket::send_params_msg;
 
 /// Paginated params.
-#[derive(Serialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Paginated {
     /// e.g. ETH_CRO, BTC_USDT. Omnit for 'all'.
     pub instrument_name: Option<String>,
-    /// Start timestamp (milliseconds since the Unix epoch) - defaults to 24 hours ago.
-    pub start_ts: Option<u64>,
-    /// End timestamp (milliseconds since the Unix epoch) - defaults to 'now'.
-    pub end_ts: Option<u64>,
-    /// Page size (Default: 20, max: 200).
-    pub page_size: Option<u64>,
-    /// Page number (0-based).
-    pub page: Option<u64>,
+    /// Defaults to the last 24 hours.
+    #[serde(flatten)]
+    pub range: TimeRange,
+    /// Defaults to the first page of 20.
+    #[serde(flatten)]
+    pub page: PageQuery,
+}
+
+impl Paginated {
+    /// Check the time range and the page size.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`crate::error::ApiError::InvalidTimeRange`] or
+    /// [`crate::error::ApiError::InvalidPageSize`].
+    pub fn validate(&self) -> Result<()> {
+        self.range.validate()?;
+        self.page.validate()
+    }
 }
 
 /// Returns the account balance of a user for a particular token.
-#[derive(Serialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetAccountSummary {
     /// Specific currency, e.g. BTC, CRO. Omit for 'all'.
     pub currency: Option<String>,
@@ -34,6 +54,10 @@ impl Action for GetAccountSummary {
     fn process(&self, tx: &UnboundedSender<Message>, id: u64) -> Result<()> {
         send_params_msg(tx, id, "private/get-account-summary", self)
     }
+
+    fn method(&self) -> &'static str {
+        "private/get-account-summary"
+    }
 }
 
 /// Creates a new BUY or SELL on the Exchange.
@@ -55,31 +79,77 @@ impl Action for GetAccountSummary {
 /// *Helpful information:*
 ///
 /// - `STOP_LIMIT` and `TAKE_PROFIT_LIMIT` will execute a `LIMIT` order when the
-/// `trigger_price` is reached.
+///   `trigger_price` is reached.
 /// - `STOP_LOSS` and `TAKE_PROFIT` will execute a `MARKET` order when the
-/// `trigger_price` is reached.
+///   `trigger_price` is reached.
 ///
 /// *To create trigger orders against market price:*
 ///
 /// - `trigger_price` below market price: `BUY` `STOP_LOSS` and `STOP_LIMIT`,
-/// `SELL` `TAKE_PROFIT` and `TAKE_PROFIT_LIMIT`.
+///   `SELL` `TAKE_PROFIT` and `TAKE_PROFIT_LIMIT`.
 /// - `trigger_price` above market price: `SELL` `STOP_LOSS` and `STOP_LIMIT`,
-/// `BUY` `TAKE_PROFIT` and `TAKE_PROFIT_LIMIT`.
-#[derive(Serialize, Clone, Debug)]
+///   `BUY` `TAKE_PROFIT` and `TAKE_PROFIT_LIMIT`.
+///
+/// The constructors, e.g. [`CreateOrder::market_buy`], only take the amounts the table above
+/// allows for their type and side, as a [`Quantity`] or a [`Notional`].
+///
+/// ```
+/// use crypto_com_api::types::{Notional, Quantity, Side};
+/// use crypto_com_api::websocket::actions::spot_trading_api::CreateOrder;
+///
+/// let limit = CreateOrder::limit("BTC_USDT", Side::Buy, 20_000.0, Quantity(0.01));
+/// let spend = CreateOrder::market_buy("BTC_USDT", Notional(100.0));
+/// let sell = CreateOrder::market_sell("BTC_USDT", Quantity(0.01)).with_client_oid("exit-1");
+///
+/// assert_eq!(spend.notional(), Some(100.0));
+/// assert_eq!(spend.quantity(), None);
+/// ```
+///
+/// `exec_inst` is sent as a string to v2 and as a list to v1, see [`CreateOrder::api_version`].
+///
+/// ```
+/// use crypto_com_api::types::{ApiVersion, ExecInst, Quantity, Side};
+/// use crypto_com_api::websocket::actions::spot_trading_api::CreateOrder;
+///
+/// let order = CreateOrder::limit("BTC_USDT", Side::Buy, 20_000.0, Quantity(0.01))
+///     .with_exec_inst(ExecInst::PostOnly);
+/// assert_eq!(serde_json::to_value(&order).unwrap()["exec_inst"], "POST_ONLY");
+///
+/// let order = order
+///     .with_api_version(ApiVersion::V1)
+///     .with_exec_inst(ExecInst::IsolatedMargin);
+/// assert_eq!(
+///     serde_json::to_value(&order).unwrap()["exec_inst"],
+///     serde_json::json!(["POST_ONLY", "ISOLATED_MARGIN"])
+/// );
+/// ```
+///
+/// Selling a notional does not compile:
+///
+/// ```compile_fail
+/// use crypto_com_api::types::Notional;
+/// use crypto_com_api::websocket::actions::spot_trading_api::CreateOrder;
+///
+/// let sell = CreateOrder::market_sell("BTC_USDT", Notional(100.0));
+/// ```
+#[derive(Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct CreateOrder {
     /// e.g. ETH_CRO, BTC_USTD.
     pub instrument_name: String,
     /// BUY, SELL
     pub side: String,
     /// LIMIT, MARKET, STOP_LOSS, STOP_LIMIT, TAKE_PROFIT, TAKE_PROFIT_LIMIT.
-    #[serde(rename(serialize = "type"))]
+    #[serde(rename = "type")]
     pub order_type: String,
     /// For LIMIT and STOP_LIMIT orders only: Unit price.
     pub price: Option<f64>,
-    /// For LIMIT Orders, MARKET, STOP_LOSS, TAKE_PROFIT orders only: Order Quantity to be sold.
-    pub quantity: Option<f64>,
-    /// For MARKET (BUY), STOP_LOSS (BUY), TAKE_PROFIT (BUY) orders only: Amount to spend.
-    pub notional: Option<f64>,
+    /// The quantity, or for MARKET (BUY), STOP_LOSS (BUY), and TAKE_PROFIT (BUY) orders the
+    /// notional to spend. Only set by the constructors so an order never has both, see
+    /// [`CreateOrder::quantity`] and [`CreateOrder::notional`].
+    #[serde(flatten, deserialize_with = "deserialize_amount")]
+    #[cfg_attr(feature = "schemars", schemars(with = "RawAmount"))]
+    amount: BuyAmount,
     /// Option Client order ID (Maximum 36 characters). If a Client Order ID is not provided, it
     /// will be the nonce in the request. As nonce can be the same among orders, it is recommended
     /// to specify client_oid to prevent order being rejected.
@@ -91,57 +161,550 @@ pub struct CreateOrder {
     /// - IMMEDIATE_OR_CANCEL
     pub time_in_force: Option<String>,
     /// (Limit Orders Only)
-    /// Options are:
-    /// - POST_ONLY
-    /// - Or leave empty
-    pub exec_inst: Option<String>,
+    /// Execution instructions, see [`ExecInst`], v2 takes at most `POST_ONLY`.
+    #[serde(default, deserialize_with = "deserialize_exec_inst")]
+    pub exec_inst: Vec<ExecInst>,
     /// Used with STOP_LOSS, STOP_LIMIT, TAKE_PROFIT, and TAKE_PROFIT_LIMIT orders. Dictates when
     /// order will be triggered.
     pub trigger_price: Option<f64>,
+    /// The API version the order is sent to, which decides how `exec_inst` is sent, not sent
+    /// itself.
+    #[serde(skip)]
+    pub api_version: ApiVersion,
+}
+
+impl Serialize for CreateOrder {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("CreateOrder", 10)?;
+        state.serialize_field("instrument_name", &self.instrument_name)?;
+        state.serialize_field("side", &self.side)?;
+        state.serialize_field("type", &self.order_type)?;
+        state.serialize_field("price", &self.price)?;
+        state.serialize_field("quantity", &self.quantity())?;
+        state.serialize_field("notional", &self.notional())?;
+        state.serialize_field("client_oid", &self.client_oid)?;
+        state.serialize_field("time_in_force", &self.time_in_force)?;
+
+        match (self.api_version, &self.exec_inst[..]) {
+            (_, []) => state.serialize_field("exec_inst", &None::<ExecInst>)?,
+            (ApiVersion::V2, [exec_inst]) => state.serialize_field("exec_inst", exec_inst)?,
+            _ => state.serialize_field("exec_inst", &self.exec_inst)?,
+        }
+
+        state.serialize_field("trigger_price", &self.trigger_price)?;
+        state.end()
+    }
+}
+
+/// `exec_inst` as sent to either version, nothing, one instruction, or a list.
+fn deserialize_exec_inst<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Vec<ExecInst>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(ExecInst),
+        Many(Vec<ExecInst>),
+    }
+
+    Ok(match Option::<OneOrMany>::deserialize(deserializer)? {
+        None => vec![],
+        Some(OneOrMany::One(exec_inst)) => vec![exec_inst],
+        Some(OneOrMany::Many(exec_inst)) => exec_inst,
+    })
+}
+
+/// `quantity` and `notional` as sent, exactly one of them is set.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+struct RawAmount {
+    /// Quantity to buy or sell.
+    quantity: Option<f64>,
+    /// Amount to spend.
+    notional: Option<f64>,
+}
+
+/// The [`BuyAmount`] of an order from its `quantity` and `notional`.
+fn deserialize_amount<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<BuyAmount, D::Error> {
+    match RawAmount::deserialize(deserializer)? {
+        RawAmount {
+            quantity: Some(quantity),
+            notional: None,
+        } => Ok(Quantity(quantity).into()),
+        RawAmount {
+            quantity: None,
+            notional: Some(notional),
+        } => Ok(Notional(notional).into()),
+        _ => Err(serde::de::Error::custom(
+            "expected exactly one of `quantity` and `notional`",
+        )),
+    }
+}
+
+impl CreateOrder {
+    /// An order of `amount` with only the mandatory fields set.
+    fn new(
+        instrument_name: impl Into<String>,
+        side: Side,
+        order_type: &str,
+        amount: BuyAmount,
+    ) -> Self {
+        Self {
+            instrument_name: instrument_name.into(),
+            side: side.as_str().to_owned(),
+            order_type: order_type.to_owned(),
+            price: None,
+            amount,
+            client_oid: None,
+            time_in_force: None,
+            exec_inst: vec![],
+            trigger_price: None,
+            api_version: ApiVersion::default(),
+        }
+    }
+
+    /// A `LIMIT` order of `quantity` at `price`.
+    #[must_use]
+    pub fn limit(
+        instrument_name: impl Into<String>,
+        side: Side,
+        price: f64,
+        quantity: Quantity,
+    ) -> Self {
+        Self {
+            price: Some(price),
+            ..Self::new(instrument_name, side, "LIMIT", quantity.into())
+        }
+    }
+
+    /// A `MARKET` `BUY` of a [`Quantity`] or for a [`Notional`].
+    #[must_use]
+    pub fn market_buy(instrument_name: impl Into<String>, amount: impl Into<BuyAmount>) -> Self {
+        Self::new(instrument_name, Side::Buy, "MARKET", amount.into())
+    }
+
+    /// A `MARKET` `SELL` of `quantity`.
+    #[must_use]
+    pub fn market_sell(instrument_name: impl Into<String>, quantity: Quantity) -> Self {
+        Self::new(instrument_name, Side::Sell, "MARKET", quantity.into())
+    }
+
+    /// A `STOP_LIMIT` order, a `LIMIT` order of `quantity` at `price` once `trigger_price` is
+    /// reached.
+    #[must_use]
+    pub fn stop_limit(
+        instrument_name: impl Into<String>,
+        side: Side,
+        price: f64,
+        quantity: Quantity,
+        trigger_price: f64,
+    ) -> Self {
+        Self {
+            order_type: "STOP_LIMIT".to_owned(),
+            trigger_price: Some(trigger_price),
+            ..Self::limit(instrument_name, side, price, quantity)
+        }
+    }
+
+    /// A `TAKE_PROFIT_LIMIT` order, a `LIMIT` order of `quantity` at `price` once `trigger_price`
+    /// is reached.
+    #[must_use]
+    pub fn take_profit_limit(
+        instrument_name: impl Into<String>,
+        side: Side,
+        price: f64,
+        quantity: Quantity,
+        trigger_price: f64,
+    ) -> Self {
+        Self {
+            order_type: "TAKE_PROFIT_LIMIT".to_owned(),
+            trigger_price: Some(trigger_price),
+            ..Self::limit(instrument_name, side, price, quantity)
+        }
+    }
+
+    /// A `STOP_LOSS` `BUY`, a `MARKET` buy for `notional` once `trigger_price` is reached.
+    #[must_use]
+    pub fn stop_loss_buy(
+        instrument_name: impl Into<String>,
+        notional: Notional,
+        trigger_price: f64,
+    ) -> Self {
+        Self::trigger_buy(instrument_name, "STOP_LOSS", notional, trigger_price)
+    }
+
+    /// A `STOP_LOSS` `SELL`, a `MARKET` sell of `quantity` once `trigger_price` is reached.
+    #[must_use]
+    pub fn stop_loss_sell(
+        instrument_name: impl Into<String>,
+        quantity: Quantity,
+        trigger_price: f64,
+    ) -> Self {
+        Self::trigger_sell(instrument_name, "STOP_LOSS", quantity, trigger_price)
+    }
+
+    /// A `TAKE_PROFIT` `BUY`, a `MARKET` buy for `notional` once `trigger_price` is reached.
+    #[must_use]
+    pub fn take_profit_buy(
+        instrument_name: impl Into<String>,
+        notional: Notional,
+        trigger_price: f64,
+    ) -> Self {
+        Self::trigger_buy(instrument_name, "TAKE_PROFIT", notional, trigger_price)
+    }
+
+    /// A `TAKE_PROFIT` `SELL`, a `MARKET` sell of `quantity` once `trigger_price` is reached.
+    #[must_use]
+    pub fn take_profit_sell(
+        instrument_name: impl Into<String>,
+        quantity: Quantity,
+        trigger_price: f64,
+    ) -> Self {
+        Self::trigger_sell(instrument_name, "TAKE_PROFIT", quantity, trigger_price)
+    }
+
+    /// A `BUY` trigger order of `order_type` for `notional`.
+    fn trigger_buy(
+        instrument_name: impl Into<String>,
+        order_type: &str,
+        notional: Notional,
+        trigger_price: f64,
+    ) -> Self {
+        Self {
+            trigger_price: Some(trigger_price),
+            ..Self::new(instrument_name, Side::Buy, order_type, notional.into())
+        }
+    }
+
+    /// A `SELL` trigger order of `order_type` of `quantity`.
+    fn trigger_sell(
+        instrument_name: impl Into<String>,
+        order_type: &str,
+        quantity: Quantity,
+        trigger_price: f64,
+    ) -> Self {
+        Self {
+            trigger_price: Some(trigger_price),
+            ..Self::new(instrument_name, Side::Sell, order_type, quantity.into())
+        }
+    }
+
+    /// The quantity of the order, or the notional it spends.
+    #[must_use]
+    pub const fn amount(&self) -> BuyAmount {
+        self.amount
+    }
+
+    /// The quantity to buy or sell, `None` for a buy of a notional.
+    #[must_use]
+    pub const fn quantity(&self) -> Option<f64> {
+        self.amount.quantity()
+    }
+
+    /// The amount to spend, `None` for an order of a quantity.
+    #[must_use]
+    pub const fn notional(&self) -> Option<f64> {
+        self.amount.notional()
+    }
+
+    /// Set the client order ID, see [`CreateOrder::client_oid`].
+    #[must_use]
+    pub fn with_client_oid(mut self, client_oid: impl Into<String>) -> Self {
+        self.client_oid = Some(client_oid.into());
+
+        self
+    }
+
+    /// Set the time in force of a limit order, e.g. `IMMEDIATE_OR_CANCEL`.
+    #[must_use]
+    pub fn with_time_in_force(mut self, time_in_force: impl Into<String>) -> Self {
+        self.time_in_force = Some(time_in_force.into());
+
+        self
+    }
+
+    /// Add an execution instruction to a limit order, e.g. [`ExecInst::PostOnly`].
+    #[must_use]
+    pub fn with_exec_inst(mut self, exec_inst: ExecInst) -> Self {
+        if !self.exec_inst.contains(&exec_inst) {
+            self.exec_inst.push(exec_inst);
+        }
+
+        self
+    }
+
+    /// Shape the order for `api_version`, [`ApiVersion::V2`] by default.
+    #[must_use]
+    pub const fn with_api_version(mut self, api_version: ApiVersion) -> Self {
+        self.api_version = api_version;
+
+        self
+    }
+
+    /// Whether the order rests on the book at a price, the only orders that take execution
+    /// instructions.
+    #[must_use]
+    pub fn is_limit(&self) -> bool {
+        matches!(
+            self.order_type.as_str(),
+            "LIMIT" | "STOP_LIMIT" | "TAKE_PROFIT_LIMIT"
+        )
+    }
+
+    /// Check the execution instructions against the order type and the API version.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`OrderError::ExecInstNotAllowed`] if the order is not a limit order, or
+    /// [`OrderError::UnsupportedExecInst`] if the API version does not accept an instruction.
+    pub fn validate_exec_inst(&self) -> Result<(), OrderError> {
+        if self.exec_inst.is_empty() {
+            return Ok(());
+        }
+
+        if !self.is_limit() {
+            return Err(OrderError::ExecInstNotAllowed(self.order_type.clone()));
+        }
+
+        match self
+            .exec_inst
+            .iter()
+            .find(|exec_inst| !exec_inst.supported_by(self.api_version))
+        {
+            Some(&exec_inst) => Err(OrderError::UnsupportedExecInst {
+                exec_inst,
+                version: self.api_version,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Validate the price, trigger price, and quantity against `public/get-instruments` data
+    /// before sending, and the execution instructions, see [`CreateOrder::validate_exec_inst`].
+    ///
+    /// # Errors
+    ///
+    /// Will return [`OrderError`] if the order would be rejected, see
+    /// [`crate::rest::data::InstrumentRule::validate_price`] and
+    /// [`crate::rest::data::InstrumentRule::validate_quantity`].
+    pub fn validate(&self, rules: &InstrumentRules) -> Result<(), OrderError> {
+        self.validate_exec_inst()?;
+
+        let rule = rules.rule(&self.instrument_name)?;
+
+        for price in [self.price, self.trigger_price].into_iter().flatten() {
+            rule.validate_price(price)?;
+        }
+
+        if let Some(quantity) = self.quantity() {
+            rule.validate_quantity(quantity)?;
+        }
+
+        Ok(())
+    }
+
+    /// Round the price and trigger price to the nearest tick and the quantity down to a tick.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`OrderError::UnknownInstrument`] if the instrument is not in the rules.
+    pub fn round(&mut self, rules: &InstrumentRules) -> Result<(), OrderError> {
+        let rule = rules.rule(&self.instrument_name)?;
+
+        self.price = self.price.map(|price| rule.round_price(price));
+        self.trigger_price = self.trigger_price.map(|price| rule.round_price(price));
+        if let BuyAmount::Quantity(Quantity(quantity)) = self.amount {
+            self.amount = Quantity(rule.round_quantity(quantity)).into();
+        }
+
+        Ok(())
+    }
 }
 
 impl Action for CreateOrder {
     fn process(&self, tx: &UnboundedSender<Message>, id: u64) -> Result<()> {
         send_params_msg(tx, id, "private/create-order", self)
     }
+
+    fn method(&self) -> &'static str {
+        "private/create-order"
+    }
+
+    fn validate(&self) -> Result<()> {
+        Ok(self.validate_exec_inst()?)
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
 }
 
 /// Cancels an existing order on the Exchange.
 ///
 /// The `user.order` subscription can be used to check when the order is successfully cancelled.
-#[derive(Serialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct CancelOrder {
     /// e.g. ETH_CRO, BTC_USDT.
     pub instrument_name: String,
     /// Order ID.
     pub order_id: String,
+    /// Client order ID the order was created with, echoed in
+    /// [`crate::websocket::data::CancelOrder`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_oid: Option<String>,
 }
 
 impl Action for CancelOrder {
     fn process(&self, tx: &UnboundedSender<Message>, id: u64) -> Result<()> {
         send_params_msg(tx, id, "private/cancel-order", self)
     }
+
+    fn method(&self) -> &'static str {
+        "private/cancel-order"
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
+}
+
+/// How the orders of a [`CreateOrderList`] depend on each other.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ContingencyType {
+    /// Independent orders created in one request.
+    List,
+    /// One cancels the other, once either order executes the other is canceled.
+    Oco,
 }
 
 /// Create a list of orders on the Exchange.
 ///
-/// `contingency_type` must be LIST, for list of orders creation.
+/// A [`ContingencyType::List`] of independent orders, see [`CreateOrderList::list`], or a
+/// [`ContingencyType::Oco`] pair, see [`Oco`].
 ///
 /// The `user.order` subscription can be used to check when the orders are successfully created.
 ///
 /// Refer to [`CreateOrder`] for more information and how to create trigger orders against market
 /// price.
-#[derive(Serialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct CreateOrderList {
-    /// LIST
-    pub contingency_type: String,
-    /// LIST: 1-10 orders.
+    /// LIST or OCO.
+    pub contingency_type: ContingencyType,
+    /// LIST: 1-10 orders, OCO: the limit order followed by the stop order.
     pub order_list: Vec<CreateOrder>,
 }
 
+impl CreateOrderList {
+    /// A [`ContingencyType::List`] of independent orders.
+    #[must_use]
+    pub fn list(order_list: Vec<CreateOrder>) -> Self {
+        Self {
+            contingency_type: ContingencyType::List,
+            order_list,
+        }
+    }
+}
+
+/// A one cancels the other pair, a `LIMIT` order at `price` and a `STOP_LIMIT` order at
+/// `stop_price` once `trigger_price` is reached, both on the same side and of the same quantity.
+///
+/// Once either executes the exchange cancels the other, follow the pair with
+/// [`crate::oco::OcoTracker`].
+///
+/// ```
+/// use crypto_com_api::types::{Quantity, Side};
+/// use crypto_com_api::websocket::actions::spot_trading_api::{
+///     ContingencyType, CreateOrderList, Oco,
+/// };
+///
+/// // Take profit at 22,000 or stop out below 19,000.
+/// let exit = CreateOrderList::from(Oco {
+///     instrument_name: "BTC_USDT".to_owned(),
+///     side: Side::Sell,
+///     quantity: Quantity(0.01),
+///     price: 22_000.0,
+///     trigger_price: 19_000.0,
+///     stop_price: 18_950.0,
+/// });
+///
+/// assert_eq!(exit.contingency_type, ContingencyType::Oco);
+/// assert_eq!(exit.order_list[1].order_type, "STOP_LIMIT");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Oco {
+    /// e.g. ETH_CRO, BTC_USDT.
+    pub instrument_name: String,
+    /// Side of both orders.
+    pub side: Side,
+    /// Quantity of both orders.
+    pub quantity: Quantity,
+    /// Price of the `LIMIT` order.
+    pub price: f64,
+    /// Price that triggers the `STOP_LIMIT` order.
+    pub trigger_price: f64,
+    /// Price of the `STOP_LIMIT` order once triggered.
+    pub stop_price: f64,
+}
+
+impl Oco {
+    /// The `LIMIT` order of the pair.
+    #[must_use]
+    pub fn limit_order(&self) -> CreateOrder {
+        CreateOrder::limit(
+            self.instrument_name.clone(),
+            self.side,
+            self.price,
+            self.quantity,
+        )
+    }
+
+    /// The `STOP_LIMIT` order of the pair.
+    #[must_use]
+    pub fn stop_order(&self) -> CreateOrder {
+        CreateOrder::stop_limit(
+            self.instrument_name.clone(),
+            self.side,
+            self.stop_price,
+            self.quantity,
+            self.trigger_price,
+        )
+    }
+}
+
+impl From<Oco> for CreateOrderList {
+    fn from(value: Oco) -> Self {
+        Self {
+            contingency_type: ContingencyType::Oco,
+            order_list: vec![value.limit_order(), value.stop_order()],
+        }
+    }
+}
+
 impl Action for CreateOrderList {
     fn process(&self, tx: &UnboundedSender<Message>, id: u64) -> Result<()> {
-        send_params_msg(tx, id, "private/cancel-order", self)
+        send_params_msg(tx, id, "private/create-order-list", self)
+    }
+
+    fn method(&self) -> &'static str {
+        "private/create-order-list"
+    }
+
+    fn validate(&self) -> Result<()> {
+        for order in &self.order_list {
+            order.validate_exec_inst()?;
+        }
+
+        Ok(())
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
     }
 }
 
@@ -152,7 +715,8 @@ impl Action for CreateOrderList {
 ///
 /// The `user.order` subscription can be used to check when each of the orders is successfully
 /// cancelled.
-#[derive(Serialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct CancelOrderList {
     /// For non contingency orders, A list of orders to be cancelled.
     pub order_list: Option<Vec<CancelOrder>>,
@@ -166,13 +730,18 @@ impl Action for CancelOrderList {
     fn process(&self, tx: &UnboundedSender<Message>, id: u64) -> Result<()> {
         send_params_msg(tx, id, "private/cancel-order-list", self)
     }
+
+    fn method(&self) -> &'static str {
+        "private/cancel-order-list"
+    }
 }
 
 /// Cancel all orders on the Exchange.
 ///
 /// The `user.order` subscription can be used to check the order is successfully
 /// cancelled.
-#[derive(Serialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct CancelAllOrders {
     /// e.g. ETH_CRO, BTC_USDT.
     pub instrument_name: String,
@@ -182,6 +751,10 @@ impl Action for CancelAllOrders {
     fn process(&self, tx: &UnboundedSender<Message>, id: u64) -> Result<()> {
         send_params_msg(tx, id, "private/cancel-all-orders", self)
     }
+
+    fn method(&self) -> &'static str {
+        "private/cancel-all-orders"
+    }
 }
 
 /// Gets the order history for a particular instrument.
@@ -192,34 +765,60 @@ impl Action for CancelAllOrders {
 /// Users should use `user.order` to keep track of real-time order updates, and
 /// `private/get-order-history` should primarily be used for recovery; typically when the websocket
 /// is disconnected.
-#[derive(Serialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetOrderHistory(pub Paginated);
 
 impl Action for GetOrderHistory {
     fn process(&self, tx: &UnboundedSender<Message>, id: u64) -> Result<()> {
         send_params_msg(tx, id, "private/get-order-history", self.0.clone())
     }
+
+    fn method(&self) -> &'static str {
+        "private/get-order-history"
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.0.validate()
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
 }
 
 /// Gets all *open* orders for a particular instrument.
-#[derive(Serialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetOpenOrders {
     /// e.g. ETH_CRO, BTC_USDT. Omit for 'all'.
     pub instrument_name: Option<String>,
-    /// Page size (Default: 20, max: 200).
-    pub page_size: Option<u64>,
-    /// Page number (0-based).
-    pub page: Option<u64>,
+    /// Defaults to the first page of 20.
+    #[serde(flatten)]
+    pub page: PageQuery,
 }
 
 impl Action for GetOpenOrders {
     fn process(&self, tx: &UnboundedSender<Message>, id: u64) -> Result<()> {
         send_params_msg(tx, id, "private/get-open-orders", self)
     }
+
+    fn method(&self) -> &'static str {
+        "private/get-open-orders"
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.page.validate()
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
 }
 
 /// Get details on a particular order ID.
-#[derive(Serialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetOrderDetail {
     /// Order ID.
     pub order_id: String,
@@ -229,6 +828,10 @@ impl Action for GetOrderDetail {
     fn process(&self, tx: &UnboundedSender<Message>, id: u64) -> Result<()> {
         send_params_msg(tx, id, "private/get-order-detail", self)
     }
+
+    fn method(&self) -> &'static str {
+        "private/get-order-detail"
+    }
 }
 
 /// Gets all executed trades for a particular instrument.
@@ -238,11 +841,24 @@ impl Action for GetOrderDetail {
 ///
 /// Users should use `user.trade` to keep track of real-time trades, and `private/get-trades`
 /// should primarily be used for recovery; typically when the websocket is disconnected.
-#[derive(Serialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetTrades(pub Paginated);
 
 impl Action for GetTrades {
     fn process(&self, tx: &UnboundedSender<Message>, id: u64) -> Result<()> {
         send_params_msg(tx, id, "private/get-trades", self)
     }
+
+    fn method(&self) -> &'static str {
+        "private/get-trades"
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.0.validate()
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
 }
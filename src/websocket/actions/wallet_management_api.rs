@@ -1,15 +1,20 @@
 //! The interaction systems for the websocket Wallet Management API.
 
-use anyhow::Result;
+use std::any::Any;
+
 use futures_channel::mpsc::UnboundedSender;
-use serde::Serialize;
-use tokio_tungstenite::tungstenite::Message;
+use serde::{Deserialize, Serialize};
+use tungstenite::Message;
 
+use crate::error::{Result, WithdrawalError};
+use crate::rest::data::{CurrencyNetworks, WithdrawalQuote};
+use crate::types::{PageQuery, TimeRange, WithdrawalStatus};
 use crate::utils::action::Action;
 use crate::websocket::send_params_msg;
 
 /// Creates a withdrawal request. Withdrawal setting must be enabled for your API key.
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct CreateWithdrawal {
     /// Optional Client withdrawal ID.
     pub client_wid: Option<String>,
@@ -25,48 +30,87 @@ pub struct CreateWithdrawal {
     pub network_id: Option<String>,
 }
 
+impl CreateWithdrawal {
+    /// Validate against `private/get-currency-networks` data before sending.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`WithdrawalError`] if the withdrawal would be rejected, see
+    /// [`CurrencyNetworks::validate_withdrawal`].
+    pub fn validate(
+        &self,
+        networks: &CurrencyNetworks,
+    ) -> Result<WithdrawalQuote, WithdrawalError> {
+        networks.validate_withdrawal(&self.currency, self.amount, self.network_id.as_deref())
+    }
+}
+
 impl Action for CreateWithdrawal {
     fn process(&self, tx: &UnboundedSender<Message>, id: u64) -> Result<()> {
         send_params_msg(tx, id, "private/create-withdrawal", self)
     }
+
+    fn method(&self) -> &'static str {
+        "private/create-withdrawal"
+    }
 }
 
 /// Get withdrawal and deposit history params.
-#[derive(Serialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct History {
     /// e.g. BTC, CRO.
     pub currency: Option<String>,
-    /// Default is 90 days from current timestamp.
-    pub start_ts: Option<u64>,
-    /// Default is current timestamp.
-    pub end_ts: Option<u64>,
-    /// Page size (Default: 20, Max: 200).
-    pub page_size: Option<u64>,
-    /// Page number(0-based)
-    pub page: Option<u64>,
-    /// 0 - Pending
-    /// 1 - Processing
-    /// 2 - Rejected
-    /// 3 - Payment In-progress
-    /// 4 - Payment Failed
-    /// 5 - Completed
-    /// 6 - Canceled
-    pub status: Option<String>,
+    /// Defaults to the last 90 days.
+    #[serde(flatten)]
+    pub range: TimeRange,
+    /// Defaults to the first page of 20.
+    #[serde(flatten)]
+    pub page: PageQuery,
+    /// Withdrawal status, filters the history when set.
+    pub status: Option<WithdrawalStatus>,
+}
+
+impl History {
+    /// Check the time range and the page size.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`crate::error::ApiError::InvalidTimeRange`] or
+    /// [`crate::error::ApiError::InvalidPageSize`].
+    pub fn validate(&self) -> Result<()> {
+        self.range.validate()?;
+        self.page.validate()
+    }
 }
 
 /// Fetches withdrawal history. Withdrawal setting must be enabled for your API Key. If you do not
 /// see the option when viewing your API Keys, this feature is not yet available for you.
-#[derive(Serialize, Debug)]
-pub struct GetWithdrawalHistory(History);
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct GetWithdrawalHistory(pub History);
 
 impl Action for GetWithdrawalHistory {
     fn process(&self, tx: &UnboundedSender<Message>, id: u64) -> Result<()> {
         send_params_msg(tx, id, "private/get-withdrawal-history", self.0.clone())
     }
+
+    fn method(&self) -> &'static str {
+        "private/get-withdrawal-history"
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.0.validate()
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
 }
 
 /// Get deposit address params.
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct GetDepositAddress {
     /// e.g. BTC, CRO.
     pub currency: String,
@@ -76,4 +120,28 @@ impl Action for GetDepositAddress {
     fn process(&self, tx: &UnboundedSender<Message>, id: u64) -> Result<()> {
         send_params_msg(tx, id, "private/get-deposit-address", self)
     }
+
+    fn method(&self) -> &'static str {
+        "private/get-deposit-address"
+    }
+}
+
+/// Create deposit address params, see [`crate::rest::data::CreateDepositAddressParams`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CreateDepositAddress {
+    /// e.g. BTC, CRO.
+    pub currency: String,
+    /// e.g. ETH, CRO, the default network of the currency when not set.
+    pub network: Option<String>,
+}
+
+impl Action for CreateDepositAddress {
+    fn process(&self, tx: &UnboundedSender<Message>, id: u64) -> Result<()> {
+        send_params_msg(tx, id, "private/create-deposit-address", self)
+    }
+
+    fn method(&self) -> &'static str {
+        "private/create-deposit-address"
+    }
 }
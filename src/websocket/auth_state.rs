@@ -0,0 +1,273 @@
+//! Authorization of the user websocket, with new credentials fetched when it is refused.
+
+use std::fmt;
+use std::sync::Arc;
+
+use anyhow::Result;
+use futures_util::future::BoxFuture;
+use futures_util::Future;
+use serde::Serialize;
+use tokio::sync::watch;
+
+use crate::api_response::ApiResponse;
+use crate::error::ApiError;
+use crate::prelude::{DataReciever, DataSender};
+use crate::utils::action_queue::wait_for_reply;
+use crate::utils::config::Credentials;
+use crate::utils::trace;
+#[cfg(feature = "websocket")]
+use crate::websocket::actions::Auth;
+use crate::websocket::{send_data, WebsocketData};
+
+/// Times the credentials are refreshed after a refused `public/auth` before giving up, unless
+/// [`CredentialRefresh::with_max_refreshes`] is used.
+pub const DEFAULT_MAX_REFRESHES: u32 = 3;
+
+/// A change in the authorization of the user websocket, sent as [`WebsocketData::AuthState`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum AuthState {
+    /// `public/auth` was sent, `attempt` counts from 1.
+    Authenticating {
+        /// Number of the attempt.
+        attempt: u32,
+    },
+    /// crypto.com accepted the credentials.
+    Authenticated,
+    /// crypto.com refused the credentials.
+    Failed {
+        /// Code of the reply, e.g. `40101` for an unknown or revoked key.
+        code: u64,
+        /// Message of the reply.
+        message: Option<String>,
+    },
+    /// New credentials are being fetched with the [`CredentialRefresh`].
+    Refreshing,
+    /// The credentials were refused and there is no refresh, or it failed or ran out of attempts.
+    GaveUp {
+        /// Code of the last refused `public/auth`.
+        code: u64,
+    },
+}
+
+/// An async callback fetching new credentials when `public/auth` is refused, e.g. from a secret
+/// store after the key was rotated or revoked.
+///
+/// Set with [`crate::controller::ControllerBuilder::with_credential_refresh`].
+#[derive(Clone)]
+pub struct CredentialRefresh {
+    /// The callback.
+    refresh: Arc<dyn Fn() -> BoxFuture<'static, Result<Credentials>> + Send + Sync>,
+    /// Most times the callback is called for one [`authenticate`].
+    max_refreshes: u32,
+}
+
+impl CredentialRefresh {
+    /// Fetch new credentials with `refresh`, at most [`DEFAULT_MAX_REFRESHES`] times in a row.
+    pub fn new<F, Fut>(refresh: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Credentials>> + Send + 'static,
+    {
+        Self {
+            refresh: Arc::new(move || Box::pin(refresh())),
+            max_refreshes: DEFAULT_MAX_REFRESHES,
+        }
+    }
+
+    /// Refresh at most `max_refreshes` times in a row before giving up.
+    #[must_use]
+    pub const fn with_max_refreshes(mut self, max_refreshes: u32) -> Self {
+        self.max_refreshes = max_refreshes;
+
+        self
+    }
+
+    /// Most times the credentials are refreshed in a row.
+    #[must_use]
+    pub const fn max_refreshes(&self) -> u32 {
+        self.max_refreshes
+    }
+
+    /// Fetch new credentials.
+    ///
+    /// # Errors
+    ///
+    /// Will return the error of the callback.
+    pub async fn refresh(&self) -> Result<Credentials> {
+        (self.refresh)().await
+    }
+}
+
+impl fmt::Debug for CredentialRefresh {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CredentialRefresh")
+            .field("max_refreshes", &self.max_refreshes)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Whether private actions can be sent on the user websocket, see [`AuthGate`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AuthStatus {
+    /// No `public/auth` was sent, or the last one was refused.
+    #[default]
+    Unauthenticated,
+    /// `public/auth` was sent and is waiting for the reply.
+    Pending,
+    /// crypto.com accepted the last `public/auth`.
+    Authenticated,
+}
+
+/// Holds private actions back until the user websocket is authorized.
+///
+/// [`crate::utils::action_queue::ActionQueue::push`] of the user websocket moves the gate to
+/// [`AuthStatus::Pending`] when `public/auth` is sent and to the outcome once it is answered.
+/// Private actions pushed while it is pending wait for the reply, and are refused with
+/// [`ApiError::NotAuthenticated`] while it is unauthenticated.
+#[derive(Debug)]
+pub struct AuthGate {
+    /// The current status, watched by the actions waiting on it.
+    status: watch::Sender<AuthStatus>,
+}
+
+impl Default for AuthGate {
+    fn default() -> Self {
+        Self {
+            status: watch::channel(AuthStatus::default()).0,
+        }
+    }
+}
+
+impl AuthGate {
+    /// A gate that is unauthenticated.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The current status.
+    #[must_use]
+    pub fn status(&self) -> AuthStatus {
+        *self.status.borrow()
+    }
+
+    /// Move to `status`, waking the actions waiting on a pending authorization.
+    pub fn set(&self, status: AuthStatus) {
+        self.status.send_replace(status);
+    }
+
+    /// Wait until the pending authorization, if any, is answered.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`ApiError::NotAuthenticated`] with `method` unless the user websocket ends up
+    /// authorized.
+    pub async fn wait(&self, method: &str) -> Result<()> {
+        let mut status_rx = self.status.subscribe();
+        let status = status_rx
+            .wait_for(|status| *status != AuthStatus::Pending)
+            .await
+            .map(|status| *status)
+            .unwrap_or_default();
+
+        if status != AuthStatus::Authenticated {
+            anyhow::bail!(ApiError::NotAuthenticated(method.to_owned()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Wait on `data_rx` for the reply to the `public/auth` request `id` and open or close `gate`.
+pub(crate) async fn track_auth(mut data_rx: DataReciever, id: u64, gate: Arc<AuthGate>) {
+    match wait_for_reply(&mut data_rx, id).await {
+        Ok(_) => gate.set(AuthStatus::Authenticated),
+        Err(err) => {
+            trace::warning!("Private actions are refused until authorized again: {err}");
+            gate.set(AuthStatus::Unauthenticated);
+        }
+    }
+}
+
+/// Send [`WebsocketData::AuthState`].
+pub fn send_auth_state(data_tx: &DataSender, state: AuthState) {
+    send_data(
+        data_tx,
+        ApiResponse::<WebsocketData>::default().websocket_data(WebsocketData::AuthState(state)),
+    );
+}
+
+/// Authorize with `credentials` through `send`, which sends the `public/auth` request and waits
+/// for the reply, e.g. [`crate::utils::action_queue::ActionQueue::request`].
+///
+/// When crypto.com refuses the credentials, new ones are fetched with `refresh` and tried until
+/// one is accepted or the refresh runs out of attempts. Every step is sent on `data_tx` as an
+/// [`AuthState`].
+///
+/// Returns the credentials that were accepted.
+///
+/// # Errors
+///
+/// Will return [`ApiError::AuthFail`] with the code of the last reply once giving up, the error of
+/// the refresh if it fails, or the error of `send` if it fails for any other reason than a refused
+/// request.
+#[cfg(feature = "websocket")]
+pub async fn authenticate<F>(
+    mut credentials: Credentials,
+    refresh: Option<&CredentialRefresh>,
+    data_tx: &DataSender,
+    mut send: F,
+) -> Result<Credentials>
+where
+    F: FnMut(Auth) -> BoxFuture<'static, Result<()>>,
+{
+    let mut attempt = 0;
+
+    loop {
+        attempt += 1;
+        send_auth_state(data_tx, AuthState::Authenticating { attempt });
+
+        let auth = Auth {
+            api_key: credentials.api_key.clone(),
+            secret_key: credentials.secret_key.clone(),
+        };
+
+        let err = match send(auth).await {
+            Ok(()) => {
+                send_auth_state(data_tx, AuthState::Authenticated);
+
+                return Ok(credentials);
+            }
+            Err(err) => err,
+        };
+
+        let Some(&ApiError::RequestFailed { code, ref message }) = err.downcast_ref() else {
+            return Err(err);
+        };
+
+        trace::warning!("Authorization was refused with code {code}.");
+        send_auth_state(
+            data_tx,
+            AuthState::Failed {
+                code,
+                message: message.clone(),
+            },
+        );
+
+        let Some(refresh) = refresh.filter(|refresh| attempt <= refresh.max_refreshes) else {
+            send_auth_state(data_tx, AuthState::GaveUp { code });
+            anyhow::bail!(ApiError::AuthFail(code));
+        };
+
+        send_auth_state(data_tx, AuthState::Refreshing);
+
+        credentials = match refresh.refresh().await {
+            Ok(credentials) => credentials,
+            Err(err) => {
+                send_auth_state(data_tx, AuthState::GaveUp { code });
+
+                return Err(err);
+            }
+        };
+    }
+}
@@ -0,0 +1,90 @@
+//! Builder for market subscription channel names.
+
+use std::fmt;
+
+use crate::types::Interval;
+
+/// A market data channel that can be passed to [`crate::websocket::actions::Subscribe`].
+///
+/// ```
+/// use crypto_com_api::types::Interval;
+/// use crypto_com_api::websocket::channel::Channel;
+///
+/// let channel = Channel::Candlestick {
+///     interval: Interval::FiveMinutes,
+///     instrument_name: "BTCUSD-PERP".to_owned(),
+/// };
+///
+/// assert_eq!(channel.to_string(), "candlestick.M5.BTCUSD-PERP");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Channel {
+    /// `book.{instrument_name}` or `book.{instrument_name}.{depth}`.
+    ///
+    /// Subscribe with [`crate::websocket::actions::BookSubscriptionType::SnapshotAndUpdate`] to
+    /// recieve `book.update` deltas after the first snapshot.
+    Book {
+        /// e.g. BTCUSD-PERP.
+        instrument_name: String,
+        /// Number of levels, the exchange default is used when omitted.
+        depth: Option<u64>,
+    },
+    /// `ticker.{instrument_name}`.
+    Ticker(String),
+    /// `ticker`, the tickers of every instrument.
+    AllTickers,
+    /// `trade.{instrument_name}`.
+    Trade(String),
+    /// `candlestick.{time_frame}.{instrument_name}`.
+    Candlestick {
+        /// The candlestick period.
+        interval: Interval,
+        /// e.g. BTCUSD-PERP.
+        instrument_name: String,
+    },
+    /// `otc_book.{instrument_name}`.
+    OtcBook(String),
+    /// `index.{instrument_name}`, e.g. `index.BTCUSD-INDEX`.
+    Index(String),
+    /// `mark.{instrument_name}`.
+    Mark(String),
+    /// `funding.{instrument_name}`, the hourly funding rate of a perpetual.
+    Funding(String),
+    /// `estimatedfunding.{instrument_name}`, the estimated funding rate for the next interval.
+    EstimatedFunding(String),
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Book {
+                ref instrument_name,
+                depth: Some(depth),
+            } => write!(f, "book.{instrument_name}.{depth}"),
+            Self::Book {
+                ref instrument_name,
+                depth: None,
+            } => write!(f, "book.{instrument_name}"),
+            Self::Ticker(ref instrument_name) => write!(f, "ticker.{instrument_name}"),
+            Self::AllTickers => f.write_str("ticker"),
+            Self::Trade(ref instrument_name) => write!(f, "trade.{instrument_name}"),
+            Self::Candlestick {
+                interval,
+                ref instrument_name,
+            } => write!(f, "candlestick.{interval}.{instrument_name}"),
+            Self::OtcBook(ref instrument_name) => write!(f, "otc_book.{instrument_name}"),
+            Self::Index(ref instrument_name) => write!(f, "index.{instrument_name}"),
+            Self::Mark(ref instrument_name) => write!(f, "mark.{instrument_name}"),
+            Self::Funding(ref instrument_name) => write!(f, "funding.{instrument_name}"),
+            Self::EstimatedFunding(ref instrument_name) => {
+                write!(f, "estimatedfunding.{instrument_name}")
+            }
+        }
+    }
+}
+
+impl From<Channel> for String {
+    fn from(value: Channel) -> Self {
+        value.to_string()
+    }
+}
@@ -0,0 +1,151 @@
+//! Health of the websocket connections.
+
+use serde::Serialize;
+use tungstenite::protocol::CloseFrame;
+
+use crate::api_response::ApiResponse;
+use crate::error::ApiError;
+use crate::prelude::DataSender;
+use crate::utils::in_flight::Api;
+use crate::websocket::{send_data, WebsocketData};
+
+/// A change in the state of a websocket connection, sent as [`WebsocketData::ConnectionState`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum ConnectionState {
+    /// The websocket handshake completed.
+    Connected(Api),
+    /// crypto.com sent a close frame, a [`ConnectionState::Disconnected`] follows.
+    Closed {
+        /// The websocket that is closing.
+        api: Api,
+        /// [Close code](https://www.rfc-editor.org/rfc/rfc6455#section-7.4), `None` if the frame
+        /// had no body.
+        code: Option<u16>,
+        /// Reason given with the close code.
+        reason: String,
+    },
+    /// The stream task stopped reading from the websocket.
+    Disconnected {
+        /// The websocket that was lost.
+        api: Api,
+        /// The error the stream task ended with, `None` if the websocket closed cleanly.
+        error: Option<String>,
+        /// What kind of error the stream task ended with, `None` if the websocket closed cleanly.
+        kind: Option<DisconnectKind>,
+    },
+    /// A reconnect was started with [`crate::controller::Controller::reconnect_market`] or
+    /// [`crate::controller::Controller::reconnect_user`].
+    Reconnecting(Api),
+    /// The reconnect completed and unanswered requests are being replayed.
+    Reconnected(Api),
+    /// New actions are sent on a second websocket signed with new keys, see
+    /// [`crate::controller::Controller::rotate_credentials`]. The old websocket closes once
+    /// drained, the [`ConnectionState::Disconnected`] that follows does not affect the new one.
+    Rotated(Api),
+    /// The reconnect failed, the websocket is still disconnected.
+    ReconnectFailed {
+        /// The websocket that could not be reconnected.
+        api: Api,
+        /// Why the reconnect failed.
+        error: String,
+        /// What kind of error the reconnect failed with.
+        kind: DisconnectKind,
+    },
+}
+
+impl ConnectionState {
+    /// The websocket the state is for.
+    #[must_use]
+    pub const fn api(&self) -> Api {
+        match *self {
+            Self::Connected(api)
+            | Self::Reconnecting(api)
+            | Self::Reconnected(api)
+            | Self::Rotated(api)
+            | Self::Closed { api, .. }
+            | Self::Disconnected { api, .. }
+            | Self::ReconnectFailed { api, .. } => api,
+        }
+    }
+
+    /// Whether the websocket can be used after this state.
+    #[must_use]
+    pub const fn is_connected(&self) -> bool {
+        matches!(
+            *self,
+            Self::Connected(_) | Self::Reconnected(_) | Self::Rotated(_)
+        )
+    }
+
+    /// The state for a stream task on `api` that ended with `res`.
+    #[must_use]
+    pub fn disconnected(api: Api, res: &anyhow::Result<()>) -> Self {
+        Self::Disconnected {
+            api,
+            error: res.as_ref().err().map(|err| format!("{err:#}")),
+            kind: res.as_ref().err().map(DisconnectKind::of),
+        }
+    }
+
+    /// The state for a close frame recieved on `api`.
+    #[must_use]
+    pub fn closed(api: Api, frame: Option<&CloseFrame<'_>>) -> Self {
+        Self::Closed {
+            api,
+            code: frame.map(|frame| u16::from(frame.code)),
+            reason: frame
+                .map(|frame| frame.reason.to_string())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// What kind of error a websocket was lost with, from the [`ApiError`] it ended with.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum DisconnectKind {
+    /// See [`ApiError::ConnectionClosed`].
+    Closed {
+        /// [Close code](https://www.rfc-editor.org/rfc/rfc6455#section-7.4), `None` if there was
+        /// no close frame or it had no body.
+        code: Option<u16>,
+        /// Reason given with the close code.
+        reason: String,
+    },
+    /// See [`ApiError::Io`].
+    Io,
+    /// See [`ApiError::Protocol`].
+    Protocol,
+    /// See [`ApiError::Capacity`].
+    Capacity,
+    /// See [`ApiError::Tls`].
+    Tls,
+    /// Any other error, e.g. from processing a message.
+    Other,
+}
+
+impl DisconnectKind {
+    /// The kind of `err`.
+    #[must_use]
+    pub fn of(err: &anyhow::Error) -> Self {
+        match err.downcast_ref::<ApiError>() {
+            Some(&ApiError::ConnectionClosed { code, ref reason }) => Self::Closed {
+                code,
+                reason: reason.clone(),
+            },
+            Some(&ApiError::Io(_)) => Self::Io,
+            Some(&ApiError::Protocol(_)) => Self::Protocol,
+            Some(&ApiError::Capacity(_)) => Self::Capacity,
+            Some(&ApiError::Tls(_)) => Self::Tls,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Broadcast `state` to every subscriber.
+pub fn send_state(data_tx: &DataSender, state: ConnectionState) {
+    send_data(
+        data_tx,
+        ApiResponse::<WebsocketData>::default()
+            .websocket_data(WebsocketData::ConnectionState(state)),
+    );
+}
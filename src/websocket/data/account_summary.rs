@@ -1,9 +1,13 @@
 //! Data from [private/get-account-summary](https://exchange-docs.crypto.com/spot/index.html#private-get-account-summary)
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use crate::websocket::data::ExtraFields;
 
 /// Account data.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct Account {
     /// Total balance.
     pub balance: f64,
@@ -15,11 +19,18 @@ pub struct Account {
     pub stake: f64,
     /// e.g. CRO.
     pub currency: String,
+    /// Fields this crate does not know yet, always empty with the `strict_schema` feature.
+    #[cfg_attr(not(feature = "strict_schema"), serde(flatten))]
+    #[cfg_attr(feature = "strict_schema", serde(skip))]
+    pub extra: ExtraFields,
 }
 
 /// Account summary.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct AccountSummary {
     /// An array of accounts.
+    #[serde(default)]
     pub accounts: Vec<Account>,
 }
@@ -1,8 +1,14 @@
 //! Data from [book.{instrument_name}](https://exchange-docs.crypto.com/spot/index.html#book-instrument_name)
 
-use serde::Deserialize;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
 
 use crate::prelude::ApiError;
+use crate::types::{parse_levels, EpochMs, Time};
+use crate::utils::clock::EventTime;
+use crate::utils::get_epoch_ms;
+use crate::utils::intern::intern;
 
 /// The raw book data response.
 ///
@@ -12,11 +18,14 @@ use crate::prelude::ApiError;
 ///     Number of standing orders in the level,
 /// )
 #[derive(Deserialize, Debug)]
-pub struct RawBook {
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct RawBook<'a> {
     /// Array of level.
-    pub bids: Vec<(String, String, String)>,
+    #[serde(borrow, default)]
+    pub bids: Vec<(&'a str, &'a str, &'a str)>,
     /// Array of level.
-    pub asks: Vec<(String, String, String)>,
+    #[serde(borrow, default)]
+    pub asks: Vec<(&'a str, &'a str, &'a str)>,
     /// Epoch millis of last book update.
     pub tt: u64,
     /// Epoch millis of message publish.
@@ -27,98 +36,79 @@ pub struct RawBook {
     pub cs: i64,
 }
 
-/// The processed data response.
-///
-/// Level: (
-///     Price of the level,
-///     Total size of the level,
-///     Number of standing orders in the level,
-/// )
-#[derive(Debug)]
-pub struct Book {
-    /// Array of level.
-    pub bids: Vec<(f64, f64, u64)>,
-    /// Array of level.
-    pub asks: Vec<(f64, f64, u64)>,
-    /// Epoch millis of last book update.
-    pub tt: u64,
-    /// Epoch millis of message publish.
-    pub t: u64,
-    /// Update sequence.
-    pub u: u64,
-    /// Internal use only.
-    pub cs: i64,
-}
+/// The processed data response, shared with the REST API.
+pub use crate::types::Book;
 
-impl TryFrom<&RawBook> for Book {
+impl TryFrom<&RawBook<'_>> for Book {
     type Error = ApiError;
 
-    fn try_from(value: &RawBook) -> Result<Self, Self::Error> {
-        let mut bids = vec![];
-
-        for bid in &value.bids {
-            bids.push((
-                bid.0.parse::<f64>()?,
-                bid.1.parse::<f64>()?,
-                bid.2.parse::<u64>()?,
-            ));
-        }
-
-        let mut asks = vec![];
-
-        for ask in &value.asks {
-            asks.push((
-                ask.0.parse::<f64>()?,
-                ask.1.parse::<f64>()?,
-                ask.2.parse::<u64>()?,
-            ));
-        }
-
+    fn try_from(value: &RawBook<'_>) -> Result<Self, Self::Error> {
         Ok(Self {
-            bids,
-            asks,
-            tt: value.tt,
-            t: value.t,
-            u: value.u,
-            cs: value.cs,
+            bids: parse_levels(&value.bids)?,
+            asks: parse_levels(&value.asks)?,
+            t: Some(Time::from_epoch_ms(value.t)),
+            tt: Some(Time::from_epoch_ms(value.tt)),
+            u: Some(value.u),
+            cs: Some(value.cs),
         })
     }
 }
 
 /// The raw book response.
 #[derive(Deserialize, Debug)]
-pub struct RawBookRes {
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct RawBookRes<'a> {
     /// Same as requested instrument_name.
-    pub instrument_name: String,
+    pub instrument_name: &'a str,
     /// Same as requested channel.
-    pub subscription: String,
+    pub subscription: &'a str,
     /// book
-    pub channel: String,
+    pub channel: &'a str,
     /// Default 50.
     pub depth: u64,
     /// [`RawBook`]
-    pub data: Vec<RawBook>,
+    #[serde(borrow, default)]
+    pub data: Vec<RawBook<'a>>,
 }
 
 /// The processed book response.
-#[derive(Debug)]
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct BookRes {
     /// Same as requested instrument_name.
-    pub instrument_name: String,
+    pub instrument_name: Arc<str>,
     /// Same as requested channel.
-    pub subscription: String,
+    pub subscription: Arc<str>,
     /// book
-    pub channel: String,
+    pub channel: Arc<str>,
     /// Default 50.
     pub depth: u64,
     /// [`Book`]
     pub data: Vec<Book>,
+    /// Local time in ms since the UNIX epoch at which the message was recieved.
+    pub received_at: u64,
 }
 
-impl TryFrom<&RawBookRes> for BookRes {
+impl BookRes {
+    /// The times of each book, books without a publish or update time are left out.
+    #[must_use]
+    pub fn event_times(&self) -> Vec<EventTime> {
+        self.data
+            .iter()
+            .filter_map(|book| {
+                let updated = book.tt.map(EpochMs::epoch_ms);
+                let published = book.t.map(EpochMs::epoch_ms).or(updated)?;
+
+                Some(EventTime::new(published, updated, self.received_at))
+            })
+            .collect()
+    }
+}
+
+impl TryFrom<&RawBookRes<'_>> for BookRes {
     type Error = ApiError;
 
-    fn try_from(value: &RawBookRes) -> Result<Self, Self::Error> {
+    fn try_from(value: &RawBookRes<'_>) -> Result<Self, Self::Error> {
         let mut books = vec![];
 
         for raw_book in &value.data {
@@ -126,19 +116,20 @@ impl TryFrom<&RawBookRes> for BookRes {
         }
 
         Ok(Self {
-            channel: value.channel.clone(),
-            subscription: value.subscription.clone(),
+            channel: intern(value.channel),
+            subscription: intern(value.subscription),
             data: books,
-            instrument_name: value.instrument_name.clone(),
+            instrument_name: intern(value.instrument_name),
             depth: value.depth,
+            received_at: get_epoch_ms(),
         })
     }
 }
 
-impl TryFrom<RawBookRes> for BookRes {
+impl TryFrom<RawBookRes<'_>> for BookRes {
     type Error = ApiError;
 
-    fn try_from(value: RawBookRes) -> Result<Self, Self::Error> {
+    fn try_from(value: RawBookRes<'_>) -> Result<Self, Self::Error> {
         let mut books = vec![];
 
         for raw_book in &value.data {
@@ -146,11 +137,181 @@ impl TryFrom<RawBookRes> for BookRes {
         }
 
         Ok(Self {
-            channel: value.channel,
-            subscription: value.subscription,
+            channel: intern(value.channel),
+            subscription: intern(value.subscription),
             data: books,
-            instrument_name: value.instrument_name,
+            instrument_name: intern(value.instrument_name),
+            depth: value.depth,
+            received_at: get_epoch_ms(),
+        })
+    }
+}
+
+/// The raw levels changed by a book update.
+///
+/// A level with a size of 0 has been removed from the book.
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct RawBookLevels<'a> {
+    /// Array of level.
+    #[serde(borrow, default)]
+    pub bids: Vec<(&'a str, &'a str, &'a str)>,
+    /// Array of level.
+    #[serde(borrow, default)]
+    pub asks: Vec<(&'a str, &'a str, &'a str)>,
+}
+
+/// The raw book update data from `book.update`.
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct RawBookUpdate<'a> {
+    /// [`RawBookLevels`]
+    #[serde(borrow)]
+    pub update: RawBookLevels<'a>,
+    /// Epoch millis of last book update.
+    pub tt: u64,
+    /// Epoch millis of message publish.
+    pub t: u64,
+    /// Update sequence.
+    pub u: u64,
+    /// Update sequence of the previous message, a book is out of sync if this is not the last `u`.
+    pub pu: u64,
+    /// Internal use only.
+    pub cs: i64,
+}
+
+/// The processed book update data.
+///
+/// Level: (
+///     Price of the level,
+///     Total size of the level, 0 when the level was removed,
+///     Number of standing orders in the level,
+/// )
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct BookUpdate {
+    /// Array of changed level.
+    pub bids: Vec<(f64, f64, u64)>,
+    /// Array of changed level.
+    pub asks: Vec<(f64, f64, u64)>,
+    /// Epoch millis of last book update.
+    pub tt: Time,
+    /// Epoch millis of message publish.
+    pub t: Time,
+    /// Update sequence.
+    pub u: u64,
+    /// Update sequence of the previous message.
+    pub pu: u64,
+    /// Internal use only.
+    pub cs: i64,
+}
+
+impl TryFrom<&RawBookUpdate<'_>> for BookUpdate {
+    type Error = ApiError;
+
+    fn try_from(value: &RawBookUpdate<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            bids: parse_levels(&value.update.bids)?,
+            asks: parse_levels(&value.update.asks)?,
+            tt: Time::from_epoch_ms(value.tt),
+            t: Time::from_epoch_ms(value.t),
+            u: value.u,
+            pu: value.pu,
+            cs: value.cs,
+        })
+    }
+}
+
+/// The raw book update response.
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct RawBookUpdateRes<'a> {
+    /// Same as requested instrument_name.
+    pub instrument_name: &'a str,
+    /// Same as requested channel.
+    pub subscription: &'a str,
+    /// book.update
+    pub channel: &'a str,
+    /// Default 50.
+    pub depth: u64,
+    /// [`RawBookUpdate`]
+    #[serde(borrow, default)]
+    pub data: Vec<RawBookUpdate<'a>>,
+}
+
+/// The processed book update response.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct BookUpdateRes {
+    /// Same as requested instrument_name.
+    pub instrument_name: Arc<str>,
+    /// Same as requested channel.
+    pub subscription: Arc<str>,
+    /// book.update
+    pub channel: Arc<str>,
+    /// Default 50.
+    pub depth: u64,
+    /// [`BookUpdate`]
+    pub data: Vec<BookUpdate>,
+    /// Local time in ms since the UNIX epoch at which the message was recieved.
+    pub received_at: u64,
+}
+
+impl BookUpdateRes {
+    /// The times of each update.
+    #[must_use]
+    pub fn event_times(&self) -> Vec<EventTime> {
+        self.data
+            .iter()
+            .map(|update| {
+                EventTime::new(
+                    update.t.epoch_ms(),
+                    Some(update.tt.epoch_ms()),
+                    self.received_at,
+                )
+            })
+            .collect()
+    }
+}
+
+impl TryFrom<&RawBookUpdateRes<'_>> for BookUpdateRes {
+    type Error = ApiError;
+
+    fn try_from(value: &RawBookUpdateRes<'_>) -> Result<Self, Self::Error> {
+        let mut updates = vec![];
+
+        for raw_update in &value.data {
+            updates.push(BookUpdate::try_from(raw_update)?);
+        }
+
+        Ok(Self {
+            channel: intern(value.channel),
+            subscription: intern(value.subscription),
+            data: updates,
+            instrument_name: intern(value.instrument_name),
+            depth: value.depth,
+            received_at: get_epoch_ms(),
+        })
+    }
+}
+
+impl TryFrom<RawBookUpdateRes<'_>> for BookUpdateRes {
+    type Error = ApiError;
+
+    fn try_from(value: RawBookUpdateRes<'_>) -> Result<Self, Self::Error> {
+        let mut updates = vec![];
+
+        for raw_update in &value.data {
+            updates.push(BookUpdate::try_from(raw_update)?);
+        }
+
+        Ok(Self {
+            channel: intern(value.channel),
+            subscription: intern(value.subscription),
+            data: updates,
+            instrument_name: intern(value.instrument_name),
             depth: value.depth,
+            received_at: get_epoch_ms(),
         })
     }
 }
@@ -0,0 +1,75 @@
+//! Data from [private/cancel-order](https://exchange-docs.crypto.com/spot/index.html#private-cancel-order)
+
+use serde::Serialize;
+
+#[cfg(feature = "websocket")]
+use crate::websocket::actions::spot_trading_api;
+use crate::websocket::data::OrderItem;
+
+/// The answer to a `private/cancel-order` request, along with the order it was for.
+///
+/// crypto.com only answers with the request ID, the order is taken from the request that was
+/// sent with that ID, kept in [`crate::utils::in_flight::InFlight`] until it is answered. A
+/// successful answer only means the cancel was accepted, the order is cancelled once `user.order`
+/// reports it as `CANCELED`, see [`CancelOrder::confirmed_by`]. The two can arrive in either
+/// order, and an order that filled before the cancel reached it is reported as `FILLED` instead.
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CancelOrder {
+    /// ID the request was sent with.
+    pub id: u64,
+    /// 0 for success.
+    pub code: u64,
+    /// For server or error messages.
+    pub message: Option<String>,
+    /// e.g. ETH_CRO, BTC_USDT, `None` if the request is not known.
+    pub instrument_name: Option<String>,
+    /// Order ID, `None` if the request is not known.
+    pub order_id: Option<String>,
+    /// Client order ID given in the request.
+    pub client_oid: Option<String>,
+}
+
+impl CancelOrder {
+    /// The answer to request `id` with `code`, filled in from `request` if it is known.
+    #[cfg(feature = "websocket")]
+    #[must_use]
+    pub fn new(
+        id: u64,
+        code: u64,
+        message: Option<String>,
+        request: Option<&spot_trading_api::CancelOrder>,
+    ) -> Self {
+        Self {
+            id,
+            code,
+            message,
+            instrument_name: request.map(|request| request.instrument_name.clone()),
+            order_id: request.map(|request| request.order_id.clone()),
+            client_oid: request.and_then(|request| request.client_oid.clone()),
+        }
+    }
+
+    /// Whether the cancel was accepted.
+    #[must_use]
+    pub const fn is_success(&self) -> bool {
+        self.code == 0
+    }
+
+    /// Whether the `user.order` update `order` reports the order of this cancel as `CANCELED`.
+    ///
+    /// Orders are matched on the order ID, or on the client order ID when the order ID is not
+    /// known. Always `false` if the request is not known.
+    #[must_use]
+    pub fn confirmed_by(&self, order: &OrderItem) -> bool {
+        if order.status != "CANCELED" {
+            return false;
+        }
+
+        match (self.order_id.as_deref(), self.client_oid.as_deref()) {
+            (Some(order_id), _) if !order_id.is_empty() => order.order_id == order_id,
+            (_, Some(client_oid)) => order.client_oid == client_oid,
+            _ => false,
+        }
+    }
+}
@@ -1,9 +1,11 @@
 //! Data from [private/cancel-order-list](https://exchange-docs.crypto.com/spot/index.html#private-cancel-order-list)
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// Cancel order list item.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct CancelOrderListItem {
     /// The index of corresponding order request (Start from 0).
     pub index: u64,
@@ -14,8 +16,11 @@ pub struct CancelOrderListItem {
 }
 
 /// Cancel order list.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct CancelOrderList {
     /// List of order cancellation result.
+    #[serde(default)]
     pub result_list: Vec<CancelOrderListItem>,
 }
@@ -1,54 +1,45 @@
 //! Data from [candlestick.{time_frame}.{instrument_name}](https://exchange-docs.crypto.com/spot/index.html#candlestick-time_frame-instrument_name)
 
-use serde::Deserialize;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
 
 use crate::prelude::ApiError;
+use crate::types::{EpochMs, Time};
+use crate::utils::clock::EventTime;
+use crate::utils::get_epoch_ms;
+use crate::utils::intern::intern;
 
 /// The raw Candlestick data response.
 #[derive(Deserialize, Debug)]
-pub struct RawCandlestick {
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct RawCandlestick<'a> {
     /// Start time of candlestick (Unix timestamp).
     pub t: u64,
     /// Update time of candlestick (Unix timestamp).
     pub ut: u64,
     /// Open.
-    pub o: String,
+    pub o: &'a str,
     /// High.
-    pub h: String,
+    pub h: &'a str,
     /// Low.
-    pub l: String,
+    pub l: &'a str,
     /// Close.
-    pub c: String,
+    pub c: &'a str,
     /// Volume.
-    pub v: String,
+    pub v: &'a str,
 }
 
-/// The processed Candlestick data response.
-#[derive(Debug)]
-pub struct Candlestick {
-    /// Start time of candlestick (Unix timestamp).
-    pub t: u64,
-    /// Update time of candlestick (Unix timestamp).
-    pub ut: u64,
-    /// Open.
-    pub o: f64,
-    /// High.
-    pub h: f64,
-    /// Low.
-    pub l: f64,
-    /// Close.
-    pub c: f64,
-    /// Volume.
-    pub v: f64,
-}
+/// The processed Candlestick data response, shared with the REST API.
+pub use crate::types::Candlestick;
 
-impl TryFrom<&RawCandlestick> for Candlestick {
+impl TryFrom<&RawCandlestick<'_>> for Candlestick {
     type Error = ApiError;
 
-    fn try_from(value: &RawCandlestick) -> Result<Self, Self::Error> {
+    fn try_from(value: &RawCandlestick<'_>) -> Result<Self, Self::Error> {
         Ok(Self {
-            t: value.t,
-            ut: value.ut,
+            t: Time::from_epoch_ms(value.t),
+            ut: Some(value.ut),
             o: value.o.parse::<f64>()?,
             h: value.h.parse::<f64>()?,
             l: value.l.parse::<f64>()?,
@@ -60,38 +51,58 @@ impl TryFrom<&RawCandlestick> for Candlestick {
 
 /// The raw Candlestick response.
 #[derive(Deserialize, Debug)]
-pub struct RawCandlestickRes {
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct RawCandlestickRes<'a> {
     /// e.g. BTCUSD-PERP
-    pub instrument_name: String,
+    pub instrument_name: &'a str,
     /// candlestick.{time_frame}.{instrument_name}
-    pub subscription: String,
+    pub subscription: &'a str,
     /// The period (e.g. M5)
-    pub interval: String,
+    pub interval: &'a str,
     /// Always candlestick.
-    pub channel: String,
+    pub channel: &'a str,
     /// [`RawCandlestick`]
-    pub data: Vec<RawCandlestick>,
+    #[serde(borrow, default)]
+    pub data: Vec<RawCandlestick<'a>>,
 }
 
 /// The processed Candlestick response.
-#[derive(Debug)]
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct CandlestickRes {
     /// e.g. BTCUSD-PERP
-    pub instrument_name: String,
+    pub instrument_name: Arc<str>,
     /// candlestick.{time_frame}.{instrument_name}
-    pub subscription: String,
+    pub subscription: Arc<str>,
     /// The period (e.g. M5)
-    pub interval: String,
+    pub interval: Arc<str>,
     /// Always candlestick.
-    pub channel: String,
+    pub channel: Arc<str>,
     /// [`Candlestick`]
     pub data: Vec<Candlestick>,
+    /// Local time in ms since the UNIX epoch at which the message was recieved.
+    pub received_at: u64,
+}
+
+impl CandlestickRes {
+    /// The times of each candlestick, published at its update time.
+    #[must_use]
+    pub fn event_times(&self) -> Vec<EventTime> {
+        self.data
+            .iter()
+            .map(|candlestick| {
+                let published = candlestick.ut.unwrap_or_else(|| candlestick.t.epoch_ms());
+
+                EventTime::new(published, None, self.received_at)
+            })
+            .collect()
+    }
 }
 
-impl TryFrom<&RawCandlestickRes> for CandlestickRes {
+impl TryFrom<&RawCandlestickRes<'_>> for CandlestickRes {
     type Error = ApiError;
 
-    fn try_from(value: &RawCandlestickRes) -> Result<Self, Self::Error> {
+    fn try_from(value: &RawCandlestickRes<'_>) -> Result<Self, Self::Error> {
         let mut candlesticks = vec![];
 
         for candlestick in &value.data {
@@ -99,19 +110,20 @@ impl TryFrom<&RawCandlestickRes> for CandlestickRes {
         }
 
         Ok(Self {
-            instrument_name: value.instrument_name.clone(),
-            subscription: value.subscription.clone(),
-            interval: value.interval.clone(),
-            channel: value.channel.clone(),
+            instrument_name: intern(value.instrument_name),
+            subscription: intern(value.subscription),
+            interval: intern(value.interval),
+            channel: intern(value.channel),
             data: candlesticks,
+            received_at: get_epoch_ms(),
         })
     }
 }
 
-impl TryFrom<RawCandlestickRes> for CandlestickRes {
+impl TryFrom<RawCandlestickRes<'_>> for CandlestickRes {
     type Error = ApiError;
 
-    fn try_from(value: RawCandlestickRes) -> Result<Self, Self::Error> {
+    fn try_from(value: RawCandlestickRes<'_>) -> Result<Self, Self::Error> {
         let mut candlesticks = vec![];
 
         for candlestick in &value.data {
@@ -119,11 +131,12 @@ impl TryFrom<RawCandlestickRes> for CandlestickRes {
         }
 
         Ok(Self {
-            instrument_name: value.instrument_name,
-            subscription: value.subscription,
-            interval: value.interval,
-            channel: value.channel,
+            instrument_name: intern(value.instrument_name),
+            subscription: intern(value.subscription),
+            interval: intern(value.interval),
+            channel: intern(value.channel),
             data: candlesticks,
+            received_at: get_epoch_ms(),
         })
     }
 }
@@ -1,9 +1,11 @@
 //! Data from [private/create-order](https://exchange-docs.crypto.com/spot/index.html#private-create-order)
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// Create order response.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct CreateOrder {
     /// Newly created order ID.
     pub order_id: u64,
@@ -1,13 +1,17 @@
 //! Data from [private/create-order-list](https://exchange-docs.crypto.com/spot/index.html#private-create-order-list)
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use crate::types::RejectReason;
 
 /// Create order list item.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct CreateOrderListItem {
     /// The index of corresponding order request (Start from 0).
     pub index: u64,
-    /// 0 if success.
+    /// 0 if success, see [`CreateOrderListItem::reject_reason`].
     pub code: u64,
     /// For server or error messages.
     pub message: Option<String>,
@@ -17,9 +21,23 @@ pub struct CreateOrderListItem {
     pub client_oid: Option<String>,
 }
 
+impl CreateOrderListItem {
+    /// Why the order was rejected, decoded from `code`, `None` if it was created.
+    #[must_use]
+    pub fn reject_reason(&self) -> Option<RejectReason> {
+        (self.code != 0).then(|| RejectReason::from_code(self.code))
+    }
+}
+
 /// Create order list.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct CreateOrderList {
     /// List of order creation result.
+    #[serde(default)]
     pub result_list: Vec<CreateOrderListItem>,
+    /// Contingency ID of an OCO list, to cancel both orders with
+    /// [`crate::websocket::actions::spot_trading_api::CancelOrderList::contingency_id`].
+    pub list_id: Option<String>,
 }
@@ -1,9 +1,13 @@
 //! Data from [private/create-withdrawal](https://exchange-docs.crypto.com/spot/index.html#private-create-withdrawal)
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{deserialize_time, Time};
 
 /// Create withdrawal data response.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct CreateWithdrawal {
     /// Newly created withdrawal ID.
     pub id: u64,
@@ -18,5 +22,6 @@ pub struct CreateWithdrawal {
     /// Address with Address Tag (if any).
     pub address: Option<String>,
     /// Create time.
-    pub create_time: u64,
+    #[serde(deserialize_with = "deserialize_time")]
+    pub create_time: Time,
 }
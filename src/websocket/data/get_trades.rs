@@ -1,9 +1,14 @@
 //! Data from [private/get-trades](https://exchange-docs.crypto.com/spot/index.html#private-get-trades)
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{deserialize_time, Time};
+use crate::websocket::data::Page;
 
 /// Trade list item.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct TradeListItem {
     /// BUY, SELL.
     pub side: String,
@@ -14,7 +19,8 @@ pub struct TradeListItem {
     /// Trade ID.
     pub trade_id: String,
     /// Trade creation time.
-    pub create_time: u64,
+    #[serde(deserialize_with = "deserialize_time")]
+    pub create_time: Time,
     /// Executed trade price.
     pub traded_price: f64,
     /// Executed trade quantity.
@@ -30,8 +36,25 @@ pub struct TradeListItem {
 }
 
 /// Trade list.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct Trades {
     /// An array of trades.
+    #[serde(default)]
     pub trade_list: Vec<TradeListItem>,
+    /// Total number of items, when sent.
+    #[serde(default)]
+    pub count: Option<u64>,
+    /// The page requested.
+    #[serde(skip)]
+    pub page: Page,
+}
+
+impl Trades {
+    /// Whether this is the last page, see [`Page::is_last`].
+    #[must_use]
+    pub fn is_last_page(&self) -> bool {
+        self.page.is_last(self.trade_list.len(), self.count)
+    }
 }
@@ -1,11 +1,10 @@
 //! All possible websocket data that can be returned from the server.
 //!
-//! `private/cancel-order` does not return data.
-//!
 //! `private/cancel-all-orders` does not return data.
 
 pub mod account_summary;
 pub mod book;
+pub mod cancel_order;
 pub mod cancel_order_list;
 pub mod candlestick;
 pub mod create_order;
@@ -21,12 +20,20 @@ pub mod trade;
 pub mod user_balance;
 pub mod user_order;
 pub mod user_trade;
+pub mod valuation;
 pub mod withdrawal_history;
 
-use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{deserialize_time, PageQuery, RejectReason, Time};
 
 pub use account_summary::*;
 pub use book::*;
+pub use cancel_order::*;
 pub use cancel_order_list::*;
 pub use candlestick::*;
 pub use create_order::*;
@@ -42,14 +49,94 @@ pub use trade::*;
 pub use user_balance::*;
 pub use user_order::*;
 pub use user_trade::*;
+pub use valuation::*;
 pub use withdrawal_history::*;
 
+/// Fields of a response that are not part of its model, kept so data added by the exchange is
+/// not lost.
+pub type ExtraFields = HashMap<String, serde_json::Value>;
+
+/// Whether a `user.*` subscription result is the state when subscribing or a change pushed after.
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum EventKind {
+    /// Sent in reply to the `subscribe` request, carrying the state at the time of subscribing.
+    Snapshot,
+    /// Pushed by crypto.com after the subscription was confirmed.
+    #[default]
+    Update,
+}
+
+impl EventKind {
+    /// The kind of a subscription result with request `id`, pushes are sent with an ID of -1.
+    #[must_use]
+    pub const fn from_id(id: i64) -> Self {
+        if id >= 0 {
+            Self::Snapshot
+        } else {
+            Self::Update
+        }
+    }
+}
+
+/// The page of a paginated request a response answers, taken from the request.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Page {
+    /// Page number (0-based).
+    pub page: u64,
+    /// Page size.
+    pub page_size: u64,
+}
+
+impl Default for Page {
+    fn default() -> Self {
+        Self {
+            page: 0,
+            page_size: Self::DEFAULT_PAGE_SIZE,
+        }
+    }
+}
+
+impl Page {
+    /// Page size used by the exchange when the request has none.
+    pub const DEFAULT_PAGE_SIZE: u64 = PageQuery::DEFAULT_PAGE_SIZE;
+
+    /// The page of a request with `page` and `page_size`, the exchange defaults are used for
+    /// either when omitted.
+    #[must_use]
+    pub fn new(page: Option<u64>, page_size: Option<u64>) -> Self {
+        Self {
+            page: page.unwrap_or_default(),
+            page_size: page_size.unwrap_or(Self::DEFAULT_PAGE_SIZE),
+        }
+    }
+
+    /// Whether this page, holding `len` items out of `count` in total, is the last one.
+    ///
+    /// Without a `count`, a page that is not full is the last one.
+    #[must_use]
+    pub fn is_last(self, len: usize, count: Option<u64>) -> bool {
+        match count {
+            Some(count) => self.page.saturating_add(1).saturating_mul(self.page_size) >= count,
+            None => u64::try_from(len).unwrap_or(u64::MAX) < self.page_size,
+        }
+    }
+}
+
+impl From<PageQuery> for Page {
+    fn from(query: PageQuery) -> Self {
+        Self::new(query.page, query.page_size)
+    }
+}
+
 /// Order item (used in many order sections).
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct OrderItem {
     /// ACTIVE, CANCELED, FILLED, REJECTED or EXPIRED.
     pub status: String,
-    /// Reason code.
+    /// Reason code, see [`OrderItem::reject_reason`].
     pub reason: Option<String>,
     /// BUY, SELL.
     pub side: String,
@@ -62,11 +149,13 @@ pub struct OrderItem {
     /// Client order ID if included in request. (Maximum 36 characters).
     pub client_oid: String,
     /// Order creation time (Unix timestamp).
-    pub create_time: u64,
+    #[serde(deserialize_with = "deserialize_time")]
+    pub create_time: Time,
     /// Order update time (Unix timestamp).
-    pub update_time: u64,
+    #[serde(deserialize_with = "deserialize_time")]
+    pub update_time: Time,
     /// LIMIT, MARKET, STOP_LOSS, STOP_LIMIT, TAKE_PROFIT, TAKE_PROFIT_LIMIT.
-    #[serde(rename(deserialize = "type"))]
+    #[serde(rename = "type")]
     pub order_type: String,
     /// e.g. ETH_CRO, BTC_USDT.
     pub instrument_name: String,
@@ -84,29 +173,151 @@ pub struct OrderItem {
     pub exec_inst: Option<String>,
     /// Used for trigger-related orders.
     pub trigger_price: Option<f64>,
+    /// Fields this crate does not know yet, always empty with the `strict_schema` feature.
+    #[cfg_attr(not(feature = "strict_schema"), serde(flatten))]
+    #[cfg_attr(feature = "strict_schema", serde(skip))]
+    pub extra: ExtraFields,
+}
+
+impl OrderItem {
+    /// Why the order was rejected, decoded from `reason`, `None` without a reason or with `0`.
+    #[must_use]
+    pub fn reject_reason(&self) -> Option<RejectReason> {
+        self.reason
+            .as_deref()
+            .filter(|reason| !reason.is_empty() && *reason != "0")
+            .map(RejectReason::from)
+    }
 }
 
 /// Scope, used in `private/set-cancel-on-disconnect` and
 /// `private/get-cancel-on-disconnect`.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct Scope {
     /// The scope parameter, `ACCOUNT` or `CONNECTION`.
     pub scope: String,
 }
 
 /// Raw response values from the websocket connections.
+///
+/// This only borrows the routing fields of a subscription result, the `data` is parsed separately
+/// into the typed response for the channel.
 #[derive(Deserialize, Debug, Clone)]
-pub struct RawRes {
+pub struct RawRes<'a> {
     /// Channel the response is coming from.
-    pub channel: String,
+    pub channel: &'a str,
     /// Subscription the response is from.
-    pub subscription: String,
+    pub subscription: &'a str,
     /// Interval the response is replying at.
-    pub interval: Option<String>,
-    /// Data from the response.
-    pub data: Option<serde_json::Value>,
+    pub interval: Option<&'a str>,
     /// Instrument requested.
-    pub instrument_name: Option<String>,
+    pub instrument_name: Option<&'a str>,
     /// Echo millis of message publish.
     pub t: Option<u64>,
 }
+
+/// The shapes of a subscription result crypto.com is known to send, tried in order.
+///
+/// The current format has `channel` and `subscription` next to `data`, older formats and some
+/// error pushes only carry one of them. Results with neither are passed through as
+/// [`crate::websocket::WebsocketData::RawSubscription`] instead of failing the stream.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum SubscriptionPayload<'a> {
+    /// `channel` and `subscription`, the current format.
+    #[serde(borrow)]
+    Routed(RawRes<'a>),
+    /// Only `subscription`, e.g. `ticker.BTC_USDT`, the channel is read from it.
+    Subscription {
+        /// Subscription the response is from.
+        subscription: &'a str,
+        /// Instrument requested.
+        instrument_name: Option<&'a str>,
+    },
+    /// Only `channel`, the subscription is the channel and the instrument.
+    Channel {
+        /// Channel the response is coming from.
+        channel: &'a str,
+        /// Instrument requested.
+        instrument_name: Option<&'a str>,
+    },
+    /// A result this crate cannot route.
+    Unknown(IgnoredAny),
+}
+
+impl<'a> SubscriptionPayload<'a> {
+    /// The channel of the result, e.g. `book` or `user.order`, `None` if it cannot be routed.
+    #[must_use]
+    pub fn channel(&self) -> Option<&'a str> {
+        match *self {
+            Self::Routed(ref res) => Some(res.channel),
+            Self::Subscription { subscription, .. } => Some(subscription_channel(subscription)),
+            Self::Channel { channel, .. } => Some(channel),
+            Self::Unknown(_) => None,
+        }
+    }
+
+    /// The instrument of the result, if it has one.
+    #[must_use]
+    pub const fn instrument_name(&self) -> Option<&'a str> {
+        match *self {
+            Self::Routed(ref res) => res.instrument_name,
+            Self::Subscription {
+                instrument_name, ..
+            }
+            | Self::Channel {
+                instrument_name, ..
+            } => instrument_name,
+            Self::Unknown(_) => None,
+        }
+    }
+
+    /// `res` with the `channel` and `subscription` fields the typed responses expect, filled in
+    /// from the other when one is missing.
+    #[must_use]
+    pub fn complete<'v>(&self, res: &'v serde_json::Value) -> Cow<'v, serde_json::Value> {
+        let (channel, subscription) = match *self {
+            Self::Routed(_) | Self::Unknown(_) => return Cow::Borrowed(res),
+            Self::Subscription { subscription, .. } => (
+                subscription_channel(subscription).to_owned(),
+                subscription.to_owned(),
+            ),
+            Self::Channel {
+                channel,
+                instrument_name,
+            } => (
+                channel.to_owned(),
+                instrument_name.map_or_else(
+                    || channel.to_owned(),
+                    |instrument_name| format!("{channel}.{instrument_name}"),
+                ),
+            ),
+        };
+
+        let mut res = res.clone();
+
+        if let Some(fields) = res.as_object_mut() {
+            fields.insert("channel".to_owned(), channel.into());
+            fields.insert("subscription".to_owned(), subscription.into());
+        }
+
+        Cow::Owned(res)
+    }
+}
+
+/// The channel of `subscription`, e.g. `user.order` of `user.order.ETH_CRO` and `candlestick` of
+/// `candlestick.M5.BTC_USDT`.
+fn subscription_channel(subscription: &str) -> &str {
+    let parts = if subscription.starts_with("user.") || subscription.starts_with("book.update") {
+        2
+    } else {
+        1
+    };
+
+    subscription
+        .match_indices('.')
+        .nth(parts - 1)
+        .map_or(subscription, |(at, _)| &subscription[..at])
+}
@@ -1,14 +1,28 @@
 //! Data from [private/get-open-orders](https://exchange-docs.crypto.com/spot/index.html#private-get-open-orders)
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::websocket::data::OrderItem;
+use crate::websocket::data::{OrderItem, Page};
 
-/// Order history.
-#[derive(Deserialize, Debug)]
+/// Open orders.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct OpenOrders {
     /// Total count of orders.
     pub count: u64,
     /// List of order history items.
+    #[serde(default)]
     pub order_list: Vec<OrderItem>,
+    /// The page requested.
+    #[serde(skip)]
+    pub page: Page,
+}
+
+impl OpenOrders {
+    /// Whether this is the last page, see [`Page::is_last`].
+    #[must_use]
+    pub fn is_last_page(&self) -> bool {
+        self.page.is_last(self.order_list.len(), Some(self.count))
+    }
 }
@@ -1,11 +1,14 @@
 //! Data from [private/get-order-detail](https://exchange-docs.crypto.com/spot/index.html#private-get-order-detail)
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
+use crate::types::{deserialize_time, Time};
 use crate::websocket::data::OrderItem;
 
 /// Trade list item.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct OrderDetailTradeListItem {
     /// BUY, SELL.
     pub side: String,
@@ -16,7 +19,8 @@ pub struct OrderDetailTradeListItem {
     /// Trade ID,
     pub trade_id: String,
     /// Trade creation time.
-    pub create_time: u64,
+    #[serde(deserialize_with = "deserialize_time")]
+    pub create_time: Time,
     /// Executed trade price.
     pub traded_price: f64,
     /// Executed trade quantity.
@@ -28,9 +32,12 @@ pub struct OrderDetailTradeListItem {
 }
 
 /// Order detail.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct OrderDetail {
     /// List of trade list items.
+    #[serde(default)]
     pub trade_list: Vec<OrderDetailTradeListItem>,
     /// Order info.
     pub order_info: OrderItem,
@@ -1,12 +1,29 @@
 //! Data from [private/get-order-history](https://exchange-docs.crypto.com/spot/index.html#private-get-order-history)
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::websocket::data::OrderItem;
+use crate::websocket::data::{OrderItem, Page};
 
 /// Order history.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct OrderHistory {
     /// List of order history items.
+    #[serde(default)]
     pub order_list: Vec<OrderItem>,
+    /// Total number of items, when sent.
+    #[serde(default)]
+    pub count: Option<u64>,
+    /// The page requested.
+    #[serde(skip)]
+    pub page: Page,
+}
+
+impl OrderHistory {
+    /// Whether this is the last page, see [`Page::is_last`].
+    #[must_use]
+    pub fn is_last_page(&self) -> bool {
+        self.page.is_last(self.order_list.len(), self.count)
+    }
 }
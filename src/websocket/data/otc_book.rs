@@ -1,8 +1,12 @@
 //! Data from [otc_book.{instrument_name}](https://exchange-docs.crypto.com/spot/index.html#otc_book-instrument_name)
 
-use serde::Deserialize;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
 
 use crate::prelude::ApiError;
+use crate::types::{EpochMs, Time};
+use crate::utils::intern::intern;
 
 /// The raw OTC Book data response.
 ///
@@ -14,11 +18,14 @@ use crate::prelude::ApiError;
 ///     Unique ID of the level,
 /// )
 #[derive(Deserialize, Debug)]
-pub struct RawOtcBook {
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct RawOtcBook<'a> {
     /// Array of level
-    pub bids: Vec<(String, String, String, u64, u64)>,
+    #[serde(borrow, default)]
+    pub bids: Vec<(&'a str, &'a str, &'a str, u64, u64)>,
     /// Array of level
-    pub asks: Vec<(String, String, String, u64, u64)>,
+    #[serde(borrow, default)]
+    pub asks: Vec<(&'a str, &'a str, &'a str, u64, u64)>,
 }
 
 /// The processed OTC Book data response.
@@ -30,7 +37,8 @@ pub struct RawOtcBook {
 ///     Expiry time of the level (milliseconds since the Unix epoch),
 ///     Unique ID of the level,
 /// )
-#[derive(Debug)]
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct OtcBook {
     /// Array of level
     pub bids: Vec<(f64, u64, u64, u64, u64)>,
@@ -38,10 +46,10 @@ pub struct OtcBook {
     pub asks: Vec<(f64, u64, u64, u64, u64)>,
 }
 
-impl TryFrom<&RawOtcBook> for OtcBook {
+impl TryFrom<&RawOtcBook<'_>> for OtcBook {
     type Error = ApiError;
 
-    fn try_from(value: &RawOtcBook) -> Result<Self, Self::Error> {
+    fn try_from(value: &RawOtcBook<'_>) -> Result<Self, Self::Error> {
         let mut bids = vec![];
 
         for bid in &value.bids {
@@ -72,43 +80,46 @@ impl TryFrom<&RawOtcBook> for OtcBook {
 
 /// The raw OTC Book response.
 #[derive(Deserialize, Debug)]
-pub struct RawOtcBookRes {
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct RawOtcBookRes<'a> {
     /// otc_book
-    pub channel: String,
+    pub channel: &'a str,
     /// otc_book.{instrument_name}
-    pub subscription: String,
+    pub subscription: &'a str,
     /// Same as requested instrument_name.
-    pub instrument_name: String,
+    pub instrument_name: &'a str,
     /// Timestamp of book publish (milliseconds since the Unix epoch).
     pub t: Option<u64>,
     /// [`RawOtcBook`]
-    pub data: Option<Vec<RawOtcBook>>,
+    #[serde(borrow)]
+    pub data: Option<Vec<RawOtcBook<'a>>>,
 }
 
 /// The processed OTC Book response.
-#[derive(Debug)]
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct OtcBookRes {
     /// otc_book
-    pub channel: String,
+    pub channel: Arc<str>,
     /// otc_book.{instrument_name}
-    pub subscription: String,
+    pub subscription: Arc<str>,
     /// Same as requested instrument_name.
-    pub instrument_name: String,
+    pub instrument_name: Arc<str>,
     /// Timestamp of book publish (milliseconds since the Unix epoch).
-    pub t: Option<u64>,
+    pub t: Option<Time>,
     /// [`OtcBook`]
     pub data: Option<Vec<OtcBook>>,
 }
 
-impl TryFrom<&RawOtcBookRes> for OtcBookRes {
+impl TryFrom<&RawOtcBookRes<'_>> for OtcBookRes {
     type Error = ApiError;
 
-    fn try_from(value: &RawOtcBookRes) -> Result<Self, Self::Error> {
+    fn try_from(value: &RawOtcBookRes<'_>) -> Result<Self, Self::Error> {
         Ok(Self {
-            channel: value.channel.clone(),
-            subscription: value.subscription.clone(),
-            instrument_name: value.instrument_name.clone(),
-            t: value.t,
+            channel: intern(value.channel),
+            subscription: intern(value.subscription),
+            instrument_name: intern(value.instrument_name),
+            t: value.t.map(Time::from_epoch_ms),
             data: if let Some(ref data) = value.data {
                 let mut books = vec![];
 
@@ -124,15 +135,15 @@ impl TryFrom<&RawOtcBookRes> for OtcBookRes {
     }
 }
 
-impl TryFrom<RawOtcBookRes> for OtcBookRes {
+impl TryFrom<RawOtcBookRes<'_>> for OtcBookRes {
     type Error = ApiError;
 
-    fn try_from(value: RawOtcBookRes) -> Result<Self, Self::Error> {
+    fn try_from(value: RawOtcBookRes<'_>) -> Result<Self, Self::Error> {
         Ok(Self {
-            channel: value.channel,
-            subscription: value.subscription,
-            instrument_name: value.instrument_name,
-            t: value.t,
+            channel: intern(value.channel),
+            subscription: intern(value.subscription),
+            instrument_name: intern(value.instrument_name),
+            t: value.t.map(Time::from_epoch_ms),
             data: if let Some(ref data) = value.data {
                 let mut books = vec![];
 
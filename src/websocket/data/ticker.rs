@@ -1,155 +1,168 @@
 //! Data from [ticker.{instrument_name}](https://exchange-docs.crypto.com/spot/index.html#ticker-instrument_name)
 
-use serde::Deserialize;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
 
 use crate::prelude::ApiError;
+use crate::types::{EpochMs, Time};
+use crate::utils::clock::EventTime;
+use crate::utils::get_epoch_ms;
+use crate::utils::intern::intern;
 
 /// The raw ticker data response.
 #[derive(Deserialize, Debug)]
-pub struct RawTicker {
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct RawTicker<'a> {
     /// Price of the 24h highest trade.
-    pub h: Option<String>,
+    pub h: Option<&'a str>,
     /// Price of the 24h lowest trade, null if there weren't any trades.
-    pub l: Option<String>,
+    pub l: Option<&'a str>,
     /// The price of the latest trade, null if there weren't any trades.
-    pub a: Option<String>,
+    pub a: Option<&'a str>,
     /// Instrument name.
-    pub i: String,
+    pub i: &'a str,
     /// The total 24h traded volume.
-    pub v: String,
+    pub v: &'a str,
     /// The total 24h traded volume value (in USD).
-    pub vv: String,
-    /// The open interest.
-    pub oi: String,
+    pub vv: &'a str,
+    /// The open interest, not sent for every instrument.
+    pub oi: Option<&'a str>,
     /// 24-hour price change, null if there weren't any trades.
-    pub c: Option<String>,
+    pub c: Option<&'a str>,
     /// The current best bid price, null if there aren't any bids.
-    pub b: Option<String>,
+    pub b: Option<&'a str>,
     /// The current best bid size, null if there aren't any bids.
-    pub bs: Option<String>,
+    pub bs: Option<&'a str>,
     /// The current best ask price, null if there aren't any asks.
-    pub k: Option<String>,
+    pub k: Option<&'a str>,
     /// The current best ask size, null if there aren't any asks.
-    pub ks: Option<String>,
+    pub ks: Option<&'a str>,
     /// Trade timestamp.
     pub t: u64,
 }
 
 /// The raw ticker response.
 #[derive(Deserialize, Debug)]
-pub struct RawTickerRes {
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct RawTickerRes<'a> {
     /// Always ticker.
-    pub channel: String,
+    pub channel: &'a str,
     /// ticker.{instrument_name}
-    pub subscription: String,
+    pub subscription: &'a str,
     /// [`RawTicker`]
-    pub data: Vec<RawTicker>,
-    /// e.g. BTCUSD-PERP.
-    pub instrument_name: String,
+    #[serde(borrow, default)]
+    pub data: Vec<RawTicker<'a>>,
+    /// e.g. BTCUSD-PERP, not sent on the all instruments `ticker` channel.
+    pub instrument_name: Option<&'a str>,
 }
 
-/// The ticker data response.
-#[derive(Debug)]
-pub struct Ticker {
-    /// Price of the 24h highest trade.
-    pub h: Option<f64>,
-    /// Price of the 24h lowest trade, null if there weren't any trades.
-    pub l: Option<f64>,
-    /// The price of the latest trade, null if there weren't any trades.
-    pub a: Option<f64>,
-    /// Instrument name.
-    pub i: String,
-    /// The total 24h traded volume.
-    pub v: f64,
-    /// The total 24h traded volume value (in USD).
-    pub vv: f64,
-    /// The open interest.
-    pub oi: f64,
-    /// 24-hour price change, null if there weren't any trades.
-    pub c: Option<f64>,
-    /// The current best bid price, null if there aren't any bids.
-    pub b: Option<f64>,
-    /// The current best bid size, null if there aren't any bids.
-    pub bs: Option<f64>,
-    /// The current best ask price, null if there aren't any asks.
-    pub k: Option<f64>,
-    /// The current best ask size, null if there aren't any asks.
-    pub ks: Option<f64>,
-    /// Trade timestamp.
-    pub t: u64,
-}
+/// The ticker data response, shared with the REST API.
+pub use crate::types::Ticker;
 
-impl TryFrom<&RawTicker> for Ticker {
+impl TryFrom<&RawTicker<'_>> for Ticker {
     type Error = ApiError;
 
-    fn try_from(value: &RawTicker) -> Result<Self, Self::Error> {
+    fn try_from(value: &RawTicker<'_>) -> Result<Self, Self::Error> {
         Ok(Self {
-            h: if let Some(ref h) = value.h {
+            h: if let Some(h) = value.h {
                 Some(h.parse::<f64>()?)
             } else {
                 None
             },
-            l: if let Some(ref l) = value.l {
+            l: if let Some(l) = value.l {
                 Some(l.parse::<f64>()?)
             } else {
                 None
             },
-            a: if let Some(ref a) = value.a {
+            a: if let Some(a) = value.a {
                 Some(a.parse::<f64>()?)
             } else {
                 None
             },
-            i: value.i.clone(),
+            i: intern(value.i),
             v: value.v.parse::<f64>()?,
             vv: value.vv.parse::<f64>()?,
-            oi: value.oi.parse::<f64>()?,
-            c: if let Some(ref c) = value.c {
+            oi: if let Some(oi) = value.oi {
+                Some(oi.parse::<f64>()?)
+            } else {
+                None
+            },
+            c: if let Some(c) = value.c {
                 Some(c.parse::<f64>()?)
             } else {
                 None
             },
-            b: if let Some(ref b) = value.b {
+            b: if let Some(b) = value.b {
                 Some(b.parse::<f64>()?)
             } else {
                 None
             },
-            bs: if let Some(ref bs) = value.bs {
+            bs: if let Some(bs) = value.bs {
                 Some(bs.parse::<f64>()?)
             } else {
                 None
             },
-            k: if let Some(ref k) = value.k {
+            k: if let Some(k) = value.k {
                 Some(k.parse::<f64>()?)
             } else {
                 None
             },
-            ks: if let Some(ref ks) = value.ks {
+            ks: if let Some(ks) = value.ks {
                 Some(ks.parse::<f64>()?)
             } else {
                 None
             },
-            t: value.t,
+            t: Time::from_epoch_ms(value.t),
         })
     }
 }
 
 /// The ticker response.
-#[derive(Debug)]
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct TickerRes {
     /// Always ticker.
-    pub channel: String,
-    /// ticker.{instrument_name}
-    pub subscription: String,
-    /// [`Ticker`]
+    pub channel: Arc<str>,
+    /// ticker.{instrument_name}, or ticker for all instruments.
+    pub subscription: Arc<str>,
+    /// [`Ticker`], one per instrument on the all instruments channel, see [`Ticker::i`].
     pub data: Vec<Ticker>,
-    /// e.g. BTCUSD-PERP.
-    pub instrument_name: String,
+    /// e.g. BTCUSD-PERP, `None` on the all instruments channel.
+    pub instrument_name: Option<Arc<str>>,
+    /// Local time in ms since the UNIX epoch at which the message was recieved.
+    pub received_at: u64,
+}
+
+impl TickerRes {
+    /// Whether this is from the all instruments `ticker` channel.
+    #[must_use]
+    pub fn is_all_instruments(&self) -> bool {
+        self.instrument_name.is_none()
+    }
+
+    /// The ticker of `instrument_name`.
+    #[must_use]
+    pub fn ticker(&self, instrument_name: &str) -> Option<&Ticker> {
+        self.data
+            .iter()
+            .find(|ticker| &*ticker.i == instrument_name)
+    }
+
+    /// The times of each ticker.
+    #[must_use]
+    pub fn event_times(&self) -> Vec<EventTime> {
+        self.data
+            .iter()
+            .map(|ticker| EventTime::new(ticker.t.epoch_ms(), None, self.received_at))
+            .collect()
+    }
 }
 
-impl TryFrom<&RawTickerRes> for TickerRes {
+impl TryFrom<&RawTickerRes<'_>> for TickerRes {
     type Error = ApiError;
 
-    fn try_from(value: &RawTickerRes) -> Result<Self, Self::Error> {
+    fn try_from(value: &RawTickerRes<'_>) -> Result<Self, Self::Error> {
         let mut tickers = vec![];
 
         for ticker in &value.data {
@@ -157,18 +170,19 @@ impl TryFrom<&RawTickerRes> for TickerRes {
         }
 
         Ok(Self {
-            channel: value.channel.clone(),
-            subscription: value.subscription.clone(),
+            channel: intern(value.channel),
+            subscription: intern(value.subscription),
             data: tickers,
-            instrument_name: value.instrument_name.clone(),
+            instrument_name: value.instrument_name.map(intern),
+            received_at: get_epoch_ms(),
         })
     }
 }
 
-impl TryFrom<RawTickerRes> for TickerRes {
+impl TryFrom<RawTickerRes<'_>> for TickerRes {
     type Error = ApiError;
 
-    fn try_from(value: RawTickerRes) -> Result<Self, Self::Error> {
+    fn try_from(value: RawTickerRes<'_>) -> Result<Self, Self::Error> {
         let mut tickers = vec![];
 
         for ticker in &value.data {
@@ -176,10 +190,11 @@ impl TryFrom<RawTickerRes> for TickerRes {
         }
 
         Ok(Self {
-            channel: value.channel,
-            subscription: value.subscription,
+            channel: intern(value.channel),
+            subscription: intern(value.subscription),
             data: tickers,
-            instrument_name: value.instrument_name,
+            instrument_name: value.instrument_name.map(intern),
+            received_at: get_epoch_ms(),
         })
     }
 }
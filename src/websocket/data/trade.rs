@@ -1,88 +1,98 @@
 //! Data from [trade.{instrument_name}](https://exchange-docs.crypto.com/spot/index.html#trade-instrument_name)
 
-use serde::Deserialize;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
 
 use crate::prelude::ApiError;
+use crate::types::{EpochMs, Time};
+use crate::utils::clock::EventTime;
+use crate::utils::get_epoch_ms;
+use crate::utils::intern::intern;
 
 /// The raw trade response data.
 #[derive(Deserialize, Debug)]
-pub struct RawTrade {
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct RawTrade<'a> {
     /// Side (buy or sell).
-    pub s: String,
+    pub s: &'a str,
     /// Trade price.
-    pub p: String,
+    pub p: &'a str,
     /// Trade quantity.
-    pub q: String,
+    pub q: &'a str,
     /// Trade timestamp.
     pub t: u64,
     /// Trade ID.
-    pub d: String,
+    pub d: &'a str,
     /// Instrument name.
-    pub i: String,
+    pub i: &'a str,
 }
 
-/// The procesed trade response data.
-#[derive(Debug)]
-pub struct Trade {
-    /// Side (buy or sell).
-    pub s: String,
-    /// Trade price.
-    pub p: f64,
-    /// Trade quantity.
-    pub q: f64,
-    /// Trade timestamp.
-    pub t: u64,
-    /// Trade ID.
-    pub d: String,
-    /// Instrument name.
-    pub i: String,
-}
+/// The procesed trade response data, shared with the REST API.
+pub use crate::types::Trade;
 
-impl TryFrom<&RawTrade> for Trade {
+impl TryFrom<&RawTrade<'_>> for Trade {
     type Error = ApiError;
 
-    fn try_from(value: &RawTrade) -> Result<Self, Self::Error> {
+    fn try_from(value: &RawTrade<'_>) -> Result<Self, Self::Error> {
         Ok(Self {
-            s: value.s.clone(),
+            s: value.s.to_owned(),
             p: value.p.parse::<f64>()?,
             q: value.q.parse::<f64>()?,
-            t: value.t,
-            d: value.d.clone(),
-            i: value.i.clone(),
+            t: Time::from_epoch_ms(value.t),
+            d: value.d.to_owned(),
+            i: intern(value.i),
+            data_time: None,
         })
     }
 }
 
 /// The raw trade response.
 #[derive(Deserialize, Debug)]
-pub struct RawTradeRes {
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct RawTradeRes<'a> {
     /// e.g. BTCUSD-PERP.
-    pub instrument_name: String,
+    pub instrument_name: &'a str,
     /// trade.{instrument_name}
-    pub subscription: String,
+    pub subscription: &'a str,
     /// Always trade.
-    pub channel: String,
+    pub channel: &'a str,
     /// [`RawTrade`]
-    pub data: Vec<RawTrade>,
+    #[serde(borrow, default)]
+    pub data: Vec<RawTrade<'a>>,
 }
 
 /// The processed trade response.
-#[derive(Debug)]
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct TradeRes {
     /// e.g. BTCUSD-PERP.
-    pub instrument_name: String,
+    pub instrument_name: Arc<str>,
     /// trade.{instrument_name}
-    pub subscription: String,
+    pub subscription: Arc<str>,
     /// Always trade.
-    pub channel: String,
+    pub channel: Arc<str>,
     /// [`Trade`]
     pub data: Vec<Trade>,
+    /// Local time in ms since the UNIX epoch at which the message was recieved.
+    pub received_at: u64,
+}
+
+impl TradeRes {
+    /// The times of each trade.
+    #[must_use]
+    pub fn event_times(&self) -> Vec<EventTime> {
+        self.data
+            .iter()
+            .map(|trade| EventTime::new(trade.t.epoch_ms(), None, self.received_at))
+            .collect()
+    }
 }
 
-impl TryFrom<&RawTradeRes> for TradeRes {
+impl TryFrom<&RawTradeRes<'_>> for TradeRes {
     type Error = ApiError;
 
-    fn try_from(value: &RawTradeRes) -> Result<Self, Self::Error> {
+    fn try_from(value: &RawTradeRes<'_>) -> Result<Self, Self::Error> {
         let mut trades = vec![];
 
         for trade in &value.data {
@@ -90,18 +100,19 @@ impl TryFrom<&RawTradeRes> for TradeRes {
         }
 
         Ok(Self {
-            instrument_name: value.instrument_name.clone(),
-            subscription: value.subscription.clone(),
-            channel: value.channel.clone(),
+            instrument_name: intern(value.instrument_name),
+            subscription: intern(value.subscription),
+            channel: intern(value.channel),
             data: trades,
+            received_at: get_epoch_ms(),
         })
     }
 }
 
-impl TryFrom<RawTradeRes> for TradeRes {
+impl TryFrom<RawTradeRes<'_>> for TradeRes {
     type Error = ApiError;
 
-    fn try_from(value: RawTradeRes) -> Result<Self, Self::Error> {
+    fn try_from(value: RawTradeRes<'_>) -> Result<Self, Self::Error> {
         let mut trades = vec![];
 
         for trade in &value.data {
@@ -109,10 +120,11 @@ impl TryFrom<RawTradeRes> for TradeRes {
         }
 
         Ok(Self {
-            instrument_name: value.instrument_name,
-            subscription: value.subscription,
-            channel: value.channel,
+            instrument_name: intern(value.instrument_name),
+            subscription: intern(value.subscription),
+            channel: intern(value.channel),
             data: trades,
+            received_at: get_epoch_ms(),
         })
     }
 }
@@ -1,9 +1,13 @@
 //! Data from [user.balance](https://exchange-docs.crypto.com/spot/index.html#user-balance)
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use crate::websocket::data::ExtraFields;
 
 /// Balance of the users currencies.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct UserBalance {
     /// e.g. CRO.
     pub currency: String,
@@ -15,4 +19,8 @@ pub struct UserBalance {
     pub order: f64,
     /// Balance locked for staking (typically only used for CRO).
     pub stake: f64,
+    /// Fields this crate does not know yet, always empty with the `strict_schema` feature.
+    #[cfg_attr(not(feature = "strict_schema"), serde(flatten))]
+    #[cfg_attr(feature = "strict_schema", serde(skip))]
+    pub extra: ExtraFields,
 }
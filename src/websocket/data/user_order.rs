@@ -1,11 +1,13 @@
 //! Data from [user.order.{instrument_name}](https://exchange-docs.crypto.com/spot/index.html#user-order-instrument_name)
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::websocket::data::OrderItem;
+use crate::websocket::data::{EventKind, OrderItem};
 
 /// Processed user order data, this JSON already comes in correctly from crypto.com.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct UserOrderRes {
     /// e.g. ETH_CRO, BTC_USDT.
     pub instrument_name: String,
@@ -14,5 +16,17 @@ pub struct UserOrderRes {
     /// user.order
     pub channel: String,
     /// [`UserOrder`]
+    #[serde(default)]
     pub data: Vec<OrderItem>,
+    /// Whether this is the snapshot sent on subscribing or a later update.
+    #[serde(skip)]
+    pub kind: EventKind,
+}
+
+impl UserOrderRes {
+    /// Whether this is the snapshot sent on subscribing.
+    #[must_use]
+    pub fn is_snapshot(&self) -> bool {
+        self.kind == EventKind::Snapshot
+    }
 }
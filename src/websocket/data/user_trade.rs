@@ -1,11 +1,14 @@
 //! Data from [user.trade.{instrument_name}](https://exchange-docs.crypto.com/spot/index.html#user-trade-instrument_name)
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::prelude::ApiError;
+use crate::types::{deserialize_time, EpochMs, Time};
+use crate::websocket::data::EventKind;
 
 /// Raw user trade response data.
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct RawUserTrade {
     /// BUY, SELL.
     pub side: String,
@@ -27,6 +30,7 @@ pub struct RawUserTrade {
 
 /// Raw user trade response.
 #[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct RawUserTradeRes {
     /// e.g. ETH_CRO, BTC_USDT.
     pub instrument_name: String,
@@ -35,11 +39,13 @@ pub struct RawUserTradeRes {
     /// user.trade
     pub channel: String,
     /// [`RawUserTrade`]
+    #[serde(default)]
     pub data: Vec<RawUserTrade>,
 }
 
 /// Processed version of [`RawUserTrade`].
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct UserTrade {
     /// BUY, SELL.
     pub side: String,
@@ -48,7 +54,8 @@ pub struct UserTrade {
     /// Trade ID.
     pub trade_id: u64,
     /// Trade creation time.
-    pub create_time: u64,
+    #[serde(deserialize_with = "deserialize_time")]
+    pub create_time: Time,
     /// Executed trade price.
     pub traded_price: f64,
     /// Executed trade quantity.
@@ -67,7 +74,7 @@ impl TryFrom<&RawUserTrade> for UserTrade {
             side: value.side.clone(),
             fee: value.fee,
             trade_id: value.trade_id.parse::<u64>()?,
-            create_time: value.create_time,
+            create_time: Time::from_epoch_ms(value.create_time),
             traded_price: value.traded_price,
             traded_quantity: value.traded_quantity,
             fee_currency: value.fee_currency.clone(),
@@ -77,7 +84,8 @@ impl TryFrom<&RawUserTrade> for UserTrade {
 }
 
 /// Processed version of [`RawUserTradeRes`].
-#[derive(Debug)]
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct UserTradeRes {
     /// e.g. ETH_CRO, BTC_USDT.
     pub instrument_name: String,
@@ -87,6 +95,24 @@ pub struct UserTradeRes {
     pub channel: String,
     /// [`UserTrade`]
     pub data: Vec<UserTrade>,
+    /// Whether this is the snapshot sent on subscribing or a later update, see
+    /// [`UserTradeRes::with_kind`].
+    pub kind: EventKind,
+}
+
+impl UserTradeRes {
+    /// Set the [`EventKind`], results are converted as updates.
+    #[must_use]
+    pub const fn with_kind(mut self, kind: EventKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Whether this is the snapshot sent on subscribing.
+    #[must_use]
+    pub fn is_snapshot(&self) -> bool {
+        self.kind == EventKind::Snapshot
+    }
 }
 
 impl TryFrom<&RawUserTradeRes> for UserTradeRes {
@@ -104,6 +130,7 @@ impl TryFrom<&RawUserTradeRes> for UserTradeRes {
             subscription: value.subscription.clone(),
             channel: value.channel.clone(),
             data: trades,
+            kind: EventKind::Update,
         })
     }
 }
@@ -123,6 +150,7 @@ impl TryFrom<RawUserTradeRes> for UserTradeRes {
             subscription: value.subscription,
             channel: value.channel,
             data: trades,
+            kind: EventKind::Update,
         })
     }
 }
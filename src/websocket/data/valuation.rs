@@ -0,0 +1,116 @@
+//! Data from the [index.{instrument_name}](https://exchange-docs.crypto.com/exchange/v1/rest-ws/index.html#index-instrument_name),
+//! [mark.{instrument_name}](https://exchange-docs.crypto.com/exchange/v1/rest-ws/index.html#mark-instrument_name),
+//! [funding.{instrument_name}](https://exchange-docs.crypto.com/exchange/v1/rest-ws/index.html#funding-instrument_name),
+//! and [estimatedfunding.{instrument_name}](https://exchange-docs.crypto.com/exchange/v1/rest-ws/index.html#estimatedfunding-instrument_name)
+//! channels, which share the same shape.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::ApiError;
+use crate::types::{EpochMs, Time};
+use crate::utils::get_epoch_ms;
+use crate::utils::intern::intern;
+
+/// The raw valuation data response.
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct RawValuation<'a> {
+    /// Value.
+    pub v: &'a str,
+    /// Timestamp.
+    pub t: u64,
+}
+
+/// The raw valuation response.
+#[derive(Deserialize, Debug)]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
+pub struct RawValuationRes<'a> {
+    /// e.g. BTCUSD-PERP or BTCUSD-INDEX.
+    pub instrument_name: &'a str,
+    /// e.g. mark.BTCUSD-PERP.
+    pub subscription: &'a str,
+    /// index, mark, funding, or estimatedfunding.
+    pub channel: &'a str,
+    /// [`RawValuation`]
+    #[serde(borrow, default)]
+    pub data: Vec<RawValuation<'a>>,
+}
+
+/// The processed valuation data response.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Valuation {
+    /// Value, the index price, the mark price, or the hourly or estimated funding rate.
+    pub v: f64,
+    /// Timestamp.
+    pub t: Time,
+}
+
+impl TryFrom<&RawValuation<'_>> for Valuation {
+    type Error = ApiError;
+
+    fn try_from(value: &RawValuation<'_>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            v: value.v.parse::<f64>()?,
+            t: Time::from_epoch_ms(value.t),
+        })
+    }
+}
+
+/// The processed valuation response.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ValuationRes {
+    /// e.g. BTCUSD-PERP or BTCUSD-INDEX.
+    pub instrument_name: Arc<str>,
+    /// e.g. mark.BTCUSD-PERP.
+    pub subscription: Arc<str>,
+    /// index, mark, funding, or estimatedfunding.
+    pub channel: Arc<str>,
+    /// [`Valuation`]
+    pub data: Vec<Valuation>,
+    /// Local time in ms since the UNIX epoch at which the message was recieved.
+    pub received_at: u64,
+}
+
+impl TryFrom<&RawValuationRes<'_>> for ValuationRes {
+    type Error = ApiError;
+
+    fn try_from(value: &RawValuationRes<'_>) -> Result<Self, Self::Error> {
+        let mut valuations = vec![];
+
+        for valuation in &value.data {
+            valuations.push(Valuation::try_from(valuation)?);
+        }
+
+        Ok(Self {
+            instrument_name: intern(value.instrument_name),
+            subscription: intern(value.subscription),
+            channel: intern(value.channel),
+            data: valuations,
+            received_at: get_epoch_ms(),
+        })
+    }
+}
+
+impl TryFrom<RawValuationRes<'_>> for ValuationRes {
+    type Error = ApiError;
+
+    fn try_from(value: RawValuationRes<'_>) -> Result<Self, Self::Error> {
+        let mut valuations = vec![];
+
+        for valuation in &value.data {
+            valuations.push(Valuation::try_from(valuation)?);
+        }
+
+        Ok(Self {
+            instrument_name: intern(value.instrument_name),
+            subscription: intern(value.subscription),
+            channel: intern(value.channel),
+            data: valuations,
+            received_at: get_epoch_ms(),
+        })
+    }
+}
@@ -1,9 +1,14 @@
 //! Data from [private/get-withdrawal-history](https://exchange-docs.crypto.com/spot/index.html#private-get-withdrawal-history)
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{deserialize_time, Time, WithdrawalStatus};
+use crate::websocket::data::Page;
 
 /// Withdrawal list item.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct WithdrawalItem {
     /// Newly created withdrawal ID.
     pub id: u64,
@@ -18,15 +23,10 @@ pub struct WithdrawalItem {
     /// Address with Address Tag (if any).
     pub address: Option<String>,
     /// Create time.
-    pub create_time: u64,
-    /// 0 - Pending
-    /// 1 - Processing
-    /// 2 - Rejected
-    /// 3 - Payment In-progress
-    /// 4 - Payment Failed
-    /// 5 - Completed
-    /// 6 - Canceled
-    pub status: String,
+    #[serde(deserialize_with = "deserialize_time")]
+    pub create_time: Time,
+    /// Withdrawal status, see [`WithdrawalStatus`].
+    pub status: WithdrawalStatus,
     /// Transaction hash.
     pub txid: String,
     /// Network for the trasaction - please see `get-currency-networks`. Only available when
@@ -35,8 +35,25 @@ pub struct WithdrawalItem {
 }
 
 /// Withdrawal history.
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "strict_schema", serde(deny_unknown_fields))]
 pub struct WithdrawalHistory {
     /// Withdrawal list.
+    #[serde(default)]
     pub withdrawal_list: Vec<WithdrawalItem>,
+    /// Total number of items, when sent.
+    #[serde(default)]
+    pub count: Option<u64>,
+    /// The page requested.
+    #[serde(skip)]
+    pub page: Page,
+}
+
+impl WithdrawalHistory {
+    /// Whether this is the last page, see [`Page::is_last`].
+    #[must_use]
+    pub fn is_last_page(&self) -> bool {
+        self.page.is_last(self.withdrawal_list.len(), self.count)
+    }
 }
@@ -0,0 +1,315 @@
+//! De-duplication of user events across reconnects.
+//!
+//! After a reconnect and a [`crate::controller::Controller::resync`], the same fill can reach
+//! the data broadcast twice, pushed on `user.trade` and again in a `private/get-trades` recovery
+//! fetch, and subscribing to `user.order` again sends the open orders again. A [`UserDedup`]
+//! remembers the most recent trades by trade ID and order updates by order ID and update time,
+//! and drops pushes that were already delivered.
+
+use std::collections::{HashMap, VecDeque};
+#[cfg(feature = "websocket")]
+use std::sync::{Arc, Mutex, PoisonError};
+
+#[cfg(feature = "websocket")]
+use anyhow::Result;
+#[cfg(feature = "websocket")]
+use tokio::sync::broadcast;
+
+#[cfg(feature = "websocket")]
+use crate::prelude::ApiError;
+use crate::types::EpochMs;
+#[cfg(feature = "websocket")]
+use crate::utils::trace;
+use crate::websocket::data::OrderItem;
+#[cfg(feature = "websocket")]
+use crate::websocket::handler::{HandlerContext, MessageHandler};
+#[cfg(feature = "websocket")]
+use crate::websocket::user_api;
+use crate::websocket::WebsocketData;
+
+/// Events remembered by default.
+pub const DEFAULT_CAPACITY: usize = 10_000;
+
+/// Methods of the user websocket whose data is de-duplicated by a [`UserDedupHandler`].
+pub const DEDUP_METHODS: [&str; 4] = [
+    "subscribe",
+    "private/get-trades",
+    "private/get-order-history",
+    "private/get-open-orders",
+];
+
+/// Most data one message is decoded into, more than any built in handler sends.
+#[cfg(feature = "websocket")]
+const MAX_DATA_PER_MESSAGE: usize = 16;
+
+/// What identifies a user event.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum EventKey {
+    /// A trade, by trade ID.
+    Trade(String),
+    /// An order update, by order ID and update time.
+    Order {
+        /// Order ID.
+        order_id: String,
+        /// Update time in ms since the UNIX epoch.
+        update_time: u64,
+    },
+}
+
+impl EventKey {
+    /// The key of the update of `order`.
+    #[must_use]
+    pub fn order(order: &OrderItem) -> Self {
+        Self::Order {
+            order_id: order.order_id.clone(),
+            update_time: order.update_time.epoch_ms(),
+        }
+    }
+}
+
+/// Drops user trades and order updates that were already delivered.
+///
+/// Remembers the last [`UserDedup::capacity`] events, forgetting the least recently seen first.
+/// `user.trade` and `user.order` pushes are filtered. Replies to `private/get-trades`,
+/// `private/get-order-history`, and `private/get-open-orders` are remembered but passed whole, so
+/// pages stay complete, unless [`UserDedup::filter_replies`] is set. A reply is never dropped,
+/// even when every item in it is a duplicate.
+///
+/// ```
+/// use crypto_com_api::websocket::dedup::{EventKey, UserDedup};
+///
+/// let dedup = UserDedup::new(1_000);
+///
+/// assert_eq!(dedup.capacity(), 1_000);
+/// assert!(!dedup.contains(&EventKey::Trade("1".to_owned())));
+/// ```
+#[derive(Clone, Debug)]
+pub struct UserDedup {
+    /// Most events remembered.
+    capacity: usize,
+    /// Whether items already delivered are removed from replies as well.
+    filter_replies: bool,
+    /// When each remembered event was last seen.
+    seen: HashMap<EventKey, u64>,
+    /// Events by when they were seen, oldest first, with stale entries for events seen again.
+    order: VecDeque<(u64, EventKey)>,
+    /// Stamp of the next event seen.
+    next: u64,
+}
+
+impl Default for UserDedup {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl UserDedup {
+    /// Remember the last `capacity` events, at least one.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            filter_replies: false,
+            seen: HashMap::new(),
+            order: VecDeque::new(),
+            next: 0,
+        }
+    }
+
+    /// Remove the items already delivered from replies as well, their counts are left as sent.
+    #[must_use]
+    pub const fn filter_replies(mut self) -> Self {
+        self.filter_replies = true;
+
+        self
+    }
+
+    /// Most events remembered.
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of events remembered.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    /// Whether no events are remembered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+
+    /// Whether `key` was delivered and is still remembered.
+    #[must_use]
+    pub fn contains(&self, key: &EventKey) -> bool {
+        self.seen.contains_key(key)
+    }
+
+    /// Forget every event.
+    pub fn clear(&mut self) {
+        self.seen.clear();
+        self.order.clear();
+    }
+
+    /// Check `data`, removing the events that were already delivered from it.
+    ///
+    /// Returns whether every event in it was a duplicate and it should not be sent, never for
+    /// replies.
+    pub fn check(&mut self, data: &mut WebsocketData) -> bool {
+        match *data {
+            WebsocketData::UserTrade(ref mut trades) => self.retain(&mut trades.data, |trade| {
+                EventKey::Trade(trade.trade_id.to_string())
+            }),
+            WebsocketData::UserOrder(ref mut orders) => {
+                self.retain(&mut orders.data, EventKey::order)
+            }
+            WebsocketData::GetTrades(ref mut trades) => {
+                self.reply(&mut trades.trade_list, |trade| {
+                    EventKey::Trade(trade.trade_id.clone())
+                });
+
+                false
+            }
+            WebsocketData::GetOrderHistory(ref mut history) => {
+                self.reply(&mut history.order_list, EventKey::order);
+
+                false
+            }
+            WebsocketData::GetOpenOrders(ref mut orders) => {
+                self.reply(&mut orders.order_list, EventKey::order);
+
+                false
+            }
+            _ => false,
+        }
+    }
+
+    /// Remove the items of a reply already delivered if replies are filtered, otherwise only
+    /// remember them.
+    fn reply<T>(&mut self, items: &mut Vec<T>, key: impl Fn(&T) -> EventKey) {
+        if self.filter_replies {
+            self.retain(items, key);
+        } else {
+            for item in items.iter() {
+                self.see(key(item));
+            }
+        }
+    }
+
+    /// Remove the items already delivered, returning whether there were items and all were.
+    fn retain<T>(&mut self, items: &mut Vec<T>, key: impl Fn(&T) -> EventKey) -> bool {
+        let had_items = !items.is_empty();
+
+        items.retain(|item| !self.see(key(item)));
+
+        had_items && items.is_empty()
+    }
+
+    /// Remember `key` as the most recently seen event, returning whether it already was.
+    fn see(&mut self, key: EventKey) -> bool {
+        let stamp = self.next;
+        self.next += 1;
+
+        let seen = self.seen.insert(key.clone(), stamp).is_some();
+        self.order.push_back((stamp, key));
+
+        while self.seen.len() > self.capacity {
+            let Some((stamp, key)) = self.order.pop_front() else {
+                break;
+            };
+
+            if self.seen.get(&key) == Some(&stamp) {
+                self.seen.remove(&key);
+            }
+        }
+
+        // Events seen again leave stale entries behind, drop them before they pile up.
+        if self.order.len() > self.capacity.saturating_mul(2) {
+            let seen = &self.seen;
+            self.order
+                .retain(|(stamp, key)| seen.get(key) == Some(stamp));
+        }
+
+        seen
+    }
+}
+
+/// User websocket handler that runs the data of the built in handlers through a [`UserDedup`]
+/// before sending it, added for each of [`DEDUP_METHODS`] with
+/// [`crate::controller::ControllerBuilder::with_user_dedup`].
+#[cfg(feature = "websocket")]
+#[derive(Clone, Debug, Default)]
+pub struct UserDedupHandler {
+    /// Shared by the methods so a trade recovered by a fetch is known to `user.trade`.
+    dedup: Arc<Mutex<UserDedup>>,
+}
+
+#[cfg(feature = "websocket")]
+impl UserDedupHandler {
+    /// De-duplicate with `dedup`.
+    #[must_use]
+    pub fn new(dedup: UserDedup) -> Self {
+        Self {
+            dedup: Arc::new(Mutex::new(dedup)),
+        }
+    }
+
+    /// The de-duplication memory, e.g. to [`UserDedup::clear`] it.
+    #[must_use]
+    pub fn dedup(&self) -> Arc<Mutex<UserDedup>> {
+        Arc::clone(&self.dedup)
+    }
+}
+
+#[cfg(feature = "websocket")]
+impl MessageHandler for UserDedupHandler {
+    fn handle(&self, ctx: &HandlerContext<'_>) -> Result<()> {
+        let Some(handler) = user_api::builtin_handlers().get(ctx.method()) else {
+            anyhow::bail!(ApiError::UnsupportedMethod(Box::new(ctx.msg.clone())));
+        };
+
+        // The built in handler sends into a channel of its own, so its data can be filtered.
+        let (data_tx, mut data_rx) = broadcast::channel(MAX_DATA_PER_MESSAGE);
+        handler.handle(&HandlerContext {
+            api: ctx.api,
+            msg: ctx.msg,
+            data_tx: &data_tx,
+            tx: ctx.tx,
+            request: ctx.request,
+            received_at: ctx.received_at,
+        })?;
+
+        let mut sent = vec![];
+
+        while let Ok(data) = data_rx.try_recv() {
+            sent.push(data);
+        }
+
+        // Nothing else holds the data once the channel is gone, so it can be changed in place.
+        drop((data_tx, data_rx));
+
+        let mut dedup = self.dedup.lock().unwrap_or_else(PoisonError::into_inner);
+
+        for data in sent {
+            let data = match Arc::try_unwrap(data) {
+                Ok(mut data) => {
+                    if data.result.as_mut().is_some_and(|data| dedup.check(data)) {
+                        continue;
+                    }
+
+                    Arc::new(data)
+                }
+                Err(data) => data,
+            };
+
+            if ctx.data_tx.send(data).is_err() {
+                trace::debug!("No subscribers for websocket data, dropping it.");
+            }
+        }
+
+        Ok(())
+    }
+}
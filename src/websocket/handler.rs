@@ -0,0 +1,221 @@
+//! Handlers that turn websocket messages into [`WebsocketData`], looked up by the message method.
+//!
+//! The methods this crate supports are handled by the built in registries, see
+//! [`crate::websocket::market_api::builtin_handlers`] and
+//! [`crate::websocket::user_api::builtin_handlers`]. Handlers for new or undocumented methods,
+//! or replacements for the built in ones, are added to [`crate::utils::config::Config`] with
+//! [`crate::controller::ControllerBuilder::with_handler`].
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use anyhow::Result;
+use futures_channel::mpsc::UnboundedSender;
+use tungstenite::Message;
+
+use crate::api_response::ApiResponse;
+use crate::prelude::{ApiError, DataSender};
+use crate::utils::in_flight::Api;
+use crate::utils::trace;
+use crate::websocket::actions::ActionKind;
+use crate::websocket::{send_data, WebsocketData};
+
+/// Everything a [`MessageHandler`] is given about a message.
+pub struct HandlerContext<'a> {
+    /// The websocket the message came from.
+    pub api: Api,
+    /// The message, with the result left as JSON.
+    pub msg: &'a ApiResponse<serde_json::Value>,
+    /// Data broadcast sender.
+    pub data_tx: &'a DataSender,
+    /// Sender for the websocket the message came from, for messages that need a reply.
+    pub tx: &'a UnboundedSender<Message>,
+    /// The action the message answers, if it was sent by this crate and still in flight.
+    pub request: Option<&'a ActionKind>,
+    /// Epoch milliseconds when the message was recieved.
+    pub received_at: u64,
+}
+
+impl<'a> HandlerContext<'a> {
+    /// The method of the message, empty if it has none.
+    #[must_use]
+    pub fn method(&self) -> &str {
+        self.msg.method.as_deref().unwrap_or_default()
+    }
+
+    /// Broadcast `data` with the ID, method, and code of the message.
+    pub fn send(&self, data: WebsocketData) {
+        send_data(self.data_tx, self.msg.websocket_data(data));
+    }
+
+    /// Broadcast [`WebsocketData::Error`] if the message has a non-zero code, returning whether
+    /// it did.
+    pub fn send_error(&self) -> bool {
+        let msg = self.msg;
+
+        let Some(code) = msg.code.filter(|code| *code != 0) else {
+            return false;
+        };
+
+        trace::warning!(
+            "{} failed with code {code}: {:?}",
+            self.method(),
+            msg.message
+        );
+
+        self.send(WebsocketData::Error {
+            method: msg.method.clone(),
+            code,
+            message: msg.message.clone(),
+            detail: msg
+                .detail_message
+                .clone()
+                .or_else(|| msg.detail_code.clone()),
+        });
+
+        true
+    }
+
+    /// The result of the message, `None` if it has none or has a non-zero code, in which case
+    /// [`WebsocketData::Error`] is broadcast.
+    #[must_use]
+    pub fn result(&self) -> Option<&'a serde_json::Value> {
+        let msg = self.msg;
+
+        if self.send_error() {
+            return None;
+        }
+
+        if msg.result.is_none() {
+            trace::warning!("Message had no result. {msg:#?}");
+        }
+
+        msg.result.as_ref()
+    }
+}
+
+impl fmt::Debug for HandlerContext<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HandlerContext")
+            .field("api", &self.api)
+            .field("msg", &self.msg)
+            .field("request", &self.request.map(|_| "Action"))
+            .field("received_at", &self.received_at)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Processes the messages of one method.
+///
+/// Implemented for any `Fn(&HandlerContext<'_>) -> Result<()>`.
+pub trait MessageHandler: Send + Sync {
+    /// Process the message in `ctx`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the message cannot be processed, the stream then follows its
+    /// [`crate::utils::config::ErrorPolicy`].
+    fn handle(&self, ctx: &HandlerContext<'_>) -> Result<()>;
+}
+
+impl<F> MessageHandler for F
+where
+    F: Fn(&HandlerContext<'_>) -> Result<()> + Send + Sync,
+{
+    fn handle(&self, ctx: &HandlerContext<'_>) -> Result<()> {
+        self(ctx)
+    }
+}
+
+/// Message handlers keyed by method.
+#[derive(Clone, Default)]
+pub struct HandlerRegistry {
+    /// Handlers keyed by method.
+    handlers: HashMap<String, Arc<dyn MessageHandler>>,
+}
+
+impl HandlerRegistry {
+    /// A registry with no handlers.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handle messages with `method` with `handler`, returning the handler it replaces.
+    pub fn register<H>(
+        &mut self,
+        method: impl Into<String>,
+        handler: H,
+    ) -> Option<Arc<dyn MessageHandler>>
+    where
+        H: MessageHandler + 'static,
+    {
+        self.handlers.insert(method.into(), Arc::new(handler))
+    }
+
+    /// Builder form of [`HandlerRegistry::register`].
+    #[must_use]
+    pub fn with_handler<H>(mut self, method: impl Into<String>, handler: H) -> Self
+    where
+        H: MessageHandler + 'static,
+    {
+        self.register(method, handler);
+
+        self
+    }
+
+    /// Stop handling messages with `method`, returning the handler that was removed.
+    pub fn remove(&mut self, method: &str) -> Option<Arc<dyn MessageHandler>> {
+        self.handlers.remove(method)
+    }
+
+    /// The handler for `method`.
+    #[must_use]
+    pub fn get(&self, method: &str) -> Option<&Arc<dyn MessageHandler>> {
+        self.handlers.get(method)
+    }
+
+    /// The methods that have a handler.
+    pub fn methods(&self) -> impl Iterator<Item = &str> {
+        self.handlers.keys().map(String::as_str)
+    }
+
+    /// Whether there are no handlers.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+}
+
+impl fmt::Debug for HandlerRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut methods: Vec<&str> = self.methods().collect();
+        methods.sort_unstable();
+
+        f.debug_struct("HandlerRegistry")
+            .field("methods", &methods)
+            .finish()
+    }
+}
+
+/// Pass `ctx` to the handler in `custom` for its method, falling back to `builtin`.
+///
+/// # Errors
+///
+/// Will return [`ApiError::UnsupportedMethod`] if neither registry handles the method.
+///
+/// Will return `Err` if the handler fails.
+pub(crate) fn dispatch(
+    custom: &HandlerRegistry,
+    builtin: &HandlerRegistry,
+    ctx: &HandlerContext<'_>,
+) -> Result<()> {
+    let method = ctx.method();
+
+    let Some(handler) = custom.get(method).or_else(|| builtin.get(method)) else {
+        anyhow::bail!(ApiError::UnsupportedMethod(Box::new(ctx.msg.clone())));
+    };
+
+    handler.handle(ctx)
+}
@@ -1,32 +1,45 @@
 //! The interaction systems for the websocket User API.
 
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 use anyhow::Result;
 use futures_channel::mpsc::UnboundedSender;
-use futures_util::future::Either;
-use futures_util::{future, pin_mut, StreamExt, TryStreamExt};
-use serde::Serialize;
+use futures_util::Future;
+#[cfg(feature = "native")]
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
+#[cfg(feature = "native")]
 use tokio::task::JoinHandle;
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tungstenite::Message;
 
 use crate::api_request::ApiRequestBuilder;
 use crate::api_response::ApiResponse;
-use crate::error::{convert_tungstenite_error, processing_error};
 use crate::prelude::{ApiError, DataSender, MessageSender};
-use crate::utils::action::ActionStore;
+use crate::utils::action::{Action, ActionStore};
 use crate::utils::config::Config;
-use crate::utils::{message_to_api_response, reprocess_data};
+use crate::utils::in_flight::{Api, InFlight};
+use crate::utils::raw_hook::RawMessageHook;
+use crate::utils::trace;
+use crate::utils::{get_epoch_ms, message_to_api_response, reprocess_value};
+use crate::websocket::connection_state::{send_state, ConnectionState};
 use crate::websocket::data::{
-    book::{BookRes, RawBookRes},
+    book::{BookRes, BookUpdateRes, RawBookRes, RawBookUpdateRes},
     candlestick::{CandlestickRes, RawCandlestickRes},
     otc_book::{OtcBookRes, RawOtcBookRes},
     ticker::{RawTickerRes, TickerRes},
     trade::{RawTradeRes, TradeRes},
-    RawRes,
+    valuation::{RawValuationRes, ValuationRes},
+    SubscriptionPayload,
+};
+use crate::websocket::handler::{dispatch, HandlerContext, HandlerRegistry};
+#[cfg(feature = "native")]
+use crate::websocket::transport::TungsteniteTransport;
+use crate::websocket::transport::{drive, WsTransport};
+use crate::websocket::{
+    handle_processing_error, respond_heartbeat, send_data, send_subscribe_ack, unsubscribe_result,
+    WebsocketData,
 };
-use crate::websocket::{respond_heartbeat, WebsocketData};
 
 /// Parameters of the subscription request.
 #[derive(Serialize, Debug)]
@@ -49,6 +62,7 @@ pub async fn process_market_actions(action: ActionStore, market_tx: MessageSende
 }
 
 /// Initialize the market action processing system.
+#[cfg(feature = "native")]
 pub async fn initialize_market_actions(
     market_tx_arc: MessageSender,
 ) -> (JoinHandle<Result<()>>, UnboundedSender<ActionStore>) {
@@ -58,7 +72,10 @@ pub async fn initialize_market_actions(
         let market_tx_arc = Arc::clone(&market_tx_arc);
 
         while let Some(item) = actions_rx.next().await {
-            process_market_actions(item, Arc::clone(&market_tx_arc)).await?;
+            // The request stays in flight and is replayed when the websocket reconnects.
+            if let Err(err) = process_market_actions(item, Arc::clone(&market_tx_arc)).await {
+                trace::warning!("Failed to send market action, it is queued for replay: {err:#}");
+            }
         }
 
         Ok(())
@@ -67,77 +84,136 @@ pub async fn initialize_market_actions(
     (join_handle, actions_tx)
 }
 
-/// Initialize the websocket market stream.
+/// Initialize the websocket market stream on a [`TungsteniteTransport`].
 ///
 /// # Errors
 ///
-/// Will return [`tungstenite::error::Error`] if [`connect_async`] fails.
+/// Will return the errors of [`initialize_market_stream_with`].
+#[cfg(feature = "native")]
 pub async fn initialize_market_stream(
     config: &Config,
-    data_tx_arc: DataSender,
+    data_tx: DataSender,
+    in_flight: Arc<InFlight>,
+    raw_hook: Option<RawMessageHook>,
+) -> Result<(JoinHandle<Result<()>>, MessageSender)> {
+    initialize_market_stream_with::<TungsteniteTransport>(config, data_tx, in_flight, raw_hook)
+        .await
+}
+
+/// Initialize the websocket market stream on a `T` connected with [`WsTransport::connect`].
+///
+/// # Errors
+///
+/// Will return `Err` if [`WsTransport::connect`] fails.
+///
+/// Will return [`ApiError::InvalidHeader`] if a header in `config` is not valid.
+#[cfg(feature = "native")]
+pub async fn initialize_market_stream_with<T: WsTransport>(
+    config: &Config,
+    data_tx: DataSender,
+    in_flight: Arc<InFlight>,
+    raw_hook: Option<RawMessageHook>,
 ) -> Result<(JoinHandle<Result<()>>, MessageSender)> {
-    let (market_tx, market_rx) = futures_channel::mpsc::unbounded();
-    let market_tx_arc = Arc::new(Mutex::new(market_tx));
     let Some(ref websocket_market_api) = config.websocket_market_api else {
         anyhow::bail!("websocket_market_api");
     };
 
-    let (market_stream, _) = connect_async(websocket_market_api).await?;
-    log::info!("WebSocket Market API handshake has been successfully completed.");
+    let transport = T::connect(config, websocket_market_api).await?;
+    trace::info!("WebSocket Market API handshake has been successfully completed.");
 
-    {
-        let data_tx_arc = Arc::clone(&data_tx_arc);
-        let data_tx = data_tx_arc.lock().await;
+    Ok(spawn_market_stream(
+        transport, config, data_tx, in_flight, raw_hook,
+    ))
+}
 
-        data_tx.unbounded_send(
-            ApiResponse::<WebsocketData>::default().websocket_data(WebsocketData::MarketHandshake),
-        )?;
-    }
+/// Process the messages of the market api recieved on `transport`, which is already connected.
+///
+/// Returns the handle of the task processing the stream and the sender for messages to the
+/// market api.
+#[cfg(feature = "native")]
+pub fn spawn_market_stream<T: WsTransport>(
+    transport: T,
+    config: &Config,
+    data_tx: DataSender,
+    in_flight: Arc<InFlight>,
+    raw_hook: Option<RawMessageHook>,
+) -> (JoinHandle<Result<()>>, MessageSender) {
+    let (stream, market_tx_arc) = market_stream(transport, config, data_tx, in_flight, raw_hook);
+
+    (tokio::spawn(stream), market_tx_arc)
+}
+
+/// Process the messages of the market api recieved on `transport`, which is already connected,
+/// on any executor, e.g. `wasm_bindgen_futures::spawn_local` with a
+/// [`crate::websocket::transport::WebSysTransport`] in the browser.
+///
+/// Returns the future processing the stream until the connection ends, and the sender for
+/// messages to the market api.
+pub fn market_stream<T: WsTransport>(
+    transport: T,
+    config: &Config,
+    data_tx: DataSender,
+    in_flight: Arc<InFlight>,
+    raw_hook: Option<RawMessageHook>,
+) -> (impl Future<Output = Result<()>>, MessageSender) {
+    let (market_tx, market_rx) = futures_channel::mpsc::unbounded();
+    let market_tx_arc = Arc::new(Mutex::new(market_tx));
+
+    send_data(
+        &data_tx,
+        ApiResponse::<WebsocketData>::default().websocket_data(WebsocketData::MarketHandshake),
+    );
+    send_state(&data_tx, ConnectionState::Connected(Api::Market));
 
-    let (market_write, market_read) = market_stream.split();
-    let rx_to_market = market_rx.map(Ok).forward(market_write);
+    let error_policy = config.error_policy;
+    let handlers = config.market_handlers.clone();
+    let request_log = config.request_log.clone();
 
-    let join_handle: JoinHandle<Result<()>> = {
+    let stream = {
         let market_tx_arc = Arc::clone(&market_tx_arc);
 
-        tokio::spawn(async move {
-            let market_to_process = {
-                market_read
-                    .map_err(convert_tungstenite_error)
-                    .try_for_each(|message| async {
-                        match process_market(
+        async move {
+            let res = drive(transport, market_rx, request_log.as_ref(), |message| {
+                let market_tx_arc = Arc::clone(&market_tx_arc);
+                let data_tx = &data_tx;
+                let in_flight = &in_flight;
+                let raw_hook = raw_hook.as_ref();
+                let handlers = &handlers;
+
+                async move {
+                    match trace::in_message_span(
+                        "market",
+                        process_market(
                             message,
-                            Arc::clone(&market_tx_arc),
-                            Arc::clone(&data_tx_arc),
-                        )
-                        .await
-                        {
-                            Ok(res) => Ok(res),
-                            Err(err) => Err(processing_error(err)),
+                            market_tx_arc,
+                            data_tx,
+                            in_flight,
+                            raw_hook,
+                            handlers,
+                        ),
+                    )
+                    .await
+                    {
+                        Ok(res) => Ok(res),
+                        Err(err) => {
+                            handle_processing_error(error_policy, Api::Market, err, data_tx)
                         }
-                    })
-            };
-
-            pin_mut!(rx_to_market, market_to_process);
-            match future::select(rx_to_market, market_to_process).await {
-                Either::Left((_rx_to_market_res, _)) => {
-                    log::info!("Market process completed");
-
-                    Ok(())
+                    }
                 }
-                Either::Right((market_to_process_res, _)) => match market_to_process_res {
-                    Ok(_) => {
-                        log::info!("Market process completed");
+            })
+            .await;
 
-                        Ok(())
-                    }
-                    Err(err) => anyhow::bail!(err),
-                },
+            if res.is_ok() {
+                trace::info!("Market process completed");
             }
-        })
+
+            send_state(&data_tx, ConnectionState::disconnected(Api::Market, &res));
+
+            res
+        }
     };
 
-    Ok((join_handle, market_tx_arc))
+    (stream, market_tx_arc)
 }
 
 /// Send a subscription request to the market api.
@@ -151,7 +227,9 @@ pub async fn initialize_market_stream(
 ///
 /// Will return [`futures_channel::mpsc::TrySendError`] if `unbounded_send` fails.
 pub fn subscribe(tx: &UnboundedSender<Message>, id: u64, channels: Vec<String>) -> Result<()> {
-    log::info!("Sending subscribe.");
+    let _span = trace::enter_request(id, "subscribe");
+
+    trace::info!("Sending subscribe.");
 
     let subscribe_msg = ApiRequestBuilder::default()
         .with_id(id)
@@ -167,98 +245,214 @@ pub fn subscribe(tx: &UnboundedSender<Message>, id: u64, channels: Vec<String>)
     Ok(())
 }
 
-/// Process the subscribe return data from the market api.
+/// Decode the result of a `subscribe` message from the market api into [`WebsocketData`].
+///
+/// This does no IO and does not depend on the websocket transport or runtime, so frames recieved
+/// by any websocket client can be decoded with it. Returns `None` if the message has no result.
 ///
 /// # Errors
 ///
-/// Will return `Err` if subscription fails to process.
+/// Will return [`serde_json::Error`] if the result does not match the channel's data.
 ///
-/// Will return [`futures_channel::mpsc::TrySendError`] if `unbounded_send` fails anywhere.
-async fn process_subscribe_result(
-    data_tx: DataSender,
-    res: &serde_json::Value,
+/// Will return [`ApiError::UnsupportedSubscription`] if the channel is not supported, results
+/// without a channel are returned as [`WebsocketData::RawSubscription`].
+pub fn decode_subscription(
     msg: &ApiResponse<serde_json::Value>,
-    sub: &RawRes,
-) -> Result<()> {
-    let data_tx = data_tx.lock().await;
+    received_at: u64,
+) -> Result<Option<WebsocketData>> {
+    let Some(ref res) = msg.result else {
+        return Ok(None);
+    };
+
+    let sub = SubscriptionPayload::deserialize(res)?;
+    let Some(channel) = sub.channel() else {
+        trace::warning!("Subscription result has no channel. {res}");
 
-    match sub.channel.as_str() {
+        return Ok(Some(WebsocketData::RawSubscription(res.clone())));
+    };
+    trace::record_subscription(channel, sub.instrument_name());
+
+    let completed = sub.complete(res);
+    let res = &*completed;
+    let data = match channel {
         "book" => {
-            let book_data = reprocess_data::<RawBookRes, BookRes>(&res.to_string())?;
-            data_tx.unbounded_send(msg.websocket_data(WebsocketData::Book(book_data)))?;
+            let mut book_data = reprocess_value::<RawBookRes<'_>, BookRes>(res)?;
+            book_data.received_at = received_at;
+            WebsocketData::Book(book_data)
+        }
+        "book.update" => {
+            let mut update_data = reprocess_value::<RawBookUpdateRes<'_>, BookUpdateRes>(res)?;
+            update_data.received_at = received_at;
+            WebsocketData::BookUpdate(update_data)
         }
         "ticker" => {
-            let ticker_data = reprocess_data::<RawTickerRes, TickerRes>(&res.to_string())?;
-            data_tx.unbounded_send(msg.websocket_data(WebsocketData::Ticker(ticker_data)))?;
+            let mut ticker_data = reprocess_value::<RawTickerRes<'_>, TickerRes>(res)?;
+            ticker_data.received_at = received_at;
+            WebsocketData::Ticker(ticker_data)
         }
         "trade" => {
-            let trade_data = reprocess_data::<RawTradeRes, TradeRes>(&res.to_string())?;
-            data_tx.unbounded_send(msg.websocket_data(WebsocketData::Trade(trade_data)))?;
+            let mut trade_data = reprocess_value::<RawTradeRes<'_>, TradeRes>(res)?;
+            trade_data.received_at = received_at;
+            WebsocketData::Trade(trade_data)
         }
         "candlestick" => {
-            let candlestick_data =
-                reprocess_data::<RawCandlestickRes, CandlestickRes>(&res.to_string())?;
-            data_tx
-                .unbounded_send(msg.websocket_data(WebsocketData::Candlestick(candlestick_data)))?;
+            let mut candlestick_data =
+                reprocess_value::<RawCandlestickRes<'_>, CandlestickRes>(res)?;
+            candlestick_data.received_at = received_at;
+            WebsocketData::Candlestick(candlestick_data)
         }
         "otc_book" => {
-            let otc_book_data = reprocess_data::<RawOtcBookRes, OtcBookRes>(&res.to_string())?;
-            data_tx.unbounded_send(msg.websocket_data(WebsocketData::OtcBook(otc_book_data)))?;
+            WebsocketData::OtcBook(reprocess_value::<RawOtcBookRes<'_>, OtcBookRes>(res)?)
+        }
+        "index" | "mark" | "funding" | "estimatedfunding" => {
+            let mut valuation_data = reprocess_value::<RawValuationRes<'_>, ValuationRes>(res)?;
+            valuation_data.received_at = received_at;
+
+            match channel {
+                "index" => WebsocketData::Index(valuation_data),
+                "mark" => WebsocketData::Mark(valuation_data),
+                "funding" => WebsocketData::Funding(valuation_data),
+                _ => WebsocketData::EstimatedFunding(valuation_data),
+            }
         }
-        _ => anyhow::bail!(ApiError::UnsupportedSubscription(msg.clone())),
+        _ => anyhow::bail!(ApiError::UnsupportedSubscription(Box::new(msg.clone()))),
+    };
+
+    Ok(Some(data))
+}
+
+/// Handle the `public/heartbeat` message by responding to it.
+///
+/// # Errors
+///
+/// Will return `Err` if [`crate::websocket::respond_heartbeat`] fails to respond.
+fn public_heartbeat(ctx: &HandlerContext<'_>) -> Result<()> {
+    respond_heartbeat(ctx.tx, ctx.msg.id.try_into()?)?;
+    ctx.send(WebsocketData::MarketHeartbeat);
+
+    Ok(())
+}
+
+/// Handle the `subscribe` acknowledgement or subscription data.
+///
+/// # Errors
+///
+/// Will return `Err` if [`decode_subscription`] fails.
+fn subscribe_result(ctx: &HandlerContext<'_>) -> Result<()> {
+    if let Some(data) = acknowledge_subscription(ctx)? {
+        ctx.send(data);
+    }
+
+    Ok(())
+}
+
+/// Send the acknowledgement of a `subscribe` reply and decode any subscription data, for handlers
+/// that replace the built in `subscribe` handler but still send its data.
+///
+/// # Errors
+///
+/// Will return `Err` if [`decode_subscription`] fails.
+pub(crate) fn acknowledge_subscription(ctx: &HandlerContext<'_>) -> Result<Option<WebsocketData>> {
+    let msg = ctx.msg;
+
+    if msg.id >= 0 {
+        send_subscribe_ack(ctx.data_tx, msg, ctx.request);
     }
 
+    let data = decode_subscription(msg, ctx.received_at)?;
+
+    if data.is_none() && msg.id < 0 {
+        trace::warning!("Subscribe message had no result. {msg:#?}");
+    }
+
+    Ok(data)
+}
+
+/// Ignore the `ping` placeholder made for websocket ping frames, they are answered on arrival.
+///
+/// # Errors
+///
+/// Never returns `Err`.
+fn ping(_ctx: &HandlerContext<'_>) -> Result<()> {
     Ok(())
 }
 
+/// The handlers for every method of the market api supported by this crate.
+///
+/// Handlers in [`Config::market_handlers`] are used before these.
+pub fn builtin_handlers() -> &'static HandlerRegistry {
+    static HANDLERS: OnceLock<HandlerRegistry> = OnceLock::new();
+
+    HANDLERS.get_or_init(|| {
+        HandlerRegistry::new()
+            .with_handler("public/heartbeat", public_heartbeat)
+            .with_handler("subscribe", subscribe_result)
+            .with_handler("unsubscribe", unsubscribe_result)
+            .with_handler("ping", ping)
+    })
+}
+
 /// Process data recieved from the market api.
 ///
+/// Each message is passed to the handler for its method in `handlers`, or in
+/// [`builtin_handlers`] when `handlers` has none.
+///
 /// # Errors
 ///
 /// Will return [`serde_json::Error`] if [`serde_json::from_str`] cannot process the text or
 /// binary data as a string. This error can also be passed down from
-/// [`crate::utils::reprocess_data`].
+/// [`crate::utils::reprocess_value`].
 ///
 /// Will return [`std::str::Utf8Error`] if [`std::str::from_utf8`] cannot process a binary message that was recieved.
 ///
-/// Will return `Err` if [`crate::websocket::respond_heartbeat`] fails to respond to heartbeat message.
+/// Will return [`ApiError::UnsupportedMethod`] if no handler exists for the method.
 ///
-/// Will return [`futures_channel::mpsc::TrySendError`] if `unbounded_send` fails anywhere.
+/// Will return `Err` if the handler fails.
 pub async fn process_market(
     message: Message,
     market_tx: MessageSender,
-    data_tx: DataSender,
+    data_tx: &DataSender,
+    in_flight: &InFlight,
+    raw_hook: Option<&RawMessageHook>,
+    handlers: &HandlerRegistry,
 ) -> Result<()> {
-    let msg = message_to_api_response(&market_tx, &message).await?;
-    let method = if let Some(ref method) = msg.method {
-        method.as_str()
-    } else {
-        ""
-    };
-    let res = msg.result.clone();
+    let received_at = get_epoch_ms();
 
-    match method {
-        "public/heartbeat" => {
-            let market_tx = market_tx.lock().await;
-            let data_tx = data_tx.lock().await;
+    if let Some(raw_hook) = raw_hook {
+        raw_hook.call(&message);
+    }
 
-            respond_heartbeat(&market_tx, msg.id.try_into()?)?;
-            data_tx.unbounded_send(msg.websocket_data(WebsocketData::MarketHeartbeat))?;
-        }
-        "subscribe" => {
-            let Some(ref res) = res else {
-                log::warn!("Subscribe message had no result. {msg:#?}");
+    if let Message::Close(ref frame) = message {
+        trace::info!("Market websocket sent a close frame. {frame:?}");
+        send_state(
+            data_tx,
+            ConnectionState::closed(Api::Market, frame.as_ref()),
+        );
 
-                return Ok(());
-            };
+        return Ok(());
+    }
 
-            let sub_result: RawRes = serde_json::from_str(&res.to_string())?;
+    let msg = message_to_api_response(&market_tx, &message).await?;
 
-            process_subscribe_result(data_tx, res, &msg, &sub_result).await?;
-        }
-        "ping" => {}
-        _ => anyhow::bail!(ApiError::UnsupportedMethod(msg.clone())),
-    }
+    let request = match u64::try_from(msg.id) {
+        Ok(id) => in_flight.resolve(id).await,
+        Err(_) => None,
+    };
 
-    Ok(())
+    trace::record_message(msg.id, msg.method.as_deref().unwrap_or_default());
+
+    let tx = market_tx.lock().await.clone();
+
+    dispatch(
+        handlers,
+        builtin_handlers(),
+        &HandlerContext {
+            api: Api::Market,
+            msg: &msg,
+            data_tx,
+            tx: &tx,
+            request: request.as_deref(),
+            received_at,
+        },
+    )
 }
@@ -1,42 +1,87 @@
 //! Data and helper functions for interacting with the websocket system.
 
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
 use anyhow::Result;
 use futures_channel::mpsc::UnboundedSender;
 use serde::Serialize;
-use tokio_tungstenite::tungstenite::Message;
+use tungstenite::client::IntoClientRequest;
+use tungstenite::handshake::client::Request;
+use tungstenite::Message;
 
+use crate::analytics::alerts::AlertFired;
 use crate::api_request::ApiRequestBuilder;
-use crate::rest::data::InstrumentsRes;
+use crate::api_response::ApiResponse;
+use crate::error::{processing_error, ApiError};
+use crate::prelude::DataSender;
+use crate::rest::data::{DepositAddress, DepositAddressItem, InstrumentsRes, SystemStatus};
+use crate::utils::action::Action;
+use crate::utils::clock::EventTime;
+use crate::utils::config::{Config, ErrorPolicy};
+use crate::utils::in_flight::{Api, InFlight};
+use crate::utils::trace;
+use crate::websocket::actions::ActionKind;
 use crate::websocket::data::{
-    AccountSummary, BookRes, CancelOrderList, CandlestickRes, CreateOrder, CreateOrderList,
-    CreateWithdrawal, OpenOrders, OrderDetail, OrderHistory, OtcBookRes, TickerRes, TradeRes,
-    Trades, UserBalance, UserOrderRes, UserTradeRes, WithdrawalHistory,
+    AccountSummary, BookRes, BookUpdateRes, CancelOrder, CancelOrderList, CandlestickRes,
+    CreateOrder, CreateOrderList, CreateWithdrawal, OpenOrders, OrderDetail, OrderHistory,
+    OtcBookRes, TickerRes, TradeRes, Trades, UserBalance, UserOrderRes, UserTradeRes, ValuationRes,
+    WithdrawalHistory,
 };
+use crate::websocket::handler::HandlerContext;
 
+use self::auth_state::AuthState;
+use self::connection_state::ConnectionState;
 use self::data::Scope;
+use self::resync::ResyncComplete;
+use self::sequence::SequenceGap;
+use self::subscribe_batch::SubscribeBatch;
 
 pub mod actions;
+pub mod auth_state;
+pub mod channel;
+pub mod connection_state;
 pub mod data;
+pub mod dedup;
+pub mod handler;
 pub mod market_api;
+pub mod resync;
+pub mod sequence;
+pub mod subscribe_batch;
+pub mod subscriptions;
+pub mod transport;
+#[cfg(feature = "websocket")]
 pub mod user_api;
 
 /// Data that could be recieved from the websocket.
-///
-/// `private/cancel-order` doesn't return data.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub enum WebsocketData {
     /// Auth with auth code.
     Auth,
+    /// The authorization of the user websocket changed, see
+    /// [`crate::controller::Controller::authenticate`].
+    AuthState(AuthState),
     /// Data from `ticker.{instrument_name}` subscription.
     Ticker(TickerRes),
     /// Data from `book.{instrument_name}` subscription.
     Book(BookRes),
+    /// Data from `book.update`, sent after the first snapshot when subscribed with
+    /// [`actions::BookSubscriptionType::SnapshotAndUpdate`].
+    BookUpdate(BookUpdateRes),
     /// Data from `trade.{instrument_name}` subscription.
     Trade(TradeRes),
     /// Data from `candlestick.{time_frame}.{instrument_name}` subscription.
     Candlestick(CandlestickRes),
     /// Data from `otc_book.{instrument_name}` subscription.
     OtcBook(OtcBookRes),
+    /// Data from `index.{instrument_name}` subscription.
+    Index(ValuationRes),
+    /// Data from `mark.{instrument_name}` subscription.
+    Mark(ValuationRes),
+    /// Data from `funding.{instrument_name}` subscription.
+    Funding(ValuationRes),
+    /// Data from `estimatedfunding.{instrument_name}` subscription.
+    EstimatedFunding(ValuationRes),
     /// Data from `user.order.{instrument_name}` subscription.
     UserOrder(UserOrderRes),
     /// Data from `user.trade.{instrument_name}` subscription.
@@ -49,14 +94,16 @@ pub enum WebsocketData {
     CreateWithdrawal(CreateWithdrawal),
     /// Data from `private/get-withdrawal-history`.
     GetWithdrawalHistory(WithdrawalHistory),
+    /// Data from `private/get-deposit-address`.
+    GetDepositAddress(DepositAddress),
+    /// Data from `private/create-deposit-address`.
+    CreateDepositAddress(DepositAddressItem),
     /// Data from `private/get-account-summary`.
     GetAccountSummary(AccountSummary),
     /// Data from `private/create-order`.
     CreateOrder(CreateOrder),
-    /// Data from `private/cancel-order`.
-    ///
-    /// This only returns the ID of the request as a confirmation of the request.
-    CancelOrder(u64),
+    /// Data from `private/cancel-order`, with the order taken from the request.
+    CancelOrder(CancelOrder),
     /// Data from `private/create-order-list`.
     CreateOrderList(CreateOrderList),
     /// Data from `private/cancel-order-list`.
@@ -83,6 +130,295 @@ pub enum WebsocketData {
     SetCancelOnDisconnect(Scope),
     /// Data from `private/get-cancel-on-disconnect`.
     GetCancelOnDisconnect(Scope),
+    /// Pending maintenance found by [`crate::controller::Controller::watch_system_status`].
+    SystemStatus(SystemStatus),
+    /// The request was not answered within the timeout set with
+    /// [`crate::controller::ControllerBuilder::with_request_timeout`], it will not be replayed.
+    RequestTimeout {
+        /// ID the request was sent with.
+        id: u64,
+        /// Method the request called.
+        method: &'static str,
+    },
+    /// The request with this ID was dropped from the full send queue and will not be replayed.
+    RequestDropped(u64),
+    /// A request that changes orders or funds was unanswered when its websocket reconnected. It is
+    /// not replayed since it may have reached the exchange, look it up by its client ID instead,
+    /// e.g. with `private/get-order-detail` or `private/get-withdrawal-history`.
+    RequestOutcomeUnknown {
+        /// ID the request was sent with.
+        id: u64,
+        /// Method the request called, one of [`crate::utils::action::MUTATING_METHODS`] for the
+        /// built-in actions.
+        method: &'static str,
+        /// The `client_oid` of an order or `client_wid` of a withdrawal, if one was set, see
+        /// [`actions::ActionKind::client_id`].
+        client_id: Option<String>,
+    },
+    /// A websocket connected, closed, or was reconnected.
+    ConnectionState(ConnectionState),
+    /// The result of a subscription push whose shape is not known, see
+    /// [`data::SubscriptionPayload`].
+    RawSubscription(serde_json::Value),
+    /// A message could not be processed and was skipped, sent with [`ErrorPolicy::Forward`].
+    ProcessingError {
+        /// The websocket the message came from.
+        api: Api,
+        /// Why the message could not be processed.
+        error: String,
+    },
+    /// The exchange answered a request with a non-zero code, e.g. a rejected order. The response
+    /// has the request ID.
+    Error {
+        /// Method of the request, e.g. `private/create-order`.
+        method: Option<String>,
+        /// The code crypto.com replied with, see
+        /// [response and reason codes](https://exchange-docs.crypto.com/exchange/v1/rest-ws/index.html#response-and-reason-codes).
+        code: u64,
+        /// The message given with the code, e.g. `INVALID_ORDERID`.
+        message: Option<String>,
+        /// The detail message, or the detail code when there is no detail message.
+        detail: Option<String>,
+    },
+    /// A `subscribe` request was accepted, the response has the request ID.
+    Subscribed {
+        /// The channels of the request, empty if the request is not known.
+        channels: Vec<String>,
+    },
+    /// An `unsubscribe` request was accepted, the response has the request ID.
+    Unsubscribed {
+        /// The channels of the request, empty if the request is not known.
+        channels: Vec<String>,
+    },
+    /// A `subscribe` request was refused, sent for each of its channels.
+    SubscribeError {
+        /// The refused channel, `None` if the request is not known.
+        channel: Option<String>,
+        /// The code crypto.com replied with.
+        code: u64,
+    },
+    /// Every request sent by [`crate::utils::action_queue::ActionQueue::subscribe_many`] has an
+    /// outcome, sent with the ID of the first request.
+    SubscribeBatch(SubscribeBatch),
+    /// A book, book update, or trade did not follow the last one of its subscription, sent before
+    /// it by [`sequence::SequenceHandler`].
+    SequenceGap(SequenceGap),
+    /// Every request sent by [`crate::controller::Controller::resync`] has an outcome and every
+    /// book has a fresh snapshot, sent with the ID of the first request.
+    ResyncComplete(ResyncComplete),
+    /// An alert started to hold, sent by [`crate::controller::Controller::watch_alerts`].
+    AlertFired(AlertFired),
+}
+
+impl WebsocketData {
+    /// The times of each book, book update, ticker, trade, or candlestick in the data, empty for
+    /// other data.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use crypto_com_api::websocket::WebsocketData;
+    ///
+    /// /// Whether any event in `data` was published more than 500ms ago.
+    /// fn is_stale(data: &WebsocketData) -> bool {
+    ///     data.event_times()
+    ///         .iter()
+    ///         .any(|time| time.age() > Duration::from_millis(500))
+    /// }
+    /// ```
+    #[must_use]
+    pub fn event_times(&self) -> Vec<EventTime> {
+        match *self {
+            Self::Book(ref books) => books.event_times(),
+            Self::BookUpdate(ref updates) => updates.event_times(),
+            Self::Ticker(ref tickers) => tickers.event_times(),
+            Self::Trade(ref trades) => trades.event_times(),
+            Self::Candlestick(ref candlesticks) => candlesticks.event_times(),
+            _ => vec![],
+        }
+    }
+
+    /// The times of the most recently published event in the data, see
+    /// [`WebsocketData::event_times`].
+    #[must_use]
+    pub fn event_time(&self) -> Option<EventTime> {
+        self.event_times()
+            .into_iter()
+            .max_by_key(|time| time.published)
+    }
+}
+
+/// Send [`WebsocketData::Subscribed`] or [`WebsocketData::SubscribeError`] for `msg`, the reply to
+/// the `subscribe` request `request`.
+pub(crate) fn send_subscribe_ack(
+    data_tx: &DataSender,
+    msg: &ApiResponse<serde_json::Value>,
+    request: Option<&ActionKind>,
+) {
+    let channels = request
+        .and_then(actions::as_subscribe)
+        .map(|request| request.channels)
+        .unwrap_or_default();
+
+    match msg.code.filter(|code| *code != 0) {
+        None => send_data(
+            data_tx,
+            msg.websocket_data(WebsocketData::Subscribed { channels }),
+        ),
+        Some(code) if channels.is_empty() => send_data(
+            data_tx,
+            msg.websocket_data(WebsocketData::SubscribeError {
+                channel: None,
+                code,
+            }),
+        ),
+        Some(code) => {
+            trace::warning!("Subscribing to {channels:?} failed with code {code}.");
+
+            for channel in channels {
+                send_data(
+                    data_tx,
+                    msg.websocket_data(WebsocketData::SubscribeError {
+                        channel: Some(channel),
+                        code,
+                    }),
+                );
+            }
+        }
+    }
+}
+
+/// Handle the reply to an `unsubscribe` request, sending [`WebsocketData::Unsubscribed`] or
+/// [`WebsocketData::Error`].
+///
+/// # Errors
+///
+/// Never returns `Err`.
+pub(crate) fn unsubscribe_result(ctx: &HandlerContext<'_>) -> Result<()> {
+    if ctx.send_error() {
+        return Ok(());
+    }
+
+    let channels = match ctx.request {
+        Some(ActionKind::Unsubscribe(request)) => request.channels.clone(),
+        _ => vec![],
+    };
+
+    ctx.send(WebsocketData::Unsubscribed { channels });
+
+    Ok(())
+}
+
+/// Apply `policy` to `err`, returned by processing a message from `api`.
+///
+/// # Errors
+///
+/// Will return the error converted with [`processing_error`] if `policy` is
+/// [`ErrorPolicy::FailFast`].
+pub fn handle_processing_error(
+    policy: ErrorPolicy,
+    api: Api,
+    err: anyhow::Error,
+    data_tx: &DataSender,
+) -> Result<(), ApiError> {
+    match policy {
+        ErrorPolicy::FailFast => return Err(processing_error(err)),
+        ErrorPolicy::SkipAndLog => {
+            trace::error!("Skipped a {api:?} message that failed to process: {err:#}");
+        }
+        ErrorPolicy::Forward => {
+            trace::debug!("Forwarding a {api:?} message that failed to process: {err:#}");
+            send_data(
+                data_tx,
+                ApiResponse::<WebsocketData>::default().websocket_data(
+                    WebsocketData::ProcessingError {
+                        api,
+                        error: format!("{err:#}"),
+                    },
+                ),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Report requests in `in_flight` that go unanswered for longer than `timeout`.
+///
+/// Runs until `in_flight` is dropped along with the controller and its websockets.
+pub async fn expire_requests(in_flight: Weak<InFlight>, data_tx: DataSender, timeout: Duration) {
+    // Checking a few times per timeout keeps the reporting delay to a fraction of it.
+    let mut ticks = tokio::time::interval((timeout / 4).max(Duration::from_millis(1)));
+
+    loop {
+        ticks.tick().await;
+
+        let Some(in_flight) = in_flight.upgrade() else {
+            return;
+        };
+
+        for (id, action) in in_flight.expire(timeout).await {
+            let method = action.method();
+            trace::warning!("Request {id} ({method}) was not answered within {timeout:?}.");
+
+            send_data(
+                &data_tx,
+                ApiResponse::<WebsocketData> {
+                    id: i64::try_from(id).unwrap_or(-1),
+                    method: Some(method.to_owned()),
+                    ..ApiResponse::default()
+                }
+                .websocket_data(WebsocketData::RequestTimeout { id, method }),
+            );
+        }
+    }
+}
+
+/// Report the requests in `pending` that change orders or funds, see [`Action::mutates`], with
+/// [`WebsocketData::RequestOutcomeUnknown`] and return the rest, which are safe to send again.
+///
+/// Replaying a mutating request could e.g. place an order or withdraw funds twice, when the first
+/// request reached the exchange before the websocket dropped.
+pub fn replayable(
+    data_tx: &DataSender,
+    pending: Vec<(u64, Arc<actions::ActionKind>)>,
+) -> Vec<(u64, Arc<actions::ActionKind>)> {
+    pending
+        .into_iter()
+        .filter(|&(id, ref action)| {
+            if !action.mutates() {
+                return true;
+            }
+
+            let method = action.method();
+            trace::warning!("Request {id} ({method}) may have been sent, it is not replayed.");
+
+            send_data(
+                data_tx,
+                ApiResponse::<WebsocketData> {
+                    id: i64::try_from(id).unwrap_or(-1),
+                    method: Some(method.to_owned()),
+                    ..ApiResponse::default()
+                }
+                .websocket_data(WebsocketData::RequestOutcomeUnknown {
+                    id,
+                    method,
+                    client_id: action.client_id().map(str::to_owned),
+                }),
+            );
+
+            false
+        })
+        .collect()
+}
+
+/// Broadcast processed data to every subscriber.
+///
+/// Having no subscribers is not an error, the data is simply dropped.
+pub fn send_data(tx: &DataSender, data: ApiResponse<WebsocketData>) {
+    if tx.send(Arc::new(data)).is_err() {
+        trace::debug!("No subscribers for websocket data, dropping it.");
+    }
 }
 
 /// Sends an API message with params to the websocket server. This is helpful for non-REST requests
@@ -97,16 +433,18 @@ pub fn send_params_msg<T: Serialize, S: Into<String>>(
     id: u64,
     method: S,
     params: T,
-) -> Result<()> {
+) -> crate::error::Result<()> {
     let method = method.into();
+    let _span = trace::enter_request(id, &method);
 
-    log::info!("Sending message to {}", method);
+    trace::info!("Sending message to {}", method);
 
     let msg = ApiRequestBuilder::default()
         .with_id(id)
         .with_method(method)
         .with_params(params)
         .with_nonce()
+        .on_authorized_session()
         .build()?;
 
     let msg = Message::Text(serde_json::to_string(&msg)?);
@@ -116,6 +454,29 @@ pub fn send_params_msg<T: Serialize, S: Into<String>>(
     Ok(())
 }
 
+/// The websocket upgrade request for `url` with the headers and user agent in `config`.
+///
+/// # Errors
+///
+/// Will return [`ApiError::InvalidHeader`] if a header is not valid, or
+/// [`tungstenite::Error`] if `url` is not a websocket URL.
+pub fn handshake_request(config: &Config, url: &url::Url) -> Result<Request> {
+    let mut request = url.as_str().into_client_request()?;
+    let extra = config.header_map()?;
+    let headers = request.headers_mut();
+
+    // The configured headers replace any the handshake would send with the same name.
+    for name in extra.keys() {
+        headers.remove(name);
+    }
+
+    for (name, value) in &extra {
+        headers.append(name, value.clone());
+    }
+
+    Ok(request)
+}
+
 /// Sends an API message to the websocket server. This is helpful for non-REST requests
 /// since we are processing the data asychronously later in another thread.
 ///
@@ -123,15 +484,21 @@ pub fn send_params_msg<T: Serialize, S: Into<String>>(
 ///
 /// Will return `Err` if `ApiRequestBuilder` does not contain method or if
 /// `get_order_history_message` fails to serialize into a string.
-pub fn send_msg<S: Into<String>>(tx: &UnboundedSender<Message>, id: u64, method: S) -> Result<()> {
+pub fn send_msg<S: Into<String>>(
+    tx: &UnboundedSender<Message>,
+    id: u64,
+    method: S,
+) -> crate::error::Result<()> {
     let method = method.into();
+    let _span = trace::enter_request(id, &method);
 
-    log::info!("Sending message to {}", method);
+    trace::info!("Sending message to {}", method);
 
     let msg = ApiRequestBuilder::default()
         .with_id(id)
         .with_method(method)
         .with_nonce()
+        .on_authorized_session()
         .build()?;
 
     let msg = Message::Text(serde_json::to_string(&msg)?);
@@ -147,8 +514,10 @@ pub fn send_msg<S: Into<String>>(tx: &UnboundedSender<Message>, id: u64, method:
 ///
 /// Will return `Err` if `ApiRequestBuilder` does not contain method or if `auth_msg` fails to
 /// serialize into a string.
-pub fn respond_heartbeat(tx: &UnboundedSender<Message>, id: u64) -> Result<()> {
-    log::info!("Responding to heartbeat!");
+pub fn respond_heartbeat(tx: &UnboundedSender<Message>, id: u64) -> crate::error::Result<()> {
+    let _span = trace::enter_request(id, "public/respond-heartbeat");
+
+    trace::info!("Responding to heartbeat!");
 
     let heartbeat_msg = ApiRequestBuilder::default()
         .with_id(id)
@@ -164,17 +533,22 @@ pub fn respond_heartbeat(tx: &UnboundedSender<Message>, id: u64) -> Result<()> {
 
 /// The an authorization request to the server.
 ///
+/// Only with the `websocket` feature, the request is signed.
+///
 /// # Errors
 ///
 /// Will return `Err` if `ApiRequestBuilder` does not contain method or if `auth_msg` fails to
 /// serialize into a string.
+#[cfg(feature = "websocket")]
 pub fn auth<S: Into<String>>(
     tx: &UnboundedSender<Message>,
     id: u64,
     api_key: S,
     secret_key: S,
-) -> Result<()> {
-    log::info!("Authorizing user");
+) -> crate::error::Result<()> {
+    let _span = trace::enter_request(id, "public/auth");
+
+    trace::info!("Authorizing user");
 
     let auth_msg = ApiRequestBuilder::default()
         .with_id(id)
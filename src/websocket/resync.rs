@@ -0,0 +1,173 @@
+//! Bringing local state back in line with the exchange after a reconnect, see
+//! [`crate::controller::Controller::resync`].
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+#[cfg(feature = "native")]
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::api_response::ApiResponse;
+#[cfg(feature = "native")]
+use crate::prelude::{DataReciever, DataSender};
+#[cfg(feature = "native")]
+use crate::utils::trace;
+#[cfg(feature = "native")]
+use crate::websocket::send_data;
+use crate::websocket::subscriptions::book_instrument;
+use crate::websocket::WebsocketData;
+
+/// A request of a resync that did not succeed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ResyncFailure {
+    /// ID the request was sent with.
+    pub id: u64,
+    /// Method of the request, e.g. `private/get-open-orders`.
+    pub method: Option<String>,
+    /// Code crypto.com replied with, `None` if there was no reply.
+    pub code: Option<u64>,
+}
+
+/// Sent as [`WebsocketData::ResyncComplete`] once every request of a resync has an outcome and
+/// every book has a fresh snapshot.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ResyncComplete {
+    /// IDs of the requests, in the order they were sent. Sent with the first.
+    pub ids: Vec<u64>,
+    /// The requests that did not succeed, local state may still be stale for these.
+    pub failures: Vec<ResyncFailure>,
+}
+
+impl ResyncComplete {
+    /// Whether every request succeeded.
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Waits for the replies to the requests of a resync and the book snapshots they ask for.
+#[derive(Clone, Debug)]
+pub struct ResyncTracker {
+    /// IDs of the requests, in the order they were sent.
+    ids: Vec<u64>,
+    /// Requests without an outcome yet.
+    pending: BTreeSet<u64>,
+    /// Instrument of each book subscription request still waiting on a snapshot, by request ID.
+    books: BTreeMap<u64, String>,
+    /// The requests that did not succeed.
+    failures: Vec<ResyncFailure>,
+}
+
+impl ResyncTracker {
+    /// Wait for the requests in `ids`, and a snapshot of the `book` channel of each request in
+    /// `books`.
+    #[must_use]
+    pub fn new(ids: Vec<u64>, books: Vec<(u64, String)>) -> Self {
+        Self {
+            pending: ids.iter().copied().collect(),
+            ids,
+            books: books
+                .into_iter()
+                .filter_map(|(id, channel)| Some((id, book_instrument(&channel)?.to_owned())))
+                .collect(),
+            failures: vec![],
+        }
+    }
+
+    /// Whether every request has an outcome and every book a snapshot.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.pending.is_empty() && self.books.is_empty()
+    }
+
+    /// Update with `data`, returns the [`ResyncComplete`] once the resync is complete.
+    pub fn process(&mut self, data: &ApiResponse<WebsocketData>) -> Option<ResyncComplete> {
+        if self.is_complete() {
+            return None;
+        }
+
+        match data.result {
+            Some(WebsocketData::RequestTimeout { id, method }) => {
+                self.fail(id, Some(method.to_owned()), None);
+            }
+            Some(WebsocketData::RequestDropped(id)) => self.fail(id, None, None),
+            ref result => {
+                if let Ok(id) = u64::try_from(data.id) {
+                    match data.code {
+                        Some(code) if code != 0 => self.fail(id, data.method.clone(), Some(code)),
+                        _ => {
+                            self.pending.remove(&id);
+                        }
+                    }
+                }
+
+                if let Some(WebsocketData::Book(ref book)) = *result {
+                    self.books
+                        .retain(|_, instrument_name| *instrument_name != *book.instrument_name);
+                }
+            }
+        }
+
+        self.is_complete().then(|| self.summary())
+    }
+
+    /// The outcome of the requests so far.
+    fn summary(&self) -> ResyncComplete {
+        ResyncComplete {
+            ids: self.ids.clone(),
+            failures: self.failures.clone(),
+        }
+    }
+
+    /// Record request `id` as failed, no snapshot is waited for if it was a book subscription.
+    fn fail(&mut self, id: u64, method: Option<String>, code: Option<u64>) {
+        if !self.pending.remove(&id) {
+            return;
+        }
+
+        self.books.remove(&id);
+        self.failures.push(ResyncFailure { id, method, code });
+    }
+}
+
+/// Feed `tracker` from `data_rx`, then send the [`WebsocketData::ResyncComplete`] on `data_tx`.
+///
+/// `data_rx` must be subscribed before the first request is sent so no reply is missed.
+#[cfg(feature = "native")]
+pub(crate) async fn collect_resync(
+    mut data_rx: DataReciever,
+    data_tx: DataSender,
+    mut tracker: ResyncTracker,
+) {
+    let complete = loop {
+        if tracker.is_complete() {
+            break tracker.summary();
+        }
+
+        let data = match data_rx.recv().await {
+            Ok(data) => data,
+            Err(RecvError::Lagged(skipped)) => {
+                trace::warning!("Resync lagged behind, {skipped} messages were skipped.");
+                continue;
+            }
+            Err(RecvError::Closed) => return,
+        };
+
+        if let Some(complete) = tracker.process(&data) {
+            break complete;
+        }
+    };
+
+    send_data(
+        &data_tx,
+        ApiResponse::<WebsocketData> {
+            id: complete
+                .ids
+                .first()
+                .map_or(-1, |&id| i64::try_from(id).unwrap_or(i64::MAX)),
+            ..ApiResponse::default()
+        }
+        .websocket_data(WebsocketData::ResyncComplete(complete)),
+    );
+}
@@ -0,0 +1,306 @@
+//! Duplicate and ordering checks for market data, by book update sequence and trade ID.
+//!
+//! Replays after a reconnect, or a missed `book.update`, would otherwise leave a local book or
+//! trade history silently wrong. A [`SequenceChecker`] remembers the last sequence of each
+//! subscription it has a [`SequencePolicy`] for and either drops what was already seen or reports
+//! a [`WebsocketData::SequenceGap`] before the message.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::websocket::data::{BookRes, BookUpdateRes, TradeRes};
+use crate::websocket::handler::{HandlerContext, MessageHandler};
+use crate::websocket::market_api::acknowledge_subscription;
+use crate::websocket::WebsocketData;
+
+/// What to do with messages of a subscription that are out of order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SequencePolicy {
+    /// Drop books, book updates, and trades that were already passed on, gaps are not reported.
+    DropDuplicates,
+    /// Pass everything on, sending a [`SequenceGap`] before duplicates and book updates that do
+    /// not follow the last one.
+    ReportGaps,
+    /// Drop duplicates and send a [`SequenceGap`] before book updates that do not follow the last
+    /// one.
+    DropAndReport,
+}
+
+impl SequencePolicy {
+    /// Whether duplicates are dropped.
+    const fn drops(self) -> bool {
+        matches!(self, Self::DropDuplicates | Self::DropAndReport)
+    }
+
+    /// Whether gaps are reported.
+    const fn reports(self) -> bool {
+        matches!(self, Self::ReportGaps | Self::DropAndReport)
+    }
+}
+
+/// A message that does not follow the last one of its subscription.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct SequenceGap {
+    /// e.g. book.BTCUSD-PERP.50 or trade.BTCUSD-PERP.
+    pub subscription: Arc<str>,
+    /// Sequence or trade ID of the last message passed on.
+    pub last: u64,
+    /// `pu` of a book update, otherwise the sequence or trade ID of the message.
+    pub found: u64,
+}
+
+/// What to do with a message, from [`SequenceChecker::check`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SequenceCheck {
+    /// Send before the message.
+    pub gap: Option<SequenceGap>,
+    /// Everything in the message was a duplicate and dropped, do not send it.
+    pub drop: bool,
+}
+
+/// Checks books, book updates, and trades against the last sequence of their subscription.
+///
+/// Book snapshots and updates are checked by `u` and `pu`, trades by their numeric trade ID.
+/// Subscriptions without a policy, and other data, are passed on unchecked.
+#[derive(Clone, Debug, Default)]
+pub struct SequenceChecker {
+    /// Policy by subscription.
+    policies: HashMap<String, SequencePolicy>,
+    /// Policy for book and trade subscriptions not in `policies`.
+    default: Option<SequencePolicy>,
+    /// Last sequence or trade ID passed on, by subscription.
+    last: HashMap<String, u64>,
+}
+
+impl SequenceChecker {
+    /// A checker with no policies, it passes everything on.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check `subscription`, e.g. book.BTCUSD-PERP.50, with `policy`.
+    #[must_use]
+    pub fn with_policy(mut self, subscription: impl Into<String>, policy: SequencePolicy) -> Self {
+        self.policies.insert(subscription.into(), policy);
+
+        self
+    }
+
+    /// Check book and trade subscriptions that have no policy of their own with `policy`.
+    #[must_use]
+    pub const fn with_default_policy(mut self, policy: SequencePolicy) -> Self {
+        self.default = Some(policy);
+
+        self
+    }
+
+    /// The last sequence or trade ID passed on for `subscription`.
+    #[must_use]
+    pub fn last_sequence(&self, subscription: &str) -> Option<u64> {
+        self.last.get(subscription).copied()
+    }
+
+    /// Forget the last sequence of `subscription`, e.g. after unsubscribing.
+    pub fn reset(&mut self, subscription: &str) {
+        self.last.remove(subscription);
+    }
+
+    /// Check `data`, removing the duplicates from it if its policy drops them.
+    pub fn check(&mut self, data: &mut WebsocketData) -> SequenceCheck {
+        match *data {
+            WebsocketData::Book(ref mut books) => self.check_books(books),
+            WebsocketData::BookUpdate(ref mut updates) => self.check_book_updates(updates),
+            WebsocketData::Trade(ref mut trades) => self.check_trades(trades),
+            _ => SequenceCheck::default(),
+        }
+    }
+
+    /// The policy for `subscription`.
+    fn policy(&self, subscription: &str) -> Option<SequencePolicy> {
+        self.policies.get(subscription).copied().or(self.default)
+    }
+
+    /// Check book snapshots, a newer snapshot replaces the book so it is never a gap.
+    fn check_books(&mut self, books: &mut BookRes) -> SequenceCheck {
+        let Some(policy) = self.policy(&books.subscription) else {
+            return SequenceCheck::default();
+        };
+        let subscription = &books.subscription;
+        let mut last = self.last_sequence(subscription);
+
+        let check = retain(&mut books.data, policy, |book| {
+            let u = book.u?;
+
+            match last {
+                Some(previous) if u <= previous => Some(gap(subscription, previous, u)),
+                _ => {
+                    last = Some(u);
+
+                    None
+                }
+            }
+        });
+
+        self.finish(subscription, last, check)
+    }
+
+    /// Check book updates in order, each should follow the last by its `pu`.
+    fn check_book_updates(&mut self, updates: &mut BookUpdateRes) -> SequenceCheck {
+        let Some(policy) = self.policy(&updates.subscription) else {
+            return SequenceCheck::default();
+        };
+        let subscription = &updates.subscription;
+        let mut last = self.last_sequence(subscription);
+        let mut skipped = None;
+
+        let mut check = retain(&mut updates.data, policy, |update| match last {
+            Some(previous) if update.u <= previous => Some(gap(subscription, previous, update.pu)),
+            previous => {
+                if let Some(previous) = previous.filter(|previous| *previous != update.pu) {
+                    skipped.get_or_insert_with(|| gap(subscription, previous, update.pu));
+                }
+                last = Some(update.u);
+
+                None
+            }
+        });
+
+        if policy.reports() {
+            check.gap = check.gap.or(skipped);
+        }
+
+        self.finish(subscription, last, check)
+    }
+
+    /// Check trades, any with an ID no newer than the newest of earlier messages is a duplicate.
+    fn check_trades(&mut self, trades: &mut TradeRes) -> SequenceCheck {
+        let Some(policy) = self.policy(&trades.subscription) else {
+            return SequenceCheck::default();
+        };
+        let subscription = &trades.subscription;
+        let previous = self.last_sequence(subscription);
+        let mut newest = previous;
+
+        // Trades are sent newest first, so the IDs are only compared with earlier messages.
+        let check = retain(&mut trades.data, policy, |trade| {
+            let id = trade.d.parse::<u64>().ok()?;
+
+            match previous {
+                Some(previous) if id <= previous => Some(gap(subscription, previous, id)),
+                _ => {
+                    newest = newest.max(Some(id));
+
+                    None
+                }
+            }
+        });
+
+        self.finish(subscription, newest, check)
+    }
+
+    /// Store the new last sequence of `subscription`.
+    fn finish(
+        &mut self,
+        subscription: &str,
+        last: Option<u64>,
+        check: SequenceCheck,
+    ) -> SequenceCheck {
+        if let Some(last) = last {
+            self.last.insert(subscription.to_owned(), last);
+        }
+
+        check
+    }
+}
+
+/// A [`SequenceGap`] in `subscription`.
+fn gap(subscription: &Arc<str>, last: u64, found: u64) -> SequenceGap {
+    SequenceGap {
+        subscription: Arc::clone(subscription),
+        last,
+        found,
+    }
+}
+
+/// Run `duplicate` over `items`, it returns the gap a duplicate would be reported with.
+///
+/// Duplicates are removed if `policy` drops them, otherwise the first is reported.
+fn retain<T>(
+    items: &mut Vec<T>,
+    policy: SequencePolicy,
+    mut duplicate: impl FnMut(&T) -> Option<SequenceGap>,
+) -> SequenceCheck {
+    let mut check = SequenceCheck::default();
+    let mut duplicates = 0_usize;
+
+    items.retain(|item| {
+        let Some(gap) = duplicate(item) else {
+            return true;
+        };
+        duplicates += 1;
+
+        if policy.drops() {
+            return false;
+        }
+
+        check.gap.get_or_insert(gap);
+
+        true
+    });
+
+    check.drop = duplicates > 0 && items.is_empty();
+
+    check
+}
+
+/// `subscribe` handler that runs subscription data through a [`SequenceChecker`] before sending
+/// it, added with [`crate::controller::ControllerBuilder::with_sequence_checking`].
+#[derive(Clone, Debug, Default)]
+pub struct SequenceHandler {
+    /// Shared so the checker can be reset while the websocket is running.
+    checker: Arc<Mutex<SequenceChecker>>,
+}
+
+impl SequenceHandler {
+    /// Check subscription data with `checker`.
+    #[must_use]
+    pub fn new(checker: SequenceChecker) -> Self {
+        Self {
+            checker: Arc::new(Mutex::new(checker)),
+        }
+    }
+
+    /// The checker, e.g. to [`SequenceChecker::reset`] a subscription.
+    #[must_use]
+    pub fn checker(&self) -> Arc<Mutex<SequenceChecker>> {
+        Arc::clone(&self.checker)
+    }
+}
+
+impl MessageHandler for SequenceHandler {
+    fn handle(&self, ctx: &HandlerContext<'_>) -> Result<()> {
+        let Some(mut data) = acknowledge_subscription(ctx)? else {
+            return Ok(());
+        };
+
+        let check = self
+            .checker
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .check(&mut data);
+
+        if let Some(gap) = check.gap {
+            ctx.send(WebsocketData::SequenceGap(gap));
+        }
+
+        if !check.drop {
+            ctx.send(data);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+//! Collecting the replies to a subscription split over several requests, see
+//! [`crate::utils::action_queue::ActionQueue::subscribe_many`].
+
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::api_response::ApiResponse;
+use crate::prelude::{DataReciever, DataSender};
+use crate::utils::trace;
+use crate::websocket::{send_data, WebsocketData};
+
+/// A request of a [`SubscribeBatch`] that did not succeed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct SubscribeFailure {
+    /// ID the request was sent with.
+    pub id: u64,
+    /// Code crypto.com replied with, `None` if there was no reply.
+    pub code: Option<u64>,
+    /// Why the request failed.
+    pub message: Option<String>,
+}
+
+/// The outcome of every request a subscription was split into, sent once all of them are
+/// answered, timed out, or dropped.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct SubscribeBatch {
+    /// IDs of the requests, in the order they were sent. The batch is sent with the first.
+    pub ids: Vec<u64>,
+    /// The requests that did not succeed.
+    pub failures: Vec<SubscribeFailure>,
+}
+
+impl SubscribeBatch {
+    /// Whether every request succeeded.
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Wait on `data_rx` for the outcome of every request in `ids`, then send the
+/// [`WebsocketData::SubscribeBatch`] on `data_tx`.
+///
+/// `data_rx` must be subscribed before the first request is sent so no reply is missed.
+pub(crate) async fn collect_batch(mut data_rx: DataReciever, data_tx: DataSender, ids: Vec<u64>) {
+    let mut pending: BTreeSet<u64> = ids.iter().copied().collect();
+    let mut batch = SubscribeBatch {
+        ids,
+        failures: vec![],
+    };
+
+    while !pending.is_empty() {
+        let data = match data_rx.recv().await {
+            Ok(data) => data,
+            Err(RecvError::Lagged(skipped)) => {
+                trace::warning!("Subscribe batch lagged behind, {skipped} messages were skipped.");
+                continue;
+            }
+            Err(RecvError::Closed) => return,
+        };
+
+        let failure = match data.result {
+            Some(WebsocketData::RequestTimeout { id, .. }) if pending.remove(&id) => {
+                Some(SubscribeFailure {
+                    id,
+                    code: None,
+                    message: Some("request timed out".to_owned()),
+                })
+            }
+            Some(WebsocketData::RequestDropped(id)) if pending.remove(&id) => {
+                Some(SubscribeFailure {
+                    id,
+                    code: None,
+                    message: Some("request was dropped from the send queue".to_owned()),
+                })
+            }
+            _ => {
+                let reply = data.method.as_deref() == Some("subscribe")
+                    && u64::try_from(data.id).is_ok_and(|id| pending.remove(&id));
+
+                if !reply {
+                    continue;
+                }
+
+                data.code
+                    .filter(|code| *code != 0)
+                    .map(|code| SubscribeFailure {
+                        id: data.id.unsigned_abs(),
+                        code: Some(code),
+                        message: data.message.clone(),
+                    })
+            }
+        };
+
+        batch.failures.extend(failure);
+    }
+
+    let Some(&first) = batch.ids.first() else {
+        return;
+    };
+
+    send_data(
+        &data_tx,
+        ApiResponse::<WebsocketData> {
+            id: i64::try_from(first).unwrap_or(i64::MAX),
+            method: Some("subscribe".to_owned()),
+            ..ApiResponse::default()
+        }
+        .websocket_data(WebsocketData::SubscribeBatch(batch)),
+    );
+}
@@ -0,0 +1,104 @@
+//! The market channels subscribed through the [`crate::controller::Controller`].
+
+use std::collections::BTreeMap;
+
+use crate::websocket::actions::{BookSubscriptionType, Subscribe};
+
+/// The book options a channel was subscribed with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SubscribeOptions {
+    /// How `book` channels are delivered.
+    pub book_subscription_type: Option<BookSubscriptionType>,
+    /// Milliseconds between `book` pushes.
+    pub book_update_frequency: Option<u64>,
+}
+
+impl From<&Subscribe> for SubscribeOptions {
+    fn from(value: &Subscribe) -> Self {
+        Self {
+            book_subscription_type: value.book_subscription_type,
+            book_update_frequency: value.book_update_frequency,
+        }
+    }
+}
+
+/// The channels subscribed to, with the options each was subscribed with so it can be subscribed
+/// to again after a reconnect.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Subscriptions {
+    /// [`SubscribeOptions`] by channel.
+    channels: BTreeMap<String, SubscribeOptions>,
+}
+
+impl Subscriptions {
+    /// Record every channel of `subscribe`, replacing the options of channels already recorded.
+    pub fn insert(&mut self, subscribe: &Subscribe) {
+        let options = SubscribeOptions::from(subscribe);
+
+        for channel in &subscribe.channels {
+            self.channels.insert(channel.clone(), options);
+        }
+    }
+
+    /// Record `channel` with `options`.
+    pub fn insert_channel(&mut self, channel: impl Into<String>, options: SubscribeOptions) {
+        self.channels.insert(channel.into(), options);
+    }
+
+    /// Forget `channel`, returns the options it was subscribed with if it was recorded.
+    pub fn remove(&mut self, channel: &str) -> Option<SubscribeOptions> {
+        self.channels.remove(channel)
+    }
+
+    /// Whether `channel` is recorded.
+    #[must_use]
+    pub fn contains(&self, channel: &str) -> bool {
+        self.channels.contains_key(channel)
+    }
+
+    /// The recorded channels, in order.
+    pub fn channels(&self) -> impl Iterator<Item = &str> {
+        self.channels.keys().map(String::as_str)
+    }
+
+    /// Number of recorded channels.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Whether no channel is recorded.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+
+    /// A subscription for each recorded `book` channel with the options it was subscribed with,
+    /// sending one requests a fresh snapshot.
+    #[must_use]
+    pub fn books(&self) -> Vec<Subscribe> {
+        self.channels
+            .iter()
+            .filter(|(channel, _)| book_instrument(channel).is_some())
+            .map(|(channel, options)| Subscribe {
+                channels: vec![channel.clone()],
+                book_subscription_type: options.book_subscription_type,
+                book_update_frequency: options.book_update_frequency,
+            })
+            .collect()
+    }
+}
+
+/// The instrument of a `book.{instrument_name}` or `book.{instrument_name}.{depth}` channel,
+/// `None` for other channels.
+#[must_use]
+pub fn book_instrument(channel: &str) -> Option<&str> {
+    let mut parts = channel.split('.');
+
+    match (parts.next(), parts.next()) {
+        (Some("book"), Some(instrument_name)) if instrument_name != "update" => {
+            Some(instrument_name)
+        }
+        _ => None,
+    }
+}
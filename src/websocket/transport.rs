@@ -0,0 +1,344 @@
+//! The websocket connection the market and user streams run on.
+//!
+//! [`TungsteniteTransport`] is used by default, implement [`WsTransport`] to run the streams on
+//! anything else, e.g. a mock in tests or another TLS backend. On wasm32 the streams run on
+//! [`WebSysTransport`], the browser's websocket.
+
+use anyhow::Result;
+use futures_channel::mpsc::UnboundedReceiver;
+#[cfg(not(target_arch = "wasm32"))]
+use futures_util::future::BoxFuture;
+#[cfg(target_arch = "wasm32")]
+use futures_util::future::{select, Either, LocalBoxFuture};
+#[cfg(feature = "native")]
+use futures_util::SinkExt;
+use futures_util::{Future, StreamExt};
+#[cfg(feature = "native")]
+use tokio::net::TcpStream;
+#[cfg(feature = "native")]
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+use tungstenite::Message;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+#[cfg(target_arch = "wasm32")]
+use web_sys::{BinaryType, CloseEvent, Event, MessageEvent, WebSocket};
+
+#[cfg(feature = "native")]
+use crate::error::convert_tungstenite_error;
+use crate::prelude::ApiError;
+use crate::utils::config::Config;
+use crate::utils::request_log::{Direction, RequestLog};
+#[cfg(feature = "native")]
+use crate::websocket::handshake_request;
+
+/// `Send`, except on wasm32 where the browser's websocket cannot be sent between threads.
+#[cfg(not(target_arch = "wasm32"))]
+pub trait MaybeSend: Send {}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Send> MaybeSend for T {}
+
+/// `Send`, except on wasm32 where the browser's websocket cannot be sent between threads.
+#[cfg(target_arch = "wasm32")]
+pub trait MaybeSend {}
+
+#[cfg(target_arch = "wasm32")]
+impl<T> MaybeSend for T {}
+
+/// The futures of a [`WsTransport`], `Send` except on wasm32.
+#[cfg(not(target_arch = "wasm32"))]
+pub type TransportFuture<'a, T> = BoxFuture<'a, T>;
+
+/// The futures of a [`WsTransport`], `Send` except on wasm32.
+#[cfg(target_arch = "wasm32")]
+pub type TransportFuture<'a, T> = LocalBoxFuture<'a, T>;
+
+/// A websocket connection.
+pub trait WsTransport: MaybeSend + Sized + 'static {
+    /// Connect to `url`, sending the headers and user agent in `config` with the handshake, see
+    /// [`handshake_request`].
+    fn connect(config: &Config, url: &url::Url) -> TransportFuture<'static, Result<Self>>;
+
+    /// Send `message`.
+    fn send(&mut self, message: Message) -> TransportFuture<'_, Result<()>>;
+
+    /// The next message, `None` once the connection has ended.
+    ///
+    /// Must be cancel safe, the future is dropped whenever a message is sent first.
+    fn receive(&mut self) -> TransportFuture<'_, Option<Result<Message>>>;
+
+    /// Close the connection.
+    fn close(&mut self) -> TransportFuture<'_, Result<()>>;
+}
+
+/// A [`WsTransport`] on [`tokio_tungstenite`].
+#[cfg(feature = "native")]
+#[derive(Debug)]
+pub struct TungsteniteTransport {
+    /// The connected websocket.
+    stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+}
+
+#[cfg(feature = "native")]
+impl WsTransport for TungsteniteTransport {
+    fn connect(config: &Config, url: &url::Url) -> TransportFuture<'static, Result<Self>> {
+        let request = handshake_request(config, url);
+
+        Box::pin(async move {
+            let (stream, _) = connect_async(request?)
+                .await
+                .map_err(convert_tungstenite_error)?;
+
+            Ok(Self { stream })
+        })
+    }
+
+    fn send(&mut self, message: Message) -> TransportFuture<'_, Result<()>> {
+        Box::pin(async move {
+            Ok(self
+                .stream
+                .send(message)
+                .await
+                .map_err(convert_tungstenite_error)?)
+        })
+    }
+
+    fn receive(&mut self) -> TransportFuture<'_, Option<Result<Message>>> {
+        Box::pin(async move {
+            Some(
+                self.stream
+                    .next()
+                    .await?
+                    .map_err(|err| anyhow::Error::from(convert_tungstenite_error(err))),
+            )
+        })
+    }
+
+    fn close(&mut self) -> TransportFuture<'_, Result<()>> {
+        Box::pin(async move {
+            Ok(self
+                .stream
+                .close(None)
+                .await
+                .map_err(convert_tungstenite_error)?)
+        })
+    }
+}
+
+/// A [`WsTransport`] on the browser's [`WebSocket`].
+///
+/// Browsers do not let the handshake carry headers, the headers and user agent in the config are
+/// not sent. The browser answers pings itself, sending a ping or pong does nothing.
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug)]
+pub struct WebSysTransport {
+    /// The connected websocket.
+    socket: WebSocket,
+    /// Messages and errors from the event handlers, ends once the websocket has closed.
+    incoming: UnboundedReceiver<Result<Message>>,
+    /// Handler of `open`, only called while connecting. The handlers are kept alive here for as
+    /// long as they are set on `socket`.
+    _on_open: Closure<dyn FnMut(Event)>,
+    /// Handler of `message`.
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+    /// Handler of `error`.
+    _on_error: Closure<dyn FnMut(Event)>,
+    /// Handler of `close`.
+    _on_close: Closure<dyn FnMut(CloseEvent)>,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl WsTransport for WebSysTransport {
+    fn connect(_config: &Config, url: &url::Url) -> TransportFuture<'static, Result<Self>> {
+        let url = url.clone();
+
+        Box::pin(async move {
+            let socket = WebSocket::new(url.as_str()).map_err(js_error)?;
+            socket.set_binary_type(BinaryType::Arraybuffer);
+
+            let (incoming_tx, incoming) = futures_channel::mpsc::unbounded();
+            let (open_tx, open_rx) = futures_channel::oneshot::channel();
+
+            let mut open_tx = Some(open_tx);
+            let on_open = Closure::<dyn FnMut(Event)>::new(move |_| {
+                if let Some(open_tx) = open_tx.take() {
+                    // The transport stopped waiting if this fails.
+                    let _ = open_tx.send(());
+                }
+            });
+
+            let message_tx = incoming_tx.clone();
+            let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+                let data = event.data();
+                let message = match data.as_string() {
+                    Some(text) => Message::Text(text),
+                    None => Message::Binary(js_sys::Uint8Array::new(&data).to_vec()),
+                };
+
+                // Fails only once the transport has been dropped.
+                let _ = message_tx.unbounded_send(Ok(message));
+            });
+
+            let error_tx = incoming_tx.clone();
+            let on_error = Closure::<dyn FnMut(Event)>::new(move |_| {
+                // Browsers do not say what went wrong.
+                let _ =
+                    error_tx.unbounded_send(Err(ApiError::Io("websocket error".to_owned()).into()));
+            });
+
+            let on_close = Closure::<dyn FnMut(CloseEvent)>::new(move |event: CloseEvent| {
+                let frame = tungstenite::protocol::CloseFrame {
+                    code: event.code().into(),
+                    reason: event.reason().into(),
+                };
+
+                let _ = incoming_tx.unbounded_send(Ok(Message::Close(Some(frame))));
+                incoming_tx.close_channel();
+            });
+
+            socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+            socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+            socket.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+            socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+            let mut transport = Self {
+                socket,
+                incoming,
+                _on_open: on_open,
+                _on_message: on_message,
+                _on_error: on_error,
+                _on_close: on_close,
+            };
+
+            // A failed handshake is reported with `error` and `close` instead of `open`.
+            let opened = match select(open_rx, transport.incoming.next()).await {
+                Either::Left(_) => Ok(()),
+                Either::Right((Some(Err(err)), _)) => Err(err),
+                Either::Right(_) => Err(ApiError::ConnectionClosed {
+                    code: None,
+                    reason: "closed during the handshake".to_owned(),
+                }
+                .into()),
+            };
+            opened?;
+            transport.socket.set_onopen(None);
+
+            Ok(transport)
+        })
+    }
+
+    fn send(&mut self, message: Message) -> TransportFuture<'_, Result<()>> {
+        let sent = match message {
+            Message::Text(text) => self.socket.send_with_str(&text),
+            Message::Binary(data) => self.socket.send_with_u8_array(&data),
+            Message::Close(Some(frame)) => self
+                .socket
+                .close_with_code_and_reason(frame.code.into(), &frame.reason),
+            Message::Close(None) => self.socket.close(),
+            Message::Ping(_) | Message::Pong(_) | Message::Frame(_) => Ok(()),
+        };
+
+        Box::pin(async move { sent.map_err(js_error) })
+    }
+
+    fn receive(&mut self) -> TransportFuture<'_, Option<Result<Message>>> {
+        Box::pin(self.incoming.next())
+    }
+
+    fn close(&mut self) -> TransportFuture<'_, Result<()>> {
+        let closed = self.socket.close();
+
+        Box::pin(async move { closed.map_err(js_error) })
+    }
+}
+
+/// Removes the event handlers before they are freed, and closes the websocket if it is open.
+#[cfg(target_arch = "wasm32")]
+impl Drop for WebSysTransport {
+    fn drop(&mut self) {
+        self.socket.set_onopen(None);
+        self.socket.set_onmessage(None);
+        self.socket.set_onerror(None);
+        self.socket.set_onclose(None);
+
+        // Closing a closed websocket does nothing.
+        let _ = self.socket.close();
+    }
+}
+
+/// A JS exception thrown by the browser's websocket as an [`ApiError::Io`].
+#[cfg(target_arch = "wasm32")]
+fn js_error(err: JsValue) -> anyhow::Error {
+    ApiError::Io(format!("{err:?}")).into()
+}
+
+/// Send the messages from `outgoing` on `transport` and pass the messages it recieves to
+/// `process`, until either ends.
+///
+/// `transport` is closed once `outgoing` ends. Each frame is logged with `request_log`, if set.
+///
+/// # Errors
+///
+/// Will return `Err` if sending or recieving fails, or if `process` fails. Will return
+/// [`ApiError::ConnectionClosed`] if the connection ends after crypto.com sent a close frame that
+/// was not a reply to one sent on `outgoing`.
+pub(crate) async fn drive<T, F, Fut>(
+    mut transport: T,
+    mut outgoing: UnboundedReceiver<Message>,
+    request_log: Option<&RequestLog>,
+    mut process: F,
+) -> Result<()>
+where
+    T: WsTransport,
+    F: FnMut(Message) -> Fut,
+    Fut: Future<Output = Result<(), ApiError>>,
+{
+    // Whether a close frame was sent, the close frame recieved after it is only the reply.
+    let mut closing = false;
+    // The close frame crypto.com sent first, if any.
+    let mut closed_by_server: Option<ApiError> = None;
+
+    loop {
+        tokio::select! {
+            message = outgoing.next() => {
+                let Some(message) = message else {
+                    return transport.close().await;
+                };
+
+                if let Some(log) = request_log {
+                    log.log(Direction::Request, &message);
+                }
+
+                closing |= matches!(message, Message::Close(_));
+                transport.send(message).await?;
+            }
+            message = transport.receive() => {
+                let Some(message) = message else {
+                    return match closed_by_server {
+                        Some(err) => Err(err.into()),
+                        None => Ok(()),
+                    };
+                };
+                let message = message?;
+
+                if let Some(log) = request_log {
+                    log.log(Direction::Response, &message);
+                }
+
+                if let Message::Close(ref frame) = message {
+                    if !closing {
+                        closed_by_server = Some(ApiError::ConnectionClosed {
+                            code: frame.as_ref().map(|frame| u16::from(frame.code)),
+                            reason: frame
+                                .as_ref()
+                                .map(|frame| frame.reason.to_string())
+                                .unwrap_or_default(),
+                        });
+                    }
+                }
+
+                process(message).await?;
+            }
+        }
+    }
+}
@@ -1,28 +1,43 @@
 //! The interaction systems for the websocket User API.
 
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 use anyhow::Result;
+#[cfg(feature = "native")]
 use futures_channel::mpsc::UnboundedSender;
-use futures_util::future::Either;
-use futures_util::{future, pin_mut, StreamExt, TryStreamExt};
+use futures_util::Future;
+#[cfg(feature = "native")]
+use futures_util::StreamExt;
+use serde::Deserialize;
 use tokio::sync::Mutex;
+#[cfg(feature = "native")]
 use tokio::task::JoinHandle;
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tungstenite::Message;
 
 use crate::api_response::ApiResponse;
-use crate::error::{convert_tungstenite_error, processing_error};
 use crate::prelude::{ApiError, DataSender, MessageSender};
-use crate::rest::data::{InstrumentsRes, RawInstrumentsRes};
-use crate::utils::action::ActionStore;
+use crate::rest::data::{DepositAddress, DepositAddressItem, InstrumentsRes, RawInstrumentsRes};
+use crate::utils::action::{Action, ActionStore};
 use crate::utils::config::Config;
-use crate::utils::{message_to_api_response, reprocess_data};
+use crate::utils::in_flight::{Api, InFlight};
+use crate::utils::raw_hook::RawMessageHook;
+use crate::utils::trace;
+use crate::utils::{get_epoch_ms, message_to_api_response, reprocess_value};
+use crate::websocket::actions::ActionKind;
+use crate::websocket::connection_state::{send_state, ConnectionState};
 use crate::websocket::data::{
-    AccountSummary, CancelOrderList, CreateOrder, CreateOrderList, CreateWithdrawal, OpenOrders,
-    OrderDetail, OrderHistory, RawRes, RawUserTradeRes, Trades, UserBalance, UserOrderRes,
-    UserTradeRes, WithdrawalHistory,
+    AccountSummary, CancelOrder, CancelOrderList, CreateOrder, CreateOrderList, CreateWithdrawal,
+    EventKind, OpenOrders, OrderDetail, OrderHistory, Page, RawUserTradeRes, SubscriptionPayload,
+    Trades, UserBalance, UserOrderRes, UserTradeRes, WithdrawalHistory,
+};
+use crate::websocket::handler::{dispatch, HandlerContext, HandlerRegistry};
+#[cfg(feature = "native")]
+use crate::websocket::transport::TungsteniteTransport;
+use crate::websocket::transport::{drive, WsTransport};
+use crate::websocket::{
+    handle_processing_error, respond_heartbeat, send_data, send_subscribe_ack, unsubscribe_result,
+    WebsocketData,
 };
-use crate::websocket::{respond_heartbeat, WebsocketData};
 
 use super::data::Scope;
 
@@ -40,6 +55,7 @@ pub async fn process_user_actions(action: ActionStore, user_tx: MessageSender) -
 }
 
 /// Initialize the market action processing system.
+#[cfg(feature = "native")]
 pub async fn initialize_user_actions(
     user_tx_arc: MessageSender,
 ) -> (JoinHandle<Result<()>>, UnboundedSender<ActionStore>) {
@@ -49,7 +65,10 @@ pub async fn initialize_user_actions(
         let user_tx_arc = Arc::clone(&user_tx_arc);
 
         while let Some(item) = actions_rx.next().await {
-            process_user_actions(item, Arc::clone(&user_tx_arc)).await?;
+            // The request stays in flight and is replayed when the websocket reconnects.
+            if let Err(err) = process_user_actions(item, Arc::clone(&user_tx_arc)).await {
+                trace::warning!("Failed to send user action, it is queued for replay: {err:#}");
+            }
         }
 
         Ok(())
@@ -58,77 +77,125 @@ pub async fn initialize_user_actions(
     (join_handle, actions_tx)
 }
 
-/// Initialize the user websocket stream.
+/// Initialize the user websocket stream on a [`TungsteniteTransport`].
 ///
 /// # Errors
 ///
-/// Will return [`tungstenite::error::Error`] if [`connect_async`] fails.
+/// Will return the errors of [`initialize_user_stream_with`].
+#[cfg(feature = "native")]
 pub async fn initialize_user_stream(
     config: &Config,
-    data_tx_arc: DataSender,
+    data_tx: DataSender,
+    in_flight: Arc<InFlight>,
+    raw_hook: Option<RawMessageHook>,
+) -> Result<(JoinHandle<Result<()>>, MessageSender)> {
+    initialize_user_stream_with::<TungsteniteTransport>(config, data_tx, in_flight, raw_hook).await
+}
+
+/// Initialize the user websocket stream on a `T` connected with [`WsTransport::connect`].
+///
+/// # Errors
+///
+/// Will return `Err` if [`WsTransport::connect`] fails.
+///
+/// Will return [`ApiError::InvalidHeader`] if a header in `config` is not valid.
+#[cfg(feature = "native")]
+pub async fn initialize_user_stream_with<T: WsTransport>(
+    config: &Config,
+    data_tx: DataSender,
+    in_flight: Arc<InFlight>,
+    raw_hook: Option<RawMessageHook>,
 ) -> Result<(JoinHandle<Result<()>>, MessageSender)> {
-    let (user_tx, user_rx) = futures_channel::mpsc::unbounded();
-    let user_tx_arc = Arc::new(Mutex::new(user_tx));
     let Some(ref websocket_user_api) = config.websocket_user_api else {
         anyhow::bail!(ApiError::ConfigMissing("websocket_user_api".to_owned()));
     };
 
-    let (user_stream, _) = connect_async(websocket_user_api).await?;
-    log::info!("WebSocket User API handshake has been successfully completed.");
+    let transport = T::connect(config, websocket_user_api).await?;
+    trace::info!("WebSocket User API handshake has been successfully completed.");
 
-    {
-        let data_tx_arc = Arc::clone(&data_tx_arc);
-        let data_tx = data_tx_arc.lock().await;
+    Ok(spawn_user_stream(
+        transport, config, data_tx, in_flight, raw_hook,
+    ))
+}
 
-        data_tx.unbounded_send(
-            ApiResponse::<WebsocketData>::default().websocket_data(WebsocketData::UserHandshake),
-        )?;
-    }
+/// Process the messages of the user api recieved on `transport`, which is already connected.
+///
+/// Returns the handle of the task processing the stream and the sender for messages to the
+/// user api.
+#[cfg(feature = "native")]
+pub fn spawn_user_stream<T: WsTransport>(
+    transport: T,
+    config: &Config,
+    data_tx: DataSender,
+    in_flight: Arc<InFlight>,
+    raw_hook: Option<RawMessageHook>,
+) -> (JoinHandle<Result<()>>, MessageSender) {
+    let (stream, user_tx_arc) = user_stream(transport, config, data_tx, in_flight, raw_hook);
 
-    let (user_write, user_read) = user_stream.split();
-    let rx_to_user = user_rx.map(Ok).forward(user_write);
+    (tokio::spawn(stream), user_tx_arc)
+}
 
-    let join_handle: JoinHandle<Result<()>> = {
-        let user_tx_arc = Arc::clone(&user_tx_arc);
+/// Process the messages of the user api recieved on `transport`, which is already connected, on
+/// any executor, see [`crate::websocket::market_api::market_stream`].
+///
+/// Returns the future processing the stream until the connection ends, and the sender for
+/// messages to the user api.
+pub fn user_stream<T: WsTransport>(
+    transport: T,
+    config: &Config,
+    data_tx: DataSender,
+    in_flight: Arc<InFlight>,
+    raw_hook: Option<RawMessageHook>,
+) -> (impl Future<Output = Result<()>>, MessageSender) {
+    let (user_tx, user_rx) = futures_channel::mpsc::unbounded();
+    let user_tx_arc = Arc::new(Mutex::new(user_tx));
 
-        tokio::spawn(async move {
-            let user_to_process = {
-                user_read
-                    .map_err(convert_tungstenite_error)
-                    .try_for_each(|message| async {
-                        match process_user(
-                            message,
-                            Arc::clone(&user_tx_arc),
-                            Arc::clone(&data_tx_arc),
-                        )
-                        .await
-                        {
-                            Ok(res) => Ok(res),
-                            Err(err) => Err(processing_error(err)),
-                        }
-                    })
-            };
+    send_data(
+        &data_tx,
+        ApiResponse::<WebsocketData>::default().websocket_data(WebsocketData::UserHandshake),
+    );
+    send_state(&data_tx, ConnectionState::Connected(Api::User));
 
-            pin_mut!(rx_to_user, user_to_process);
-            match future::select(rx_to_user, user_to_process).await {
-                Either::Left((_rx_to_user_res, _)) => {
-                    log::info!("User process completed");
+    let error_policy = config.error_policy;
+    let handlers = config.user_handlers.clone();
+    let request_log = config.request_log.clone();
 
-                    Ok(())
-                }
-                Either::Right((user_to_process_res, _)) => match user_to_process_res {
-                    Ok(_) => {
-                        log::info!("User process completed");
+    let stream = {
+        let user_tx_arc = Arc::clone(&user_tx_arc);
 
-                        Ok(())
+        async move {
+            let res = drive(transport, user_rx, request_log.as_ref(), |message| {
+                let user_tx_arc = Arc::clone(&user_tx_arc);
+                let data_tx = &data_tx;
+                let in_flight = &in_flight;
+                let raw_hook = raw_hook.as_ref();
+                let handlers = &handlers;
+
+                async move {
+                    match trace::in_message_span(
+                        "user",
+                        process_user(message, user_tx_arc, data_tx, in_flight, raw_hook, handlers),
+                    )
+                    .await
+                    {
+                        Ok(res) => Ok(res),
+                        Err(err) => handle_processing_error(error_policy, Api::User, err, data_tx),
                     }
-                    Err(err) => anyhow::bail!(err),
-                },
+                }
+            })
+            .await;
+
+            if res.is_ok() {
+                trace::info!("User process completed");
             }
-        })
+
+            send_state(&data_tx, ConnectionState::disconnected(Api::User, &res));
+
+            res
+        }
     };
 
-    Ok((join_handle, user_tx_arc))
+    (stream, user_tx_arc)
 }
 
 /// Handle the `public/get-instruments` result.
@@ -136,23 +203,13 @@ pub async fn initialize_user_stream(
 /// # Errors
 ///
 /// Will return [`serde_json::Error`] if [`serde_json::from_str`] cannot process the result string.
-///
-/// Will return [`futures_channel::mpsc::TrySendError`] if `unbounded_send` fails anywhere.
-async fn public_get_instruments(
-    arc_tx: &DataSender,
-    msg: &ApiResponse<serde_json::Value>,
-) -> Result<()> {
-    let Some(ref res) = msg.result else {
-        log::warn!("Message had no result. {msg:#?}");
-
+fn public_get_instruments(ctx: &HandlerContext<'_>) -> Result<()> {
+    let Some(res) = ctx.result() else {
         return Ok(());
     };
 
-    let tx = arc_tx.lock().await;
-
-    let instrument_data = reprocess_data::<RawInstrumentsRes, InstrumentsRes>(&res.to_string())?;
-    tx.unbounded_send(msg.websocket_data(WebsocketData::GetInstruments(instrument_data)))?;
-    drop(tx);
+    let instrument_data = reprocess_value::<RawInstrumentsRes, InstrumentsRes>(res)?;
+    ctx.send(WebsocketData::GetInstruments(instrument_data));
 
     Ok(())
 }
@@ -162,23 +219,45 @@ async fn public_get_instruments(
 /// # Errors
 ///
 /// Will return [`serde_json::Error`] if [`serde_json::from_str`] cannot process the result string.
-///
-/// Will return [`futures_channel::mpsc::TrySendError`] if `unbounded_send` fails anywhere.
-async fn private_create_withdrawal(
-    arc_tx: &DataSender,
-    msg: &ApiResponse<serde_json::Value>,
-) -> Result<()> {
-    let Some(ref res) = msg.result else {
-        log::warn!("Message had no result. {msg:#?}");
+fn private_create_withdrawal(ctx: &HandlerContext<'_>) -> Result<()> {
+    let Some(res) = ctx.result() else {
+        return Ok(());
+    };
+
+    let create_withdrawal_data = CreateWithdrawal::deserialize(res)?;
+    ctx.send(WebsocketData::CreateWithdrawal(create_withdrawal_data));
+
+    Ok(())
+}
 
+/// Handle the `private/get-deposit-address` result.
+///
+/// # Errors
+///
+/// Will return [`serde_json::Error`] if [`serde_json::from_str`] cannot process the result string.
+fn private_get_deposit_address(ctx: &HandlerContext<'_>) -> Result<()> {
+    let Some(res) = ctx.result() else {
         return Ok(());
     };
 
-    let tx = arc_tx.lock().await;
+    let deposit_address_data = DepositAddress::deserialize(res)?;
+    ctx.send(WebsocketData::GetDepositAddress(deposit_address_data));
+
+    Ok(())
+}
+
+/// Handle the `private/create-deposit-address` result.
+///
+/// # Errors
+///
+/// Will return [`serde_json::Error`] if [`serde_json::from_str`] cannot process the result string.
+fn private_create_deposit_address(ctx: &HandlerContext<'_>) -> Result<()> {
+    let Some(res) = ctx.result() else {
+        return Ok(());
+    };
 
-    let create_withdrawal_data: CreateWithdrawal = serde_json::from_str(&res.to_string())?;
-    tx.unbounded_send(msg.websocket_data(WebsocketData::CreateWithdrawal(create_withdrawal_data)))?;
-    drop(tx);
+    let deposit_address_data = DepositAddressItem::deserialize(res)?;
+    ctx.send(WebsocketData::CreateDepositAddress(deposit_address_data));
 
     Ok(())
 }
@@ -188,51 +267,42 @@ async fn private_create_withdrawal(
 /// # Errors
 ///
 /// Will return [`serde_json::Error`] if [`serde_json::from_str`] cannot process the result string.
-///
-/// Will return [`futures_channel::mpsc::TrySendError`] if `unbounded_send` fails anywhere.
-async fn private_get_withdrawal_history(
-    arc_tx: &DataSender,
-    msg: &ApiResponse<serde_json::Value>,
-) -> Result<()> {
-    let Some(ref res) = msg.result else {
-        log::warn!("Message had no result. {msg:#?}");
-
+fn private_get_withdrawal_history(ctx: &HandlerContext<'_>) -> Result<()> {
+    let Some(res) = ctx.result() else {
         return Ok(());
     };
 
-    let tx = arc_tx.lock().await;
-
-    let withdrawal_history_data: WithdrawalHistory = serde_json::from_str(&res.to_string())?;
-    tx.unbounded_send(
-        msg.websocket_data(WebsocketData::GetWithdrawalHistory(withdrawal_history_data)),
-    )?;
-    drop(tx);
+    let mut withdrawal_history_data = WithdrawalHistory::deserialize(res)?;
+    withdrawal_history_data.page = request_page(ctx);
+    ctx.send(WebsocketData::GetWithdrawalHistory(withdrawal_history_data));
 
     Ok(())
 }
 
+/// The page requested by the paginated request `ctx` answers, the exchange defaults if it is not
+/// known.
+fn request_page(ctx: &HandlerContext<'_>) -> Page {
+    match ctx.request {
+        Some(ActionKind::GetOrderHistory(request)) => Page::from(request.0.page),
+        Some(ActionKind::GetOpenOrders(request)) => Page::from(request.page),
+        Some(ActionKind::GetTrades(request)) => Page::from(request.0.page),
+        Some(ActionKind::GetWithdrawalHistory(request)) => Page::from(request.0.page),
+        _ => Page::default(),
+    }
+}
+
 /// Handle the `private/get-account-summary` result.
 ///
 /// # Errors
 ///
 /// Will return [`serde_json::Error`] if [`serde_json::from_str`] cannot process the result string.
-///
-/// Will return [`futures_channel::mpsc::TrySendError`] if `unbounded_send` fails anywhere.
-async fn private_get_account_summary(
-    arc_tx: &DataSender,
-    msg: &ApiResponse<serde_json::Value>,
-) -> Result<()> {
-    let Some(ref res) = msg.result else {
-        log::warn!("Message had no result. {msg:#?}");
-
+fn private_get_account_summary(ctx: &HandlerContext<'_>) -> Result<()> {
+    let Some(res) = ctx.result() else {
         return Ok(());
     };
 
-    let tx = arc_tx.lock().await;
-
-    let account_summary_data: AccountSummary = serde_json::from_str(&res.to_string())?;
-    tx.unbounded_send(msg.websocket_data(WebsocketData::GetAccountSummary(account_summary_data)))?;
-    drop(tx);
+    let account_summary_data = AccountSummary::deserialize(res)?;
+    ctx.send(WebsocketData::GetAccountSummary(account_summary_data));
 
     Ok(())
 }
@@ -242,23 +312,13 @@ async fn private_get_account_summary(
 /// # Errors
 ///
 /// Will return [`serde_json::Error`] if [`serde_json::from_str`] cannot process the result string.
-///
-/// Will return [`futures_channel::mpsc::TrySendError`] if `unbounded_send` fails anywhere.
-async fn private_create_order(
-    arc_tx: &DataSender,
-    msg: &ApiResponse<serde_json::Value>,
-) -> Result<()> {
-    let Some(ref res) = msg.result else {
-        log::warn!("Message had no result. {msg:#?}");
-
+fn private_create_order(ctx: &HandlerContext<'_>) -> Result<()> {
+    let Some(res) = ctx.result() else {
         return Ok(());
     };
 
-    let tx = arc_tx.lock().await;
-
-    let create_order_data: CreateOrder = serde_json::from_str(&res.to_string())?;
-    tx.unbounded_send(msg.websocket_data(WebsocketData::CreateOrder(create_order_data)))?;
-    drop(tx);
+    let create_order_data = CreateOrder::deserialize(res)?;
+    ctx.send(WebsocketData::CreateOrder(create_order_data));
 
     Ok(())
 }
@@ -268,23 +328,13 @@ async fn private_create_order(
 /// # Errors
 ///
 /// Will return [`serde_json::Error`] if [`serde_json::from_str`] cannot process the result string.
-///
-/// Will return [`futures_channel::mpsc::TrySendError`] if `unbounded_send` fails anywhere.
-async fn private_create_order_list(
-    arc_tx: &DataSender,
-    msg: &ApiResponse<serde_json::Value>,
-) -> Result<()> {
-    let Some(ref res) = msg.result else {
-        log::warn!("Message had no result. {msg:#?}");
-
+fn private_create_order_list(ctx: &HandlerContext<'_>) -> Result<()> {
+    let Some(res) = ctx.result() else {
         return Ok(());
     };
 
-    let tx = arc_tx.lock().await;
-
-    let create_order_list_data: CreateOrderList = serde_json::from_str(&res.to_string())?;
-    tx.unbounded_send(msg.websocket_data(WebsocketData::CreateOrderList(create_order_list_data)))?;
-    drop(tx);
+    let create_order_list_data = CreateOrderList::deserialize(res)?;
+    ctx.send(WebsocketData::CreateOrderList(create_order_list_data));
 
     Ok(())
 }
@@ -294,23 +344,36 @@ async fn private_create_order_list(
 /// # Errors
 ///
 /// Will return [`serde_json::Error`] if [`serde_json::from_str`] cannot process the result string.
-///
-/// Will return [`futures_channel::mpsc::TrySendError`] if `unbounded_send` fails anywhere.
-async fn private_cancel_order_list(
-    arc_tx: &DataSender,
-    msg: &ApiResponse<serde_json::Value>,
-) -> Result<()> {
-    let Some(ref res) = msg.result else {
-        log::warn!("Message had no result. {msg:#?}");
-
+fn private_cancel_order_list(ctx: &HandlerContext<'_>) -> Result<()> {
+    let Some(res) = ctx.result() else {
         return Ok(());
     };
 
-    let tx = arc_tx.lock().await;
+    let cancel_order_list_data = CancelOrderList::deserialize(res)?;
+    ctx.send(WebsocketData::CancelOrderList(cancel_order_list_data));
+
+    Ok(())
+}
+
+/// Handle the `private/cancel-order` result, with the order taken from the request it answers.
+///
+/// # Errors
+///
+/// Will return [`std::num::TryFromIntError`] if the message ID is negative.
+fn private_cancel_order(ctx: &HandlerContext<'_>) -> Result<()> {
+    let msg = ctx.msg;
+    let request = match ctx.request {
+        Some(ActionKind::CancelOrder(request)) => Some(request),
+        _ => None,
+    };
 
-    let cancel_order_list_data: CancelOrderList = serde_json::from_str(&res.to_string())?;
-    tx.unbounded_send(msg.websocket_data(WebsocketData::CancelOrderList(cancel_order_list_data)))?;
-    drop(tx);
+    let cancel_order_data = CancelOrder::new(
+        msg.id.try_into()?,
+        msg.code.unwrap_or_default(),
+        msg.message.clone(),
+        request,
+    );
+    ctx.send(WebsocketData::CancelOrder(cancel_order_data));
 
     Ok(())
 }
@@ -320,16 +383,10 @@ async fn private_cancel_order_list(
 /// # Errors
 ///
 /// Will return [`serde_json::Error`] if [`serde_json::from_str`] cannot process the result string.
-///
-/// Will return [`futures_channel::mpsc::TrySendError`] if `unbounded_send` fails anywhere.
-async fn private_cancel_all_orders(
-    arc_tx: &DataSender,
-    msg: &ApiResponse<serde_json::Value>,
-) -> Result<()> {
-    let tx = arc_tx.lock().await;
-
-    tx.unbounded_send(msg.websocket_data(WebsocketData::CancelAllOrders))?;
-    drop(tx);
+fn private_cancel_all_orders(ctx: &HandlerContext<'_>) -> Result<()> {
+    if !ctx.send_error() {
+        ctx.send(WebsocketData::CancelAllOrders);
+    }
 
     Ok(())
 }
@@ -339,23 +396,14 @@ async fn private_cancel_all_orders(
 /// # Errors
 ///
 /// Will return [`serde_json::Error`] if [`serde_json::from_str`] cannot process the result string.
-///
-/// Will return [`futures_channel::mpsc::TrySendError`] if `unbounded_send` fails anywhere.
-async fn private_get_order_history(
-    arc_tx: &DataSender,
-    msg: &ApiResponse<serde_json::Value>,
-) -> Result<()> {
-    let Some(ref res) = msg.result else {
-        log::warn!("Message had no result. {msg:#?}");
-
+fn private_get_order_history(ctx: &HandlerContext<'_>) -> Result<()> {
+    let Some(res) = ctx.result() else {
         return Ok(());
     };
 
-    let tx = arc_tx.lock().await;
-
-    let order_history_data: OrderHistory = serde_json::from_str(&res.to_string())?;
-    tx.unbounded_send(msg.websocket_data(WebsocketData::GetOrderHistory(order_history_data)))?;
-    drop(tx);
+    let mut order_history_data = OrderHistory::deserialize(res)?;
+    order_history_data.page = request_page(ctx);
+    ctx.send(WebsocketData::GetOrderHistory(order_history_data));
 
     Ok(())
 }
@@ -365,23 +413,14 @@ async fn private_get_order_history(
 /// # Errors
 ///
 /// Will return [`serde_json::Error`] if [`serde_json::from_str`] cannot process the result string.
-///
-/// Will return [`futures_channel::mpsc::TrySendError`] if `unbounded_send` fails anywhere.
-async fn private_get_open_orders(
-    arc_tx: &DataSender,
-    msg: &ApiResponse<serde_json::Value>,
-) -> Result<()> {
-    let Some(ref res) = msg.result else {
-        log::warn!("Message had no result. {msg:#?}");
-
+fn private_get_open_orders(ctx: &HandlerContext<'_>) -> Result<()> {
+    let Some(res) = ctx.result() else {
         return Ok(());
     };
 
-    let tx = arc_tx.lock().await;
-
-    let open_orders_data: OpenOrders = serde_json::from_str(&res.to_string())?;
-    tx.unbounded_send(msg.websocket_data(WebsocketData::GetOpenOrders(open_orders_data)))?;
-    drop(tx);
+    let mut open_orders_data = OpenOrders::deserialize(res)?;
+    open_orders_data.page = request_page(ctx);
+    ctx.send(WebsocketData::GetOpenOrders(open_orders_data));
 
     Ok(())
 }
@@ -391,25 +430,13 @@ async fn private_get_open_orders(
 /// # Errors
 ///
 /// Will return [`serde_json::Error`] if [`serde_json::from_str`] cannot process the result string.
-///
-/// Will return [`futures_channel::mpsc::TrySendError`] if `unbounded_send` fails anywhere.
-async fn private_get_order_detail(
-    arc_tx: &DataSender,
-    msg: &ApiResponse<serde_json::Value>,
-) -> Result<()> {
-    let Some(ref res) = msg.result else {
-        log::warn!("Message had no result. {msg:#?}");
-
+fn private_get_order_detail(ctx: &HandlerContext<'_>) -> Result<()> {
+    let Some(res) = ctx.result() else {
         return Ok(());
     };
 
-    let tx = arc_tx.lock().await;
-
-    let order_detail_data: OrderDetail = serde_json::from_str(&res.to_string())?;
-    tx.unbounded_send(
-        msg.websocket_data(WebsocketData::GetOrderDetail(Box::new(order_detail_data))),
-    )?;
-    drop(tx);
+    let order_detail_data = OrderDetail::deserialize(res)?;
+    ctx.send(WebsocketData::GetOrderDetail(Box::new(order_detail_data)));
 
     Ok(())
 }
@@ -419,23 +446,14 @@ async fn private_get_order_detail(
 /// # Errors
 ///
 /// Will return [`serde_json::Error`] if [`serde_json::from_str`] cannot process the result string.
-///
-/// Will return [`futures_channel::mpsc::TrySendError`] if `unbounded_send` fails anywhere.
-async fn private_get_trades(
-    arc_tx: &DataSender,
-    msg: &ApiResponse<serde_json::Value>,
-) -> Result<()> {
-    let Some(ref res) = msg.result else {
-        log::warn!("Message had no result. {msg:#?}");
-
+fn private_get_trades(ctx: &HandlerContext<'_>) -> Result<()> {
+    let Some(res) = ctx.result() else {
         return Ok(());
     };
 
-    let tx = arc_tx.lock().await;
-
-    let trades_data: Trades = serde_json::from_str(&res.to_string())?;
-    tx.unbounded_send(msg.websocket_data(WebsocketData::GetTrades(trades_data)))?;
-    drop(tx);
+    let mut trades_data = Trades::deserialize(res)?;
+    trades_data.page = request_page(ctx);
+    ctx.send(WebsocketData::GetTrades(trades_data));
 
     Ok(())
 }
@@ -445,25 +463,15 @@ async fn private_get_trades(
 /// # Errors
 ///
 /// Will return [`serde_json::Error`] if [`serde_json::from_str`] cannot process the result string.
-///
-/// Will return [`futures_channel::mpsc::TrySendError`] if `unbounded_send` fails anywhere.
-async fn private_set_cancel_on_disconnect(
-    arc_tx: &DataSender,
-    msg: &ApiResponse<serde_json::Value>,
-) -> Result<()> {
-    let Some(ref res) = msg.result else {
-        log::warn!("Message had no result. {msg:#?}");
-
+fn private_set_cancel_on_disconnect(ctx: &HandlerContext<'_>) -> Result<()> {
+    let Some(res) = ctx.result() else {
         return Ok(());
     };
 
-    let tx = arc_tx.lock().await;
-
-    let cancel_on_disconnect_data: Scope = serde_json::from_str(&res.to_string())?;
-    tx.unbounded_send(msg.websocket_data(WebsocketData::SetCancelOnDisconnect(
+    let cancel_on_disconnect_data = Scope::deserialize(res)?;
+    ctx.send(WebsocketData::SetCancelOnDisconnect(
         cancel_on_disconnect_data,
-    )))?;
-    drop(tx);
+    ));
 
     Ok(())
 }
@@ -473,141 +481,215 @@ async fn private_set_cancel_on_disconnect(
 /// # Errors
 ///
 /// Will return [`serde_json::Error`] if [`serde_json::from_str`] cannot process the result string.
-///
-/// Will return [`futures_channel::mpsc::TrySendError`] if `unbounded_send` fails anywhere.
-async fn private_get_cancel_on_disconnect(
-    arc_tx: &DataSender,
-    msg: &ApiResponse<serde_json::Value>,
-) -> Result<()> {
-    let Some(ref res) = msg.result else {
-        log::warn!("Message had no result. {msg:#?}");
-
+fn private_get_cancel_on_disconnect(ctx: &HandlerContext<'_>) -> Result<()> {
+    let Some(res) = ctx.result() else {
         return Ok(());
     };
 
-    let tx = arc_tx.lock().await;
-
-    let cancel_on_disconnect_data: Scope = serde_json::from_str(&res.to_string())?;
-    tx.unbounded_send(msg.websocket_data(WebsocketData::GetCancelOnDisconnect(
+    let cancel_on_disconnect_data = Scope::deserialize(res)?;
+    ctx.send(WebsocketData::GetCancelOnDisconnect(
         cancel_on_disconnect_data,
-    )))?;
-    drop(tx);
+    ));
 
     Ok(())
 }
 
-/// Process the subscribe return data from the market api.
+/// Handle the `public/heartbeat` message by responding to it.
 ///
 /// # Errors
 ///
-/// Will return `Err` if subscription fails to process.
-async fn process_subscribe_result(
-    data_tx: DataSender,
-    res: &serde_json::Value,
-    msg: &ApiResponse<serde_json::Value>,
-    sub: &RawRes,
-) -> Result<()> {
-    match sub.channel.as_str() {
-        "user.order" => {
-            let data_tx = data_tx.lock().await;
+/// Will return `Err` if [`crate::websocket::respond_heartbeat`] fails to respond.
+fn public_heartbeat(ctx: &HandlerContext<'_>) -> Result<()> {
+    respond_heartbeat(ctx.tx, ctx.msg.id.try_into()?)?;
+    ctx.send(WebsocketData::UserHeartbeat);
+
+    Ok(())
+}
+
+/// Handle the `public/auth` result.
+///
+/// # Errors
+///
+/// Never returns `Err`.
+fn public_auth(ctx: &HandlerContext<'_>) -> Result<()> {
+    if !ctx.send_error() {
+        ctx.send(WebsocketData::Auth);
+    }
+
+    Ok(())
+}
+
+/// Handle the `subscribe` acknowledgement or subscription data.
+///
+/// # Errors
+///
+/// Will return [`serde_json::Error`] if the result does not match the channel's data.
+///
+/// Will return [`ApiError::UnsupportedSubscription`] if the channel is not supported, or a
+/// `user.balance` result has no `data`.
+fn subscribe_result(ctx: &HandlerContext<'_>) -> Result<()> {
+    let msg = ctx.msg;
 
-            let user_order_data: UserOrderRes = serde_json::from_str(&res.to_string())?;
-            data_tx
-                .unbounded_send(msg.websocket_data(WebsocketData::UserOrder(user_order_data)))?;
+    if msg.id >= 0 {
+        send_subscribe_ack(ctx.data_tx, msg, ctx.request);
+    }
+
+    let Some(ref res) = msg.result else {
+        if msg.id < 0 {
+            trace::warning!("Subscribe message had no result. {msg:#?}");
         }
-        "user.trade" => {
-            let data_tx = data_tx.lock().await;
 
-            let user_trade_data =
-                reprocess_data::<RawUserTradeRes, UserTradeRes>(&res.to_string())?;
-            data_tx
-                .unbounded_send(msg.websocket_data(WebsocketData::UserTrade(user_trade_data)))?;
+        return Ok(());
+    };
+
+    let sub = SubscriptionPayload::deserialize(res)?;
+    let Some(channel) = sub.channel() else {
+        trace::warning!("Subscription result has no channel. {res}");
+        ctx.send(WebsocketData::RawSubscription(res.clone()));
+
+        return Ok(());
+    };
+    trace::record_subscription(channel, sub.instrument_name());
+
+    let completed = sub.complete(res);
+    let res = &*completed;
+    match channel {
+        "user.order" => {
+            let mut user_order_data = UserOrderRes::deserialize(res)?;
+            user_order_data.kind = EventKind::from_id(msg.id);
+            ctx.send(WebsocketData::UserOrder(user_order_data));
+        }
+        "user.trade" => {
+            let user_trade_data = reprocess_value::<RawUserTradeRes, UserTradeRes>(res)?
+                .with_kind(EventKind::from_id(msg.id));
+            ctx.send(WebsocketData::UserTrade(user_trade_data));
         }
         "user.balance" => {
-            let data_tx = data_tx.lock().await;
-
-            let user_balance_data: Vec<UserBalance> = serde_json::from_str(&res.to_string())?;
-            data_tx.unbounded_send(
-                msg.websocket_data(WebsocketData::UserBalance(user_balance_data)),
-            )?;
+            // The balances are in `data`, like the other channels.
+            let Some(data) = res.get("data") else {
+                anyhow::bail!(ApiError::UnsupportedSubscription(Box::new(msg.clone())));
+            };
+            let user_balance_data = Vec::<UserBalance>::deserialize(data)?;
+            ctx.send(WebsocketData::UserBalance(user_balance_data));
         }
-        _ => anyhow::bail!(ApiError::UnsupportedSubscription(msg.clone())),
+        _ => anyhow::bail!(ApiError::UnsupportedSubscription(Box::new(msg.clone()))),
     }
 
     Ok(())
 }
 
+/// Ignore the `ping` placeholder made for websocket ping frames, they are answered on arrival.
+///
+/// # Errors
+///
+/// Never returns `Err`.
+fn ping(_ctx: &HandlerContext<'_>) -> Result<()> {
+    Ok(())
+}
+
+/// The handlers for every method of the user api supported by this crate.
+///
+/// Handlers in [`Config::user_handlers`] are used before these.
+pub fn builtin_handlers() -> &'static HandlerRegistry {
+    static HANDLERS: OnceLock<HandlerRegistry> = OnceLock::new();
+
+    HANDLERS.get_or_init(|| {
+        HandlerRegistry::new()
+            .with_handler("public/heartbeat", public_heartbeat)
+            .with_handler("public/auth", public_auth)
+            .with_handler("public/get-instruments", public_get_instruments)
+            .with_handler("private/create-withdrawal", private_create_withdrawal)
+            .with_handler(
+                "private/get-withdrawal-history",
+                private_get_withdrawal_history,
+            )
+            .with_handler("private/get-deposit-address", private_get_deposit_address)
+            .with_handler(
+                "private/create-deposit-address",
+                private_create_deposit_address,
+            )
+            .with_handler("private/get-account-summary", private_get_account_summary)
+            .with_handler("private/create-order", private_create_order)
+            .with_handler("private/cancel-order", private_cancel_order)
+            .with_handler("private/create-order-list", private_create_order_list)
+            .with_handler("private/cancel-order-list", private_cancel_order_list)
+            .with_handler("private/cancel-all-orders", private_cancel_all_orders)
+            .with_handler("private/get-order-history", private_get_order_history)
+            .with_handler("private/get-open-orders", private_get_open_orders)
+            .with_handler("private/get-order-detail", private_get_order_detail)
+            .with_handler("private/get-trades", private_get_trades)
+            .with_handler(
+                "private/set-cancel-on-disconnect",
+                private_set_cancel_on_disconnect,
+            )
+            .with_handler(
+                "private/get-cancel-on-disconnect",
+                private_get_cancel_on_disconnect,
+            )
+            .with_handler("subscribe", subscribe_result)
+            .with_handler("unsubscribe", unsubscribe_result)
+            .with_handler("ping", ping)
+    })
+}
+
 /// Process the user data.
 ///
+/// Each message is passed to the handler for its method in `handlers`, or in
+/// [`builtin_handlers`] when `handlers` has none.
+///
 /// # Errors
 ///
 /// Will return [`serde_json::Error`] if [`serde_json::from_str`] cannot process the text or
 /// binary data as string. This error can also be passed down from
-/// [`crate::utils::reprocess_data`].
+/// [`crate::utils::reprocess_value`].
 ///
 /// Will return [`std::str::Utf8Error`] if [`str::from_utf8`] cannot process a binary message that was recieved.
 ///
-/// Will return `Err` if [`crate::websocket::respond_heartbeat`] fails to respond to heartbeat message.
+/// Will return [`ApiError::UnsupportedMethod`] if no handler exists for the method.
 ///
-/// Will return [`futures_channel::mpsc::TrySendError`] if `unbounded_send` fails anywhere.
+/// Will return `Err` if the handler fails.
 pub async fn process_user(
     message: Message,
     user_tx: MessageSender,
-    data_tx: DataSender,
+    data_tx: &DataSender,
+    in_flight: &InFlight,
+    raw_hook: Option<&RawMessageHook>,
+    handlers: &HandlerRegistry,
 ) -> Result<()> {
-    let msg = message_to_api_response(&user_tx, &message).await?;
-    let method = if let Some(ref method) = msg.method {
-        method.as_str()
-    } else {
-        ""
-    };
-    let res = msg.result.clone();
-
-    match method {
-        "public/heartbeat" => {
-            let user_tx = user_tx.lock().await;
-            let data_tx = data_tx.lock().await;
+    let received_at = get_epoch_ms();
 
-            respond_heartbeat(&user_tx, msg.id.try_into()?)?;
-            data_tx.unbounded_send(msg.websocket_data(WebsocketData::UserHeartbeat))?;
-        }
-        "public/auth" => {
-            let data_tx = data_tx.lock().await;
+    if let Some(raw_hook) = raw_hook {
+        raw_hook.call(&message);
+    }
 
-            data_tx.unbounded_send(msg.websocket_data(WebsocketData::Auth))?;
-        }
-        "public/get-instruments" => public_get_instruments(&data_tx, &msg).await?,
-        "private/create-withdrawal" => private_create_withdrawal(&data_tx, &msg).await?,
-        "private/get-withdrawal-history" => private_get_withdrawal_history(&data_tx, &msg).await?,
-        "private/get-account-summary" => private_get_account_summary(&data_tx, &msg).await?,
-        "private/create-order" => private_create_order(&data_tx, &msg).await?,
-        "private/create-order-list" => private_create_order_list(&data_tx, &msg).await?,
-        "private/cancel-order-list" => private_cancel_order_list(&data_tx, &msg).await?,
-        "private/cancel-all-orders" => private_cancel_all_orders(&data_tx, &msg).await?,
-        "private/get-order-history" => private_get_order_history(&data_tx, &msg).await?,
-        "private/get-open-orders" => private_get_open_orders(&data_tx, &msg).await?,
-        "private/get-order-detail" => private_get_order_detail(&data_tx, &msg).await?,
-        "private/get-trades" => private_get_trades(&data_tx, &msg).await?,
-        "private/set-cancel-on-disconnect" => {
-            private_set_cancel_on_disconnect(&data_tx, &msg).await?;
-        }
-        "private/get-cancel-on-disconnect" => {
-            private_get_cancel_on_disconnect(&data_tx, &msg).await?;
-        }
-        "subscribe" => {
-            let Some(ref res) = res else {
-                log::warn!("Subscribe message had no result. {msg:#?}");
+    if let Message::Close(ref frame) = message {
+        trace::info!("User websocket sent a close frame. {frame:?}");
+        send_state(data_tx, ConnectionState::closed(Api::User, frame.as_ref()));
 
-                return Ok(());
-            };
+        return Ok(());
+    }
 
-            let sub_result: RawRes = serde_json::from_str(&res.to_string())?;
+    let msg = message_to_api_response(&user_tx, &message).await?;
 
-            process_subscribe_result(data_tx, res, &msg, &sub_result).await?;
-        }
-        "ping" => {}
-        _ => anyhow::bail!(ApiError::UnsupportedMethod(msg.clone())),
-    }
+    let request = match u64::try_from(msg.id) {
+        Ok(id) => in_flight.resolve(id).await,
+        Err(_) => None,
+    };
 
-    Ok(())
+    trace::record_message(msg.id, msg.method.as_deref().unwrap_or_default());
+
+    let tx = user_tx.lock().await.clone();
+
+    dispatch(
+        handlers,
+        builtin_handlers(),
+        &HandlerContext {
+            api: Api::User,
+            msg: &msg,
+            data_tx,
+            tx: &tx,
+            request: request.as_deref(),
+            received_at,
+        },
+    )
 }
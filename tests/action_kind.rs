@@ -0,0 +1,84 @@
+#![cfg(feature = "websocket")]
+
+use std::sync::Arc;
+
+use crypto_com_api::types::{Quantity, Side};
+use crypto_com_api::utils::action::Action;
+use crypto_com_api::utils::in_flight::{Api, InFlight};
+use crypto_com_api::websocket::actions::spot_trading_api::CreateOrder;
+use crypto_com_api::websocket::actions::{ActionKind, Auth, GetInstruments, Subscribe};
+
+#[test]
+fn built_in_actions_round_trip() -> anyhow::Result<()> {
+    let action = ActionKind::from(CreateOrder::limit(
+        "BTC_USDT",
+        Side::Buy,
+        20_000.0,
+        Quantity(0.5),
+    ));
+
+    let json = serde_json::to_value(&action)?;
+    assert_eq!(json["action"], "CreateOrder");
+    assert_eq!(json["params"]["type"], "LIMIT");
+
+    let ActionKind::CreateOrder(order) = serde_json::from_value(json)? else {
+        panic!("expected a create order");
+    };
+    assert_eq!(order.price, Some(20_000.0));
+    assert_eq!(order.quantity(), Some(0.5));
+
+    // The wrapped action is what is sent.
+    assert_eq!(action.method(), "private/create-order");
+    assert!(action
+        .as_action()
+        .as_any()
+        .is_some_and(|action| action.is::<CreateOrder>()));
+
+    Ok(())
+}
+
+#[test]
+fn auth_and_custom_actions_are_not_serialized() {
+    let auth = ActionKind::from(Auth {
+        api_key: "key".to_owned(),
+        secret_key: "secret".to_owned(),
+    });
+    let custom = ActionKind::from(Arc::new(GetInstruments) as Arc<dyn Action>);
+
+    assert!(!auth.is_serializable());
+    assert!(serde_json::to_string(&auth).is_err());
+    assert!(!custom.is_serializable());
+    assert!(serde_json::to_string(&custom).is_err());
+    assert_eq!(custom.method(), "public/get-instruments");
+}
+
+#[tokio::test]
+async fn pending_requests_can_be_saved() -> anyhow::Result<()> {
+    let in_flight = InFlight::default();
+    let subscribe = Subscribe {
+        channels: vec!["ticker.BTC_USDT".to_owned()],
+        ..Subscribe::default()
+    };
+
+    in_flight
+        .insert(1, Api::Market, Arc::new(subscribe.into()))
+        .await;
+    in_flight
+        .insert(2, Api::User, Arc::new(GetInstruments.into()))
+        .await;
+
+    let pending = in_flight.pending(Api::Market).await;
+    let saved: Vec<&ActionKind> = pending.iter().map(|(_, action)| &**action).collect();
+    let json = serde_json::to_string(&saved)?;
+    let restored: Vec<ActionKind> = serde_json::from_str(&json)?;
+
+    assert_eq!(pending[0].0, 1);
+    assert!(matches!(
+        restored[..],
+        [ActionKind::Subscribe(ref subscribe)] if subscribe.channels == ["ticker.BTC_USDT"]
+    ));
+    // Reading the requests leaves them in flight.
+    assert_eq!(in_flight.ids().await, vec![1, 2]);
+
+    Ok(())
+}
@@ -0,0 +1,132 @@
+#![cfg(feature = "websocket")]
+
+use std::time::Duration;
+
+use crypto_com_api::analytics::alerts::{Alert, AlertCondition, AlertEngine, AlertFired};
+use crypto_com_api::controller::ControllerBuilder;
+use crypto_com_api::testing::decode_frame;
+use crypto_com_api::utils::in_flight::Api;
+use crypto_com_api::websocket::WebsocketData;
+
+/// Feed `engine` a ticker of BTC_USDT with last price `price` and best bid and ask `bid` and
+/// `ask`, recieved at `at`.
+fn ticker(engine: &AlertEngine, at: u64, price: f64, bid: f64, ask: f64) -> Vec<AlertFired> {
+    let frame = format!(
+        r#"{{"id":-1,"method":"subscribe","code":0,"result":{{"instrument_name":"BTC_USDT","subscription":"ticker.BTC_USDT","channel":"ticker","data":[{{"a":"{price}","i":"BTC_USDT","v":"1","vv":"1","b":"{bid}","k":"{ask}","t":{at}}}]}}}}"#
+    );
+
+    decode_frame(Api::Market, &frame, at)
+        .expect("a ticker frame")
+        .iter()
+        .filter_map(|data| data.result.as_ref())
+        .flat_map(|data| engine.process(data))
+        .collect()
+}
+
+#[test]
+fn crossings_fire_once_until_they_rearm() {
+    let engine = AlertEngine::new();
+    let above = engine.add(Alert::new("BTC_USDT", AlertCondition::PriceAbove(100.0)));
+    engine.add(Alert::new("ETH_USDT", AlertCondition::PriceAbove(1.0)));
+
+    assert!(ticker(&engine, 0, 99.0, 98.0, 100.0).is_empty());
+
+    let fired = ticker(&engine, 1, 101.0, 100.0, 102.0);
+    assert_eq!(fired.len(), 1);
+    assert_eq!(fired[0].id, above);
+    assert_eq!(fired[0].value, 101.0);
+    assert_eq!(fired[0].received_at, 1);
+
+    // Still above, it does not fire again until the price falls back below.
+    assert!(ticker(&engine, 2, 105.0, 104.0, 106.0).is_empty());
+    assert!(ticker(&engine, 3, 95.0, 94.0, 96.0).is_empty());
+    assert_eq!(ticker(&engine, 4, 100.0, 99.0, 101.0).len(), 1);
+
+    // Removed alerts stop firing.
+    engine.remove(above);
+    assert!(ticker(&engine, 5, 90.0, 89.0, 91.0).is_empty());
+    assert!(ticker(&engine, 6, 110.0, 109.0, 111.0).is_empty());
+}
+
+#[test]
+fn moves_are_measured_over_the_window() {
+    let engine = AlertEngine::new();
+    engine.add(Alert::new(
+        "BTC_USDT",
+        AlertCondition::Move {
+            percent: 5.0,
+            window: Duration::from_secs(10),
+        },
+    ));
+
+    assert!(ticker(&engine, 0, 100.0, 99.0, 101.0).is_empty());
+    // A slow drift never moves 5% within 10 seconds.
+    assert!(ticker(&engine, 8_000, 103.0, 102.0, 104.0).is_empty());
+    assert!(ticker(&engine, 16_000, 106.0, 105.0, 107.0).is_empty());
+
+    let fired = ticker(&engine, 17_000, 97.0, 96.0, 98.0);
+    assert_eq!(fired.len(), 1);
+    assert!((fired[0].value + 5.825).abs() < 0.001);
+}
+
+#[test]
+fn spreads_are_read_from_tickers_and_books() -> anyhow::Result<()> {
+    let engine = AlertEngine::new();
+    engine.add(Alert::new("BTC_USDT", AlertCondition::SpreadWider(0.25)));
+
+    assert!(ticker(&engine, 0, 100.0, 99.9, 100.1).is_empty());
+    assert_eq!(ticker(&engine, 1, 100.0, 99.5, 100.5)[0].value, 1.0);
+
+    // The book fixture has a spread of 0.5, the ticker above left the alert holding.
+    let book = std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/market/book.json"
+    ))?;
+    let data = decode_frame(Api::Market, &book, 2)?;
+    let result = data[0].result.as_ref().expect("a book");
+    assert!(engine.process(result).is_empty());
+
+    ticker(&engine, 3, 100.0, 99.9, 100.1);
+    let fired = engine.process(result);
+    assert_eq!(fired.len(), 1);
+    assert_eq!(fired[0].value, 0.5);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn the_controller_broadcasts_fired_alerts() -> anyhow::Result<()> {
+    let mut controller = ControllerBuilder::new().build();
+    let mut data_rx = controller.subscribe_data();
+    let engine = AlertEngine::new();
+    let handle = controller.watch_alerts(engine.clone());
+
+    // Added after the task started, from another clone.
+    let id = engine.add(Alert::new(
+        "BTCUSD-PERP",
+        AlertCondition::PriceBelow(60_000.0),
+    ));
+
+    let ticker = std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/market/ticker.json"
+    ))?;
+    for data in decode_frame(Api::Market, &ticker, 0)? {
+        controller.data_tx.send(data)?;
+    }
+
+    let fired = loop {
+        let data = tokio::time::timeout(Duration::from_secs(1), data_rx.recv()).await??;
+
+        if let Some(WebsocketData::AlertFired(ref fired)) = data.result {
+            break fired.clone();
+        }
+    };
+
+    assert_eq!(fired.id, id);
+    assert_eq!(fired.value, 51_174.5);
+
+    handle.abort();
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use crypto_com_api::api_request::{authorization, ApiRequestBuilder, Authorization};
+use crypto_com_api::prelude::ApiError;
+
+#[test]
+fn methods_are_classified() {
+    assert_eq!(authorization("public/auth"), Authorization::Signed);
+    assert_eq!(authorization("public/get-book"), Authorization::Public);
+    assert_eq!(authorization("private/get-trades"), Authorization::Signed);
+    assert_eq!(authorization("subscribe"), Authorization::Public);
+}
+
+#[test]
+fn unsigned_private_request_lists_missing_pieces() {
+    let err = ApiRequestBuilder::default()
+        .with_id(1)
+        .with_method("private/get-account-summary")
+        .with_api_key("key")
+        .build()
+        .unwrap_err();
+
+    let ApiError::InvalidApiRequest(missing) = err else {
+        panic!("expected InvalidApiRequest");
+    };
+
+    assert!(missing.contains("sig") && missing.contains("nonce"));
+    assert!(!missing.contains("api_key"));
+}
+
+#[test]
+fn signed_and_session_requests_build() {
+    assert!(ApiRequestBuilder::default()
+        .with_id(1)
+        .with_method("private/get-account-summary")
+        .with_api_key("key")
+        .with_nonce()
+        .with_digital_signature("secret")
+        .build()
+        .is_ok());
+
+    assert!(ApiRequestBuilder::default()
+        .with_id(1)
+        .with_method("private/get-account-summary")
+        .with_nonce()
+        .on_authorized_session()
+        .build()
+        .is_ok());
+}
+
+#[test]
+fn signature_is_hmac_of_the_payload() {
+    use crypto_com_api::signing::{payload, sign};
+
+    let params = serde_json::json!({ "currency": "CRO", "page": 0 });
+
+    assert_eq!(
+        payload(
+            "private/get-account-summary",
+            Some(11),
+            Some("key"),
+            Some(&params),
+            Some(1)
+        ),
+        "private/get-account-summary11keycurrencyCROpage01"
+    );
+    assert_eq!(
+        sign("key", "The quick brown fox jumps over the lazy dog"),
+        "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+    );
+}
@@ -0,0 +1,212 @@
+#![cfg(feature = "websocket")]
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+
+use crypto_com_api::error::ApiError;
+use crypto_com_api::prelude::DataReciever;
+use crypto_com_api::utils::action::{Access, Action};
+use crypto_com_api::utils::config::Credentials;
+use crypto_com_api::websocket::actions::spot_trading_api::GetAccountSummary;
+use crypto_com_api::websocket::actions::{
+    GetInstruments, Subscribe, SubscribeUserBalance, SubscribeUserOrders,
+};
+use crypto_com_api::websocket::auth_state::{
+    authenticate, AuthGate, AuthState, AuthStatus, CredentialRefresh,
+};
+use crypto_com_api::websocket::WebsocketData;
+
+fn credentials(api_key: &str) -> Credentials {
+    Credentials {
+        api_key: api_key.to_owned(),
+        secret_key: "secret".to_owned(),
+    }
+}
+
+fn states(data_rx: &mut DataReciever) -> Vec<AuthState> {
+    let mut states = vec![];
+
+    while let Ok(data) = data_rx.try_recv() {
+        if let Some(WebsocketData::AuthState(ref state)) = data.result {
+            states.push(state.clone());
+        }
+    }
+
+    states
+}
+
+fn refused() -> anyhow::Error {
+    ApiError::RequestFailed {
+        code: 40101,
+        message: Some("UNAUTHORIZED".to_owned()),
+    }
+    .into()
+}
+
+#[tokio::test]
+async fn refused_keys_are_refreshed_and_retried() -> Result<()> {
+    let (data_tx, mut data_rx) = tokio::sync::broadcast::channel(16);
+    let refreshes = Arc::new(AtomicU32::new(0));
+    let refresh = {
+        let refreshes = Arc::clone(&refreshes);
+
+        CredentialRefresh::new(move || {
+            refreshes.fetch_add(1, Ordering::SeqCst);
+
+            async { Ok(credentials("rotated")) }
+        })
+    };
+    let sent = Arc::new(Mutex::new(vec![]));
+
+    let accepted = authenticate(credentials("revoked"), Some(&refresh), &data_tx, |auth| {
+        sent.lock().unwrap().push(auth.api_key.clone());
+
+        Box::pin(async move {
+            if auth.api_key == "revoked" {
+                Err(refused())
+            } else {
+                Ok(())
+            }
+        })
+    })
+    .await?;
+
+    assert_eq!(accepted, credentials("rotated"));
+    assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+    assert_eq!(*sent.lock().unwrap(), ["revoked", "rotated"]);
+    assert_eq!(
+        states(&mut data_rx),
+        [
+            AuthState::Authenticating { attempt: 1 },
+            AuthState::Failed {
+                code: 40101,
+                message: Some("UNAUTHORIZED".to_owned()),
+            },
+            AuthState::Refreshing,
+            AuthState::Authenticating { attempt: 2 },
+            AuthState::Authenticated,
+        ]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn gives_up_without_a_refresh() {
+    let (data_tx, mut data_rx) = tokio::sync::broadcast::channel(16);
+
+    let err = authenticate(credentials("revoked"), None, &data_tx, |_| {
+        Box::pin(async { Err(refused()) })
+    })
+    .await
+    .expect_err("refused keys fail without a refresh");
+
+    assert!(matches!(
+        err.downcast_ref::<ApiError>(),
+        Some(&ApiError::AuthFail(40101))
+    ));
+    assert_eq!(
+        states(&mut data_rx).last(),
+        Some(&AuthState::GaveUp { code: 40101 })
+    );
+}
+
+#[tokio::test]
+async fn gives_up_once_the_refreshes_run_out() {
+    let (data_tx, mut data_rx) = tokio::sync::broadcast::channel(16);
+    let refresh =
+        CredentialRefresh::new(|| async { Ok(credentials("still revoked")) }).with_max_refreshes(2);
+    let attempts = AtomicU32::new(0);
+
+    let err = authenticate(credentials("revoked"), Some(&refresh), &data_tx, |_| {
+        attempts.fetch_add(1, Ordering::SeqCst);
+
+        Box::pin(async { Err(refused()) })
+    })
+    .await
+    .expect_err("keys that are never accepted fail");
+
+    assert!(matches!(
+        err.downcast_ref::<ApiError>(),
+        Some(&ApiError::AuthFail(40101))
+    ));
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    assert_eq!(
+        states(&mut data_rx)
+            .iter()
+            .filter(|state| **state == AuthState::Refreshing)
+            .count(),
+        2
+    );
+}
+
+#[tokio::test]
+async fn other_errors_are_not_retried() {
+    let (data_tx, _data_rx) = tokio::sync::broadcast::channel(16);
+    let refresh = CredentialRefresh::new(|| async { Ok(credentials("rotated")) });
+
+    let err = authenticate(credentials("key"), Some(&refresh), &data_tx, |_| {
+        Box::pin(async { Err(ApiError::Unanswered(1).into()) })
+    })
+    .await
+    .expect_err("unanswered requests fail");
+
+    assert!(matches!(
+        err.downcast_ref::<ApiError>(),
+        Some(&ApiError::Unanswered(1))
+    ));
+}
+
+#[test]
+fn actions_are_classified_by_access() {
+    assert_eq!(GetInstruments.access(), Access::Public);
+    assert_eq!(
+        GetAccountSummary { currency: None }.access(),
+        Access::Private
+    );
+    assert_eq!(SubscribeUserBalance.access(), Access::Private);
+    assert_eq!(SubscribeUserOrders(None).access(), Access::Private);
+
+    let market = Subscribe {
+        channels: vec!["book.BTC_USDT.10".to_owned()],
+        ..Subscribe::default()
+    };
+    assert_eq!(market.access(), Access::Public);
+
+    let user = Subscribe {
+        channels: vec!["ticker.BTC_USDT".to_owned(), "user.order".to_owned()],
+        ..Subscribe::default()
+    };
+    assert_eq!(user.access(), Access::Private);
+}
+
+#[tokio::test]
+async fn private_actions_wait_for_the_auth_reply() -> Result<()> {
+    let gate = Arc::new(AuthGate::new());
+
+    assert!(matches!(
+        gate.wait("private/get-account-summary")
+            .await
+            .unwrap_err()
+            .downcast_ref(),
+        Some(ApiError::NotAuthenticated(method)) if method == "private/get-account-summary"
+    ));
+
+    gate.set(AuthStatus::Pending);
+    let waiting = tokio::spawn({
+        let gate = Arc::clone(&gate);
+
+        async move { gate.wait("private/create-order").await }
+    });
+
+    tokio::task::yield_now().await;
+    assert!(!waiting.is_finished());
+
+    gate.set(AuthStatus::Authenticated);
+    waiting.await??;
+    assert_eq!(gate.status(), AuthStatus::Authenticated);
+
+    Ok(())
+}
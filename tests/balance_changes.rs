@@ -0,0 +1,148 @@
+#![cfg(feature = "websocket")]
+
+use crypto_com_api::analytics::{BalanceChange, BalanceChangeKind, BalanceTracker};
+use crypto_com_api::error::ApiError;
+use crypto_com_api::testing::decode_frame;
+use crypto_com_api::types::{EpochMs, Time};
+use crypto_com_api::utils::in_flight::Api;
+use crypto_com_api::websocket::data::{
+    Account, AccountSummary, EventKind, ExtraFields, UserBalance, UserTrade, UserTradeRes,
+};
+use crypto_com_api::websocket::WebsocketData;
+
+fn balance(currency: &str, balance: f64) -> UserBalance {
+    UserBalance {
+        currency: currency.to_owned(),
+        balance,
+        available: balance,
+        order: 0.0,
+        stake: 0.0,
+        extra: ExtraFields::new(),
+    }
+}
+
+fn buy(price: f64, quantity: f64, fee: f64, kind: EventKind) -> UserTradeRes {
+    UserTradeRes {
+        instrument_name: "CRO_USDT".to_owned(),
+        subscription: "user.trade.CRO_USDT".to_owned(),
+        channel: "user.trade".to_owned(),
+        data: vec![UserTrade {
+            side: "BUY".to_owned(),
+            fee,
+            trade_id: 1,
+            create_time: Time::from_epoch_ms(1),
+            traded_price: price,
+            traded_quantity: quantity,
+            fee_currency: "CRO".to_owned(),
+            order_id: 1,
+        }],
+        kind,
+    }
+}
+
+fn change(currency: &str, kind: BalanceChangeKind, amount: f64, balance: f64) -> BalanceChange {
+    BalanceChange {
+        currency: currency.to_owned(),
+        kind,
+        amount,
+        balance,
+    }
+}
+
+#[test]
+fn first_snapshot_is_the_baseline() {
+    let mut tracker = BalanceTracker::new();
+
+    assert!(tracker
+        .apply_balances(&[balance("CRO", 10.0), balance("USDT", 100.0)])
+        .is_empty());
+    assert_eq!(tracker.balance("CRO"), Some(10.0));
+}
+
+#[test]
+fn deposits_and_withdrawals() {
+    let mut tracker = BalanceTracker::new();
+    tracker.apply_balances(&[balance("CRO", 10.0), balance("USDT", 100.0)]);
+
+    let changes = tracker.apply_balances(&[
+        balance("CRO", 15.0),
+        balance("USDT", 60.0),
+        balance("BTC", 0.5),
+    ]);
+
+    assert_eq!(
+        changes,
+        vec![
+            change("CRO", BalanceChangeKind::Deposit, 5.0, 15.0),
+            change("USDT", BalanceChangeKind::Withdrawal, -40.0, 60.0),
+            change("BTC", BalanceChangeKind::Deposit, 0.5, 0.5),
+        ]
+    );
+}
+
+#[test]
+fn fills_and_fees_explain_the_change() {
+    let mut tracker = BalanceTracker::new();
+    tracker.process(&WebsocketData::GetAccountSummary(AccountSummary {
+        accounts: vec![
+            Account {
+                balance: 10.0,
+                available: 10.0,
+                order: 0.0,
+                stake: 0.0,
+                currency: "CRO".to_owned(),
+                extra: ExtraFields::new(),
+            },
+            Account {
+                balance: 10.0,
+                available: 10.0,
+                order: 0.0,
+                stake: 0.0,
+                currency: "USDT".to_owned(),
+                extra: ExtraFields::new(),
+            },
+        ],
+    }));
+
+    // Trades sent when subscribing are already in the balances.
+    tracker.process(&WebsocketData::UserTrade(buy(
+        0.5,
+        100.0,
+        1.0,
+        EventKind::Snapshot,
+    )));
+    tracker.process(&WebsocketData::UserTrade(buy(
+        0.5,
+        20.0,
+        0.25,
+        EventKind::Update,
+    )));
+
+    let changes = tracker.process(&WebsocketData::UserBalance(vec![
+        balance("CRO", 32.75),
+        balance("USDT", 0.0),
+    ]));
+
+    assert_eq!(
+        changes,
+        vec![
+            change("CRO", BalanceChangeKind::TradeFill, 20.0, 32.75),
+            change("CRO", BalanceChangeKind::Fee, -0.25, 32.75),
+            change("CRO", BalanceChangeKind::Deposit, 3.0, 32.75),
+            change("USDT", BalanceChangeKind::TradeFill, -10.0, 0.0),
+        ]
+    );
+}
+
+#[test]
+fn balance_push_without_data_is_an_error() {
+    let frame = r#"{
+        "id": -1, "method": "subscribe", "code": 0,
+        "result": { "subscription": "user.balance", "channel": "user.balance" }
+    }"#;
+
+    assert!(matches!(
+        decode_frame(Api::User, frame, 0),
+        Err(ApiError::UnsupportedSubscription(_))
+    ));
+}
@@ -0,0 +1,38 @@
+#![cfg(feature = "rest")]
+
+use crypto_com_api::prelude::ApiError;
+use crypto_com_api::rest::data::{BookRes, RawBookRes};
+use crypto_com_api::types::{Depth, EpochMs};
+
+#[test]
+fn depth_is_one_to_fifty() -> anyhow::Result<()> {
+    assert_eq!(Depth::new(1)?, Depth::MIN);
+    assert_eq!(Depth::try_from(50)?, Depth::MAX);
+    assert_eq!(Depth::TEN.to_string(), "10");
+    assert_eq!(Depth::default(), Depth::FIFTY);
+
+    assert!(matches!(Depth::new(0), Err(ApiError::InvalidDepth(0))));
+    assert!(matches!(Depth::new(51), Err(ApiError::InvalidDepth(51))));
+
+    Ok(())
+}
+
+#[test]
+fn rest_book_keeps_timestamps() -> anyhow::Result<()> {
+    let raw: RawBookRes = serde_json::from_str(
+        r#"{
+            "instrument_name": "BTC_USDT", "depth": 1,
+            "data": [{
+                "bids": [["100.0", "1.0", "2"]],
+                "asks": [["101.0", "0.5", "1"]],
+                "t": 1613580710768, "tt": 1613580710700
+            }]
+        }"#,
+    )?;
+    let book = &BookRes::try_from(&raw)?.data[0];
+
+    assert_eq!(book.t.map(|t| t.epoch_ms()), Some(1_613_580_710_768));
+    assert_eq!(book.tt.map(|tt| tt.epoch_ms()), Some(1_613_580_710_700));
+
+    Ok(())
+}
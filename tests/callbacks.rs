@@ -0,0 +1,96 @@
+#![cfg(feature = "websocket")]
+
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+
+use crypto_com_api::callbacks::Callbacks;
+use crypto_com_api::controller::ControllerBuilder;
+use crypto_com_api::testing::decode_frame;
+use crypto_com_api::utils::in_flight::Api;
+
+/// The frame of fixture `name`.
+fn fixture(name: &str) -> String {
+    std::fs::read_to_string(format!(
+        "{}/tests/fixtures/{name}.json",
+        env!("CARGO_MANIFEST_DIR")
+    ))
+    .expect("a fixture")
+}
+
+#[test]
+fn data_goes_to_the_callbacks_of_its_channel() -> anyhow::Result<()> {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut callbacks = Callbacks::new();
+
+    let ticker_tx = tx.clone();
+    callbacks.on_ticker(move |tickers| {
+        let _ = ticker_tx.send(format!("ticker {}", tickers.data[0].i));
+    });
+    let second_tx = tx.clone();
+    callbacks.on_ticker(move |_| {
+        let _ = second_tx.send("second ticker".to_owned());
+    });
+    callbacks.on_user_balance(move |balances| {
+        let _ = tx.send(format!("balance {}", balances.len()));
+    });
+    assert_eq!(callbacks.len(), 3);
+
+    for (api, name) in [
+        (Api::Market, "market/ticker"),
+        (Api::Market, "market/trade"),
+        (Api::User, "user/user_balance"),
+    ] {
+        for data in decode_frame(api, &fixture(name), 0)? {
+            if let Some(ref data) = data.result {
+                callbacks.dispatch(data);
+            }
+        }
+    }
+
+    let mut calls = vec![];
+
+    while let Ok(call) = rx.try_recv() {
+        calls.push(call);
+    }
+
+    // Nothing is registered for trades.
+    assert_eq!(calls, ["ticker BTCUSD-PERP", "second ticker", "balance 1"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn the_controller_dispatches_the_data_broadcast() -> anyhow::Result<()> {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut controller = ControllerBuilder::new().build();
+
+    let ticker_tx = tx.clone();
+    controller
+        .on_ticker(move |tickers| {
+            let _ = ticker_tx.send(tickers.data.len());
+        })
+        .on_trade(move |trades| {
+            let _ = tx.send(trades.data.len() * 100);
+        });
+    assert!(controller
+        .callback_listener
+        .as_ref()
+        .is_some_and(|listener| listener.is_alive()));
+
+    for name in ["market/ticker", "market/trade"] {
+        for data in decode_frame(Api::Market, &fixture(name), 0)? {
+            controller.data_tx.send(data)?;
+        }
+    }
+
+    let mut calls = vec![];
+
+    for _ in 0..2 {
+        calls.push(tokio::time::timeout(Duration::from_secs(5), rx.recv()).await?);
+    }
+
+    assert_eq!(calls, [Some(1), Some(100)]);
+
+    Ok(())
+}
@@ -0,0 +1,129 @@
+#![cfg(feature = "websocket")]
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+use tokio_tungstenite::tungstenite::Message;
+
+use crypto_com_api::testing::decode_frame;
+use crypto_com_api::utils::in_flight::{Api, InFlight};
+use crypto_com_api::websocket::actions::spot_trading_api::CancelOrder;
+use crypto_com_api::websocket::data;
+use crypto_com_api::websocket::handler::HandlerRegistry;
+use crypto_com_api::websocket::{user_api::process_user, WebsocketData};
+
+async fn reply(frame: &str, in_flight: &InFlight) -> anyhow::Result<WebsocketData> {
+    let (user_tx, _user_rx) = futures_channel::mpsc::unbounded();
+    let (data_tx, mut data_rx) = tokio::sync::broadcast::channel(4);
+
+    process_user(
+        Message::Text(frame.to_owned()),
+        Arc::new(Mutex::new(user_tx)),
+        &data_tx,
+        in_flight,
+        None,
+        &HandlerRegistry::new(),
+    )
+    .await?;
+
+    let data = data_rx.recv().await?;
+    let data = Arc::try_unwrap(data).map_err(|_| anyhow::anyhow!("data is shared"))?;
+
+    data.result.ok_or_else(|| anyhow::anyhow!("no data"))
+}
+
+#[tokio::test]
+async fn cancel_reply_carries_the_order() -> anyhow::Result<()> {
+    let in_flight = InFlight::default();
+    in_flight
+        .insert(
+            9,
+            Api::User,
+            Arc::new(
+                CancelOrder {
+                    instrument_name: "ETH_CRO".to_owned(),
+                    order_id: "1138210129647637539".to_owned(),
+                    client_oid: Some("my-order".to_owned()),
+                }
+                .into(),
+            ),
+        )
+        .await;
+
+    let WebsocketData::CancelOrder(cancel) = reply(
+        r#"{"id": 9, "method": "private/cancel-order", "code": 0}"#,
+        &in_flight,
+    )
+    .await?
+    else {
+        anyhow::bail!("expected a cancel order reply");
+    };
+
+    assert!(cancel.is_success());
+    assert_eq!(cancel.id, 9);
+    assert_eq!(cancel.instrument_name.as_deref(), Some("ETH_CRO"));
+    assert_eq!(cancel.order_id.as_deref(), Some("1138210129647637539"));
+    assert_eq!(cancel.client_oid.as_deref(), Some("my-order"));
+    assert!(in_flight.ids().await.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn rejected_cancel_of_unknown_request() -> anyhow::Result<()> {
+    let WebsocketData::CancelOrder(cancel) = reply(
+        r#"{"id": 4, "method": "private/cancel-order", "code": 316, "message": "NOT_FOUND"}"#,
+        &InFlight::default(),
+    )
+    .await?
+    else {
+        anyhow::bail!("expected a cancel order reply");
+    };
+
+    assert!(!cancel.is_success());
+    assert_eq!(cancel.code, 316);
+    assert_eq!(cancel.message.as_deref(), Some("NOT_FOUND"));
+    assert_eq!(cancel.order_id, None);
+
+    Ok(())
+}
+
+#[test]
+fn cancel_is_confirmed_by_the_canceled_order_update() -> anyhow::Result<()> {
+    let fixture = std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/user/user_order.json"
+    ))?;
+    let decoded = decode_frame(Api::User, &fixture, 0)?;
+    let Some(WebsocketData::UserOrder(ref orders)) = decoded[0].result else {
+        anyhow::bail!("expected a user.order update");
+    };
+    let mut order = orders.data[0].clone();
+
+    let request = CancelOrder {
+        instrument_name: "ETH_CRO".to_owned(),
+        order_id: "366455245775097673".to_owned(),
+        client_oid: None,
+    };
+    let cancel = data::CancelOrder::new(9, 0, None, Some(&request));
+
+    // Still active, the cancel was only accepted.
+    assert!(!cancel.confirmed_by(&order));
+
+    order.status = "CANCELED".to_owned();
+    assert!(cancel.confirmed_by(&order));
+
+    // Without the order ID the client order ID is matched.
+    let by_client_oid = data::CancelOrder {
+        order_id: None,
+        client_oid: Some("my_order_0002".to_owned()),
+        ..cancel.clone()
+    };
+    assert!(by_client_oid.confirmed_by(&order));
+
+    order.order_id = "1".to_owned();
+    assert!(!cancel.confirmed_by(&order));
+    assert!(!data::CancelOrder::new(4, 0, None, None).confirmed_by(&order));
+
+    Ok(())
+}
@@ -0,0 +1,160 @@
+#![cfg(all(feature = "rest", feature = "websocket"))]
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use tokio::sync::broadcast;
+
+use crypto_com_api::api_response::ApiResponse;
+use crypto_com_api::history::{CandleEvent, CandleSource, CandleStream};
+use crypto_com_api::prelude::DataSender;
+use crypto_com_api::rest::data::{Candlestick, CandlestickParams};
+use crypto_com_api::testing::decode_frame;
+use crypto_com_api::types::{EpochMs, Interval, Time};
+use crypto_com_api::utils::in_flight::Api;
+use crypto_com_api::websocket::connection_state::ConnectionState;
+use crypto_com_api::websocket::WebsocketData;
+
+const MINUTE: u64 = 60_000;
+
+/// One candle a minute closing at its minute, or failing every request when `down`.
+#[derive(Default)]
+struct FakeSource {
+    down: bool,
+    requests: Arc<AtomicU32>,
+}
+
+impl CandleSource for FakeSource {
+    async fn fetch(&self, params: &CandlestickParams) -> anyhow::Result<Vec<Candlestick>> {
+        self.requests.fetch_add(1, Ordering::SeqCst);
+
+        if self.down {
+            anyhow::bail!("connection refused");
+        }
+
+        let start = params.start_ts.unwrap_or_default();
+        let end = params.end_ts.unwrap_or_default();
+
+        #[allow(clippy::cast_precision_loss)]
+        Ok((start..end)
+            .filter(|t| t % MINUTE == 0)
+            .map(|t| Candlestick {
+                t: Time::from_epoch_ms(t),
+                c: (t / MINUTE) as f64,
+                ..Candlestick::default()
+            })
+            .collect())
+    }
+}
+
+/// Send a live `instrument_name` candle of `minute` closing at `close`.
+fn send_candle(
+    data_tx: &DataSender,
+    instrument_name: &str,
+    minute: u64,
+    close: f64,
+) -> anyhow::Result<()> {
+    let t = minute * MINUTE;
+    let frame = format!(
+        r#"{{"id":-1,"method":"subscribe","code":0,"result":{{"instrument_name":"{instrument_name}","subscription":"candlestick.1m.{instrument_name}","interval":"1m","channel":"candlestick","data":[{{"t":{t},"ut":{t},"o":"1","h":"1","l":"1","c":"{close}","v":"1"}}]}}}}"#
+    );
+
+    for data in decode_frame(Api::Market, &frame, 0)? {
+        data_tx.send(data)?;
+    }
+
+    Ok(())
+}
+
+fn send_state(data_tx: &DataSender, state: ConnectionState) -> anyhow::Result<()> {
+    data_tx.send(
+        ApiResponse::<WebsocketData>::default()
+            .websocket_data(WebsocketData::ConnectionState(state))
+            .into(),
+    )?;
+
+    Ok(())
+}
+
+/// Each event as a kind and the minute of its candle.
+async fn events(stream: &mut CandleStream<FakeSource>, n: usize) -> Vec<(&'static str, u64)> {
+    let mut events = vec![];
+
+    for _ in 0..n {
+        let event = stream.next().await.expect("an event");
+        let minute = event
+            .candle()
+            .map_or(0, |candle| candle.t.epoch_ms() / MINUTE);
+
+        events.push(match event {
+            CandleEvent::Live(_) => ("live", minute),
+            CandleEvent::Backfilled(_) => ("backfilled", minute),
+            CandleEvent::BackfillFailed { start_ts, .. } => ("failed", start_ts / MINUTE),
+        });
+    }
+
+    events
+}
+
+#[tokio::test]
+async fn missed_candles_are_backfilled_in_order() -> anyhow::Result<()> {
+    let source = FakeSource::default();
+    let requests = Arc::clone(&source.requests);
+    let (data_tx, data_rx) = broadcast::channel(64);
+    let mut stream = CandleStream::new(data_rx, source, "BTC_USDT", Interval::OneMinute);
+
+    send_candle(&data_tx, "BTC_USDT", 1, 10.0)?;
+    send_candle(&data_tx, "BTC_USDT", 2, 20.0)?;
+    // Candles of another instrument and a reconnect of the user websocket are ignored.
+    send_candle(&data_tx, "ETH_USDT", 3, 3.0)?;
+    send_state(&data_tx, ConnectionState::Reconnecting(Api::User))?;
+    send_candle(&data_tx, "BTC_USDT", 3, 30.0)?;
+    send_state(&data_tx, ConnectionState::Reconnecting(Api::Market))?;
+    send_state(&data_tx, ConnectionState::Reconnected(Api::Market))?;
+    send_candle(&data_tx, "BTC_USDT", 6, 60.0)?;
+    send_candle(&data_tx, "BTC_USDT", 6, 61.0)?;
+
+    assert_eq!(
+        events(&mut stream, 8).await,
+        [
+            ("live", 1),
+            ("live", 2),
+            ("live", 3),
+            ("backfilled", 3),
+            ("backfilled", 4),
+            ("backfilled", 5),
+            ("live", 6),
+            ("live", 6),
+        ]
+    );
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+    assert_eq!(stream.last(), Some(6 * MINUTE));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn a_failed_backfill_is_reported() -> anyhow::Result<()> {
+    let source = FakeSource {
+        down: true,
+        ..FakeSource::default()
+    };
+    let requests = Arc::clone(&source.requests);
+    let (data_tx, data_rx) = broadcast::channel(64);
+    let mut stream = CandleStream::new(data_rx, source, "BTC_USDT", Interval::OneMinute);
+    assert_eq!(stream.subscription(), "candlestick.M1.BTC_USDT");
+
+    // Nothing to backfill before the first candle.
+    send_state(&data_tx, ConnectionState::Reconnecting(Api::Market))?;
+    send_candle(&data_tx, "BTC_USDT", 1, 10.0)?;
+    send_state(&data_tx, ConnectionState::Reconnecting(Api::Market))?;
+    send_candle(&data_tx, "BTC_USDT", 4, 40.0)?;
+
+    assert_eq!(
+        events(&mut stream, 3).await,
+        [("live", 1), ("failed", 1), ("live", 4)]
+    );
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+    Ok(())
+}
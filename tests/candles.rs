@@ -0,0 +1,65 @@
+#![cfg(feature = "websocket")]
+
+use crypto_com_api::analytics::{align, fill_gaps, CandleGapFiller};
+use crypto_com_api::types::{Candlestick, EpochMs, Interval, Time};
+
+const MINUTE: u64 = 60_000;
+
+fn candle(t: u64, c: f64, v: f64) -> Candlestick {
+    Candlestick {
+        t: Time::from_epoch_ms(t),
+        ut: None,
+        o: c - 1.0,
+        h: c + 1.0,
+        l: c - 2.0,
+        c,
+        v,
+    }
+}
+
+#[test]
+fn gaps_are_filled_at_the_previous_close() {
+    let filled = fill_gaps(
+        &[
+            candle(4 * MINUTE + 5, 104.0, 3.0),
+            candle(MINUTE + 10, 101.0, 1.0),
+        ],
+        Interval::OneMinute,
+    );
+
+    let times: Vec<u64> = filled.iter().map(|candle| candle.t.epoch_ms()).collect();
+    assert_eq!(times, [MINUTE, 2 * MINUTE, 3 * MINUTE, 4 * MINUTE]);
+
+    assert_eq!(filled[1].o, 101.0);
+    assert_eq!(filled[1].h, 101.0);
+    assert_eq!(filled[2].c, 101.0);
+    assert_eq!(filled[2].v, 0.0);
+    assert_eq!(filled[3].c, 104.0);
+}
+
+#[test]
+fn last_candle_of_a_period_wins() {
+    let filled = fill_gaps(
+        &[candle(MINUTE, 100.0, 1.0), candle(MINUTE + 30, 102.0, 2.0)],
+        Interval::OneMinute,
+    );
+
+    assert_eq!(filled, [candle(MINUTE, 102.0, 2.0)]);
+}
+
+#[test]
+fn stream_updates_and_stale_candles() {
+    let mut filler = CandleGapFiller::new(Interval::FiveMinutes);
+
+    assert_eq!(align(7 * MINUTE, Interval::FiveMinutes), 5 * MINUTE);
+
+    assert_eq!(filler.push(&candle(5 * MINUTE, 100.0, 1.0)).len(), 1);
+    assert_eq!(filler.push(&candle(5 * MINUTE, 101.0, 2.0)).len(), 1);
+    assert!(filler.push(&candle(0, 99.0, 1.0)).is_empty());
+
+    let pushed = filler.push(&candle(20 * MINUTE, 110.0, 1.0));
+    assert_eq!(pushed.len(), 3);
+    assert_eq!(pushed[0].c, 101.0);
+    assert_eq!(pushed[0].t.epoch_ms(), 10 * MINUTE);
+    assert_eq!(filler.last().map(|candle| candle.c), Some(110.0));
+}
@@ -0,0 +1,109 @@
+use crypto_com_api::api_request::ApiRequestBuilder;
+use std::time::Duration;
+
+use crypto_com_api::utils::clock::{clock_offset, set_clock_offset, EventTime, SkewEstimator};
+use crypto_com_api::utils::get_epoch_ms;
+
+#[test]
+fn estimate_is_the_fastest_message() {
+    let mut estimator = SkewEstimator::with_capacity(3);
+    assert_eq!(estimator.estimate(), None);
+
+    // Exchange clock 500ms ahead, messages in flight for 40, 10, and 25ms.
+    estimator.observe(10_460, 10_000);
+    estimator.observe(11_490, 11_000);
+    estimator.observe(12_475, 12_000);
+    assert_eq!(estimator.estimate(), Some(490));
+
+    // The fastest sample falls out of the window.
+    estimator.observe(13_470, 13_000);
+    estimator.observe(14_300, 14_000);
+    assert_eq!(estimator.estimate(), Some(475));
+
+    // Exchange clock behind.
+    let mut estimator = SkewEstimator::new();
+    estimator.observe(9_000, 10_000);
+    assert_eq!(estimator.estimate(), Some(-1_000));
+}
+
+#[test]
+fn nonce_uses_the_offset() -> anyhow::Result<()> {
+    let mut estimator = SkewEstimator::new();
+    estimator.observe(get_epoch_ms() + 60_000, get_epoch_ms());
+    let offset = estimator.apply().expect("a sample was observed");
+    assert_eq!(clock_offset(), offset);
+
+    let before = get_epoch_ms();
+    let request = ApiRequestBuilder::default()
+        .with_id(1)
+        .with_method("public/get-instruments")
+        .with_nonce()
+        .build()?;
+    set_clock_offset(0);
+
+    let nonce = request.nonce.expect("the nonce is set");
+    assert!(nonce >= before + 59_000);
+
+    Ok(())
+}
+
+#[test]
+fn event_time_is_corrected_by_the_skew() {
+    // Exchange clock 500ms ahead, published 30ms before it was recieved.
+    let time = EventTime::new(10_470, Some(10_465), 10_000).with_skew(500);
+
+    assert_eq!(time.received_exchange_ms(), 10_500);
+    assert_eq!(time.latency(), Duration::from_millis(30));
+    assert_eq!(time.age_at(10_100), Duration::from_millis(130));
+
+    // A skew that puts the receipt before the publish is no latency rather than an underflow.
+    assert_eq!(time.with_skew(0).latency(), Duration::ZERO);
+}
+
+#[cfg(feature = "websocket")]
+#[test]
+fn event_times_of_market_data() -> anyhow::Result<()> {
+    use crypto_com_api::testing::decode_frame;
+    use crypto_com_api::utils::in_flight::Api;
+
+    let book = decode_frame(
+        Api::Market,
+        r#"{
+            "id": -1, "method": "subscribe", "code": 0,
+            "result": {
+                "instrument_name": "BTC_USDT", "subscription": "book.BTC_USDT.10",
+                "channel": "book", "depth": 10,
+                "data": [{ "bids": [], "asks": [], "tt": 990, "t": 1000, "u": 1, "cs": 0 }]
+            }
+        }"#,
+        1_020,
+    )?;
+    let candlestick = decode_frame(
+        Api::Market,
+        r#"{
+            "id": -1, "method": "subscribe", "code": 0,
+            "result": {
+                "instrument_name": "BTC_USDT", "subscription": "candlestick.1m.BTC_USDT",
+                "interval": "1m", "channel": "candlestick",
+                "data": [{ "t": 0, "ut": 1005, "o": "1", "h": "1", "l": "1", "c": "1", "v": "1" }]
+            }
+        }"#,
+        1_020,
+    )?;
+
+    let book = book[0].result.as_ref().expect("book data");
+    let time = book.event_time().expect("a book has times").with_skew(0);
+
+    assert_eq!(time.published, 1_000);
+    assert_eq!(time.updated, Some(990));
+    assert_eq!(time.latency(), Duration::from_millis(20));
+
+    let candlestick = candlestick[0].result.as_ref().expect("candlestick data");
+    let times = candlestick.event_times();
+
+    assert_eq!(times.len(), 1);
+    assert_eq!(times[0].published, 1_005);
+    assert_eq!(times[0].updated, None);
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+//! Factories shared by the integration tests, included with `mod common;`.
+
+// Not every test uses every factory.
+#![allow(dead_code)]
+
+use crypto_com_api::websocket::data::UserOrderRes;
+use futures_util::StreamExt;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+use tokio_tungstenite::tungstenite::Message;
+
+/// A `user.order` push of one order of `instrument_name`, `fields` set or override the fields of
+/// an active `BUY` of 1 at 1 with nothing filled.
+pub fn user_order(instrument_name: &str, fields: serde_json::Value) -> UserOrderRes {
+    let mut order = serde_json::json!({
+        "status": "ACTIVE", "side": "BUY", "price": 1.0, "quantity": 1.0, "order_id": "1",
+        "client_oid": "", "create_time": 1, "update_time": 1, "type": "LIMIT",
+        "instrument_name": instrument_name, "cumulative_quantity": 0.0,
+        "cumulative_value": 0.0, "avg_price": 0.0, "fee_currency": "USDT",
+        "time_in_force": "GOOD_TILL_CANCEL"
+    });
+
+    if let (Some(order), serde_json::Value::Object(fields)) = (order.as_object_mut(), fields) {
+        order.extend(fields);
+    }
+
+    serde_json::from_value(serde_json::json!({
+        "instrument_name": instrument_name,
+        "subscription": format!("user.order.{instrument_name}"),
+        "channel": "user.order",
+        "data": [order]
+    }))
+    .expect("a valid user order")
+}
+
+/// A websocket server on a free local port standing in for crypto.com, it accepts one connection
+/// and forwards every text frame it receives.
+pub async fn mock_websocket() -> (url::Url, UnboundedReceiver<String>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("a free local port");
+    let address = listener.local_addr().expect("a bound address");
+    let (frames_tx, frames_rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        let Ok((stream, _)) = listener.accept().await else {
+            return;
+        };
+        let Ok(mut websocket) = tokio_tungstenite::accept_async(stream).await else {
+            return;
+        };
+
+        while let Some(Ok(message)) = websocket.next().await {
+            if let Message::Text(text) = message {
+                if frames_tx.send(text).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+
+    let url = format!("ws://{address}").parse().expect("a valid URL");
+
+    (url, frames_rx)
+}
@@ -0,0 +1,134 @@
+#![cfg(feature = "websocket")]
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+use tokio_tungstenite::tungstenite::error::CapacityError;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+
+use crypto_com_api::error::{convert_tungstenite_error, ApiError};
+use crypto_com_api::utils::in_flight::{Api, InFlight};
+use crypto_com_api::websocket::connection_state::{ConnectionState, DisconnectKind};
+use crypto_com_api::websocket::handler::HandlerRegistry;
+use crypto_com_api::websocket::{market_api::process_market, WebsocketData};
+
+#[tokio::test]
+async fn close_frame_is_reported() -> anyhow::Result<()> {
+    let (market_tx, _market_rx) = futures_channel::mpsc::unbounded();
+    let (data_tx, mut data_rx) = tokio::sync::broadcast::channel(4);
+
+    let frame = CloseFrame {
+        code: CloseCode::Away,
+        reason: "maintenance".into(),
+    };
+
+    process_market(
+        Message::Close(Some(frame)),
+        Arc::new(Mutex::new(market_tx)),
+        &data_tx,
+        &InFlight::default(),
+        None,
+        &HandlerRegistry::new(),
+    )
+    .await?;
+
+    let data = data_rx.recv().await?;
+    let Some(WebsocketData::ConnectionState(ref state)) = data.result else {
+        anyhow::bail!("expected a connection state, got {data:?}");
+    };
+
+    assert_eq!(
+        *state,
+        ConnectionState::Closed {
+            api: Api::Market,
+            code: Some(1001),
+            reason: "maintenance".to_owned(),
+        }
+    );
+    assert_eq!(state.api(), Api::Market);
+    assert!(!state.is_connected());
+
+    Ok(())
+}
+
+#[test]
+fn reconnected_is_connected() {
+    assert!(ConnectionState::Reconnected(Api::User).is_connected());
+    assert!(!ConnectionState::Reconnecting(Api::User).is_connected());
+    assert_eq!(
+        ConnectionState::Disconnected {
+            api: Api::User,
+            error: None,
+            kind: None,
+        }
+        .api(),
+        Api::User
+    );
+}
+
+#[test]
+fn tungstenite_errors_are_kept_apart() {
+    assert!(matches!(
+        convert_tungstenite_error(WsError::ConnectionClosed),
+        ApiError::ConnectionClosed { code: None, .. }
+    ));
+    assert!(matches!(
+        convert_tungstenite_error(WsError::Io(std::io::ErrorKind::ConnectionReset.into())),
+        ApiError::Io(_)
+    ));
+    assert!(matches!(
+        convert_tungstenite_error(WsError::Capacity(CapacityError::MessageTooLong {
+            size: 2,
+            max_size: 1,
+        })),
+        ApiError::Capacity(_)
+    ));
+    assert!(matches!(
+        convert_tungstenite_error(WsError::AttackAttempt),
+        ApiError::Protocol(_)
+    ));
+}
+
+#[test]
+fn disconnected_carries_the_close_code() {
+    let res: anyhow::Result<()> = Err(ApiError::ConnectionClosed {
+        code: Some(1001),
+        reason: "maintenance".to_owned(),
+    }
+    .into());
+
+    let ConnectionState::Disconnected { api, error, kind } =
+        ConnectionState::disconnected(Api::Market, &res)
+    else {
+        panic!("expected a disconnected state");
+    };
+
+    assert_eq!(api, Api::Market);
+    assert!(error.is_some_and(|error| error.contains("maintenance")));
+    assert_eq!(
+        kind,
+        Some(DisconnectKind::Closed {
+            code: Some(1001),
+            reason: "maintenance".to_owned(),
+        })
+    );
+
+    assert_eq!(
+        ConnectionState::disconnected(Api::Market, &Ok(())),
+        ConnectionState::Disconnected {
+            api: Api::Market,
+            error: None,
+            kind: None,
+        }
+    );
+    assert_eq!(
+        DisconnectKind::of(&ApiError::Tls("handshake".to_owned()).into()),
+        DisconnectKind::Tls
+    );
+    assert_eq!(
+        DisconnectKind::of(&anyhow::anyhow!("other")),
+        DisconnectKind::Other
+    );
+}
@@ -0,0 +1,75 @@
+#![cfg(feature = "websocket")]
+
+use crypto_com_api::controller::ControllerBuilder;
+use crypto_com_api::prelude::ApiError;
+use crypto_com_api::utils::config::{Config, Credentials};
+use crypto_com_api::websocket::actions::GetInstruments;
+
+#[test]
+fn for_credentials_signs_with_named_keys() -> anyhow::Result<()> {
+    let config = Config {
+        api_key: Some("main".to_owned()),
+        secret_key: Some("main-secret".to_owned()),
+        ..Config::default()
+    }
+    .with_credentials("sub", "sub-key", "sub-secret");
+
+    assert_eq!(
+        config.credentials("sub"),
+        Some(&Credentials {
+            api_key: "sub-key".to_owned(),
+            secret_key: "sub-secret".to_owned(),
+        })
+    );
+
+    let sub = config.for_credentials("sub")?;
+
+    assert_eq!(sub.api_key.as_deref(), Some("sub-key"));
+    assert_eq!(sub.secret_key.as_deref(), Some("sub-secret"));
+    assert_eq!(sub.keyring, config.keyring);
+    assert_eq!(config.api_key.as_deref(), Some("main"));
+
+    Ok(())
+}
+
+#[test]
+fn unknown_credentials_are_rejected() {
+    let err = Config::default()
+        .for_credentials("missing")
+        .expect_err("no credentials were added");
+
+    assert!(matches!(
+        err.downcast_ref::<ApiError>(),
+        Some(ApiError::UnknownCredentials(name)) if name == "missing"
+    ));
+}
+
+#[tokio::test]
+async fn account_action_needs_known_credentials() {
+    let mut controller = ControllerBuilder::new()
+        .with_credentials("sub", "sub-key", "sub-secret")
+        .build();
+
+    let err = controller
+        .push_account_action("other", GetInstruments)
+        .await
+        .expect_err("other is not in the keyring");
+
+    assert!(matches!(
+        &err,
+        ApiError::UnknownCredentials(name) if name == "other"
+    ));
+    assert!(controller.account_queue("other").is_none());
+
+    // Known credentials without a user websocket URL fail to connect.
+    let err = controller
+        .push_account_action("sub", GetInstruments)
+        .await
+        .expect_err("no user websocket URL is set");
+
+    assert!(matches!(
+        &err,
+        ApiError::ConfigMissing(field) if field == "websocket_user_api"
+    ));
+    assert!(controller.accounts.is_empty());
+}
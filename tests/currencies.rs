@@ -0,0 +1,125 @@
+#![cfg(all(feature = "rest", feature = "websocket"))]
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crypto_com_api::rest::data::{
+    ConversionRate, Currencies, CurrencyMap, CurrencyNetworks, InstrumentsRes, RawConversionRate,
+    RawInstrumentsRes,
+};
+use crypto_com_api::rest::instrument_cache::InstrumentCache;
+use crypto_com_api::types::{EpochMs, Time};
+use crypto_com_api::utils::config::Config;
+
+/// A raw instrument trading `base` against `quote`.
+fn instrument(base: &str, quote: &str, quantity_decimals: u8) -> String {
+    format!(
+        r#"{{
+            "instrument_name": "{base}_{quote}",
+            "quote_currency": "{quote}",
+            "base_currency": "{base}",
+            "price_decimals": 2,
+            "quantity_decimals": {quantity_decimals},
+            "margin_trading_enabled": false,
+            "margin_trading_enabled_5x": false,
+            "margin_trading_enabled_10x": false,
+            "max_quantity": "100000000",
+            "min_quantity": "0.0001",
+            "max_price": "1000000",
+            "min_price": "0.01",
+            "last_update_date": 0,
+            "quantity_tick_size": "0.0001",
+            "price_tick_size": "0.05"
+        }}"#
+    )
+}
+
+fn instruments() -> InstrumentsRes {
+    let raw: RawInstrumentsRes = serde_json::from_str(&format!(
+        r#"{{"instruments": [{}, {}, {}]}}"#,
+        instrument("BTC", "USDT", 6),
+        instrument("ETH", "BTC", 4),
+        instrument("BTC", "USD", 5),
+    ))
+    .expect("instruments fixture");
+
+    InstrumentsRes::try_from(raw).expect("instruments")
+}
+
+#[test]
+fn currencies_are_gathered_from_the_instruments() {
+    let currencies = Currencies::from(&instruments());
+
+    let names: Vec<&str> = currencies
+        .iter()
+        .map(|currency| currency.currency.as_str())
+        .collect();
+    assert_eq!(names, ["BTC", "ETH", "USD", "USDT"]);
+
+    let btc = currencies.get("BTC").expect("BTC");
+    assert_eq!(btc.decimals, Some(6));
+    assert_eq!(btc.base_of, ["BTC_USDT", "BTC_USD"]);
+    assert_eq!(btc.quote_of, ["ETH_BTC"]);
+    assert_eq!(btc.display_name, None);
+
+    // Only ever quoted, so its decimals are unknown.
+    assert_eq!(currencies.get("USDT").and_then(|usdt| usdt.decimals), None);
+
+    let networks = CurrencyNetworks {
+        update_time: Time::from_epoch_ms(0),
+        currency_map: HashMap::from([(
+            "BTC".to_owned(),
+            CurrencyMap {
+                full_name: "Bitcoin".to_owned(),
+                default_network: "BTC".to_owned(),
+                network_list: vec![],
+            },
+        )]),
+    };
+    let currencies = currencies.with_networks(&networks);
+
+    assert_eq!(
+        currencies
+            .get("BTC")
+            .and_then(|btc| btc.display_name.as_deref()),
+        Some("Bitcoin")
+    );
+    assert_eq!(
+        currencies
+            .get("ETH")
+            .and_then(|eth| eth.display_name.clone()),
+        None
+    );
+}
+
+#[tokio::test]
+async fn currencies_are_cached_with_the_instruments() -> anyhow::Result<()> {
+    let cache = InstrumentCache::new(Config::default(), Duration::from_secs(60));
+    cache.insert(instruments());
+
+    assert_eq!(cache.currencies().await?.len(), 4);
+    assert_eq!(
+        cache.currency("ETH").await?.map(|eth| eth.base_of),
+        Some(vec!["ETH_BTC".to_owned()])
+    );
+    assert!(cache.currency("CRO").await?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn conversion_rates_are_parsed() -> anyhow::Result<()> {
+    let raw: RawConversionRate = serde_json::from_str(
+        r#"{"instrument_name": "CDCETH", "conversion_rate": "1.0203040506"}"#,
+    )?;
+
+    assert_eq!(
+        ConversionRate::try_from(&raw)?,
+        ConversionRate {
+            instrument_name: "CDCETH".to_owned(),
+            conversion_rate: 1.020_304_050_6,
+        }
+    );
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+#![cfg(feature = "websocket")]
+
+use crypto_com_api::api_response::ApiResponse;
+use crypto_com_api::websocket::data::OpenOrders;
+use crypto_com_api::websocket::{market_api::decode_subscription, WebsocketData};
+
+#[test]
+fn ticker_can_be_cloned_compared_and_serialized() -> anyhow::Result<()> {
+    let msg: ApiResponse<serde_json::Value> = serde_json::from_str(
+        r#"{
+            "id": -1,
+            "method": "subscribe",
+            "code": 0,
+            "result": {
+                "instrument_name": "BTCUSD-PERP",
+                "subscription": "ticker.BTCUSD-PERP",
+                "channel": "ticker",
+                "data": [{
+                    "h": "51790.00", "l": "47895.50", "a": "51174.500", "i": "BTCUSD-PERP",
+                    "v": "879.5024", "vv": "26370000.12", "oi": "12345.12",
+                    "c": "0.03955106", "b": "51170.000", "bs": "0.1000", "k": "51180.000",
+                    "ks": "0.2000", "t": 1613580710768
+                }]
+            }
+        }"#,
+    )?;
+
+    let Some(WebsocketData::Ticker(ticker)) = decode_subscription(&msg, 5)? else {
+        panic!("expected ticker data");
+    };
+
+    let snapshot = ticker.clone();
+    assert_eq!(snapshot, ticker);
+
+    let json = serde_json::to_value(&ticker)?;
+    assert_eq!(json["subscription"], "ticker.BTCUSD-PERP");
+    assert_eq!(json["data"][0]["i"], "BTCUSD-PERP");
+    assert_eq!(json["data"][0]["a"], 51174.5);
+    assert_eq!(json["received_at"], 5);
+
+    Ok(())
+}
+
+#[test]
+fn open_orders_round_trip() -> anyhow::Result<()> {
+    let orders: OpenOrders = serde_json::from_str(
+        r#"{
+            "count": 1,
+            "order_list": [{
+                "status": "ACTIVE",
+                "side": "BUY",
+                "price": 1.0,
+                "quantity": 1.0,
+                "order_id": "2015106383706015873",
+                "client_oid": "my_order_0002",
+                "create_time": 1613575617173,
+                "update_time": 1613575617173,
+                "type": "LIMIT",
+                "instrument_name": "ETH_CRO",
+                "cumulative_quantity": 0.0,
+                "cumulative_value": 0.0,
+                "avg_price": 0.0,
+                "fee_currency": "CRO",
+                "time_in_force": "GOOD_TILL_CANCEL"
+            }]
+        }"#,
+    )?;
+
+    let json = serde_json::to_string(&orders)?;
+    assert!(json.contains(r#""type":"LIMIT""#));
+
+    let restored: OpenOrders = serde_json::from_str(&json)?;
+    assert_eq!(restored, orders);
+
+    Ok(())
+}
@@ -0,0 +1,120 @@
+#![cfg(feature = "websocket")]
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+use tokio_tungstenite::tungstenite::Message;
+
+use crypto_com_api::testing::decode_frame;
+use crypto_com_api::utils::in_flight::{Api, InFlight};
+use crypto_com_api::websocket::dedup::{EventKey, UserDedup, UserDedupHandler, DEDUP_METHODS};
+use crypto_com_api::websocket::handler::HandlerRegistry;
+use crypto_com_api::websocket::{user_api::process_user, WebsocketData};
+
+/// A `user.trade` push of trade `trade_id`.
+fn trade_push(trade_id: u64) -> String {
+    format!(
+        r#"{{"id":-1,"method":"subscribe","code":0,"result":{{"instrument_name":"ETH_CRO","subscription":"user.trade.ETH_CRO","channel":"user.trade","data":[{{"side":"SELL","fee":0.014,"trade_id":"{trade_id}","create_time":1588777459755,"traded_price":7,"traded_quantity":1,"fee_currency":"CRO","order_id":"1"}}]}}}}"#
+    )
+}
+
+/// The `private/get-trades` reply to request `id` holding trade `trade_id`.
+fn trades_reply(id: u64, trade_id: u64) -> String {
+    format!(
+        r#"{{"id":{id},"method":"private/get-trades","code":0,"result":{{"trade_list":[{{"side":"SELL","instrument_name":"ETH_CRO","fee":0.014,"trade_id":"{trade_id}","create_time":1588777459755,"traded_price":7,"traded_quantity":1,"fee_currency":"CRO","order_id":"1"}}]}}}}"#
+    )
+}
+
+/// The data `dedup` lets through for `frame`.
+fn check(dedup: &mut UserDedup, frame: &str) -> Vec<WebsocketData> {
+    decode_frame(Api::User, frame, 0)
+        .expect("a user frame")
+        .into_iter()
+        .filter_map(|data| Arc::try_unwrap(data).ok()?.result)
+        .filter_map(|mut data| (!dedup.check(&mut data)).then_some(data))
+        .collect()
+}
+
+#[test]
+fn repeated_pushes_are_dropped() {
+    let mut dedup = UserDedup::new(2);
+
+    assert_eq!(check(&mut dedup, &trade_push(1)).len(), 1);
+    assert!(check(&mut dedup, &trade_push(1)).is_empty());
+
+    // Replies are passed whole, but what they return is not pushed again.
+    let reply = check(&mut dedup, &trades_reply(4, 2));
+    assert!(matches!(
+        reply[..],
+        [WebsocketData::GetTrades(ref trades)] if trades.trade_list.len() == 1
+    ));
+    assert!(check(&mut dedup, &trade_push(2)).is_empty());
+    assert!(dedup.contains(&EventKey::Trade("2".to_owned())));
+
+    // The least recently seen trade is forgotten first.
+    check(&mut dedup, &trade_push(3));
+    assert_eq!(dedup.len(), 2);
+    assert!(!dedup.contains(&EventKey::Trade("1".to_owned())));
+    assert_eq!(check(&mut dedup, &trade_push(1)).len(), 1);
+}
+
+#[test]
+fn order_updates_are_keyed_by_update_time() -> anyhow::Result<()> {
+    let mut dedup = UserDedup::default();
+    let fixture = std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/user/user_order.json"
+    ))?;
+
+    assert_eq!(check(&mut dedup, &fixture).len(), 1);
+    assert!(check(&mut dedup, &fixture).is_empty());
+
+    // A later update of the same order is new.
+    let updated = fixture.replace("\"update_time\": 1", "\"update_time\": 2");
+    assert_ne!(updated, fixture);
+    assert_eq!(check(&mut dedup, &updated).len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn handler_filters_the_broadcast() -> anyhow::Result<()> {
+    let (user_tx, _user_rx) = futures_channel::mpsc::unbounded();
+    let user_tx = Arc::new(Mutex::new(user_tx));
+    let (data_tx, mut data_rx) = tokio::sync::broadcast::channel(16);
+    let in_flight = InFlight::default();
+
+    let handler = UserDedupHandler::new(UserDedup::default());
+    let handlers = DEDUP_METHODS
+        .iter()
+        .fold(HandlerRegistry::new(), |handlers, &method| {
+            handlers.with_handler(method, handler.clone())
+        });
+
+    for frame in [trade_push(1), trades_reply(7, 1), trade_push(1)] {
+        process_user(
+            Message::Text(frame),
+            Arc::clone(&user_tx),
+            &data_tx,
+            &in_flight,
+            None,
+            &handlers,
+        )
+        .await?;
+    }
+
+    let mut sent = vec![];
+
+    while let Ok(data) = data_rx.try_recv() {
+        sent.push(data.id);
+    }
+
+    // The push, then the reply, the repeated push is dropped.
+    assert_eq!(sent, [-1, 7]);
+    assert_eq!(
+        handler.dedup().lock().map(|dedup| dedup.len()).ok(),
+        Some(1)
+    );
+
+    Ok(())
+}
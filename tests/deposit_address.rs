@@ -0,0 +1,67 @@
+#![cfg(feature = "rest")]
+
+use crypto_com_api::rest::data::DepositAddress;
+
+fn addresses() -> DepositAddress {
+    serde_json::from_str(
+        r#"{
+            "deposit_address_list": [
+                {
+                    "id": 1,
+                    "currency": "CRO",
+                    "network": "CRO",
+                    "address": "cro1old",
+                    "create_time": 1615886328000,
+                    "status": "0"
+                },
+                {
+                    "id": 2,
+                    "currency": "CRO",
+                    "network": "ETH",
+                    "address": "0xerc20",
+                    "create_time": 1615886329000,
+                    "status": "1"
+                },
+                {
+                    "id": 3,
+                    "currency": "CRO",
+                    "network": "CRO",
+                    "address": "cro1active?memo=42",
+                    "create_time": 1615886327000,
+                    "status": "1"
+                }
+            ]
+        }"#,
+    )
+    .expect("deposit address fixture")
+}
+
+#[test]
+fn addresses_are_selected_by_network() {
+    let addresses = addresses();
+
+    assert_eq!(addresses.on_network("cro").count(), 2);
+
+    // Active addresses are preferred over newer inactive ones.
+    let cro = addresses.select("CRO", "CRO").expect("a CRO address");
+    assert_eq!(cro.id, 3);
+    assert_eq!(cro.base_address(), Some("cro1active"));
+    assert_eq!(cro.address_tag(), Some("42"));
+
+    assert_eq!(addresses.select("CRO", "ETH").map(|item| item.id), Some(2));
+    assert!(addresses.select("CRO", "BTC").is_none());
+}
+
+#[test]
+fn payment_uris_keep_the_tag() {
+    let addresses = addresses();
+
+    let erc20 = addresses.select("CRO", "ETH").expect("an ERC20 address");
+    assert_eq!(erc20.payment_uri(None).as_deref(), Some("ethereum:0xerc20"));
+
+    let cro = addresses.select("CRO", "CRO").expect("a CRO address");
+    assert_eq!(
+        cro.payment_uri(Some(12.5)).as_deref(),
+        Some("cro:cro1active?memo=42&amount=12.5")
+    );
+}
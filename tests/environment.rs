@@ -0,0 +1,57 @@
+#![cfg(feature = "rest")]
+
+use crypto_com_api::prelude::ApiError;
+use crypto_com_api::rest::private::{create_withdrawal, CreateWithdrawal};
+use crypto_com_api::utils::config::Config;
+use crypto_com_api::utils::environment::Environment;
+
+#[test]
+fn environments_supply_their_urls() {
+    let config = Config::for_environment(Environment::UatSandbox);
+
+    assert_eq!(config.environment, Some(Environment::UatSandbox));
+    assert_eq!(
+        config.rest_url.map(String::from).as_deref(),
+        Some("https://uat-api.3ona.co/v2/")
+    );
+    assert_eq!(
+        config.websocket_user_api.map(String::from).as_deref(),
+        Some("wss://uat-stream.3ona.co/v2/user")
+    );
+
+    for environment in [Environment::Production, Environment::UatSandbox] {
+        assert_eq!(environment.rest_url().as_str(), environment.rest_url_str());
+        assert_eq!(
+            environment.websocket_market_url().as_str(),
+            environment.websocket_market_url_str()
+        );
+    }
+}
+
+#[tokio::test]
+async fn sandbox_refuses_withdrawals() {
+    let config = Config::for_environment(Environment::UatSandbox).with_credentials(
+        "main",
+        "api_key",
+        "secret_key",
+    );
+    let config = config.for_credentials("main").expect("credentials are set");
+
+    let params = CreateWithdrawal {
+        client_wid: None,
+        currency: "CRO".to_owned(),
+        amount: 10.0,
+        address: "2NBqqD5GRJ8wHy1PYyCXTe9ke5226FhavBf".to_owned(),
+        address_tag: None,
+        network_id: None,
+    };
+
+    let err = create_withdrawal(&config, params)
+        .await
+        .expect_err("the sandbox refuses withdrawals");
+
+    assert!(matches!(
+        &err,
+        ApiError::WithdrawalRefused(Environment::UatSandbox)
+    ));
+}
@@ -0,0 +1,76 @@
+#![cfg(feature = "websocket")]
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+use tokio_tungstenite::tungstenite::Message;
+
+use crypto_com_api::prelude::ApiError;
+use crypto_com_api::utils::config::ErrorPolicy;
+use crypto_com_api::utils::in_flight::{Api, InFlight};
+use crypto_com_api::websocket::handler::HandlerRegistry;
+use crypto_com_api::websocket::{
+    handle_processing_error, market_api::process_market, WebsocketData,
+};
+
+async fn malformed() -> anyhow::Error {
+    let (market_tx, _market_rx) = futures_channel::mpsc::unbounded();
+    let (data_tx, _data_rx) = tokio::sync::broadcast::channel(4);
+
+    process_market(
+        Message::Text("{not json".to_owned()),
+        Arc::new(Mutex::new(market_tx)),
+        &data_tx,
+        &InFlight::default(),
+        None,
+        &HandlerRegistry::new(),
+    )
+    .await
+    .expect_err("the message is not json")
+}
+
+#[tokio::test]
+async fn fail_fast_stops_the_stream() {
+    let (data_tx, _data_rx) = tokio::sync::broadcast::channel(4);
+
+    let res = handle_processing_error(
+        ErrorPolicy::FailFast,
+        Api::Market,
+        malformed().await,
+        &data_tx,
+    );
+
+    assert!(matches!(res, Err(ApiError::SerdeJSON)));
+}
+
+#[tokio::test]
+async fn skip_and_log_carries_on() {
+    let (data_tx, mut data_rx) = tokio::sync::broadcast::channel(4);
+
+    let res = handle_processing_error(
+        ErrorPolicy::SkipAndLog,
+        Api::Market,
+        malformed().await,
+        &data_tx,
+    );
+
+    assert!(res.is_ok());
+    assert!(data_rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn forward_sends_the_error() -> anyhow::Result<()> {
+    let (data_tx, mut data_rx) = tokio::sync::broadcast::channel(4);
+
+    handle_processing_error(ErrorPolicy::Forward, Api::User, malformed().await, &data_tx)?;
+
+    let data = data_rx.recv().await?;
+    let Some(WebsocketData::ProcessingError { api, ref error }) = data.result else {
+        anyhow::bail!("expected a processing error, got {data:?}");
+    };
+
+    assert_eq!(api, Api::User);
+    assert!(!error.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,129 @@
+#![cfg(feature = "websocket")]
+
+mod common;
+
+use std::sync::Arc;
+
+use crypto_com_api::api_response::ApiResponse;
+use crypto_com_api::controller::ControllerBuilder;
+use crypto_com_api::fills::{FillEvent, FillStatus, FillTracker, OrderRef};
+use crypto_com_api::types::{EpochMs, Time};
+use crypto_com_api::websocket::data::{CreateOrder, EventKind, UserTrade, UserTradeRes};
+use crypto_com_api::websocket::WebsocketData;
+
+fn trades(fills: &[(u64, u64, f64, f64)]) -> WebsocketData {
+    WebsocketData::UserTrade(UserTradeRes {
+        instrument_name: "ETH_CRO".to_owned(),
+        subscription: "user.trade.ETH_CRO".to_owned(),
+        channel: "user.trade".to_owned(),
+        data: fills
+            .iter()
+            .map(|&(trade_id, order_id, price, quantity)| UserTrade {
+                side: "BUY".to_owned(),
+                fee: 0.1,
+                trade_id,
+                create_time: Time::from_epoch_ms(1),
+                traded_price: price,
+                traded_quantity: quantity,
+                fee_currency: "CRO".to_owned(),
+                order_id,
+            })
+            .collect(),
+        kind: EventKind::Update,
+    })
+}
+
+fn order(order_id: u64, status: &str, quantity: f64, cumulative_quantity: f64) -> WebsocketData {
+    WebsocketData::UserOrder(common::user_order(
+        "ETH_CRO",
+        serde_json::json!({
+            "status": status, "quantity": quantity, "order_id": order_id.to_string(),
+            "client_oid": "my-order", "cumulative_quantity": cumulative_quantity,
+            "fee_currency": "CRO"
+        }),
+    ))
+}
+
+#[test]
+fn partial_fills_complete_at_the_order_quantity() {
+    let mut tracker = FillTracker::new(OrderRef::OrderId(7));
+
+    assert!(tracker.process(&order(7, "ACTIVE", 3.0, 0.0)).is_empty());
+
+    let events = tracker.process(&trades(&[(1, 7, 10.0, 1.0), (2, 8, 99.0, 5.0)]));
+    assert!(matches!(
+        events[..],
+        [FillEvent::Fill(ref fill)] if fill.filled_quantity == 1.0
+    ));
+
+    // A repeated trade is only counted once.
+    assert!(tracker.process(&trades(&[(1, 7, 10.0, 1.0)])).is_empty());
+
+    let events = tracker.process(&trades(&[(3, 7, 13.0, 2.0)]));
+    let [FillEvent::Fill(_), FillEvent::Complete(ref summary)] = events[..] else {
+        panic!("expected a fill and completion, got {events:?}");
+    };
+
+    assert_eq!(summary.order_id, 7);
+    assert_eq!(summary.status, FillStatus::Filled);
+    assert_eq!(summary.filled_quantity, 3.0);
+    assert_eq!(summary.average_price, Some(12.0));
+    assert!((summary.fees - 0.2).abs() < 1e-9);
+
+    assert!(tracker.is_complete());
+    assert!(tracker.process(&trades(&[(4, 7, 13.0, 1.0)])).is_empty());
+}
+
+#[test]
+fn client_oid_is_resolved_with_earlier_trades() {
+    let mut tracker =
+        FillTracker::new(OrderRef::ClientOid("my-order".to_owned())).with_quantity(2.0);
+
+    assert!(tracker.process(&trades(&[(1, 7, 10.0, 1.0)])).is_empty());
+    assert_eq!(tracker.order_id(), None);
+
+    let events = tracker.process(&WebsocketData::CreateOrder(CreateOrder {
+        order_id: 7,
+        client_oid: Some("my-order".to_owned()),
+    }));
+
+    assert_eq!(tracker.order_id(), Some(7));
+    assert!(matches!(events[..], [FillEvent::Fill(_)]));
+}
+
+#[test]
+fn cancel_completes_once_its_fills_arrive() {
+    let mut tracker = FillTracker::new(OrderRef::OrderId(7));
+
+    assert!(tracker.process(&order(7, "CANCELED", 3.0, 1.0)).is_empty());
+
+    let events = tracker.process(&trades(&[(1, 7, 10.0, 1.0)]));
+    let [FillEvent::Fill(_), FillEvent::Complete(ref summary)] = events[..] else {
+        panic!("expected a fill and completion, got {events:?}");
+    };
+
+    assert_eq!(summary.status, FillStatus::Closed("CANCELED".to_owned()));
+    assert_eq!(summary.filled_quantity, 1.0);
+}
+
+#[tokio::test]
+async fn stream_ends_after_completion() -> anyhow::Result<()> {
+    let mut controller = ControllerBuilder::new().build();
+    let mut fills = controller.watch_fills(OrderRef::OrderId(7));
+
+    for data in [order(7, "REJECTED", 3.0, 0.0), trades(&[(1, 7, 10.0, 1.0)])] {
+        controller.data_tx.send(Arc::new(
+            ApiResponse::<WebsocketData>::default().websocket_data(data),
+        ))?;
+    }
+
+    let Some(FillEvent::Complete(summary)) = fills.next().await else {
+        anyhow::bail!("expected the order to be complete");
+    };
+
+    assert_eq!(summary.status, FillStatus::Closed("REJECTED".to_owned()));
+    assert_eq!(summary.average_price, None);
+    assert_eq!(fills.next().await, None);
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+#![cfg(feature = "websocket")]
+
+//! Decodes every frame in `tests/fixtures/{market,user}` and compares the data, as JSON, with the
+//! `.snap` file next to it. Timestamps serialize differently with the `datetime` feature, which
+//! compares with the `.datetime.snap` file instead. Run with `UPDATE_GOLDEN=1` to write the
+//! snapshots after a deliberate change.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crypto_com_api::testing::decode_frame;
+use crypto_com_api::utils::in_flight::Api;
+
+/// Received time given to every frame, so the snapshots do not change between runs.
+const RECEIVED_AT: u64 = 1_700_000_000_000;
+
+/// Extension of the snapshots for the enabled features.
+const SNAPSHOT: &str = if cfg!(feature = "datetime") {
+    "datetime.snap"
+} else {
+    "snap"
+};
+
+fn fixtures(dir: &str) -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(dir);
+    let mut paths: Vec<PathBuf> = fs::read_dir(&dir)
+        .unwrap_or_else(|err| panic!("cannot read {}: {err}", dir.display()))
+        .map(|entry| entry.expect("a fixture entry").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+
+    paths.sort();
+    paths
+}
+
+fn render(api: Api, path: &Path) -> String {
+    let text = fs::read_to_string(path).expect("a readable fixture");
+    let sent = decode_frame(api, &text, RECEIVED_AT)
+        .unwrap_or_else(|err| panic!("{} failed to decode: {err:#}", path.display()));
+
+    let mut rendered = String::new();
+
+    for data in sent {
+        let json = serde_json::to_string_pretty(&*data).expect("decoded data serializes");
+        writeln!(rendered, "{json}").expect("writing to a string");
+    }
+
+    rendered
+}
+
+fn check(api: Api, dir: &str) {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let mut failed = vec![];
+
+    for path in fixtures(dir) {
+        let rendered = render(api, &path);
+        let snap = path.with_extension(SNAPSHOT);
+
+        if update {
+            fs::write(&snap, rendered).expect("a writable snapshot");
+            continue;
+        }
+
+        match fs::read_to_string(&snap) {
+            Ok(expected) if expected == rendered => {}
+            Ok(expected) => failed.push(format!(
+                "{} changed.\n--- expected\n{expected}\n+++ decoded\n{rendered}",
+                snap.display()
+            )),
+            Err(_) => failed.push(format!("{} is missing.", snap.display())),
+        }
+    }
+
+    assert!(
+        failed.is_empty(),
+        "{}\nRun with UPDATE_GOLDEN=1 if the changes are intended.",
+        failed.join("\n")
+    );
+}
+
+#[test]
+fn market_fixtures_match_snapshots() {
+    check(Api::Market, "market");
+}
+
+#[test]
+fn user_fixtures_match_snapshots() {
+    check(Api::User, "user");
+}
@@ -0,0 +1,32 @@
+#![cfg(feature = "rest")]
+
+use crypto_com_api::rest::data::valuations::{funding_payment, RawValuationsRes, ValuationsRes};
+use crypto_com_api::types::EpochMs;
+
+#[test]
+fn latest_funding_rate() -> anyhow::Result<()> {
+    let raw: RawValuationsRes = serde_json::from_str(
+        r#"{
+            "instrument_name": "BTCUSD-PERP",
+            "data": [
+                {"v": "0.00002", "t": 1654755600000},
+                {"v": "-0.00001", "t": 1654759200000},
+                {"v": "0.00003", "t": 1654752000000}
+            ]
+        }"#,
+    )?;
+    let res = ValuationsRes::try_from(raw)?;
+
+    let latest = res.latest().expect("a funding rate");
+    assert_eq!(latest.t.epoch_ms(), 1_654_759_200_000);
+    assert!((latest.v + 0.00001).abs() < f64::EPSILON);
+
+    Ok(())
+}
+
+#[test]
+fn longs_pay_positive_funding() {
+    assert!((funding_payment(2.0, 30_000.0, 0.0001) - 6.0).abs() < 1e-9);
+    assert!((funding_payment(-2.0, 30_000.0, 0.0001) + 6.0).abs() < 1e-9);
+    assert!((funding_payment(2.0, 30_000.0, -0.0001) + 6.0).abs() < 1e-9);
+}
@@ -0,0 +1,98 @@
+#![cfg(feature = "websocket")]
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+use tokio_tungstenite::tungstenite::Message;
+
+use crypto_com_api::api_response::ApiResponse;
+use crypto_com_api::prelude::ApiError;
+use crypto_com_api::utils::in_flight::InFlight;
+use crypto_com_api::websocket::handler::{HandlerContext, HandlerRegistry};
+use crypto_com_api::websocket::{user_api::process_user, WebsocketData};
+
+async fn process(
+    frame: &str,
+    handlers: &HandlerRegistry,
+) -> anyhow::Result<Vec<Arc<ApiResponse<WebsocketData>>>> {
+    let (user_tx, _user_rx) = futures_channel::mpsc::unbounded();
+    let (data_tx, mut data_rx) = tokio::sync::broadcast::channel(8);
+
+    process_user(
+        Message::Text(frame.to_owned()),
+        Arc::new(Mutex::new(user_tx)),
+        &data_tx,
+        &InFlight::default(),
+        None,
+        handlers,
+    )
+    .await?;
+
+    let mut sent = vec![];
+
+    while let Ok(data) = data_rx.try_recv() {
+        sent.push(data);
+    }
+
+    Ok(sent)
+}
+
+#[tokio::test]
+async fn custom_handler_for_new_method() -> anyhow::Result<()> {
+    let frame = r#"{"id": 4, "method": "private/get-fee-rate", "code": 0, "result": {}}"#;
+
+    let err = process(frame, &HandlerRegistry::new())
+        .await
+        .expect_err("method is not built in");
+    assert!(matches!(
+        err.downcast_ref::<ApiError>(),
+        Some(ApiError::UnsupportedMethod(_))
+    ));
+
+    let handlers =
+        HandlerRegistry::new().with_handler("private/get-fee-rate", |ctx: &HandlerContext<'_>| {
+            assert_eq!(ctx.method(), "private/get-fee-rate");
+            ctx.send(WebsocketData::Auth);
+
+            Ok(())
+        });
+
+    let sent = process(frame, &handlers).await?;
+
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].id, 4);
+    assert!(matches!(sent[0].result, Some(WebsocketData::Auth)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn custom_handler_replaces_builtin() -> anyhow::Result<()> {
+    let frame = r#"{"id": 1, "method": "public/auth", "code": 0}"#;
+
+    let sent = process(frame, &HandlerRegistry::new()).await?;
+    assert!(matches!(sent[0].result, Some(WebsocketData::Auth)));
+
+    let handlers =
+        HandlerRegistry::new().with_handler("public/auth", |_: &HandlerContext<'_>| Ok(()));
+
+    assert!(process(frame, &handlers).await?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn builtin_handlers_cover_the_methods() {
+    let mut methods: Vec<&str> = crypto_com_api::websocket::market_api::builtin_handlers()
+        .methods()
+        .collect();
+    methods.sort_unstable();
+
+    assert_eq!(
+        methods,
+        ["ping", "public/heartbeat", "subscribe", "unsubscribe"]
+    );
+    assert!(crypto_com_api::websocket::user_api::builtin_handlers()
+        .get("private/cancel-order")
+        .is_some());
+}
@@ -0,0 +1,80 @@
+#![cfg(feature = "websocket")]
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+use crypto_com_api::prelude::ApiError;
+use crypto_com_api::rest::public::get_instruments;
+use crypto_com_api::utils::config::Config;
+use crypto_com_api::utils::environment::Environment;
+use crypto_com_api::websocket::handshake_request;
+
+fn gateway_config() -> Config {
+    Config::default()
+        .with_header("X-Forwarded-For", "10.0.0.1")
+        .with_user_agent("desk-gateway/1.0")
+}
+
+#[test]
+fn handshake_carries_headers() -> anyhow::Result<()> {
+    let url = Environment::Production.websocket_market_url();
+    let request = handshake_request(&gateway_config(), &url)?;
+
+    assert_eq!(request.headers()["x-forwarded-for"], "10.0.0.1");
+    assert_eq!(request.headers()["user-agent"], "desk-gateway/1.0");
+    assert!(request.headers().contains_key("sec-websocket-key"));
+
+    Ok(())
+}
+
+#[test]
+fn invalid_header_is_rejected() {
+    let config = Config::default().with_header("Bad Header", "value");
+
+    assert!(matches!(
+        config.header_map(),
+        Err(ApiError::InvalidHeader(ref name)) if name == "Bad Header"
+    ));
+}
+
+#[tokio::test]
+async fn rest_requests_carry_headers() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+
+    let server = thread::spawn(move || -> std::io::Result<String> {
+        let (mut stream, _) = listener.accept()?;
+        let mut request = vec![];
+        let mut buf = [0; 1024];
+
+        while !request.ends_with(b"\r\n\r\n") {
+            let read = stream.read(&mut buf)?;
+            if read == 0 {
+                break;
+            }
+            request.extend_from_slice(&buf[..read]);
+        }
+
+        let body = r#"{"id": 1, "method": "public/get-instruments", "code": 0}"#;
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        )?;
+
+        Ok(String::from_utf8_lossy(&request).to_lowercase())
+    });
+
+    let config = Config {
+        rest_url: Some(url::Url::parse(&format!("http://{addr}/v2/"))?),
+        ..gateway_config()
+    };
+    get_instruments(&config).await?;
+
+    let request = server.join().expect("server thread")?;
+    assert!(request.contains("x-forwarded-for: 10.0.0.1"));
+    assert!(request.contains("user-agent: desk-gateway/1.0"));
+
+    Ok(())
+}
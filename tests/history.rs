@@ -0,0 +1,206 @@
+#![cfg(feature = "rest")]
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crypto_com_api::history::{
+    CandleDownloader, CandleSource, CsvSink, TradeDownloader, TradeSource, TOO_MANY_REQUESTS,
+};
+use crypto_com_api::prelude::ApiError;
+use crypto_com_api::rest::data::{Candlestick, CandlestickParams, Trade, TradesParams};
+use crypto_com_api::types::{EpochMs, Interval, Time};
+
+const MINUTE: u64 = 60_000;
+
+/// One candle a minute, served newest first with the last candle of each page repeated on the
+/// next, and rate limited on the first `rate_limited` requests.
+struct FakeSource {
+    rate_limited: AtomicU32,
+}
+
+impl FakeSource {
+    fn new(rate_limited: u32) -> Self {
+        Self {
+            rate_limited: AtomicU32::new(rate_limited),
+        }
+    }
+}
+
+impl CandleSource for FakeSource {
+    async fn fetch(&self, params: &CandlestickParams) -> anyhow::Result<Vec<Candlestick>> {
+        if self
+            .rate_limited
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok()
+        {
+            anyhow::bail!(ApiError::RequestFailed {
+                code: TOO_MANY_REQUESTS,
+                message: None,
+            });
+        }
+
+        let start = params.start_ts.unwrap_or_default().saturating_sub(MINUTE);
+        let end = params.end_ts.unwrap_or_default();
+
+        #[allow(clippy::cast_precision_loss)]
+        Ok((start..=end)
+            .rev()
+            .filter(|t| t % MINUTE == 0)
+            .map(|t| Candlestick {
+                t: Time::from_epoch_ms(t),
+                c: (t / MINUTE) as f64,
+                ..Candlestick::default()
+            })
+            .collect())
+    }
+}
+
+#[tokio::test]
+async fn pages_are_stitched_in_order() -> anyhow::Result<()> {
+    let source = FakeSource::new(0);
+    let mut candles: Vec<Candlestick> = Vec::new();
+    let updates = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&updates);
+
+    let progress = CandleDownloader::new("BTC_USDT", Interval::OneMinute, 0, 25 * MINUTE)
+        .with_page_size(10)
+        .with_min_request_interval(Duration::ZERO)
+        .with_progress(move |progress| seen.lock().unwrap().push(progress.cursor))
+        .download(&source, &mut candles)
+        .await?;
+
+    let times: Vec<u64> = candles
+        .iter()
+        .map(|candle| candle.t.epoch_ms() / MINUTE)
+        .collect();
+    assert_eq!(times, (0..25).collect::<Vec<_>>());
+    assert_eq!(progress.requests, 3);
+    assert_eq!(progress.written, 25);
+    assert!(progress.fetched > progress.written);
+    assert!((progress.fraction() - 1.0).abs() < f64::EPSILON);
+    assert_eq!(
+        *updates.lock().unwrap(),
+        vec![10 * MINUTE, 20 * MINUTE, 25 * MINUTE]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn rate_limited_requests_are_retried() -> anyhow::Result<()> {
+    let source = FakeSource::new(2);
+    let mut candles: Vec<Candlestick> = Vec::new();
+
+    let progress = CandleDownloader::new("BTC_USDT", Interval::OneMinute, 0, 5 * MINUTE)
+        .with_min_request_interval(Duration::from_millis(1))
+        .download(&source, &mut candles)
+        .await?;
+
+    assert_eq!(candles.len(), 5);
+    assert_eq!(progress.requests, 3);
+
+    let source = FakeSource::new(2);
+    let res = CandleDownloader::new("BTC_USDT", Interval::OneMinute, 0, 5 * MINUTE)
+        .with_min_request_interval(Duration::from_millis(1))
+        .with_max_retries(1)
+        .download(&source, &mut Vec::new())
+        .await;
+
+    assert!(res.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn csv_sink_writes_header_and_rows() -> anyhow::Result<()> {
+    let source = FakeSource::new(0);
+    let mut sink = CsvSink::new(Vec::new());
+
+    CandleDownloader::new("BTC_USDT", Interval::OneMinute, MINUTE, 3 * MINUTE)
+        .with_min_request_interval(Duration::ZERO)
+        .download(&source, &mut sink)
+        .await?;
+
+    assert_eq!(
+        String::from_utf8(sink.into_inner())?,
+        "t,o,h,l,c,v\n60000,0,0,0,1,0\n120000,0,0,0,2,0\n"
+    );
+
+    Ok(())
+}
+
+const SECOND: u64 = 1_000;
+
+/// Two trades a second, `{second}a` and `{second}b`, served newest first up to `count`.
+struct FakeTrades;
+
+impl TradeSource for FakeTrades {
+    async fn fetch(&self, params: &TradesParams) -> anyhow::Result<Vec<Trade>> {
+        let start = params.start_ts.unwrap_or_default();
+        let end = params.end_ts.unwrap_or_default();
+        let count = usize::try_from(params.count.unwrap_or(25))?;
+
+        Ok((start..=end)
+            .rev()
+            .filter(|t| t % SECOND == 0)
+            .flat_map(|t| {
+                ["b", "a"].map(|suffix| Trade {
+                    t: Time::from_epoch_ms(t),
+                    d: format!("{}{suffix}", t / SECOND),
+                    ..Trade::default()
+                })
+            })
+            .take(count)
+            .collect())
+    }
+}
+
+#[tokio::test]
+async fn trades_are_walked_backward_without_repeats() -> anyhow::Result<()> {
+    let mut downloader = TradeDownloader::new("BTC_USDT", 0, 10 * SECOND)
+        .with_page_size(5)
+        .with_min_request_interval(Duration::ZERO);
+
+    let trades = downloader.download_vec(&FakeTrades).await?;
+
+    assert_eq!(trades.len(), 20);
+    assert!(trades
+        .windows(2)
+        .all(|pair| pair[0].t.epoch_ms() <= pair[1].t.epoch_ms()));
+
+    let mut ids: Vec<String> = trades.iter().map(|trade| trade.d.clone()).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    let mut expected: Vec<String> = (0..10)
+        .flat_map(|second| [format!("{second}a"), format!("{second}b")])
+        .collect();
+    expected.sort_unstable();
+    assert_eq!(ids, expected);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn trades_can_be_streamed() -> anyhow::Result<()> {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut sink = tx;
+
+    let progress = TradeDownloader::new("BTC_USDT", 2 * SECOND, 5 * SECOND)
+        .with_min_request_interval(Duration::ZERO)
+        .download(&FakeTrades, &mut sink)
+        .await?;
+    drop(sink);
+
+    let mut times = vec![];
+    while let Some(trade) = rx.recv().await {
+        times.push(trade.t.epoch_ms() / SECOND);
+    }
+
+    assert_eq!(times, [4, 4, 3, 3, 2, 2]);
+    assert_eq!(progress.written, 6);
+    assert_eq!(progress.requests, 1);
+    assert!((progress.fraction() - 1.0).abs() < f64::EPSILON);
+
+    Ok(())
+}
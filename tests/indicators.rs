@@ -0,0 +1,135 @@
+#![cfg(feature = "indicators")]
+
+use crypto_com_api::analytics::{
+    Atr, Bollinger, Ema, Indicator, IndicatorPipeline, IndicatorValue, Macd, Rsi, Sma,
+};
+use crypto_com_api::types::{Candlestick, EpochMs, Interval, Time};
+use crypto_com_api::websocket::data::CandlestickRes;
+
+const MINUTE: u64 = 60_000;
+
+fn candle(t: u64, c: f64) -> Candlestick {
+    Candlestick {
+        t: Time::from_epoch_ms(t),
+        ut: None,
+        o: c,
+        h: c + 1.0,
+        l: c - 1.0,
+        c,
+        v: 1.0,
+    }
+}
+
+fn res(data: Vec<Candlestick>) -> CandlestickRes {
+    CandlestickRes {
+        instrument_name: "BTCUSD-PERP".into(),
+        subscription: "candlestick.1m.BTCUSD-PERP".into(),
+        interval: "1m".into(),
+        channel: "candlestick".into(),
+        data,
+        received_at: 0,
+    }
+}
+
+fn single(value: Option<IndicatorValue>) -> f64 {
+    match value {
+        Some(IndicatorValue::Single(value)) => value,
+        other => panic!("expected a single value, got {other:?}"),
+    }
+}
+
+#[test]
+fn sma_and_ema_average_the_close() {
+    let mut sma = Sma::new(3);
+    let mut ema = Ema::new(3);
+
+    for (i, close) in [1.0, 2.0].into_iter().enumerate() {
+        assert_eq!(sma.update(&candle(i as u64 * MINUTE, close)), None);
+        assert_eq!(ema.update(&candle(i as u64 * MINUTE, close)), None);
+    }
+
+    assert_eq!(single(sma.update(&candle(2 * MINUTE, 3.0))), 2.0);
+    assert_eq!(single(ema.update(&candle(2 * MINUTE, 3.0))), 2.0);
+
+    assert_eq!(single(sma.update(&candle(3 * MINUTE, 7.0))), 4.0);
+    assert_eq!(single(ema.update(&candle(3 * MINUTE, 7.0))), 4.5);
+    assert_eq!(sma.name(), "SMA(3)");
+}
+
+#[test]
+fn rsi_is_100_without_losses() {
+    let mut rsi = Rsi::new(2);
+
+    assert_eq!(rsi.update(&candle(0, 1.0)), None);
+    assert_eq!(rsi.update(&candle(MINUTE, 2.0)), None);
+    assert_eq!(single(rsi.update(&candle(2 * MINUTE, 3.0))), 100.0);
+
+    // Average gain (1 + 0) / 2 = 0.5, average loss (0 + 1) / 2 = 0.5.
+    assert_eq!(single(rsi.update(&candle(3 * MINUTE, 2.0))), 50.0);
+}
+
+#[test]
+fn atr_uses_the_previous_close() {
+    let mut atr = Atr::new(2);
+
+    assert_eq!(atr.update(&candle(0, 10.0)), None);
+    // Gap up from 10 to a low of 19, true range 21 - 10 = 11.
+    assert_eq!(single(atr.update(&candle(MINUTE, 20.0))), 6.5);
+}
+
+#[test]
+fn macd_and_bollinger_bands() {
+    let mut macd = Macd::new(1, 2, 1);
+    let mut bollinger = Bollinger::new(2, 2.0);
+
+    assert_eq!(macd.update(&candle(0, 1.0)), None);
+    assert_eq!(bollinger.update(&candle(0, 1.0)), None);
+
+    assert_eq!(
+        macd.update(&candle(MINUTE, 3.0)),
+        Some(IndicatorValue::Macd {
+            macd: 1.0,
+            signal: 1.0,
+            histogram: 0.0,
+        })
+    );
+    assert_eq!(
+        bollinger.update(&candle(MINUTE, 3.0)),
+        Some(IndicatorValue::Bands {
+            upper: 4.0,
+            middle: 2.0,
+            lower: 0.0,
+        })
+    );
+}
+
+#[test]
+fn pipeline_emits_once_a_candle_completes() {
+    let mut pipeline = IndicatorPipeline::new(Interval::OneMinute)
+        .with_indicator(Sma::new(2))
+        .with_indicator(Rsi::new(14));
+
+    assert!(pipeline.process(&res(vec![candle(MINUTE, 1.0)])).is_empty());
+    // An update to the same period replaces the pending candle.
+    assert!(pipeline
+        .process(&res(vec![candle(MINUTE + 5, 3.0)]))
+        .is_empty());
+    assert_eq!(pipeline.pending().map(|candle| candle.c), Some(3.0));
+
+    let events = pipeline.process(&res(vec![candle(2 * MINUTE, 5.0)]));
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].candle.t.epoch_ms(), MINUTE);
+    assert_eq!(events[0].candle.c, 3.0);
+    assert_eq!(events[0].value("SMA(2)"), None);
+
+    // The skipped period is filled at the previous close and completes too.
+    let events = pipeline.process(&res(vec![candle(4 * MINUTE, 9.0)]));
+    let times: Vec<u64> = events
+        .iter()
+        .map(|event| event.candle.t.epoch_ms())
+        .collect();
+    assert_eq!(times, [2 * MINUTE, 3 * MINUTE]);
+    assert_eq!(single(events[0].value("SMA(2)")), 4.0);
+    assert_eq!(single(events[1].value("SMA(2)")), 5.0);
+    assert_eq!(events[1].value("RSI(14)"), None);
+}
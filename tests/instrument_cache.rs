@@ -0,0 +1,131 @@
+#![cfg(all(feature = "rest", feature = "websocket"))]
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crypto_com_api::error::RiskViolation;
+use crypto_com_api::rest::data::{InstrumentsRes, RawInstrumentsRes};
+use crypto_com_api::rest::instrument_cache::{InstrumentCache, DEFAULT_TTL};
+use crypto_com_api::risk::{RiskLimits, RiskManager};
+use crypto_com_api::types::{Quantity, Side};
+use crypto_com_api::utils::config::Config;
+use crypto_com_api::websocket::actions::spot_trading_api::CreateOrder;
+
+const INSTRUMENTS: &str = r#"{
+    "instruments": [{
+        "instrument_name": "BTC_USDT",
+        "quote_currency": "USDT",
+        "base_currency": "BTC",
+        "price_decimals": 2,
+        "quantity_decimals": 6,
+        "margin_trading_enabled": false,
+        "margin_trading_enabled_5x": false,
+        "margin_trading_enabled_10x": false,
+        "max_quantity": "100000000",
+        "min_quantity": "0.0001",
+        "max_price": "1000000",
+        "min_price": "0.01",
+        "last_update_date": 0,
+        "quantity_tick_size": "0.0001",
+        "price_tick_size": "0.05"
+    }]
+}"#;
+
+fn instruments() -> InstrumentsRes {
+    let raw: RawInstrumentsRes = serde_json::from_str(INSTRUMENTS).expect("instruments fixture");
+
+    InstrumentsRes::try_from(raw).expect("instruments")
+}
+
+fn order(instrument_name: &str, price: f64, quantity: f64) -> CreateOrder {
+    CreateOrder::limit(instrument_name, Side::Buy, price, Quantity(quantity))
+}
+
+#[tokio::test]
+async fn instruments_are_fetched_once_per_ttl() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+
+    // Answers a single request, a second fetch would fail to connect.
+    let server = thread::spawn(move || -> std::io::Result<()> {
+        let (mut stream, _) = listener.accept()?;
+        let mut request = vec![];
+        let mut buf = [0; 1024];
+
+        while !request.ends_with(b"\r\n\r\n") {
+            let read = stream.read(&mut buf)?;
+            if read == 0 {
+                break;
+            }
+            request.extend_from_slice(&buf[..read]);
+        }
+
+        let body = format!(
+            r#"{{"id": 1, "method": "public/get-instruments", "code": 0, "result": {INSTRUMENTS}}}"#
+        );
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        )
+    });
+
+    let config = Config {
+        rest_url: Some(url::Url::parse(&format!("http://{addr}/v2/"))?),
+        ..Config::default()
+    };
+    let cache = InstrumentCache::new(config, DEFAULT_TTL);
+    assert!(!cache.is_fresh());
+
+    let instrument = cache.instrument("BTC_USDT").await?;
+    assert_eq!(
+        instrument.map(|instrument| instrument.price_decimals),
+        Some(2)
+    );
+    server.join().expect("server thread")?;
+
+    assert!(cache.is_fresh());
+    assert!(cache.instrument("ETH_USDT").await?.is_none());
+    assert_eq!(cache.instruments().await?.instruments.len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn orders_are_rounded_with_the_cached_rules() -> anyhow::Result<()> {
+    let cache = InstrumentCache::new(Config::default(), Duration::from_secs(60));
+    cache.insert(instruments());
+
+    let mut rounded = order("BTC_USDT", 20_000.03, 0.123_456);
+    cache.round_order(&mut rounded).await?;
+
+    assert_eq!(rounded.price, Some(20_000.05));
+    assert_eq!(rounded.quantity(), Some(0.1234));
+    assert!(cache.validate_order(&rounded).await.is_ok());
+
+    cache.invalidate();
+    assert!(!cache.is_fresh());
+    assert!(cache.cached_rules().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn risk_manager_rejects_unlisted_instruments() {
+    let cache = Arc::new(InstrumentCache::new(Config::default(), DEFAULT_TTL));
+    let manager = RiskManager::new(RiskLimits::default()).with_instrument_cache(Arc::clone(&cache));
+
+    // Nothing is checked before the instruments are known.
+    assert_eq!(manager.check_order(&order("ETH_USDT", 1.0, 1.0)), Ok(()));
+
+    cache.insert(instruments());
+
+    assert_eq!(manager.check_order(&order("BTC_USDT", 1.0, 1.0)), Ok(()));
+    assert_eq!(
+        manager.check_order(&order("ETH_USDT", 1.0, 1.0)),
+        Err(RiskViolation::UnknownInstrument("ETH_USDT".to_owned()))
+    );
+}
@@ -0,0 +1,85 @@
+#![cfg(all(feature = "rest", feature = "websocket"))]
+
+use crypto_com_api::error::OrderError;
+use crypto_com_api::rest::data::{InstrumentRules, InstrumentsRes, RawInstrumentsRes};
+use crypto_com_api::types::{Quantity, Side};
+use crypto_com_api::websocket::actions::spot_trading_api::CreateOrder;
+
+fn rules() -> InstrumentRules {
+    let raw: RawInstrumentsRes = serde_json::from_str(
+        r#"{
+            "instruments": [{
+                "instrument_name": "BTC_USDT",
+                "quote_currency": "USDT",
+                "base_currency": "BTC",
+                "price_decimals": 2,
+                "quantity_decimals": 6,
+                "margin_trading_enabled": false,
+                "margin_trading_enabled_5x": false,
+                "margin_trading_enabled_10x": false,
+                "max_quantity": "100000000",
+                "min_quantity": "0.0001",
+                "max_price": "1000000",
+                "min_price": "0.01",
+                "last_update_date": 0,
+                "quantity_tick_size": "0.0001",
+                "price_tick_size": "0.05"
+            }]
+        }"#,
+    )
+    .expect("instruments fixture");
+
+    InstrumentRules::from(&InstrumentsRes::try_from(raw).expect("instruments"))
+}
+
+fn order(price: f64, quantity: f64) -> CreateOrder {
+    CreateOrder::limit("BTC_USDT", Side::Buy, price, Quantity(quantity))
+}
+
+#[test]
+fn rounds_to_ticks() {
+    let rules = rules();
+
+    assert_eq!(rules.round_price("BTC_USDT", 20_000.03), Ok(20_000.05));
+    assert_eq!(rules.round_price("BTC_USDT", 20_000.02), Ok(20_000.0));
+    assert_eq!(rules.round_quantity("BTC_USDT", 0.123_456), Ok(0.1234));
+    assert_eq!(rules.round_quantity("BTC_USDT", 0.3), Ok(0.3));
+}
+
+#[test]
+fn validates_orders() {
+    let rules = rules();
+
+    assert_eq!(order(20_000.15, 0.3).validate(&rules), Ok(()));
+    assert!(matches!(
+        order(20_000.01, 0.3).validate(&rules),
+        Err(OrderError::PriceNotOnTick { .. })
+    ));
+    assert!(matches!(
+        order(20_000.0, 0.000_05).validate(&rules),
+        Err(OrderError::QuantityNotOnTick { .. })
+    ));
+    assert!(matches!(
+        order(2_000_000.0, 0.3).validate(&rules),
+        Err(OrderError::PriceOutOfRange { .. })
+    ));
+
+    let mut unknown = order(1.0, 1.0);
+    unknown.instrument_name = "ETH_USDT".to_owned();
+    assert_eq!(
+        unknown.validate(&rules),
+        Err(OrderError::UnknownInstrument("ETH_USDT".to_owned()))
+    );
+}
+
+#[test]
+fn rounded_order_is_valid() {
+    let rules = rules();
+    let mut order = order(20_000.03, 0.123_456);
+
+    order.round(&rules).expect("known instrument");
+
+    assert_eq!(order.price, Some(20_000.05));
+    assert_eq!(order.quantity(), Some(0.1234));
+    assert_eq!(order.validate(&rules), Ok(()));
+}
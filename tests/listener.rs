@@ -0,0 +1,64 @@
+#![cfg(feature = "websocket")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crypto_com_api::api_response::ApiResponse;
+use crypto_com_api::controller::ControllerBuilder;
+use crypto_com_api::websocket::WebsocketData;
+
+#[tokio::test]
+async fn listener_stops_from_its_handle() -> anyhow::Result<()> {
+    let mut controller = ControllerBuilder::new().build();
+    let seen = Arc::new(AtomicUsize::new(0));
+
+    let handle = {
+        let seen = Arc::clone(&seen);
+
+        controller.listen(move |_| {
+            seen.fetch_add(1, Ordering::SeqCst);
+            Ok(false)
+        })
+    };
+
+    controller.data_tx.send(Arc::new(
+        ApiResponse::<WebsocketData>::default().websocket_data(WebsocketData::MarketHeartbeat),
+    ))?;
+
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(handle.is_alive());
+    assert_eq!(seen.load(Ordering::SeqCst), 1);
+
+    handle.stop();
+    tokio::time::timeout(Duration::from_secs(1), handle.join()).await??;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn stopping_before_the_loop_runs_is_not_lost() -> anyhow::Result<()> {
+    let mut controller = ControllerBuilder::new().build();
+
+    let handle = controller.listen(|_| Ok(false));
+    handle.stop();
+
+    tokio::time::timeout(Duration::from_secs(1), handle).await???;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn callback_can_still_end_the_listener() -> anyhow::Result<()> {
+    let mut controller = ControllerBuilder::new().build();
+
+    let handle = controller.listen(|_| Ok(true));
+
+    controller.data_tx.send(Arc::new(
+        ApiResponse::<WebsocketData>::default().websocket_data(WebsocketData::MarketHeartbeat),
+    ))?;
+
+    tokio::time::timeout(Duration::from_secs(1), handle.join()).await??;
+
+    Ok(())
+}
@@ -0,0 +1,232 @@
+#![cfg(feature = "websocket")]
+
+use crypto_com_api::api_response::ApiResponse;
+use crypto_com_api::websocket::channel::Channel;
+use crypto_com_api::websocket::{market_api::decode_subscription, WebsocketData};
+
+#[test]
+fn decode_ticker_frame() -> anyhow::Result<()> {
+    let msg: ApiResponse<serde_json::Value> = serde_json::from_str(
+        r#"{
+            "id": -1,
+            "method": "subscribe",
+            "code": 0,
+            "result": {
+                "instrument_name": "BTCUSD-PERP",
+                "subscription": "ticker.BTCUSD-PERP",
+                "channel": "ticker",
+                "data": [{
+                    "h": "51790.00", "l": "47895.50", "a": "51174.500", "i": "BTCUSD-PERP",
+                    "v": "879.5024", "vv": "26370000.12", "oi": "12345.12", "c": "0.03955106",
+                    "b": "51170.000", "bs": "0.1000", "k": "51180.000", "ks": "0.2000",
+                    "t": 1613580710768
+                }]
+            }
+        }"#,
+    )?;
+
+    let Some(WebsocketData::Ticker(ticker)) = decode_subscription(&msg, 42)? else {
+        panic!("expected ticker data");
+    };
+
+    assert_eq!(ticker.instrument_name.as_deref(), Some("BTCUSD-PERP"));
+    assert_eq!(ticker.received_at, 42);
+    assert_eq!(ticker.data.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn decode_mark_price_frame() -> anyhow::Result<()> {
+    let msg: ApiResponse<serde_json::Value> = serde_json::from_str(
+        r#"{
+            "id": -1,
+            "method": "subscribe",
+            "code": 0,
+            "result": {
+                "instrument_name": "BTCUSD-PERP",
+                "subscription": "mark.BTCUSD-PERP",
+                "channel": "mark",
+                "data": [{"v": "39362.36", "t": 1654752000000}]
+            }
+        }"#,
+    )?;
+
+    let Some(WebsocketData::Mark(mark)) = decode_subscription(&msg, 7)? else {
+        panic!("expected mark price data");
+    };
+
+    assert_eq!(&*mark.subscription, "mark.BTCUSD-PERP");
+    assert_eq!(mark.received_at, 7);
+    assert!((mark.data[0].v - 39362.36).abs() < f64::EPSILON);
+
+    Ok(())
+}
+
+#[test]
+fn decode_funding_frames() -> anyhow::Result<()> {
+    assert_eq!(
+        Channel::EstimatedFunding("BTCUSD-PERP".to_owned()).to_string(),
+        "estimatedfunding.BTCUSD-PERP"
+    );
+
+    for (channel, estimated) in [("funding", false), ("estimatedfunding", true)] {
+        let msg: ApiResponse<serde_json::Value> = serde_json::from_str(&format!(
+            r#"{{
+                "id": -1,
+                "method": "subscribe",
+                "code": 0,
+                "result": {{
+                    "instrument_name": "BTCUSD-PERP",
+                    "subscription": "{channel}.BTCUSD-PERP",
+                    "channel": "{channel}",
+                    "data": [{{"v": "0.00001", "t": 1654752000000}}]
+                }}
+            }}"#
+        ))?;
+
+        let rate = match decode_subscription(&msg, 0)? {
+            Some(WebsocketData::Funding(rate)) if !estimated => rate,
+            Some(WebsocketData::EstimatedFunding(rate)) if estimated => rate,
+            other => panic!("unexpected {channel} data: {other:?}"),
+        };
+
+        assert!((rate.data[0].v - 0.00001).abs() < f64::EPSILON);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn decode_unsupported_channel() -> anyhow::Result<()> {
+    let msg: ApiResponse<serde_json::Value> = serde_json::from_str(
+        r#"{"id": -1, "method": "subscribe", "result": {"channel": "nope", "subscription": "nope"}}"#,
+    )?;
+
+    assert!(decode_subscription(&msg, 0).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn decode_all_tickers_frame() -> anyhow::Result<()> {
+    assert_eq!(Channel::AllTickers.to_string(), "ticker");
+
+    let msg: ApiResponse<serde_json::Value> = serde_json::from_str(
+        r#"{
+            "id": -1,
+            "method": "subscribe",
+            "code": 0,
+            "result": {
+                "subscription": "ticker",
+                "channel": "ticker",
+                "data": [
+                    {
+                        "h": "51790.00", "l": "47895.50", "a": "51174.500", "i": "BTCUSD-PERP",
+                        "v": "879.5024", "vv": "26370000.12", "oi": "12345.12",
+                        "c": "0.03955106", "b": "51170.000", "bs": "0.1000", "k": "51180.000",
+                        "ks": "0.2000", "t": 1613580710768
+                    },
+                    {
+                        "h": "0.0950", "l": "0.0890", "a": "0.0921", "i": "CRO_USDT",
+                        "v": "1200000", "vv": "110520.00", "c": "0.0112",
+                        "b": "0.0920", "bs": "5000", "k": "0.0922", "ks": "2500",
+                        "t": 1613580710770
+                    }
+                ]
+            }
+        }"#,
+    )?;
+
+    let Some(WebsocketData::Ticker(tickers)) = decode_subscription(&msg, 3)? else {
+        panic!("expected ticker data");
+    };
+
+    assert!(tickers.is_all_instruments());
+    assert_eq!(&*tickers.subscription, "ticker");
+    assert_eq!(tickers.data.len(), 2);
+
+    let cro = tickers.ticker("CRO_USDT").expect("CRO_USDT ticker");
+    assert_eq!(cro.oi, None);
+    assert_eq!(cro.b, Some(0.092));
+    assert_eq!(
+        tickers.ticker("BTCUSD-PERP").and_then(|ticker| ticker.oi),
+        Some(12345.12)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn decode_frame_without_a_channel() -> anyhow::Result<()> {
+    let msg: ApiResponse<serde_json::Value> = serde_json::from_str(
+        r#"{
+            "id": -1,
+            "method": "subscribe",
+            "code": 0,
+            "result": {
+                "instrument_name": "BTCUSD-PERP",
+                "subscription": "mark.BTCUSD-PERP",
+                "data": [{"v": "39362.36", "t": 1654752000000}]
+            }
+        }"#,
+    )?;
+
+    let Some(WebsocketData::Mark(mark)) = decode_subscription(&msg, 7)? else {
+        panic!("expected mark price data");
+    };
+
+    assert_eq!(&*mark.subscription, "mark.BTCUSD-PERP");
+    assert_eq!(mark.data.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn unknown_frames_are_passed_through() -> anyhow::Result<()> {
+    let msg: ApiResponse<serde_json::Value> = serde_json::from_str(
+        r#"{
+            "id": -1,
+            "method": "subscribe",
+            "code": 0,
+            "result": {"data": [{"v": "39362.36"}]}
+        }"#,
+    )?;
+
+    let Some(WebsocketData::RawSubscription(raw)) = decode_subscription(&msg, 7)? else {
+        panic!("expected the raw result");
+    };
+
+    assert_eq!(raw["data"][0]["v"], "39362.36");
+
+    Ok(())
+}
+
+#[test]
+fn subscription_payload_shapes() -> anyhow::Result<()> {
+    use crypto_com_api::websocket::data::SubscriptionPayload;
+    use serde::Deserialize;
+
+    let routed = serde_json::json!({"channel": "book", "subscription": "book.BTC_USDT.10"});
+    let payload = SubscriptionPayload::deserialize(&routed)?;
+    assert!(matches!(payload, SubscriptionPayload::Routed(_)));
+    assert_eq!(payload.channel(), Some("book"));
+
+    let subscription = serde_json::json!({"subscription": "user.order.ETH_CRO"});
+    let payload = SubscriptionPayload::deserialize(&subscription)?;
+    assert_eq!(payload.channel(), Some("user.order"));
+
+    let channel = serde_json::json!({"channel": "ticker", "instrument_name": "BTC_USDT"});
+    let payload = SubscriptionPayload::deserialize(&channel)?;
+    assert_eq!(payload.channel(), Some("ticker"));
+    assert_eq!(
+        payload.complete(&channel)["subscription"],
+        "ticker.BTC_USDT"
+    );
+
+    let unknown = serde_json::json!([1, 2, 3]);
+    let payload = SubscriptionPayload::deserialize(&unknown)?;
+    assert_eq!(payload.channel(), None);
+
+    Ok(())
+}
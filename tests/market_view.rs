@@ -0,0 +1,63 @@
+#![cfg(feature = "websocket")]
+
+use std::time::Duration;
+
+use crypto_com_api::analytics::MarketView;
+use crypto_com_api::api_response::ApiResponse;
+use crypto_com_api::websocket::{market_api::decode_subscription, WebsocketData};
+
+fn decode(frame: &str) -> anyhow::Result<WebsocketData> {
+    let msg: ApiResponse<serde_json::Value> = serde_json::from_str(frame)?;
+
+    decode_subscription(&msg, 0)?.ok_or_else(|| anyhow::anyhow!("no data"))
+}
+
+const BOOK: &str = r#"{
+    "id": -1, "method": "subscribe", "code": 0,
+    "result": {
+        "instrument_name": "BTCUSD-PERP", "subscription": "book.BTCUSD-PERP",
+        "channel": "book", "depth": 50,
+        "data": [{
+            "bids": [["100.0", "1.0", "1"]],
+            "asks": [["101.0", "2.0", "1"]],
+            "tt": 1, "t": 1, "u": 1, "cs": 0
+        }]
+    }
+}"#;
+
+const TRADES: &str = r#"{
+    "id": -1, "method": "subscribe", "code": 0,
+    "result": {
+        "instrument_name": "BTCUSD-PERP", "subscription": "trade.BTCUSD-PERP",
+        "channel": "trade",
+        "data": [
+            {"s": "BUY", "p": "102.0", "q": "1.0", "t": 70000, "d": "3", "i": "BTCUSD-PERP"},
+            {"s": "SELL", "p": "100.0", "q": "3.0", "t": 10001, "d": "1", "i": "BTCUSD-PERP"},
+            {"s": "BUY", "p": "101.0", "q": "1.0", "t": 65000, "d": "2", "i": "BTCUSD-PERP"}
+        ]
+    }
+}"#;
+
+#[tokio::test]
+async fn book_and_trades_are_combined() -> anyhow::Result<()> {
+    let view = MarketView::new(
+        "BTCUSD-PERP",
+        vec![Duration::from_secs(10), Duration::from_secs(60)],
+    );
+
+    view.process(&decode(BOOK)?).await?;
+    view.process(&decode(TRADES)?).await?;
+
+    let snapshot = view.snapshot().await;
+
+    assert_eq!(snapshot.best_bid, Some((100.0, 1.0, 1)));
+    assert_eq!(snapshot.best_ask, Some((101.0, 2.0, 1)));
+    assert_eq!(snapshot.last_trade.map(|trade| trade.price), Some(102.0));
+
+    assert!((snapshot.windows[0].volume - 2.0).abs() < f64::EPSILON);
+    assert_eq!(snapshot.windows[0].vwap, Some(101.5));
+    assert!((snapshot.windows[1].volume - 5.0).abs() < f64::EPSILON);
+    assert_eq!(snapshot.windows[1].vwap, Some(100.6));
+
+    Ok(())
+}
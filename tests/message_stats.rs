@@ -0,0 +1,51 @@
+use std::time::{Duration, Instant};
+
+use crypto_com_api::utils::message_stats::{MessageStats, OTHER};
+
+const BOOK: &str = r#"{"id":-1,"method":"subscribe","code":0,"result":{"instrument_name":"BTC_USDT","subscription":"book.BTC_USDT.10","channel":"book","data":[]}}"#;
+const TRADE: &str = r#"{"id":-1,"method":"subscribe","code":0,"result":{"instrument_name":"ETH_USDT","subscription":"trade.ETH_USDT","channel":"trade","data":[]}}"#;
+const HEARTBEAT: &str = r#"{"id":1,"method":"public/heartbeat"}"#;
+
+#[test]
+fn messages_are_counted_by_subscription() {
+    let stats = MessageStats::new(Duration::from_secs(10));
+    let start = Instant::now();
+
+    stats.record_at(BOOK, start);
+    stats.record_at(BOOK, start + Duration::from_millis(500));
+    stats.record_at(TRADE, start + Duration::from_secs(1));
+    stats.record_at(HEARTBEAT, start + Duration::from_secs(2));
+
+    let snapshot = stats.snapshot_at(start + Duration::from_secs(5));
+    let book = &snapshot.subscriptions["book.BTC_USDT.10"];
+
+    assert_eq!(book.channel, "book");
+    assert_eq!(book.messages, 2);
+    assert_eq!(book.bytes, 2 * BOOK.len() as u64);
+    assert_eq!(book.largest_message, BOOK.len() as u64);
+    assert_eq!(snapshot.subscriptions[OTHER].messages, 1);
+    assert_eq!(snapshot.channel("trade").bytes, TRADE.len() as u64);
+    assert_eq!(snapshot.total().messages, 4);
+}
+
+#[test]
+fn rates_roll_over_the_window() {
+    let stats = MessageStats::new(Duration::from_millis(1500));
+    let start = Instant::now();
+
+    assert_eq!(stats.window(), Duration::from_secs(2));
+
+    for millis in [0, 100, 1_200, 3_100] {
+        stats.record_at(BOOK, start + Duration::from_millis(millis));
+    }
+
+    // The first two messages left the window, the totals keep them.
+    let snapshot = stats.snapshot_at(start + Duration::from_millis(3_500));
+    let book = snapshot.channel("book");
+
+    assert_eq!(snapshot.window, Duration::from_secs(2));
+    assert_eq!(book.messages, 4);
+    assert_eq!(book.window_messages, 1);
+    assert_eq!(book.window_bytes, BOOK.len() as u64);
+    assert_eq!(book.messages_per_sec, 0.5);
+}
@@ -0,0 +1,113 @@
+#![cfg(feature = "websocket")]
+
+mod common;
+
+use crypto_com_api::oco::{OcoEvent, OcoLeg, OcoTracker};
+use crypto_com_api::types::{Quantity, Side};
+use crypto_com_api::websocket::actions::spot_trading_api::{CreateOrderList, Oco};
+use crypto_com_api::websocket::data::CreateOrderList as CreateOrderListRes;
+use crypto_com_api::websocket::WebsocketData;
+
+fn reply(codes: [u64; 2]) -> WebsocketData {
+    let res: CreateOrderListRes = serde_json::from_value(serde_json::json!({
+        "result_list": [
+            { "index": 0, "code": codes[0], "order_id": 11 },
+            { "index": 1, "code": codes[1], "message": "INVALID_TRIGGER_PRICE", "order_id": 12 }
+        ],
+        "list_id": "6498090546073120100"
+    }))
+    .expect("a valid order list reply");
+
+    WebsocketData::CreateOrderList(res)
+}
+
+fn order(order_id: u64, status: &str) -> WebsocketData {
+    WebsocketData::UserOrder(common::user_order(
+        "BTC_USDT",
+        serde_json::json!({
+            "status": status, "side": "SELL", "price": 22000.0, "quantity": 0.01,
+            "order_id": order_id.to_string()
+        }),
+    ))
+}
+
+#[test]
+fn oco_serializes_both_legs() {
+    let list = CreateOrderList::from(Oco {
+        instrument_name: "BTC_USDT".to_owned(),
+        side: Side::Sell,
+        quantity: Quantity(0.01),
+        price: 22_000.0,
+        trigger_price: 19_000.0,
+        stop_price: 18_950.0,
+    });
+
+    let value = serde_json::to_value(&list).expect("an order list serializes");
+
+    assert_eq!(value["contingency_type"], "OCO");
+    assert_eq!(value["order_list"][0]["type"], "LIMIT");
+    assert_eq!(value["order_list"][0]["price"], 22_000.0);
+    assert_eq!(value["order_list"][1]["type"], "STOP_LIMIT");
+    assert_eq!(value["order_list"][1]["price"], 18_950.0);
+    assert_eq!(value["order_list"][1]["trigger_price"], 19_000.0);
+    assert_eq!(value["order_list"][1]["side"], "SELL");
+}
+
+#[test]
+fn executed_leg_cancels_the_other() {
+    let mut tracker = OcoTracker::new();
+
+    // Updates before the reply are kept until the order IDs are known.
+    assert!(tracker.process(&order(12, "ACTIVE")).is_empty());
+    assert!(tracker.process(&order(11, "FILLED")).is_empty());
+
+    assert_eq!(
+        tracker.process(&reply([0, 0])),
+        vec![
+            OcoEvent::Placed {
+                limit_order_id: 11,
+                stop_order_id: 12,
+                list_id: Some("6498090546073120100".to_owned()),
+            },
+            OcoEvent::Executed {
+                leg: OcoLeg::Limit,
+                order_id: 11,
+            },
+        ]
+    );
+    assert_eq!(tracker.order_id(OcoLeg::Stop), Some(12));
+
+    // Another pair's orders are ignored.
+    assert!(tracker.process(&order(13, "CANCELED")).is_empty());
+
+    assert_eq!(
+        tracker.process(&order(12, "CANCELED")),
+        vec![
+            OcoEvent::Canceled {
+                leg: OcoLeg::Stop,
+                order_id: 12,
+                status: "CANCELED".to_owned(),
+            },
+            OcoEvent::Complete {
+                executed: Some(OcoLeg::Limit),
+            },
+        ]
+    );
+    assert!(tracker.is_complete());
+    assert!(tracker.process(&order(12, "CANCELED")).is_empty());
+}
+
+#[test]
+fn rejected_leg_completes_the_pair() {
+    let mut tracker = OcoTracker::new();
+
+    assert_eq!(
+        tracker.process(&reply([0, 213])),
+        vec![OcoEvent::Rejected {
+            leg: OcoLeg::Stop,
+            code: 213,
+            message: Some("INVALID_TRIGGER_PRICE".to_owned()),
+        }]
+    );
+    assert!(tracker.is_complete());
+}
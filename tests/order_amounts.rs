@@ -0,0 +1,156 @@
+#![cfg(feature = "websocket")]
+
+use crypto_com_api::error::OrderError;
+use crypto_com_api::types::{ApiVersion, BuyAmount, ExecInst, Notional, Quantity, Side};
+use crypto_com_api::utils::action::Action;
+use crypto_com_api::websocket::actions::spot_trading_api::CreateOrder;
+use crypto_com_api::websocket::actions::ActionKind;
+
+#[test]
+fn market_buys_take_a_quantity_or_a_notional() {
+    let by_quantity = CreateOrder::market_buy("BTC_USDT", Quantity(0.5));
+    assert_eq!(by_quantity.side, "BUY");
+    assert_eq!(by_quantity.order_type, "MARKET");
+    assert_eq!(by_quantity.quantity(), Some(0.5));
+    assert_eq!(by_quantity.notional(), None);
+
+    let by_notional = CreateOrder::market_buy("BTC_USDT", Notional(250.0));
+    assert_eq!(by_notional.quantity(), None);
+    assert_eq!(by_notional.notional(), Some(250.0));
+
+    assert_eq!(
+        BuyAmount::from(Notional(1.0)),
+        BuyAmount::Notional(Notional(1.0))
+    );
+}
+
+#[test]
+fn constructors_set_the_mandatory_parameters() {
+    let limit = CreateOrder::limit("BTC_USDT", Side::Sell, 21_000.0, Quantity(0.1))
+        .with_client_oid("limit-1")
+        .with_exec_inst(ExecInst::PostOnly);
+    assert_eq!(
+        (limit.side.as_str(), limit.order_type.as_str()),
+        ("SELL", "LIMIT")
+    );
+    assert_eq!((limit.price, limit.quantity()), (Some(21_000.0), Some(0.1)));
+    assert_eq!(limit.client_oid.as_deref(), Some("limit-1"));
+    assert_eq!(limit.exec_inst, [ExecInst::PostOnly]);
+
+    let sell = CreateOrder::market_sell("BTC_USDT", Quantity(0.1));
+    assert_eq!((sell.side.as_str(), sell.quantity()), ("SELL", Some(0.1)));
+    assert_eq!(sell.notional(), None);
+
+    let stop_limit =
+        CreateOrder::stop_limit("BTC_USDT", Side::Buy, 20_500.0, Quantity(0.1), 20_400.0);
+    assert_eq!(stop_limit.order_type, "STOP_LIMIT");
+    assert_eq!(stop_limit.price, Some(20_500.0));
+    assert_eq!(stop_limit.trigger_price, Some(20_400.0));
+
+    let take_profit_limit =
+        CreateOrder::take_profit_limit("BTC_USDT", Side::Sell, 22_000.0, Quantity(0.1), 22_100.0);
+    assert_eq!(take_profit_limit.order_type, "TAKE_PROFIT_LIMIT");
+
+    let stop_buy = CreateOrder::stop_loss_buy("BTC_USDT", Notional(100.0), 19_000.0);
+    assert_eq!(
+        (stop_buy.side.as_str(), stop_buy.order_type.as_str()),
+        ("BUY", "STOP_LOSS")
+    );
+    assert_eq!(
+        (stop_buy.notional(), stop_buy.quantity()),
+        (Some(100.0), None)
+    );
+
+    let stop_sell = CreateOrder::stop_loss_sell("BTC_USDT", Quantity(0.1), 19_000.0);
+    assert_eq!(
+        (stop_sell.notional(), stop_sell.quantity()),
+        (None, Some(0.1))
+    );
+
+    let take_profit_buy = CreateOrder::take_profit_buy("BTC_USDT", Notional(100.0), 23_000.0);
+    assert_eq!(take_profit_buy.order_type, "TAKE_PROFIT");
+    assert_eq!(take_profit_buy.notional(), Some(100.0));
+
+    let take_profit_sell = CreateOrder::take_profit_sell("BTC_USDT", Quantity(0.1), 23_000.0);
+    assert_eq!(take_profit_sell.trigger_price, Some(23_000.0));
+    assert_eq!(take_profit_sell.quantity(), Some(0.1));
+}
+
+#[test]
+fn amounts_serialize_under_their_own_field() -> anyhow::Result<()> {
+    let order = serde_json::to_value(CreateOrder::market_buy("BTC_USDT", Notional(250.0)))?;
+
+    assert_eq!(order["type"], "MARKET");
+    assert_eq!(order["notional"], 250.0);
+    assert!(order["quantity"].is_null());
+
+    Ok(())
+}
+
+#[test]
+fn exec_inst_is_shaped_for_the_api_version() -> anyhow::Result<()> {
+    let limit = CreateOrder::limit("BTC_USDT", Side::Buy, 20_000.0, Quantity(0.1));
+    assert!(serde_json::to_value(&limit)?["exec_inst"].is_null());
+
+    let post_only = limit.with_exec_inst(ExecInst::PostOnly);
+    assert_eq!(serde_json::to_value(&post_only)?["exec_inst"], "POST_ONLY");
+
+    let v1 = post_only.with_api_version(ApiVersion::V1);
+    assert_eq!(
+        serde_json::to_value(&v1)?["exec_inst"],
+        serde_json::json!(["POST_ONLY"])
+    );
+
+    // Both shapes read back the same.
+    for json in [r#""POST_ONLY""#, r#"["POST_ONLY"]"#] {
+        let order: CreateOrder = serde_json::from_str(&format!(
+            r#"{{"instrument_name":"BTC_USDT","side":"BUY","type":"LIMIT","price":1,"quantity":1,"notional":null,"client_oid":null,"time_in_force":null,"exec_inst":{json},"trigger_price":null}}"#
+        ))?;
+        assert_eq!(order.exec_inst, [ExecInst::PostOnly]);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn exec_inst_is_only_allowed_on_limit_orders() {
+    let limit = CreateOrder::limit("BTC_USDT", Side::Buy, 20_000.0, Quantity(0.1))
+        .with_exec_inst(ExecInst::SmartPostOnly);
+    assert_eq!(
+        limit.validate_exec_inst(),
+        Err(OrderError::UnsupportedExecInst {
+            exec_inst: ExecInst::SmartPostOnly,
+            version: ApiVersion::V2,
+        })
+    );
+    assert_eq!(
+        limit.with_api_version(ApiVersion::V1).validate_exec_inst(),
+        Ok(())
+    );
+
+    let market =
+        CreateOrder::market_sell("BTC_USDT", Quantity(0.1)).with_exec_inst(ExecInst::PostOnly);
+    assert_eq!(
+        market.validate_exec_inst(),
+        Err(OrderError::ExecInstNotAllowed("MARKET".to_owned()))
+    );
+    assert!(ActionKind::from(market).validate().is_err());
+}
+
+#[test]
+fn orders_read_back_with_exactly_one_amount() -> anyhow::Result<()> {
+    let spend = CreateOrder::market_buy("BTC_USDT", Notional(250.0));
+    let read: CreateOrder = serde_json::from_value(serde_json::to_value(&spend)?)?;
+    assert_eq!(read.amount(), BuyAmount::Notional(Notional(250.0)));
+
+    for amounts in [
+        r#""quantity":1,"notional":1"#,
+        r#""quantity":null,"notional":null"#,
+    ] {
+        let json =
+            format!(r#"{{"instrument_name":"BTC_USDT","side":"BUY","type":"MARKET",{amounts}}}"#);
+        assert!(serde_json::from_str::<CreateOrder>(&json).is_err());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+#![cfg(feature = "websocket")]
+
+use crypto_com_api::analytics::OrderBook;
+use crypto_com_api::api_response::ApiResponse;
+use crypto_com_api::prelude::ApiError;
+use crypto_com_api::websocket::{market_api::decode_subscription, WebsocketData};
+
+fn decode(frame: &str) -> anyhow::Result<WebsocketData> {
+    let msg: ApiResponse<serde_json::Value> = serde_json::from_str(frame)?;
+
+    decode_subscription(&msg, 0)?.ok_or_else(|| anyhow::anyhow!("no data"))
+}
+
+const SNAPSHOT: &str = r#"{
+    "id": -1, "method": "subscribe", "code": 0,
+    "result": {
+        "instrument_name": "BTCUSD-PERP", "subscription": "book.BTCUSD-PERP.10",
+        "channel": "book", "depth": 10,
+        "data": [{
+            "bids": [["30000.0", "1.0", "2"], ["29999.5", "2.0", "1"]],
+            "asks": [["30000.5", "0.5", "1"], ["30001.0", "3.0", "4"]],
+            "tt": 1, "t": 1, "u": 100, "cs": 0
+        }]
+    }
+}"#;
+
+fn update(pu: u64, u: u64) -> String {
+    format!(
+        r#"{{
+            "id": -1, "method": "subscribe", "code": 0,
+            "result": {{
+                "instrument_name": "BTCUSD-PERP", "subscription": "book.BTCUSD-PERP.10",
+                "channel": "book.update", "depth": 10,
+                "data": [{{
+                    "update": {{
+                        "bids": [["30000.0", "0", "0"], ["30000.2", "1.5", "1"]],
+                        "asks": [["30001.0", "1.0", "2"]]
+                    }},
+                    "tt": 2, "t": 2, "u": {u}, "pu": {pu}, "cs": 0
+                }}]
+            }}
+        }}"#
+    )
+}
+
+#[test]
+fn updates_apply_on_top_of_snapshot() -> anyhow::Result<()> {
+    let mut book = OrderBook::new("BTCUSD-PERP");
+
+    book.process(&decode(SNAPSHOT)?)?;
+    book.process(&decode(&update(100, 101))?)?;
+
+    assert_eq!(book.best_bid(), Some((30000.2, 1.5, 1)));
+    assert_eq!(book.bids.len(), 2);
+    assert_eq!(book.asks, vec![(30000.5, 0.5, 1), (30001.0, 1.0, 2)]);
+    assert_eq!(book.sequence, Some(101));
+
+    Ok(())
+}
+
+#[test]
+fn missed_update_is_out_of_sync() -> anyhow::Result<()> {
+    let mut book = OrderBook::new("BTCUSD-PERP");
+
+    book.process(&decode(SNAPSHOT)?)?;
+
+    assert!(matches!(
+        book.process(&decode(&update(105, 106))?),
+        Err(ApiError::BookOutOfSync {
+            expected: Some(100),
+            found: 105,
+        })
+    ));
+    assert_eq!(book.sequence, None);
+
+    Ok(())
+}
+
+#[test]
+fn levels_are_keyed_by_tick() -> anyhow::Result<()> {
+    use crypto_com_api::types::Side;
+    use crypto_com_api::websocket::data::BookUpdate;
+
+    let mut book = OrderBook::new("BTCUSD-PERP").with_tick_size(0.1);
+
+    book.process(&decode(SNAPSHOT)?)?;
+    book.apply_update(&BookUpdate {
+        bids: vec![(29_999.500_000_001, 4.0, 3)],
+        asks: vec![(30_000.499_999_999, 0.0, 0)],
+        tt: Default::default(),
+        t: Default::default(),
+        u: 101,
+        pu: 100,
+        cs: 0,
+    })?;
+
+    assert_eq!(book.bids.len(), 2);
+    assert_eq!(
+        book.level(Side::Buy, 29_999.5),
+        Some((29_999.500_000_001, 4.0, 3))
+    );
+    assert_eq!(book.level(Side::Sell, 30_000.5), None);
+    assert_eq!(book.level(Side::Sell, 30_001.0), Some((30_001.0, 3.0, 4)));
+    assert_eq!(book.best_ask(), Some((30_001.0, 3.0, 4)));
+
+    let top = book.top(1);
+    assert_eq!(top.bids, &[(30_000.0, 1.0, 2)]);
+    assert_eq!(top.asks, &[(30_001.0, 3.0, 4)]);
+    assert_eq!(book.top(10).bids.len(), 2);
+
+    Ok(())
+}
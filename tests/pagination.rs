@@ -0,0 +1,143 @@
+#![cfg(feature = "websocket")]
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+use tokio_tungstenite::tungstenite::Message;
+
+use crypto_com_api::prelude::ApiError;
+use crypto_com_api::types::{PageQuery, TimeRange};
+use crypto_com_api::utils::action::Action;
+use crypto_com_api::utils::in_flight::{Api, InFlight};
+use crypto_com_api::websocket::actions::spot_trading_api::{GetOrderHistory, Paginated};
+use crypto_com_api::websocket::actions::ActionKind;
+use crypto_com_api::websocket::data::Page;
+use crypto_com_api::websocket::handler::HandlerRegistry;
+use crypto_com_api::websocket::{user_api::process_user, WebsocketData};
+
+#[test]
+fn last_page() {
+    let page = Page::new(Some(1), Some(10));
+
+    assert!(!page.is_last(10, Some(25)));
+    assert!(Page::new(Some(2), Some(10)).is_last(5, Some(25)));
+    assert!(!page.is_last(10, None));
+    assert!(page.is_last(9, None));
+    assert_eq!(Page::new(None, None), Page::default());
+}
+
+#[tokio::test]
+async fn order_history_keeps_count_and_page() -> anyhow::Result<()> {
+    let (user_tx, _user_rx) = futures_channel::mpsc::unbounded();
+    let (data_tx, mut data_rx) = tokio::sync::broadcast::channel(4);
+    let in_flight = InFlight::default();
+
+    in_flight
+        .insert(
+            3,
+            Api::User,
+            Arc::new(
+                GetOrderHistory(Paginated {
+                    instrument_name: Some("ETH_CRO".to_owned()),
+                    page: PageQuery::new(0, 1)?,
+                    ..Paginated::default()
+                })
+                .into(),
+            ),
+        )
+        .await;
+
+    process_user(
+        Message::Text(
+            r#"{
+                "id": 3, "method": "private/get-order-history", "code": 0,
+                "result": {
+                    "count": 2,
+                    "order_list": [{
+                        "status": "FILLED", "side": "BUY", "price": 1.0, "quantity": 1.0,
+                        "order_id": "1", "client_oid": "a", "create_time": 1, "update_time": 1,
+                        "type": "LIMIT", "instrument_name": "ETH_CRO",
+                        "cumulative_quantity": 1, "cumulative_value": 1, "avg_price": 1,
+                        "fee_currency": "CRO", "time_in_force": "GOOD_TILL_CANCEL"
+                    }]
+                }
+            }"#
+            .to_owned(),
+        ),
+        Arc::new(Mutex::new(user_tx)),
+        &data_tx,
+        &in_flight,
+        None,
+        &HandlerRegistry::new(),
+    )
+    .await?;
+
+    let data = data_rx.recv().await?;
+    let Some(WebsocketData::GetOrderHistory(ref history)) = data.result else {
+        anyhow::bail!("expected order history, got {data:?}");
+    };
+
+    assert_eq!(history.count, Some(2));
+    assert_eq!(history.page, Page::new(Some(0), Some(1)));
+    assert!(!history.is_last_page());
+
+    Ok(())
+}
+
+#[test]
+fn page_and_range_are_flattened_into_the_params() -> anyhow::Result<()> {
+    let params = Paginated {
+        instrument_name: Some("ETH_CRO".to_owned()),
+        range: TimeRange::new(1_000, 2_000)?,
+        page: PageQuery::new(3, 50)?,
+    };
+
+    assert_eq!(
+        serde_json::to_value(&params)?,
+        serde_json::json!({
+            "instrument_name": "ETH_CRO",
+            "start_ts": 1_000,
+            "end_ts": 2_000,
+            "page_size": 50,
+            "page": 3,
+        })
+    );
+    assert_eq!(Page::from(params.page.next()), Page::new(Some(4), Some(50)));
+
+    Ok(())
+}
+
+#[test]
+fn invalid_pages_are_refused() {
+    let too_large = Paginated {
+        page: PageQuery {
+            page_size: Some(500),
+            page: None,
+        },
+        ..Paginated::default()
+    };
+    let backwards = Paginated {
+        range: TimeRange {
+            start_ts: Some(2_000),
+            end_ts: Some(1_000),
+        },
+        ..Paginated::default()
+    };
+
+    // Queues check the actions before sending them.
+    assert!(matches!(
+        ActionKind::from(GetOrderHistory(too_large)).validate(),
+        Err(ApiError::InvalidPageSize(500))
+    ));
+    assert!(matches!(
+        ActionKind::from(GetOrderHistory(backwards)).validate(),
+        Err(ApiError::InvalidTimeRange { .. })
+    ));
+    assert!(ActionKind::from(GetOrderHistory(Paginated::default()))
+        .validate()
+        .is_ok());
+    assert!(matches!(
+        PageQuery::new(0, 0),
+        Err(ApiError::InvalidPageSize(0))
+    ));
+}
@@ -0,0 +1,100 @@
+#![cfg(feature = "persistence")]
+
+use crypto_com_api::persistence::{SqliteStore, Store};
+use crypto_com_api::types::{EpochMs, Time};
+use crypto_com_api::utils::in_flight::Api;
+use crypto_com_api::websocket::connection_state::ConnectionState;
+use crypto_com_api::websocket::data::{
+    EventKind, ExtraFields, OrderItem, UserOrderRes, UserTrade, UserTradeRes,
+};
+use crypto_com_api::websocket::WebsocketData;
+
+fn order(order_id: &str, status: &str, update_time: u64) -> OrderItem {
+    OrderItem {
+        status: status.to_owned(),
+        reason: None,
+        side: "BUY".to_owned(),
+        price: 100.0,
+        quantity: 1.0,
+        order_id: order_id.to_owned(),
+        client_oid: String::new(),
+        create_time: Time::from_epoch_ms(1),
+        update_time: Time::from_epoch_ms(update_time),
+        order_type: "LIMIT".to_owned(),
+        instrument_name: "BTC_USDT".to_owned(),
+        cumulative_quantity: 0.0,
+        cumulative_value: 0.0,
+        avg_price: 0.0,
+        fee_currency: "USDT".to_owned(),
+        time_in_force: "GOOD_TILL_CANCEL".to_owned(),
+        exec_inst: None,
+        trigger_price: None,
+        extra: ExtraFields::new(),
+    }
+}
+
+fn orders(data: Vec<OrderItem>) -> WebsocketData {
+    WebsocketData::UserOrder(UserOrderRes {
+        instrument_name: "BTC_USDT".to_owned(),
+        subscription: "user.order.BTC_USDT".to_owned(),
+        channel: "user.order".to_owned(),
+        data,
+        kind: EventKind::Update,
+    })
+}
+
+#[test]
+fn open_orders_are_restored() -> anyhow::Result<()> {
+    let mut store = SqliteStore::open_in_memory()?;
+
+    assert_eq!(store.process(&orders(vec![order("1", "ACTIVE", 1)]))?, 1);
+    store.process(&orders(vec![order("2", "ACTIVE", 2)]))?;
+    store.process(&orders(vec![order("1", "FILLED", 3)]))?;
+
+    let open = store.open_orders()?;
+
+    assert_eq!(open.len(), 1);
+    assert_eq!(open[0], order("2", "ACTIVE", 2));
+
+    Ok(())
+}
+
+#[test]
+fn fills_are_recorded_once() -> anyhow::Result<()> {
+    let mut store = SqliteStore::open_in_memory()?;
+    let trade = UserTrade {
+        side: "BUY".to_owned(),
+        fee: 0.1,
+        trade_id: 7,
+        create_time: Time::from_epoch_ms(5),
+        traded_price: 100.0,
+        traded_quantity: 0.5,
+        fee_currency: "USDT".to_owned(),
+        order_id: 1,
+    };
+    let trades = WebsocketData::UserTrade(UserTradeRes {
+        instrument_name: "BTC_USDT".to_owned(),
+        subscription: "user.trade.BTC_USDT".to_owned(),
+        channel: "user.trade".to_owned(),
+        data: vec![trade.clone()],
+        kind: EventKind::Update,
+    });
+
+    store.process(&trades)?;
+    store.process(&trades)?;
+    store.process(&WebsocketData::ConnectionState(ConnectionState::Connected(
+        Api::User,
+    )))?;
+
+    assert_eq!(store.trades_of(1)?, vec![trade]);
+
+    let events: i64 =
+        store
+            .connection()
+            .query_row("SELECT COUNT(*) FROM connection_events", [], |row| {
+                row.get(0)
+            })?;
+    assert_eq!(events, 1);
+
+    Ok(())
+}
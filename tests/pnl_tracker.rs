@@ -0,0 +1,67 @@
+#![cfg(feature = "websocket")]
+
+use crypto_com_api::analytics::PnlTracker;
+use crypto_com_api::types::{EpochMs, Time};
+use crypto_com_api::websocket::data::{
+    EventKind, ExtraFields, UserBalance, UserTrade, UserTradeRes,
+};
+
+fn fill(side: &str, price: f64, quantity: f64, create_time: u64) -> UserTrade {
+    UserTrade {
+        side: side.to_owned(),
+        fee: 0.1,
+        trade_id: create_time,
+        create_time: Time::from_epoch_ms(create_time),
+        traded_price: price,
+        traded_quantity: quantity,
+        fee_currency: "USDT".to_owned(),
+        order_id: create_time,
+    }
+}
+
+fn trades(data: Vec<UserTrade>) -> UserTradeRes {
+    UserTradeRes {
+        instrument_name: "BTC_USDT".to_owned(),
+        subscription: "user.trade.BTC_USDT".to_owned(),
+        channel: "user.trade".to_owned(),
+        data,
+        kind: EventKind::Update,
+    }
+}
+
+#[tokio::test]
+async fn position_and_realized_pnl() {
+    let tracker = PnlTracker::with_balances(&[UserBalance {
+        currency: "USDT".to_owned(),
+        balance: 10_000.0,
+        available: 10_000.0,
+        order: 0.0,
+        stake: 0.0,
+        extra: ExtraFields::new(),
+    }]);
+
+    // Newest first, as the exchange sends them.
+    tracker
+        .apply_trades(&trades(vec![
+            fill("BUY", 200.0, 1.0, 2),
+            fill("BUY", 100.0, 1.0, 1),
+        ]))
+        .await;
+
+    let position = tracker.position("BTC_USDT").await.expect("position");
+    assert_eq!(position.size, 2.0);
+    assert_eq!(position.avg_entry_price, 150.0);
+
+    tracker
+        .apply_trades(&trades(vec![fill("SELL", 250.0, 1.5, 3)]))
+        .await;
+
+    let snapshot = tracker.snapshot().await;
+    let position = &snapshot.positions["BTC_USDT"];
+    assert_eq!(position.size, 0.5);
+    assert_eq!(position.avg_entry_price, 150.0);
+    assert_eq!(position.realized_pnl, 150.0);
+    assert_eq!(snapshot.balances["BTC"], 0.5);
+    assert!((snapshot.balances["USDT"] - 10_074.7).abs() < 1e-9);
+    assert!((snapshot.fees["USDT"] - 0.3).abs() < 1e-9);
+}
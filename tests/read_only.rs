@@ -0,0 +1,59 @@
+#![cfg(feature = "websocket")]
+
+use crypto_com_api::controller::ControllerBuilder;
+use crypto_com_api::error::ApiError;
+use crypto_com_api::types::{Quantity, Side};
+use crypto_com_api::utils::action::Action;
+use crypto_com_api::websocket::actions::spot_trading_api::{
+    CancelAllOrders, CreateOrder, GetOpenOrders,
+};
+use crypto_com_api::websocket::actions::{ActionKind, GetInstruments};
+
+#[test]
+fn mutating_actions_are_recognized() {
+    let order = ActionKind::from(CreateOrder::limit(
+        "BTC_USDT",
+        Side::Buy,
+        20_000.0,
+        Quantity(0.5),
+    ));
+    let cancel = ActionKind::from(CancelAllOrders {
+        instrument_name: "BTC_USDT".to_owned(),
+    });
+    let open_orders = ActionKind::from(GetOpenOrders::default());
+
+    assert!(order.mutates());
+    assert!(cancel.mutates());
+    assert!(!open_orders.mutates());
+    assert!(!ActionKind::from(GetInstruments).mutates());
+}
+
+#[tokio::test]
+async fn read_only_controllers_refuse_mutating_actions() {
+    let mut controller = ControllerBuilder::new().read_only().build();
+    assert!(controller.is_read_only());
+
+    let order = CreateOrder::limit("BTC_USDT", Side::Buy, 20_000.0, Quantity(0.5));
+    let err = controller
+        .push_account_action("trading", order.clone())
+        .await
+        .expect_err("orders are refused");
+    assert!(matches!(err, ApiError::ReadOnly(ref method) if method == "private/create-order"));
+
+    // Reads get as far as looking up the account.
+    let err = controller
+        .push_account_action("trading", GetInstruments)
+        .await
+        .expect_err("there is no such account");
+    assert!(matches!(err, ApiError::UnknownCredentials(_)));
+
+    controller.set_read_only(false);
+    let err = controller
+        .push_account_action("trading", order)
+        .await
+        .expect_err("there is no such account");
+    assert!(matches!(err, ApiError::UnknownCredentials(_)));
+
+    let read_only = controller.into_read_only();
+    assert!(read_only.message_stats().is_none());
+}
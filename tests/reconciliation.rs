@@ -0,0 +1,193 @@
+#![cfg(feature = "rest")]
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crypto_com_api::prelude::ApiError;
+use crypto_com_api::reconciliation::{
+    Discrepancy, PendingItem, Reconciler, ReconciliationReport, TransferSource,
+};
+use crypto_com_api::rest::data::{
+    Account, DepositHistoryItem, DepositHistoryParams, WithdrawalHistoryItem,
+};
+use crypto_com_api::rest::private::GetWithdrawalHistoryParams;
+use crypto_com_api::types::{DepositStatus, EpochMs, Time, TimeRange, WithdrawalStatus};
+
+fn deposit(
+    id: u64,
+    currency: &str,
+    amount: f64,
+    at: u64,
+    status: DepositStatus,
+) -> DepositHistoryItem {
+    DepositHistoryItem {
+        id,
+        currency: currency.to_owned(),
+        amount,
+        fee: 0.5,
+        address: "address".to_owned(),
+        create_time: Time::from_epoch_ms(at),
+        status,
+    }
+}
+
+fn withdrawal(
+    id: u64,
+    currency: &str,
+    amount: f64,
+    txid: &str,
+    status: WithdrawalStatus,
+) -> WithdrawalHistoryItem {
+    WithdrawalHistoryItem {
+        id,
+        client_wid: None,
+        currency: currency.to_owned(),
+        amount,
+        fee: 0.25,
+        address: None,
+        create_time: Time::from_epoch_ms(id * 10),
+        status,
+        txid: txid.to_owned(),
+        network_id: String::new(),
+    }
+}
+
+#[test]
+fn transfers_are_totalled_and_checked() -> anyhow::Result<()> {
+    let deposits = [
+        deposit(1, "BTC", 2.0, 10, DepositStatus::Arrived),
+        deposit(1, "BTC", 2.0, 10, DepositStatus::Arrived),
+        deposit(2, "BTC", 1.0, 30, DepositStatus::NotArrived),
+        deposit(3, "ETH", 5.0, 20, DepositStatus::Failed),
+        deposit(4, "BTC", 8.0, 5_000, DepositStatus::Arrived),
+    ];
+    let withdrawals = [
+        withdrawal(1, "BTC", 1.0, "a", WithdrawalStatus::Completed),
+        withdrawal(2, "BTC", 1.0, "a", WithdrawalStatus::Completed),
+        withdrawal(3, "BTC", 1.0, "", WithdrawalStatus::Completed),
+        withdrawal(4, "ETH", 1.0, "b", WithdrawalStatus::Cancelled),
+        withdrawal(5, "CRO", 4.0, "", WithdrawalStatus::Processing),
+    ];
+    let accounts = [Account {
+        balance: 10.0,
+        available: 9.0,
+        order: 1.0,
+        stake: 0.0,
+        currency: "BTC".to_owned(),
+    }];
+
+    let report = ReconciliationReport::build(
+        TimeRange::new(0, 1_000)?,
+        &deposits,
+        &withdrawals,
+        &accounts,
+    );
+
+    // The repeated deposit and the one after the range are left out.
+    let btc = report.totals("BTC").expect("BTC totals");
+    assert_eq!(btc.deposited, 2.0);
+    assert_eq!(btc.deposits, 1);
+    assert_eq!(btc.pending_deposits, 1.0);
+    assert_eq!(btc.withdrawn, 3.0);
+    assert_eq!(btc.withdrawal_fees, 0.75);
+    assert_eq!(btc.fees(), 1.25);
+    assert_eq!(btc.net_flow(), -2.25);
+    assert_eq!(btc.balance, Some(10.0));
+
+    let eth = report.totals("ETH").expect("ETH totals");
+    assert_eq!(eth.failed, 2);
+    assert_eq!(eth.balance, None);
+
+    let pending: Vec<(&str, f64)> = report
+        .pending
+        .iter()
+        .map(|item| (item.currency(), item.amount()))
+        .collect();
+    assert_eq!(pending, [("BTC", 1.0), ("CRO", 4.0)]);
+    assert!(matches!(report.pending[0], PendingItem::Deposit(_)));
+
+    assert_eq!(
+        report.discrepancies,
+        [
+            Discrepancy::MissingTxid { withdrawal_id: 3 },
+            Discrepancy::FailedWithTxid {
+                withdrawal_id: 4,
+                txid: "b".to_owned(),
+            },
+            Discrepancy::DuplicateTxid {
+                txid: "a".to_owned(),
+                withdrawal_ids: vec![1, 2],
+            },
+        ]
+    );
+    assert!(!report.is_clean());
+
+    Ok(())
+}
+
+/// Five BTC deposits and no withdrawals, counting the requests.
+#[derive(Default)]
+struct FakeSource {
+    requests: AtomicU32,
+}
+
+impl TransferSource for FakeSource {
+    async fn deposits(
+        &self,
+        params: &DepositHistoryParams,
+    ) -> anyhow::Result<Vec<DepositHistoryItem>> {
+        self.requests.fetch_add(1, Ordering::SeqCst);
+
+        let page_size = params.page.page_size.unwrap_or_default();
+        let start = params.page.page.unwrap_or_default() * page_size;
+
+        Ok((start..5)
+            .take(usize::try_from(page_size)?)
+            .map(|id| deposit(id, "BTC", 1.0, id, DepositStatus::Arrived))
+            .collect())
+    }
+
+    async fn withdrawals(
+        &self,
+        _params: &GetWithdrawalHistoryParams,
+    ) -> anyhow::Result<Vec<WithdrawalHistoryItem>> {
+        self.requests.fetch_add(1, Ordering::SeqCst);
+
+        Ok(vec![])
+    }
+
+    async fn accounts(&self, currency: Option<&str>) -> anyhow::Result<Vec<Account>> {
+        self.requests.fetch_add(1, Ordering::SeqCst);
+        assert_eq!(currency, Some("BTC"));
+
+        Ok(vec![])
+    }
+}
+
+#[tokio::test]
+async fn every_page_is_fetched() -> anyhow::Result<()> {
+    let source = FakeSource::default();
+
+    let report = Reconciler::new(TimeRange::since(0))
+        .with_currency("BTC")
+        .with_page_size(2)
+        .run(&source)
+        .await?;
+
+    assert_eq!(report.totals("BTC").map(|btc| btc.deposits), Some(5));
+    assert!(report.is_clean());
+    // Three pages of deposits, one of withdrawals, and the account summary.
+    assert_eq!(source.requests.load(Ordering::SeqCst), 5);
+
+    let err = Reconciler::new(TimeRange::since(0))
+        .with_page_size(0)
+        .run(&source)
+        .await
+        .expect_err("an invalid page size");
+    assert!(matches!(
+        err.downcast_ref::<ApiError>(),
+        Some(ApiError::InvalidPageSize(0))
+    ));
+    assert_eq!(source.requests.load(Ordering::SeqCst), 5);
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+#![cfg(feature = "websocket")]
+
+mod common;
+
+use crypto_com_api::types::RejectReason;
+use crypto_com_api::websocket::data::{CreateOrderList, UserOrderRes};
+
+fn rejected(reason: Option<&str>) -> UserOrderRes {
+    common::user_order(
+        "BTC_USDT",
+        serde_json::json!({
+            "status": "REJECTED", "reason": reason, "price": 20000.0, "quantity": 0.01,
+            "exec_inst": "POST_ONLY"
+        }),
+    )
+}
+
+#[test]
+fn order_reasons_are_decoded() {
+    let reason = |raw| rejected(raw).data[0].reject_reason();
+
+    assert_eq!(reason(Some("43005")), Some(RejectReason::PostOnlyRejected));
+    assert_eq!(
+        reason(Some("MIN_NOTIONAL_VIOLATED")),
+        Some(RejectReason::MinNotionalViolated)
+    );
+    assert_eq!(
+        reason(Some("NOT_A_CODE")),
+        Some(RejectReason::Unknown("NOT_A_CODE".to_owned()))
+    );
+    assert_eq!(reason(Some("0")), None);
+    assert_eq!(reason(None), None);
+
+    let post_only = RejectReason::PostOnlyRejected;
+    assert_eq!(post_only.code(), Some(43_005));
+    assert_eq!(post_only.name(), "POST_ONLY_REJ");
+    assert_eq!(
+        post_only.to_string(),
+        "POST_ONLY order would have taken liquidity (43005 POST_ONLY_REJ)"
+    );
+}
+
+#[test]
+fn unknown_codes_are_kept() {
+    let unknown = RejectReason::from_code(99_999);
+
+    assert_eq!(unknown, RejectReason::Unknown("99999".to_owned()));
+    assert_eq!(unknown.code(), Some(99_999));
+    assert_eq!(unknown.to_string(), "Unknown reason (99999)");
+}
+
+#[test]
+fn order_list_codes_are_decoded() -> anyhow::Result<()> {
+    let list: CreateOrderList = serde_json::from_value(serde_json::json!({
+        "result_list": [
+            { "index": 0, "code": 0, "order_id": 11 },
+            { "index": 1, "code": 30006, "message": "MIN_PRICE_VIOLATED", "order_id": 12 }
+        ]
+    }))?;
+
+    assert_eq!(list.result_list[0].reject_reason(), None);
+    assert_eq!(
+        list.result_list[1].reject_reason(),
+        Some(RejectReason::MinPriceViolated)
+    );
+
+    Ok(())
+}
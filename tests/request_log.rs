@@ -0,0 +1,53 @@
+use crypto_com_api::utils::request_log::{Direction, RequestLog, Verbosity, REDACTED};
+
+const WITHDRAWAL: &str = r#"{"id":4,"method":"private/create-withdrawal","api_key":"key","sig":"abc","params":{"currency":"BTC","amount":1,"address":"bc1q","address_tag":"memo"}}"#;
+
+#[test]
+fn secrets_are_redacted_wherever_they_are() -> anyhow::Result<()> {
+    let log = RequestLog::new(Verbosity::Requests).redact("client_wid");
+    let frame = log.frame(Direction::Request, WITHDRAWAL);
+
+    assert_eq!(frame.id, Some(4));
+    assert_eq!(frame.method.as_deref(), Some("private/create-withdrawal"));
+
+    let payload: serde_json::Value = serde_json::from_str(frame.payload.as_deref().unwrap())?;
+    assert_eq!(payload["api_key"], REDACTED);
+    assert_eq!(payload["sig"], REDACTED);
+    assert_eq!(payload["params"]["address"], REDACTED);
+    assert_eq!(payload["params"]["address_tag"], REDACTED);
+    assert_eq!(payload["params"]["currency"], "BTC");
+
+    let redacted = log
+        .redact_text(r#"{"result":{"data":[{"client_wid":"w1","address":"bc1q"}]}}"#)
+        .unwrap();
+    assert!(!redacted.contains("w1") && !redacted.contains("bc1q"));
+    assert!(log.redact_text("not json").is_none());
+
+    Ok(())
+}
+
+#[test]
+fn verbosity_limits_the_payloads() {
+    let reply =
+        r#"{"id":4,"method":"private/create-withdrawal","code":0,"result":{"address":"bc1q"}}"#;
+
+    let methods = RequestLog::new(Verbosity::Methods);
+    assert_eq!(methods.frame(Direction::Request, WITHDRAWAL).payload, None);
+
+    let requests = RequestLog::new(Verbosity::Requests);
+    assert!(requests
+        .frame(Direction::Request, WITHDRAWAL)
+        .payload
+        .is_some());
+    let response = requests.frame(Direction::Response, reply);
+    assert_eq!(response.id, Some(4));
+    assert_eq!(response.payload, None);
+
+    let all = RequestLog::new(Verbosity::All);
+    assert_eq!(
+        all.frame(Direction::Response, reply).payload.as_deref(),
+        Some(
+            r#"{"code":0,"id":4,"method":"private/create-withdrawal","result":{"address":"[redacted]"}}"#
+        )
+    );
+}
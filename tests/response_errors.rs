@@ -0,0 +1,94 @@
+#![cfg(feature = "websocket")]
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+use tokio_tungstenite::tungstenite::Message;
+
+use crypto_com_api::utils::in_flight::InFlight;
+use crypto_com_api::websocket::handler::HandlerRegistry;
+use crypto_com_api::websocket::{user_api::process_user, WebsocketData};
+
+async fn process(frame: &str) -> anyhow::Result<Vec<WebsocketData>> {
+    let (user_tx, _user_rx) = futures_channel::mpsc::unbounded();
+    let (data_tx, mut data_rx) = tokio::sync::broadcast::channel(4);
+
+    process_user(
+        Message::Text(frame.to_owned()),
+        Arc::new(Mutex::new(user_tx)),
+        &data_tx,
+        &InFlight::default(),
+        None,
+        &HandlerRegistry::new(),
+    )
+    .await?;
+
+    let mut sent = vec![];
+
+    while let Ok(data) = data_rx.try_recv() {
+        let data = Arc::try_unwrap(data).map_err(|_| anyhow::anyhow!("data is shared"))?;
+        sent.extend(data.result);
+    }
+
+    Ok(sent)
+}
+
+#[tokio::test]
+async fn rejected_order_is_an_error_event() -> anyhow::Result<()> {
+    let sent = process(
+        r#"{
+            "id": 11, "method": "private/create-order", "code": 306,
+            "message": "INSUFFICIENT_AVAILABLE_BALANCE",
+            "detail_code": "306", "detail_message": "Insufficient balance"
+        }"#,
+    )
+    .await?;
+
+    let [WebsocketData::Error {
+        ref method,
+        code,
+        ref message,
+        ref detail,
+    }] = sent[..]
+    else {
+        anyhow::bail!("expected one error, got {sent:?}");
+    };
+
+    assert_eq!(method.as_deref(), Some("private/create-order"));
+    assert_eq!(code, 306);
+    assert_eq!(message.as_deref(), Some("INSUFFICIENT_AVAILABLE_BALANCE"));
+    assert_eq!(detail.as_deref(), Some("Insufficient balance"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn failed_auth_is_not_reported_as_auth() -> anyhow::Result<()> {
+    let sent = process(r#"{"id": 1, "method": "public/auth", "code": 10002}"#).await?;
+
+    assert!(matches!(
+        sent[..],
+        [WebsocketData::Error {
+            code: 10002,
+            detail: None,
+            ..
+        }]
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn successful_response_has_no_error() -> anyhow::Result<()> {
+    let sent = process(
+        r#"{
+            "id": 12, "method": "private/create-order", "code": 0,
+            "result": {"order_id": 1, "client_oid": "a"}
+        }"#,
+    )
+    .await?;
+
+    assert!(matches!(sent[..], [WebsocketData::CreateOrder(_)]));
+
+    Ok(())
+}
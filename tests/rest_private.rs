@@ -0,0 +1,233 @@
+#![cfg(feature = "rest")]
+
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener};
+use std::thread::{self, JoinHandle};
+
+use crypto_com_api::rest::data::{
+    AccountSummaryParams, DustConversionQuoteParams, EarnPositionStatus, EarnProductsParams,
+    EarnTerm, RedeemParams,
+};
+use crypto_com_api::rest::earn;
+use crypto_com_api::rest::private::{convert_dust, get_account_summary, get_dust_conversion_quote};
+use crypto_com_api::utils::config::Config;
+
+/// Answer one request with an empty account summary and return the request as it was recieved.
+fn serve_once() -> anyhow::Result<(SocketAddr, JoinHandle<std::io::Result<String>>)> {
+    serve(
+        r#"{"id": 0, "method": "private/get-account-summary", "code": 0, "result": {"accounts": []}}"#,
+    )
+}
+
+/// Answer one request with `body` and return the request as it was recieved.
+fn serve(body: &'static str) -> anyhow::Result<(SocketAddr, JoinHandle<std::io::Result<String>>)> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept()?;
+        let mut request = vec![];
+        let mut buf = [0; 1024];
+
+        // Read the head, then the body by its content length.
+        let body_start = loop {
+            let read = stream.read(&mut buf)?;
+            request.extend_from_slice(&buf[..read]);
+
+            if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                break end + 4;
+            }
+
+            if read == 0 {
+                break request.len();
+            }
+        };
+        let head = String::from_utf8_lossy(&request[..body_start]).to_lowercase();
+        let length = head
+            .lines()
+            .find_map(|line| line.strip_prefix("content-length: "))
+            .and_then(|length| length.trim().parse::<usize>().ok())
+            .unwrap_or_default();
+
+        while request.len() < body_start + length {
+            let read = stream.read(&mut buf)?;
+            if read == 0 {
+                break;
+            }
+            request.extend_from_slice(&buf[..read]);
+        }
+
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        )?;
+
+        Ok(String::from_utf8_lossy(&request).into_owned())
+    });
+
+    Ok((addr, server))
+}
+
+fn config(addr: SocketAddr) -> anyhow::Result<Config> {
+    Ok(Config {
+        rest_url: Some(url::Url::parse(&format!("http://{addr}/v2/"))?),
+        api_key: Some("key".to_owned()),
+        secret_key: Some("secret".to_owned()),
+        ..Config::default()
+    })
+}
+
+fn assert_private_request(request: &str) {
+    assert!(request.starts_with("POST /v2/private/get-account-summary HTTP/1.1\r\n"));
+    assert!(request
+        .to_lowercase()
+        .contains("content-type: application/json"));
+    assert!(request.contains(r#""method":"private/get-account-summary""#));
+    assert!(request.contains(r#""sig":"#));
+}
+
+#[tokio::test]
+async fn private_method_is_posted_to_its_path() -> anyhow::Result<()> {
+    let (addr, server) = serve_once()?;
+
+    let res = get_account_summary(&config(addr)?, AccountSummaryParams { currency: None }).await?;
+    assert_eq!(res.code, Some(0));
+
+    assert_private_request(&server.join().expect("server thread")?);
+
+    Ok(())
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_private_method_is_posted_to_its_path() -> anyhow::Result<()> {
+    let (addr, server) = serve_once()?;
+
+    let res = crypto_com_api::rest::blocking::private::get_account_summary(
+        &config(addr)?,
+        AccountSummaryParams { currency: None },
+    )?;
+    assert_eq!(res.code, Some(0));
+
+    assert_private_request(&server.join().expect("server thread")?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn dust_is_quoted_then_converted() -> anyhow::Result<()> {
+    let (addr, server) = serve(
+        r#"{"id": 0, "method": "private/get-dust-conversion-quote", "code": 0, "result": {
+            "quote_id": "q-1", "target_currency": "CRO", "target_quantity": 12.5,
+            "items": [
+                {"currency": "BTC", "quantity": 0.00001, "target_quantity": 10.0, "fee": 0.2},
+                {"currency": "ETH", "quantity": 0.0001, "target_quantity": 2.5, "fee": 0.05}
+            ],
+            "expire_time": 1686000010000
+        }}"#,
+    )?;
+
+    let res = get_dust_conversion_quote(
+        &config(addr)?,
+        DustConversionQuoteParams {
+            currencies: vec!["BTC".to_owned(), "ETH".to_owned()],
+        },
+    )
+    .await?;
+    let quote = res.result.expect("a quote");
+    assert_eq!(quote.items.len(), 2);
+
+    let request = server.join().expect("server thread")?;
+    assert!(request.starts_with("POST /v2/private/get-dust-conversion-quote HTTP/1.1\r\n"));
+    assert!(request.contains(r#""currencies":["BTC","ETH"]"#));
+
+    let (addr, server) = serve(
+        r#"{"id": 0, "method": "private/convert-dust", "code": 0, "result": {
+            "conversion_id": "c-1", "quote_id": "q-1", "target_currency": "CRO",
+            "target_quantity": 12.5,
+            "items": [
+                {"currency": "BTC", "quantity": 0.00001, "target_quantity": 10.0, "fee": 0.2},
+                {"currency": "ETH", "quantity": 0.0001, "target_quantity": 2.5, "fee": 0.05}
+            ],
+            "create_time": 1686000005000
+        }}"#,
+    )?;
+
+    let res = convert_dust(&config(addr)?, (&quote).into()).await?;
+    let conversion = res.result.expect("a conversion");
+    assert_eq!(conversion.quote_id, quote.quote_id);
+    assert!((conversion.total_fee() - 0.25).abs() < 1e-9);
+
+    let request = server.join().expect("server thread")?;
+    assert!(request.starts_with("POST /v2/private/convert-dust HTTP/1.1\r\n"));
+    assert!(request.contains(r#""quote_id":"q-1""#));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn earn_products_are_fetched_and_ranked() -> anyhow::Result<()> {
+    let (addr, server) = serve(
+        r#"{"id": 0, "method": "private/get-earn-products", "code": 0, "result": {"products": [
+            {"product_id": "cro-flex", "currency": "CRO", "term": "FLEXIBLE", "duration_days": null,
+             "apr": 0.02, "min_amount": 10.0, "max_amount": null, "available": true},
+            {"product_id": "cro-flex-boost", "currency": "CRO", "term": "FLEXIBLE",
+             "duration_days": null, "apr": 0.04, "min_amount": 10.0, "max_amount": 500.0,
+             "available": true},
+            {"product_id": "cro-30", "currency": "CRO", "term": "FIXED", "duration_days": 30,
+             "apr": 0.08, "min_amount": 100.0, "max_amount": null, "available": true},
+            {"product_id": "cro-flex-closed", "currency": "CRO", "term": "FLEXIBLE",
+             "duration_days": null, "apr": 0.1, "min_amount": 1.0, "max_amount": null,
+             "available": false}
+        ]}}"#,
+    )?;
+
+    let res = earn::get_products(&config(addr)?, EarnProductsParams::default()).await?;
+    let products = res.result.expect("products");
+
+    let best = products.best("CRO", EarnTerm::Flexible).expect("a product");
+    assert_eq!(best.product_id, "cro-flex-boost");
+    assert!(best.accepts(100.0));
+    assert!(!best.accepts(1_000.0));
+    assert_eq!(
+        products
+            .best("CRO", EarnTerm::Fixed)
+            .map(|p| p.duration_days),
+        Some(Some(30))
+    );
+    assert!(products.best("BTC", EarnTerm::Flexible).is_none());
+
+    let request = server.join().expect("server thread")?;
+    assert!(request.starts_with("POST /v2/private/get-earn-products HTTP/1.1\r\n"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn earn_positions_are_redeemed() -> anyhow::Result<()> {
+    let (addr, server) = serve(
+        r#"{"id": 0, "method": "private/earn-redeem", "code": 0, "result": {
+            "position_id": "p-1", "product_id": "cro-flex", "currency": "CRO", "amount": 100.0,
+            "accrued_interest": 0.5, "apr": 0.02, "status": "REDEEMING",
+            "start_time": 1686000000000, "end_time": null
+        }}"#,
+    )?;
+
+    let res = earn::redeem(
+        &config(addr)?,
+        RedeemParams {
+            position_id: "p-1".to_owned(),
+            amount: None,
+        },
+    )
+    .await?;
+    let position = res.result.expect("a position");
+    assert_eq!(position.status, EarnPositionStatus::Redeeming);
+
+    let request = server.join().expect("server thread")?;
+    assert!(request.starts_with("POST /v2/private/earn-redeem HTTP/1.1\r\n"));
+    assert!(request.contains(r#""position_id":"p-1""#));
+
+    Ok(())
+}
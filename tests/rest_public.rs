@@ -3,24 +3,19 @@
 use anyhow::Result;
 use crypto_com_api::{
     rest::public::{get_book, get_candlestick, get_instruments, get_ticker, get_trades},
-    utils::config::Config,
+    types::{Depth, Interval},
+    utils::{config::Config, environment::Environment},
 };
 
 fn create_config() -> Config {
-    Config {
-        rest_url: Some(
-            url::Url::parse("https://uat-api.3ona.co/v2/")
-                .expect("UAS Rest API Sandbox Invalid URL"),
-        ),
-        ..Default::default()
-    }
+    Config::for_environment(Environment::UatSandbox)
 }
 
 #[tokio::test]
 async fn rest_get_book() -> Result<()> {
     let config = create_config();
 
-    let _ = get_book(&config, "BTC_USDT".to_owned(), 50).await?;
+    let _ = get_book(&config, "BTC_USDT".to_owned(), Depth::MAX).await?;
 
     Ok(())
 }
@@ -29,7 +24,7 @@ async fn rest_get_book() -> Result<()> {
 async fn rest_get_candlestick() -> Result<()> {
     let config = create_config();
 
-    let _ = get_candlestick(&config, "BTC_USDT".to_owned(), "5m".to_owned()).await?;
+    let _ = get_candlestick(&config, "BTC_USDT".to_owned(), Interval::FiveMinutes).await?;
 
     Ok(())
 }
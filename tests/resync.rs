@@ -0,0 +1,99 @@
+#![cfg(feature = "websocket")]
+
+use crypto_com_api::api_response::ApiResponse;
+use crypto_com_api::websocket::actions::{BookSubscriptionType, Subscribe};
+use crypto_com_api::websocket::market_api::decode_subscription;
+use crypto_com_api::websocket::resync::{ResyncFailure, ResyncTracker};
+use crypto_com_api::websocket::subscriptions::{book_instrument, Subscriptions};
+use crypto_com_api::websocket::WebsocketData;
+
+const SNAPSHOT: &str = r#"{
+    "id": -1, "method": "subscribe", "code": 0,
+    "result": {
+        "instrument_name": "BTCUSD-PERP", "subscription": "book.BTCUSD-PERP.10",
+        "channel": "book", "depth": 10,
+        "data": [{
+            "bids": [["30000.0", "1.0", "2"]],
+            "asks": [["30000.5", "0.5", "1"]],
+            "tt": 1, "t": 1, "u": 100, "cs": 0
+        }]
+    }
+}"#;
+
+fn reply(id: i64, method: &str, code: u64) -> ApiResponse<WebsocketData> {
+    ApiResponse {
+        id,
+        method: Some(method.to_owned()),
+        code: Some(code),
+        ..ApiResponse::default()
+    }
+}
+
+#[test]
+fn book_channels_are_resubscribed_with_their_options() {
+    let mut subscriptions = Subscriptions::default();
+
+    subscriptions.insert(
+        &Subscribe {
+            channels: vec![
+                "book.BTCUSD-PERP.10".to_owned(),
+                "ticker.BTCUSD-PERP".to_owned(),
+            ],
+            ..Subscribe::default()
+        }
+        .with_book_subscription_type(BookSubscriptionType::SnapshotAndUpdate),
+    );
+
+    let books = subscriptions.books();
+
+    assert_eq!(subscriptions.len(), 2);
+    assert_eq!(books.len(), 1);
+    assert_eq!(books[0].channels, vec!["book.BTCUSD-PERP.10".to_owned()]);
+    assert_eq!(
+        books[0].book_subscription_type,
+        Some(BookSubscriptionType::SnapshotAndUpdate)
+    );
+
+    assert!(subscriptions.remove("book.BTCUSD-PERP.10").is_some());
+    assert!(subscriptions.books().is_empty());
+
+    assert_eq!(book_instrument("book.BTC_USDT"), Some("BTC_USDT"));
+    assert_eq!(book_instrument("book.update"), None);
+    assert_eq!(book_instrument("ticker.BTC_USDT"), None);
+}
+
+#[test]
+fn complete_once_replied_and_books_have_snapshots() -> anyhow::Result<()> {
+    let mut tracker =
+        ResyncTracker::new(vec![1, 2, 3], vec![(3, "book.BTCUSD-PERP.10".to_owned())]);
+
+    assert_eq!(
+        tracker.process(&reply(1, "private/get-open-orders", 0)),
+        None
+    );
+    assert_eq!(
+        tracker.process(&reply(2, "private/get-account-summary", 10_001)),
+        None
+    );
+    assert_eq!(tracker.process(&reply(3, "subscribe", 0)), None);
+    assert!(!tracker.is_complete());
+
+    let msg: ApiResponse<serde_json::Value> = serde_json::from_str(SNAPSHOT)?;
+    let book = ApiResponse::<WebsocketData>::default()
+        .websocket_data(decode_subscription(&msg, 0)?.expect("book data"));
+
+    let complete = tracker.process(&book).expect("resync complete");
+
+    assert_eq!(complete.ids, vec![1, 2, 3]);
+    assert_eq!(
+        complete.failures,
+        vec![ResyncFailure {
+            id: 2,
+            method: Some("private/get-account-summary".to_owned()),
+            code: Some(10_001),
+        }]
+    );
+    assert!(!complete.is_success());
+
+    Ok(())
+}
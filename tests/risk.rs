@@ -0,0 +1,242 @@
+#![cfg(feature = "websocket")]
+
+mod common;
+
+use crypto_com_api::api_response::ApiResponse;
+use crypto_com_api::controller::ControllerBuilder;
+use crypto_com_api::error::{ApiError, RiskViolation};
+use crypto_com_api::risk::{order_notional, RiskLimits, RiskManager};
+use crypto_com_api::types::{EpochMs, Notional, Quantity, Side, Time};
+use crypto_com_api::websocket::actions::spot_trading_api::{CreateOrder, CreateOrderList};
+use crypto_com_api::websocket::data::{
+    EventKind, ExtraFields, OrderItem, UserOrderRes, UserTrade, UserTradeRes,
+};
+use crypto_com_api::websocket::{market_api::decode_subscription, WebsocketData};
+
+fn order(side: Side, price: Option<f64>, quantity: f64) -> CreateOrder {
+    match (price, side) {
+        (Some(price), _) => CreateOrder::limit("BTC_USDT", side, price, Quantity(quantity)),
+        (None, Side::Buy) => CreateOrder::market_buy("BTC_USDT", Quantity(quantity)),
+        (None, Side::Sell) => CreateOrder::market_sell("BTC_USDT", Quantity(quantity)),
+    }
+}
+
+fn open_order(order_id: &str, status: &str) -> OrderItem {
+    OrderItem {
+        status: status.to_owned(),
+        reason: None,
+        side: "BUY".to_owned(),
+        price: 100.0,
+        quantity: 1.0,
+        order_id: order_id.to_owned(),
+        client_oid: String::new(),
+        create_time: Time::from_epoch_ms(1),
+        update_time: Time::from_epoch_ms(1),
+        order_type: "LIMIT".to_owned(),
+        instrument_name: "BTC_USDT".to_owned(),
+        cumulative_quantity: 0.0,
+        cumulative_value: 0.0,
+        avg_price: 0.0,
+        fee_currency: "USDT".to_owned(),
+        time_in_force: "GOOD_TILL_CANCEL".to_owned(),
+        exec_inst: None,
+        trigger_price: None,
+        extra: ExtraFields::new(),
+    }
+}
+
+fn orders(data: Vec<OrderItem>) -> WebsocketData {
+    WebsocketData::UserOrder(UserOrderRes {
+        instrument_name: "BTC_USDT".to_owned(),
+        subscription: "user.order.BTC_USDT".to_owned(),
+        channel: "user.order".to_owned(),
+        data,
+        kind: EventKind::Update,
+    })
+}
+
+#[test]
+fn notional_limit() {
+    let manager = RiskManager::new(RiskLimits {
+        max_order_notional: Some(1_000.0),
+        ..RiskLimits::default()
+    });
+
+    assert_eq!(
+        order_notional(&order(Side::Buy, Some(100.0), 5.0)),
+        Some(500.0)
+    );
+    assert_eq!(order_notional(&order(Side::Buy, None, 5.0)), None);
+
+    assert!(manager
+        .check_order(&order(Side::Buy, Some(100.0), 10.0))
+        .is_ok());
+    assert_eq!(
+        manager.check_order(&order(Side::Buy, Some(100.0), 11.0)),
+        Err(RiskViolation::OrderNotional {
+            notional: 1_100.0,
+            max: 1_000.0,
+        })
+    );
+}
+
+#[test]
+fn open_orders_limit_counts_the_list() {
+    let mut manager = RiskManager::new(RiskLimits {
+        max_open_orders: Some(3),
+        ..RiskLimits::default()
+    });
+
+    manager.process(&orders(vec![
+        open_order("1", "ACTIVE"),
+        open_order("2", "ACTIVE"),
+    ]));
+    assert_eq!(manager.open_orders(), 2);
+
+    let list = CreateOrderList::list(vec![
+        order(Side::Buy, Some(99.0), 1.0),
+        order(Side::Buy, Some(98.0), 1.0),
+    ]);
+    assert_eq!(
+        manager.check_order_list(&list),
+        Err(RiskViolation::OpenOrders { open: 4, max: 3 })
+    );
+
+    manager.process(&orders(vec![open_order("1", "FILLED")]));
+    assert_eq!(manager.open_orders(), 1);
+    assert!(manager.check_order_list(&list).is_ok());
+}
+
+#[test]
+fn position_limit_follows_fills() {
+    let mut manager = RiskManager::new(RiskLimits {
+        max_position: Some(2.0),
+        ..RiskLimits::default()
+    });
+
+    manager.process(&WebsocketData::UserTrade(UserTradeRes {
+        instrument_name: "BTC_USDT".to_owned(),
+        subscription: "user.trade.BTC_USDT".to_owned(),
+        channel: "user.trade".to_owned(),
+        data: vec![UserTrade {
+            side: "BUY".to_owned(),
+            fee: 0.1,
+            trade_id: 1,
+            create_time: Time::from_epoch_ms(1),
+            traded_price: 100.0,
+            traded_quantity: 1.5,
+            fee_currency: "USDT".to_owned(),
+            order_id: 1,
+        }],
+        kind: EventKind::Update,
+    }));
+    assert_eq!(manager.position("BTC_USDT"), 1.5);
+
+    assert!(manager.check_order(&order(Side::Buy, None, 0.5)).is_ok());
+    assert!(manager.check_order(&order(Side::Sell, None, 3.5)).is_ok());
+    assert!(matches!(
+        manager.check_order(&order(Side::Buy, None, 1.0)),
+        Err(RiskViolation::Position { max, .. }) if max == 2.0
+    ));
+}
+
+#[test]
+fn kill_switch_rejects_everything() {
+    let mut manager = RiskManager::new(RiskLimits::default());
+
+    manager.set_kill_switch(true);
+    assert_eq!(
+        manager.check_order(&order(Side::Buy, Some(1.0), 1.0)),
+        Err(RiskViolation::KillSwitch)
+    );
+
+    manager.set_kill_switch(false);
+    assert!(manager
+        .check_order(&order(Side::Buy, Some(1.0), 1.0))
+        .is_ok());
+}
+
+#[test]
+fn market_orders_by_quantity_are_priced_at_the_last_trade() -> anyhow::Result<()> {
+    let mut manager = RiskManager::new(RiskLimits {
+        max_order_notional: Some(1_000.0),
+        ..RiskLimits::default()
+    });
+
+    assert_eq!(
+        manager.check_order(&order(Side::Sell, None, 5.0)),
+        Err(RiskViolation::Unpriced("BTC_USDT".to_owned()))
+    );
+
+    let trades: ApiResponse<serde_json::Value> = serde_json::from_str(
+        r#"{
+            "id": -1, "method": "subscribe", "code": 0,
+            "result": {
+                "instrument_name": "BTC_USDT", "subscription": "trade.BTC_USDT",
+                "channel": "trade",
+                "data": [
+                    {"s": "BUY", "p": "300.0", "q": "1.0", "t": 2, "d": "2", "i": "BTC_USDT"},
+                    {"s": "SELL", "p": "100.0", "q": "1.0", "t": 1, "d": "1", "i": "BTC_USDT"}
+                ]
+            }
+        }"#,
+    )?;
+    manager.process(&decode_subscription(&trades, 0)?.expect("trade data"));
+    assert_eq!(manager.last_price("BTC_USDT"), Some(300.0));
+
+    assert!(manager.check_order(&order(Side::Sell, None, 3.0)).is_ok());
+    assert_eq!(
+        manager.check_order(&order(Side::Sell, None, 5.0)),
+        Err(RiskViolation::OrderNotional {
+            notional: 1_500.0,
+            max: 1_000.0,
+        })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn market_buys_by_notional_count_toward_the_position() {
+    let mut manager = RiskManager::new(RiskLimits {
+        max_position: Some(2.0),
+        ..RiskLimits::default()
+    });
+    let buy = CreateOrder::market_buy("BTC_USDT", Notional(300.0));
+
+    assert_eq!(
+        manager.check_order(&buy),
+        Err(RiskViolation::Unpriced("BTC_USDT".to_owned()))
+    );
+
+    manager.set_last_price("BTC_USDT", 200.0);
+    assert!(manager.check_order(&buy).is_ok());
+
+    manager.set_last_price("BTC_USDT", 100.0);
+    assert!(matches!(
+        manager.check_order(&buy),
+        Err(RiskViolation::Position { position, .. }) if position == 3.0
+    ));
+}
+
+#[tokio::test]
+async fn queue_orders_are_checked() -> anyhow::Result<()> {
+    let (url, _frames_rx) = common::mock_websocket().await;
+    let mut controller = ControllerBuilder::new()
+        .with_user_websocket(url)
+        .await?
+        .build();
+    // Handed out before the risk manager is set.
+    let queue = controller.user_queue();
+
+    let mut manager = RiskManager::new(RiskLimits::default());
+    manager.set_kill_switch(true);
+    let _risk = controller.set_risk_manager(manager);
+
+    assert!(matches!(
+        queue.push(order(Side::Buy, Some(1.0), 1.0)).await,
+        Err(ApiError::RiskViolation(RiskViolation::KillSwitch))
+    ));
+
+    Ok(())
+}
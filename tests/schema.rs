@@ -0,0 +1,132 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use crypto_com_api::schema::{EventKind, Transport};
+
+/// The `public/` and `private/` methods in the code under `src/{dir}`, comments aside, e.g. what
+/// each action's `method()` and each REST function sends.
+fn methods_in(dir: &str) -> BTreeSet<String> {
+    let mut methods = BTreeSet::new();
+    let mut dirs = vec![Path::new(env!("CARGO_MANIFEST_DIR")).join("src").join(dir)];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir).expect("a readable source directory") {
+            let path = entry.expect("a source entry").path();
+
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+
+            let code = fs::read_to_string(&path).expect("a readable source file");
+
+            for line in code
+                .lines()
+                .filter(|line| !line.trim_start().starts_with("//"))
+            {
+                for prefix in ["\"public/", "\"private/", "}public/", "}private/"] {
+                    for (at, _) in line.match_indices(prefix) {
+                        let method = &line[at + 1..];
+                        let end = method
+                            .find(|c: char| !(c.is_ascii_lowercase() || c == '-' || c == '/'))
+                            .unwrap_or(method.len());
+
+                        if method[end..].starts_with('"') {
+                            methods.insert(method[..end].to_owned());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    methods
+}
+
+fn missing(methods: BTreeSet<String>, transports: &[Transport]) -> Vec<String> {
+    let schema = crypto_com_api::schema();
+
+    methods
+        .into_iter()
+        .filter(|method| {
+            !schema
+                .iter()
+                .any(|e| e.name == method && transports.contains(&e.transport))
+        })
+        .collect()
+}
+
+#[test]
+fn schema_lists_methods_and_channels() {
+    let schema = crypto_com_api::schema();
+
+    let create_order = schema
+        .iter()
+        .find(|e| e.name == "private/create-order" && e.transport == Transport::WebsocketUser)
+        .expect("private/create-order missing from schema");
+    assert_eq!(create_order.kind, EventKind::Method);
+    assert!(create_order.private);
+    assert!(create_order.params.unwrap().ends_with("CreateOrder"));
+
+    let book = schema
+        .iter()
+        .find(|e| e.name == "book.{instrument_name}")
+        .expect("book channel missing from schema");
+    assert_eq!(book.kind, EventKind::Channel);
+    assert!(!book.private);
+    assert!(book.response.unwrap().ends_with("BookRes"));
+
+    assert!(schema
+        .iter()
+        .any(|e| e.name == "public/get-ticker" && e.transport == Transport::Rest));
+}
+
+#[cfg(feature = "websocket")]
+#[test]
+fn schema_lists_every_websocket_method() {
+    let methods = methods_in("websocket");
+    assert!(methods.contains("private/create-order"));
+
+    let missing = missing(
+        methods,
+        &[Transport::WebsocketUser, Transport::WebsocketMarket],
+    );
+    assert!(missing.is_empty(), "missing from schema: {missing:?}");
+
+    for transport in [Transport::WebsocketUser, Transport::WebsocketMarket] {
+        assert!(crypto_com_api::schema()
+            .iter()
+            .any(|e| e.name == "public/heartbeat" && e.transport == transport));
+    }
+}
+
+#[cfg(feature = "rest")]
+#[test]
+fn schema_lists_every_rest_method() {
+    let methods = methods_in("rest");
+    assert!(methods.contains("public/get-book"));
+
+    let missing = missing(methods, &[Transport::Rest]);
+    assert!(missing.is_empty(), "missing from schema: {missing:?}");
+}
+
+#[cfg(feature = "schemars")]
+#[test]
+fn schema_has_json_schemas() {
+    let schema = crypto_com_api::schema();
+
+    for entry in schema {
+        assert_eq!(entry.params.is_some(), entry.params_schema.is_some());
+        assert_eq!(entry.response.is_some(), entry.response_schema.is_some());
+    }
+
+    let create_order = schema
+        .iter()
+        .find(|e| e.name == "private/create-order" && e.transport == Transport::WebsocketUser)
+        .expect("private/create-order missing from schema");
+    let params = create_order.params_schema.as_ref().unwrap().as_value();
+
+    assert!(params["properties"].get("instrument_name").is_some());
+    assert!(params["properties"].get("api_version").is_none());
+}
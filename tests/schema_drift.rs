@@ -0,0 +1,43 @@
+#![cfg(feature = "websocket")]
+
+use crypto_com_api::websocket::data::UserBalance;
+
+const BALANCE: &str = r#"{
+    "currency": "CRO", "balance": 10.0, "available": 9.0, "order": 1.0, "stake": 0.0,
+    "reserved_for_margin": 2.5
+}"#;
+
+#[cfg(not(feature = "strict_schema"))]
+#[test]
+fn unknown_fields_are_kept() -> anyhow::Result<()> {
+    let balance: UserBalance = serde_json::from_str(BALANCE)?;
+
+    assert_eq!(balance.available, 9.0);
+    assert_eq!(balance.extra["reserved_for_margin"], 2.5);
+
+    let json = serde_json::to_value(&balance)?;
+    assert_eq!(json["reserved_for_margin"], 2.5);
+    assert!(json.get("extra").is_none());
+
+    Ok(())
+}
+
+#[cfg(not(feature = "strict_schema"))]
+#[test]
+fn missing_lists_are_empty() -> anyhow::Result<()> {
+    use crypto_com_api::websocket::data::AccountSummary;
+
+    let summary: AccountSummary = serde_json::from_str("{}")?;
+
+    assert!(summary.accounts.is_empty());
+
+    Ok(())
+}
+
+#[cfg(feature = "strict_schema")]
+#[test]
+fn unknown_fields_are_refused() {
+    let err = serde_json::from_str::<UserBalance>(BALANCE).expect_err("the field is not known");
+
+    assert!(err.to_string().contains("reserved_for_margin"));
+}
@@ -0,0 +1,207 @@
+#![cfg(feature = "websocket")]
+
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crypto_com_api::controller::ControllerBuilder;
+use crypto_com_api::prelude::ApiError;
+use crypto_com_api::utils::environment::Environment;
+use crypto_com_api::utils::in_flight::{Api, InFlight};
+use crypto_com_api::websocket::actions::wallet_management_api::CreateWithdrawal;
+use crypto_com_api::websocket::actions::GetInstruments;
+use crypto_com_api::websocket::{expire_requests, replayable, WebsocketData};
+
+#[tokio::test]
+async fn oldest_request_is_dropped_at_capacity() {
+    let in_flight = InFlight::with_capacity(2);
+
+    assert_eq!(
+        in_flight
+            .insert(0, Api::Market, Arc::new(GetInstruments.into()))
+            .await,
+        None
+    );
+    assert_eq!(
+        in_flight
+            .insert(1, Api::User, Arc::new(GetInstruments.into()))
+            .await,
+        None
+    );
+    assert_eq!(
+        in_flight
+            .insert(2, Api::Market, Arc::new(GetInstruments.into()))
+            .await,
+        Some(0)
+    );
+
+    assert_eq!(in_flight.ids().await, vec![1, 2]);
+}
+
+#[tokio::test]
+async fn take_only_returns_requests_for_the_api() {
+    let in_flight = InFlight::default();
+
+    for id in [3, 1, 2] {
+        let api = if id == 2 { Api::User } else { Api::Market };
+        in_flight
+            .insert(id, api, Arc::new(GetInstruments.into()))
+            .await;
+    }
+
+    let market: Vec<u64> = in_flight
+        .take(Api::Market)
+        .await
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+
+    assert_eq!(market, vec![1, 3]);
+    assert_eq!(in_flight.ids().await, vec![2]);
+}
+
+#[tokio::test]
+async fn settled_before_waits_for_older_requests_only() {
+    let in_flight = Arc::new(InFlight::default());
+
+    for (id, api) in [
+        (1, Api::User),
+        (2, Api::Market),
+        (3, Api::User),
+        (4, Api::User),
+    ] {
+        in_flight
+            .insert(id, api, Arc::new(GetInstruments.into()))
+            .await;
+    }
+
+    let settled = tokio::spawn({
+        let in_flight = Arc::clone(&in_flight);
+
+        async move { in_flight.settled_before(Api::User, 4).await }
+    });
+
+    in_flight.resolve(1).await;
+    tokio::task::yield_now().await;
+    assert!(!settled.is_finished());
+
+    in_flight.resolve(3).await;
+    tokio::time::timeout(Duration::from_secs(1), settled)
+        .await
+        .expect("requests 2 and 4 are not waited on")
+        .expect("the wait does not panic");
+
+    let taken: Vec<u64> = in_flight
+        .take_before(Api::Market, 4)
+        .await
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+
+    assert_eq!(taken, vec![2]);
+    assert_eq!(in_flight.ids().await, vec![4]);
+}
+
+#[tokio::test]
+async fn unanswered_request_times_out() -> anyhow::Result<()> {
+    let in_flight = Arc::new(InFlight::default());
+    let (data_tx, mut data_rx) = tokio::sync::broadcast::channel(4);
+
+    in_flight
+        .insert(7, Api::User, Arc::new(GetInstruments.into()))
+        .await;
+    tokio::spawn(expire_requests(
+        Arc::downgrade(&in_flight),
+        data_tx,
+        Duration::from_millis(20),
+    ));
+
+    let data = data_rx.recv().await?;
+
+    assert!(matches!(
+        data.result,
+        Some(WebsocketData::RequestTimeout {
+            id: 7,
+            method: "public/get-instruments"
+        })
+    ));
+    assert!(in_flight.ids().await.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn pending_withdrawal_is_not_replayed() -> anyhow::Result<()> {
+    let in_flight = InFlight::default();
+    let (data_tx, mut data_rx) = tokio::sync::broadcast::channel(4);
+
+    let withdrawal = CreateWithdrawal {
+        client_wid: Some("rent".to_owned()),
+        currency: "BTC".to_owned(),
+        amount: 0.5,
+        address: "bc1qexample".to_owned(),
+        address_tag: None,
+        network_id: None,
+    };
+    in_flight
+        .insert(1, Api::User, Arc::new(withdrawal.into()))
+        .await;
+    in_flight
+        .insert(2, Api::User, Arc::new(GetInstruments.into()))
+        .await;
+
+    let replayed: Vec<u64> = replayable(&data_tx, in_flight.take(Api::User).await)
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+
+    assert_eq!(replayed, vec![2]);
+
+    let data = data_rx.recv().await?;
+
+    assert!(matches!(
+        data.result,
+        Some(WebsocketData::RequestOutcomeUnknown {
+            id: 1,
+            method: "private/create-withdrawal",
+            client_id: Some(ref client_wid),
+        }) if client_wid == "rent"
+    ));
+    assert!(data_rx.try_recv().is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn queue_refuses_sandbox_withdrawal() -> anyhow::Result<()> {
+    let (url, mut frames_rx) = common::mock_websocket().await;
+    let controller = ControllerBuilder::new()
+        .with_environment(Environment::UatSandbox)
+        .with_user_websocket(url)
+        .await?
+        .build();
+    let queue = controller.user_queue();
+
+    let withdrawal = CreateWithdrawal {
+        client_wid: None,
+        currency: "BTC".to_owned(),
+        amount: 0.5,
+        address: "bc1qexample".to_owned(),
+        address_tag: None,
+        network_id: None,
+    };
+
+    assert!(matches!(
+        queue.push(withdrawal).await,
+        Err(ApiError::WithdrawalRefused(Environment::UatSandbox))
+    ));
+
+    // The next frame on the socket is the request pushed after the refused withdrawal.
+    queue.push(GetInstruments).await?;
+    let frame = tokio::time::timeout(Duration::from_secs(5), frames_rx.recv()).await?;
+
+    assert!(frame.is_some_and(|frame| frame.contains("public/get-instruments")));
+
+    Ok(())
+}
@@ -0,0 +1,223 @@
+#![cfg(feature = "websocket")]
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+use tokio_tungstenite::tungstenite::Message;
+
+use crypto_com_api::api_response::ApiResponse;
+use crypto_com_api::utils::in_flight::InFlight;
+use crypto_com_api::websocket::handler::HandlerRegistry;
+use crypto_com_api::websocket::market_api::{decode_subscription, process_market};
+use crypto_com_api::websocket::sequence::{
+    SequenceChecker, SequenceGap, SequenceHandler, SequencePolicy,
+};
+use crypto_com_api::websocket::WebsocketData;
+
+const BOOK: &str = "book.BTCUSD-PERP.10";
+
+fn snapshot(u: u64) -> String {
+    format!(
+        r#"{{
+            "id": -1, "method": "subscribe", "code": 0,
+            "result": {{
+                "instrument_name": "BTCUSD-PERP", "subscription": "{BOOK}",
+                "channel": "book", "depth": 10,
+                "data": [{{"bids": [], "asks": [], "tt": 1, "t": 1, "u": {u}, "cs": 0}}]
+            }}
+        }}"#
+    )
+}
+
+fn update(pu: u64, u: u64) -> String {
+    format!(
+        r#"{{
+            "id": -1, "method": "subscribe", "code": 0,
+            "result": {{
+                "instrument_name": "BTCUSD-PERP", "subscription": "{BOOK}",
+                "channel": "book.update", "depth": 10,
+                "data": [{{
+                    "update": {{"bids": [], "asks": []}},
+                    "tt": 2, "t": 2, "u": {u}, "pu": {pu}, "cs": 0
+                }}]
+            }}
+        }}"#
+    )
+}
+
+fn trades(ids: &[u64]) -> String {
+    let data = ids
+        .iter()
+        .map(|id| {
+            format!(
+                r#"{{"s": "BUY", "p": "1", "q": "1", "t": 1, "d": "{id}", "i": "BTCUSD-PERP"}}"#
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+
+    format!(
+        r#"{{
+            "id": -1, "method": "subscribe", "code": 0,
+            "result": {{
+                "instrument_name": "BTCUSD-PERP", "subscription": "trade.BTCUSD-PERP",
+                "channel": "trade", "data": [{data}]
+            }}
+        }}"#
+    )
+}
+
+fn decode(frame: &str) -> anyhow::Result<WebsocketData> {
+    let msg: ApiResponse<serde_json::Value> = serde_json::from_str(frame)?;
+
+    decode_subscription(&msg, 0)?.ok_or_else(|| anyhow::anyhow!("no data"))
+}
+
+#[test]
+fn replayed_book_messages_are_dropped() -> anyhow::Result<()> {
+    let mut checker = SequenceChecker::new().with_policy(BOOK, SequencePolicy::DropDuplicates);
+
+    assert!(!checker.check(&mut decode(&snapshot(100))?).drop);
+    assert!(!checker.check(&mut decode(&update(100, 101))?).drop);
+
+    // Replayed after a reconnect.
+    assert!(checker.check(&mut decode(&snapshot(100))?).drop);
+    assert!(checker.check(&mut decode(&update(100, 101))?).drop);
+
+    // A skipped update is passed on without a gap.
+    let check = checker.check(&mut decode(&update(105, 106))?);
+    assert_eq!(check.gap, None);
+    assert!(!check.drop);
+    assert_eq!(checker.last_sequence(BOOK), Some(106));
+
+    Ok(())
+}
+
+#[test]
+fn skipped_book_update_is_reported() -> anyhow::Result<()> {
+    let mut checker = SequenceChecker::new().with_default_policy(SequencePolicy::DropAndReport);
+
+    checker.check(&mut decode(&snapshot(100))?);
+
+    let check = checker.check(&mut decode(&update(103, 104))?);
+    assert_eq!(
+        check.gap,
+        Some(SequenceGap {
+            subscription: Arc::from(BOOK),
+            last: 100,
+            found: 103,
+        })
+    );
+    assert!(!check.drop);
+
+    // Duplicates are dropped without a gap.
+    let check = checker.check(&mut decode(&update(103, 104))?);
+    assert_eq!(check.gap, None);
+    assert!(check.drop);
+
+    Ok(())
+}
+
+#[test]
+fn duplicate_trades_are_removed() -> anyhow::Result<()> {
+    let mut checker = SequenceChecker::new().with_default_policy(SequencePolicy::DropDuplicates);
+
+    checker.check(&mut decode(&trades(&[3, 2, 1]))?);
+
+    let mut data = decode(&trades(&[5, 4, 3, 2]))?;
+    let check = checker.check(&mut data);
+    assert!(!check.drop);
+
+    let WebsocketData::Trade(trades_res) = data else {
+        anyhow::bail!("expected trades");
+    };
+    let ids: Vec<_> = trades_res
+        .data
+        .iter()
+        .map(|trade| trade.d.as_str())
+        .collect();
+    assert_eq!(ids, ["5", "4"]);
+
+    assert!(checker.check(&mut decode(&trades(&[4]))?).drop);
+
+    Ok(())
+}
+
+#[test]
+fn report_gaps_passes_duplicates_on() -> anyhow::Result<()> {
+    let mut checker =
+        SequenceChecker::new().with_policy("trade.BTCUSD-PERP", SequencePolicy::ReportGaps);
+
+    checker.check(&mut decode(&trades(&[2, 1]))?);
+
+    let mut data = decode(&trades(&[2]))?;
+    let check = checker.check(&mut data);
+    assert!(!check.drop);
+    assert_eq!(check.gap.map(|gap| (gap.last, gap.found)), Some((2, 2)));
+    assert!(matches!(data, WebsocketData::Trade(ref trades) if trades.data.len() == 1));
+
+    // Subscriptions without a policy are not checked.
+    assert_eq!(
+        checker.check(&mut decode(&snapshot(1))?),
+        Default::default()
+    );
+    assert_eq!(checker.last_sequence(BOOK), None);
+
+    Ok(())
+}
+
+async fn process(frame: &str, handlers: &HandlerRegistry) -> anyhow::Result<Vec<WebsocketData>> {
+    let (market_tx, _market_rx) = futures_channel::mpsc::unbounded();
+    let (data_tx, mut data_rx) = tokio::sync::broadcast::channel(8);
+
+    process_market(
+        Message::Text(frame.to_owned()),
+        Arc::new(Mutex::new(market_tx)),
+        &data_tx,
+        &InFlight::default(),
+        None,
+        handlers,
+    )
+    .await?;
+
+    let mut sent = vec![];
+
+    while let Ok(data) = data_rx.try_recv() {
+        let data = Arc::try_unwrap(data).map_err(|_| anyhow::anyhow!("data is shared"))?;
+        sent.extend(data.result);
+    }
+
+    Ok(sent)
+}
+
+#[tokio::test]
+async fn handler_sends_gap_before_data() -> anyhow::Result<()> {
+    let handler = SequenceHandler::new(
+        SequenceChecker::new().with_policy(BOOK, SequencePolicy::DropAndReport),
+    );
+    let checker = handler.checker();
+    let handlers = HandlerRegistry::new().with_handler("subscribe", handler);
+
+    let sent = process(&snapshot(100), &handlers).await?;
+    assert!(matches!(sent[..], [WebsocketData::Book(_)]));
+
+    let sent = process(&update(101, 102), &handlers).await?;
+    assert!(matches!(
+        sent[..],
+        [
+            WebsocketData::SequenceGap(SequenceGap {
+                last: 100,
+                found: 101,
+                ..
+            }),
+            WebsocketData::BookUpdate(_)
+        ]
+    ));
+
+    assert!(process(&update(101, 102), &handlers).await?.is_empty());
+
+    checker.lock().expect("checker lock").reset(BOOK);
+    assert_eq!(process(&update(101, 102), &handlers).await?.len(), 1);
+
+    Ok(())
+}
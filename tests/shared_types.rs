@@ -0,0 +1,73 @@
+#![cfg(feature = "websocket")]
+
+use crypto_com_api::analytics::order_book::OrderBook;
+use crypto_com_api::api_response::ApiResponse;
+use crypto_com_api::rest::data::{BookRes, RawBookRes, RawTickerRes, TickerRes};
+use crypto_com_api::types::Ticker;
+use crypto_com_api::websocket::{market_api::decode_subscription, WebsocketData};
+
+/// Consumers take the shared type whichever API the data came from.
+fn best_bid(ticker: &Ticker) -> Option<f64> {
+    ticker.b
+}
+
+#[test]
+fn ticker_is_shared() -> anyhow::Result<()> {
+    let raw: RawTickerRes = serde_json::from_str(
+        r#"{"data": [{
+            "h": "51790.00", "l": "47895.50", "a": "51174.500", "i": "BTC_USDT",
+            "v": "879.5024", "vv": "26370000.12", "oi": null, "c": "0.03955106",
+            "b": "51170.000", "k": "51180.000", "t": 1613580710768
+        }]}"#,
+    )?;
+    let rest = TickerRes::try_from(raw)?;
+
+    let msg: ApiResponse<serde_json::Value> = serde_json::from_str(
+        r#"{
+            "id": -1, "method": "subscribe", "code": 0,
+            "result": {
+                "instrument_name": "BTC_USDT", "subscription": "ticker.BTC_USDT",
+                "channel": "ticker",
+                "data": [{
+                    "h": "51790.00", "l": "47895.50", "a": "51174.500", "i": "BTC_USDT",
+                    "v": "879.5024", "vv": "26370000.12", "oi": "0", "c": "0.03955106",
+                    "b": "51170.000", "bs": "0.1000", "k": "51180.000", "ks": "0.2000",
+                    "t": 1613580710768
+                }]
+            }
+        }"#,
+    )?;
+    let Some(WebsocketData::Ticker(ws)) = decode_subscription(&msg, 0)? else {
+        panic!("expected ticker data");
+    };
+
+    assert_eq!(best_bid(&rest.data[0]), best_bid(&ws.data[0]));
+    assert_eq!(rest.data[0].i, ws.data[0].i);
+    assert_eq!(rest.data[0].bs, None);
+    assert_eq!(ws.data[0].bs, Some(0.1));
+
+    Ok(())
+}
+
+#[test]
+fn rest_book_seeds_order_book() -> anyhow::Result<()> {
+    let raw: RawBookRes = serde_json::from_str(
+        r#"{
+            "instrument_name": "BTC_USDT", "depth": 2,
+            "data": [{
+                "bids": [["100.0", "1.0", "2"], ["99.0", "3.0", "1"]],
+                "asks": [["101.0", "0.5", "1"]],
+                "t": 1613580710768
+            }]
+        }"#,
+    )?;
+    let rest = BookRes::try_from(&raw)?;
+
+    let mut book = OrderBook::new("BTC_USDT");
+    book.apply_snapshot(&rest.data[0]);
+
+    assert_eq!(book.best_bid(), Some((100.0, 1.0, 2)));
+    assert_eq!(book.sequence, None);
+
+    Ok(())
+}
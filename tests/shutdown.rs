@@ -0,0 +1,114 @@
+#![cfg(feature = "websocket")]
+
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_channel::mpsc::UnboundedReceiver;
+use futures_util::StreamExt;
+use tokio::sync::Mutex;
+
+use crypto_com_api::api_response::ApiResponse;
+use crypto_com_api::controller::{Controller, ControllerBuilder, NoMarketWs, NoUserWs};
+use crypto_com_api::shutdown::{PolicyOutcome, ShutdownContext, ShutdownHook, Signal};
+use crypto_com_api::utils::action::{Action, ActionStore};
+use crypto_com_api::websocket::auth_state::AuthStatus;
+
+#[tokio::test]
+async fn policy_runs_before_the_controller_shuts_down() -> anyhow::Result<()> {
+    let mut controller = ControllerBuilder::new().build();
+
+    let report = ShutdownHook::new(|context: ShutdownContext| async move {
+        assert!(context.user_queue.is_none());
+        assert!(context.market_queue.is_none());
+
+        anyhow::bail!("nothing to cancel")
+    })
+    .with_drain_timeout(Duration::from_millis(10))
+    .run(&mut controller, None)
+    .await?;
+
+    assert!(matches!(report.policy, PolicyOutcome::Failed(_)));
+    assert!(report.unanswered.is_empty());
+    assert!(controller.shutting_down.load(Ordering::SeqCst));
+
+    Ok(())
+}
+
+/// A controller whose user actions go to the returned receiver, authorized.
+fn user_controller() -> (
+    Controller<NoUserWs, NoMarketWs>,
+    UnboundedReceiver<ActionStore>,
+) {
+    let (actions_tx, actions_rx) = futures_channel::mpsc::unbounded();
+    let mut builder = ControllerBuilder::new();
+    builder.user_actions_tx = Some(Arc::new(Mutex::new(actions_tx)));
+    let controller = builder.build();
+    controller.user_auth.set(AuthStatus::Authenticated);
+
+    (controller, actions_rx)
+}
+
+#[tokio::test]
+async fn orders_are_cancelled_before_closing() -> anyhow::Result<()> {
+    let (mut controller, mut actions_rx) = user_controller();
+    let data_tx = controller.data_tx.clone();
+
+    // Answers every request with code 0.
+    let exchange = tokio::spawn(async move {
+        let mut methods = vec![];
+
+        while let Some(sent) = actions_rx.next().await {
+            methods.push(sent.action.method());
+            let _ = data_tx.send(Arc::new(ApiResponse {
+                id: i64::try_from(sent.id).expect("an ID"),
+                code: Some(0),
+                ..ApiResponse::default()
+            }));
+        }
+
+        methods
+    });
+
+    let report =
+        ShutdownHook::cancel_all_orders(vec!["BTC_USDT".to_owned(), "ETH_USDT".to_owned()])
+            .with_drain_timeout(Duration::from_millis(10))
+            .run(&mut controller, Some(Signal::Terminate))
+            .await?;
+
+    assert!(matches!(report.policy, PolicyOutcome::Completed));
+    assert_eq!(report.signal, Some(Signal::Terminate));
+
+    // Dropping the controller closes the actions channel.
+    drop(controller);
+    assert_eq!(
+        exchange.await?,
+        [
+            "private/set-cancel-on-disconnect",
+            "private/cancel-all-orders",
+            "private/cancel-all-orders",
+        ]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn a_hung_policy_is_cut_off() -> anyhow::Result<()> {
+    let (mut controller, mut actions_rx) = user_controller();
+
+    // Nothing answers, so the policy waits for the first reply until it times out.
+    let report = ShutdownHook::cancel_all_orders(vec!["BTC_USDT".to_owned()])
+        .with_policy_timeout(Duration::from_millis(50))
+        .with_drain_timeout(Duration::from_millis(10))
+        .run(&mut controller, None)
+        .await?;
+
+    assert!(matches!(report.policy, PolicyOutcome::TimedOut));
+
+    let sent = actions_rx.next().await.expect("an action");
+    assert_eq!(sent.action.method(), "private/set-cancel-on-disconnect");
+    assert_eq!(report.unanswered, [sent.id]);
+
+    Ok(())
+}
@@ -0,0 +1,250 @@
+#![cfg(feature = "websocket")]
+
+mod common;
+
+use std::time::Duration;
+
+use crypto_com_api::api_response::ApiResponse;
+use crypto_com_api::controller::ControllerBuilder;
+use crypto_com_api::error::OrderError;
+use crypto_com_api::simulator::SimulatedExchange;
+use crypto_com_api::types::{ExecInst, Quantity, Side};
+use crypto_com_api::websocket::actions::spot_trading_api::{CancelOrder, CreateOrder};
+use crypto_com_api::websocket::actions::GetInstruments;
+use crypto_com_api::websocket::data::OrderItem;
+use crypto_com_api::websocket::{market_api::decode_subscription, WebsocketData};
+
+fn decode(frame: &str) -> anyhow::Result<WebsocketData> {
+    let msg: ApiResponse<serde_json::Value> = serde_json::from_str(frame)?;
+
+    decode_subscription(&msg, 0)?.ok_or_else(|| anyhow::anyhow!("no data"))
+}
+
+const SNAPSHOT: &str = r#"{
+    "id": -1, "method": "subscribe", "code": 0,
+    "result": {
+        "instrument_name": "BTCUSD-PERP", "subscription": "book.BTCUSD-PERP.10",
+        "channel": "book", "depth": 10,
+        "data": [{
+            "bids": [["30000.0", "1.0", "2"], ["29999.5", "2.0", "1"]],
+            "asks": [["30000.5", "0.5", "1"], ["30001.0", "3.0", "4"]],
+            "tt": 1, "t": 1, "u": 100, "cs": 0
+        }]
+    }
+}"#;
+
+const ASKS_DROP: &str = r#"{
+    "id": -1, "method": "subscribe", "code": 0,
+    "result": {
+        "instrument_name": "BTCUSD-PERP", "subscription": "book.BTCUSD-PERP.10",
+        "channel": "book.update", "depth": 10,
+        "data": [{
+            "update": { "bids": [], "asks": [["29999.0", "2.0", "1"]] },
+            "tt": 2, "t": 2, "u": 101, "pu": 100, "cs": 0
+        }]
+    }
+}"#;
+
+fn last_order(events: &[WebsocketData]) -> Option<&OrderItem> {
+    events.iter().rev().find_map(|event| match *event {
+        WebsocketData::UserOrder(ref orders) => orders.data.last(),
+        _ => None,
+    })
+}
+
+fn fills(events: &[WebsocketData]) -> Vec<(f64, f64)> {
+    events
+        .iter()
+        .filter_map(|event| match *event {
+            WebsocketData::UserTrade(ref trades) => Some(&trades.data),
+            _ => None,
+        })
+        .flatten()
+        .map(|trade| (trade.traded_price, trade.traded_quantity))
+        .collect()
+}
+
+#[test]
+fn market_order_walks_the_book() -> anyhow::Result<()> {
+    let mut exchange = SimulatedExchange::new().with_fees(0.0, 0.001, "USD");
+    exchange.process(&decode(SNAPSHOT)?);
+
+    let (order_id, events) =
+        exchange.create_order(&CreateOrder::market_buy("BTCUSD-PERP", Quantity(1.0)));
+
+    assert_eq!(fills(&events), vec![(30000.5, 0.5), (30001.0, 0.5)]);
+
+    let item = last_order(&events).expect("order update");
+    assert_eq!(item.status, "FILLED");
+    assert_eq!(item.order_id, order_id.to_string());
+    assert!((item.avg_price - 30000.75).abs() < 1e-9);
+
+    let WebsocketData::UserTrade(ref trades) = events[0] else {
+        anyhow::bail!("expected trades first");
+    };
+    assert!((trades.data[0].fee - 30000.5 * 0.5 * 0.001).abs() < 1e-9);
+
+    Ok(())
+}
+
+#[test]
+fn limit_order_rests_until_the_book_crosses() -> anyhow::Result<()> {
+    let mut exchange = SimulatedExchange::new();
+    exchange.process(&decode(SNAPSHOT)?);
+
+    let (_, events) = exchange.create_order(&CreateOrder::limit(
+        "BTCUSD-PERP",
+        Side::Buy,
+        29999.0,
+        Quantity(1.0),
+    ));
+
+    assert!(fills(&events).is_empty());
+    assert_eq!(
+        last_order(&events).map(|item| item.status.as_str()),
+        Some("ACTIVE")
+    );
+    assert_eq!(exchange.open_orders.len(), 1);
+
+    let events = exchange.process(&decode(ASKS_DROP)?);
+
+    assert_eq!(fills(&events), vec![(29999.0, 1.0)]);
+    assert_eq!(
+        last_order(&events).map(|item| item.status.as_str()),
+        Some("FILLED")
+    );
+    assert!(exchange.open_orders.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn time_in_force_and_post_only() -> anyhow::Result<()> {
+    let mut exchange = SimulatedExchange::new();
+    exchange.process(&decode(SNAPSHOT)?);
+
+    let fok = CreateOrder::limit("BTCUSD-PERP", Side::Buy, 30000.5, Quantity(1.0))
+        .with_time_in_force("FILL_OR_KILL");
+    let (_, events) = exchange.create_order(&fok);
+    assert!(fills(&events).is_empty());
+    assert_eq!(
+        last_order(&events).map(|item| item.status.as_str()),
+        Some("CANCELED")
+    );
+
+    let ioc = CreateOrder::limit("BTCUSD-PERP", Side::Buy, 30000.5, Quantity(1.0))
+        .with_time_in_force("IMMEDIATE_OR_CANCEL");
+    let (_, events) = exchange.create_order(&ioc);
+    assert_eq!(fills(&events), vec![(30000.5, 0.5)]);
+    assert_eq!(
+        last_order(&events).map(|item| item.status.as_str()),
+        Some("CANCELED")
+    );
+
+    let post_only = CreateOrder::limit("BTCUSD-PERP", Side::Sell, 29999.5, Quantity(1.0))
+        .with_exec_inst(ExecInst::PostOnly);
+    let (_, events) = exchange.create_order(&post_only);
+    assert_eq!(
+        last_order(&events).map(|item| item.status.as_str()),
+        Some("REJECTED")
+    );
+
+    assert!(exchange.open_orders.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn cancel_removes_resting_orders() -> anyhow::Result<()> {
+    let mut exchange = SimulatedExchange::new();
+    exchange.process(&decode(SNAPSHOT)?);
+
+    let (order_id, _) = exchange.create_order(&CreateOrder::limit(
+        "BTCUSD-PERP",
+        Side::Sell,
+        30010.0,
+        Quantity(2.0),
+    ));
+    let cancel = CancelOrder {
+        instrument_name: "BTCUSD-PERP".to_owned(),
+        order_id: order_id.to_string(),
+        client_oid: None,
+    };
+
+    let events = exchange.cancel_order(&cancel)?;
+    assert_eq!(
+        last_order(&events).map(|item| item.status.as_str()),
+        Some("CANCELED")
+    );
+    assert!(exchange.open_orders.is_empty());
+
+    assert!(matches!(
+        exchange.cancel_order(&cancel),
+        Err(OrderError::UnknownOrder(ref id)) if *id == order_id.to_string()
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn orders_without_a_book_are_rejected() {
+    let mut exchange = SimulatedExchange::new();
+
+    let (_, events) = exchange.create_order(&CreateOrder::market_buy("BTCUSD-PERP", Quantity(1.0)));
+
+    let item = last_order(&events).expect("order update");
+    assert_eq!(item.status, "REJECTED");
+    assert_eq!(item.reason.as_deref(), Some("NO_BOOK"));
+}
+
+#[tokio::test]
+async fn queue_orders_are_simulated_in_dry_run() -> anyhow::Result<()> {
+    let (url, mut frames_rx) = common::mock_websocket().await;
+    let mut controller = ControllerBuilder::new()
+        .with_user_websocket(url)
+        .await?
+        .build();
+    // Handed out before dry run mode starts.
+    let queue = controller.user_queue();
+    let _simulator = controller.dry_run(SimulatedExchange::new());
+
+    let reply = queue
+        .request(CreateOrder::market_buy("BTCUSD-PERP", Quantity(1.0)))
+        .await?;
+
+    assert!(matches!(reply.result, Some(WebsocketData::CreateOrder(_))));
+
+    // The next frame on the socket is the request pushed after the simulated order.
+    queue.push(GetInstruments).await?;
+    let frame = tokio::time::timeout(Duration::from_secs(5), frames_rx.recv()).await?;
+
+    assert!(frame.is_some_and(|frame| frame.contains("public/get-instruments")));
+
+    Ok(())
+}
+
+#[test]
+fn cancel_all_removes_the_instrument_orders() -> anyhow::Result<()> {
+    let mut exchange = SimulatedExchange::new();
+    exchange.process(&decode(SNAPSHOT)?);
+
+    for instrument_name in ["BTCUSD-PERP", "ETHUSD-PERP"] {
+        exchange.create_order(&CreateOrder::limit(
+            instrument_name,
+            Side::Buy,
+            29000.0,
+            Quantity(1.0),
+        ));
+    }
+
+    let events = exchange.cancel_all_orders("BTCUSD-PERP");
+
+    assert_eq!(
+        last_order(&events).map(|item| item.status.as_str()),
+        Some("CANCELED")
+    );
+    assert_eq!(exchange.open_orders.len(), 1);
+    assert_eq!(exchange.open_orders[0].instrument_name, "ETHUSD-PERP");
+
+    Ok(())
+}
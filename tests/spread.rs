@@ -0,0 +1,99 @@
+#![cfg(feature = "websocket")]
+
+use std::time::Duration;
+
+use crypto_com_api::analytics::spread::{Smoothing, SpreadEvent, SpreadTracker};
+use crypto_com_api::controller::ControllerBuilder;
+use crypto_com_api::testing::decode_frame;
+use crypto_com_api::utils::in_flight::Api;
+
+/// Feed `tracker` a ticker of `instrument_name` with best bid and ask `bid` and `ask`, recieved
+/// at `at`.
+fn ticker(
+    tracker: &mut SpreadTracker,
+    instrument_name: &str,
+    at: u64,
+    bid: f64,
+    ask: f64,
+) -> Vec<SpreadEvent> {
+    let frame = format!(
+        r#"{{"id":-1,"method":"subscribe","code":0,"result":{{"instrument_name":"{instrument_name}","subscription":"ticker.{instrument_name}","channel":"ticker","data":[{{"i":"{instrument_name}","v":"1","vv":"1","b":"{bid}","k":"{ask}","t":{at}}}]}}}}"#
+    );
+
+    decode_frame(Api::Market, &frame, at)
+        .expect("a ticker frame")
+        .iter()
+        .filter_map(|data| data.result.as_ref())
+        .flat_map(|data| tracker.process(data))
+        .collect()
+}
+
+#[test]
+fn quotes_are_aligned_against_the_base() {
+    let mut tracker = SpreadTracker::new("BTC_USDT", "BTCUSD-PERP")
+        .with_instrument("BTC_USD")
+        .with_max_skew(Duration::from_millis(500));
+
+    // Nothing to compare until both sides of a pair are quoted.
+    assert!(ticker(&mut tracker, "BTCUSD-PERP", 0, 101.0, 103.0).is_empty());
+    assert!(ticker(&mut tracker, "ETH_USDT", 0, 1.0, 2.0).is_empty());
+
+    let events = ticker(&mut tracker, "BTC_USDT", 100, 99.0, 101.0);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].other.instrument_name, "BTCUSD-PERP");
+    assert_eq!(events[0].spread, 2.0);
+    assert_eq!(events[0].basis, 2.0);
+    assert_eq!(events[0].sell_other, 0.0);
+    assert_eq!(events[0].buy_other, -4.0);
+    assert_eq!(events[0].received_at, 100);
+
+    // A quote of the base is compared to every other instrument.
+    assert_eq!(
+        ticker(&mut tracker, "BTC_USD", 200, 98.0, 100.0)[0].spread,
+        -1.0
+    );
+    assert_eq!(ticker(&mut tracker, "BTC_USDT", 300, 99.0, 101.0).len(), 2);
+
+    // The perpetual quote from 0 is now too old to compare.
+    let events = ticker(&mut tracker, "BTC_USDT", 600, 99.0, 101.0);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].other.instrument_name, "BTC_USD");
+}
+
+#[test]
+fn spreads_are_smoothed() {
+    let mut tracker =
+        SpreadTracker::new("BTC_USDT", "BTCUSD-PERP").with_smoothing(Smoothing::Ema(0.5));
+
+    ticker(&mut tracker, "BTC_USDT", 0, 99.0, 101.0);
+    let first = ticker(&mut tracker, "BTCUSD-PERP", 1, 103.0, 105.0);
+    assert_eq!(first[0].smoothed_spread, 4.0);
+
+    let second = ticker(&mut tracker, "BTCUSD-PERP", 2, 101.0, 103.0);
+    assert_eq!(second[0].spread, 2.0);
+    assert_eq!(second[0].smoothed_spread, 3.0);
+    assert_eq!(second[0].smoothed_basis, 3.0);
+}
+
+#[tokio::test]
+async fn the_stream_reads_the_data_broadcast() -> anyhow::Result<()> {
+    let mut controller = ControllerBuilder::new().build();
+    let mut stream = controller.watch_spread(SpreadTracker::new("BTC_USDT", "BTCUSD-PERP"));
+
+    for (instrument_name, bid, ask) in [("BTC_USDT", 99.0, 101.0), ("BTCUSD-PERP", 100.0, 102.0)] {
+        let frame = format!(
+            r#"{{"id":-1,"method":"subscribe","code":0,"result":{{"instrument_name":"{instrument_name}","subscription":"ticker.{instrument_name}","channel":"ticker","data":[{{"i":"{instrument_name}","v":"1","vv":"1","b":"{bid}","k":"{ask}","t":1}}]}}}}"#
+        );
+
+        for data in decode_frame(Api::Market, &frame, 1)? {
+            controller.data_tx.send(data)?;
+        }
+    }
+
+    let event = stream.next().await.expect("a spread");
+    assert_eq!(event.base.instrument_name, "BTC_USDT");
+    assert_eq!(event.spread, 1.0);
+    assert!(stream.tracker().quote("BTCUSD-PERP").is_some());
+
+    Ok(())
+}
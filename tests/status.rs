@@ -0,0 +1,20 @@
+use crypto_com_api::types::{DepositStatus, WithdrawalStatus};
+
+#[test]
+fn status_codes_round_trip() -> anyhow::Result<()> {
+    assert_eq!(
+        serde_json::from_str::<WithdrawalStatus>(r#""5""#)?,
+        WithdrawalStatus::Completed
+    );
+    assert_eq!(
+        serde_json::from_str::<DepositStatus>("1")?,
+        DepositStatus::Arrived
+    );
+    assert_eq!(
+        serde_json::to_string(&WithdrawalStatus::Cancelled)?,
+        r#""6""#
+    );
+    assert!(serde_json::from_str::<DepositStatus>(r#""4""#).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,127 @@
+#![cfg(feature = "websocket")]
+
+use anyhow::Result;
+use crypto_com_api::analytics::OrderBook;
+use crypto_com_api::api_response::ApiResponse;
+use crypto_com_api::strategy::{OrderGateway, OrderRequest, Strategy, StrategyRunner};
+use crypto_com_api::types::{ExecInst, Quantity, Side};
+use crypto_com_api::websocket::actions::spot_trading_api::CreateOrder;
+use crypto_com_api::websocket::channel::Channel;
+use crypto_com_api::websocket::data::Ticker;
+use crypto_com_api::websocket::{market_api::decode_subscription, WebsocketData};
+
+fn decode(frame: &str) -> Result<WebsocketData> {
+    let msg: ApiResponse<serde_json::Value> = serde_json::from_str(frame)?;
+
+    decode_subscription(&msg, 0)?.ok_or_else(|| anyhow::anyhow!("no data"))
+}
+
+const SNAPSHOT: &str = r#"{
+    "id": -1, "method": "subscribe", "code": 0,
+    "result": {
+        "instrument_name": "BTCUSD-PERP", "subscription": "book.BTCUSD-PERP.10",
+        "channel": "book", "depth": 10,
+        "data": [{
+            "bids": [["30000.0", "1.0", "2"]],
+            "asks": [["30000.5", "0.5", "1"]],
+            "tt": 1, "t": 1, "u": 100, "cs": 0
+        }]
+    }
+}"#;
+
+const GAPPED_UPDATE: &str = r#"{
+    "id": -1, "method": "subscribe", "code": 0,
+    "result": {
+        "instrument_name": "BTCUSD-PERP", "subscription": "book.BTCUSD-PERP.10",
+        "channel": "book.update", "depth": 10,
+        "data": [{
+            "update": { "bids": [["30000.2", "1.5", "1"]], "asks": [] },
+            "tt": 2, "t": 2, "u": 105, "pu": 104, "cs": 0
+        }]
+    }
+}"#;
+
+/// Bids one tick under the best bid on every book it sees.
+#[derive(Default)]
+struct Joiner {
+    books: usize,
+    ticks: usize,
+}
+
+impl Strategy for Joiner {
+    fn channels(&self) -> Vec<Channel> {
+        vec![Channel::Book {
+            instrument_name: "BTCUSD-PERP".to_owned(),
+            depth: Some(10),
+        }]
+    }
+
+    fn on_tick(&mut self, _ticker: &Ticker, _gateway: &mut OrderGateway) -> Result<()> {
+        self.ticks += 1;
+
+        Ok(())
+    }
+
+    fn on_book(&mut self, book: &OrderBook, gateway: &mut OrderGateway) -> Result<()> {
+        self.books += 1;
+
+        if let Some((price, _, _)) = book.best_bid() {
+            gateway.create_order(
+                CreateOrder::limit(
+                    book.instrument_name.clone(),
+                    Side::Buy,
+                    price - 0.1,
+                    Quantity(0.01),
+                )
+                .with_exec_inst(ExecInst::PostOnly),
+            );
+        }
+
+        gateway.stop();
+
+        Ok(())
+    }
+}
+
+#[test]
+fn book_channels_get_a_local_book() {
+    let runner = StrategyRunner::new(Joiner::default());
+
+    assert!(runner.book("BTCUSD-PERP").is_some());
+    assert!(runner.book("ETHUSD-PERP").is_none());
+}
+
+#[test]
+fn book_snapshot_reaches_on_book() -> Result<()> {
+    let mut runner = StrategyRunner::new(Joiner::default());
+    let mut gateway = OrderGateway::new();
+
+    runner.dispatch(&decode(SNAPSHOT)?, &mut gateway)?;
+
+    assert_eq!(runner.strategy().books, 1);
+    assert_eq!(runner.strategy().ticks, 0);
+    assert!(gateway.is_stopped());
+
+    let requests = gateway.take_requests();
+    assert_eq!(requests.len(), 1);
+    match requests[0] {
+        OrderRequest::Create(ref order) => assert_eq!(order.price, Some(29999.9)),
+        OrderRequest::Cancel(_) => panic!("expected a create"),
+    }
+    assert!(gateway.requests().is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn missed_book_update_is_not_passed_on() -> Result<()> {
+    let mut runner = StrategyRunner::new(Joiner::default());
+    let mut gateway = OrderGateway::new();
+
+    runner.dispatch(&decode(SNAPSHOT)?, &mut gateway)?;
+    runner.dispatch(&decode(GAPPED_UPDATE)?, &mut gateway)?;
+
+    assert_eq!(runner.strategy().books, 1);
+
+    Ok(())
+}
@@ -0,0 +1,306 @@
+#![cfg(feature = "websocket")]
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+use tokio_tungstenite::tungstenite::Message;
+
+use crypto_com_api::api_response::ApiResponse;
+use crypto_com_api::prelude::ApiError;
+use crypto_com_api::types::Interval;
+use crypto_com_api::utils::action::Action;
+use crypto_com_api::utils::in_flight::{Api, InFlight};
+use crypto_com_api::websocket::actions::{
+    BookSubscriptionType, Subscribe, SubscribeUserBalance, SubscribeUserOrders,
+    SubscribeUserTrades, Unsubscribe,
+};
+use crypto_com_api::websocket::handler::HandlerRegistry;
+use crypto_com_api::websocket::user_api::process_user;
+use crypto_com_api::websocket::{market_api::process_market, WebsocketData};
+
+#[test]
+fn chunks_keep_book_options() {
+    let subscribe = Subscribe {
+        channels: (0..5).map(|n| format!("ticker.{n}")).collect(),
+        ..Subscribe::default()
+    }
+    .with_book_subscription_type(BookSubscriptionType::SnapshotAndUpdate);
+
+    let chunks = subscribe.chunks(2);
+
+    assert_eq!(
+        chunks
+            .iter()
+            .map(|chunk| chunk.channels.len())
+            .collect::<Vec<_>>(),
+        vec![2, 2, 1]
+    );
+    assert!(chunks.iter().all(|chunk| {
+        chunk.book_subscription_type == Some(BookSubscriptionType::SnapshotAndUpdate)
+    }));
+    assert_eq!(chunks[2].channels, vec!["ticker.4".to_owned()]);
+    assert_eq!(subscribe.chunks(0).len(), 5);
+}
+
+#[test]
+fn candlestick_intervals_are_validated() {
+    let valid = Subscribe {
+        channels: Interval::all()
+            .filter(|interval| interval.has_channel())
+            .map(|interval| format!("candlestick.{interval}.BTCUSD-PERP"))
+            .chain(["book.BTCUSD-PERP.10".to_owned()])
+            .collect(),
+        ..Subscribe::default()
+    };
+    assert!(valid.validate().is_ok());
+
+    let invalid = Subscribe {
+        channels: vec![
+            "ticker.BTCUSD-PERP".to_owned(),
+            "candlestick.M2.BTCUSD-PERP".to_owned(),
+        ],
+        ..Subscribe::default()
+    };
+    assert!(matches!(
+        invalid.validate(),
+        Err(ApiError::InvalidInterval(ref interval)) if interval == "M2"
+    ));
+
+    // The REST names and the REST only six hours are not channels.
+    for interval in ["5m", "1h", "H6"] {
+        let rest = Subscribe {
+            channels: vec![format!("candlestick.{interval}.BTCUSD-PERP")],
+            ..Subscribe::default()
+        };
+        assert!(matches!(
+            rest.validate(),
+            Err(ApiError::InvalidInterval(ref invalid)) if invalid == interval
+        ));
+    }
+}
+
+#[test]
+fn every_interval_is_listed_once() {
+    let intervals: Vec<Interval> = Interval::all().collect();
+
+    assert_eq!(intervals.len(), 12);
+    assert_eq!(intervals.first(), Some(&Interval::OneMinute));
+    assert_eq!(intervals.last(), Some(&Interval::OneMonth));
+    assert!(intervals
+        .windows(2)
+        .all(|pair| pair[0].as_duration() < pair[1].as_duration()));
+    assert!(intervals
+        .iter()
+        .all(|interval| interval.as_websocket_str().parse::<Interval>().ok() == Some(*interval)));
+}
+
+async fn reply(
+    frame: &str,
+    in_flight: &InFlight,
+) -> anyhow::Result<Vec<Arc<ApiResponse<WebsocketData>>>> {
+    let (market_tx, _market_rx) = futures_channel::mpsc::unbounded();
+    let (data_tx, mut data_rx) = tokio::sync::broadcast::channel(8);
+
+    process_market(
+        Message::Text(frame.to_owned()),
+        Arc::new(Mutex::new(market_tx)),
+        &data_tx,
+        in_flight,
+        None,
+        &HandlerRegistry::new(),
+    )
+    .await?;
+
+    let mut sent = vec![];
+
+    while let Ok(data) = data_rx.try_recv() {
+        sent.push(data);
+    }
+
+    Ok(sent)
+}
+
+#[tokio::test]
+async fn confirmation_lists_the_channels() -> anyhow::Result<()> {
+    let in_flight = InFlight::default();
+    let channels = vec![
+        "ticker.BTCUSD-PERP".to_owned(),
+        "trade.BTCUSD-PERP".to_owned(),
+    ];
+    in_flight
+        .insert(
+            3,
+            Api::Market,
+            Arc::new(
+                Subscribe {
+                    channels: channels.clone(),
+                    ..Subscribe::default()
+                }
+                .into(),
+            ),
+        )
+        .await;
+
+    let sent = reply(r#"{"id": 3, "method": "subscribe", "code": 0}"#, &in_flight).await?;
+
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].id, 3);
+    assert!(matches!(
+        sent[0].result,
+        Some(WebsocketData::Subscribed { channels: ref subscribed }) if *subscribed == channels
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn failure_is_sent_for_each_channel() -> anyhow::Result<()> {
+    let in_flight = InFlight::default();
+    in_flight
+        .insert(
+            5,
+            Api::Market,
+            Arc::new(
+                Subscribe {
+                    channels: vec!["ticker.A".to_owned(), "ticker.B".to_owned()],
+                    ..Subscribe::default()
+                }
+                .into(),
+            ),
+        )
+        .await;
+
+    let sent = reply(
+        r#"{"id": 5, "method": "subscribe", "code": 10004, "message": "BAD_REQUEST"}"#,
+        &in_flight,
+    )
+    .await?;
+
+    let failed: Vec<_> = sent
+        .iter()
+        .filter_map(|data| match data.result {
+            Some(WebsocketData::SubscribeError { ref channel, code }) => {
+                Some((channel.clone(), code))
+            }
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(
+        failed,
+        vec![
+            (Some("ticker.A".to_owned()), 10004),
+            (Some("ticker.B".to_owned()), 10004)
+        ]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn failure_of_unknown_request() -> anyhow::Result<()> {
+    let sent = reply(
+        r#"{"id": 3, "method": "subscribe", "code": 10004, "message": "BAD_REQUEST"}"#,
+        &InFlight::default(),
+    )
+    .await?;
+
+    assert_eq!(sent.len(), 1);
+    assert!(matches!(
+        sent[0].result,
+        Some(WebsocketData::SubscribeError {
+            channel: None,
+            code: 10004
+        })
+    ));
+    assert_eq!(sent[0].message.as_deref(), Some("BAD_REQUEST"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn unsubscribe_confirmation_lists_the_channels() -> anyhow::Result<()> {
+    let in_flight = InFlight::default();
+    in_flight
+        .insert(
+            9,
+            Api::Market,
+            Arc::new(
+                Unsubscribe {
+                    channels: vec!["ticker.A".to_owned()],
+                }
+                .into(),
+            ),
+        )
+        .await;
+
+    let sent = reply(
+        r#"{"id": 9, "method": "unsubscribe", "code": 0}"#,
+        &in_flight,
+    )
+    .await?;
+
+    assert_eq!(sent.len(), 1);
+    assert!(matches!(
+        sent[0].result,
+        Some(WebsocketData::Unsubscribed { ref channels }) if *channels == ["ticker.A"]
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn user_subscriptions_name_their_channels() -> anyhow::Result<()> {
+    assert_eq!(SubscribeUserBalance.channel(), "user.balance");
+    assert_eq!(SubscribeUserOrders(None).channel(), "user.order");
+    assert_eq!(
+        SubscribeUserOrders(Some("ETH_CRO".to_owned())).channel(),
+        "user.order.ETH_CRO"
+    );
+    assert_eq!(SubscribeUserTrades(None).channel(), "user.trade");
+    assert_eq!(
+        SubscribeUserTrades(Some("ETH_CRO".to_owned())).channel(),
+        "user.trade.ETH_CRO"
+    );
+
+    let (tx, mut rx) = futures_channel::mpsc::unbounded();
+    SubscribeUserOrders(Some("ETH_CRO".to_owned())).process(&tx, 4)?;
+
+    let Ok(Some(Message::Text(sent))) = rx.try_next() else {
+        anyhow::bail!("expected a subscribe request");
+    };
+    let sent: serde_json::Value = serde_json::from_str(&sent)?;
+    assert_eq!(sent["method"], "subscribe");
+    assert_eq!(sent["params"]["channels"][0], "user.order.ETH_CRO");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn user_subscription_confirmation_lists_the_channel() -> anyhow::Result<()> {
+    let in_flight = InFlight::default();
+    in_flight
+        .insert(2, Api::User, Arc::new(SubscribeUserBalance.into()))
+        .await;
+
+    let (user_tx, _user_rx) = futures_channel::mpsc::unbounded();
+    let (data_tx, mut data_rx) = tokio::sync::broadcast::channel(8);
+
+    process_user(
+        Message::Text(r#"{"id": 2, "method": "subscribe", "code": 0}"#.to_owned()),
+        Arc::new(Mutex::new(user_tx)),
+        &data_tx,
+        &in_flight,
+        None,
+        &HandlerRegistry::new(),
+    )
+    .await?;
+
+    let data = data_rx.try_recv()?;
+    assert!(matches!(
+        data.result,
+        Some(WebsocketData::Subscribed { ref channels }) if *channels == ["user.balance"]
+    ));
+
+    Ok(())
+}
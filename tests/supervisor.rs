@@ -0,0 +1,47 @@
+#![cfg(feature = "websocket")]
+
+use crypto_com_api::supervisor::{Supervisor, Task};
+
+#[tokio::test]
+async fn first_exit_is_recorded() {
+    let supervisor = Supervisor::new();
+
+    supervisor.supervise(
+        Task::MarketStream,
+        tokio::spawn(async { anyhow::bail!("connection reset") }),
+    );
+
+    let exit = supervisor.wait().await;
+    assert_eq!(exit.task, Task::MarketStream);
+    assert!(exit.into_result().is_err());
+
+    supervisor.supervise(Task::UserStream, tokio::spawn(async { Ok(()) }));
+    tokio::task::yield_now().await;
+
+    assert_eq!(
+        supervisor.exit().map(|exit| exit.task),
+        Some(Task::MarketStream)
+    );
+}
+
+#[tokio::test]
+async fn replaced_handle_exit_is_not_recorded() {
+    let supervisor = Supervisor::new();
+    let (old_tx, old_rx) = tokio::sync::oneshot::channel::<()>();
+
+    supervisor.supervise(
+        Task::UserStream,
+        tokio::spawn(async move {
+            let _ = old_rx.await;
+            anyhow::bail!("closed after rotation")
+        }),
+    );
+    supervisor.supervise(Task::UserStream, tokio::spawn(std::future::pending()));
+
+    old_tx.send(()).expect("the old task is waiting");
+    for _ in 0..8 {
+        tokio::task::yield_now().await;
+    }
+
+    assert!(supervisor.exit().is_none());
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "rest")]
+
+use crypto_com_api::api_response::ApiResponse;
+use crypto_com_api::rest::data::{Announcements, Impact, SystemStatus};
+
+const ANNOUNCEMENTS: &str = r#"{
+    "id": 1,
+    "method": "public/get-announcements",
+    "code": 0,
+    "result": {
+        "data": [
+            {
+                "id": "1", "category": "system", "product_type": "Spot,Derivative",
+                "announced_at": 100, "title": "Scheduled maintenance", "content": "",
+                "instrument_name": null,
+                "impacted_params": {"spot_trading_impacted": "ALL"},
+                "start_time": 2000, "end_time": 3000
+            },
+            {
+                "id": "2", "category": "system", "product_type": "Spot",
+                "announced_at": 50, "title": "Past maintenance", "content": "",
+                "instrument_name": null, "impacted_params": {},
+                "start_time": 100, "end_time": 200
+            },
+            {
+                "id": "3", "category": "list", "product_type": "Spot",
+                "announced_at": 60, "title": "New listing", "content": "",
+                "instrument_name": "NEW_USD", "impacted_params": {},
+                "start_time": 900, "end_time": null
+            }
+        ]
+    }
+}"#;
+
+#[test]
+fn pending_maintenance_is_found() -> anyhow::Result<()> {
+    let res: ApiResponse<Announcements> = serde_json::from_str(ANNOUNCEMENTS)?;
+    let announcements = res.result.map(|res| res.data).unwrap_or_default();
+
+    let status = SystemStatus::new(&announcements, 1000);
+
+    assert_eq!(status.maintenance.len(), 1);
+    assert_eq!(
+        status.maintenance[0].impacted_params.spot_trading_impacted,
+        Some(Impact::All)
+    );
+    assert!(!status.is_under_maintenance());
+    assert_eq!(status.next_maintenance(), Some(2000));
+
+    assert!(SystemStatus::new(&announcements, 2500).is_under_maintenance());
+
+    Ok(())
+}
@@ -0,0 +1,144 @@
+#![cfg(feature = "websocket")]
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use crypto_com_api::controller::ControllerBuilder;
+use crypto_com_api::error::{ApiError, RiskViolation};
+use crypto_com_api::risk::{RiskLimits, RiskManager};
+use crypto_com_api::throttle::{OrderQueue, QueuedAction, ThrottleLimits};
+use crypto_com_api::types::{Quantity, Side};
+use crypto_com_api::websocket::actions::spot_trading_api::{CancelOrder, CreateOrder};
+use crypto_com_api::websocket::auth_state::AuthStatus;
+
+fn create(instrument_name: &str, client_oid: &str) -> QueuedAction {
+    QueuedAction::Create(
+        CreateOrder::limit(instrument_name, Side::Buy, 100.0, Quantity(1.0))
+            .with_client_oid(client_oid),
+    )
+}
+
+fn cancel(instrument_name: &str, order_id: &str) -> QueuedAction {
+    QueuedAction::Cancel(CancelOrder {
+        instrument_name: instrument_name.to_owned(),
+        order_id: order_id.to_owned(),
+        client_oid: None,
+    })
+}
+
+fn label(action: &QueuedAction) -> String {
+    match *action {
+        QueuedAction::Create(ref order) => order.client_oid.clone().unwrap_or_default(),
+        QueuedAction::Cancel(ref cancel) => format!("cancel {}", cancel.order_id),
+    }
+}
+
+fn limits(max_actions: usize) -> ThrottleLimits {
+    ThrottleLimits {
+        max_actions,
+        window: Duration::from_millis(100),
+    }
+}
+
+#[test]
+fn actions_are_paced_per_instrument() {
+    let mut queue = OrderQueue::new(limits(2));
+    let start = Instant::now();
+
+    for oid in ["a", "b", "c"] {
+        queue.push(create("BTC_USDT", oid));
+    }
+    queue.push(create("ETH_USDT", "d"));
+
+    let mut sent = vec![];
+    while let Some(action) = queue.pop_ready(start) {
+        sent.push(label(&action));
+    }
+
+    assert_eq!(sent, vec!["a", "b", "d"]);
+    assert_eq!(queue.depth("BTC_USDT"), 1);
+    assert_eq!(
+        queue.next_ready_at(start),
+        Some(start + Duration::from_millis(100))
+    );
+
+    assert!(queue.pop_ready(start + Duration::from_millis(99)).is_none());
+    assert_eq!(
+        queue
+            .pop_ready(start + Duration::from_millis(100))
+            .as_ref()
+            .map(label),
+        Some("c".to_owned())
+    );
+    assert_eq!(queue.next_ready_at(start), None);
+}
+
+#[test]
+fn cancels_jump_ahead_of_orders() {
+    let mut queue = OrderQueue::new(limits(1));
+    let start = Instant::now();
+
+    queue.push(create("ETH_USDT", "a"));
+    queue.push(create("BTC_USDT", "b"));
+    queue.push(create("BTC_USDT", "c"));
+    queue.push(cancel("BTC_USDT", "1"));
+    queue.push(cancel("BTC_USDT", "2"));
+
+    let metrics = queue.metrics();
+    assert_eq!(metrics.queued, 5);
+    assert_eq!(metrics.cancels_queued, 2);
+    assert_eq!(metrics.depth.get("BTC_USDT"), Some(&4));
+    assert_eq!(metrics.depth.get("ETH_USDT"), Some(&1));
+
+    let mut sent = vec![];
+    let mut now = start;
+    while queue.next_ready_at(now).is_some() {
+        now = queue.next_ready_at(now).unwrap_or(now);
+        while let Some(action) = queue.pop_ready(now) {
+            sent.push(label(&action));
+        }
+    }
+
+    assert_eq!(sent, vec!["cancel 1", "a", "cancel 2", "b", "c"]);
+    assert_eq!(queue.metrics().sent, 5);
+    assert!(queue.metrics().depth.is_empty());
+}
+
+#[tokio::test]
+async fn scheduler_reports_each_result_and_keeps_sending() -> anyhow::Result<()> {
+    let (url, mut frames_rx) = common::mock_websocket().await;
+    let mut controller = ControllerBuilder::new()
+        .with_user_websocket(url)
+        .await?
+        .build();
+    controller.user_auth.set(AuthStatus::Authenticated);
+    let _risk = controller.set_risk_manager(RiskManager::new(RiskLimits {
+        max_order_notional: Some(150.0),
+        ..RiskLimits::default()
+    }));
+
+    let scheduler = controller.order_scheduler(limits(2));
+    let rejected = scheduler.create_order(CreateOrder::limit(
+        "BTC_USDT",
+        Side::Buy,
+        100.0,
+        Quantity(2.0),
+    ));
+    let sent = scheduler.push(create("BTC_USDT", "sent"));
+
+    assert!(matches!(
+        rejected.await?,
+        Err(ApiError::RiskViolation(RiskViolation::OrderNotional { .. }))
+    ));
+    let id = sent.await??;
+
+    let frame = tokio::time::timeout(Duration::from_secs(5), frames_rx.recv()).await?;
+
+    assert!(
+        frame.is_some_and(|frame| frame.contains(&format!(r#""id":{id}"#))
+            && frame.contains(r#""client_oid":"sent""#))
+    );
+
+    Ok(())
+}
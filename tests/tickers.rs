@@ -0,0 +1,31 @@
+#![cfg(feature = "rest")]
+
+use crypto_com_api::rest::data::{RawTickerRes, TickerRes, TickersRes};
+
+#[test]
+fn tickers_by_instrument_name() -> anyhow::Result<()> {
+    let raw: RawTickerRes = serde_json::from_str(
+        r#"{
+            "data": [{
+                "h": "51000.00", "l": "49000.00", "a": "50100.00", "i": "BTC_USDT",
+                "v": "10.5", "vv": "525000.00", "oi": "0", "c": "0.01",
+                "b": "50099.50", "k": "50100.50", "t": 1613580710768
+            }, {
+                "h": null, "l": null, "a": null, "i": "CRO_USDT",
+                "v": "0", "vv": "0", "oi": null, "c": null,
+                "b": "0.0750", "k": null, "t": 1613580710768
+            }]
+        }"#,
+    )?;
+
+    let tickers = TickersRes::from(TickerRes::try_from(raw)?);
+
+    assert_eq!(tickers.tickers.len(), 2);
+    assert_eq!(tickers.ticker("BTC_USDT").and_then(|t| t.a), Some(50_100.0));
+    assert_eq!(tickers.best_bid_ask("BTC_USDT"), Some((50_099.5, 50_100.5)));
+    assert_eq!(tickers.mid_price("BTC_USDT"), Some(50_100.0));
+    assert_eq!(tickers.best_bid_ask("CRO_USDT"), None);
+    assert_eq!(tickers.best_bid_ask("ETH_USDT"), None);
+
+    Ok(())
+}
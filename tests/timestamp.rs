@@ -0,0 +1,67 @@
+use crypto_com_api::types::{deserialize_time, EpochMs, Time};
+
+#[derive(serde::Deserialize)]
+struct Sent {
+    #[serde(deserialize_with = "deserialize_time")]
+    t: Time,
+}
+
+#[test]
+fn reads_milliseconds() -> anyhow::Result<()> {
+    let sent: Sent = serde_json::from_str(r#"{"t": 1672531200123}"#)?;
+
+    assert_eq!(sent.t.epoch_ms(), 1_672_531_200_123);
+    assert_eq!(sent.t, Time::from_epoch_ms(1_672_531_200_123));
+    assert!(serde_json::from_str::<Sent>(r#"{"t": "soon"}"#).is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "datetime")]
+#[test]
+fn datetime_serde_round_trip() -> anyhow::Result<()> {
+    let time = Time::from_epoch_ms(1_672_531_200_123);
+
+    let json = serde_json::to_string(&time)?;
+    assert_eq!(json, r#""2023-01-01T00:00:00.123Z""#);
+
+    let sent: Sent = serde_json::from_str(&format!(r#"{{"t": {json}}}"#))?;
+    assert_eq!(sent.t, time);
+
+    Ok(())
+}
+
+#[cfg(feature = "datetime")]
+#[test]
+fn out_of_range_milliseconds_are_the_epoch() {
+    assert_eq!(Time::from_epoch_ms(u64::MAX).epoch_ms(), 0);
+}
+
+#[cfg(all(feature = "datetime", feature = "websocket"))]
+#[test]
+fn processed_structs_use_timestamps() -> anyhow::Result<()> {
+    use crypto_com_api::api_response::ApiResponse;
+    use crypto_com_api::websocket::{market_api::decode_subscription, WebsocketData};
+
+    let msg: ApiResponse<serde_json::Value> = serde_json::from_str(
+        r#"{
+            "id": -1, "method": "subscribe", "code": 0,
+            "result": {
+                "instrument_name": "BTCUSD-PERP", "subscription": "trade.BTCUSD-PERP",
+                "channel": "trade",
+                "data": [{"s": "BUY", "p": "1", "q": "1", "t": 1672531200123, "d": "1", "i": "BTCUSD-PERP"}]
+            }
+        }"#,
+    )?;
+
+    let Some(WebsocketData::Trade(trades)) = decode_subscription(&msg, 0)? else {
+        anyhow::bail!("expected trades");
+    };
+
+    assert_eq!(
+        serde_json::to_string(&trades.data[0].t)?,
+        r#""2023-01-01T00:00:00.123Z""#
+    );
+
+    Ok(())
+}
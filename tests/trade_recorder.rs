@@ -0,0 +1,95 @@
+#![cfg(feature = "websocket")]
+
+use std::fs;
+use std::path::PathBuf;
+
+use crypto_com_api::api_response::ApiResponse;
+use crypto_com_api::recorder::{RecordFormat, TradeRecorder};
+use crypto_com_api::websocket::{market_api::decode_subscription, WebsocketData};
+
+const TRADES: &str = r#"{
+    "id": -1, "method": "subscribe", "code": 0,
+    "result": {
+        "instrument_name": "BTCUSD-PERP", "subscription": "trade.BTCUSD-PERP", "channel": "trade",
+        "data": [
+            {"s": "BUY", "p": "100.5", "q": "0.1", "t": 1, "d": "11", "i": "BTCUSD-PERP"},
+            {"s": "SELL", "p": "100.0", "q": "0.2", "t": 2, "d": "12", "i": "BTCUSD-PERP"}
+        ]
+    }
+}"#;
+
+fn trades() -> anyhow::Result<WebsocketData> {
+    let msg: ApiResponse<serde_json::Value> = serde_json::from_str(TRADES)?;
+
+    decode_subscription(&msg, 0)?.ok_or_else(|| anyhow::anyhow!("no data"))
+}
+
+fn empty_dir(name: &str) -> anyhow::Result<PathBuf> {
+    let dir = std::env::temp_dir().join(format!("crypto-com-api-{name}-{}", std::process::id()));
+
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+
+    Ok(dir)
+}
+
+fn files(dir: &PathBuf) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    files.sort();
+
+    Ok(files)
+}
+
+#[test]
+fn csv_rows_are_written() -> anyhow::Result<()> {
+    let dir = empty_dir("csv")?;
+    let mut recorder = TradeRecorder::new(&dir, "btc", RecordFormat::Csv);
+
+    assert_eq!(recorder.process(&trades()?)?, 2);
+    recorder.flush()?;
+
+    let path = recorder.current_path().map(PathBuf::from);
+    assert!(path
+        .as_ref()
+        .is_some_and(|path| path.extension().is_some_and(|ext| ext == "csv")));
+
+    let contents = fs::read_to_string(files(&dir)?.remove(0))?;
+    assert_eq!(
+        contents,
+        "source,instrument_name,trade_id,side,price,quantity,t,fee,fee_currency,order_id\n\
+         market,BTCUSD-PERP,11,BUY,100.5,0.1,1,,,\n\
+         market,BTCUSD-PERP,12,SELL,100,0.2,2,,,\n"
+    );
+
+    fs::remove_dir_all(&dir)?;
+
+    Ok(())
+}
+
+#[test]
+fn files_rotate_by_size() -> anyhow::Result<()> {
+    let dir = empty_dir("rotate")?;
+    let mut recorder = TradeRecorder::new(&dir, "btc", RecordFormat::JsonLines).with_max_bytes(1);
+
+    recorder.process(&trades()?)?;
+    recorder.process(&trades()?)?;
+    recorder.rotate()?;
+
+    let files = files(&dir)?;
+    assert_eq!(files.len(), 4);
+
+    for file in files {
+        let contents = fs::read_to_string(file)?;
+        let record: serde_json::Value = serde_json::from_str(contents.trim_end())?;
+
+        assert_eq!(record["source"], "market");
+        assert!(record.get("fee").is_none());
+    }
+
+    fs::remove_dir_all(&dir)?;
+
+    Ok(())
+}
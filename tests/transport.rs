@@ -0,0 +1,184 @@
+#![cfg(feature = "websocket")]
+
+use std::sync::{Arc, Mutex, PoisonError};
+
+use anyhow::Result;
+use futures_channel::mpsc::{UnboundedReceiver, UnboundedSender};
+use futures_util::future::BoxFuture;
+use futures_util::StreamExt;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::Message;
+
+use crypto_com_api::error::ApiError;
+use crypto_com_api::utils::config::Config;
+use crypto_com_api::utils::in_flight::InFlight;
+use crypto_com_api::utils::request_log::{Direction, LoggedFrame, RequestLog, Verbosity};
+use crypto_com_api::websocket::connection_state::{ConnectionState, DisconnectKind};
+use crypto_com_api::websocket::market_api::spawn_market_stream;
+use crypto_com_api::websocket::transport::WsTransport;
+use crypto_com_api::websocket::WebsocketData;
+
+/// Recieves what the test sends to `incoming`, sends to `outgoing` for the test to read.
+struct MockTransport {
+    incoming: UnboundedReceiver<Message>,
+    outgoing: UnboundedSender<Message>,
+}
+
+impl WsTransport for MockTransport {
+    fn connect(_config: &Config, _url: &url::Url) -> BoxFuture<'static, Result<Self>> {
+        Box::pin(async { anyhow::bail!("mock transports are made by the test") })
+    }
+
+    fn send(&mut self, message: Message) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move { Ok(self.outgoing.unbounded_send(message)?) })
+    }
+
+    fn receive(&mut self) -> BoxFuture<'_, Option<Result<Message>>> {
+        Box::pin(async move { self.incoming.next().await.map(Ok) })
+    }
+
+    fn close(&mut self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+#[tokio::test]
+async fn market_stream_runs_on_a_mock_transport() -> Result<()> {
+    let (server_tx, incoming) = futures_channel::mpsc::unbounded();
+    let (outgoing, mut server_rx) = futures_channel::mpsc::unbounded();
+    let (data_tx, mut data_rx) = tokio::sync::broadcast::channel(16);
+
+    let (join_handle, _market_tx) = spawn_market_stream(
+        MockTransport { incoming, outgoing },
+        &Config::default(),
+        data_tx,
+        Arc::new(InFlight::default()),
+        None,
+    );
+
+    let data = data_rx.recv().await?;
+    assert!(matches!(data.result, Some(WebsocketData::MarketHandshake)));
+
+    server_tx.unbounded_send(Message::Text(
+        r#"{"id": 7, "method": "public/heartbeat", "code": 0}"#.to_owned(),
+    ))?;
+
+    let Some(Message::Text(reply)) = server_rx.next().await else {
+        anyhow::bail!("expected a heartbeat reply");
+    };
+    let reply: serde_json::Value = serde_json::from_str(&reply)?;
+    assert_eq!(reply["id"], 7);
+    assert_eq!(reply["method"], "public/respond-heartbeat");
+
+    drop(server_tx);
+    join_handle.await??;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn server_close_ends_the_stream_with_its_code() -> Result<()> {
+    let (server_tx, incoming) = futures_channel::mpsc::unbounded();
+    let (outgoing, _server_rx) = futures_channel::mpsc::unbounded();
+    let (data_tx, mut data_rx) = tokio::sync::broadcast::channel(16);
+
+    let (join_handle, _market_tx) = spawn_market_stream(
+        MockTransport { incoming, outgoing },
+        &Config::default(),
+        data_tx,
+        Arc::new(InFlight::default()),
+        None,
+    );
+
+    server_tx.unbounded_send(Message::Close(Some(CloseFrame {
+        code: CloseCode::Away,
+        reason: "maintenance".into(),
+    })))?;
+    drop(server_tx);
+
+    let err = join_handle
+        .await?
+        .expect_err("a close from the server fails the stream");
+    assert!(matches!(
+        err.downcast_ref::<ApiError>(),
+        Some(&ApiError::ConnectionClosed {
+            code: Some(1001),
+            ..
+        })
+    ));
+
+    let kind = loop {
+        let data = data_rx.recv().await?;
+
+        if let Some(WebsocketData::ConnectionState(ConnectionState::Disconnected {
+            ref kind,
+            ..
+        })) = data.result
+        {
+            break kind.clone();
+        }
+    };
+
+    assert_eq!(
+        kind,
+        Some(DisconnectKind::Closed {
+            code: Some(1001),
+            reason: "maintenance".to_owned(),
+        })
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn frames_are_logged_both_ways() -> Result<()> {
+    let (server_tx, incoming) = futures_channel::mpsc::unbounded();
+    let (outgoing, mut server_rx) = futures_channel::mpsc::unbounded();
+    let (data_tx, _data_rx) = tokio::sync::broadcast::channel(16);
+    let logged: Arc<Mutex<Vec<LoggedFrame>>> = Arc::default();
+
+    let log = {
+        let logged = Arc::clone(&logged);
+
+        RequestLog::new(Verbosity::Requests).with_sink(move |frame| {
+            logged
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(frame.clone());
+        })
+    };
+
+    let (join_handle, _market_tx) = spawn_market_stream(
+        MockTransport { incoming, outgoing },
+        &Config::default().with_request_log(log),
+        data_tx,
+        Arc::new(InFlight::default()),
+        None,
+    );
+
+    server_tx.unbounded_send(Message::Text(
+        r#"{"id": 7, "method": "public/heartbeat", "code": 0}"#.to_owned(),
+    ))?;
+    server_rx.next().await;
+
+    drop(server_tx);
+    join_handle.await??;
+
+    let logged = logged.lock().unwrap_or_else(PoisonError::into_inner);
+    let [ref heartbeat, ref reply] = logged[..] else {
+        panic!("expected a heartbeat and its reply, got {logged:?}");
+    };
+
+    assert_eq!(heartbeat.direction, Direction::Response);
+    assert_eq!(heartbeat.method.as_deref(), Some("public/heartbeat"));
+    assert_eq!(heartbeat.payload, None);
+    assert_eq!(reply.direction, Direction::Request);
+    assert_eq!(reply.id, Some(7));
+    assert!(reply
+        .payload
+        .as_deref()
+        .is_some_and(|payload| payload.contains("public/respond-heartbeat")));
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+#![cfg(feature = "websocket")]
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+use tokio_tungstenite::tungstenite::Message;
+
+use crypto_com_api::utils::in_flight::InFlight;
+use crypto_com_api::websocket::data::EventKind;
+use crypto_com_api::websocket::handler::HandlerRegistry;
+use crypto_com_api::websocket::{user_api::process_user, WebsocketData};
+
+fn user_order(id: i64) -> String {
+    format!(
+        r#"{{
+            "id": {id}, "method": "subscribe", "code": 0,
+            "result": {{
+                "instrument_name": "ETH_CRO", "subscription": "user.order.ETH_CRO",
+                "channel": "user.order",
+                "data": [{{
+                    "status": "ACTIVE", "side": "BUY", "price": 1.0, "quantity": 1.0,
+                    "order_id": "1", "client_oid": "a", "create_time": 1, "update_time": 1,
+                    "type": "LIMIT", "instrument_name": "ETH_CRO", "cumulative_quantity": 0,
+                    "cumulative_value": 0, "avg_price": 0, "fee_currency": "CRO",
+                    "time_in_force": "GOOD_TILL_CANCEL"
+                }}]
+            }}
+        }}"#
+    )
+}
+
+fn user_trade(id: i64) -> String {
+    format!(
+        r#"{{
+            "id": {id}, "method": "subscribe", "code": 0,
+            "result": {{
+                "instrument_name": "ETH_CRO", "subscription": "user.trade.ETH_CRO",
+                "channel": "user.trade",
+                "data": [{{
+                    "side": "BUY", "fee": 0.1, "trade_id": "2", "create_time": 1,
+                    "traded_price": 1.0, "traded_quantity": 1.0, "fee_currency": "CRO",
+                    "order_id": "1"
+                }}]
+            }}
+        }}"#
+    )
+}
+
+async fn process(frame: String) -> anyhow::Result<WebsocketData> {
+    let (user_tx, _user_rx) = futures_channel::mpsc::unbounded();
+    let (data_tx, mut data_rx) = tokio::sync::broadcast::channel(4);
+
+    process_user(
+        Message::Text(frame),
+        Arc::new(Mutex::new(user_tx)),
+        &data_tx,
+        &InFlight::default(),
+        None,
+        &HandlerRegistry::new(),
+    )
+    .await?;
+
+    loop {
+        let data = data_rx.recv().await?;
+        let data = Arc::try_unwrap(data).map_err(|_| anyhow::anyhow!("data is shared"))?;
+
+        match data.result {
+            Some(WebsocketData::Subscribed { .. }) => continue,
+            result => return result.ok_or_else(|| anyhow::anyhow!("no data")),
+        }
+    }
+}
+
+#[tokio::test]
+async fn subscribe_reply_is_a_snapshot() -> anyhow::Result<()> {
+    let WebsocketData::UserOrder(orders) = process(user_order(7)).await? else {
+        anyhow::bail!("expected user orders");
+    };
+    assert!(orders.is_snapshot());
+
+    let WebsocketData::UserTrade(trades) = process(user_trade(7)).await? else {
+        anyhow::bail!("expected user trades");
+    };
+    assert_eq!(trades.kind, EventKind::Snapshot);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn pushes_are_updates() -> anyhow::Result<()> {
+    let WebsocketData::UserOrder(orders) = process(user_order(-1)).await? else {
+        anyhow::bail!("expected user orders");
+    };
+    assert_eq!(orders.kind, EventKind::Update);
+
+    let WebsocketData::UserTrade(trades) = process(user_trade(-1)).await? else {
+        anyhow::bail!("expected user trades");
+    };
+    assert!(!trades.is_snapshot());
+
+    Ok(())
+}
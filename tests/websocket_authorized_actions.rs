@@ -1,6 +1,7 @@
 #![cfg(all(feature = "websocket", feature = "test_authorized"))]
 
 use anyhow::Result as AnyhowResult;
+use crypto_com_api::types::PageQuery;
 use crypto_com_api::websocket::actions::spot_trading_api::{
     GetAccountSummary, GetOpenOrders, GetOrderHistory, GetTrades, Paginated,
 };
@@ -52,10 +53,10 @@ async fn auth() -> AnyhowResult<()> {
     });
 
     controller
-        .push_user_action(Box::new(Auth {
+        .push_user_action(Auth {
             api_key: std::env::var("API_KEY")?,
             secret_key: std::env::var("SECRET_KEY")?,
-        }))
+        })
         .await?;
 
     join_handle.await?
@@ -87,16 +88,16 @@ async fn get_account_summary() -> AnyhowResult<()> {
     });
 
     controller
-        .push_user_action(Box::new(Auth {
+        .push_user_action(Auth {
             api_key: std::env::var("API_KEY")?,
             secret_key: std::env::var("SECRET_KEY")?,
-        }))
+        })
         .await?;
 
     controller
-        .push_user_action(Box::new(GetAccountSummary {
+        .push_user_action(GetAccountSummary {
             currency: Some("CRO".to_string()),
-        }))
+        })
         .await?;
 
     join_handle.await?
@@ -128,20 +129,14 @@ async fn get_order_history() -> AnyhowResult<()> {
     });
 
     controller
-        .push_user_action(Box::new(Auth {
+        .push_user_action(Auth {
             api_key: std::env::var("API_KEY")?,
             secret_key: std::env::var("SECRET_KEY")?,
-        }))
+        })
         .await?;
 
     controller
-        .push_user_action(Box::new(GetOrderHistory(Paginated {
-            instrument_name: None,
-            start_ts: None,
-            end_ts: None,
-            page_size: None,
-            page: None,
-        })))
+        .push_user_action(GetOrderHistory(Paginated::default()))
         .await?;
 
     join_handle.await?
@@ -173,18 +168,14 @@ async fn get_open_orders() -> AnyhowResult<()> {
     });
 
     controller
-        .push_user_action(Box::new(Auth {
+        .push_user_action(Auth {
             api_key: std::env::var("API_KEY")?,
             secret_key: std::env::var("SECRET_KEY")?,
-        }))
+        })
         .await?;
 
     controller
-        .push_user_action(Box::new(GetOpenOrders {
-            instrument_name: None,
-            page_size: None,
-            page: None,
-        }))
+        .push_user_action(GetOpenOrders::default())
         .await?;
 
     join_handle.await?
@@ -216,20 +207,14 @@ async fn get_trades() -> AnyhowResult<()> {
     });
 
     controller
-        .push_user_action(Box::new(Auth {
+        .push_user_action(Auth {
             api_key: std::env::var("API_KEY")?,
             secret_key: std::env::var("SECRET_KEY")?,
-        }))
+        })
         .await?;
 
     controller
-        .push_user_action(Box::new(GetTrades(Paginated {
-            instrument_name: None,
-            start_ts: None,
-            end_ts: None,
-            page_size: None,
-            page: None,
-        })))
+        .push_user_action(GetTrades(Paginated::default()))
         .await?;
 
     join_handle.await?
@@ -261,16 +246,16 @@ async fn set_cancel_on_disconnect() -> AnyhowResult<()> {
     });
 
     controller
-        .push_user_action(Box::new(Auth {
+        .push_user_action(Auth {
             api_key: std::env::var("API_KEY")?,
             secret_key: std::env::var("SECRET_KEY")?,
-        }))
+        })
         .await?;
 
     controller
-        .push_user_action(Box::new(SetCancelOnDisconnect {
+        .push_user_action(SetCancelOnDisconnect {
             scope: "CONNECTION".to_string(),
-        }))
+        })
         .await?;
 
     join_handle.await?
@@ -302,22 +287,52 @@ async fn get_cancel_on_disconnect() -> AnyhowResult<()> {
     });
 
     controller
-        .push_user_action(Box::new(Auth {
+        .push_user_action(Auth {
             api_key: std::env::var("API_KEY")?,
             secret_key: std::env::var("SECRET_KEY")?,
-        }))
+        })
         .await?;
 
     // Set the scope so we can later get it.
     controller
-        .push_user_action(Box::new(SetCancelOnDisconnect {
+        .push_user_action(SetCancelOnDisconnect {
             scope: "CONNECTION".to_string(),
-        }))
+        })
         .await?;
 
+    controller.push_user_action(GetCancelOnDisconnect).await?;
+
+    join_handle.await?
+}
+
+#[tokio::test]
+async fn typed_user_requests() -> AnyhowResult<()> {
+    dotenv::dotenv()?;
+
+    let mut controller = get_controller().await?;
+
     controller
-        .push_user_action(Box::new(GetCancelOnDisconnect))
+        .push_user_action(Auth {
+            api_key: std::env::var("API_KEY")?,
+            secret_key: std::env::var("SECRET_KEY")?,
+        })
         .await?;
 
-    join_handle.await?
+    let summary = controller
+        .get_account_summary(Some("CRO".to_owned()))
+        .await?;
+    assert!(summary
+        .accounts
+        .iter()
+        .all(|account| account.currency == "CRO"));
+
+    controller.get_open_orders(None).await?;
+    controller
+        .get_trades(Paginated {
+            page: PageQuery::new(0, 1)?,
+            ..Paginated::default()
+        })
+        .await?;
+
+    Ok(())
 }
@@ -3,28 +3,27 @@
 use anyhow::Result;
 use crypto_com_api::{
     controller::{Controller, ControllerBuilder, MarketWs, NoMarketWs, NoUserWs, UserWs},
+    types::Interval,
+    utils::environment::Environment,
     websocket::{
         actions::{GetInstruments, Subscribe},
+        channel::Channel,
         WebsocketData,
     },
 };
 
 async fn get_controller_sandbox() -> Result<Controller<NoUserWs, MarketWs>> {
-    let websocket_user_url = url::Url::parse("wss://uat-stream.3ona.co/v2/market")
-        .expect("UAT Sandbox Market API Invalid URL");
-
     Ok(ControllerBuilder::new()
-        .with_market_websocket(websocket_user_url)
+        .with_environment(Environment::UatSandbox)
+        .with_market_websocket(Environment::UatSandbox.websocket_market_url())
         .await?
         .build())
 }
 
 async fn get_controller_sandbox_user() -> Result<Controller<UserWs, NoMarketWs>> {
-    let websocket_user_url = url::Url::parse("wss://uat-stream.3ona.co/v2/user")
-        .expect("UAT Sandbox User API Invalid URL");
-
     Ok(ControllerBuilder::new()
-        .with_user_websocket(websocket_user_url)
+        .with_environment(Environment::UatSandbox)
+        .with_user_websocket(Environment::UatSandbox.websocket_user_url())
         .await?
         .build())
 }
@@ -63,9 +62,10 @@ async fn book_subscription() -> Result<()> {
     });
 
     controller
-        .push_market_action(Box::new(Subscribe {
+        .push_market_action(Subscribe {
             channels: vec!["book.BTCUSD-PERP".to_string()],
-        }))
+            ..Subscribe::default()
+        })
         .await?;
 
     join_handle.await?
@@ -87,9 +87,10 @@ async fn ticker_subscription() -> Result<()> {
     });
 
     controller
-        .push_market_action(Box::new(Subscribe {
+        .push_market_action(Subscribe {
             channels: vec!["ticker.BTCUSD-PERP".to_string()],
-        }))
+            ..Subscribe::default()
+        })
         .await?;
 
     join_handle.await?
@@ -111,9 +112,10 @@ async fn trade_subscription() -> Result<()> {
     });
 
     controller
-        .push_market_action(Box::new(Subscribe {
+        .push_market_action(Subscribe {
             channels: vec!["trade.BTC_USDT".to_string()],
-        }))
+            ..Subscribe::default()
+        })
         .await?;
 
     join_handle.await?
@@ -135,9 +137,10 @@ async fn candlestick_subscription() -> Result<()> {
     });
 
     controller
-        .push_market_action(Box::new(Subscribe {
-            channels: vec!["candlestick.M1.BTCUSD-PERP".to_string()],
-        }))
+        .push_market_action(Subscribe::from_iter([Channel::Candlestick {
+            interval: Interval::OneMinute,
+            instrument_name: "BTCUSD-PERP".to_owned(),
+        }]))
         .await?;
 
     join_handle.await?
@@ -160,9 +163,10 @@ async fn otc_book_subscription() -> Result<()> {
     });
 
     controller
-        .push_market_action(Box::new(Subscribe {
+        .push_market_action(Subscribe {
             channels: vec!["otc_book.BTC_USDT".to_string()],
-        }))
+            ..Subscribe::default()
+        })
         .await?;
 
     join_handle.await?
@@ -184,9 +188,7 @@ async fn get_instruments() -> Result<()> {
         }
     });
 
-    controller
-        .push_user_action(Box::new(GetInstruments))
-        .await?;
+    controller.push_user_action(GetInstruments).await?;
 
     join_handle.await?
 }
@@ -0,0 +1,115 @@
+#![cfg(feature = "rest")]
+
+use crypto_com_api::error::WithdrawalError;
+use crypto_com_api::prelude::ApiError;
+use crypto_com_api::rest::data::{CreateWithdrawalRes, CurrencyNetworks};
+use crypto_com_api::rest::private::{estimate_withdrawal_fee, CreateWithdrawal};
+use crypto_com_api::utils::config::Config;
+
+fn networks() -> CurrencyNetworks {
+    serde_json::from_str(
+        r#"{
+            "update_time": 1641151604000,
+            "currency_map": {
+                "CRO": {
+                    "full_name": "Cronos",
+                    "default_network": "CRONOS",
+                    "network_list": [
+                        {
+                            "network_id": "CRONOS",
+                            "withdrawal_enabled": true,
+                            "deposit_enabled": true,
+                            "withdrawal_fee": 1.0,
+                            "min_withdrawal_amount": 10.0,
+                            "confirmation_required": 0
+                        },
+                        {
+                            "network_id": "ETH",
+                            "withdrawal_enabled": false,
+                            "deposit_enabled": true,
+                            "withdrawal_fee": 50.0,
+                            "min_withdrawal_amount": 100.0,
+                            "confirmation_required": 12
+                        }
+                    ]
+                }
+            }
+        }"#,
+    )
+    .expect("currency networks fixture")
+}
+
+#[test]
+fn default_network_quote() {
+    let quote = networks()
+        .validate_withdrawal("CRO", 25.0, None)
+        .expect("valid withdrawal");
+
+    assert_eq!(quote.network_id, "CRONOS");
+    assert_eq!(quote.fee, 1.0);
+    assert_eq!(quote.amount_received, 24.0);
+}
+
+#[test]
+fn rejected_withdrawals() {
+    let networks = networks();
+
+    assert_eq!(
+        networks.validate_withdrawal("BTC", 1.0, None),
+        Err(WithdrawalError::UnknownCurrency("BTC".to_owned()))
+    );
+    assert!(matches!(
+        networks.validate_withdrawal("CRO", 25.0, Some("SOL")),
+        Err(WithdrawalError::UnknownNetwork { .. })
+    ));
+    assert!(matches!(
+        networks.validate_withdrawal("CRO", 500.0, Some("ETH")),
+        Err(WithdrawalError::WithdrawalDisabled { .. })
+    ));
+    assert!(matches!(
+        networks.validate_withdrawal("CRO", 5.0, Some("CRONOS")),
+        Err(WithdrawalError::BelowMinimum { .. })
+    ));
+}
+
+#[test]
+fn quoted_fee_is_reconciled() -> anyhow::Result<()> {
+    let mut withdrawal: CreateWithdrawalRes = serde_json::from_str(
+        r#"{
+            "id": 2220, "client_wid": "my_withdrawal_002", "currency": "CRO",
+            "amount": 10.0, "fee": 1.5, "address": "2NBqqD5GRJ8wHy1PYyCXTe9ke5226FhavBf",
+            "create_time": 1607063412000
+        }"#,
+    )?;
+
+    assert_eq!(withdrawal.expected_fee, None);
+    assert_eq!(withdrawal.fee_difference(), None);
+
+    let quote = networks().validate_withdrawal("CRO", 10.0, None)?;
+    withdrawal.expected_fee = Some(quote.fee);
+
+    assert_eq!(withdrawal.fee_difference(), Some(0.5));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn estimate_needs_rest_url() {
+    let params = CreateWithdrawal {
+        client_wid: None,
+        currency: "CRO".to_owned(),
+        amount: 10.0,
+        address: "2NBqqD5GRJ8wHy1PYyCXTe9ke5226FhavBf".to_owned(),
+        address_tag: None,
+        network_id: None,
+    };
+
+    let err = estimate_withdrawal_fee(&Config::default(), &params)
+        .await
+        .expect_err("no rest_url is set");
+
+    assert!(matches!(
+        &err,
+        ApiError::ConfigMissing(field) if field == "rest_url"
+    ));
+}